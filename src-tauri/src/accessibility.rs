@@ -0,0 +1,312 @@
+// src-tauri/src/accessibility.rs
+// Screen-reader friendly announcements. Token streams and live captions change many times a second,
+// which a screen reader would either read in fragments or interrupt constantly. When enabled, this
+// module watches the stream and caption events as they are emitted and turns them into whole-sentence
+// announcements on the `accessibility-announcement` event, with the politeness an aria-live region
+// needs. Verbosity decides how much is said:
+//   brief    - a response is announced once, when it's complete; captions without a speaker label
+//   standard - responses in batches of complete sentences; captions with their source
+//   verbose  - every sentence as it completes, plus tool use and response start/end
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const ANNOUNCEMENT_EVENT: &str = "accessibility-announcement";
+const STREAM_PREFIX: &str = "ollama-stream-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Brief,
+    Standard,
+    Verbose,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub enabled: bool,
+    pub verbosity: Verbosity,
+    pub min_batch_chars: usize, // Standard verbosity holds sentences back until this much is ready
+    pub max_batch_wait_ms: u64, // ...or until the oldest held text has waited this long
+    pub announce_captions: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            verbosity: Verbosity::Standard,
+            min_batch_chars: 120,
+            max_batch_wait_ms: 4000,
+            announce_captions: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+    pub source: String,     // Stream session id or caption source
+    pub kind: String,       // "response", "caption" or "status"
+    pub text: String,
+    pub politeness: String, // aria-live: "polite" or "assertive"
+}
+
+/// Text of one response stream waiting to be announced.
+struct StreamBuffer {
+    text: String,
+    held_since: Option<Instant>,
+}
+
+impl StreamBuffer {
+    fn new() -> Self {
+        Self { text: String::new(), held_since: None }
+    }
+
+    fn push(&mut self, text: &str, now: Instant) {
+        if self.text.is_empty() && !text.trim().is_empty() {
+            self.held_since = Some(now);
+        }
+        self.text.push_str(text);
+    }
+
+    /// Complete sentences ready to be announced under `settings`, removed from the buffer.
+    fn take_ready(&mut self, settings: &AccessibilitySettings, now: Instant) -> Option<String> {
+        let end = match settings.verbosity {
+            Verbosity::Brief => return None,
+            Verbosity::Verbose => sentence_end(&self.text)?,
+            Verbosity::Standard => {
+                let end = sentence_end(&self.text)?;
+                let waited = self.held_since.is_some_and(|since| now.duration_since(since) >= Duration::from_millis(settings.max_batch_wait_ms));
+                if end < settings.min_batch_chars && !waited {
+                    return None;
+                }
+                end
+            }
+        };
+        let ready: String = self.text.drain(..end).collect();
+        self.held_since = (!self.text.trim().is_empty()).then_some(now);
+        Some(ready)
+    }
+
+    fn take_all(&mut self) -> String {
+        self.held_since = None;
+        std::mem::take(&mut self.text)
+    }
+}
+
+/// Byte index just past the last complete sentence, if there is one.
+fn sentence_end(text: &str) -> Option<usize> {
+    let mut end = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let boundary = match c {
+            '\n' => true,
+            '.' | '!' | '?' | ':' => chars.peek().is_some_and(|&(_, next)| next.is_whitespace()),
+            _ => false,
+        };
+        if boundary {
+            end = Some(i + c.len_utf8());
+        }
+    }
+    end.filter(|&end| !text[..end].trim().is_empty())
+}
+
+/// Drop the markdown a screen reader would read out literally.
+fn speakable(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.trim_start_matches('#').trim_start();
+            let line = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")).unwrap_or(line);
+            line.replace("**", "").replace("__", "").replace('`', "")
+        })
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+lazy_static::lazy_static! {
+    static ref SETTINGS: Mutex<Option<AccessibilitySettings>> = Mutex::new(None);
+    static ref STREAMS: Mutex<HashMap<String, StreamBuffer>> = Mutex::new(HashMap::new());
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("accessibility.json")
+}
+
+// Read once and kept: every stream chunk asks
+fn load_settings() -> AccessibilitySettings {
+    let Ok(mut cached) = SETTINGS.lock() else {
+        return AccessibilitySettings::default();
+    };
+    cached
+        .get_or_insert_with(|| {
+            get_settings_path()
+                .ok()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+fn announce(app_handle: &AppHandle, source: &str, kind: &str, text: &str, assertive: bool) {
+    let text = speakable(text);
+    if text.is_empty() {
+        return;
+    }
+    let announcement = Announcement {
+        source: source.to_string(),
+        kind: kind.to_string(),
+        text,
+        politeness: if assertive { "assertive" } else { "polite" }.to_string(),
+    };
+    if let Err(e) = app_handle.emit(ANNOUNCEMENT_EVENT, announcement) {
+        eprintln!("Failed to emit accessibility announcement: {}", e);
+    }
+}
+
+/// Whether an event about to be emitted should be shown to `observe_stream_event`.
+pub fn wants(event: &str) -> bool {
+    event.starts_with(STREAM_PREFIX) && load_settings().enabled
+}
+
+/// Follow a response stream event (before any throttling) and announce what's ready.
+pub fn observe_stream_event(app_handle: &AppHandle, event: &str, payload: &Value) {
+    let settings = load_settings();
+    if !settings.enabled {
+        return;
+    }
+    let Some(session_id) = event.strip_prefix(STREAM_PREFIX) else {
+        return;
+    };
+    let verbose = settings.verbosity == Verbosity::Verbose;
+    let Ok(mut streams) = STREAMS.lock() else {
+        return;
+    };
+    let now = Instant::now();
+
+    match payload.get("type").and_then(Value::as_str).unwrap_or_default() {
+        "start" => {
+            streams.insert(session_id.to_string(), StreamBuffer::new());
+            if verbose {
+                announce(app_handle, session_id, "status", "Generating a response.", false);
+            }
+        }
+        "chunk" => {
+            let buffer = streams.entry(session_id.to_string()).or_insert_with(StreamBuffer::new);
+            buffer.push(payload.get("text").and_then(Value::as_str).unwrap_or_default(), now);
+            if let Some(ready) = buffer.take_ready(&settings, now) {
+                announce(app_handle, session_id, "response", &ready, false);
+            }
+        }
+        "tool_call" if verbose => {
+            let tool = payload.get("tool_name").and_then(Value::as_str).unwrap_or("a tool");
+            announce(app_handle, session_id, "status", &format!("Using {}.", tool), false);
+        }
+        "complete" => {
+            if let Some(mut buffer) = streams.remove(session_id) {
+                announce(app_handle, session_id, "response", &buffer.take_all(), false);
+            }
+            if verbose {
+                announce(app_handle, session_id, "status", "Response complete.", false);
+            }
+        }
+        kind @ ("error" | "cancelled" | "timeout" | "terminated") => {
+            // What was said so far still gets read, then why it stopped
+            if let Some(mut buffer) = streams.remove(session_id) {
+                announce(app_handle, session_id, "response", &buffer.take_all(), false);
+            }
+            let message = match kind {
+                "error" => format!("Response failed: {}", payload.get("error").and_then(Value::as_str).unwrap_or("unknown error")),
+                "cancelled" => "Response cancelled.".to_string(),
+                _ => "Response stopped early.".to_string(),
+            };
+            announce(app_handle, session_id, "status", &message, kind == "error");
+        }
+        _ => {}
+    }
+}
+
+fn source_label(source: &str) -> &str {
+    match source {
+        "loopback" => "System audio",
+        "microphone" | "mic" => "You",
+        other => other,
+    }
+}
+
+/// Announce a finished caption segment. Interim captions are never announced.
+pub fn observe_caption(app_handle: &AppHandle, source: &str, text: &str) {
+    let settings = load_settings();
+    if !settings.enabled || !settings.announce_captions {
+        return;
+    }
+    let text = match settings.verbosity {
+        Verbosity::Brief => text.to_string(),
+        Verbosity::Standard | Verbosity::Verbose => format!("{}: {}", source_label(source), text),
+    };
+    announce(app_handle, source, "caption", &text, false);
+}
+
+#[tauri::command]
+pub fn get_accessibility_settings() -> Result<AccessibilitySettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_accessibility_settings(settings: AccessibilitySettings) -> Result<(), String> {
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    if !settings.enabled {
+        if let Ok(mut streams) = STREAMS.lock() {
+            streams.clear();
+        }
+    }
+    if let Ok(mut cached) = SETTINGS.lock() {
+        *cached = Some(settings.clone());
+    }
+    println!("💾 Saved accessibility settings (enabled: {}, verbosity: {:?})", settings.enabled, settings.verbosity);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_text_is_announced_in_complete_sentences() {
+        let settings = AccessibilitySettings { min_batch_chars: 30, ..Default::default() };
+        let start = Instant::now();
+        let mut buffer = StreamBuffer::new();
+
+        buffer.push("The build ", start);
+        buffer.push("passed. Tests run in 3.5", start);
+        // One short sentence isn't enough for a standard batch yet
+        assert_eq!(buffer.take_ready(&settings, start), None);
+        buffer.push(" seconds. Next", start);
+        assert_eq!(buffer.take_ready(&settings, start).as_deref(), Some("The build passed. Tests run in 3.5 seconds."));
+        assert_eq!(buffer.text, " Next");
+
+        // Held text goes out after the wait even when the batch is short
+        buffer.push(" step done. And", start);
+        let later = start + Duration::from_millis(settings.max_batch_wait_ms);
+        assert_eq!(buffer.take_ready(&settings, later).as_deref(), Some(" Next step done."));
+
+        let brief = AccessibilitySettings { verbosity: Verbosity::Brief, ..settings };
+        assert_eq!(buffer.take_ready(&brief, later), None);
+        assert_eq!(speakable("## Summary\n- **Fast** `cargo` build"), "Summary Fast cargo build");
+    }
+}
@@ -0,0 +1,236 @@
+// src-tauri/src/agent_models.rs
+// Which Ollama model each built-in agent runs. The user's choices are kept in the database settings
+// table; agents without one use their default from ollama::agent_model_and_prompt. The model is
+// resolved when a request starts, and if it isn't installed an installed one is used instead so a
+// fresh setup still answers. Background helpers (query condensation, digests, tagging and the like)
+// have no user choice but fall back the same way through resolve_helper_model.
+use crate::data::settings::SettingsStorage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+const AGENT_MODELS_KEY: &str = "agent_models";
+const AGENT_USAGE_KEY: &str = "agent_usage";
+// Also the warm-up order for agents that have never been used
+const AGENT_TYPES: [&str; 5] = ["enteract", "conversational_ai", "coding", "research", "vision"];
+// A use from a week ago counts half as much as one today when predicting the next agents
+const USAGE_HALF_LIFE_DAYS: f64 = 7.0;
+const VISION_HINTS: [&str; 4] = ["vl", "llava", "vision", "moondream"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentModel {
+    pub agent_type: String,
+    pub model: String,
+    pub default_model: String,
+    pub custom: bool, // The user picked the model rather than the default
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AgentUsage {
+    count: u64,
+    last_used: i64, // Unix millis
+}
+
+// "conversational" is an alias the frontend still uses in places
+fn canonical(agent_type: &str) -> &str {
+    if agent_type == "conversational" { "conversational_ai" } else { agent_type }
+}
+
+fn default_model(agent_type: &str) -> Result<&'static str, String> {
+    crate::ollama::agent_model_and_prompt(agent_type)
+        .map(|(model, _)| model)
+        .ok_or_else(|| format!("Unknown agent type: {}", agent_type))
+}
+
+fn load_agent_models(app_handle: &AppHandle) -> HashMap<String, String> {
+    SettingsStorage::new(app_handle)
+        .and_then(|storage| storage.get(AGENT_MODELS_KEY))
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to load agent models, using defaults: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn is_installed(model: &str, installed: &[String]) -> bool {
+    installed.iter().any(|name| name == model || *name == format!("{}:latest", model))
+}
+
+/// The model to run: the preferred one if installed, else the agent's default, else the first
+/// installed model that suits the agent. None when nothing is installed.
+fn pick_installed(agent_type: &str, preferred: &str, default_model: &str, installed: &[String]) -> Option<String> {
+    if is_installed(preferred, installed) {
+        return Some(preferred.to_string());
+    }
+    if is_installed(default_model, installed) {
+        return Some(default_model.to_string());
+    }
+    let wants_vision = agent_type == "vision";
+    installed
+        .iter()
+        .filter(|name| !name.contains("embed"))
+        .find(|name| is_vision_model(name) == wants_vision)
+        .cloned()
+}
+
+/// Whether a model name looks like a vision (image input) model.
+pub(crate) fn is_vision_model(name: &str) -> bool {
+    VISION_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+fn record_agent_use(app_handle: &AppHandle, agent_type: &str) {
+    let result = SettingsStorage::new(app_handle).and_then(|mut storage| {
+        let mut usage: HashMap<String, AgentUsage> = storage.get(AGENT_USAGE_KEY)?.unwrap_or_default();
+        let entry = usage.entry(agent_type.to_string()).or_default();
+        entry.count += 1;
+        entry.last_used = chrono::Utc::now().timestamp_millis();
+        storage.set(AGENT_USAGE_KEY, &usage)
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to record {} agent use: {}", agent_type, e);
+    }
+}
+
+fn usage_score(usage: &AgentUsage, now: i64) -> f64 {
+    let age_days = (now - usage.last_used).max(0) as f64 / 86_400_000.0;
+    usage.count as f64 * 0.5f64.powf(age_days / USAGE_HALF_LIFE_DAYS)
+}
+
+/// Built-in agents ordered by how likely they are to be used next: frequent, recent use first,
+/// then agents that haven't been used in their default order.
+pub fn predicted_agent_order(app_handle: &AppHandle) -> Vec<&'static str> {
+    let usage: HashMap<String, AgentUsage> = SettingsStorage::new(app_handle)
+        .and_then(|storage| storage.get(AGENT_USAGE_KEY))
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let now = chrono::Utc::now().timestamp_millis();
+    let score = |agent_type: &str| usage.get(agent_type).map_or(0.0, |usage| usage_score(usage, now));
+
+    let mut agents = AGENT_TYPES.to_vec();
+    agents.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    agents
+}
+
+/// The model an agent should use for a request starting now.
+pub async fn resolve_agent_model(app_handle: &AppHandle, agent_type: &str) -> Result<String, String> {
+    let agent_type = canonical(agent_type);
+    default_model(agent_type)?;
+    record_agent_use(app_handle, agent_type);
+
+    // If Ollama can't be reached, keep the preferred model and let the request report the error
+    let installed: Option<Vec<String>> = crate::ollama::get_ollama_models()
+        .await
+        .ok()
+        .map(|models| models.into_iter().map(|model| model.name).collect());
+    planned_agent_model(app_handle, agent_type, installed.as_deref())
+}
+
+/// The model an agent would run given what's installed (unknown when None), without counting
+/// it as a use of the agent.
+pub fn planned_agent_model(app_handle: &AppHandle, agent_type: &str, installed: Option<&[String]>) -> Result<String, String> {
+    let agent_type = canonical(agent_type);
+    let default_model = default_model(agent_type)?;
+    let preferred = load_agent_models(app_handle)
+        .remove(agent_type)
+        .unwrap_or_else(|| default_model.to_string());
+
+    let Some(installed) = installed else {
+        return Ok(preferred);
+    };
+    match pick_installed(agent_type, &preferred, default_model, installed) {
+        Some(model) => {
+            if model != preferred {
+                println!("⚠️ {} is not installed; the {} agent will use {}", preferred, agent_type, model);
+            }
+            Ok(model)
+        }
+        None => Ok(preferred),
+    }
+}
+
+/// The model a background helper runs: `preferred` if installed, else an installed chat model.
+/// Keeps `preferred` when Ollama can't be reached or has nothing installed, so the request reports it.
+pub async fn resolve_helper_model(preferred: &str) -> String {
+    let installed: Vec<String> = match crate::ollama::get_ollama_models().await {
+        Ok(models) => models.into_iter().map(|model| model.name).collect(),
+        Err(_) => return preferred.to_string(),
+    };
+    match pick_installed("helper", preferred, preferred, &installed) {
+        Some(model) => {
+            if model != preferred {
+                println!("⚠️ {} is not installed; using {} instead", preferred, model);
+            }
+            model
+        }
+        None => preferred.to_string(),
+    }
+}
+
+fn agent_model(agent_type: &str, saved: &HashMap<String, String>) -> Result<AgentModel, String> {
+    let default_model = default_model(agent_type)?;
+    let custom = saved.get(agent_type);
+    Ok(AgentModel {
+        agent_type: agent_type.to_string(),
+        model: custom.cloned().unwrap_or_else(|| default_model.to_string()),
+        default_model: default_model.to_string(),
+        custom: custom.is_some(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_agent_models(app_handle: AppHandle) -> Result<Vec<AgentModel>, String> {
+    let saved = load_agent_models(&app_handle);
+    AGENT_TYPES.iter().map(|agent_type| agent_model(agent_type, &saved)).collect()
+}
+
+/// Pick the model for an agent; None (or an empty name) goes back to the default.
+#[tauri::command]
+pub async fn set_agent_model(app_handle: AppHandle, agent_type: String, model: Option<String>) -> Result<AgentModel, String> {
+    let agent_type = canonical(&agent_type);
+    default_model(agent_type)?;
+
+    let mut storage = SettingsStorage::new(&app_handle).map_err(|e| format!("Failed to open settings: {}", e))?;
+    let mut saved: HashMap<String, String> = storage
+        .get(AGENT_MODELS_KEY)
+        .map_err(|e| format!("Failed to load agent models: {}", e))?
+        .unwrap_or_default();
+    match model.map(|model| model.trim().to_string()).filter(|model| !model.is_empty()) {
+        Some(model) => {
+            println!("🤖 The {} agent will use {}", agent_type, model);
+            saved.insert(agent_type.to_string(), model);
+        }
+        None => {
+            saved.remove(agent_type);
+        }
+    }
+    storage
+        .set(AGENT_MODELS_KEY, &saved)
+        .map_err(|e| format!("Failed to save agent models: {}", e))?;
+
+    agent_model(agent_type, &saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_installed_falls_back_to_a_suitable_model() {
+        let installed = vec![
+            "nomic-embed-text:latest".to_string(),
+            "llama3.2:3b".to_string(),
+            "llava:7b".to_string(),
+            "gemma3:1b-it-qat".to_string(),
+        ];
+
+        assert_eq!(pick_installed("enteract", "llama3.2:3b", "gemma3:1b-it-qat", &installed).as_deref(), Some("llama3.2:3b"));
+        assert_eq!(pick_installed("enteract", "mistral", "gemma3:1b-it-qat", &installed).as_deref(), Some("gemma3:1b-it-qat"));
+        assert_eq!(pick_installed("coding", "qwen2.5-coder:1.5b", "qwen2.5-coder:1.5b", &installed).as_deref(), Some("llama3.2:3b"));
+        assert_eq!(pick_installed("vision", "qwen2.5vl:3b", "qwen2.5vl:3b", &installed).as_deref(), Some("llava:7b"));
+        // Helpers take any installed chat model, never a vision or embedding one
+        assert_eq!(pick_installed("helper", "gemma3:4b-it-qat", "gemma3:4b-it-qat", &installed).as_deref(), Some("llama3.2:3b"));
+        assert_eq!(pick_installed("enteract", "gemma3:1b-it-qat", "gemma3:1b-it-qat", &[]), None);
+    }
+}
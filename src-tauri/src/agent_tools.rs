@@ -0,0 +1,348 @@
+// src-tauri/src/agent_tools.rs
+// Tool calling for agents. The tools of an MCP session are offered to the model through the `tools`
+// field of Ollama's /api/chat. When a reply asks for tools, each call runs through
+// MCPSession::execute_tool (and so through the session's approval prompts), the results go back as
+// `tool` messages and the model carries on. Everything streams on the usual ollama-stream-{session}
+// events, with `tool_call`/`tool_result` events in between for the UI.
+use crate::generation_options::{build_ollama_options, estimate_tokens, GenerationOptions};
+use crate::mcp::server::MCPSession;
+use crate::mcp::types::{ToolExecutionResult, ToolInfo};
+use crate::ollama_http::{client, EndpointClass};
+use crate::ollama::{build_chat_messages, ChatContextMessage, NdjsonLines, TokenUsage};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// The last round is sent without tools so the model has to answer
+const MAX_TOOL_ROUNDS: usize = 8;
+const MAX_TOOL_RESULT_CHARS: usize = 4000;
+// Generous: after a tool result the model re-reads the whole conversation before the first token
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default, Deserialize)]
+struct ReplyMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    tool_calls: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatLine {
+    #[serde(default)]
+    message: Option<ReplyMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+struct Round {
+    text: String,
+    tool_calls: Vec<serde_json::Value>,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    cancelled: bool,
+}
+
+/// The session's tools in Ollama's function-calling format.
+pub fn tool_definitions(tools: &[ToolInfo]) -> Vec<serde_json::Value> {
+    tools
+        .iter()
+        .map(|tool| {
+            let approval = if tool.requires_approval { ", asks the user for approval" } else { "" };
+            serde_json::json!({
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": format!("{} (risk: {:?}{})", tool.description, tool.danger_level, approval),
+                    "parameters": tool.parameters_schema,
+                }
+            })
+        })
+        .collect()
+}
+
+// Name and arguments of a tool call; some models send the arguments as a JSON string
+fn parse_call(call: &serde_json::Value) -> Option<(String, serde_json::Value)> {
+    let function = call.get("function")?;
+    let name = function.get("name")?.as_str()?.to_string();
+    let arguments = match function.get("arguments") {
+        Some(serde_json::Value::String(text)) => serde_json::from_str(text).unwrap_or_else(|_| serde_json::json!({})),
+        Some(arguments) if arguments.is_object() => arguments.clone(),
+        _ => serde_json::json!({}),
+    };
+    Some((name, arguments))
+}
+
+// Screenshots are far too big to feed back as text
+fn strip_images(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if key == "image_base64" {
+                    *field = serde_json::Value::String("[image omitted]".to_string());
+                } else {
+                    strip_images(field);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_images),
+        _ => {}
+    }
+}
+
+/// What the model is told a tool call returned.
+fn tool_result_content(result: &Result<ToolExecutionResult, String>) -> String {
+    let mut value = match result {
+        Ok(result) if result.success => result.result.clone(),
+        Ok(result) => serde_json::json!({ "error": result.error.clone().unwrap_or_else(|| "Tool failed".to_string()) }),
+        Err(e) => serde_json::json!({ "error": e }),
+    };
+    strip_images(&mut value);
+    let content = value.to_string();
+    if content.chars().count() > MAX_TOOL_RESULT_CHARS {
+        format!("{}... (truncated)", content.chars().take(MAX_TOOL_RESULT_CHARS).collect::<String>())
+    } else {
+        content
+    }
+}
+
+fn emit(app_handle: &AppHandle, session_id: &str, event: serde_json::Value) {
+    if let Err(e) = crate::event_throttle::emit_critical(app_handle, &format!("ollama-stream-{}", session_id), event) {
+        eprintln!("Failed to emit tool stream event: {}", e);
+    }
+}
+
+async fn stream_round(
+    app_handle: &AppHandle,
+    session_id: &str,
+    body: &serde_json::Value,
+    cancel: &tokio_util::sync::CancellationToken,
+) -> Result<Round, String> {
+    let url = format!("{}/api/chat", crate::ollama::ollama_base_url());
+    let mut round = Round { text: String::new(), tool_calls: Vec::new(), prompt_tokens: 0, completion_tokens: 0, cancelled: false };
+
+    let response = tokio::select! {
+        _ = cancel.cancelled() => return Ok(Round { cancelled: true, ..round }),
+        response = crate::ollama_retry::send_with_retry(EndpointClass::Generation, client(EndpointClass::Generation).post(&url).json(body)) => response?,
+    };
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Generation failed: {}", error_text));
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut lines = NdjsonLines::default();
+    loop {
+        let chunk = tokio::select! {
+            _ = cancel.cancelled() => return Ok(Round { cancelled: true, ..round }),
+            chunk = tokio::time::timeout(CHUNK_TIMEOUT, stream.next()) => chunk,
+        };
+        let bytes = match chunk {
+            Err(_) => return Err("Ollama stopped responding".to_string()),
+            Ok(None) => return Ok(round),
+            Ok(Some(Err(e))) => return Err(format!("Stream error: {}", e)),
+            Ok(Some(Ok(bytes))) => bytes,
+        };
+        for line in lines.push(&bytes) {
+            let line: ChatLine = match serde_json::from_str(&line) {
+                Ok(line) => line,
+                Err(e) => {
+                    eprintln!("Failed to parse tool-calling stream line: {} - Line: {}", e, line);
+                    continue;
+                }
+            };
+            if let Some(error) = line.error {
+                return Err(error);
+            }
+            let message = line.message.unwrap_or_default();
+            if !message.content.is_empty() {
+                crate::event_throttle::emit_throttled(app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
+                    "type": "chunk",
+                    "text": message.content,
+                    "done": false
+                }));
+                round.text.push_str(&message.content);
+            }
+            round.tool_calls.extend(message.tool_calls);
+            if line.done {
+                round.prompt_tokens = line.prompt_eval_count.unwrap_or(0);
+                round.completion_tokens = line.eval_count.unwrap_or(0);
+                return Ok(round);
+            }
+        }
+    }
+}
+
+/// Answer `prompt` with `model`, letting it call the MCP session's tools along the way. Returns the
+/// text the user saw.
+pub async fn run_agent_with_tools(
+    app_handle: &AppHandle,
+    mcp_session: Arc<MCPSession>,
+    model: &str,
+    system_prompt: &str,
+    prompt: String,
+    context: Option<Vec<ChatContextMessage>>,
+    session_id: &str,
+    agent_type: &str,
+    options: Option<GenerationOptions>,
+) -> Result<String, String> {
+    let tools = mcp_session.get_available_tools().await;
+    let definitions = tool_definitions(&tools);
+    let system_prompt = format!("{}\n\n{}", system_prompt, crate::system_prompts::AGENT_TOOLS_PROMPT);
+    let mut messages: Vec<serde_json::Value> = build_chat_messages(&system_prompt, prompt, context)
+        .into_iter()
+        .filter_map(|message| serde_json::to_value(message).ok())
+        .collect();
+    // The tool schemas count against the window too
+    let prompt_tokens = messages.iter().map(|message| estimate_tokens(&message.to_string())).sum::<usize>()
+        + estimate_tokens(&serde_json::Value::Array(definitions.clone()).to_string());
+    let options = build_ollama_options(agent_type, options.as_ref(), prompt_tokens);
+
+    println!("🧰 Starting {} agent with {} MCP tools for session: {}", agent_type, tools.len(), session_id);
+    crate::event_throttle::emit_critical(app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
+        "type": "start",
+        "model": model,
+        "agent_type": agent_type,
+        "endpoint": "chat",
+        "mcp_enabled": true,
+        "mcp_session_id": mcp_session.id,
+        "provenance": {
+            "provider": "ollama",
+            "modelTag": model,
+            "generationOptions": &options,
+        }
+    })).map_err(|e| format!("Failed to emit start event: {}", e))?;
+
+    let cancel = crate::ollama::register_session(session_id);
+    let mut text = String::new();
+    let (mut prompt_tokens, mut completion_tokens) = (0, 0);
+    for round_index in 0..MAX_TOOL_ROUNDS {
+        let mut body = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "stream": true,
+            "options": options,
+        });
+        if round_index + 1 < MAX_TOOL_ROUNDS {
+            body["tools"] = serde_json::Value::Array(definitions.clone());
+        }
+        if let Some(keep_alive) = crate::model_residency::keep_alive() {
+            body["keep_alive"] = serde_json::Value::String(keep_alive);
+        }
+        crate::model_residency::note_model_use(model);
+
+        let round = match stream_round(app_handle, session_id, &body, &cancel).await {
+            Ok(round) => round,
+            Err(e) => {
+                eprintln!("❌ Tool-calling agent failed for session {}: {}", session_id, e);
+                emit(app_handle, session_id, serde_json::json!({ "type": "error", "error": e }));
+                crate::ollama::cleanup_session(session_id);
+                return Err(e);
+            }
+        };
+        text.push_str(&round.text);
+        prompt_tokens += round.prompt_tokens;
+        completion_tokens += round.completion_tokens;
+        if round.cancelled {
+            emit(app_handle, session_id, serde_json::json!({ "type": "cancelled", "message": "Response cancelled by user" }));
+            crate::ollama::cleanup_session(session_id);
+            return Ok(text);
+        }
+        if round.tool_calls.is_empty() {
+            break;
+        }
+
+        messages.push(serde_json::json!({
+            "role": "assistant",
+            "content": round.text,
+            "tool_calls": round.tool_calls,
+        }));
+        for call in &round.tool_calls {
+            let Some((tool_name, arguments)) = parse_call(call) else {
+                continue;
+            };
+            println!("🔧 Agent calls {} with {}", tool_name, arguments);
+            emit(app_handle, session_id, serde_json::json!({
+                "type": "tool_call",
+                "tool_name": tool_name,
+                "arguments": arguments,
+            }));
+
+            // Waits here while the user decides on an approval prompt
+            let result = mcp_session.execute_tool(&tool_name, arguments).await;
+            let content = tool_result_content(&result);
+            let success = matches!(&result, Ok(result) if result.success);
+            emit(app_handle, session_id, serde_json::json!({
+                "type": "tool_result",
+                "tool_name": tool_name,
+                "success": success,
+                "result": content,
+            }));
+            if let Ok(result) = &result {
+                let _ = app_handle.emit(&format!("mcp-tool-result-{}", session_id), serde_json::json!({
+                    "tool_name": tool_name,
+                    "result": result,
+                    "session_id": session_id
+                }));
+            }
+            messages.push(serde_json::json!({
+                "role": "tool",
+                "tool_name": tool_name,
+                "content": content,
+            }));
+        }
+        if cancel.is_cancelled() {
+            emit(app_handle, session_id, serde_json::json!({ "type": "cancelled", "message": "Response cancelled by user" }));
+            crate::ollama::cleanup_session(session_id);
+            return Ok(text);
+        }
+        // Keep the next round's words apart from this one's in the streamed message
+        if !round.text.trim().is_empty() {
+            crate::event_throttle::emit_throttled(app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
+                "type": "chunk",
+                "text": "\n\n",
+                "done": false
+            }));
+            text.push_str("\n\n");
+        }
+    }
+
+    crate::ollama::cleanup_session(session_id);
+    println!("✅ Tool-calling agent finished for session: {}", session_id);
+    emit(app_handle, session_id, serde_json::json!({
+        "type": "complete",
+        "usage": TokenUsage::from_counts(Some(prompt_tokens), Some(completion_tokens)),
+    }));
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_calls_and_results_are_shaped_for_the_model() {
+        let call = serde_json::json!({ "function": { "name": "click", "arguments": "{\"x\": 10, \"y\": 20}" } });
+        assert_eq!(parse_call(&call), Some(("click".to_string(), serde_json::json!({ "x": 10, "y": 20 }))));
+
+        let screenshot = Ok(ToolExecutionResult {
+            success: true,
+            result: serde_json::json!({ "image_base64": "iVBORw0KGgo...", "width": 1920, "height": 1080 }),
+            error: None,
+            execution_time_ms: 12,
+            tool_name: "take_screenshot".to_string(),
+        });
+        let content = tool_result_content(&screenshot);
+        assert!(content.contains("[image omitted]"));
+        assert!(content.contains("1920"));
+        assert_eq!(tool_result_content(&Err("User denied approval".to_string())), r#"{"error":"User denied approval"}"#);
+    }
+}
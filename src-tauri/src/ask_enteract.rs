@@ -0,0 +1,350 @@
+// src-tauri/src/ask_enteract.rs
+// One question over everything stored locally. The question is routed to the sources it seems to
+// be about (chats, recorded conversations, indexed documents, what's on screen), each source is
+// searched, the hits are merged into one numbered evidence list that keeps where every item came
+// from, and the answer streams like any agent response with [n] citations into that list.
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::data::chat::ChatStorage;
+use crate::data::conversation::ConversationStorage;
+use crate::data::types::MessageSearchHit;
+use crate::enhanced_rag_commands::{get_rag_system, EnhancedRagSystemState};
+
+const MAX_EVIDENCE: usize = 8;
+const MAX_PER_SOURCE: usize = 4;
+const MESSAGE_CANDIDATES: usize = 200;
+const MAX_TERMS: usize = 8;
+const EXCERPT_CHARS: usize = 600;
+
+const ASK_SYSTEM_PROMPT: &str = "You answer questions about the user's own chats, recorded conversations, documents and screen \
+using only the numbered evidence you are given. Each item says where it came from. Cite the items you rely on inline like [1] \
+or [2][3]. If the evidence doesn't answer the question, say so instead of guessing.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    Chats,
+    Conversations,
+    Documents,
+    Screen,
+}
+
+impl DataSource {
+    fn label(&self) -> &'static str {
+        match self {
+            DataSource::Chats => "chat",
+            DataSource::Conversations => "conversation",
+            DataSource::Documents => "document",
+            DataSource::Screen => "screen",
+        }
+    }
+}
+
+// Words and phrases that point a question at one source; matched on whole words
+const SOURCE_CUES: &[(DataSource, &[&str])] = &[
+    (DataSource::Chats, &["chat", "chats", "you said", "you told me", "i asked", "your answer", "assistant"]),
+    (DataSource::Conversations, &[
+        "meeting", "meetings", "call", "calls", "conversation", "conversations", "transcript", "discussed",
+        "mentioned", "talked", "standup", "interview", "said",
+    ]),
+    (DataSource::Documents, &["document", "documents", "doc", "docs", "file", "files", "pdf", "report", "spec", "contract", "paper"]),
+    (DataSource::Screen, &["screen", "window", "this page", "looking at", "this app"]),
+];
+
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "are", "was", "were", "what", "when", "where", "which", "who", "why", "how", "did", "does",
+    "about", "with", "from", "that", "this", "there", "have", "has", "had", "you", "your", "my", "me", "our", "can",
+    "could", "would", "should", "tell", "any", "all", "into", "its", "it's", "been", "said", "say",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Evidence {
+    pub index: usize, // The [n] label used in the answer
+    pub source: DataSource,
+    pub reference_id: String, // Chat or conversation id, document id, or the on-screen app name
+    pub item_id: Option<String>, // Message or chunk id within it
+    pub title: String,
+    pub excerpt: String,
+    pub timestamp: Option<String>,
+    pub score: f32,
+    // Chat messages only: the user's own marks on the message
+    #[serde(default)]
+    pub bookmarked: bool,
+    #[serde(default)]
+    pub reactions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnteractAnswer {
+    pub sources: Vec<DataSource>,
+    pub answer: String,
+    pub evidence: Vec<Evidence>,
+    pub cited: Vec<usize>, // Evidence labels the answer actually used
+}
+
+fn padded_words(text: &str) -> String {
+    let words: Vec<String> = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    format!(" {} ", words.join(" "))
+}
+
+/// Sources the question seems to be about. Questions with no cue search everything but the screen.
+pub fn classify_question(question: &str) -> Vec<DataSource> {
+    let words = padded_words(question);
+    let sources: Vec<DataSource> = SOURCE_CUES
+        .iter()
+        .filter(|(_, cues)| cues.iter().any(|cue| words.contains(&format!(" {} ", cue))))
+        .map(|(source, _)| *source)
+        .collect();
+    if sources.is_empty() {
+        vec![DataSource::Chats, DataSource::Conversations, DataSource::Documents]
+    } else {
+        sources
+    }
+}
+
+fn search_terms(question: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in padded_words(question).split_whitespace() {
+        if word.chars().count() >= 3 && !STOP_WORDS.contains(&word) && !terms.iter().any(|t| t == word) {
+            terms.push(word.to_string());
+        }
+    }
+    terms.truncate(MAX_TERMS);
+    terms
+}
+
+fn excerpt(text: &str) -> String {
+    text.trim().chars().take(EXCERPT_CHARS).collect()
+}
+
+// Share of the search terms that appear in the text
+fn term_score(text: &str, terms: &[String]) -> f32 {
+    let words = padded_words(text);
+    let matched = terms.iter().filter(|term| words.contains(&format!(" {}", term))).count();
+    matched as f32 / terms.len().max(1) as f32
+}
+
+fn message_evidence(source: DataSource, hits: Vec<MessageSearchHit>, terms: &[String]) -> Vec<Evidence> {
+    let mut evidence: Vec<Evidence> = hits
+        .into_iter()
+        .map(|hit| Evidence {
+            index: 0,
+            source,
+            score: term_score(&hit.text, terms),
+            reference_id: hit.session_id,
+            item_id: Some(hit.message_id),
+            title: format!("{} ({})", hit.session_title, hit.sender),
+            excerpt: excerpt(&hit.text),
+            timestamp: Some(hit.timestamp),
+            bookmarked: hit.bookmarked,
+            reactions: hit.reactions,
+        })
+        .collect();
+    // Stable sort keeps newer messages first among equal scores
+    evidence.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    evidence.truncate(MAX_PER_SOURCE);
+    evidence
+}
+
+fn search_chats(app_handle: &AppHandle, terms: &[String]) -> Result<Vec<Evidence>, String> {
+    let hits = ChatStorage::new(app_handle)
+        .and_then(|storage| storage.search_messages(terms, MESSAGE_CANDIDATES))
+        .map_err(|e| format!("Chat search failed: {}", e))?;
+    Ok(message_evidence(DataSource::Chats, hits, terms))
+}
+
+fn search_conversations(app_handle: &AppHandle, terms: &[String]) -> Result<Vec<Evidence>, String> {
+    let hits = ConversationStorage::new(app_handle)
+        .and_then(|storage| storage.search_messages(terms, MESSAGE_CANDIDATES))
+        .map_err(|e| format!("Conversation search failed: {}", e))?;
+    Ok(message_evidence(DataSource::Conversations, hits, terms))
+}
+
+async fn search_documents(state: &State<'_, EnhancedRagSystemState>, question: &str) -> Result<Vec<Evidence>, String> {
+    let system = get_rag_system(state)?;
+    let chunks = system.search_documents(question, Vec::new()).await.map_err(|e| e.to_string())?;
+
+    let mut evidence = Vec::new();
+    for chunk in chunks.into_iter().take(MAX_PER_SOURCE) {
+        let title = system
+            .get_document_text(&chunk.document_id)
+            .map(|(file_name, _)| file_name)
+            .unwrap_or_else(|_| chunk.document_id.clone());
+        evidence.push(Evidence {
+            index: 0,
+            source: DataSource::Documents,
+            reference_id: chunk.document_id,
+            item_id: Some(chunk.id),
+            title,
+            excerpt: excerpt(&chunk.content),
+            timestamp: None,
+            score: chunk.similarity_score.or(chunk.bm25_score).unwrap_or(0.0),
+            bookmarked: false,
+            reactions: Vec::new(),
+        });
+    }
+    Ok(evidence)
+}
+
+async fn screen_evidence() -> Vec<Evidence> {
+    crate::window_manager::active_app()
+        .await
+        .map(|app| Evidence {
+            index: 0,
+            source: DataSource::Screen,
+            reference_id: app.app_name.clone(),
+            item_id: None,
+            title: app.app_name.clone(),
+            excerpt: format!("The user is working in {} with the window \"{}\" in front.", app.app_name, app.window_title),
+            timestamp: Some(chrono::Utc::now().to_rfc3339()),
+            score: 1.0,
+            bookmarked: false,
+            reactions: Vec::new(),
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Take the best of each source in turn so one busy source can't crowd out the others, then
+/// number the result.
+pub fn merge_evidence(per_source: Vec<Vec<Evidence>>) -> Vec<Evidence> {
+    let mut queues: Vec<std::vec::IntoIter<Evidence>> = per_source.into_iter().map(Vec::into_iter).collect();
+    let mut merged = Vec::new();
+    while merged.len() < MAX_EVIDENCE {
+        let before = merged.len();
+        for queue in queues.iter_mut() {
+            if merged.len() == MAX_EVIDENCE {
+                break;
+            }
+            if let Some(item) = queue.next() {
+                merged.push(item);
+            }
+        }
+        if merged.len() == before {
+            break;
+        }
+    }
+    for (position, item) in merged.iter_mut().enumerate() {
+        item.index = position + 1;
+    }
+    merged
+}
+
+/// Answer a question from whichever local data it's about. The evidence list is emitted on
+/// `ask-enteract-evidence-{session_id}` before the answer streams on `ollama-stream-{session_id}`.
+#[tauri::command]
+pub async fn ask_enteract(
+    app_handle: AppHandle,
+    state: State<'_, EnhancedRagSystemState>,
+    question: String,
+    session_id: String,
+    sources: Option<Vec<DataSource>>, // Skips classification when set
+) -> Result<EnteractAnswer, String> {
+    let question = question.trim().to_string();
+    if question.is_empty() {
+        return Err("Question is empty".to_string());
+    }
+    let sources = sources.filter(|s| !s.is_empty()).unwrap_or_else(|| classify_question(&question));
+    let terms = search_terms(&question);
+    println!("🧭 Routing question to {:?} with terms {:?}", sources, terms);
+
+    let mut per_source = Vec::new();
+    for source in &sources {
+        let found = match source {
+            DataSource::Chats => search_chats(&app_handle, &terms),
+            DataSource::Conversations => search_conversations(&app_handle, &terms),
+            DataSource::Documents => search_documents(&state, &question).await,
+            DataSource::Screen => Ok(screen_evidence().await),
+        };
+        // One source failing (e.g. the document index still loading) shouldn't sink the answer
+        match found {
+            Ok(found) => per_source.push(found),
+            Err(e) => eprintln!("⚠️ Skipping {} search: {}", source.label(), e),
+        }
+    }
+    let evidence = merge_evidence(per_source);
+    if let Err(e) = app_handle.emit(&format!("ask-enteract-evidence-{}", session_id), &evidence) {
+        eprintln!("Failed to emit evidence: {}", e);
+    }
+
+    let items: Vec<String> = evidence
+        .iter()
+        .map(|item| {
+            let when = item.timestamp.as_deref().map(|t| format!(", {}", t)).unwrap_or_default();
+            format!("[{}] ({}: {}{}) {}", item.index, item.source.label(), item.title, when, item.excerpt)
+        })
+        .collect();
+    let prompt = if items.is_empty() {
+        format!("No evidence was found in the user's data.\n\nQuestion: {}", question)
+    } else {
+        format!("Evidence:\n\n{}\n\nQuestion: {}", items.join("\n\n"), question)
+    };
+
+    let model = crate::agent_models::resolve_agent_model(&app_handle, "enteract").await?;
+    println!("🧭 Answering from {} evidence items", evidence.len());
+    let answer = crate::ollama::generate_agent_response_stream(
+        app_handle.clone(),
+        model,
+        prompt,
+        ASK_SYSTEM_PROMPT.to_string(),
+        None,
+        session_id,
+        "enteract".to_string(),
+        None,
+    ).await?;
+
+    Ok(EnteractAnswer {
+        sources,
+        cited: crate::file_qa::cited_labels(&answer, evidence.len()),
+        answer,
+        evidence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(source: DataSource, title: &str) -> Evidence {
+        Evidence {
+            index: 0,
+            source,
+            reference_id: title.to_string(),
+            item_id: None,
+            title: title.to_string(),
+            excerpt: String::new(),
+            timestamp: None,
+            score: 1.0,
+            bookmarked: false,
+            reactions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_questions_are_routed_by_their_cues() {
+        assert_eq!(classify_question("What did we decide in Monday's meeting?"), vec![DataSource::Conversations]);
+        assert_eq!(classify_question("Summarize the PDF on pricing"), vec![DataSource::Documents]);
+        assert_eq!(classify_question("What's in this window?"), vec![DataSource::Screen]);
+        assert_eq!(
+            classify_question("Where are the Q3 numbers?"),
+            vec![DataSource::Chats, DataSource::Conversations, DataSource::Documents]
+        );
+    }
+
+    #[test]
+    fn test_merge_takes_sources_in_turn() {
+        let chats = (0..6).map(|i| item(DataSource::Chats, &format!("chat {}", i))).collect();
+        let documents = vec![item(DataSource::Documents, "spec")];
+        let merged = merge_evidence(vec![chats, documents]);
+
+        assert_eq!(merged.len(), 7);
+        assert_eq!(merged[1].title, "spec");
+        assert_eq!(merged.iter().map(|e| e.index).collect::<Vec<_>>(), (1..=merged.len()).collect::<Vec<_>>());
+    }
+}
@@ -12,8 +12,11 @@ pub mod types;
 pub mod device_enumerator;
 pub mod audio_processor; 
 pub mod capture_engine;
+pub mod capture_pipeline;
 pub mod quality_filter;
 pub mod settings;
+pub mod quality_metrics;
+pub mod latency;
 
 // Re-export main types and functions
 pub use types::{CAPTURE_STATE, CaptureState, AudioLoopbackDevice, DeviceType, LoopbackMethod, AudioDeviceSettings};
@@ -2,10 +2,11 @@
 // use crate::audio_loopback::quality_filter::{estimate_transcription_confidence, is_transcription_quality_ok};
 use anyhow::Result;
 use tauri::{AppHandle, Emitter};
-use base64::prelude::*;
 use serde_json;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::time::Instant;
+use crate::audio_loopback::latency::SegmentTiming;
 
 // Audio processing for transcription with improved quality filtering
 #[tauri::command]
@@ -13,6 +14,34 @@ pub async fn process_audio_for_transcription(
     audio_data: Vec<u8>,
     sample_rate: u32,
     app_handle: AppHandle
+) -> Result<String, String> {
+    // Loopback audio only reaches here from a capture that passed the consent check
+    if crate::consent::recording_session("loopback").is_none() {
+        return Err("Loopback audio is only transcribed while capture is recording a conversation".to_string());
+    }
+    transcribe_segment(audio_data, sample_rate, app_handle, None).await
+}
+
+/// Transcribe a segment from the capture pipeline, recording its stage latencies when `timing`
+/// is given.
+pub async fn transcribe_segment(
+    audio_data: Vec<u8>,
+    sample_rate: u32,
+    app_handle: AppHandle,
+    mut timing: Option<SegmentTiming>
+) -> Result<String, String> {
+    let result = run_transcription(audio_data, sample_rate, app_handle, &mut timing).await;
+    if let Some(timing) = &timing {
+        crate::audio_loopback::latency::record_segment(timing);
+    }
+    result
+}
+
+async fn run_transcription(
+    audio_data: Vec<u8>,
+    sample_rate: u32,
+    app_handle: AppHandle,
+    timing: &mut Option<SegmentTiming>
 ) -> Result<String, String> {
     // First process the audio through our pipeline to match Python's fast_audio_process
     // println!("[PROCESS] Input: {} bytes, {} Hz", audio_data.len(), sample_rate); // Commented out: Audio loopback is working, reducing console noise for debugging focus
@@ -39,8 +68,9 @@ pub async fn process_audio_for_transcription(
     let db_level = if rms > 0.0 { 20.0 * rms.log10() } else { -60.0 };
     
     // Python checks RMS on int16 samples: rms < 100 
-    // For int16, RMS of 100 = 100/32768 = 0.00305 in float32
-    if rms < 0.00305 {
+    // For int16, RMS of 100 = 100/32768 = 0.00305 in float32; the profile's VAD setting scales it
+    let profile = crate::conversation_profiles::active_profile();
+    if rms < profile.vad_rms_threshold() {
         log_transcription_debug("[PROCESS] Audio too quiet - skipping", rms, db_level);
         return Ok("".to_string());
     }
@@ -58,8 +88,6 @@ pub async fn process_audio_for_transcription(
     // The direct method was hanging, so let's use what works and fix the quality filtering
     log_transcription_debug("[MAIN] Using file-based transcription with improved filtering...", rms, db_level);
     
-    let audio_base64 = base64::prelude::BASE64_STANDARD.encode(&pcm16_bytes);
-    
     // Load settings to get the selected loopback whisper model
     let model_size = match crate::audio_loopback::settings::load_general_settings().await {
         Ok(Some(settings)) => {
@@ -77,6 +105,12 @@ pub async fn process_audio_for_transcription(
         Err(_) => "small".to_string() // Error loading settings, use default
     };
     
+    // The conversation profile may pin its own model
+    let model_size = profile.whisper_model.clone().unwrap_or(model_size);
+    
+    // Drop to a lighter model when the power policy asks for it
+    let model_size = crate::power::preferred_whisper_model(&model_size);
+    
     // println!("[AUDIO_PROCESSOR] Using Whisper model: {}", model_size); // Commented out: Audio loopback is working, reducing console noise for debugging focus
     
     let config = crate::speech::WhisperModelConfig {
@@ -87,19 +121,34 @@ pub async fn process_audio_for_transcription(
         maxSegmentLength: 30,
     };
     
-    match crate::speech::transcribe_audio_base64(audio_base64, config).await {
+    if let Some(timing) = timing.as_mut() {
+        timing.whisper_started_at = Some(Instant::now());
+    }
+    let transcription = crate::stt_provider::transcribe_pcm16(crate::stt_provider::SttStream::Loopback, pcm16_bytes, config, true).await;
+    if let Some(timing) = timing.as_mut() {
+        timing.whisper_finished_at = Some(Instant::now());
+    }
+    
+    match transcription {
         Ok(result) => {
             let text = result.text.trim();
             log_transcription_debug(&format!("[MAIN] Raw Whisper result: '{}'", text), rms, db_level);
             
             if !text.is_empty() && text.len() > 1 {
+                // A provider's own score can only lower the text-based estimate; unscored results use the estimate alone
                 let estimated_confidence = estimate_python_style_confidence(text);
+                let estimated_confidence = if result.confidence_unknown {
+                    estimated_confidence
+                } else {
+                    estimated_confidence.min(result.confidence)
+                };
                 
                 // Clean up the text - remove brackets and convert to proper format
                 let cleaned_text = clean_whisper_output(text);
                 log_transcription_debug(&format!("[MAIN] Cleaned text: '{}'", cleaned_text), rms, db_level);
                 
                 let is_quality_ok = is_python_style_quality_ok(&cleaned_text, estimated_confidence);
+                crate::audio_loopback::quality_metrics::record_transcription(estimated_confidence as f64, is_quality_ok);
                 
                 if !is_quality_ok {
                     log_transcription_debug(&format!("[MAIN FILTERED] {} (conf: {:.3})", cleaned_text, estimated_confidence), rms, db_level);
@@ -115,8 +164,20 @@ pub async fn process_audio_for_transcription(
                     "timestamp": chrono::Utc::now().timestamp_millis(),
                     "source": "loopback",
                     "confidence": estimated_confidence,
-                    "audioLevel": db_level
+                    "audioLevel": db_level,
+                    "audio": result.audio,
+                    "profile": profile.id,
+                    "isQuestion": profile.question_detection
+                        && crate::conversation_profiles::looks_like_question(&cleaned_text)
                 }));
+                if let Some(timing) = timing.as_mut() {
+                    timing.event_emitted_at = Some(Instant::now());
+                }
+                
+                // Caption overlay: the chunk ended about now and lasted as long as its samples
+                let end_ms = chrono::Utc::now().timestamp_millis();
+                let start_ms = end_ms - (processed_samples.len() / 16) as i64;
+                crate::captions::push_caption(&app_handle, "loopback", &cleaned_text, true, start_ms, end_ms);
                 
                 return Ok(cleaned_text.to_string());
             }
@@ -1,7 +1,9 @@
 // src-tauri/src/audio_loopback/capture_engine.rs
 use crate::audio_loopback::types::*;
 use crate::audio_loopback::device_enumerator::WASAPILoopbackEnumerator;
-use crate::audio_loopback::audio_processor::{process_audio_for_transcription, process_audio_chunk, calculate_audio_level};
+use crate::audio_loopback::audio_processor::transcribe_segment;
+use crate::audio_loopback::capture_pipeline::{CapturePipeline, CaptureSink};
+use crate::audio_loopback::latency::SegmentTiming;
 use anyhow::Result;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
@@ -13,6 +15,8 @@ use serde_json;
 #[tauri::command]
 pub async fn start_audio_loopback_capture(
     device_id: String,
+    profile_id: Option<String>,
+    session_id: String,
     app_handle: AppHandle
 ) -> Result<String, String> {
     // Check if already capturing
@@ -23,6 +27,14 @@ pub async fn start_audio_loopback_capture(
         }
     }
     
+    // Switch the pipeline to the requested conversation profile before capture starts
+    if let Some(profile_id) = profile_id {
+        crate::conversation_profiles::activate_profile(&app_handle, &profile_id)?;
+    }
+    
+    // Nothing is captured until the conversation's recording consent allows it
+    crate::consent::begin_recording(&app_handle, "loopback", &session_id)?;
+    
     // println!("🎤 Starting audio capture for device: {}", device_id); // Commented out: Audio loopback is working, reducing console noise for debugging focus
     
     // Create stop channel
@@ -38,6 +50,9 @@ pub async fn start_audio_loopback_capture(
         }
     });
     
+    // Quality report counters cover one capture run
+    crate::audio_loopback::quality_metrics::reset_quality_metrics(&session_id);
+    
     // Update state
     {
         let mut state = CAPTURE_STATE.lock().unwrap();
@@ -58,6 +73,7 @@ pub async fn stop_audio_loopback_capture() -> Result<(), String> {
         state.is_capturing = false;
         (state.stop_tx.take(), state.capture_handle.take())
     };
+    crate::consent::end_recording("loopback");
     
     // Send stop signal
     if let Some(tx) = stop_tx {
@@ -72,6 +88,37 @@ pub async fn stop_audio_loopback_capture() -> Result<(), String> {
     Ok(())
 }
 
+// Sends pipeline output to the app: transcription of buffered speech and `audio-chunk` previews
+struct AppCaptureSink {
+    app_handle: AppHandle,
+    device_id: String,
+    device_sample_rate: u32,
+    start_time: Instant,
+}
+
+impl CaptureSink for AppCaptureSink {
+    fn transcribe(&mut self, pcm16_stereo: Vec<u8>, sample_rate: u32, timing: SegmentTiming) {
+        let app_handle = self.app_handle.clone();
+        tokio::spawn(async move {
+            // Results are emitted by the transcription path itself
+            let _ = transcribe_segment(pcm16_stereo, sample_rate, app_handle, Some(timing)).await;
+        });
+    }
+
+    fn audio_chunk(&mut self, pcm16_mono: Vec<u8>, level: f32, total_samples: u64) {
+        let _emit_result = self.app_handle.emit("audio-chunk", serde_json::json!({
+            "deviceId": self.device_id,
+            "audioData": base64::prelude::BASE64_STANDARD.encode(&pcm16_mono),
+            "sampleRate": self.device_sample_rate,
+            "channels": 1,
+            "level": level,
+            "timestamp": chrono::Utc::now().timestamp_millis(),
+            "duration": self.start_time.elapsed().as_secs(),
+            "totalSamples": total_samples
+        }));
+    }
+}
+
 // Main audio capture loop with reduced logging
 fn run_audio_capture_loop_sync(
     device_id: String,
@@ -150,19 +197,19 @@ fn run_audio_capture_loop_sync(
     std::thread::sleep(Duration::from_millis(100));
     
     let start_time = Instant::now();
-    let mut total_samples = 0u64;
-    let mut last_emit = Instant::now();
     let mut error_count = 0u32;
+    let vad_rms_threshold = crate::conversation_profiles::active_profile().vad_rms_threshold();
+    let mut pipeline = CapturePipeline::new(vad_rms_threshold, start_time);
+    let mut sink = AppCaptureSink {
+        app_handle: app_handle.clone(),
+        device_id: device_id.clone(),
+        device_sample_rate: device_info.sample_rate,
+        start_time,
+    };
     
-    // Transcription buffer setup - MATCHING PYTHON CONFIG
-    let mut transcription_buffer: Vec<f32> = Vec::new();
-    let transcription_buffer_duration = 4.0;  // Python: BUFFER_DURATION = 4.0
-    // Important: Buffer size is at 16kHz (Whisper rate), not device rate
-    let transcription_buffer_size = (16000.0 * transcription_buffer_duration) as usize;
-    let mut last_transcription = Instant::now();
-    let transcription_interval = Duration::from_millis(800);  // Python: PROCESSING_INTERVAL = 0.8
-    let min_audio_length = 1.5;  // Python: MIN_AUDIO_LENGTH = 1.5
-    let min_audio_samples = (16000.0 * min_audio_length) as usize;  // At 16kHz
+    // The power policy can stretch the transcription interval; it's checked once a second
+    pipeline.set_min_interval(crate::power::transcription_interval());
+    let mut last_power_check = Instant::now();
     
     // Main capture loop with reduced logging
     loop {
@@ -170,7 +217,14 @@ fn run_audio_capture_loop_sync(
             break;
         }
         
+        if last_power_check.elapsed() >= Duration::from_secs(1) {
+            pipeline.set_min_interval(crate::power::transcription_interval());
+            last_power_check = Instant::now();
+        }
+        
         if h_event.wait_for_event(100).is_err() {
+            // No audio is playing; the last utterance may still need flushing
+            pipeline.idle(Instant::now(), &mut sink);
             std::thread::sleep(Duration::from_millis(10));
             continue;
         }
@@ -193,6 +247,7 @@ fn run_audio_capture_loop_sync(
         
         let calculated_buffer_size = frames_available as usize * bytes_per_frame as usize;
         if calculated_buffer_size > 1_048_576 {
+            crate::audio_loopback::quality_metrics::record_dropped_chunk();
             std::thread::sleep(Duration::from_millis(10));
             continue;
         }
@@ -209,6 +264,7 @@ fn run_audio_capture_loop_sync(
             },
             Err(_) => {
                 error_count += 1;
+                crate::audio_loopback::quality_metrics::record_dropped_chunk();
                 if error_count > 10 {
                     break;
                 }
@@ -239,118 +295,7 @@ fn run_audio_capture_loop_sync(
             }
         }
         
-        // Process audio - MATCHING PYTHON PIPELINE
-        // Python always outputs at 16kHz for Whisper
-        let processed_audio = process_audio_chunk(
-            audio_data,
-            bits_per_sample,
-            channels,
-            format.get_samplespersec(),
-            16000  // Always resample to 16kHz for Whisper
-        );
-        
-        total_samples += processed_audio.len() as u64;
-        transcription_buffer.extend_from_slice(&processed_audio);
-        
-        // Trim buffer
-        if transcription_buffer.len() > transcription_buffer_size * 2 {
-            let excess = transcription_buffer.len() - transcription_buffer_size;
-            transcription_buffer.drain(0..excess);
-        }
-        
-        // Try transcription
-        let now = Instant::now();
-        if transcription_buffer.len() >= min_audio_samples && 
-           now.duration_since(last_transcription) > transcription_interval {
-            
-            // Python checks RMS > 100 for int16, which is ~0.00305 for float32
-            let buffer_rms = (transcription_buffer.iter().map(|&x| x * x).sum::<f32>() / transcription_buffer.len() as f32).sqrt();
-            let buffer_level = calculate_audio_level(&transcription_buffer);
-            
-            // Log buffer state every transcription attempt
-            // println!("[CAPTURE] Buffer: {} samples, RMS: {:.6}, Level: {:.1}dB", 
-            //          transcription_buffer.len(), buffer_rms, buffer_level);
-            // Commented out: Audio loopback is working, reducing console noise for debugging focus
-            
-            if buffer_rms > 0.00305 {  // Match Python's RMS threshold
-                // The transcription buffer already contains mono f32 samples at 16kHz
-                // We need to convert to stereo PCM16 bytes for the transcription function
-                // which expects stereo input (it will convert back to mono)
-                let int16_samples: Vec<i16> = transcription_buffer.iter()
-                    .map(|&sample| (sample * 32767.0).clamp(-32768.0, 32767.0) as i16)
-                    .collect();
-                
-                // Create stereo PCM16 by duplicating mono samples
-                let mut stereo_pcm16_bytes = Vec::with_capacity(int16_samples.len() * 4);
-                for &sample in &int16_samples {
-                    let bytes = sample.to_le_bytes();
-                    stereo_pcm16_bytes.extend_from_slice(&bytes);  // Left channel
-                    stereo_pcm16_bytes.extend_from_slice(&bytes);  // Right channel (duplicate)
-                }
-                let pcm16_bytes = stereo_pcm16_bytes;
-                
-                let app_handle_clone = app_handle.clone();
-                let audio_bytes_clone = pcm16_bytes.clone();
-                // Important: We're passing 16kHz since we already resampled
-                let sample_rate = 16000;
-                
-                // println!("[CAPTURE] Sending {} bytes for transcription (RMS: {:.6})", 
-                //          pcm16_bytes.len(), buffer_rms);
-                // Commented out: Audio loopback is working, reducing console noise for debugging focus
-                
-                tokio::spawn(async move {
-                    match process_audio_for_transcription(
-                        audio_bytes_clone,
-                        sample_rate,
-                        app_handle_clone
-                    ).await {
-                        Ok(text) => {
-                            if !text.is_empty() {
-                                // println!("[CAPTURE] Transcription result: '{}'", text); // Commented out: Audio loopback is working, reducing console noise for debugging focus
-                            }
-                        },
-                        Err(e) => {} // println!("[CAPTURE] Transcription error: {}", e) // Commented out: Audio loopback is working, reducing console noise for debugging focus
-                    }
-                });
-                
-                last_transcription = now;
-                
-                // Keep overlap - Python uses 1.0 second at 16kHz
-                let overlap_duration = 1.0;
-                let overlap_size = (16000.0 * overlap_duration) as usize;
-                if transcription_buffer.len() > overlap_size {
-                    let samples_to_remove = transcription_buffer.len() - overlap_size;
-                    transcription_buffer.drain(0..samples_to_remove);
-                }
-            }
-        }
-        
-        // Emit audio chunk periodically with reduced logging
-        let now = Instant::now();
-        if now.duration_since(last_emit) > Duration::from_millis(100) {
-            let pcm16_data: Vec<i16> = processed_audio.iter()
-                .map(|&sample| (sample * 32767.0).clamp(-32768.0, 32767.0) as i16)
-                .collect();
-            
-            let audio_bytes: Vec<u8> = pcm16_data.iter()
-                .flat_map(|&sample| sample.to_le_bytes())
-                .collect();
-            
-            let level = calculate_audio_level(&processed_audio);
-            
-            let _emit_result = app_handle.emit("audio-chunk", serde_json::json!({
-                "deviceId": device_id,
-                "audioData": base64::prelude::BASE64_STANDARD.encode(&audio_bytes),
-                "sampleRate": device_info.sample_rate,
-                "channels": 1,
-                "level": level,
-                "timestamp": chrono::Utc::now().timestamp_millis(),
-                "duration": start_time.elapsed().as_secs(),
-                "totalSamples": total_samples
-            }));
-            
-            last_emit = now;
-        }
+        pipeline.push(audio_data, bits_per_sample, channels, format.get_samplespersec(), Instant::now(), &mut sink);
     }
     
     let _ = audio_client.stop_stream();
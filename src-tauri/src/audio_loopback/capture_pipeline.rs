@@ -0,0 +1,253 @@
+// src-tauri/src/audio_loopback/capture_pipeline.rs
+// Everything the capture loop does with audio once it has read it from the device: resample to
+// 16kHz mono, keep the rolling transcription buffer, decide when there's enough speech to
+// transcribe, and emit level/preview chunks. Each chunk is checked for speech: while someone is
+// talking the buffer is transcribed on a short interval, an utterance is flushed as soon as the
+// speaker pauses, and silence between utterances is dropped instead of sent to Whisper. The device loop only reads bytes and hands them in,
+// so a synthetic source can drive the same pipeline in tests. Time is passed in rather than read,
+// which keeps the transcription cadence deterministic for a given input.
+use crate::audio_loopback::audio_processor::{calculate_audio_level, process_audio_chunk};
+use crate::audio_loopback::latency::SegmentTiming;
+use std::time::{Duration, Instant};
+
+const WHISPER_RATE: u32 = 16000;
+const BUFFER_DURATION: f32 = 4.0; // Python: BUFFER_DURATION = 4.0
+const MIN_AUDIO_LENGTH: f32 = 1.5; // Python: MIN_AUDIO_LENGTH = 1.5
+const OVERLAP_DURATION: f32 = 1.0; // Python keeps 1.0 second between transcriptions
+const MIN_UTTERANCE: f32 = 0.4; // Shorter bursts of sound (clicks, coughs) aren't worth a Whisper call
+const PRE_ROLL: f32 = 0.3; // Silence kept ahead of speech so the first word isn't clipped
+// While someone is talking, transcribe often for low latency; once they pause, wait longer
+const ACTIVE_INTERVAL: Duration = Duration::from_millis(600);
+const IDLE_INTERVAL: Duration = Duration::from_millis(1500);
+const SPEECH_HOLD: Duration = Duration::from_millis(300); // Gap between words that still counts as talking
+const END_OF_UTTERANCE: Duration = Duration::from_millis(500); // Silence that ends an utterance
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where the pipeline's output goes: the app in production, a recorder in tests.
+pub trait CaptureSink: Send {
+    /// Stereo PCM16 at `sample_rate`, which is what `process_audio_for_transcription` expects.
+    /// `timing` has the segment's capture and hand-off times for latency tracing.
+    fn transcribe(&mut self, pcm16_stereo: Vec<u8>, sample_rate: u32, timing: SegmentTiming);
+    /// Mono PCM16 preview of the latest chunk for meters and waveforms.
+    fn audio_chunk(&mut self, pcm16_mono: Vec<u8>, level: f32, total_samples: u64);
+}
+
+pub struct CapturePipeline {
+    transcription_buffer: Vec<f32>,
+    buffer_size: usize,
+    min_audio_samples: usize,
+    min_utterance_samples: usize,
+    pre_roll_samples: usize,
+    vad_rms_threshold: f32,
+    min_interval: Duration, // Floor on the transcription interval, from the power policy
+    last_transcription: Instant,
+    last_emit: Instant,
+    last_speech: Option<Instant>,
+    // Speech in the buffer that hasn't been transcribed yet, and whether this utterance has been
+    // partly transcribed already (its tail is then worth sending however short it is)
+    untranscribed_speech_samples: usize,
+    utterance_transcribed: bool,
+    total_samples: u64,
+}
+
+fn to_pcm16(samples: &[f32]) -> Vec<i16> {
+    samples
+        .iter()
+        .map(|&sample| (sample * 32767.0).clamp(-32768.0, 32767.0) as i16)
+        .collect()
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|&x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+impl CapturePipeline {
+    pub fn new(vad_rms_threshold: f32, now: Instant) -> Self {
+        Self {
+            transcription_buffer: Vec::new(),
+            // Buffer sizes are at 16kHz (Whisper rate), not device rate
+            buffer_size: (WHISPER_RATE as f32 * BUFFER_DURATION) as usize,
+            min_audio_samples: (WHISPER_RATE as f32 * MIN_AUDIO_LENGTH) as usize,
+            min_utterance_samples: (WHISPER_RATE as f32 * MIN_UTTERANCE) as usize,
+            pre_roll_samples: (WHISPER_RATE as f32 * PRE_ROLL) as usize,
+            vad_rms_threshold,
+            min_interval: Duration::ZERO,
+            last_transcription: now,
+            last_emit: now,
+            last_speech: None,
+            untranscribed_speech_samples: 0,
+            utterance_transcribed: false,
+            total_samples: 0,
+        }
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.total_samples
+    }
+
+    /// Transcribe ongoing speech at most this often; the end of an utterance is still flushed
+    /// as soon as the speaker pauses.
+    pub fn set_min_interval(&mut self, interval: Duration) {
+        self.min_interval = interval;
+    }
+
+    /// Feed one chunk as read from the device.
+    pub fn push(
+        &mut self,
+        audio_data: &[u8],
+        bits_per_sample: u16,
+        channels: u16,
+        sample_rate: u32,
+        now: Instant,
+        sink: &mut dyn CaptureSink,
+    ) {
+        // Python always outputs at 16kHz for Whisper
+        let processed_audio = process_audio_chunk(audio_data, bits_per_sample, channels, sample_rate, WHISPER_RATE);
+
+        self.total_samples += processed_audio.len() as u64;
+        crate::audio_loopback::quality_metrics::record_chunk(&processed_audio);
+        self.transcription_buffer.extend_from_slice(&processed_audio);
+
+        // Python checks RMS > 100 for int16, which is ~0.00305 for float32
+        if rms(&processed_audio) > self.vad_rms_threshold {
+            self.last_speech = Some(now);
+            self.untranscribed_speech_samples += processed_audio.len();
+        } else if self.untranscribed_speech_samples == 0 && !self.utterance_transcribed {
+            // Between utterances only a short lead-in is worth keeping
+            let excess = self.transcription_buffer.len().saturating_sub(self.pre_roll_samples);
+            self.transcription_buffer.drain(0..excess);
+        }
+
+        // Trim buffer
+        if self.transcription_buffer.len() > self.buffer_size * 2 {
+            let excess = self.transcription_buffer.len() - self.buffer_size;
+            self.transcription_buffer.drain(0..excess);
+        }
+
+        self.transcribe_if_due(now, sink);
+
+        if now.duration_since(self.last_emit) > EMIT_INTERVAL {
+            let audio_bytes: Vec<u8> = to_pcm16(&processed_audio)
+                .iter()
+                .flat_map(|&sample| sample.to_le_bytes())
+                .collect();
+            sink.audio_chunk(audio_bytes, calculate_audio_level(&processed_audio), self.total_samples);
+            self.last_emit = now;
+        }
+    }
+
+    /// Call when the device has delivered nothing for a while; loopback devices go quiet when
+    /// nothing is playing, and the end of the last utterance still has to be flushed.
+    pub fn idle(&mut self, now: Instant, sink: &mut dyn CaptureSink) {
+        self.transcribe_if_due(now, sink);
+    }
+
+    fn transcribe_if_due(&mut self, now: Instant, sink: &mut dyn CaptureSink) {
+        let silence = self.last_speech.map(|last| now.duration_since(last));
+        let utterance_ended = silence.is_some_and(|silence| silence >= END_OF_UTTERANCE);
+
+        if utterance_ended && (self.untranscribed_speech_samples > 0 || self.utterance_transcribed) {
+            let worth_sending = self.untranscribed_speech_samples > 0
+                && (self.utterance_transcribed || self.untranscribed_speech_samples >= self.min_utterance_samples);
+            if worth_sending {
+                self.send(now, sink);
+            }
+            // The utterance is done; nothing before this point needs to be heard again
+            self.transcription_buffer.clear();
+            self.untranscribed_speech_samples = 0;
+            self.utterance_transcribed = false;
+            return;
+        }
+
+        let interval = match silence {
+            Some(silence) if silence <= SPEECH_HOLD => ACTIVE_INTERVAL,
+            _ => IDLE_INTERVAL,
+        }
+        .max(self.min_interval);
+        if self.untranscribed_speech_samples > 0
+            && self.transcription_buffer.len() >= self.min_audio_samples
+            && now.duration_since(self.last_transcription) > interval
+        {
+            self.send(now, sink);
+            self.utterance_transcribed = true;
+
+            // Keep overlap
+            let overlap_size = (WHISPER_RATE as f32 * OVERLAP_DURATION) as usize;
+            if self.transcription_buffer.len() > overlap_size {
+                let samples_to_remove = self.transcription_buffer.len() - overlap_size;
+                self.transcription_buffer.drain(0..samples_to_remove);
+            }
+        }
+    }
+
+    fn send(&mut self, now: Instant, sink: &mut dyn CaptureSink) {
+        // `now` is when the chunk that made this segment due was read; the rest is wall-clock time
+        let mut timing = SegmentTiming::new(now, Instant::now());
+        // The transcription function expects stereo input (it will convert back to mono),
+        // so duplicate each mono sample into both channels
+        let mut stereo_pcm16_bytes = Vec::with_capacity(self.transcription_buffer.len() * 4);
+        for sample in to_pcm16(&self.transcription_buffer) {
+            let bytes = sample.to_le_bytes();
+            stereo_pcm16_bytes.extend_from_slice(&bytes); // Left channel
+            stereo_pcm16_bytes.extend_from_slice(&bytes); // Right channel (duplicate)
+        }
+        // We're passing 16kHz since we already resampled
+        timing.emitted_at = Instant::now();
+        sink.transcribe(stereo_pcm16_bytes, WHISPER_RATE, timing);
+        self.last_transcription = now;
+        self.untranscribed_speech_samples = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::synthetic_audio::{RecordingSink, SyntheticSource};
+
+    #[test]
+    fn test_tone_is_sent_for_transcription_and_silence_is_not() {
+        let mut sink = RecordingSink::default();
+        // 48kHz stereo PCM16 in 10ms device chunks, like a typical shared-mode WASAPI stream
+        let mut source = SyntheticSource::new(48000, 2, 10);
+        source.silence(3.0).run(0.0025, &mut sink);
+        assert!(sink.transcriptions.is_empty());
+
+        let mut sink = RecordingSink::default();
+        let mut source = SyntheticSource::new(48000, 2, 10);
+        source.tone(440.0, 0.5, 3.0).run(0.0025, &mut sink);
+        // Continuous speech is transcribed at the short interval: at 1.5s, 2.11s and 2.72s
+        assert_eq!(sink.transcriptions.len(), 3);
+        // The first request goes out once 1.5s is buffered, as 16kHz stereo PCM16
+        let (first, rate) = &sink.transcriptions[0];
+        assert_eq!(*rate, 16000);
+        assert_eq!(first.len(), 24000 * 4);
+        assert!(!sink.chunks.is_empty());
+
+        // On battery the power policy stretches the interval: one request, at 2.01s
+        let mut sink = RecordingSink::default();
+        let mut source = SyntheticSource::new(48000, 2, 10);
+        source.tone(440.0, 0.5, 3.0).run_with_min_interval(0.0025, Duration::from_secs(2), &mut sink);
+        assert_eq!(sink.transcriptions.len(), 1);
+    }
+
+    #[test]
+    fn test_end_of_utterance_flushes_before_the_minimum_length() {
+        let mut sink = RecordingSink::default();
+        let mut source = SyntheticSource::new(48000, 2, 10);
+        source.silence(0.5).tone(440.0, 0.5, 1.0).silence(1.0).run(0.0025, &mut sink);
+
+        // 0.3s lead-in, 1s of speech and the 0.5s of silence that ended it, sent once
+        assert_eq!(sink.transcriptions.len(), 1);
+        assert_eq!(sink.transcriptions[0].0.len(), (4800 + 16000 + 8000) * 4);
+
+        // A click is too short to transcribe, even once the device goes quiet
+        let mut sink = RecordingSink::default();
+        let mut source = SyntheticSource::new(48000, 2, 10);
+        let mut pipeline = source.tone(440.0, 0.5, 0.1).run(0.0025, &mut sink);
+        pipeline.idle(Instant::now() + Duration::from_secs(10), &mut sink);
+        assert!(sink.transcriptions.is_empty());
+    }
+}
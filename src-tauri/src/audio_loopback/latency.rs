@@ -0,0 +1,241 @@
+// src-tauri/src/audio_loopback/latency.rs
+// Per-segment latency of the loopback caption pipeline, from the device read that completed a
+// segment to the transcription event reaching the frontend. Each transcribed segment carries a
+// SegmentTiming through the pipeline; the finished timings are kept in a rolling window, and a
+// warning is logged when a stage's recent p95 goes over its threshold (once, until it recovers).
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Instant;
+
+const MAX_TRACKED_SEGMENTS: usize = 200;
+// Recent segments a regression is judged on, so one slow segment doesn't warn on its own
+const REGRESSION_WINDOW: usize = 20;
+const MIN_SEGMENTS_FOR_WARNING: usize = 5;
+const RECENT_SEGMENTS_IN_REPORT: usize = 20;
+
+// (stage, p95 threshold in ms). Whisper gets the most room: it's the inference itself.
+const STAGES: [(&str, f64); 6] = [
+    ("vad", 50.0),          // Device read -> resample and speech decision
+    ("chunk_emit", 20.0),   // Decision -> buffer handed off for transcription
+    ("queue", 250.0),       // Hand-off -> Whisper starts (task spawn, settings, preprocessing)
+    ("whisper", 2500.0),    // Inference
+    ("event_emit", 50.0),   // Whisper result -> filtered and emitted to the frontend
+    ("total", 3000.0),
+];
+
+/// Timestamps of one segment on its way through the pipeline. Later stages stay None when the
+/// segment was dropped before reaching them (too quiet, filtered out, failed).
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentTiming {
+    pub captured_at: Instant,
+    pub vad_at: Instant,
+    pub emitted_at: Instant,
+    pub whisper_started_at: Option<Instant>,
+    pub whisper_finished_at: Option<Instant>,
+    pub event_emitted_at: Option<Instant>,
+}
+
+impl SegmentTiming {
+    pub fn new(captured_at: Instant, vad_at: Instant) -> Self {
+        Self {
+            captured_at,
+            vad_at,
+            emitted_at: vad_at,
+            whisper_started_at: None,
+            whisper_finished_at: None,
+            event_emitted_at: None,
+        }
+    }
+
+    fn latency(&self) -> SegmentLatency {
+        let ms = |from: Instant, to: Option<Instant>| to.map(|to| to.saturating_duration_since(from).as_secs_f64() * 1000.0);
+        SegmentLatency {
+            vad_ms: ms(self.captured_at, Some(self.vad_at)),
+            chunk_emit_ms: ms(self.vad_at, Some(self.emitted_at)),
+            queue_ms: self.whisper_started_at.and_then(|start| ms(self.emitted_at, Some(start))),
+            whisper_ms: self.whisper_started_at.and_then(|start| ms(start, self.whisper_finished_at)),
+            event_emit_ms: self.whisper_finished_at.and_then(|end| ms(end, self.event_emitted_at)),
+            total_ms: ms(self.captured_at, self.event_emitted_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentLatency {
+    pub vad_ms: Option<f64>,
+    pub chunk_emit_ms: Option<f64>,
+    pub queue_ms: Option<f64>,
+    pub whisper_ms: Option<f64>,
+    pub event_emit_ms: Option<f64>,
+    pub total_ms: Option<f64>,
+}
+
+impl SegmentLatency {
+    fn stage(&self, stage: &str) -> Option<f64> {
+        match stage {
+            "vad" => self.vad_ms,
+            "chunk_emit" => self.chunk_emit_ms,
+            "queue" => self.queue_ms,
+            "whisper" => self.whisper_ms,
+            "event_emit" => self.event_emit_ms,
+            "total" => self.total_ms,
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageLatency {
+    pub stage: String,
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub threshold_ms: f64,
+    pub regressed: bool, // Recent p95 is over the threshold
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineLatencyReport {
+    pub segments: usize,
+    pub stages: Vec<StageLatency>,
+    pub recent: Vec<SegmentLatency>, // Newest last
+    pub warnings: Vec<String>,
+}
+
+#[derive(Default)]
+struct LatencyLog {
+    segments: Vec<SegmentLatency>,
+    regressed: Vec<&'static str>, // Stages already warned about
+}
+
+lazy_static::lazy_static! {
+    static ref LATENCY_LOG: Mutex<LatencyLog> = Mutex::new(LatencyLog::default());
+}
+
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * fraction).round() as usize;
+    sorted[index.min(sorted.len() - 1)]
+}
+
+fn stage_values(segments: &[SegmentLatency], stage: &str) -> Vec<f64> {
+    let mut values: Vec<f64> = segments.iter().filter_map(|segment| segment.stage(stage)).collect();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    values
+}
+
+// Stages whose p95 over the newest segments is over their threshold
+fn regressed_stages(segments: &[SegmentLatency]) -> Vec<(&'static str, f64, f64)> {
+    let recent = &segments[segments.len().saturating_sub(REGRESSION_WINDOW)..];
+    STAGES
+        .iter()
+        .filter_map(|&(stage, threshold)| {
+            let values = stage_values(recent, stage);
+            if values.len() < MIN_SEGMENTS_FOR_WARNING {
+                return None;
+            }
+            let p95 = percentile(&values, 0.95);
+            (p95 > threshold).then_some((stage, p95, threshold))
+        })
+        .collect()
+}
+
+/// Record a segment once it's done with the pipeline, however far it got.
+pub fn record_segment(timing: &SegmentTiming) {
+    let Ok(mut log) = LATENCY_LOG.lock() else {
+        return;
+    };
+    log.segments.push(timing.latency());
+    if log.segments.len() > MAX_TRACKED_SEGMENTS {
+        log.segments.remove(0);
+    }
+
+    let regressed = regressed_stages(&log.segments);
+    for &(stage, p95, threshold) in &regressed {
+        if !log.regressed.contains(&stage) {
+            println!("⚠️ Caption latency: {} stage p95 is {:.0}ms (threshold {:.0}ms)", stage, p95, threshold);
+        }
+    }
+    for stage in &log.regressed {
+        if !regressed.iter().any(|(regressed, _, _)| regressed == stage) {
+            println!("✅ Caption latency: {} stage is back under its threshold", stage);
+        }
+    }
+    log.regressed = regressed.into_iter().map(|(stage, _, _)| stage).collect();
+}
+
+fn build_report(segments: &[SegmentLatency]) -> PipelineLatencyReport {
+    let regressed = regressed_stages(segments);
+    let stages = STAGES
+        .iter()
+        .map(|&(stage, threshold)| {
+            let values = stage_values(segments, stage);
+            StageLatency {
+                stage: stage.to_string(),
+                samples: values.len(),
+                p50_ms: percentile(&values, 0.5),
+                p95_ms: percentile(&values, 0.95),
+                max_ms: values.last().copied().unwrap_or(0.0),
+                threshold_ms: threshold,
+                regressed: regressed.iter().any(|(regressed, _, _)| *regressed == stage),
+            }
+        })
+        .collect();
+    PipelineLatencyReport {
+        segments: segments.len(),
+        stages,
+        recent: segments[segments.len().saturating_sub(RECENT_SEGMENTS_IN_REPORT)..].to_vec(),
+        warnings: regressed
+            .into_iter()
+            .map(|(stage, p95, threshold)| format!("{} p95 is {:.0}ms, over the {:.0}ms threshold", stage, p95, threshold))
+            .collect(),
+    }
+}
+
+#[tauri::command]
+pub fn get_pipeline_latency_report() -> Result<PipelineLatencyReport, String> {
+    let log = LATENCY_LOG.lock().map_err(|e| e.to_string())?;
+    Ok(build_report(&log.segments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stage_latencies_and_regressions() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut timing = SegmentTiming::new(start, at(10));
+        timing.emitted_at = at(12);
+        timing.whisper_started_at = Some(at(100));
+        timing.whisper_finished_at = Some(at(4100));
+        timing.event_emitted_at = Some(at(4110));
+
+        let latency = timing.latency();
+        assert_eq!(latency.vad_ms, Some(10.0));
+        assert_eq!(latency.queue_ms, Some(88.0));
+        assert_eq!(latency.whisper_ms, Some(4000.0));
+        assert_eq!(latency.total_ms, Some(4110.0));
+
+        // A segment dropped before Whisper only has the early stages
+        let dropped = SegmentTiming::new(start, at(5)).latency();
+        assert_eq!(dropped.whisper_ms, None);
+        assert_eq!(dropped.total_ms, None);
+
+        let segments = vec![latency; MIN_SEGMENTS_FOR_WARNING];
+        let report = build_report(&segments);
+        let whisper = report.stages.iter().find(|stage| stage.stage == "whisper").unwrap();
+        assert!(whisper.regressed);
+        assert_eq!(whisper.p50_ms, 4000.0);
+        assert!(!report.stages.iter().find(|stage| stage.stage == "vad").unwrap().regressed);
+        assert_eq!(report.warnings.len(), 2); // whisper and total
+    }
+}
@@ -0,0 +1,216 @@
+// src-tauri/src/audio_loopback/quality_metrics.rs
+// Running audio/transcription quality counters for the current capture, turned into a
+// per-session quality report when the conversation ends. The counters belong to the session the
+// capture records for and are never reported under another one.
+use crate::data::types::{ConfidenceDistribution, SessionQualityReport};
+use std::sync::{Arc, Mutex};
+
+// Enough per-chunk levels for hours of audio at ~100ms chunks without unbounded growth
+const MAX_TRACKED_CHUNKS: usize = 50_000;
+const MAX_TRACKED_TRANSCRIPTIONS: usize = 20_000;
+const CLIPPING_LEVEL: f32 = 0.99;
+
+#[derive(Debug, Clone, Default)]
+pub struct CaptureQualityMetrics {
+    pub session_id: Option<String>,
+    pub chunk_rms: Vec<f32>,
+    pub audio_chunks: u64,
+    pub clipping_events: u64,
+    pub dropped_chunks: u64,
+    pub filtered_transcriptions: u64,
+    pub transcriptions: u64, // All attempts; the confidences below stop growing at the cap
+    pub transcription_confidences: Vec<f64>,
+}
+
+lazy_static::lazy_static! {
+    static ref QUALITY_METRICS: Arc<Mutex<CaptureQualityMetrics>> = Arc::new(Mutex::new(CaptureQualityMetrics::default()));
+}
+
+pub fn reset_quality_metrics(session_id: &str) {
+    if let Ok(mut metrics) = QUALITY_METRICS.lock() {
+        *metrics = CaptureQualityMetrics { session_id: Some(session_id.to_string()), ..Default::default() };
+    }
+}
+
+pub fn record_chunk(samples: &[f32]) {
+    if samples.is_empty() {
+        return;
+    }
+
+    let rms = (samples.iter().map(|&x| x * x).sum::<f32>() / samples.len() as f32).sqrt();
+
+    // Count runs of clipped samples rather than every clipped sample
+    let mut clipping_events = 0;
+    let mut in_clip = false;
+    for &sample in samples {
+        let clipped = sample.abs() >= CLIPPING_LEVEL;
+        if clipped && !in_clip {
+            clipping_events += 1;
+        }
+        in_clip = clipped;
+    }
+
+    if let Ok(mut metrics) = QUALITY_METRICS.lock() {
+        metrics.audio_chunks += 1;
+        metrics.clipping_events += clipping_events;
+        if metrics.chunk_rms.len() < MAX_TRACKED_CHUNKS {
+            metrics.chunk_rms.push(rms);
+        }
+    }
+}
+
+pub fn record_dropped_chunk() {
+    if let Ok(mut metrics) = QUALITY_METRICS.lock() {
+        metrics.dropped_chunks += 1;
+    }
+}
+
+pub fn record_transcription(confidence: f64, accepted: bool) {
+    if let Ok(mut metrics) = QUALITY_METRICS.lock() {
+        metrics.transcriptions += 1;
+        if metrics.transcription_confidences.len() < MAX_TRACKED_TRANSCRIPTIONS {
+            metrics.transcription_confidences.push(confidence);
+        }
+        if !accepted {
+            metrics.filtered_transcriptions += 1;
+        }
+    }
+}
+
+/// The capture metrics, if the latest capture recorded for `session_id`.
+pub fn snapshot_for(session_id: &str) -> Option<CaptureQualityMetrics> {
+    let metrics = QUALITY_METRICS.lock().ok()?;
+    (metrics.session_id.as_deref() == Some(session_id)).then(|| metrics.clone())
+}
+
+/// Average SNR of speech chunks against the noise floor (10th percentile of chunk levels).
+pub fn average_snr_db(chunk_rms: &[f32]) -> Option<f64> {
+    if chunk_rms.len() < 10 {
+        return None;
+    }
+
+    let mut sorted: Vec<f32> = chunk_rms.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let noise_floor = sorted[sorted.len() / 10].max(1e-5);
+
+    // Chunks well above the floor are treated as speech
+    let speech: Vec<f64> = chunk_rms
+        .iter()
+        .filter(|&&rms| rms > noise_floor * 2.0)
+        .map(|&rms| 20.0 * (rms / noise_floor).log10() as f64)
+        .collect();
+    if speech.is_empty() {
+        return Some(0.0);
+    }
+    Some(speech.iter().sum::<f64>() / speech.len() as f64)
+}
+
+fn confidence_distribution(confidences: &[f64]) -> ConfidenceDistribution {
+    if confidences.is_empty() {
+        return ConfidenceDistribution::default();
+    }
+
+    ConfidenceDistribution {
+        count: confidences.len() as u32,
+        mean: Some(confidences.iter().sum::<f64>() / confidences.len() as f64),
+        low: confidences.iter().filter(|&&c| c < 0.5).count() as u32,
+        medium: confidences.iter().filter(|&&c| (0.5..0.8).contains(&c)).count() as u32,
+        high: confidences.iter().filter(|&&c| c >= 0.8).count() as u32,
+    }
+}
+
+/// Combine capture metrics with the confidences stored on the session's messages.
+pub fn build_report(
+    session_id: &str,
+    metrics: &CaptureQualityMetrics,
+    message_confidences: &[f64],
+    is_final: bool,
+) -> SessionQualityReport {
+    // Stored message confidences describe the saved transcript; fall back to live results
+    let confidences = if message_confidences.is_empty() {
+        &metrics.transcription_confidences[..]
+    } else {
+        message_confidences
+    };
+    let confidence = confidence_distribution(confidences);
+    let average_snr_db = average_snr_db(&metrics.chunk_rms);
+
+    let mut issues = Vec::new();
+    if let Some(snr) = average_snr_db {
+        if snr < 10.0 {
+            issues.push(format!("Low signal-to-noise ratio ({:.1} dB): background noise is competing with speech", snr));
+        }
+    }
+    if metrics.audio_chunks > 0 && metrics.clipping_events * 100 > metrics.audio_chunks {
+        issues.push(format!("{} clipping events: input level is too high and distorting speech", metrics.clipping_events));
+    }
+    let total_chunks = metrics.audio_chunks + metrics.dropped_chunks;
+    if total_chunks > 0 && metrics.dropped_chunks * 20 > total_chunks {
+        issues.push(format!("{} audio chunks were dropped: the device may be busy or the system overloaded", metrics.dropped_chunks));
+    }
+    if let Some(mean) = confidence.mean {
+        if mean < 0.6 {
+            issues.push(format!("Low average transcription confidence ({:.2})", mean));
+        }
+    }
+    let attempts = metrics.transcriptions;
+    if attempts > 0 && metrics.filtered_transcriptions * 3 > attempts {
+        issues.push(format!(
+            "{} of {} transcriptions were discarded as low quality",
+            metrics.filtered_transcriptions, attempts
+        ));
+    }
+
+    SessionQualityReport {
+        session_id: session_id.to_string(),
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        audio_chunks: metrics.audio_chunks,
+        average_snr_db,
+        clipping_events: metrics.clipping_events,
+        dropped_chunks: metrics.dropped_chunks,
+        filtered_transcriptions: metrics.filtered_transcriptions,
+        confidence,
+        issues,
+        is_final,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snr_against_the_noise_floor() {
+        assert_eq!(average_snr_db(&[0.1; 9]), None);
+        // Ten quiet chunks set the floor; speech at 10x the floor is 20 dB above it
+        let mut levels = vec![0.01; 10];
+        levels.extend([0.1; 10]);
+        assert!((average_snr_db(&levels).unwrap() - 20.0).abs() < 1e-3);
+        assert_eq!(average_snr_db(&[0.01; 20]), Some(0.0));
+    }
+
+    #[test]
+    fn test_report_buckets_confidences_and_flags_issues() {
+        let metrics = CaptureQualityMetrics {
+            session_id: Some("s1".to_string()),
+            audio_chunks: 100,
+            clipping_events: 2,
+            dropped_chunks: 10,
+            filtered_transcriptions: 2,
+            transcriptions: 5,
+            transcription_confidences: vec![0.2, 0.3, 0.9, 0.9, 0.9],
+            ..Default::default()
+        };
+
+        // Live confidences stand in until messages with confidences are stored
+        let live = build_report("s1", &metrics, &[], false);
+        assert_eq!((live.confidence.count, live.confidence.low, live.confidence.medium, live.confidence.high), (5, 2, 0, 3));
+        assert!((live.confidence.mean.unwrap() - 0.64).abs() < 1e-9);
+        assert_eq!(live.issues.len(), 3, "{:?}", live.issues); // Clipping, dropped chunks, filtered transcriptions
+
+        let stored = build_report("s1", &metrics, &[0.5, 0.55], true);
+        assert_eq!((stored.confidence.count, stored.confidence.medium), (2, 2));
+        assert!(stored.issues.iter().any(|issue| issue.starts_with("Low average transcription confidence")));
+        assert!(stored.is_final);
+    }
+}
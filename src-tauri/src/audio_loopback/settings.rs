@@ -63,6 +63,13 @@ pub async fn load_audio_settings() -> Result<Option<AudioDeviceSettings>, String
     Ok(Some(settings))
 }
 
+/// One boolean general setting, read synchronously for callers outside the async runtime.
+pub fn general_setting_bool(key: &str) -> Option<bool> {
+    let json = fs::read_to_string(get_general_settings_path().ok()?).ok()?;
+    let settings: HashMap<String, serde_json::Value> = serde_json::from_str(&json).ok()?;
+    settings.get(key).and_then(|value| value.as_bool())
+}
+
 #[tauri::command]
 pub async fn save_general_settings(settings: HashMap<String, serde_json::Value>) -> Result<(), String> {
     let settings_path = get_general_settings_path()
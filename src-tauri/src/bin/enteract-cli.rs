@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(enteract_lib::cli::main())
+}
@@ -0,0 +1,309 @@
+// src-tauri/src/captions.rs
+// Caption feed for the overlay window. Each transcript segment keeps a stable id while its interim
+// text is corrected in place, words carry timings so the overlay can reveal them smoothly, and
+// profanity masking and line layout happen here so every window renders the same frame.
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+// Older segments are only kept around for the line layout
+const MAX_SEGMENTS: usize = 12;
+const BUILT_IN_PROFANITY: &[&str] = &[
+    "fuck", "fucking", "fucked", "shit", "shitty", "bitch", "bastard", "asshole", "dick", "cunt", "damn", "crap", "piss",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfanityMask {
+    Off,
+    Partial, // f***
+    Full,    // ****
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionSettings {
+    pub max_lines: usize,
+    pub max_chars_per_line: usize,
+    pub profanity_mask: ProfanityMask,
+    pub masked_words: Vec<String>, // Added to the built-in list
+    pub show_interim: bool,
+}
+
+impl Default for CaptionSettings {
+    fn default() -> Self {
+        Self {
+            max_lines: 2,
+            max_chars_per_line: 42,
+            profanity_mask: ProfanityMask::Partial,
+            masked_words: Vec::new(),
+            show_interim: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CaptionWord {
+    pub text: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionSegment {
+    pub id: String,
+    pub source: String, // microphone | loopback
+    pub words: Vec<CaptionWord>,
+    pub is_final: bool,
+    pub revision: u32, // Bumped on every in-place correction
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionFrame {
+    pub segments: Vec<CaptionSegment>,
+    pub lines: Vec<String>, // Laid out to the configured width, newest last
+    pub updated_segment_id: Option<String>,
+}
+
+#[derive(Default)]
+struct CaptionFeed {
+    next_id: u64,
+    segments: VecDeque<CaptionSegment>,
+}
+
+lazy_static::lazy_static! {
+    static ref CAPTION_FEED: Mutex<CaptionFeed> = Mutex::new(CaptionFeed::default());
+    static ref CAPTION_SETTINGS: Mutex<Option<CaptionSettings>> = Mutex::new(None);
+}
+
+fn get_caption_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("caption_settings.json")
+}
+
+// Cached: captions are pushed several times a second
+fn current_settings() -> CaptionSettings {
+    let mut cached = CAPTION_SETTINGS.lock().unwrap();
+    cached
+        .get_or_insert_with(|| {
+            get_caption_settings_path()
+                .ok()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+/// Spread the words over the segment's span, weighted by length, when the STT gives no word times.
+pub fn time_words(text: &str, start_ms: i64, end_ms: i64) -> Vec<CaptionWord> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let total_weight: usize = words.iter().map(|w| w.chars().count() + 1).sum();
+    let span = (end_ms - start_ms).max(0);
+
+    let mut elapsed = 0;
+    words
+        .into_iter()
+        .map(|word| {
+            let weight = word.chars().count() + 1;
+            let word_start = start_ms + span * elapsed as i64 / total_weight.max(1) as i64;
+            elapsed += weight;
+            let word_end = start_ms + span * elapsed as i64 / total_weight.max(1) as i64;
+            CaptionWord { text: word.to_string(), start_ms: word_start, end_ms: word_end }
+        })
+        .collect()
+}
+
+pub fn mask_word(word: &str, settings: &CaptionSettings) -> String {
+    if settings.profanity_mask == ProfanityMask::Off {
+        return word.to_string();
+    }
+    let core: String = word.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    let listed = BUILT_IN_PROFANITY.contains(&core.as_str())
+        || settings.masked_words.iter().any(|w| w.eq_ignore_ascii_case(&core));
+    if !listed {
+        return word.to_string();
+    }
+
+    // Keep surrounding punctuation so "damn!" still ends a sentence
+    let mut seen_letter = false;
+    word.chars()
+        .map(|c| {
+            if !c.is_alphanumeric() {
+                return c;
+            }
+            let keep = settings.profanity_mask == ProfanityMask::Partial && !seen_letter;
+            seen_letter = true;
+            if keep { c } else { '*' }
+        })
+        .collect()
+}
+
+/// Greedy wrap of the segments' words, keeping only the last `max_lines` lines.
+pub fn layout_lines(segments: &[CaptionSegment], settings: &CaptionSettings) -> Vec<String> {
+    let width = settings.max_chars_per_line.max(10);
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in segments.iter().flat_map(|s| s.words.iter()) {
+        let length = current.chars().count() + word.text.chars().count() + usize::from(!current.is_empty());
+        if length > width && !current.is_empty() {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(&word.text);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    let skip = lines.len().saturating_sub(settings.max_lines.max(1));
+    lines.split_off(skip)
+}
+
+fn build_frame(feed: &CaptionFeed, settings: &CaptionSettings, updated: Option<String>) -> CaptionFrame {
+    let segments: Vec<CaptionSegment> = feed.segments
+        .iter()
+        .filter(|s| s.is_final || settings.show_interim)
+        .cloned()
+        .collect();
+    CaptionFrame {
+        lines: layout_lines(&segments, settings),
+        segments,
+        updated_segment_id: updated,
+    }
+}
+
+/// Add or correct a caption segment. Interim text replaces the source's open segment in place;
+/// final text closes it. Returns the segment id.
+pub fn push_caption(app_handle: &AppHandle, source: &str, text: &str, is_final: bool, start_ms: i64, end_ms: i64) -> String {
+    let settings = current_settings();
+    let words: Vec<CaptionWord> = time_words(text, start_ms, end_ms)
+        .into_iter()
+        .map(|word| CaptionWord { text: mask_word(&word.text, &settings), ..word })
+        .collect();
+    // Announced as shown, masking included
+    let spoken = is_final.then(|| words.iter().map(|word| word.text.as_str()).collect::<Vec<_>>().join(" "));
+
+    let mut feed = CAPTION_FEED.lock().unwrap();
+    let open = feed.segments.iter().position(|s| s.source == source && !s.is_final);
+    let id = match open {
+        Some(index) => {
+            let segment = &mut feed.segments[index];
+            segment.words = words;
+            segment.is_final = is_final;
+            segment.revision += 1;
+            segment.id.clone()
+        }
+        None => {
+            feed.next_id += 1;
+            let id = format!("{}-{}", source, feed.next_id);
+            feed.segments.push_back(CaptionSegment {
+                id: id.clone(),
+                source: source.to_string(),
+                words,
+                is_final,
+                revision: 0,
+            });
+            id
+        }
+    };
+    // Final segments never go back to being open, so drop the oldest ones first
+    while feed.segments.len() > MAX_SEGMENTS {
+        feed.segments.pop_front();
+    }
+
+    let frame = build_frame(&feed, &settings, Some(id.clone()));
+    drop(feed);
+    crate::event_throttle::emit_throttled(app_handle, "caption-frame", &frame);
+    if let Some(spoken) = spoken {
+        crate::accessibility::observe_caption(app_handle, source, &spoken);
+    }
+    id
+}
+
+#[tauri::command]
+pub async fn get_caption_frame() -> Result<CaptionFrame, String> {
+    let feed = CAPTION_FEED.lock().map_err(|e| e.to_string())?;
+    Ok(build_frame(&feed, &current_settings(), None))
+}
+
+/// For transcripts produced on the frontend (e.g. microphone interim results).
+#[tauri::command]
+pub async fn push_caption_text(
+    app_handle: AppHandle,
+    source: String,
+    text: String,
+    is_final: bool,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+) -> Result<String, String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let end_ms = end_ms.unwrap_or(now);
+    let start_ms = start_ms.unwrap_or(end_ms).min(end_ms);
+    Ok(push_caption(&app_handle, &source, &text, is_final, start_ms, end_ms))
+}
+
+#[tauri::command]
+pub async fn clear_captions(app_handle: AppHandle) -> Result<(), String> {
+    let mut feed = CAPTION_FEED.lock().map_err(|e| e.to_string())?;
+    feed.segments.clear();
+    let frame = build_frame(&feed, &current_settings(), None);
+    drop(feed);
+    crate::event_throttle::emit_critical(&app_handle, "caption-frame", &frame).map_err(|e| format!("Failed to emit caption frame: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_caption_settings() -> Result<CaptionSettings, String> {
+    Ok(current_settings())
+}
+
+#[tauri::command]
+pub async fn save_caption_settings(settings: CaptionSettings) -> Result<(), String> {
+    if !(1..=10).contains(&settings.max_lines) {
+        return Err("Captions can show between 1 and 10 lines".to_string());
+    }
+    if !(10..=200).contains(&settings.max_chars_per_line) {
+        return Err("Line width must be between 10 and 200 characters".to_string());
+    }
+
+    let path = get_caption_settings_path()
+        .map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+
+    *CAPTION_SETTINGS.lock().map_err(|e| e.to_string())? = Some(settings);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_words_covers_span() {
+        let words = time_words("hello big world", 1000, 2600);
+        assert_eq!(words.len(), 3);
+        assert_eq!(words[0].start_ms, 1000);
+        assert_eq!(words[2].end_ms, 2600);
+        assert!(words.windows(2).all(|pair| pair[0].end_ms == pair[1].start_ms));
+    }
+
+    #[test]
+    fn test_masking_and_layout() {
+        let settings = CaptionSettings { max_lines: 2, max_chars_per_line: 12, ..CaptionSettings::default() };
+        assert_eq!(mask_word("Damn!", &settings), "D***!");
+        assert_eq!(mask_word("hello", &settings), "hello");
+
+        let segment = CaptionSegment {
+            id: "loopback-1".to_string(),
+            source: "loopback".to_string(),
+            words: time_words("one two three four five six", 0, 600),
+            is_final: true,
+            revision: 0,
+        };
+        assert_eq!(layout_lines(&[segment], &settings), vec!["three four", "five six"]);
+    }
+}
@@ -0,0 +1,293 @@
+// src-tauri/src/cli.rs
+// Headless entry point behind the `cli` feature, built as the `enteract-cli` binary:
+//   enteract-cli ask "question" [--docs] [--model <name>]
+//   enteract-cli transcribe file.wav [--model tiny|base|small] [--language <code>]
+//   enteract-cli index ./docs [--no-wait]
+// It opens the same per-user data directory as the app, so documents indexed here show up in the
+// GUI and the other way round. Output goes to stdout; progress and errors go to stderr.
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::enhanced_rag_system::EnhancedRagSystem;
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+use crate::speech::WhisperModelConfig;
+
+const USAGE: &str = "Usage:
+  enteract-cli ask <question> [--docs] [--model <name>]
+  enteract-cli transcribe <file.wav> [--model tiny|base|small] [--language <code>]
+  enteract-cli index <directory> [--no-wait]";
+
+const INDEXED_EXTENSIONS: &[&str] = &["pdf", "md", "markdown", "txt"];
+const DOC_CONTEXT_CHUNKS: usize = 5;
+
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(raw: Vec<String>) -> Self {
+        let mut positional = Vec::new();
+        let mut flags = Vec::new();
+        let mut iter = raw.into_iter().peekable();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = iter.next_if(|next| !next.starts_with("--"));
+                    // Boolean flags must not swallow the positional argument that follows them
+                    match (name, value) {
+                        ("docs" | "no-wait", Some(value)) => {
+                            flags.push((name.to_string(), None));
+                            positional.push(value);
+                        }
+                        (_, value) => flags.push((name.to_string(), value)),
+                    }
+                }
+                None => positional.push(arg),
+            }
+        }
+        Self { positional, flags }
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.flags.iter().any(|(flag, _)| flag == name)
+    }
+
+    fn value(&self, name: &str) -> Option<String> {
+        self.flags.iter().find(|(flag, _)| flag == name).and_then(|(_, value)| value.clone())
+    }
+}
+
+/// Run the CLI with the process arguments and return the exit code.
+pub fn main() -> i32 {
+    let mut raw: Vec<String> = std::env::args().skip(1).collect();
+    if raw.is_empty() {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+    let command = raw.remove(0);
+    let args = Args::parse(raw);
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return 1;
+        }
+    };
+    let result = runtime.block_on(async {
+        match command.as_str() {
+            "ask" => ask(&args).await,
+            "transcribe" => transcribe(&args).await,
+            "index" => index(&args).await,
+            "help" | "--help" | "-h" => {
+                println!("{}", USAGE);
+                Ok(())
+            }
+            other => Err(format!("Unknown command '{}'\n{}", other, USAGE)),
+        }
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            1
+        }
+    }
+}
+
+fn open_documents() -> Result<EnhancedRagSystem, String> {
+    let data_dir = crate::data::paths::default_user_data_dir();
+    crate::data::paths::ensure_private_dir(&data_dir)
+        .map_err(|e| format!("Failed to create data directory {}: {}", data_dir.display(), e))?;
+    EnhancedRagSystem::open(&data_dir).map_err(|e| {
+        // Tantivy allows one index writer per directory
+        format!("Failed to open the document store in {} (is Enteract running?): {}", data_dir.display(), e)
+    })
+}
+
+async fn ask(args: &Args) -> Result<(), String> {
+    let question = args.positional.join(" ");
+    if question.trim().is_empty() {
+        return Err(format!("ask needs a question\n{}", USAGE));
+    }
+    let (default_model, system) = crate::ollama::agent_model_and_prompt("enteract")
+        .ok_or_else(|| "The enteract agent is not configured".to_string())?;
+    let model = args.value("model").unwrap_or_else(|| default_model.to_string());
+
+    let mut prompt = question.clone();
+    if args.has("docs") {
+        let documents = open_documents()?;
+        let chunks = documents
+            .search_documents(&question, Vec::new())
+            .await
+            .map_err(|e| format!("Document search failed: {}", e))?;
+        let context: Vec<String> = chunks
+            .iter()
+            .take(DOC_CONTEXT_CHUNKS)
+            .enumerate()
+            .map(|(i, chunk)| format!("[{}] {}", i + 1, chunk.content.trim()))
+            .collect();
+        if context.is_empty() {
+            eprintln!("No matching documents, answering without them");
+        } else {
+            prompt = format!("Use these excerpts from my documents where relevant:\n\n{}\n\nQuestion: {}", context.join("\n\n"), question);
+        }
+    }
+
+    let options = build_ollama_options("enteract", None, estimate_tokens(&prompt) + estimate_tokens(system));
+    let answer = crate::ollama::generate_completion(&model, prompt, Some(system.to_string()), Some(serde_json::Value::Object(options))).await?;
+    println!("{}", answer.trim());
+    Ok(())
+}
+
+/// Decode a WAV file into 16-bit PCM bytes plus its channel count and sample rate.
+fn read_wav_pcm16(path: &Path) -> Result<(Vec<u8>, u16, u32), String> {
+    let mut reader = hound::WavReader::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let samples: Vec<i16> = match spec.sample_format {
+        hound::SampleFormat::Int if spec.bits_per_sample <= 16 => reader
+            .samples::<i16>()
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?,
+        hound::SampleFormat::Int => {
+            let shift = spec.bits_per_sample - 16;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|s| (s >> shift) as i16))
+                .collect::<Result<_, _>>()
+                .map_err(|e| e.to_string())?
+        }
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .map(|s| s.map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?,
+    };
+    let bytes = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    Ok((bytes, spec.channels, spec.sample_rate))
+}
+
+async fn transcribe(args: &Args) -> Result<(), String> {
+    let path = args
+        .positional
+        .first()
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("transcribe needs a WAV file\n{}", USAGE))?;
+    let (pcm, channels, sample_rate) = read_wav_pcm16(&path)?;
+    // Whisper wants 16kHz mono
+    let samples = crate::audio_loopback::audio_processor::process_audio_chunk(&pcm, 16, channels, sample_rate, 16000);
+    if samples.is_empty() {
+        return Err(format!("{} contains no audio", path.display()));
+    }
+
+    let config = WhisperModelConfig {
+        modelSize: args.value("model").unwrap_or_else(|| "base".to_string()),
+        language: args.value("language"),
+        enableVad: false,
+        silenceThreshold: 0.01,
+        maxSegmentLength: 30,
+    };
+    eprintln!("Transcribing {:.1}s of audio with the '{}' model", samples.len() as f32 / 16000.0, config.modelSize);
+    let result = crate::speech::transcribe_samples_local(samples, config).await?;
+    println!("{}", result.text.trim());
+    Ok(())
+}
+
+fn collect_documents(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_documents(&path, found)?;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map_or(false, |e| INDEXED_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+async fn index(args: &Args) -> Result<(), String> {
+    let dir = args
+        .positional
+        .first()
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("index needs a directory\n{}", USAGE))?;
+    let dir = dir.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
+    let mut files = Vec::new();
+    collect_documents(&dir, &mut files)?;
+    files.sort();
+    if files.is_empty() {
+        return Err(format!("No {} files in {}", INDEXED_EXTENSIONS.join("/"), dir.display()));
+    }
+
+    let documents = open_documents()?;
+    let mut document_ids = Vec::new();
+    let mut failed = 0;
+    for path in &files {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let uploaded = match std::fs::read(path) {
+            Ok(content) => documents
+                .upload_document(file_name, content, crate::file_qa::file_type_for_path(path).to_string())
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match uploaded {
+            Ok(document) => {
+                if let Err(e) = documents.track_document_source(&document.id, &path.to_string_lossy()) {
+                    eprintln!("Failed to record source for {}: {}", path.display(), e);
+                }
+                println!("indexed\t{}\t{}", document.id, path.display());
+                document_ids.push(document.id);
+            }
+            Err(e) => {
+                eprintln!("failed\t{}\t{}", path.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    if !args.has("no-wait") {
+        // Embeddings are generated in the background; the process must stay up until they finish
+        eprintln!("Waiting for embeddings...");
+        loop {
+            let statuses = documents
+                .get_embedding_status_for_documents(&document_ids)
+                .map_err(|e| format!("Failed to check embedding status: {}", e))?;
+            let waiting = statuses.values().filter(|s| *s == "pending" || *s == "processing").count();
+            if waiting == 0 {
+                failed += statuses.values().filter(|s| *s == "failed").count();
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    eprintln!("Indexed {} of {} files", files.len() - failed.min(files.len()), files.len());
+    if failed > 0 {
+        return Err(format!("{} files failed to index", failed));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boolean_flags_do_not_swallow_positionals() {
+        let args = Args::parse(vec!["--docs".into(), "what".into(), "changed".into(), "--model".into(), "llama3".into()]);
+        assert!(args.has("docs"));
+        assert_eq!(args.positional, vec!["what", "changed"]);
+        assert_eq!(args.value("model").as_deref(), Some("llama3"));
+    }
+}
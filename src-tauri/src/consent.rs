@@ -0,0 +1,439 @@
+// src-tauri/src/consent.rs
+// Recording consent. Capture refuses to start for a conversation until consent has been confirmed
+// for that session, unless the consent policy allows recording without it: the policy can require
+// consent always, never, or by jurisdiction, where only places known to need just one party's
+// consent (the user's own) are let through and anything unknown still asks. Every grant,
+// withdrawal and recording start or stop is stored with the session for auditing. Every capture
+// names the conversation it records for; nothing falls back to an earlier one, and each source keeps
+// its own. While anything records, the indicator state goes out on the `recording-indicator` event
+// for the overlay.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use crate::data::conversation::ConversationStorage;
+use crate::data::types::ConsentEvent;
+
+const INDICATOR_EVENT: &str = "recording-indicator";
+
+// One-party consent jurisdictions (ISO 3166 codes, US states as "US-XX"). A convenience default,
+// not legal advice: anyone unsure should set the policy to always ask.
+const ONE_PARTY_JURISDICTIONS: &[&str] = &[
+    "US-AL", "US-AK", "US-AZ", "US-AR", "US-CO", "US-DC", "US-GA", "US-HI", "US-ID", "US-IN",
+    "US-IA", "US-KS", "US-KY", "US-LA", "US-ME", "US-MN", "US-MS", "US-MO", "US-NE", "US-NJ",
+    "US-NM", "US-NY", "US-NC", "US-ND", "US-OH", "US-OK", "US-RI", "US-SC", "US-SD", "US-TN",
+    "US-TX", "US-UT", "US-VT", "US-VA", "US-WV", "US-WI", "US-WY", "CA", "GB",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentRequirement {
+    Always,
+    ByJurisdiction,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentBasis {
+    Explicit,     // Confirmed for this session
+    Jurisdiction, // The configured jurisdiction only needs the user's consent
+    NotRequired,  // The policy never asks
+}
+
+impl ConsentBasis {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ConsentBasis::Explicit => "explicit",
+            ConsentBasis::Jurisdiction => "jurisdiction",
+            ConsentBasis::NotRequired => "not_required",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsentSettings {
+    pub requirement: ConsentRequirement,
+    pub jurisdiction: Option<String>, // e.g. "US-NY", "GB"
+}
+
+impl Default for ConsentSettings {
+    fn default() -> Self {
+        Self {
+            requirement: ConsentRequirement::ByJurisdiction,
+            jurisdiction: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentStatus {
+    pub session_id: String,
+    pub required: bool,              // The policy alone doesn't allow recording
+    pub granted: bool,               // Consent was confirmed for this session
+    pub basis: Option<ConsentBasis>, // Why recording may start; None when it may not
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingIndicator {
+    pub recording: bool,
+    pub sources: Vec<String>,               // "loopback", "microphone", in the order they started
+    pub sessions: HashMap<String, String>,  // Source -> the session it records
+    pub session_id: Option<String>,         // The most recently started source's session
+    pub basis: Option<ConsentBasis>,
+    pub since: Option<i64>, // Unix ms the first source started
+}
+
+struct ActiveRecording {
+    session_id: String,
+    basis: ConsentBasis,
+    since: i64,
+}
+
+#[derive(Default)]
+struct ConsentState {
+    granted: HashMap<String, bool>, // Explicit consent per session, as last confirmed or withdrawn
+    recordings: HashMap<String, ActiveRecording>, // By source; each may record a different session
+    app_handle: Option<AppHandle>,  // For indicator updates from stop paths that have none
+}
+
+impl ConsentState {
+    fn indicator(&self) -> RecordingIndicator {
+        let mut active: Vec<_> = self.recordings.iter().collect();
+        active.sort_by(|(a_source, a), (b_source, b)| a.since.cmp(&b.since).then_with(|| a_source.cmp(b_source)));
+        let latest = active.last().map(|(_, recording)| *recording);
+        RecordingIndicator {
+            recording: !active.is_empty(),
+            sources: active.iter().map(|(source, _)| source.to_string()).collect(),
+            sessions: active.iter().map(|(source, recording)| (source.to_string(), recording.session_id.clone())).collect(),
+            session_id: latest.map(|recording| recording.session_id.clone()),
+            basis: latest.map(|recording| recording.basis),
+            since: active.first().map(|(_, recording)| recording.since),
+        }
+    }
+
+    fn is_recording(&self, session_id: &str) -> bool {
+        self.recordings.values().any(|recording| recording.session_id == session_id)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref STATE: Mutex<ConsentState> = Mutex::new(ConsentState::default());
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("consent.json")
+}
+
+fn load_settings() -> ConsentSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn normalize_jurisdiction(jurisdiction: &str) -> String {
+    jurisdiction.trim().to_uppercase().replace('_', "-")
+}
+
+/// Whether the policy lets recording start without consent confirmed for the session.
+fn policy_basis(settings: &ConsentSettings) -> Option<ConsentBasis> {
+    match settings.requirement {
+        ConsentRequirement::Always => None,
+        ConsentRequirement::Never => Some(ConsentBasis::NotRequired),
+        ConsentRequirement::ByJurisdiction => settings
+            .jurisdiction
+            .as_deref()
+            .map(normalize_jurisdiction)
+            .filter(|jurisdiction| ONE_PARTY_JURISDICTIONS.contains(&jurisdiction.as_str()))
+            .map(|_| ConsentBasis::Jurisdiction),
+    }
+}
+
+fn record_event(app_handle: &AppHandle, event: ConsentEvent) {
+    let result = ConversationStorage::new(app_handle)
+        .and_then(|mut storage| storage.record_consent_event(&event));
+    if let Err(e) = result {
+        eprintln!("⚠️ Failed to store consent event for {}: {}", event.session_id, e);
+    }
+}
+
+fn event(session_id: &str, kind: &str) -> ConsentEvent {
+    ConsentEvent {
+        session_id: session_id.to_string(),
+        event: kind.to_string(),
+        basis: None,
+        method: None,
+        jurisdiction: None,
+        source: None,
+        note: None,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+    }
+}
+
+// Consent confirmed earlier is remembered across restarts through the audit trail
+fn explicit_consent(app_handle: &AppHandle, session_id: &str) -> Result<bool, String> {
+    if let Some(&granted) = STATE.lock().map_err(|e| e.to_string())?.granted.get(session_id) {
+        return Ok(granted);
+    }
+    let granted = ConversationStorage::new(app_handle)
+        .and_then(|storage| storage.get_consent_events(session_id))
+        .map_err(|e| format!("Failed to read consent for {}: {}", session_id, e))?
+        .iter()
+        .rev()
+        .find_map(|event| match event.event.as_str() {
+            "granted" => Some(true),
+            "revoked" => Some(false),
+            _ => None,
+        })
+        .unwrap_or(false);
+    STATE.lock().map_err(|e| e.to_string())?.granted.insert(session_id.to_string(), granted);
+    Ok(granted)
+}
+
+fn status(app_handle: &AppHandle, session_id: &str) -> Result<ConsentStatus, String> {
+    let policy = policy_basis(&load_settings());
+    let granted = explicit_consent(app_handle, session_id)?;
+    Ok(ConsentStatus {
+        session_id: session_id.to_string(),
+        required: policy.is_none(),
+        granted,
+        basis: if granted { Some(ConsentBasis::Explicit) } else { policy },
+    })
+}
+
+fn emit_indicator(app_handle: &AppHandle, indicator: &RecordingIndicator) {
+    if let Err(e) = app_handle.emit(INDICATOR_EVENT, indicator) {
+        eprintln!("Failed to emit recording indicator: {}", e);
+    }
+}
+
+/// Check that `source` may start recording for `session_id`, then show it on the recording
+/// indicator and note the start in the session's audit trail.
+pub fn begin_recording(app_handle: &AppHandle, source: &str, session_id: &str) -> Result<(), String> {
+    if session_id.trim().is_empty() {
+        return Err("Recording needs a conversation to record for. Start one and confirm consent first.".to_string());
+    }
+    let basis = status(app_handle, session_id)?.basis.ok_or_else(|| {
+        println!("🚫 Refused to record {} for {}: no consent", source, session_id);
+        "Everyone in the conversation must consent before recording starts. Confirm consent for this conversation first.".to_string()
+    })?;
+
+    let (indicator, switched_from) = {
+        let mut state = STATE.lock().map_err(|e| e.to_string())?;
+        state.app_handle = Some(app_handle.clone());
+        let switched_from = match state.recordings.get_mut(source) {
+            Some(recording) if recording.session_id == session_id => {
+                recording.basis = basis;
+                None
+            }
+            _ => state.recordings.insert(source.to_string(), ActiveRecording {
+                session_id: session_id.to_string(),
+                basis,
+                since: chrono::Utc::now().timestamp_millis(),
+            }).map(|previous| previous.session_id),
+        };
+        (state.indicator(), switched_from)
+    };
+    emit_indicator(app_handle, &indicator);
+    // The source moved to another conversation; the one it left stops being recorded
+    if let Some(previous) = switched_from {
+        record_event(app_handle, ConsentEvent {
+            source: Some(source.to_string()),
+            ..event(&previous, "recording_stopped")
+        });
+    }
+
+    let settings = load_settings();
+    record_event(app_handle, ConsentEvent {
+        basis: Some(basis.as_str().to_string()),
+        jurisdiction: settings.jurisdiction.filter(|_| basis == ConsentBasis::Jurisdiction),
+        source: Some(source.to_string()),
+        ..event(session_id, "recording_started")
+    });
+    println!("🔴 Recording {} for {} ({})", source, session_id, basis.as_str());
+    Ok(())
+}
+
+/// Gate for audio that arrives for `session_id`: passes while `source` is already recording that
+/// session, and otherwise starts it only if consent allows.
+pub fn ensure_recording(app_handle: &AppHandle, source: &str, session_id: &str) -> Result<(), String> {
+    let recording = {
+        let state = STATE.lock().map_err(|e| e.to_string())?;
+        state.recordings.get(source).is_some_and(|recording| recording.session_id == session_id)
+    };
+    if recording {
+        return Ok(());
+    }
+    begin_recording(app_handle, source, session_id)
+}
+
+/// The session `source` is recording for, if it is recording.
+pub fn recording_session(source: &str) -> Option<String> {
+    let state = STATE.lock().ok()?;
+    state.recordings.get(source).map(|recording| recording.session_id.clone())
+}
+
+/// Take `source` off the recording indicator. Sources that weren't recording are ignored.
+pub fn end_recording(source: &str) {
+    let (app_handle, session_id, indicator) = {
+        let Ok(mut state) = STATE.lock() else {
+            return;
+        };
+        let Some(stopped) = state.recordings.remove(source) else {
+            return;
+        };
+        (state.app_handle.clone(), stopped.session_id, state.indicator())
+    };
+    let Some(app_handle) = app_handle else {
+        return;
+    };
+    emit_indicator(&app_handle, &indicator);
+    record_event(&app_handle, ConsentEvent {
+        source: Some(source.to_string()),
+        ..event(&session_id, "recording_stopped")
+    });
+}
+
+/// Confirm that everyone in the conversation consents to recording. `method` says how consent
+/// was collected (e.g. "banner", "verbal", "calendar_notice").
+#[tauri::command]
+pub fn grant_recording_consent(app_handle: AppHandle, session_id: String, method: Option<String>, note: Option<String>) -> Result<ConsentStatus, String> {
+    {
+        let mut state = STATE.lock().map_err(|e| e.to_string())?;
+        state.granted.insert(session_id.clone(), true);
+    }
+    record_event(&app_handle, ConsentEvent {
+        method: Some(method.unwrap_or_else(|| "banner".to_string())),
+        jurisdiction: load_settings().jurisdiction,
+        note,
+        ..event(&session_id, "granted")
+    });
+    println!("✅ Recording consent confirmed for {}", session_id);
+    status(&app_handle, &session_id)
+}
+
+/// Withdraw consent. Capture recording for the session stops unless the policy alone allows it.
+#[tauri::command]
+pub async fn revoke_recording_consent(app_handle: AppHandle, session_id: String, note: Option<String>) -> Result<ConsentStatus, String> {
+    let recording_this_session = {
+        let mut state = STATE.lock().map_err(|e| e.to_string())?;
+        state.granted.insert(session_id.clone(), false);
+        state.is_recording(&session_id)
+    };
+    record_event(&app_handle, ConsentEvent { note, ..event(&session_id, "revoked") });
+    println!("🚫 Recording consent withdrawn for {}", session_id);
+
+    let status = status(&app_handle, &session_id)?;
+    if recording_this_session && status.basis.is_none() {
+        // Only the sources recording this session; another conversation may still be recording
+        if recording_session("loopback").as_deref() == Some(session_id.as_str()) {
+            crate::audio_loopback::stop_audio_loopback_capture().await?;
+            crate::conversation_audio::stop_conversation_audio().await?;
+        }
+        // Microphone capture runs in the window, which stops when it sees the indicator change
+        if recording_session("microphone").as_deref() == Some(session_id.as_str()) {
+            end_recording("microphone");
+        }
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn get_recording_consent(app_handle: AppHandle, session_id: String) -> Result<ConsentStatus, String> {
+    status(&app_handle, &session_id)
+}
+
+/// Microphone capture runs in the window; it reports here so the indicator shows it. Its audio is
+/// gated again when it reaches transcribe_audio_base64.
+#[tauri::command]
+pub fn set_microphone_recording(app_handle: AppHandle, session_id: String, active: bool) -> Result<RecordingIndicator, String> {
+    if active {
+        begin_recording(&app_handle, "microphone", &session_id)?;
+    } else {
+        end_recording("microphone");
+    }
+    get_recording_indicator()
+}
+
+#[tauri::command]
+pub fn get_recording_indicator() -> Result<RecordingIndicator, String> {
+    Ok(STATE.lock().map_err(|e| e.to_string())?.indicator())
+}
+
+#[tauri::command]
+pub fn get_consent_audit(app_handle: AppHandle, session_id: String) -> Result<Vec<ConsentEvent>, String> {
+    ConversationStorage::new(&app_handle)
+        .and_then(|storage| storage.get_consent_events(&session_id))
+        .map_err(|e| format!("Failed to read consent audit: {}", e))
+}
+
+#[tauri::command]
+pub fn get_consent_settings() -> Result<ConsentSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_consent_settings(settings: ConsentSettings) -> Result<(), String> {
+    let settings = ConsentSettings {
+        jurisdiction: settings.jurisdiction.map(|j| normalize_jurisdiction(&j)).filter(|j| !j.is_empty()),
+        ..settings
+    };
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("💾 Saved consent policy ({:?}, jurisdiction: {:?})", settings.requirement, settings.jurisdiction);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_known_one_party_jurisdictions_skip_consent() {
+        let by = |jurisdiction: Option<&str>| ConsentSettings {
+            requirement: ConsentRequirement::ByJurisdiction,
+            jurisdiction: jurisdiction.map(str::to_string),
+        };
+        assert_eq!(policy_basis(&by(Some("us_ny"))), Some(ConsentBasis::Jurisdiction));
+        assert_eq!(policy_basis(&by(Some("GB"))), Some(ConsentBasis::Jurisdiction));
+        // All-party states, unknown places and an unset jurisdiction all ask
+        assert_eq!(policy_basis(&by(Some("US-CA"))), None);
+        assert_eq!(policy_basis(&by(Some("DE"))), None);
+        assert_eq!(policy_basis(&by(None)), None);
+
+        let always = ConsentSettings { requirement: ConsentRequirement::Always, ..by(Some("US-NY")) };
+        assert_eq!(policy_basis(&always), None);
+        let never = ConsentSettings { requirement: ConsentRequirement::Never, jurisdiction: None };
+        assert_eq!(policy_basis(&never), Some(ConsentBasis::NotRequired));
+    }
+
+    #[test]
+    fn test_indicator_keeps_each_source_on_its_own_session() {
+        let mut state = ConsentState::default();
+        let recording = |session_id: &str, since| ActiveRecording { session_id: session_id.to_string(), basis: ConsentBasis::Explicit, since };
+        state.recordings.insert("microphone".to_string(), recording("standup", 100));
+        state.recordings.insert("loopback".to_string(), recording("interview", 200));
+
+        let indicator = state.indicator();
+        assert_eq!(indicator.sources, vec!["microphone", "loopback"]);
+        assert_eq!(indicator.sessions["microphone"], "standup");
+        assert_eq!(indicator.sessions["loopback"], "interview");
+        assert_eq!(indicator.session_id.as_deref(), Some("interview"));
+        assert_eq!(indicator.since, Some(100));
+
+        // Stopping one source leaves the other on its session
+        state.recordings.remove("loopback");
+        assert!(state.is_recording("standup"));
+        assert!(!state.is_recording("interview"));
+        assert_eq!(state.indicator().session_id.as_deref(), Some("standup"));
+    }
+}
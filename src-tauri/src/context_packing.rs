@@ -0,0 +1,303 @@
+// src-tauri/src/context_packing.rs
+// Packs chunks from several pinned documents into one token budget. Every document first gets its
+// minimum number of best chunks, then the remaining budget goes greedily to the chunk with the
+// highest marginal relevance (relevance minus redundancy with what is already packed), within each
+// document's maximum. The per-chunk decisions are returned so the UI can show why something was left out.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::generation_options::estimate_tokens;
+use crate::simple_embedding_service::cosine_similarity;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PackingConstraints {
+    pub budget_tokens: usize,
+    pub min_chunks_per_document: usize,
+    pub max_chunks_per_document: usize,
+    pub diversity: f32, // 0 ranks by relevance only, 1 by novelty only
+}
+
+impl Default for PackingConstraints {
+    fn default() -> Self {
+        Self {
+            budget_tokens: 2000,
+            min_chunks_per_document: 1,
+            max_chunks_per_document: 6,
+            diversity: 0.3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PackingCandidate {
+    pub chunk_id: String,
+    pub document_id: String,
+    pub file_name: String,
+    pub chunk_index: i32,
+    pub content: String,
+    pub tokens: usize,
+    pub relevance: f32,
+    pub embedding: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackedChunk {
+    pub chunk_id: String,
+    pub document_id: String,
+    pub chunk_index: i32,
+    pub content: String,
+    pub tokens: usize,
+    pub relevance: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChunkDecision {
+    pub chunk_id: String,
+    pub document_id: String,
+    pub chunk_index: i32,
+    pub tokens: usize,
+    pub relevance: f32,
+    pub included: bool,
+    // "document_minimum" | "marginal_relevance" when included;
+    // "over_budget" | "document_maximum" | "redundant" when left out
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DocumentAllocation {
+    pub document_id: String,
+    pub file_name: String,
+    pub chunks_included: usize,
+    pub tokens_used: usize,
+    pub best_relevance: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContextPacking {
+    pub context: String,
+    pub chunks: Vec<PackedChunk>,
+    pub documents: Vec<DocumentAllocation>,
+    pub decisions: Vec<ChunkDecision>,
+    pub tokens_used: usize,
+    pub budget_tokens: usize,
+}
+
+fn document_header(file_name: &str) -> String {
+    format!("From \"{}\":\n", file_name)
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+/// Share of the query's words found in the text; the relevance fallback for chunks without embeddings.
+pub fn lexical_relevance(query: &str, text: &str) -> f32 {
+    let query_words = words(query);
+    if query_words.is_empty() {
+        return 0.0;
+    }
+    let text_words = words(text);
+    query_words.intersection(&text_words).count() as f32 / query_words.len() as f32
+}
+
+fn similarity(a: &PackingCandidate, b: &PackingCandidate, a_words: &HashSet<String>, b_words: &HashSet<String>) -> f32 {
+    if let (Some(x), Some(y)) = (&a.embedding, &b.embedding) {
+        return cosine_similarity(x, y);
+    }
+    let union = a_words.union(b_words).count();
+    if union == 0 {
+        0.0
+    } else {
+        a_words.intersection(b_words).count() as f32 / union as f32
+    }
+}
+
+pub fn pack(candidates: Vec<PackingCandidate>, constraints: &PackingConstraints) -> ContextPacking {
+    let diversity = constraints.diversity.clamp(0.0, 1.0);
+    let candidate_words: Vec<HashSet<String>> = candidates.iter().map(|c| words(&c.content)).collect();
+
+    // Documents in order of their best chunk, so minimums go to the most relevant documents first
+    let mut document_order: Vec<(&str, f32)> = Vec::new();
+    for candidate in &candidates {
+        match document_order.iter_mut().find(|(id, _)| *id == candidate.document_id) {
+            Some((_, best)) => *best = best.max(candidate.relevance),
+            None => document_order.push((&candidate.document_id, candidate.relevance)),
+        }
+    }
+    document_order.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut selected: Vec<(usize, &'static str)> = Vec::new();
+    let mut per_document: HashMap<&str, usize> = HashMap::new();
+    let mut tokens_used = 0;
+
+    // The header is paid for when a document's first chunk goes in
+    let cost = |index: usize, per_document: &HashMap<&str, usize>| {
+        let candidate = &candidates[index];
+        let header = if per_document.contains_key(candidate.document_id.as_str()) {
+            0
+        } else {
+            estimate_tokens(&document_header(&candidate.file_name))
+        };
+        candidate.tokens + header
+    };
+
+    for (document_id, _) in &document_order {
+        let mut ranked: Vec<usize> = (0..candidates.len())
+            .filter(|&i| candidates[i].document_id == *document_id)
+            .collect();
+        ranked.sort_by(|&a, &b| candidates[b].relevance.partial_cmp(&candidates[a].relevance).unwrap_or(std::cmp::Ordering::Equal));
+        for index in ranked.into_iter().take(constraints.min_chunks_per_document.min(constraints.max_chunks_per_document)) {
+            let chunk_cost = cost(index, &per_document);
+            if tokens_used + chunk_cost > constraints.budget_tokens {
+                continue;
+            }
+            tokens_used += chunk_cost;
+            *per_document.entry(*document_id).or_default() += 1;
+            selected.push((index, "document_minimum"));
+        }
+    }
+
+    loop {
+        let mut best: Option<(usize, f32)> = None;
+        for index in 0..candidates.len() {
+            let candidate = &candidates[index];
+            if selected.iter().any(|(i, _)| *i == index)
+                || per_document.get(candidate.document_id.as_str()).copied().unwrap_or(0) >= constraints.max_chunks_per_document
+                || tokens_used + cost(index, &per_document) > constraints.budget_tokens
+            {
+                continue;
+            }
+            let redundancy = selected
+                .iter()
+                .map(|(i, _)| similarity(candidate, &candidates[*i], &candidate_words[index], &candidate_words[*i]))
+                .fold(0.0, f32::max);
+            let marginal = (1.0 - diversity) * candidate.relevance - diversity * redundancy;
+            if best.map_or(true, |(_, score)| marginal > score) {
+                best = Some((index, marginal));
+            }
+        }
+        match best {
+            Some((index, marginal)) if marginal > 0.0 => {
+                tokens_used += cost(index, &per_document);
+                *per_document.entry(candidates[index].document_id.as_str()).or_default() += 1;
+                selected.push((index, "marginal_relevance"));
+            }
+            _ => break,
+        }
+    }
+
+    let mut decisions: Vec<ChunkDecision> = candidates
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let reason = match selected.iter().find(|(i, _)| *i == index) {
+                Some((_, reason)) => *reason,
+                None if per_document.get(candidate.document_id.as_str()).copied().unwrap_or(0) >= constraints.max_chunks_per_document => "document_maximum",
+                None if tokens_used + cost(index, &per_document) > constraints.budget_tokens => "over_budget",
+                None => "redundant",
+            };
+            ChunkDecision {
+                chunk_id: candidate.chunk_id.clone(),
+                document_id: candidate.document_id.clone(),
+                chunk_index: candidate.chunk_index,
+                tokens: candidate.tokens,
+                relevance: candidate.relevance,
+                included: selected.iter().any(|(i, _)| *i == index),
+                reason: reason.to_string(),
+            }
+        })
+        .collect();
+    decisions.sort_by(|a, b| b.relevance.partial_cmp(&a.relevance).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Render document by document, chunks in reading order
+    let mut context = String::new();
+    let mut chunks = Vec::new();
+    let mut documents = Vec::new();
+    for (document_id, best_relevance) in &document_order {
+        let mut picked: Vec<&PackingCandidate> = selected
+            .iter()
+            .map(|(i, _)| &candidates[*i])
+            .filter(|c| c.document_id == *document_id)
+            .collect();
+        picked.sort_by_key(|c| c.chunk_index);
+
+        let file_name = candidates.iter().find(|c| c.document_id == *document_id).map(|c| c.file_name.clone()).unwrap_or_default();
+        let mut document_tokens = 0;
+        if !picked.is_empty() {
+            context.push_str(&document_header(&file_name));
+            document_tokens += estimate_tokens(&document_header(&file_name));
+        }
+        for candidate in &picked {
+            context.push_str(&format!("- {}\n", candidate.content.trim()));
+            document_tokens += candidate.tokens;
+            chunks.push(PackedChunk {
+                chunk_id: candidate.chunk_id.clone(),
+                document_id: candidate.document_id.clone(),
+                chunk_index: candidate.chunk_index,
+                content: candidate.content.clone(),
+                tokens: candidate.tokens,
+                relevance: candidate.relevance,
+            });
+        }
+        if !picked.is_empty() {
+            context.push('\n');
+        }
+        documents.push(DocumentAllocation {
+            document_id: document_id.to_string(),
+            file_name,
+            chunks_included: picked.len(),
+            tokens_used: document_tokens,
+            best_relevance: *best_relevance,
+        });
+    }
+
+    ContextPacking {
+        context: context.trim_end().to_string(),
+        chunks,
+        documents,
+        decisions,
+        tokens_used,
+        budget_tokens: constraints.budget_tokens,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(document_id: &str, chunk_index: i32, content: &str, relevance: f32) -> PackingCandidate {
+        PackingCandidate {
+            chunk_id: format!("{}-{}", document_id, chunk_index),
+            document_id: document_id.to_string(),
+            file_name: format!("{}.md", document_id),
+            chunk_index,
+            content: content.to_string(),
+            tokens: 100,
+            relevance,
+            embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_every_document_gets_its_minimum() {
+        let candidates = vec![
+            candidate("a", 0, "quarterly revenue grew in europe", 0.9),
+            candidate("a", 1, "quarterly revenue grew in europe again", 0.85),
+            candidate("a", 2, "hiring plans for the sales team", 0.6),
+            candidate("b", 0, "office move scheduled for march", 0.2),
+        ];
+        let constraints = PackingConstraints { budget_tokens: 340, ..Default::default() };
+        let packing = pack(candidates, &constraints);
+
+        let included: Vec<&str> = packing.chunks.iter().map(|c| c.chunk_id.as_str()).collect();
+        assert!(included.contains(&"b-0"), "low-scoring document still gets its minimum");
+        assert!(included.contains(&"a-0"));
+        assert!(packing.tokens_used <= 340);
+        // The near-duplicate of a-0 loses to the more novel chunk
+        assert!(included.contains(&"a-2") && !included.contains(&"a-1"));
+    }
+}
@@ -0,0 +1,219 @@
+// src-tauri/src/context_window.rs
+// Keeps agent requests inside the model's context window. History that doesn't fit alongside the
+// system prompt and the new request is dropped oldest first, and (unless turned off) a small model
+// summarizes what was dropped so the summary rides along as a system line instead. Summaries are
+// cached by the history they cover, so a long chat only summarizes the newly dropped turns.
+use crate::generation_options::estimate_tokens;
+use crate::ollama::{generate_completion, ChatContextMessage};
+use crate::system_prompts::HISTORY_SUMMARY_PROMPT;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const DEFAULT_SUMMARY_MODEL: &str = "gemma3:1b-it-qat";
+// Room kept for the summary line when history has to be dropped
+const SUMMARY_TOKENS: usize = 300;
+const MAX_SUMMARY_INPUT_CHARS: usize = 12_000;
+const MAX_MESSAGE_CHARS: usize = 1500;
+const MAX_CACHED_SUMMARIES: usize = 32;
+const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextWindowSettings {
+    pub summarize_dropped: bool,
+    pub summary_model: Option<String>, // None uses a small built-in model
+}
+
+impl Default for ContextWindowSettings {
+    fn default() -> Self {
+        Self {
+            summarize_dropped: true,
+            summary_model: None,
+        }
+    }
+}
+
+struct CachedSummary {
+    covered: usize, // How many leading history messages the summary covers
+    fingerprint: u64,
+    summary: String,
+}
+
+lazy_static::lazy_static! {
+    static ref SUMMARY_CACHE: Mutex<Vec<CachedSummary>> = Mutex::new(Vec::new());
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("context_window.json")
+}
+
+fn load_settings() -> ContextWindowSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn fingerprint(messages: &[ChatContextMessage]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for message in messages {
+        message.role.hash(&mut hasher);
+        message.content.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// How many of the oldest history messages must go for the request to fit `limit` tokens.
+fn messages_to_drop(system_prompt: &str, prompt: &str, context: &[ChatContextMessage], limit: usize) -> usize {
+    let mut total = estimate_tokens(system_prompt)
+        + estimate_tokens(prompt)
+        + context.iter().map(|message| estimate_tokens(&message.content)).sum::<usize>();
+    let mut dropped = 0;
+    while total > limit && dropped < context.len() {
+        total -= estimate_tokens(&context[dropped].content);
+        dropped += 1;
+    }
+    dropped
+}
+
+// The longest cached summary that covers a prefix of `dropped`
+fn cached_summary(dropped: &[ChatContextMessage]) -> Option<(usize, String)> {
+    let cache = SUMMARY_CACHE.lock().ok()?;
+    cache
+        .iter()
+        .filter(|entry| entry.covered <= dropped.len() && entry.fingerprint == fingerprint(&dropped[..entry.covered]))
+        .max_by_key(|entry| entry.covered)
+        .map(|entry| (entry.covered, entry.summary.clone()))
+}
+
+fn cache_summary(dropped: &[ChatContextMessage], summary: &str) {
+    if let Ok(mut cache) = SUMMARY_CACHE.lock() {
+        if cache.len() >= MAX_CACHED_SUMMARIES {
+            cache.remove(0);
+        }
+        cache.push(CachedSummary {
+            covered: dropped.len(),
+            fingerprint: fingerprint(dropped),
+            summary: summary.to_string(),
+        });
+    }
+}
+
+async fn summarize(settings: &ContextWindowSettings, dropped: &[ChatContextMessage]) -> Result<String, String> {
+    let (covered, earlier) = match cached_summary(dropped) {
+        Some((covered, summary)) if covered == dropped.len() => return Ok(summary),
+        Some((covered, summary)) => (covered, Some(summary)),
+        None => (0, None),
+    };
+
+    let mut transcript = String::new();
+    for message in &dropped[covered..] {
+        let content: String = message.content.chars().take(MAX_MESSAGE_CHARS).collect();
+        transcript.push_str(&format!("{}: {}\n", message.role, content));
+    }
+    // Keep the end of an overlong transcript; it's closest to what comes next
+    let overflow = transcript.chars().count().saturating_sub(MAX_SUMMARY_INPUT_CHARS);
+    let transcript: String = transcript.chars().skip(overflow).collect();
+    let prompt = match earlier {
+        Some(earlier) => format!("Earlier summary:\n{}\n\nConversation since then:\n{}\nSummary:", earlier, transcript),
+        None => format!("Conversation:\n{}\nSummary:", transcript),
+    };
+
+    let model = crate::agent_models::resolve_helper_model(settings.summary_model.as_deref().unwrap_or(DEFAULT_SUMMARY_MODEL)).await;
+    let options = serde_json::json!({ "temperature": 0.2, "num_predict": SUMMARY_TOKENS - 50 });
+    let summary = generate_completion(&model, prompt, Some(HISTORY_SUMMARY_PROMPT.to_string()), Some(options))
+        .await?
+        .trim()
+        .to_string();
+    if summary.is_empty() {
+        return Err("The summary model returned nothing".to_string());
+    }
+    cache_summary(dropped, &summary);
+    Ok(summary)
+}
+
+/// Fit the history of an agent request into `window_limit` prompt tokens (the model's window; None
+/// for remote providers) and the user's context budget, whichever is smaller.
+pub async fn fit_context(
+    app_handle: &AppHandle,
+    system_prompt: &str,
+    prompt: &str,
+    context: Option<Vec<ChatContextMessage>>,
+    window_limit: Option<usize>,
+) -> Option<Vec<ChatContextMessage>> {
+    let mut context = context?;
+    let budget = crate::data::chat::context_budget(app_handle).map(|budget| budget as usize);
+    let limit = match (window_limit, budget) {
+        (Some(window), Some(budget)) => window.min(budget),
+        (Some(limit), None) | (None, Some(limit)) => limit,
+        (None, None) => return Some(context),
+    };
+    if messages_to_drop(system_prompt, prompt, &context, limit) == 0 {
+        return Some(context);
+    }
+
+    let settings = load_settings();
+    let target = if settings.summarize_dropped { limit.saturating_sub(SUMMARY_TOKENS) } else { limit };
+    let dropped = messages_to_drop(system_prompt, prompt, &context, target);
+    let removed: Vec<ChatContextMessage> = context.drain(..dropped).collect();
+    println!("✂️ Dropped {} oldest context message(s) to fit {} prompt tokens", dropped, limit);
+
+    if settings.summarize_dropped {
+        match summarize(&settings, &removed).await {
+            Ok(summary) => context.insert(0, ChatContextMessage {
+                role: "system".to_string(),
+                content: format!("{} {}", SUMMARY_PREFIX, summary),
+            }),
+            Err(e) => eprintln!("Failed to summarize dropped history: {}", e),
+        }
+    }
+    Some(context)
+}
+
+#[tauri::command]
+pub async fn get_context_window_settings() -> Result<ContextWindowSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn save_context_window_settings(settings: ContextWindowSettings) -> Result<(), String> {
+    let settings = ContextWindowSettings {
+        summary_model: settings.summary_model.map(|model| model.trim().to_string()).filter(|model| !model.is_empty()),
+        ..settings
+    };
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("🪟 Saved context window settings (summarize dropped history: {})", settings.summarize_dropped);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(content: &str) -> ChatContextMessage {
+        ChatContextMessage { role: "user".to_string(), content: content.to_string() }
+    }
+
+    #[test]
+    fn test_oldest_messages_are_dropped_and_summaries_reused_by_prefix() {
+        let long = "word ".repeat(200);
+        let context = vec![message(&long), message(&long), message("recent question")];
+
+        assert_eq!(messages_to_drop("Be brief", "Next", &context, 10_000), 0);
+        assert_eq!(messages_to_drop("Be brief", "Next", &context, 300), 1);
+        assert_eq!(messages_to_drop("Be brief", "Next", &context, 1), 3);
+
+        cache_summary(&context[..1], "The user pasted a long text.");
+        assert_eq!(cached_summary(&context[..2]), Some((1, "The user pasted a long text.".to_string())));
+        assert_eq!(cached_summary(&[message("something else")]), None);
+    }
+}
@@ -0,0 +1,241 @@
+// src-tauri/src/conversation_audio.rs
+// Optional copy of the audio that was transcribed during a conversation, so a transcript line can be
+// played back to check what was actually said. Each stream gets one raw 16 kHz mono PCM16 file per
+// conversation; chunks are appended in the order they were transcribed, and every transcription
+// result carries the byte span of its chunk. Audio too quiet to transcribe is never stored.
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::data::conversation::ConversationStorage;
+use crate::data::types::ConversationMessage;
+use crate::stt_provider::SttStream;
+
+const SAMPLE_RATE: u32 = 16000;
+const BYTES_PER_MS: u64 = (SAMPLE_RATE as u64 * 2) / 1000;
+
+/// Where a transcribed chunk sits in its stream's audio file.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioSpan {
+    pub offset: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageAudio {
+    pub message_id: String,
+    pub source: String,
+    pub duration_ms: u64,
+    pub wav_base64: String,
+}
+
+struct ActiveArchive {
+    session_id: String,
+    dir: PathBuf,
+}
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_ARCHIVE: Mutex<Option<ActiveArchive>> = Mutex::new(None);
+}
+
+fn audio_root(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::data::paths::user_data_dir(app_handle)?.join("conversation_audio"))
+}
+
+fn stream_file_name(source: &str) -> String {
+    format!("{}.pcm", source)
+}
+
+fn stream_source(stream: SttStream) -> &'static str {
+    match stream {
+        SttStream::Microphone => "microphone",
+        SttStream::Loopback => "loopback",
+    }
+}
+
+// A message's source ends up in a file path, so only the archived streams are accepted
+fn archived_source(source: &str) -> Result<&'static str, String> {
+    [SttStream::Microphone, SttStream::Loopback]
+        .into_iter()
+        .map(stream_source)
+        .find(|archived| *archived == source)
+        .ok_or_else(|| format!("Audio is only kept for microphone and loopback messages, not \"{}\"", source))
+}
+
+// The microphone path sometimes sends a whole WAV file; keep only its sample data
+fn pcm_payload(audio: &[u8]) -> &[u8] {
+    if audio.len() < 12 || &audio[0..4] != b"RIFF" || &audio[8..12] != b"WAVE" {
+        return audio;
+    }
+    let mut position = 12;
+    while position + 8 <= audio.len() {
+        let chunk_size = u32::from_le_bytes([audio[position + 4], audio[position + 5], audio[position + 6], audio[position + 7]]) as usize;
+        if &audio[position..position + 4] == b"data" {
+            let start = position + 8;
+            return &audio[start..(start + chunk_size).min(audio.len())];
+        }
+        position += 8 + chunk_size + (chunk_size % 2);
+    }
+    audio
+}
+
+/// Append a transcribed chunk to the active conversation's audio, if audio is being kept.
+pub fn append(stream: SttStream, audio: &[u8]) -> Option<AudioSpan> {
+    let archive = ACTIVE_ARCHIVE.lock().ok()?;
+    let archive = archive.as_ref()?;
+    let pcm = pcm_payload(audio);
+    if pcm.is_empty() {
+        return None;
+    }
+
+    let path = archive.dir.join(stream_file_name(stream_source(stream)));
+    let result = (|| -> std::io::Result<AudioSpan> {
+        fs::create_dir_all(&archive.dir)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let offset = file.metadata()?.len();
+        file.write_all(pcm)?;
+        Ok(AudioSpan { offset, duration_ms: pcm.len() as u64 / BYTES_PER_MS })
+    })();
+
+    match result {
+        Ok(span) => Some(span),
+        Err(e) => {
+            eprintln!("Failed to store audio for {}: {}", archive.session_id, e);
+            None
+        }
+    }
+}
+
+fn wav_bytes(pcm: &[u8]) -> Result<Vec<u8>, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+        for sample in pcm.chunks_exact(2) {
+            writer
+                .write_sample(i16::from_le_bytes([sample[0], sample[1]]))
+                .map_err(|e| format!("Failed to write WAV sample: {}", e))?;
+        }
+        writer.finalize().map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+    }
+    Ok(cursor.into_inner())
+}
+
+/// Delete the stored audio of a conversation, e.g. when the conversation itself is deleted.
+pub fn remove_session_audio(app_handle: &AppHandle, session_id: &str) {
+    if let Ok(root) = audio_root(app_handle) {
+        let dir = root.join(session_id);
+        if dir.exists() {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                eprintln!("Failed to remove audio for {}: {}", session_id, e);
+            }
+        }
+    }
+}
+
+pub fn remove_all_audio(app_handle: &AppHandle) {
+    if let Ok(root) = audio_root(app_handle) {
+        if root.exists() {
+            if let Err(e) = fs::remove_dir_all(&root) {
+                eprintln!("Failed to remove conversation audio: {}", e);
+            }
+        }
+    }
+}
+
+/// Start keeping transcribed audio for a session when the "persistConversationAudio" setting is on.
+/// Returns whether audio is being kept.
+#[tauri::command]
+pub async fn start_conversation_audio(app_handle: AppHandle, session_id: String) -> Result<bool, String> {
+    let enabled = crate::audio_loopback::settings::load_general_settings()
+        .await?
+        .and_then(|settings| settings.get("persistConversationAudio").and_then(|v| v.as_bool()))
+        .unwrap_or(false);
+
+    let mut archive = ACTIVE_ARCHIVE.lock().map_err(|e| e.to_string())?;
+    if !enabled {
+        *archive = None;
+        return Ok(false);
+    }
+    let dir = audio_root(&app_handle)?.join(&session_id);
+    println!("🎧 Keeping conversation audio for {}", session_id);
+    *archive = Some(ActiveArchive { session_id, dir });
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn stop_conversation_audio() -> Result<(), String> {
+    let mut archive = ACTIVE_ARCHIVE.lock().map_err(|e| e.to_string())?;
+    *archive = None;
+    Ok(())
+}
+
+/// The kept PCM16 audio behind one transcript message.
+pub fn message_pcm(app_handle: &AppHandle, session_id: &str, message: &ConversationMessage) -> Result<Vec<u8>, String> {
+    let (Some(offset), Some(duration_ms)) = (message.audio_offset, message.audio_duration_ms) else {
+        return Err("No audio was kept for this message".to_string());
+    };
+
+    let source = archived_source(&message.source)?;
+    let path = audio_root(app_handle)?.join(session_id).join(stream_file_name(source));
+    let mut file = fs::File::open(&path).map_err(|e| format!("Audio for this conversation is gone: {}", e))?;
+    // Keep sample alignment even if a span was stored oddly
+    let length = (duration_ms as u64 * BYTES_PER_MS) & !1;
+    let mut pcm = Vec::with_capacity(length as usize);
+    file.seek(SeekFrom::Start(offset as u64 & !1)).map_err(|e| format!("Failed to read audio: {}", e))?;
+    file.take(length).read_to_end(&mut pcm).map_err(|e| format!("Failed to read audio: {}", e))?;
+    if pcm.is_empty() {
+        return Err("The stored audio for this message is empty".to_string());
+    }
+    Ok(pcm)
+}
+
+/// The audio behind one transcript message, as a WAV clip for playback.
+#[tauri::command]
+pub async fn get_message_audio(app_handle: AppHandle, message_id: String) -> Result<MessageAudio, String> {
+    let (session_id, message) = ConversationStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize conversation storage: {}", e))?
+        .get_message(&message_id)
+        .map_err(|e| format!("Failed to load message: {}", e))?
+        .ok_or_else(|| format!("Message not found: {}", message_id))?;
+    let pcm = message_pcm(&app_handle, &session_id, &message)?;
+
+    Ok(MessageAudio {
+        message_id,
+        source: message.source,
+        duration_ms: pcm.len() as u64 / BYTES_PER_MS,
+        wav_base64: general_purpose::STANDARD.encode(wav_bytes(&pcm)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pcm_payload_strips_wav_header() {
+        let pcm: Vec<u8> = (0..64).collect();
+        let wav = wav_bytes(&pcm).unwrap();
+        assert_eq!(pcm_payload(&wav), pcm.as_slice());
+        assert_eq!(pcm_payload(&pcm), pcm.as_slice());
+    }
+
+    #[test]
+    fn test_only_archived_sources_map_to_audio_files() {
+        assert_eq!(archived_source("microphone"), Ok("microphone"));
+        assert_eq!(archived_source("loopback"), Ok("loopback"));
+        assert!(archived_source("assistant").is_err());
+        assert!(archived_source("../loopback").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+// src-tauri/src/conversation_handoff.rs
+// "Continue this meeting topic in a chat": summarise a conversation (or part of its transcript)
+// into a context brief and seed a new chat with it, linking the two in both directions.
+use crate::data::chat::ChatStorage;
+use crate::data::conversation::{redaction, ConversationStorage};
+use crate::data::types::{ChatMessage, ChatSession, ConversationChatLink, MessageMetadata};
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+const BRIEF_MODEL: &str = "gemma3:1b-it-qat";
+// Keep the newest part of long transcripts so the brief model isn't overrun
+const MAX_TRANSCRIPT_TOKENS: usize = 6000;
+
+const BRIEF_SYSTEM_PROMPT: &str = "You write short context briefs so a conversation can be continued in a chat with an assistant. \
+Cover the topic, key points, decisions made and open questions. Use short bullet points under those headings and do not invent details.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationHandoff {
+    pub chat: ChatSession,
+    pub link: ConversationChatLink,
+}
+
+fn transcript_for_range(
+    messages: &[crate::data::types::ConversationMessage],
+    range_start: Option<i64>,
+    range_end: Option<i64>,
+) -> String {
+    let lines = redaction::transcript_lines(
+        messages
+            .iter()
+            .filter(|m| range_start.map_or(true, |start| m.timestamp >= start))
+            .filter(|m| range_end.map_or(true, |end| m.timestamp <= end)),
+    );
+
+    // Drop the oldest lines until the transcript fits the budget
+    let mut kept = Vec::new();
+    let mut tokens = 0;
+    for line in lines.iter().rev() {
+        let line_tokens = estimate_tokens(line);
+        if tokens + line_tokens > MAX_TRANSCRIPT_TOKENS {
+            break;
+        }
+        tokens += line_tokens;
+        kept.push(line.as_str());
+    }
+    kept.reverse();
+    kept.join("\n")
+}
+
+/// Create a chat seeded with a brief of the conversation and return it for the chat store.
+#[tauri::command]
+pub async fn continue_conversation_in_chat(
+    app_handle: AppHandle,
+    conversation_id: String,
+    range_start: Option<i64>,
+    range_end: Option<i64>,
+) -> Result<ConversationHandoff, String> {
+    let conversation = ConversationStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize conversation storage: {}", e))?
+        .get_session(&conversation_id)
+        .map_err(|e| format!("Failed to load conversation: {}", e))?
+        .ok_or_else(|| format!("Conversation not found: {}", conversation_id))?;
+
+    let transcript = transcript_for_range(&conversation.messages, range_start, range_end);
+    if transcript.is_empty() {
+        return Err("No transcript in the selected range".to_string());
+    }
+
+    let prompt = format!("Conversation \"{}\":\n{}\n\nWrite the context brief.", conversation.name, transcript);
+    let options = serde_json::Value::Object(
+        build_ollama_options("handoff", None, estimate_tokens(&prompt) + estimate_tokens(BRIEF_SYSTEM_PROMPT))
+    );
+    let model = crate::agent_models::resolve_helper_model(BRIEF_MODEL).await;
+    let brief = crate::ollama::generate_completion(
+        &model,
+        prompt,
+        Some(BRIEF_SYSTEM_PROMPT.to_string()),
+        Some(options.clone()),
+    ).await?;
+    let brief = brief.trim().to_string();
+
+    let now = chrono::Utc::now();
+    let mut chat_storage = ChatStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize chat storage: {}", e))?;
+    let message_id = chat_storage.next_message_id()
+        .map_err(|e| format!("Failed to allocate message id: {}", e))?;
+
+    let started = chrono::DateTime::from_timestamp_millis(conversation.start_time)
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let chat = ChatSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: format!("Follow-up: {}", conversation.name),
+        history: vec![ChatMessage {
+            id: message_id,
+            text: format!("Continuing from conversation \"{}\" ({})\n\n{}", conversation.name, started, brief),
+            sender: "system".to_string(),
+            timestamp: now.to_rfc3339(),
+            is_interim: None,
+            confidence: None,
+            source: Some("conversation".to_string()),
+            attachments: None,
+            thinking: None,
+            message_type: Some("conversation_handoff".to_string()),
+            metadata: Some(MessageMetadata {
+                agent_type: Some("handoff".to_string()),
+                model: Some(model.clone()),
+                tokens: None,
+                processing_time: None,
+                analysis_type: None,
+                search_queries: None,
+                sources: Some(vec![format!("conversation:{}", conversation.id)]),
+                model_tag: Some(model),
+                provider: Some("ollama".to_string()),
+                generation_options: Some(options),
+                rag_chunk_ids: None,
+                screen_context_id: None,
+                prompt_tokens: None,
+                completion_tokens: None,
+            }),
+            bookmarked: None,
+            reactions: None,
+        }],
+        created_at: now.to_rfc3339(),
+        updated_at: now.to_rfc3339(),
+        model_id: None,
+    };
+    chat_storage.create_chat_session(chat.clone())
+        .map_err(|e| format!("Failed to create chat: {}", e))?;
+
+    let link = ConversationChatLink {
+        conversation_id: conversation.id.clone(),
+        chat_id: chat.id.clone(),
+        range_start,
+        range_end,
+        brief,
+        created_at: now.timestamp_millis(),
+    };
+    ConversationStorage::new(&app_handle)
+        .and_then(|mut storage| storage.save_chat_link(&link))
+        .map_err(|e| format!("Failed to link conversation and chat: {}", e))?;
+
+    println!("🔗 Continued conversation {} in chat {}", conversation.id, chat.id);
+    let handoff = ConversationHandoff { chat, link };
+    if let Err(e) = app_handle.emit("conversation-handoff-created", &handoff) {
+        eprintln!("Failed to emit handoff event: {}", e);
+    }
+    Ok(handoff)
+}
+
+/// Chats continued from a conversation, or the conversation a chat came from when `chat_id` is given.
+#[tauri::command]
+pub async fn get_conversation_chat_links(
+    app_handle: AppHandle,
+    conversation_id: Option<String>,
+    chat_id: Option<String>,
+) -> Result<Vec<ConversationChatLink>, String> {
+    let storage = ConversationStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize conversation storage: {}", e))?;
+    let links = match (conversation_id, chat_id) {
+        (_, Some(chat_id)) => storage.get_chat_links(&chat_id, true),
+        (Some(conversation_id), None) => storage.get_chat_links(&conversation_id, false),
+        (None, None) => return Err("Provide a conversation id or a chat id".to_string()),
+    };
+    links.map_err(|e| format!("Failed to load conversation links: {}", e))
+}
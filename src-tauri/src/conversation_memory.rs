@@ -0,0 +1,262 @@
+// src-tauri/src/conversation_memory.rs
+// Conversation memory for live-meeting insights. Each conversation keeps a rolling summary and the
+// people, organizations and topics that came up (stored in the data module). After an insight is
+// generated the messages said since the last update are folded into the memory in the background,
+// and the next insight prompt starts from it, so suggestions build on the whole meeting rather
+// than only the recent context the frontend sends. Off-the-record messages never reach the memory;
+// redacting a range resets it.
+use crate::data::conversation::redaction::{is_redacted, transcript_lines};
+use crate::data::conversation::ConversationStorage;
+use crate::data::types::{ConversationMemory, ConversationMessage, MemoryEntity};
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+use crate::knowledge_graph::{normalize_entity_name, ExtractedEntity, ENTITY_TYPES};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+// New messages needed before the memory is updated again
+const MIN_NEW_MESSAGES: usize = 6;
+// New transcript read per update; a long backlog is folded in over several updates
+const WINDOW_TOKENS: usize = 3000;
+const MAX_SUMMARY_CHARS: usize = 1500;
+const MAX_ENTITIES: usize = 30;
+
+const MEMORY_SYSTEM_PROMPT: &str = "You maintain the running memory of a live conversation. Given the summary so far and \
+the next part of the transcript, rewrite the summary so it covers the whole conversation in at most 150 words: topics, \
+decisions, open questions and commitments. Also list the specific people, organizations, products, projects and places \
+named in the new part. Use names exactly as written. Reply with JSON only.";
+
+lazy_static::lazy_static! {
+    // Conversations whose memory is being updated, so insight bursts don't stack updates
+    static ref UPDATING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+fn memory_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "summary": { "type": "string" },
+            "entities": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "type": { "type": "string", "enum": ENTITY_TYPES },
+                    },
+                    "required": ["name", "type"],
+                },
+            },
+        },
+        "required": ["summary", "entities"],
+    })
+}
+
+/// Messages not yet folded into a memory covering up to `covered_until`, oldest first and no
+/// more than one window's worth of on-the-record text (always at least one message when there are
+/// any). Redacted messages don't count towards the window, so they can't stall the memory.
+fn unfolded(messages: &[ConversationMessage], covered_until: i64) -> &[ConversationMessage] {
+    let start = messages.iter().position(|m| m.timestamp > covered_until).unwrap_or(messages.len());
+    let mut tokens = 0;
+    let mut end = start;
+    for message in &messages[start..] {
+        if !is_redacted(message) {
+            tokens += estimate_tokens(&message.content) + 2;
+        }
+        if tokens > WINDOW_TOKENS && end > start {
+            break;
+        }
+        end += 1;
+    }
+    &messages[start..end]
+}
+
+/// Count the entities the model found in `text` into the memory's list. Names that don't appear
+/// in the text were made up and are dropped.
+fn merge_entities(mut entities: Vec<MemoryEntity>, found: Vec<ExtractedEntity>, text: &str, seen_at: i64) -> Vec<MemoryEntity> {
+    let text = text.to_lowercase();
+    let mut counted = HashSet::new();
+    for entity in found {
+        let name = entity.name.trim().to_string();
+        let key = normalize_entity_name(&name);
+        if key.chars().count() < 2 || name.chars().count() > 80 || !text.contains(&name.to_lowercase()) || !counted.insert(key.clone()) {
+            continue;
+        }
+        match entities.iter_mut().find(|e| normalize_entity_name(&e.name) == key) {
+            Some(existing) => {
+                existing.mentions += 1;
+                existing.last_seen = seen_at;
+            }
+            None => {
+                let entity_type = entity.entity_type.to_lowercase();
+                entities.push(MemoryEntity {
+                    name,
+                    entity_type: if ENTITY_TYPES.contains(&entity_type.as_str()) { entity_type } else { "topic".to_string() },
+                    mentions: 1,
+                    last_seen: seen_at,
+                });
+            }
+        }
+    }
+    entities.sort_by(|a, b| b.mentions.cmp(&a.mentions).then(b.last_seen.cmp(&a.last_seen)));
+    entities.truncate(MAX_ENTITIES);
+    entities
+}
+
+/// The memory as a prompt section, or None while it's still empty.
+pub fn render(memory: &ConversationMemory) -> Option<String> {
+    if memory.summary.trim().is_empty() && memory.entities.is_empty() {
+        return None;
+    }
+    let mut section = String::from("Conversation memory (covers the conversation so far):\n");
+    if !memory.summary.trim().is_empty() {
+        section.push_str(&format!("Summary: {}\n", memory.summary.trim()));
+    }
+    if !memory.entities.is_empty() {
+        let mentioned: Vec<String> = memory.entities.iter().map(|e| format!("{} ({})", e.name, e.entity_type)).collect();
+        section.push_str(&format!("Mentioned: {}\n", mentioned.join(", ")));
+    }
+    Some(section)
+}
+
+/// The stored memory of a conversation, if any.
+pub fn load(app_handle: &AppHandle, conversation_id: &str) -> Option<ConversationMemory> {
+    ConversationStorage::new(app_handle)
+        .and_then(|storage| storage.get_conversation_memory(conversation_id))
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to load conversation memory: {}", e);
+            None
+        })
+}
+
+/// Fold the messages said since the last update into the memory, once at least `min_new` of them
+/// are on the record. Returns the updated memory, or None when there was nothing to do.
+async fn update(app_handle: &AppHandle, conversation_id: &str, min_new: usize) -> Result<Option<ConversationMemory>, String> {
+    let (session, memory) = {
+        let storage = ConversationStorage::new(app_handle)
+            .map_err(|e| format!("Failed to initialize conversation storage: {}", e))?;
+        let Some(session) = storage.get_session(conversation_id).map_err(|e| format!("Failed to load conversation: {}", e))? else {
+            return Ok(None);
+        };
+        let memory = storage.get_conversation_memory(conversation_id)
+            .map_err(|e| format!("Failed to load conversation memory: {}", e))?
+            .unwrap_or_else(|| ConversationMemory { session_id: conversation_id.to_string(), ..Default::default() });
+        (session, memory)
+    };
+
+    let window = unfolded(&session.messages, memory.covered_until);
+    let fresh = window.iter().filter(|m| !is_redacted(m) && !m.content.trim().is_empty()).count();
+    if fresh < min_new.max(1) {
+        return Ok(None);
+    }
+    let covered_until = window.last().map_or(memory.covered_until, |m| m.timestamp);
+    let transcript = transcript_lines(window).join("\n");
+
+    let known: Vec<&str> = memory.entities.iter().map(|e| e.name.as_str()).collect();
+    let prompt = format!(
+        "Summary so far:\n{}\n\nAlready mentioned: {}\n\nNext part of the conversation:\n{}\n\nUpdate the memory.",
+        if memory.summary.is_empty() { "(none yet)" } else { memory.summary.as_str() },
+        if known.is_empty() { "(nothing yet)".to_string() } else { known.join(", ") },
+        transcript
+    );
+    let prompt_tokens = estimate_tokens(&prompt) + estimate_tokens(MEMORY_SYSTEM_PROMPT);
+    let model = crate::agent_models::resolve_agent_model(app_handle, "conversational_ai").await?;
+    let options = build_ollama_options("conversational_ai", None, prompt_tokens);
+    let reply = crate::ollama::generate_structured(
+        &model,
+        prompt,
+        Some(MEMORY_SYSTEM_PROMPT.to_string()),
+        memory_schema(),
+        Some(serde_json::Value::Object(options)),
+    ).await?;
+
+    let summary: String = reply["summary"].as_str().unwrap_or(&memory.summary).trim().chars().take(MAX_SUMMARY_CHARS).collect();
+    let found: Vec<ExtractedEntity> = serde_json::from_value(reply["entities"].clone()).unwrap_or_default();
+    crate::insight_budget::record(app_handle, Some(conversation_id), prompt_tokens + estimate_tokens(&summary));
+
+    let updated = ConversationMemory {
+        session_id: conversation_id.to_string(),
+        entities: merge_entities(memory.entities, found, &transcript, covered_until),
+        summary,
+        covered_until,
+        updated_at: chrono::Utc::now().timestamp_millis(),
+    };
+    ConversationStorage::new(app_handle)
+        .and_then(|mut storage| storage.save_conversation_memory(&updated))
+        .map_err(|e| format!("Failed to save conversation memory: {}", e))?;
+    println!("🧠 Folded {} messages into the memory of conversation {} ({} entities)", fresh, conversation_id, updated.entities.len());
+    let _ = app_handle.emit("conversation-memory-updated", &updated);
+    Ok(Some(updated))
+}
+
+/// Update a conversation's memory in the background if enough has been said since the last time.
+pub fn schedule_update(app_handle: &AppHandle, conversation_id: String) {
+    if !UPDATING.lock().map(|mut updating| updating.insert(conversation_id.clone())).unwrap_or(false) {
+        return;
+    }
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = update(&app_handle, &conversation_id, MIN_NEW_MESSAGES).await {
+            eprintln!("Conversation memory update failed for {}: {}", conversation_id, e);
+        }
+        if let Ok(mut updating) = UPDATING.lock() {
+            updating.remove(&conversation_id);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_conversation_memory(app_handle: AppHandle, conversation_id: String) -> Result<Option<ConversationMemory>, String> {
+    ConversationStorage::new(&app_handle)
+        .and_then(|storage| storage.get_conversation_memory(&conversation_id))
+        .map_err(|e| format!("Failed to load conversation memory: {}", e))
+}
+
+/// Fold everything said since the last update into the memory now.
+#[tauri::command]
+pub async fn update_conversation_memory(app_handle: AppHandle, conversation_id: String) -> Result<Option<ConversationMemory>, String> {
+    update(&app_handle, &conversation_id, 1).await?;
+    get_conversation_memory(app_handle, conversation_id)
+}
+
+/// Forget a conversation's memory; it's rebuilt from the transcript as insights are generated.
+#[tauri::command]
+pub fn reset_conversation_memory(app_handle: AppHandle, conversation_id: String) -> Result<(), String> {
+    ConversationStorage::new(&app_handle)
+        .and_then(|mut storage| storage.clear_conversation_memory(&conversation_id))
+        .map_err(|e| format!("Failed to reset conversation memory: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(timestamp: i64, content: &str) -> ConversationMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": format!("m{}", timestamp), "type": "user", "source": "loopback", "content": content,
+            "timestamp": timestamp, "confidence": 0.9
+        })).unwrap()
+    }
+
+    fn found(name: &str, entity_type: &str) -> ExtractedEntity {
+        serde_json::from_value(serde_json::json!({ "name": name, "type": entity_type })).unwrap()
+    }
+
+    #[test]
+    fn test_memory_folds_only_new_messages_and_counts_real_entities() {
+        let messages: Vec<ConversationMessage> = (1..=5).map(|t| message(t, "Dana will send the Acme contract")).collect();
+        assert_eq!(unfolded(&messages, 2).len(), 3);
+        assert!(unfolded(&messages, 5).is_empty());
+
+        let text = "Them: Dana will send the Acme contract\nMe: Good, loop in Priya";
+        let entities = merge_entities(vec![], vec![found("Dana", "person"), found("Acme", "company"), found("Zorblax", "product")], text, 10);
+        // "Zorblax" isn't in the transcript; an unknown type becomes a topic
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities.iter().find(|e| e.name == "Acme").unwrap().entity_type, "topic");
+
+        let entities = merge_entities(entities, vec![found("acme", "organization"), found("Priya", "person")], text, 20);
+        assert_eq!(entities[0].name, "Acme");
+        assert_eq!(entities[0].mentions, 2);
+        assert_eq!(entities.len(), 3);
+    }
+}
@@ -0,0 +1,265 @@
+// src-tauri/src/conversation_profiles.rs
+// Named conversation profiles that bundle the capture/transcription/insight pipeline settings,
+// so meetings, interviews and dictation can each run with their own tuning.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ProfileKind {
+    Meeting,
+    Interview,
+    Dictation,
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SummaryStyle {
+    Bullets,
+    Narrative,
+    ActionItems,
+    QuestionsAndAnswers,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationProfile {
+    pub id: String,
+    pub name: String,
+    pub kind: ProfileKind,
+    pub whisper_model: Option<String>, // None keeps the model from general settings
+    pub insight_model: Option<String>,
+    pub vad_aggressiveness: u8, // 0 (lenient) ..= 3 (aggressive)
+    pub insights_enabled: bool,
+    // Recognize speakers by voiceprint; only takes effect with the speakerFingerprinting setting on
+    #[serde(default = "default_diarization_enabled")]
+    pub diarization_enabled: bool,
+    pub insight_interval_secs: u64, // Least time between insights for one conversation; 0 for no limit
+    pub question_detection: bool,
+    pub summary_style: SummaryStyle,
+    pub built_in: bool,
+}
+
+fn default_diarization_enabled() -> bool {
+    true
+}
+
+impl ConversationProfile {
+    /// RMS gate for sending audio to Whisper; higher aggressiveness drops more quiet audio.
+    pub fn vad_rms_threshold(&self) -> f32 {
+        // 0.00305 matches the original int16 RMS > 100 check
+        match self.vad_aggressiveness {
+            0 => 0.0015,
+            1 => 0.00305,
+            2 => 0.006,
+            _ => 0.012,
+        }
+    }
+}
+
+fn built_in_profiles() -> Vec<ConversationProfile> {
+    vec![
+        ConversationProfile {
+            id: "meeting".to_string(),
+            name: "Meeting".to_string(),
+            kind: ProfileKind::Meeting,
+            whisper_model: None,
+            insight_model: None,
+            vad_aggressiveness: 1,
+            insights_enabled: true,
+            diarization_enabled: true,
+            insight_interval_secs: 30,
+            question_detection: false,
+            summary_style: SummaryStyle::ActionItems,
+            built_in: true,
+        },
+        ConversationProfile {
+            id: "interview".to_string(),
+            name: "Interview".to_string(),
+            kind: ProfileKind::Interview,
+            whisper_model: None,
+            insight_model: None,
+            vad_aggressiveness: 1,
+            insights_enabled: true,
+            diarization_enabled: true,
+            insight_interval_secs: 15,
+            question_detection: true,
+            summary_style: SummaryStyle::QuestionsAndAnswers,
+            built_in: true,
+        },
+        ConversationProfile {
+            id: "dictation".to_string(),
+            name: "Dictation".to_string(),
+            kind: ProfileKind::Dictation,
+            whisper_model: None,
+            insight_model: None,
+            vad_aggressiveness: 2,
+            insights_enabled: false,
+            diarization_enabled: false,
+            insight_interval_secs: 0,
+            question_detection: false,
+            summary_style: SummaryStyle::Narrative,
+            built_in: true,
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct StoredProfiles {
+    active_profile_id: Option<String>,
+    profiles: HashMap<String, ConversationProfile>,
+}
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_PROFILE: Arc<Mutex<Option<ConversationProfile>>> = Arc::new(Mutex::new(None));
+}
+
+fn get_profiles_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("conversation_profiles.json")
+}
+
+// Built-ins are always present; a saved copy with the same id overrides them
+fn load_stored() -> Result<StoredProfiles, String> {
+    let path = get_profiles_path().map_err(|e| format!("Failed to get profiles path: {}", e))?;
+    let mut stored: StoredProfiles = if path.exists() {
+        let json = fs::read_to_string(path).map_err(|e| format!("Failed to read profiles file: {}", e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to parse profiles: {}", e))?
+    } else {
+        StoredProfiles::default()
+    };
+
+    for profile in built_in_profiles() {
+        stored.profiles.entry(profile.id.clone()).or_insert(profile);
+    }
+    Ok(stored)
+}
+
+fn store(stored: &StoredProfiles) -> Result<(), String> {
+    let path = get_profiles_path().map_err(|e| format!("Failed to get profiles path: {}", e))?;
+    let json = serde_json::to_string_pretty(stored)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write profiles file: {}", e))
+}
+
+/// The profile driving the current pipeline, falling back to the saved selection or "meeting".
+pub fn active_profile() -> ConversationProfile {
+    if let Ok(active) = ACTIVE_PROFILE.lock() {
+        if let Some(profile) = active.as_ref() {
+            return profile.clone();
+        }
+    }
+
+    let profile = load_stored().ok().and_then(|stored| {
+        let id = stored.active_profile_id.clone().unwrap_or_else(|| "meeting".to_string());
+        stored.profiles.get(&id).cloned()
+    });
+    let profile = profile.unwrap_or_else(|| built_in_profiles().remove(0));
+
+    if let Ok(mut active) = ACTIVE_PROFILE.lock() {
+        *active = Some(profile.clone());
+    }
+    profile
+}
+
+pub fn activate_profile(app_handle: &AppHandle, profile_id: &str) -> Result<ConversationProfile, String> {
+    let mut stored = load_stored()?;
+    let profile = stored
+        .profiles
+        .get(profile_id)
+        .cloned()
+        .ok_or_else(|| format!("Conversation profile not found: {}", profile_id))?;
+
+    stored.active_profile_id = Some(profile_id.to_string());
+    store(&stored)?;
+
+    {
+        let mut active = ACTIVE_PROFILE.lock().map_err(|e| e.to_string())?;
+        *active = Some(profile.clone());
+    }
+
+    println!("🎛️ Conversation profile: {}", profile.name);
+    if let Err(e) = app_handle.emit("conversation-profile-changed", &profile) {
+        eprintln!("Failed to emit profile change: {}", e);
+    }
+    Ok(profile)
+}
+
+/// Rough question check used by interview mode to flag transcript segments.
+pub fn looks_like_question(text: &str) -> bool {
+    let trimmed = text.trim();
+    if trimmed.ends_with('?') {
+        return true;
+    }
+
+    let lower = trimmed.to_lowercase();
+    const QUESTION_STARTS: &[&str] = &[
+        "what", "why", "how", "when", "where", "who", "which", "can you", "could you", "would you",
+        "do you", "did you", "have you", "are you", "is there", "tell me about", "walk me through",
+    ];
+    QUESTION_STARTS.iter().any(|start| lower.starts_with(start))
+}
+
+#[tauri::command]
+pub async fn list_conversation_profiles() -> Result<Vec<ConversationProfile>, String> {
+    let mut profiles: Vec<ConversationProfile> = load_stored()?.profiles.into_values().collect();
+    profiles.sort_by(|a, b| b.built_in.cmp(&a.built_in).then(a.name.cmp(&b.name)));
+    Ok(profiles)
+}
+
+#[tauri::command]
+pub async fn save_conversation_profile(mut profile: ConversationProfile) -> Result<ConversationProfile, String> {
+    if profile.name.trim().is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    if profile.id.is_empty() {
+        profile.id = uuid::Uuid::new_v4().to_string();
+    }
+    profile.vad_aggressiveness = profile.vad_aggressiveness.min(3);
+    profile.built_in = built_in_profiles().iter().any(|p| p.id == profile.id);
+
+    let mut stored = load_stored()?;
+    stored.profiles.insert(profile.id.clone(), profile.clone());
+    store(&stored)?;
+
+    // Keep the running pipeline in sync when the active profile is edited
+    if let Ok(mut active) = ACTIVE_PROFILE.lock() {
+        if active.as_ref().map(|p| p.id == profile.id).unwrap_or(false) {
+            *active = Some(profile.clone());
+        }
+    }
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn delete_conversation_profile(profile_id: String) -> Result<(), String> {
+    if built_in_profiles().iter().any(|p| p.id == profile_id) {
+        return Err("Built-in profiles cannot be deleted".to_string());
+    }
+
+    let mut stored = load_stored()?;
+    if stored.profiles.remove(&profile_id).is_none() {
+        return Err(format!("Conversation profile not found: {}", profile_id));
+    }
+    if stored.active_profile_id.as_deref() == Some(profile_id.as_str()) {
+        stored.active_profile_id = None;
+        if let Ok(mut active) = ACTIVE_PROFILE.lock() {
+            *active = None;
+        }
+    }
+    store(&stored)
+}
+
+#[tauri::command]
+pub async fn get_active_conversation_profile() -> Result<ConversationProfile, String> {
+    Ok(active_profile())
+}
+
+#[tauri::command]
+pub async fn set_active_conversation_profile(
+    app_handle: AppHandle,
+    profile_id: String,
+) -> Result<ConversationProfile, String> {
+    activate_profile(&app_handle, &profile_id)
+}
@@ -0,0 +1,331 @@
+// src-tauri/src/conversation_templates.rs
+// Structured note templates (1:1, standup, interview or user-defined). A template is picked when a
+// session starts; when the session ends the transcript is summarized into the template's sections
+// and stored on the conversation as a 'template' insight.
+use crate::data::conversation::{redaction, ConversationStorage};
+use crate::data::types::{
+    ConversationInsight, ConversationSession, ConversationTemplate, FilledSection, FilledTemplate, InsightType, TemplateSection
+};
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+use tauri::{AppHandle, Emitter};
+
+const TEMPLATE_MODEL: &str = "gemma3:1b-it-qat";
+// Keep the newest part of long transcripts so the model isn't overrun
+const MAX_TRANSCRIPT_TOKENS: usize = 6000;
+const MAX_ITEMS_PER_SECTION: usize = 12;
+
+const TEMPLATE_SYSTEM_PROMPT: &str = "You fill in structured notes from a conversation transcript. For each requested section, \
+list short, specific points taken from the transcript. Leave a section empty when nothing fits it and do not invent details. \
+Reply with JSON only: an object mapping each section key to an array of strings.";
+
+fn section(key: &str, title: &str, instructions: &str) -> TemplateSection {
+    TemplateSection {
+        key: key.to_string(),
+        title: title.to_string(),
+        instructions: instructions.to_string(),
+    }
+}
+
+fn built_in_templates() -> Vec<ConversationTemplate> {
+    vec![
+        ConversationTemplate {
+            id: "one_on_one".to_string(),
+            name: "1:1".to_string(),
+            description: "Manager/report check-in".to_string(),
+            sections: vec![
+                section("feedback", "Feedback", "Feedback given or received, and who it was for"),
+                section("decisions", "Decisions", "Decisions or agreements reached"),
+                section("blockers", "Blockers", "Problems or risks raised"),
+                section("action_items", "Action items", "Follow-ups, with an owner where mentioned"),
+            ],
+            built_in: true,
+        },
+        ConversationTemplate {
+            id: "standup".to_string(),
+            name: "Standup".to_string(),
+            description: "Daily team sync".to_string(),
+            sections: vec![
+                section("done", "Done", "Work people reported finishing"),
+                section("planned", "Planned", "Work people plan to do next"),
+                section("blockers", "Blockers", "Anything blocking progress, and who is blocked"),
+            ],
+            built_in: true,
+        },
+        ConversationTemplate {
+            id: "interview".to_string(),
+            name: "Interview".to_string(),
+            description: "Candidate interview".to_string(),
+            sections: vec![
+                section("background", "Background", "The candidate's relevant experience"),
+                section("strengths", "Strengths", "Evidence of strengths from their answers"),
+                section("concerns", "Concerns", "Gaps, weak answers or red flags"),
+                section("questions", "Questions asked", "Questions the candidate asked"),
+            ],
+            built_in: true,
+        },
+    ]
+}
+
+/// Built-ins plus custom templates; a saved template with a built-in's id overrides it.
+pub fn all_templates(storage: &ConversationStorage) -> Result<Vec<ConversationTemplate>, String> {
+    let custom = storage.list_templates().map_err(|e| format!("Failed to load templates: {}", e))?;
+    let mut templates: Vec<ConversationTemplate> = built_in_templates()
+        .into_iter()
+        .filter(|built_in| !custom.iter().any(|t| t.id == built_in.id))
+        .collect();
+    templates.extend(custom);
+    Ok(templates)
+}
+
+fn find_template(storage: &ConversationStorage, template_id: &str) -> Result<ConversationTemplate, String> {
+    all_templates(storage)?
+        .into_iter()
+        .find(|t| t.id == template_id)
+        .ok_or_else(|| format!("Template not found: {}", template_id))
+}
+
+fn open_storage(app_handle: &AppHandle) -> Result<ConversationStorage, String> {
+    ConversationStorage::new(app_handle).map_err(|e| format!("Failed to initialize conversation storage: {}", e))
+}
+
+fn transcript(session: &ConversationSession) -> String {
+    let lines = redaction::transcript_lines(&session.messages);
+    let mut kept = Vec::new();
+    let mut tokens = 0;
+    for line in lines.iter().rev() {
+        let line_tokens = estimate_tokens(line);
+        if tokens + line_tokens > MAX_TRANSCRIPT_TOKENS {
+            break;
+        }
+        tokens += line_tokens;
+        kept.push(line.as_str());
+    }
+    kept.reverse();
+    kept.join("\n")
+}
+
+/// Map the model's JSON reply onto the template's sections, ignoring keys the template doesn't have.
+pub fn parse_filled(response: &str, template: &ConversationTemplate) -> FilledTemplate {
+    let parsed: serde_json::Map<String, serde_json::Value> = match (response.find('{'), response.rfind('}')) {
+        (Some(start), Some(end)) if end > start => serde_json::from_str(&response[start..=end]).unwrap_or_default(),
+        _ => serde_json::Map::new(),
+    };
+
+    let sections = template.sections
+        .iter()
+        .map(|section| {
+            let items = parsed
+                .get(&section.key)
+                .and_then(|value| value.as_array())
+                .map(|items| {
+                    items.iter()
+                        .filter_map(|item| item.as_str())
+                        .map(|item| item.trim().trim_start_matches("- ").to_string())
+                        .filter(|item| !item.is_empty())
+                        .take(MAX_ITEMS_PER_SECTION)
+                        .collect()
+                })
+                .unwrap_or_default();
+            FilledSection { key: section.key.clone(), title: section.title.clone(), items }
+        })
+        .collect();
+
+    FilledTemplate {
+        template_id: template.id.clone(),
+        template_name: template.name.clone(),
+        sections,
+    }
+}
+
+fn render_text(filled: &FilledTemplate) -> String {
+    let mut text = String::new();
+    for section in &filled.sections {
+        text.push_str(&format!("{}:\n", section.title));
+        if section.items.is_empty() {
+            text.push_str("- (none)\n");
+        }
+        for item in &section.items {
+            text.push_str(&format!("- {}\n", item));
+        }
+    }
+    text.trim_end().to_string()
+}
+
+/// Summarize the session into the template and store it, replacing any earlier fill.
+pub async fn fill_template(
+    app_handle: &AppHandle,
+    session: &ConversationSession,
+    template: &ConversationTemplate,
+) -> Result<ConversationInsight, String> {
+    let transcript = transcript(session);
+    if transcript.is_empty() {
+        return Err("The conversation has no transcript to summarize".to_string());
+    }
+
+    let sections: Vec<String> = template.sections
+        .iter()
+        .map(|s| format!("- \"{}\" ({}): {}", s.key, s.title, s.instructions))
+        .collect();
+    let prompt = format!(
+        "Template: {}\nSections:\n{}\n\nTranscript:\n{}\n\nFill in the sections.",
+        template.name, sections.join("\n"), transcript
+    );
+    let options = build_ollama_options("templates", None, estimate_tokens(&prompt) + estimate_tokens(TEMPLATE_SYSTEM_PROMPT));
+    let model = crate::agent_models::resolve_helper_model(TEMPLATE_MODEL).await;
+    let response = crate::ollama::generate_completion(
+        &model,
+        prompt,
+        Some(TEMPLATE_SYSTEM_PROMPT.to_string()),
+        Some(serde_json::Value::Object(options)),
+    ).await?;
+
+    let filled = parse_filled(&response, template);
+    let insight = ConversationInsight {
+        id: format!("template-{}", session.id),
+        text: render_text(&filled),
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        context_length: estimate_tokens(&transcript) as i32,
+        insight_type: InsightType::Template,
+        template: Some(filled),
+    };
+    open_storage(app_handle)?
+        .save_conversation_insight(&session.id, insight.clone())
+        .map_err(|e| format!("Failed to save filled template: {}", e))?;
+
+    let _ = app_handle.emit("conversation-template-filled", serde_json::json!({
+        "conversationId": session.id,
+        "insight": &insight,
+    }));
+    println!("📝 Filled {} template for conversation {}", template.name, session.id);
+    let link = format!("enteract://conversation/{}", session.id);
+    crate::notifications::notify(
+        app_handle,
+        crate::notifications::BackgroundNotification::new(
+            "summary",
+            format!("{} notes are ready", template.name),
+            format!("\"{}\" has been summarized.", session.name),
+            Some(link.clone()),
+        )
+        .with_action("open-notes", "View notes", link),
+    );
+    Ok(insight)
+}
+
+/// Fill the session's template in the background, if it has one. Called when a session ends.
+pub fn queue_template_fill(app_handle: &AppHandle, session: ConversationSession) {
+    let Some(template_id) = session.template_id.clone() else {
+        return;
+    };
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let template = match open_storage(&handle).and_then(|storage| find_template(&storage, &template_id)) {
+            Ok(template) => template,
+            Err(e) => {
+                eprintln!("Skipping template fill for {}: {}", session.id, e);
+                return;
+            }
+        };
+        if let Err(e) = fill_template(&handle, &session, &template).await {
+            eprintln!("Template fill failed for {}: {}", session.id, e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_conversation_templates(app_handle: AppHandle) -> Result<Vec<ConversationTemplate>, String> {
+    all_templates(&open_storage(&app_handle)?)
+}
+
+#[tauri::command]
+pub async fn save_conversation_template(
+    app_handle: AppHandle,
+    template: ConversationTemplate,
+) -> Result<ConversationTemplate, String> {
+    if template.name.trim().is_empty() {
+        return Err("Template name is empty".to_string());
+    }
+    if template.sections.is_empty() {
+        return Err("A template needs at least one section".to_string());
+    }
+    for (index, section) in template.sections.iter().enumerate() {
+        if section.key.trim().is_empty() || section.title.trim().is_empty() {
+            return Err(format!("Section {} needs a key and a title", index + 1));
+        }
+        if template.sections[..index].iter().any(|s| s.key == section.key) {
+            return Err(format!("Duplicate section key: {}", section.key));
+        }
+    }
+
+    let mut template = template;
+    if template.id.trim().is_empty() {
+        template.id = uuid::Uuid::new_v4().to_string();
+    }
+    template.built_in = false;
+    open_storage(&app_handle)?
+        .save_template(&template)
+        .map_err(|e| format!("Failed to save template: {}", e))?;
+    Ok(template)
+}
+
+/// Deleting an override of a built-in template restores the built-in.
+#[tauri::command]
+pub async fn delete_conversation_template(app_handle: AppHandle, template_id: String) -> Result<bool, String> {
+    open_storage(&app_handle)?
+        .delete_template(&template_id)
+        .map_err(|e| format!("Failed to delete template: {}", e))
+}
+
+/// Pick the note template for a session, usually right when it starts; None clears it.
+#[tauri::command]
+pub async fn set_conversation_template(
+    app_handle: AppHandle,
+    session_id: String,
+    template_id: Option<String>,
+) -> Result<(), String> {
+    let mut storage = open_storage(&app_handle)?;
+    if let Some(template_id) = &template_id {
+        find_template(&storage, template_id)?;
+    }
+    storage
+        .set_session_template(&session_id, template_id.as_deref())
+        .map_err(|e| format!("Failed to set template for session {}: {}", session_id, e))
+}
+
+/// Fill a template now, e.g. to retry or to try a different template on a finished conversation.
+#[tauri::command]
+pub async fn fill_conversation_template(
+    app_handle: AppHandle,
+    session_id: String,
+    template_id: Option<String>,
+) -> Result<ConversationInsight, String> {
+    let (session, template) = {
+        let storage = open_storage(&app_handle)?;
+        let session = storage
+            .get_session(&session_id)
+            .map_err(|e| format!("Failed to load conversation: {}", e))?
+            .ok_or_else(|| format!("Conversation not found: {}", session_id))?;
+        let template_id = template_id
+            .or_else(|| session.template_id.clone())
+            .ok_or("The conversation has no template; pick one first")?;
+        let template = find_template(&storage, &template_id)?;
+        (session, template)
+    };
+    fill_template(&app_handle, &session, &template).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_filled_keeps_template_sections() {
+        let template = built_in_templates().into_iter().find(|t| t.id == "standup").unwrap();
+        let response = "Sure!\n```json\n{\"done\": [\"- Shipped login\", \"\"], \"blockers\": [\"Waiting on API keys\"], \"mood\": [\"good\"]}\n```";
+        let filled = parse_filled(response, &template);
+
+        assert_eq!(filled.sections.len(), 3);
+        assert_eq!(filled.sections[0].items, vec!["Shipped login"]);
+        assert!(filled.sections[1].items.is_empty());
+        assert_eq!(filled.sections[2].items, vec!["Waiting on API keys"]);
+    }
+}
@@ -0,0 +1,278 @@
+// Database backups. The database is copied with SQLite's online backup API, so backups are consistent
+// while the app keeps writing, into a `backups` folder next to it. Scheduled backups run in the
+// background at a configurable interval; each kind of backup keeps only its newest copies. Every
+// backup is integrity-checked before it's kept and again before it's restored.
+
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::AppHandle;
+
+const BACKUP_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "enteract_data-";
+// Let startup finish before the first scheduled check
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(120);
+const CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    pub keep: usize, // Newest backups kept per kind
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub kind: String, // "scheduled", "manual" or "pre-restore"
+    pub created_at: i64, // Unix seconds
+    pub size_bytes: u64,
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("backup_settings.json")
+}
+
+fn load_settings() -> BackupSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn backup_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::data::paths::user_data_dir(app_handle)?.join(BACKUP_DIR);
+    crate::data::paths::ensure_private_dir(&dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    Ok(dir)
+}
+
+fn file_name_for(kind: &str, created_at: chrono::DateTime<chrono::Utc>) -> String {
+    format!("{}{}-{}.db", BACKUP_PREFIX, created_at.format("%Y%m%d-%H%M%S"), kind)
+}
+
+// enteract_data-20261016-101500-scheduled.db -> (kind, created_at)
+fn parse_file_name(file_name: &str) -> Option<(String, i64)> {
+    let rest = file_name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(".db")?;
+    let created_at = chrono::NaiveDateTime::parse_from_str(rest.get(..15)?, "%Y%m%d-%H%M%S").ok()?;
+    let kind = rest.get(15..)?.strip_prefix('-')?;
+    if kind.is_empty() {
+        return None;
+    }
+    Some((kind.to_string(), created_at.and_utc().timestamp()))
+}
+
+/// Backups in `dir`, newest first.
+fn list_in(dir: &Path) -> Vec<BackupInfo> {
+    let mut backups: Vec<BackupInfo> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let (kind, created_at) = parse_file_name(&file_name)?;
+            Some(BackupInfo {
+                file_name,
+                kind,
+                created_at,
+                size_bytes: entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.file_name.cmp(&a.file_name)));
+    backups
+}
+
+/// Run SQLite's integrity check on a backup file without changing it.
+pub fn verify(path: &Path) -> Result<(), String> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+    let result: String = connection
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Integrity check failed to run: {}", e))?;
+    if result == "ok" {
+        Ok(())
+    } else {
+        Err(format!("Backup is corrupt: {}", result))
+    }
+}
+
+/// Copy the database at `db_path` into `dir` as a verified backup of `kind`, then drop the oldest
+/// backups of that kind beyond `keep`.
+fn back_up(db_path: &Path, dir: &Path, kind: &str, keep: usize) -> Result<BackupInfo, String> {
+    let file_name = file_name_for(kind, chrono::Utc::now());
+    let path = dir.join(&file_name);
+    // Written under a temporary name so a half-written or corrupt copy is never listed
+    let partial = dir.join(format!("{}.partial", file_name));
+
+    let source = Connection::open(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+    source
+        .backup(DatabaseName::Main, &partial, None::<fn(Progress)>)
+        .map_err(|e| format!("Backup failed: {}", e))?;
+    if let Err(e) = verify(&partial) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to save backup: {}", e))?;
+
+    for old in list_in(dir).into_iter().filter(|backup| backup.kind == kind).skip(keep.max(1)) {
+        if let Err(e) = fs::remove_file(dir.join(&old.file_name)) {
+            eprintln!("Failed to remove old backup {}: {}", old.file_name, e);
+        }
+    }
+
+    let size_bytes = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+    println!("💾 Backed up the database to {} ({} bytes)", file_name, size_bytes);
+    let (kind, created_at) = parse_file_name(&file_name).ok_or("Backup was saved under an unexpected name")?;
+    Ok(BackupInfo { file_name, kind, created_at, size_bytes })
+}
+
+fn create_backup(app_handle: &AppHandle, kind: &str) -> Result<BackupInfo, String> {
+    let db_path = crate::data::paths::database_path(app_handle)?;
+    back_up(&db_path, &backup_dir(app_handle)?, kind, load_settings().keep)
+}
+
+/// Back up on the configured interval for as long as the app runs.
+pub fn start_backup_scheduler(app_handle: AppHandle) {
+    if crate::data::paths::in_memory_database_requested() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+        loop {
+            let settings = load_settings();
+            if settings.enabled {
+                let last = backup_dir(&app_handle)
+                    .map(|dir| list_in(&dir))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|backup| backup.kind == "scheduled")
+                    .map(|backup| backup.created_at);
+                let due = match last {
+                    Some(last) => chrono::Utc::now().timestamp() - last >= settings.interval_hours.max(1) as i64 * 3600,
+                    None => true,
+                };
+                if due {
+                    let handle = app_handle.clone();
+                    let result = tauri::async_runtime::spawn_blocking(move || create_backup(&handle, "scheduled")).await;
+                    match result {
+                        Ok(Err(e)) => eprintln!("❌ Scheduled database backup failed: {}", e),
+                        Err(e) => eprintln!("❌ Scheduled database backup panicked: {}", e),
+                        Ok(Ok(_)) => {}
+                    }
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn create_backup_now(app_handle: AppHandle) -> Result<BackupInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || create_backup(&app_handle, "manual"))
+        .await
+        .map_err(|e| format!("Backup task failed: {}", e))?
+}
+
+#[tauri::command]
+pub fn list_backups(app_handle: AppHandle) -> Result<Vec<BackupInfo>, String> {
+    Ok(list_in(&backup_dir(&app_handle)?))
+}
+
+/// Replace the database with a backup from the backups folder. The current database is backed up
+/// first (as "pre-restore") so the restore can be undone.
+#[tauri::command]
+pub async fn restore_backup(app_handle: AppHandle, file: String) -> Result<BackupInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        // Only a plain file name from the backups folder; never an arbitrary path
+        if parse_file_name(&file).is_none() || file.contains(['/', '\\']) {
+            return Err(format!("Not a backup file: {}", file));
+        }
+        let dir = backup_dir(&app_handle)?;
+        let path = dir.join(&file);
+        if !path.is_file() {
+            return Err(format!("Backup not found: {}", file));
+        }
+        verify(&path)?;
+
+        let safety = create_backup(&app_handle, "pre-restore")?;
+        let db_path = crate::data::paths::database_path(&app_handle)?;
+        let mut target = Connection::open(&db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+        target
+            .restore(DatabaseName::Main, &path, None::<fn(Progress)>)
+            .map_err(|e| format!("Restore failed (the previous data is in {}): {}", safety.file_name, e))?;
+        println!("♻️ Restored the database from {} (previous data saved as {})", file, safety.file_name);
+        Ok(safety)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn get_backup_settings() -> Result<BackupSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn save_backup_settings(settings: BackupSettings) -> Result<(), String> {
+    if settings.interval_hours == 0 || settings.keep == 0 {
+        return Err("Backup interval and retention must be at least 1".to_string());
+    }
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("💾 Saved backup settings: every {}h, keep {}", settings.interval_hours, settings.keep);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backups_are_verified_and_rotated() {
+        let dir = std::env::temp_dir().join(format!("enteract-backup-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("source.db");
+        let backups = dir.join("backups");
+        fs::create_dir_all(&backups).unwrap();
+        let connection = Connection::open(&db_path).unwrap();
+        connection.execute_batch("CREATE TABLE notes (text TEXT); INSERT INTO notes VALUES ('kept');").unwrap();
+
+        // Older scheduled backups that rotation should drop
+        for name in ["enteract_data-20200101-000000-scheduled.db", "enteract_data-20200102-000000-scheduled.db"] {
+            fs::copy(&db_path, backups.join(name)).unwrap();
+        }
+        let info = back_up(&db_path, &backups, "scheduled", 2).unwrap();
+
+        let listed = list_in(&backups);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].file_name, info.file_name);
+        assert_eq!(listed[1].file_name, "enteract_data-20200102-000000-scheduled.db");
+        verify(&backups.join(&info.file_name)).unwrap();
+        let copy = Connection::open(backups.join(&info.file_name)).unwrap();
+        let text: String = copy.query_row("SELECT text FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(text, "kept");
+
+        fs::write(backups.join("enteract_data-20200103-000000-manual.db"), b"not a database").unwrap();
+        assert!(verify(&backups.join("enteract_data-20200103-000000-manual.db")).is_err());
+        assert!(parse_file_name("../enteract_data.db").is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
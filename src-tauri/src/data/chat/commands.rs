@@ -1,6 +1,7 @@
 // Tauri commands for chat storage operations
 use tauri::{AppHandle, command};
-use crate::data::types::{SaveChatsPayload, LoadChatsResponse};
+use crate::data::settings::SettingsStorage;
+use crate::data::types::{SaveChatsPayload, LoadChatsResponse, MessageVariant, MessageProvenance, BookmarkedMessage, BookmarkFilter, ChatTokenUsage};
 use super::storage::ChatStorage;
 
 #[command]
@@ -9,8 +10,16 @@ pub fn save_chat_sessions(
     payload: SaveChatsPayload,
 ) -> Result<(), String> {
     match ChatStorage::new(&app_handle) {
-        Ok(mut storage) => storage.save_chat_sessions(payload)
-            .map_err(|e| format!("Failed to save chat sessions: {}", e)),
+        Ok(mut storage) => {
+            // Collect code blocks and graph sources before the payload is consumed; both are indexed once the save succeeds
+            let snippets = crate::snippet_library::collect_snippet_candidates(&payload.chats);
+            let knowledge_sources = payload.chats.iter().map(crate::knowledge_graph::chat_source).collect();
+            storage.save_chat_sessions(payload)
+                .map_err(|e| format!("Failed to save chat sessions: {}", e))?;
+            crate::snippet_library::index_snippets(&app_handle, snippets);
+            crate::knowledge_graph::queue_sources(&app_handle, knowledge_sources);
+            Ok(())
+        }
         Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
     }
 }
@@ -22,4 +31,121 @@ pub fn load_chat_sessions(app_handle: AppHandle) -> Result<LoadChatsResponse, St
             .map_err(|e| format!("Failed to load chat sessions: {}", e)),
         Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
     }
-}
\ No newline at end of file
+}
+
+#[command]
+pub fn get_message_variants(
+    app_handle: AppHandle,
+    session_id: String,
+    message_id: i32,
+) -> Result<Vec<MessageVariant>, String> {
+    match ChatStorage::new(&app_handle) {
+        Ok(storage) => storage.get_message_variants(&session_id, message_id)
+            .map_err(|e| format!("Failed to load message variants: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+#[command]
+pub fn select_message_variant(
+    app_handle: AppHandle,
+    variant_id: String,
+) -> Result<MessageVariant, String> {
+    match ChatStorage::new(&app_handle) {
+        Ok(mut storage) => storage.select_message_variant(&variant_id)
+            .map_err(|e| format!("Failed to select message variant: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+/// Everything recorded about how a message was produced: model tag, provider, resolved options,
+/// RAG chunks and screen context, plus the selected variant if one replaced the original text.
+#[command]
+pub fn get_message_provenance(
+    app_handle: AppHandle,
+    message_id: i32,
+) -> Result<MessageProvenance, String> {
+    match ChatStorage::new(&app_handle) {
+        Ok(storage) => storage.get_message_provenance(message_id)
+            .map_err(|e| format!("Failed to load message provenance: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+/// Bookmark a message, or remove its bookmark. Returns whether it is bookmarked now.
+#[command]
+pub fn toggle_bookmark(
+    app_handle: AppHandle,
+    message_id: i32,
+) -> Result<bool, String> {
+    match ChatStorage::new(&app_handle) {
+        Ok(mut storage) => storage.toggle_bookmark(message_id)
+            .map_err(|e| format!("Failed to toggle bookmark: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+#[command]
+pub fn toggle_message_reaction(
+    app_handle: AppHandle,
+    message_id: i32,
+    reaction: String,
+) -> Result<Vec<String>, String> {
+    let reaction = reaction.trim();
+    if reaction.is_empty() {
+        return Err("Reaction is empty".to_string());
+    }
+    match ChatStorage::new(&app_handle) {
+        Ok(mut storage) => storage.toggle_message_reaction(message_id, reaction)
+            .map_err(|e| format!("Failed to toggle reaction: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+#[command]
+pub fn list_bookmarked_messages(
+    app_handle: AppHandle,
+    filter: Option<BookmarkFilter>,
+) -> Result<Vec<BookmarkedMessage>, String> {
+    match ChatStorage::new(&app_handle) {
+        Ok(storage) => storage.list_bookmarked_messages(&filter.unwrap_or_default())
+            .map_err(|e| format!("Failed to load bookmarks: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+#[command]
+pub fn get_chat_token_usage(app_handle: AppHandle, chat_id: String) -> Result<ChatTokenUsage, String> {
+    match ChatStorage::new(&app_handle) {
+        Ok(storage) => storage.get_token_usage(&chat_id)
+            .map_err(|e| format!("Failed to load token usage: {}", e)),
+        Err(e) => Err(format!("Failed to initialize chat storage: {}", e))
+    }
+}
+
+const CONTEXT_BUDGET_KEY: &str = "context_budget_tokens";
+
+/// Most tokens an agent request may carry (system prompt, history and the new prompt), if capped.
+pub fn context_budget(app_handle: &AppHandle) -> Option<u32> {
+    SettingsStorage::new(app_handle)
+        .and_then(|storage| storage.get(CONTEXT_BUDGET_KEY))
+        .ok()
+        .flatten()
+}
+
+#[command]
+pub fn get_context_budget(app_handle: AppHandle) -> Result<Option<u32>, String> {
+    Ok(context_budget(&app_handle))
+}
+
+/// Cap the context of agent requests; None removes the cap. Older history is dropped to fit.
+#[command]
+pub fn set_context_budget(app_handle: AppHandle, tokens: Option<u32>) -> Result<(), String> {
+    if tokens.is_some_and(|tokens| tokens < 256) {
+        return Err("The context budget must be at least 256 tokens".to_string());
+    }
+    let mut storage = SettingsStorage::new(&app_handle).map_err(|e| format!("Failed to open settings: {}", e))?;
+    storage
+        .set(CONTEXT_BUDGET_KEY, &tokens)
+        .map_err(|e| format!("Failed to save context budget: {}", e))
+}
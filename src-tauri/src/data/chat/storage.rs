@@ -1,11 +1,13 @@
 // SQLite storage implementation for chat sessions
 use rusqlite::{Connection, Result, params};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use crate::data::types::{
     ChatSession, ChatMessage, MessageAttachment, ThinkingProcess, ThinkingStep, MessageMetadata,
-    SaveChatsPayload, LoadChatsResponse
+    SaveChatsPayload, LoadChatsResponse, MessageVariant, CodeSnippet, MessageProvenance,
+    BookmarkedMessage, BookmarkFilter, MessageSearchHit, ChatTokenUsage
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::data::paths::open_database;
 
 pub struct ChatStorage {
     connection: Connection,
@@ -17,48 +19,13 @@ impl ChatStorage {
             rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
             Some(e)
         ))?;
-        
-        // Ensure parent directory exists
-        if let Some(parent) = db_path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)
-                    .map_err(|e| rusqlite::Error::SqliteFailure(
-                        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
-                        Some(format!("Failed to create directory: {}", e))
-                    ))?;
-            }
-        }
+        Self::open(&db_path)
+    }
 
-        let connection = Connection::open(&db_path)?;
-        
-        // Configure SQLite for optimal performance using safer approach
-        connection.execute("PRAGMA foreign_keys = ON", params![]).map_err(|e| {
-            println!("⚠️ Warning: Failed to set foreign_keys: {}", e);
-            e
-        })?;
-        
-        // Set journal mode with proper handling (WAL returns a result, so use query_row)
-        match connection.query_row("PRAGMA journal_mode = WAL", params![], |row| row.get::<_, String>(0)) {
-            Ok(mode) => {
-                if mode.to_lowercase() == "wal" {
-                    println!("✅ WAL mode enabled successfully");
-                } else {
-                    println!("ℹ️ Journal mode is: {} (WAL may not be available)", mode);
-                }
-            }
-            Err(e) => println!("⚠️ Warning: Could not set journal mode: {}", e),
-        }
-        
-        // Set other pragmas with execute (they don't necessarily return meaningful results)
-        connection.execute("PRAGMA synchronous = NORMAL", params![]).ok();
-        connection.execute("PRAGMA cache_size = 10000", params![]).ok();
-        connection.execute("PRAGMA temp_store = memory", params![]).ok();
-        
-        println!("✅ SQLite configuration applied successfully");
-        
-        let mut storage = Self { connection };
+    /// Chat storage on the database at `db_path`, creating the chat tables if needed.
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let mut storage = Self { connection: open_database(db_path)? };
         storage.initialize_chat_tables()?;
-        
         Ok(storage)
     }
 
@@ -140,11 +107,56 @@ impl ChatStorage {
                 analysis_types TEXT, -- JSON array stored as text
                 search_queries TEXT, -- JSON array stored as text
                 sources TEXT, -- JSON array stored as text
+                model_tag TEXT,
+                provider TEXT,
+                generation_options TEXT, -- JSON object stored as text
+                rag_chunk_ids TEXT, -- JSON array stored as text
+                screen_context_id TEXT,
+                prompt_tokens INTEGER,
+                completion_tokens INTEGER,
                 FOREIGN KEY (message_id) REFERENCES chat_messages(id) ON DELETE CASCADE
             );
 
+            -- Message variants (no foreign key: chat saves replace every message row)
+            CREATE TABLE IF NOT EXISTS message_variants (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                message_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                text TEXT NOT NULL,
+                model TEXT,
+                agent_type TEXT,
+                created_at TEXT NOT NULL,
+                is_selected INTEGER NOT NULL CHECK(is_selected IN (0, 1))
+            );
+
+            -- Snippet library: code blocks extracted from assistant messages, kept after the chat is gone
+            CREATE TABLE IF NOT EXISTS code_snippets (
+                id TEXT PRIMARY KEY,
+                content_hash TEXT NOT NULL UNIQUE,
+                language TEXT NOT NULL,
+                code TEXT NOT NULL,
+                description TEXT NOT NULL,
+                description_generated INTEGER NOT NULL DEFAULT 0 CHECK(description_generated IN (0, 1)),
+                chat_id TEXT,
+                message_id INTEGER,
+                created_at TEXT NOT NULL,
+                use_count INTEGER NOT NULL DEFAULT 0
+            );
+
+            -- Bookmarks and reactions (no foreign key: chat saves replace every message row)
+            CREATE TABLE IF NOT EXISTS message_marks (
+                message_id INTEGER PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                bookmarked_at TEXT, -- NULL when not bookmarked
+                reactions TEXT NOT NULL DEFAULT '[]' -- JSON array stored as text
+            );
+
             -- Indexes for performance
+            CREATE INDEX IF NOT EXISTS idx_message_marks_bookmarked ON message_marks(bookmarked_at);
+            CREATE INDEX IF NOT EXISTS idx_code_snippets_language ON code_snippets(language);
             CREATE INDEX IF NOT EXISTS idx_chat_sessions_updated_desc ON chat_sessions(updated_at DESC);
+            CREATE INDEX IF NOT EXISTS idx_message_variants_message ON message_variants(session_id, message_id);
             CREATE INDEX IF NOT EXISTS idx_chat_messages_session_timestamp ON chat_messages(session_id, timestamp);
             CREATE INDEX IF NOT EXISTS idx_message_attachments_message ON message_attachments(message_id);
             CREATE INDEX IF NOT EXISTS idx_thinking_processes_message ON thinking_processes(message_id);
@@ -152,6 +164,14 @@ impl ChatStorage {
             CREATE INDEX IF NOT EXISTS idx_message_metadata_message ON message_metadata(message_id);
         "#)?;
 
+        // Provenance columns for databases created before they existed
+        for column in ["model_tag", "provider", "generation_options", "rag_chunk_ids", "screen_context_id"] {
+            let _ = self.connection.execute(&format!("ALTER TABLE message_metadata ADD COLUMN {} TEXT", column), params![]);
+        }
+        for column in ["prompt_tokens", "completion_tokens"] {
+            let _ = self.connection.execute(&format!("ALTER TABLE message_metadata ADD COLUMN {} INTEGER", column), params![]);
+        }
+
         Ok(())
     }
 
@@ -163,89 +183,205 @@ impl ChatStorage {
 
         let sessions_count = payload.chats.len();
         for session in payload.chats {
-            // Insert session
+            Self::insert_chat_session(&tx, session)?;
+        }
+
+        // Drop variants whose chat no longer exists, and marks whose message no longer exists
+        tx.execute("DELETE FROM message_variants WHERE session_id NOT IN (SELECT id FROM chat_sessions)", params![])?;
+        tx.execute("DELETE FROM message_marks WHERE message_id NOT IN (SELECT id FROM chat_messages)", params![])?;
+
+        tx.commit()?;
+        println!("✅ Saved {} chat sessions to SQLite", sessions_count);
+        Ok(())
+    }
+
+    fn insert_chat_session(tx: &Connection, session: ChatSession) -> Result<()> {
+        // Insert session
+        tx.execute(
+            "INSERT INTO chat_sessions (id, title, created_at, updated_at, model_id) VALUES (?, ?, ?, ?, ?)",
+            params![session.id, session.title, session.created_at, session.updated_at, session.model_id]
+        )?;
+
+        // Insert messages and related data
+        for message in session.history {
+            // Insert main message
             tx.execute(
-                "INSERT INTO chat_sessions (id, title, created_at, updated_at, model_id) VALUES (?, ?, ?, ?, ?)",
-                params![session.id, session.title, session.created_at, session.updated_at, session.model_id]
+                "INSERT INTO chat_messages (id, session_id, text, sender, timestamp, is_interim, confidence, source, message_type) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    message.id, session.id, message.text, message.sender, message.timestamp,
+                    message.is_interim.map(|b| if b { 1 } else { 0 }),
+                    message.confidence, message.source, message.message_type
+                ]
             )?;
 
-            // Insert messages and related data
-            for message in session.history {
-                // Insert main message
+            // Insert attachments if present
+            if let Some(attachments) = message.attachments {
+                for attachment in attachments {
+                    tx.execute(
+                        "INSERT INTO message_attachments (id, message_id, type, name, size, mime_type, url, base64_data, thumbnail, extracted_text, width, height, upload_progress, upload_status, error)
+                         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                        params![
+                            attachment.id, message.id, attachment.attachment_type, attachment.name, attachment.size,
+                            attachment.mime_type, attachment.url, attachment.base64_data, attachment.thumbnail,
+                            attachment.extracted_text, 
+                            attachment.dimensions.as_ref().map(|d| d.width),
+                            attachment.dimensions.as_ref().map(|d| d.height),
+                            attachment.upload_progress, attachment.upload_status, attachment.error
+                        ]
+                    )?;
+                }
+            }
+
+            // Insert thinking process if present
+            if let Some(thinking) = message.thinking {
                 tx.execute(
-                    "INSERT INTO chat_messages (id, session_id, text, sender, timestamp, is_interim, confidence, source, message_type) 
-                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    "INSERT INTO thinking_processes (message_id, is_visible, content, is_streaming) VALUES (?, ?, ?, ?)",
                     params![
-                        message.id, session.id, message.text, message.sender, message.timestamp,
-                        message.is_interim.map(|b| if b { 1 } else { 0 }),
-                        message.confidence, message.source, message.message_type
+                        message.id, 
+                        if thinking.is_visible { 1 } else { 0 },
+                        thinking.content,
+                        if thinking.is_streaming { 1 } else { 0 }
                     ]
                 )?;
 
-                // Insert attachments if present
-                if let Some(attachments) = message.attachments {
-                    for attachment in attachments {
+                let thinking_id: i64 = tx.last_insert_rowid();
+
+                // Insert thinking steps if present
+                if let Some(steps) = thinking.steps {
+                    for step in steps {
                         tx.execute(
-                            "INSERT INTO message_attachments (id, message_id, type, name, size, mime_type, url, base64_data, thumbnail, extracted_text, width, height, upload_progress, upload_status, error)
-                             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                            params![
-                                attachment.id, message.id, attachment.attachment_type, attachment.name, attachment.size,
-                                attachment.mime_type, attachment.url, attachment.base64_data, attachment.thumbnail,
-                                attachment.extracted_text, 
-                                attachment.dimensions.as_ref().map(|d| d.width),
-                                attachment.dimensions.as_ref().map(|d| d.height),
-                                attachment.upload_progress, attachment.upload_status, attachment.error
-                            ]
+                            "INSERT INTO thinking_steps (id, thinking_id, title, content, timestamp, status) VALUES (?, ?, ?, ?, ?, ?)",
+                            params![step.id, thinking_id, step.title, step.content, step.timestamp, step.status]
                         )?;
                     }
                 }
+            }
 
-                // Insert thinking process if present
-                if let Some(thinking) = message.thinking {
-                    tx.execute(
-                        "INSERT INTO thinking_processes (message_id, is_visible, content, is_streaming) VALUES (?, ?, ?, ?)",
-                        params![
-                            message.id, 
-                            if thinking.is_visible { 1 } else { 0 },
-                            thinking.content,
-                            if thinking.is_streaming { 1 } else { 0 }
-                        ]
-                    )?;
+            // Insert message metadata if present
+            if let Some(metadata) = message.metadata {
+                tx.execute(
+                    "INSERT INTO message_metadata (message_id, agent_type, model, tokens, processing_time, analysis_types, search_queries, sources,
+                                                   model_tag, provider, generation_options, rag_chunk_ids, screen_context_id,
+                                                   prompt_tokens, completion_tokens)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        message.id, metadata.agent_type, metadata.model, metadata.tokens, metadata.processing_time,
+                        metadata.analysis_type.map(|v| serde_json::to_string(&v).unwrap_or_default()),
+                        metadata.search_queries.map(|v| serde_json::to_string(&v).unwrap_or_default()),
+                        metadata.sources.map(|v| serde_json::to_string(&v).unwrap_or_default()),
+                        metadata.model_tag, metadata.provider,
+                        metadata.generation_options.map(|v| v.to_string()),
+                        metadata.rag_chunk_ids.map(|v| serde_json::to_string(&v).unwrap_or_default()),
+                        metadata.screen_context_id, metadata.prompt_tokens, metadata.completion_tokens
+                    ]
+                )?;
+            }
+        }
 
-                    let thinking_id: i64 = tx.last_insert_rowid();
+        Ok(())
+    }
 
-                    // Insert thinking steps if present
-                    if let Some(steps) = thinking.steps {
-                        for step in steps {
-                            tx.execute(
-                                "INSERT INTO thinking_steps (id, thinking_id, title, content, timestamp, status) VALUES (?, ?, ?, ?, ?, ?)",
-                                params![step.id, thinking_id, step.title, step.content, step.timestamp, step.status]
-                            )?;
-                        }
-                    }
-                }
+    /// Add one chat without touching the others (backend-created chats such as handoffs).
+    pub fn create_chat_session(&mut self, session: ChatSession) -> Result<()> {
+        let tx = self.connection.transaction()?;
+        Self::insert_chat_session(&tx, session)?;
+        tx.commit()
+    }
 
-                // Insert message metadata if present
-                if let Some(metadata) = message.metadata {
-                    tx.execute(
-                        "INSERT INTO message_metadata (message_id, agent_type, model, tokens, processing_time, analysis_types, search_queries, sources)
-                         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-                        params![
-                            message.id, metadata.agent_type, metadata.model, metadata.tokens, metadata.processing_time,
-                            metadata.analysis_type.map(|v| serde_json::to_string(&v).unwrap_or_default()),
-                            metadata.search_queries.map(|v| serde_json::to_string(&v).unwrap_or_default()),
-                            metadata.sources.map(|v| serde_json::to_string(&v).unwrap_or_default())
-                        ]
-                    )?;
-                }
-            }
-        }
+    /// Message ids are assigned by the frontend; pick one that can't collide with existing rows.
+    pub fn next_message_id(&self) -> Result<i32> {
+        self.connection.query_row(
+            "SELECT COALESCE(MAX(id), 0) + 1 FROM chat_messages",
+            params![],
+            |row| row.get(0)
+        )
+    }
 
-        tx.commit()?;
-        println!("✅ Saved {} chat sessions to SQLite", sessions_count);
+    /// Insert a snippet unless identical code is already in the library. Returns whether it was added.
+    pub fn insert_snippet(&mut self, snippet: &CodeSnippet, content_hash: &str) -> Result<bool> {
+        let inserted = self.connection.execute(
+            "INSERT OR IGNORE INTO code_snippets (id, content_hash, language, code, description, chat_id, message_id, created_at, use_count)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, 0)",
+            params![
+                snippet.id, content_hash, snippet.language, snippet.code, snippet.description,
+                snippet.chat_id, snippet.message_id, snippet.created_at
+            ]
+        )?;
+        Ok(inserted > 0)
+    }
+
+    pub fn set_snippet_description(&mut self, snippet_id: &str, description: &str) -> Result<()> {
+        self.connection.execute(
+            "UPDATE code_snippets SET description = ?, description_generated = 1 WHERE id = ?",
+            params![description, snippet_id]
+        )?;
+        Ok(())
+    }
+
+    pub fn search_snippets(&self, query: Option<&str>, language: Option<&str>, limit: usize) -> Result<Vec<CodeSnippet>> {
+        let pattern = query
+            .map(|q| q.trim())
+            .filter(|q| !q.is_empty())
+            .map(|q| format!("%{}%", q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+
+        let mut stmt = self.connection.prepare(
+            "SELECT id, language, code, description, chat_id, message_id, created_at, use_count
+             FROM code_snippets
+             WHERE (?1 IS NULL OR language = ?1)
+               AND (?2 IS NULL OR description LIKE ?2 ESCAPE '\\' OR code LIKE ?2 ESCAPE '\\')
+             ORDER BY use_count DESC, created_at DESC
+             LIMIT ?3"
+        )?;
+
+        let language = language.map(|l| l.to_lowercase());
+        let snippets = stmt.query_map(params![language, pattern, limit as i64], Self::snippet_from_row)?;
+        snippets.collect()
+    }
+
+    pub fn get_snippet(&self, snippet_id: &str) -> Result<CodeSnippet> {
+        self.connection.query_row(
+            "SELECT id, language, code, description, chat_id, message_id, created_at, use_count
+             FROM code_snippets WHERE id = ?",
+            params![snippet_id],
+            Self::snippet_from_row
+        )
+    }
+
+    pub fn record_snippet_use(&mut self, snippet_id: &str) -> Result<()> {
+        self.connection.execute(
+            "UPDATE code_snippets SET use_count = use_count + 1 WHERE id = ?",
+            params![snippet_id]
+        )?;
         Ok(())
     }
 
+    pub fn delete_snippet(&mut self, snippet_id: &str) -> Result<bool> {
+        let affected = self.connection.execute("DELETE FROM code_snippets WHERE id = ?", params![snippet_id])?;
+        Ok(affected > 0)
+    }
+
+    pub fn snippet_languages(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT language, COUNT(*) FROM code_snippets GROUP BY language ORDER BY COUNT(*) DESC"
+        )?;
+        let languages = stmt.query_map(params![], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        languages.collect()
+    }
+
+    fn snippet_from_row(row: &rusqlite::Row) -> Result<CodeSnippet> {
+        Ok(CodeSnippet {
+            id: row.get("id")?,
+            language: row.get("language")?,
+            code: row.get("code")?,
+            description: row.get("description")?,
+            chat_id: row.get("chat_id")?,
+            message_id: row.get("message_id")?,
+            created_at: row.get("created_at")?,
+            use_count: row.get("use_count")?,
+        })
+    }
+
     pub fn load_chat_sessions(&self) -> Result<LoadChatsResponse> {
         let mut sessions = Vec::new();
 
@@ -288,8 +424,10 @@ impl ChatStorage {
         let mut messages = Vec::new();
 
         let mut stmt = self.connection.prepare(
-            "SELECT id, text, sender, timestamp, is_interim, confidence, source, message_type 
-             FROM chat_messages WHERE session_id = ? ORDER BY timestamp"
+            "SELECT m.id, m.text, m.sender, m.timestamp, m.is_interim, m.confidence, m.source, m.message_type,
+                    mk.bookmarked_at, mk.reactions
+             FROM chat_messages m LEFT JOIN message_marks mk ON mk.message_id = m.id
+             WHERE m.session_id = ? ORDER BY m.timestamp"
         )?;
 
         let message_iter = stmt.query_map([session_id], |row| {
@@ -307,6 +445,10 @@ impl ChatStorage {
                 attachments: self.load_attachments_for_message(message_id).ok(),
                 thinking: self.load_thinking_for_message(message_id).ok(),
                 metadata: self.load_metadata_for_message(message_id).ok(),
+                bookmarked: row.get::<_, Option<String>>("bookmarked_at")?.map(|_| true),
+                reactions: row.get::<_, Option<String>>("reactions")?
+                    .and_then(|s| serde_json::from_str::<Vec<String>>(&s).ok())
+                    .filter(|reactions| !reactions.is_empty()),
             })
         })?;
 
@@ -412,7 +554,9 @@ impl ChatStorage {
 
     fn load_metadata_for_message(&self, message_id: i32) -> Result<MessageMetadata> {
         let mut stmt = self.connection.prepare(
-            "SELECT agent_type, model, tokens, processing_time, analysis_types, search_queries, sources 
+            "SELECT agent_type, model, tokens, processing_time, analysis_types, search_queries, sources,
+                    model_tag, provider, generation_options, rag_chunk_ids, screen_context_id,
+                    prompt_tokens, completion_tokens
              FROM message_metadata WHERE message_id = ?"
         )?;
 
@@ -420,6 +564,8 @@ impl ChatStorage {
             let analysis_types: Option<String> = row.get("analysis_types")?;
             let search_queries: Option<String> = row.get("search_queries")?;
             let sources: Option<String> = row.get("sources")?;
+            let generation_options: Option<String> = row.get("generation_options")?;
+            let rag_chunk_ids: Option<String> = row.get("rag_chunk_ids")?;
 
             Ok(MessageMetadata {
                 agent_type: row.get("agent_type")?,
@@ -429,17 +575,348 @@ impl ChatStorage {
                 analysis_type: analysis_types.and_then(|s| serde_json::from_str(&s).ok()),
                 search_queries: search_queries.and_then(|s| serde_json::from_str(&s).ok()),
                 sources: sources.and_then(|s| serde_json::from_str(&s).ok()),
+                model_tag: row.get("model_tag")?,
+                provider: row.get("provider")?,
+                generation_options: generation_options.and_then(|s| serde_json::from_str(&s).ok()),
+                rag_chunk_ids: rag_chunk_ids.and_then(|s| serde_json::from_str(&s).ok()),
+                screen_context_id: row.get("screen_context_id")?,
+                prompt_tokens: row.get("prompt_tokens")?,
+                completion_tokens: row.get("completion_tokens")?,
             })
         })
     }
+
+    pub fn get_message_provenance(&self, message_id: i32) -> Result<MessageProvenance> {
+        let (session_id, sender, timestamp): (String, String, String) = self.connection.query_row(
+            "SELECT session_id, sender, timestamp FROM chat_messages WHERE id = ?",
+            params![message_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        )?;
+
+        let metadata = match self.load_metadata_for_message(message_id) {
+            Ok(metadata) => Some(metadata),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e),
+        };
+        let selected_variant = self.get_message_variants(&session_id, message_id)?
+            .into_iter()
+            .find(|variant| variant.is_selected);
+
+        Ok(MessageProvenance { message_id, session_id, sender, timestamp, metadata, selected_variant })
+    }
+
+    pub fn save_message_variant(&mut self, variant: &MessageVariant) -> Result<()> {
+        let tx = self.connection.transaction()?;
+
+        // Only one variant per message is shown at a time
+        if variant.is_selected {
+            tx.execute(
+                "UPDATE message_variants SET is_selected = 0 WHERE session_id = ? AND message_id = ?",
+                params![variant.session_id, variant.message_id]
+            )?;
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO message_variants (id, session_id, message_id, kind, text, model, agent_type, created_at, is_selected)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                variant.id, variant.session_id, variant.message_id, variant.kind, variant.text,
+                variant.model, variant.agent_type, variant.created_at,
+                if variant.is_selected { 1 } else { 0 }
+            ]
+        )?;
+
+        tx.commit()
+    }
+
+    pub fn get_message_variants(&self, session_id: &str, message_id: i32) -> Result<Vec<MessageVariant>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, session_id, message_id, kind, text, model, agent_type, created_at, is_selected
+             FROM message_variants WHERE session_id = ? AND message_id = ? ORDER BY created_at"
+        )?;
+
+        let variants = stmt.query_map(params![session_id, message_id], |row| {
+            Ok(MessageVariant {
+                id: row.get("id")?,
+                session_id: row.get("session_id")?,
+                message_id: row.get("message_id")?,
+                kind: row.get("kind")?,
+                text: row.get("text")?,
+                model: row.get("model")?,
+                agent_type: row.get("agent_type")?,
+                created_at: row.get("created_at")?,
+                is_selected: row.get::<_, i32>("is_selected")? == 1,
+            })
+        })?;
+
+        variants.collect()
+    }
+
+    /// Mark one variant as the displayed response and return it.
+    pub fn select_message_variant(&mut self, variant_id: &str) -> Result<MessageVariant> {
+        let tx = self.connection.transaction()?;
+        let (session_id, message_id): (String, i32) = tx.query_row(
+            "SELECT session_id, message_id FROM message_variants WHERE id = ?",
+            params![variant_id],
+            |row| Ok((row.get(0)?, row.get(1)?))
+        )?;
+
+        tx.execute(
+            "UPDATE message_variants SET is_selected = (id = ?) WHERE session_id = ? AND message_id = ?",
+            params![variant_id, session_id, message_id]
+        )?;
+        tx.commit()?;
+
+        self.get_message_variants(&session_id, message_id)?
+            .into_iter()
+            .find(|variant| variant.id == variant_id)
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    fn message_session_id(&self, message_id: i32) -> Result<String> {
+        self.connection.query_row(
+            "SELECT session_id FROM chat_messages WHERE id = ?",
+            params![message_id],
+            |row| row.get(0)
+        )
+    }
+
+    fn message_reactions(&self, message_id: i32) -> Result<Vec<String>> {
+        let reactions: Option<String> = self.connection.query_row(
+            "SELECT reactions FROM message_marks WHERE message_id = ?",
+            params![message_id],
+            |row| row.get(0)
+        ).map(Some).or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+        Ok(reactions.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default())
+    }
+
+    /// Flip the bookmark on a message and return whether it is now bookmarked.
+    pub fn toggle_bookmark(&mut self, message_id: i32) -> Result<bool> {
+        let session_id = self.message_session_id(message_id)?;
+        self.connection.execute(
+            "INSERT INTO message_marks (message_id, session_id, bookmarked_at) VALUES (?, ?, ?)
+             ON CONFLICT(message_id) DO UPDATE SET session_id = excluded.session_id,
+                 bookmarked_at = CASE WHEN bookmarked_at IS NULL THEN excluded.bookmarked_at ELSE NULL END",
+            params![message_id, session_id, chrono::Utc::now().to_rfc3339()]
+        )?;
+        self.connection.query_row(
+            "SELECT bookmarked_at IS NOT NULL FROM message_marks WHERE message_id = ?",
+            params![message_id],
+            |row| row.get(0)
+        )
+    }
+
+    /// Add the reaction if the message doesn't have it, remove it if it does; returns the new set.
+    pub fn toggle_message_reaction(&mut self, message_id: i32, reaction: &str) -> Result<Vec<String>> {
+        let session_id = self.message_session_id(message_id)?;
+        let mut reactions = self.message_reactions(message_id)?;
+        match reactions.iter().position(|r| r == reaction) {
+            Some(index) => { reactions.remove(index); }
+            None => reactions.push(reaction.to_string()),
+        }
+        let reactions_json = serde_json::to_string(&reactions).unwrap_or_else(|_| "[]".to_string());
+        self.connection.execute(
+            "INSERT INTO message_marks (message_id, session_id, reactions) VALUES (?, ?, ?)
+             ON CONFLICT(message_id) DO UPDATE SET session_id = excluded.session_id, reactions = excluded.reactions",
+            params![message_id, session_id, reactions_json]
+        )?;
+        Ok(reactions)
+    }
+
+    /// Newest messages containing any of `terms` (case-insensitive), across all chats.
+    pub fn search_messages(&self, terms: &[String], limit: usize) -> Result<Vec<MessageSearchHit>> {
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let matches = vec!["m.text LIKE ? ESCAPE '\\'"; terms.len()].join(" OR ");
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT m.id, m.session_id, s.title, m.sender, m.text, m.timestamp, mk.bookmarked_at, mk.reactions
+             FROM chat_messages m JOIN chat_sessions s ON s.id = m.session_id
+             LEFT JOIN message_marks mk ON mk.message_id = m.id
+             WHERE ({}) AND COALESCE(m.is_interim, 0) = 0
+             ORDER BY m.timestamp DESC LIMIT {}",
+            matches, limit
+        ))?;
+
+        // % and _ in a term are literal text, not wildcards
+        let patterns = terms.iter().map(|term| format!("%{}%", term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+        let hits = stmt.query_map(rusqlite::params_from_iter(patterns), |row| {
+            Ok(MessageSearchHit {
+                session_id: row.get("session_id")?,
+                session_title: row.get("title")?,
+                message_id: row.get::<_, i32>("id")?.to_string(),
+                sender: row.get("sender")?,
+                text: row.get("text")?,
+                timestamp: row.get("timestamp")?,
+                bookmarked: row.get::<_, Option<String>>("bookmarked_at")?.is_some(),
+                reactions: row.get::<_, Option<String>>("reactions")?
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+            })
+        })?;
+        hits.collect()
+    }
+
+    /// Tokens a chat has consumed. Messages saved before counts were recorded are left out.
+    pub fn get_token_usage(&self, session_id: &str) -> Result<ChatTokenUsage> {
+        self.connection.query_row(
+            "SELECT COUNT(*) AS counted,
+                    COALESCE(SUM(md.prompt_tokens), 0) AS prompt_tokens,
+                    COALESCE(SUM(md.completion_tokens), 0) AS completion_tokens,
+                    COALESCE(SUM(COALESCE(md.tokens, COALESCE(md.prompt_tokens, 0) + COALESCE(md.completion_tokens, 0))), 0) AS total_tokens
+             FROM chat_messages m JOIN message_metadata md ON md.message_id = m.id
+             WHERE m.session_id = ?
+               AND (md.tokens IS NOT NULL OR md.prompt_tokens IS NOT NULL OR md.completion_tokens IS NOT NULL)",
+            [session_id],
+            |row| Ok(ChatTokenUsage {
+                session_id: session_id.to_string(),
+                prompt_tokens: row.get("prompt_tokens")?,
+                completion_tokens: row.get("completion_tokens")?,
+                total_tokens: row.get("total_tokens")?,
+                counted_messages: row.get::<_, i64>("counted")? as usize,
+            }),
+        )
+    }
+
+    /// Bookmarked messages across all chats, most recently bookmarked first.
+    pub fn list_bookmarked_messages(&self, filter: &BookmarkFilter) -> Result<Vec<BookmarkedMessage>> {
+        let query = filter.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+        let mut stmt = self.connection.prepare(
+            "SELECT m.id, m.session_id, s.title, m.text, m.sender, m.timestamp, mk.reactions, mk.bookmarked_at
+             FROM message_marks mk
+             JOIN chat_messages m ON m.id = mk.message_id
+             JOIN chat_sessions s ON s.id = m.session_id
+             WHERE mk.bookmarked_at IS NOT NULL
+               AND (?1 IS NULL OR m.session_id = ?1)
+               AND (?2 IS NULL OR m.text LIKE '%' || ?2 || '%')
+             ORDER BY mk.bookmarked_at DESC"
+        )?;
+
+        let bookmarks = stmt.query_map(params![filter.session_id, query], |row| {
+            let reactions: String = row.get("reactions")?;
+            Ok(BookmarkedMessage {
+                message_id: row.get("id")?,
+                session_id: row.get("session_id")?,
+                session_title: row.get("title")?,
+                text: row.get("text")?,
+                sender: row.get("sender")?,
+                timestamp: row.get("timestamp")?,
+                reactions: serde_json::from_str(&reactions).unwrap_or_default(),
+                bookmarked_at: row.get("bookmarked_at")?,
+            })
+        })?;
+
+        let mut results = Vec::new();
+        for bookmark in bookmarks {
+            let bookmark = bookmark?;
+            if let Some(reaction) = &filter.reaction {
+                if !bookmark.reactions.contains(reaction) {
+                    continue;
+                }
+            }
+            results.push(bookmark);
+            if filter.limit.is_some_and(|limit| results.len() >= limit) {
+                break;
+            }
+        }
+        Ok(results)
+    }
 }
 
 // Helper function to get database path
 fn get_database_path(app_handle: &AppHandle) -> std::result::Result<PathBuf, String> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    crate::data::paths::database_path(app_handle)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_db::MemoryDatabase;
+
+    fn payload(texts: &[&str]) -> SaveChatsPayload {
+        serde_json::from_value(serde_json::json!({
+            "chats": [{
+                "id": "chat-1", "title": "Deploy notes", "createdAt": "2024-01-01T00:00:00Z",
+                "updatedAt": "2024-01-01T00:00:00Z", "modelId": null,
+                "history": texts.iter().enumerate().map(|(i, text)| serde_json::json!({
+                    "id": i + 1, "text": text, "sender": "assistant", "timestamp": format!("2024-01-01T00:00:0{}Z", i),
+                    "isInterim": null, "confidence": null, "source": null, "attachments": null,
+                    "thinking": null, "messageType": "text", "metadata": null
+                })).collect::<Vec<_>>()
+            }]
+        })).unwrap()
+    }
 
-    Ok(app_data_dir.join("enteract_data.db"))
-}
\ No newline at end of file
+    #[test]
+    fn test_bookmarks_and_reactions_survive_chat_saves() {
+        let db = MemoryDatabase::new();
+        let mut storage = ChatStorage::open(db.path()).unwrap();
+        storage.save_chat_sessions(payload(&["Run the migration first", "Then restart the workers"])).unwrap();
+
+        assert!(storage.toggle_bookmark(2).unwrap());
+        assert_eq!(storage.toggle_message_reaction(2, "👍").unwrap(), vec!["👍"]);
+        assert_eq!(storage.toggle_message_reaction(1, "👍").unwrap(), vec!["👍"]);
+        assert!(storage.toggle_message_reaction(1, "👍").unwrap().is_empty());
+
+        // The frontend saves the whole chat again without knowing about the marks
+        storage.save_chat_sessions(payload(&["Run the migration first", "Then restart the workers"])).unwrap();
+        let history = &storage.load_chat_sessions().unwrap().chats[0].history;
+        assert_eq!(history[1].bookmarked, Some(true));
+        assert_eq!(history[1].reactions.as_deref(), Some(&["👍".to_string()][..]));
+        assert_eq!(history[0].bookmarked, None);
+
+        let filter = BookmarkFilter { query: Some("WORKERS".to_string()), ..Default::default() };
+        let bookmarks = storage.list_bookmarked_messages(&filter).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].session_title, "Deploy notes");
+
+        let hits = storage.search_messages(&["workers".to_string(), "migration".to_string()], 10).unwrap();
+        let workers = hits.iter().find(|hit| hit.message_id == "2").unwrap();
+        assert!(workers.bookmarked);
+        assert_eq!(workers.reactions, vec!["👍"]);
+        let migration = hits.iter().find(|hit| hit.message_id == "1").unwrap();
+        assert!(!migration.bookmarked && migration.reactions.is_empty());
+
+        assert!(!storage.toggle_bookmark(2).unwrap());
+        assert!(storage.list_bookmarked_messages(&BookmarkFilter::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_message_search_treats_wildcards_as_text() {
+        let db = MemoryDatabase::new();
+        let mut storage = ChatStorage::open(db.path()).unwrap();
+        storage.save_chat_sessions(payload(&["CPU at 95% on db_primary", "CPU at 95 percent on dbXprimary"])).unwrap();
+
+        let search = |term: &str| -> Vec<String> {
+            storage.search_messages(&[term.to_string()], 10).unwrap().into_iter().map(|hit| hit.message_id).collect()
+        };
+        assert_eq!(search("95%"), vec!["1"]);
+        assert_eq!(search("db_primary"), vec!["1"]);
+        assert_eq!(search("%").len(), 1);
+    }
+
+    #[test]
+    fn test_saved_message_round_trips_its_provenance() {
+        let db = MemoryDatabase::new();
+        let mut storage = ChatStorage::open(db.path()).unwrap();
+        let mut chats = payload(&["Restart the workers"]);
+        // What agentService.ts stores after the stream's start event
+        chats.chats[0].history[0].metadata = Some(serde_json::from_value(serde_json::json!({
+            "agentType": "enteract", "model": "gemma3:1b-it-qat", "tokens": 42,
+            "modelTag": "gemma3:1b-it-qat", "provider": "ollama",
+            "generationOptions": { "temperature": 0.2, "num_ctx": 4096 },
+            "ragChunkIds": ["chunk-a", "chunk-b"], "screenContextId": "screen-7"
+        })).unwrap());
+        storage.save_chat_sessions(chats).unwrap();
+
+        let provenance = storage.get_message_provenance(1).unwrap();
+        assert_eq!(provenance.session_id, "chat-1");
+        let metadata = provenance.metadata.expect("metadata was saved");
+        assert_eq!(metadata.model_tag.as_deref(), Some("gemma3:1b-it-qat"));
+        assert_eq!(metadata.provider.as_deref(), Some("ollama"));
+        assert_eq!(metadata.generation_options, Some(serde_json::json!({ "temperature": 0.2, "num_ctx": 4096 })));
+        assert_eq!(metadata.rag_chunk_ids, Some(vec!["chunk-a".to_string(), "chunk-b".to_string()]));
+        assert_eq!(metadata.screen_context_id.as_deref(), Some("screen-7"));
+    }
+}
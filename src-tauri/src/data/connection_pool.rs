@@ -5,7 +5,7 @@ use std::sync::{Arc, Mutex};
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 use rusqlite::{Connection, Result as SqliteResult};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use crate::data::errors::{DatabaseError, DatabaseErrorType, DatabaseResult};
 
 #[derive(Debug)]
@@ -98,15 +98,12 @@ pub struct ConnectionPool {
 
 impl ConnectionPool {
     pub fn new(app_handle: &AppHandle, config: Option<ConnectionPoolConfig>) -> DatabaseResult<Self> {
-        let db_path = app_handle
-            .path()
-            .app_data_dir()
+        let db_path = crate::data::paths::database_path(app_handle)
             .map_err(|e| DatabaseError::new(
                 DatabaseErrorType::InitializationFailed,
-                format!("Failed to get app data directory: {}", e),
+                e,
                 "connection_pool_init".to_string(),
-            ))?
-            .join("enteract_data.db");
+            ))?;
 
         let config = config.unwrap_or_default();
         
@@ -2,10 +2,35 @@
 use tauri::{AppHandle, command};
 use crate::data::types::{
     SaveConversationsPayload, LoadConversationsResponse,
-    ConversationMessage, ConversationInsight, ConversationMessageUpdate
+    ConversationMessage, ConversationInsight, ConversationMessageUpdate, SessionQualityReport,
+    ConversationAppMessage, ConversationAppUsage, InsightType, InsightCounts
 };
+use super::redaction;
 use super::storage::ConversationStorage;
 
+// Messages are saved moments after they're spoken, so the current foreground app stands in for the
+// one at speech time. Older messages (retried or imported saves) are left untagged rather than guessed.
+const ACTIVE_APP_STAMP_WINDOW_MS: i64 = 15_000;
+
+fn needs_active_app(message: &ConversationMessage) -> bool {
+    let age = chrono::Utc::now().timestamp_millis() - message.timestamp;
+    message.active_app.is_none() && (0..=ACTIVE_APP_STAMP_WINDOW_MS).contains(&age)
+}
+
+/// Tag the messages that need it with the foreground app, looked up once for all of them.
+async fn stamp_active_app(messages: &mut [ConversationMessage]) {
+    if !messages.iter().any(needs_active_app) {
+        return;
+    }
+    let Some(app) = crate::window_manager::active_app().await else {
+        return;
+    };
+    for message in messages.iter_mut().filter(|message| needs_active_app(message)) {
+        message.active_app = Some(app.app_name.clone());
+        message.active_window_title = Some(app.window_title.clone());
+    }
+}
+
 #[command]
 pub fn save_conversations(
     app_handle: AppHandle,
@@ -33,8 +58,12 @@ pub fn delete_conversation(
     conversation_id: String,
 ) -> Result<(), String> {
     match ConversationStorage::new(&app_handle) {
-        Ok(mut storage) => storage.delete_conversation(&conversation_id)
-            .map_err(|e| format!("Failed to delete conversation: {}", e)),
+        Ok(mut storage) => {
+            storage.delete_conversation(&conversation_id)
+                .map_err(|e| format!("Failed to delete conversation: {}", e))?;
+            crate::conversation_audio::remove_session_audio(&app_handle, &conversation_id);
+            Ok(())
+        }
         Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
     }
 }
@@ -42,18 +71,22 @@ pub fn delete_conversation(
 #[command]
 pub fn clear_all_conversations(app_handle: AppHandle) -> Result<(), String> {
     match ConversationStorage::new(&app_handle) {
-        Ok(mut storage) => storage.clear_all_conversations()
-            .map_err(|e| format!("Failed to clear conversations: {}", e)),
+        Ok(mut storage) => {
+            storage.clear_all_conversations()
+                .map_err(|e| format!("Failed to clear conversations: {}", e))?;
+            crate::conversation_audio::remove_all_audio(&app_handle);
+            Ok(())
+        }
         Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
     }
 }
 
 // Message-level operations
 #[command]
-pub fn save_conversation_message(
+pub async fn save_conversation_message(
     app_handle: AppHandle,
     session_id: String,
-    message: ConversationMessage,
+    mut message: ConversationMessage,
 ) -> Result<(), String> {
     println!("📥 save_conversation_message called - session_id: {}, message_id: {}", session_id, message.id);
     println!("📝 Message details - type: '{}', source: '{}', content length: {}", 
@@ -71,6 +104,7 @@ pub fn save_conversation_message(
         println!("❌ {}", error_msg);
         return Err(error_msg);
     }
+    stamp_active_app(std::slice::from_mut(&mut message)).await;
     
     match ConversationStorage::new(&app_handle) {
         Ok(mut storage) => {
@@ -96,12 +130,13 @@ pub fn save_conversation_message(
 }
 
 #[command]
-pub fn batch_save_conversation_messages(
+pub async fn batch_save_conversation_messages(
     app_handle: AppHandle,
     session_id: String,
-    messages: Vec<ConversationMessage>,
+    mut messages: Vec<ConversationMessage>,
 ) -> Result<(), String> {
     println!("📥 batch_save_conversation_messages called - session_id: {}, message_count: {}", session_id, messages.len());
+    stamp_active_app(&mut messages).await;
     
     match ConversationStorage::new(&app_handle) {
         Ok(mut storage) => {
@@ -153,6 +188,91 @@ pub fn delete_conversation_message(
     }
 }
 
+/// Take a time range of a conversation off the record (or put it back). Ended conversations are
+/// re-queued for the knowledge graph so redacted text is dropped from it, and the conversation's
+/// memory is rebuilt.
+#[command]
+pub fn set_conversation_range_redacted(
+    app_handle: AppHandle,
+    session_id: String,
+    range_start: Option<i64>,
+    range_end: Option<i64>,
+    redacted: Option<bool>,
+) -> Result<usize, String> {
+    let redacted = redacted.unwrap_or(true);
+    let mut storage = ConversationStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize conversation storage: {}", e))?;
+    let changed = storage.set_range_redacted(&session_id, range_start, range_end, redacted)
+        .map_err(|e| format!("Failed to update redaction: {}", e))?;
+
+    if changed > 0 {
+        println!("🙈 {} {} messages in session {}", if redacted { "Redacted" } else { "Restored" }, changed, session_id);
+        // The memory summary may quote what was just taken off the record
+        if let Err(e) = storage.clear_conversation_memory(&session_id) {
+            println!("⚠️ Failed to reset conversation memory: {}", e);
+        }
+        match storage.get_session(&session_id) {
+            Ok(Some(session)) if !session.is_active => crate::knowledge_graph::queue_sources(
+                &app_handle,
+                vec![crate::knowledge_graph::conversation_source(&session)],
+            ),
+            Ok(_) => {}
+            Err(e) => println!("⚠️ Failed to load session for the knowledge graph: {}", e),
+        }
+    }
+    Ok(changed)
+}
+
+/// Write a Markdown transcript, with off-the-record stretches marked as omitted.
+#[command]
+pub fn export_conversation_transcript(
+    app_handle: AppHandle,
+    session_id: String,
+    path: String,
+) -> Result<(), String> {
+    let session = ConversationStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize conversation storage: {}", e))?
+        .get_session(&session_id)
+        .map_err(|e| format!("Failed to load conversation: {}", e))?
+        .ok_or_else(|| format!("Conversation not found: {}", session_id))?;
+
+    let started = chrono::DateTime::from_timestamp_millis(session.start_time)
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let mut markdown = format!("# {}\n\n_{}_\n\n", session.name, started);
+    for line in redaction::transcript_lines(&session.messages) {
+        markdown.push_str(&line);
+        markdown.push_str("\n\n");
+    }
+    std::fs::write(&path, markdown).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Transcript lines spoken while an application was in the foreground, optionally within one session.
+#[command]
+pub fn get_conversation_messages_by_app(
+    app_handle: AppHandle,
+    app_name: String,
+    session_id: Option<String>,
+) -> Result<Vec<ConversationAppMessage>, String> {
+    match ConversationStorage::new(&app_handle) {
+        Ok(storage) => storage.get_messages_by_app(&app_name, session_id.as_deref())
+            .map_err(|e| format!("Failed to get messages by app: {}", e)),
+        Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
+    }
+}
+
+#[command]
+pub fn get_conversation_app_usage(
+    app_handle: AppHandle,
+    session_id: Option<String>,
+) -> Result<Vec<ConversationAppUsage>, String> {
+    match ConversationStorage::new(&app_handle) {
+        Ok(storage) => storage.get_app_usage(session_id.as_deref())
+            .map_err(|e| format!("Failed to get conversation app usage: {}", e)),
+        Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
+    }
+}
+
 // Insight operations
 #[command]
 pub fn save_conversation_insight(
@@ -179,6 +299,33 @@ pub fn get_conversation_insights(
     }
 }
 
+/// Insights of the given types for the UI's insight tabs, newest first. An empty list means all types.
+#[command]
+pub fn get_insights_by_type(
+    app_handle: AppHandle,
+    session_id: String,
+    types: Vec<InsightType>,
+    limit: Option<usize>,
+) -> Result<Vec<ConversationInsight>, String> {
+    match ConversationStorage::new(&app_handle) {
+        Ok(storage) => storage.get_insights_by_type(&session_id, &types, limit)
+            .map_err(|e| format!("Failed to get insights by type: {}", e)),
+        Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
+    }
+}
+
+#[command]
+pub fn get_insight_counts(
+    app_handle: AppHandle,
+    session_id: String,
+) -> Result<InsightCounts, String> {
+    match ConversationStorage::new(&app_handle) {
+        Ok(storage) => storage.get_insight_counts(&session_id)
+            .map_err(|e| format!("Failed to count insights: {}", e)),
+        Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
+    }
+}
+
 // Session metadata operations  
 #[command]
 pub fn update_session_metadata(
@@ -192,7 +339,26 @@ pub fn update_session_metadata(
         Ok(mut storage) => {
             let name_ref = name.as_deref();
             storage.update_session_metadata(&session_id, name_ref, end_time, is_active)
-                .map_err(|e| format!("Failed to update session metadata: {}", e))
+                .map_err(|e| format!("Failed to update session metadata: {}", e))?;
+            
+            // Ending a session snapshots its audio quality report
+            if matches!(end_time, Some(Some(_))) {
+                if let Err(e) = storage.finalize_session_quality(&session_id) {
+                    println!("⚠️ Failed to store session quality report: {}", e);
+                }
+                match storage.get_session(&session_id) {
+                    Ok(Some(session)) => {
+                        crate::knowledge_graph::queue_sources(
+                            &app_handle,
+                            vec![crate::knowledge_graph::conversation_source(&session)],
+                        );
+                        crate::conversation_templates::queue_template_fill(&app_handle, session);
+                    }
+                    Ok(None) => {}
+                    Err(e) => println!("⚠️ Failed to load session for the knowledge graph: {}", e),
+                }
+            }
+            Ok(())
         }
         Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
     }
@@ -211,6 +377,29 @@ pub fn update_session_active_state(
     }
 }
 
+#[command]
+pub fn get_session_quality(
+    app_handle: AppHandle,
+    session_id: String,
+) -> Result<SessionQualityReport, String> {
+    match ConversationStorage::new(&app_handle) {
+        Ok(storage) => {
+            if let Some(report) = storage.get_session_quality_report(&session_id)
+                .map_err(|e| format!("Failed to load session quality report: {}", e))? {
+                return Ok(report);
+            }
+            
+            // Session still running: report on what has been captured so far without storing it.
+            // Capture metrics only exist for the session being captured; others go by their messages
+            let confidences = storage.get_session_confidences(&session_id)
+                .map_err(|e| format!("Failed to load session confidences: {}", e))?;
+            let metrics = crate::audio_loopback::quality_metrics::snapshot_for(&session_id).unwrap_or_default();
+            Ok(crate::audio_loopback::quality_metrics::build_report(&session_id, &metrics, &confidences, false))
+        }
+        Err(e) => Err(format!("Failed to initialize conversation storage: {}", e))
+    }
+}
+
 #[command]
 pub fn ping_backend() -> Result<String, String> {
     Ok("pong".to_string())
@@ -2,6 +2,7 @@
 
 pub mod storage;
 pub mod commands;
+pub mod redaction;
 
 // Re-export the main functionality
 pub use storage::*;
@@ -0,0 +1,108 @@
+// Off-the-record ranges. Redacted messages stay in the user's own transcript, but everything derived
+// from a conversation (summaries, insights, chat briefs, the knowledge graph, exports) renders it
+// through here so their text never leaves the data layer and the gap stays visible.
+use crate::data::types::ConversationMessage;
+
+pub fn is_redacted(message: &ConversationMessage) -> bool {
+    message.redacted.unwrap_or(false)
+}
+
+fn omission_marker(count: usize) -> String {
+    format!("[Off the record: {} message{} omitted]", count, if count == 1 { "" } else { "s" })
+}
+
+/// "Me:"/"Them:" transcript lines (or the speaker's name once one is assigned), with each run of
+/// redacted messages collapsed into one marker.
+pub fn transcript_lines<'a>(messages: impl IntoIterator<Item = &'a ConversationMessage>) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut omitted = 0;
+    for message in messages {
+        if is_redacted(message) {
+            omitted += 1;
+            continue;
+        }
+        if message.content.trim().is_empty() {
+            continue;
+        }
+        if omitted > 0 {
+            lines.push(omission_marker(omitted));
+            omitted = 0;
+        }
+        let speaker = match &message.speaker_name {
+            Some(name) => name.as_str(),
+            None if message.source == "loopback" => "Them",
+            None => "Me",
+        };
+        lines.push(format!("{}: {}", speaker, message.content.trim()));
+    }
+    if omitted > 0 {
+        lines.push(omission_marker(omitted));
+    }
+    lines
+}
+
+/// Blank out lines of a frontend-assembled "Speaker: text" context that quote a redacted message,
+/// including quotes the frontend truncated with "...". Erring towards over-redaction is fine here.
+pub fn scrub_redacted(text: &str, messages: &[ConversationMessage]) -> String {
+    let redacted: Vec<&str> = messages
+        .iter()
+        .filter(|m| is_redacted(m))
+        .map(|m| m.content.trim())
+        .filter(|content| !content.is_empty())
+        .collect();
+    if redacted.is_empty() {
+        return text.to_string();
+    }
+
+    text.lines()
+        .map(|line| {
+            let (speaker, quoted) = line.split_once(": ").unwrap_or(("", line));
+            let quoted = quoted.trim().trim_end_matches("...");
+            if !quoted.is_empty() && redacted.iter().any(|content| content.starts_with(quoted)) {
+                if speaker.is_empty() { "[Off the record]".to_string() } else { format!("{}: [Off the record]", speaker) }
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(source: &str, content: &str, redacted: bool) -> ConversationMessage {
+        serde_json::from_value(serde_json::json!({
+            "id": content,
+            "type": "user",
+            "source": source,
+            "content": content,
+            "timestamp": 0,
+            "confidence": null,
+            "redacted": redacted,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_redacted_runs_become_markers() {
+        let messages = vec![
+            message("microphone", "hello", false),
+            message("loopback", "salary is 90k", true),
+            message("microphone", "and the bonus", true),
+            message("loopback", "back on track", false),
+            message("loopback", "one more secret", true),
+        ];
+        assert_eq!(transcript_lines(&messages), vec![
+            "Me: hello",
+            "[Off the record: 2 messages omitted]",
+            "Them: back on track",
+            "[Off the record: 1 message omitted]",
+        ]);
+        assert_eq!(
+            scrub_redacted("System: salary is...\nUser: hello", &messages),
+            "System: [Off the record]\nUser: hello"
+        );
+    }
+}
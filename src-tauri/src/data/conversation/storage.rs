@@ -1,11 +1,15 @@
 // SQLite storage implementation for conversation sessions
 use rusqlite::{Connection, Result, params};
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use crate::data::types::{
     ConversationSession, ConversationMessage, ConversationInsight, ConversationMessageUpdate,
-    SaveConversationsPayload, LoadConversationsResponse
+    SaveConversationsPayload, LoadConversationsResponse, SessionQualityReport, ConversationChatLink,
+    ConversationAppMessage, ConversationAppUsage, ConversationTemplate, InsightType, InsightCounts, MessageSearchHit,
+    Speaker, ConsentEvent, ConversationMemory
 };
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use crate::data::paths::open_database;
 
 pub struct ConversationStorage {
     connection: Connection,
@@ -17,49 +21,13 @@ impl ConversationStorage {
             rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
             Some(e)
         ))?;
-        
-        // Ensure parent directory exists
-        if let Some(parent) = db_path.parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)
-                    .map_err(|e| rusqlite::Error::SqliteFailure(
-                        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
-                        Some(format!("Failed to create directory: {}", e))
-                    ))?;
-            }
-        }
+        Self::open(&db_path)
+    }
 
-        let connection = Connection::open(&db_path)?;
-        println!("ℹ️ Opened database connection at: {:?}", db_path);
-        
-        // Configure SQLite for optimal performance using safer approach
-        connection.execute("PRAGMA foreign_keys = ON", params![]).map_err(|e| {
-            println!("⚠️ Warning: Failed to set foreign_keys: {}", e);
-            e
-        })?;
-        
-        // Set journal mode with proper handling (WAL returns a result, so use query_row)
-        match connection.query_row("PRAGMA journal_mode = WAL", params![], |row| row.get::<_, String>(0)) {
-            Ok(mode) => {
-                if mode.to_lowercase() == "wal" {
-                    println!("✅ WAL mode enabled successfully");
-                } else {
-                    println!("ℹ️ Journal mode is: {} (WAL may not be available)", mode);
-                }
-            }
-            Err(e) => println!("⚠️ Warning: Could not set journal mode: {}", e),
-        }
-        
-        // Set other pragmas with execute (they don't necessarily return meaningful results)
-        connection.execute("PRAGMA synchronous = NORMAL", params![]).ok();
-        connection.execute("PRAGMA cache_size = 10000", params![]).ok();
-        connection.execute("PRAGMA temp_store = memory", params![]).ok();
-        
-        println!("✅ SQLite configuration applied successfully");
-        
-        let mut storage = Self { connection };
+    /// Conversation storage on the database at `db_path`, creating its tables if needed.
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let mut storage = Self { connection: open_database(db_path)? };
         storage.initialize_conversation_tables()?;
-        
         Ok(storage)
     }
 
@@ -72,7 +40,8 @@ impl ConversationStorage {
                 name TEXT NOT NULL,
                 start_time INTEGER NOT NULL,
                 end_time INTEGER,
-                is_active INTEGER NOT NULL CHECK(is_active IN (0, 1))
+                is_active INTEGER NOT NULL CHECK(is_active IN (0, 1)),
+                template_id TEXT
             );
 
             -- Conversation messages table
@@ -84,6 +53,12 @@ impl ConversationStorage {
                 content TEXT NOT NULL,
                 timestamp INTEGER NOT NULL,
                 confidence REAL,
+                active_app TEXT,
+                active_window_title TEXT,
+                redacted INTEGER NOT NULL DEFAULT 0,
+                audio_offset INTEGER,
+                audio_duration_ms INTEGER,
+                speaker_id TEXT,
                 FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
             );
 
@@ -94,11 +69,76 @@ impl ConversationStorage {
                 text TEXT NOT NULL,
                 timestamp INTEGER NOT NULL,
                 context_length INTEGER NOT NULL,
-                insight_type TEXT NOT NULL CHECK(insight_type IN ('insight', 'welcome', 'question', 'answer')),
+                insight_type TEXT NOT NULL CHECK(insight_type IN ('summary', 'action_item', 'question', 'fact_check', 'sentiment', 'custom', 'insight', 'welcome', 'answer', 'template')),
+                template_data TEXT,
+                FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
+            );
+
+            -- User-defined note templates; built-ins live in code and can be overridden by id
+            CREATE TABLE IF NOT EXISTS conversation_templates (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT NOT NULL,
+                sections TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+
+            -- Audio/transcription quality report computed when a session ends
+            CREATE TABLE IF NOT EXISTS conversation_quality_reports (
+                session_id TEXT PRIMARY KEY,
+                report TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
+            );
+
+            -- Chats started from a conversation ("continue this topic in a chat")
+            CREATE TABLE IF NOT EXISTS conversation_chat_links (
+                conversation_id TEXT NOT NULL,
+                chat_id TEXT NOT NULL,
+                range_start INTEGER,
+                range_end INTEGER,
+                brief TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (conversation_id, chat_id),
+                FOREIGN KEY (conversation_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
+            );
+
+            -- Speakers named by the user, recognized across conversations by an optional voiceprint
+            CREATE TABLE IF NOT EXISTS speakers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                voiceprint BLOB,
+                voiceprint_samples INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            );
+
+            -- Recording consent given, withdrawn and relied on, kept for auditing. No foreign key:
+            -- consent is usually confirmed before the session is first saved
+            CREATE TABLE IF NOT EXISTS conversation_consent_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                event TEXT NOT NULL,
+                basis TEXT,
+                method TEXT,
+                jurisdiction TEXT,
+                source TEXT,
+                note TEXT,
+                timestamp INTEGER NOT NULL
+            );
+
+            -- Rolling summary and named entities of a conversation, for the conversational AI
+            CREATE TABLE IF NOT EXISTS conversation_memory (
+                session_id TEXT PRIMARY KEY,
+                summary TEXT NOT NULL,
+                entities TEXT NOT NULL,
+                covered_until INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
                 FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
             );
 
             -- Indexes for performance
+            CREATE INDEX IF NOT EXISTS idx_conversation_consent_events_session ON conversation_consent_events(session_id, timestamp);
+            CREATE INDEX IF NOT EXISTS idx_conversation_chat_links_chat ON conversation_chat_links(chat_id);
             CREATE INDEX IF NOT EXISTS idx_conversation_sessions_active_start ON conversation_sessions(is_active, start_time DESC);
             CREATE INDEX IF NOT EXISTS idx_conversation_messages_session_timestamp ON conversation_messages(session_id, timestamp);
             CREATE INDEX IF NOT EXISTS idx_conversation_messages_type ON conversation_messages(type);
@@ -107,6 +147,64 @@ impl ConversationStorage {
             CREATE INDEX IF NOT EXISTS idx_conversation_insights_type ON conversation_insights(insight_type);
         "#)?;
 
+        // Active application columns for databases created before they existed
+        for column in ["active_app", "active_window_title"] {
+            let _ = self.connection.execute(&format!("ALTER TABLE conversation_messages ADD COLUMN {} TEXT", column), params![]);
+        }
+        for column in ["audio_offset", "audio_duration_ms"] {
+            let _ = self.connection.execute(&format!("ALTER TABLE conversation_messages ADD COLUMN {} INTEGER", column), params![]);
+        }
+        let _ = self.connection.execute(
+            "ALTER TABLE conversation_messages ADD COLUMN redacted INTEGER NOT NULL DEFAULT 0",
+            params![],
+        );
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_conversation_messages_active_app ON conversation_messages(active_app)",
+            params![],
+        )?;
+        let _ = self.connection.execute("ALTER TABLE conversation_sessions ADD COLUMN template_id TEXT", params![]);
+        let _ = self.connection.execute("ALTER TABLE conversation_messages ADD COLUMN speaker_id TEXT", params![]);
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_conversation_messages_speaker ON conversation_messages(speaker_id)",
+            params![],
+        )?;
+
+        // The insight type CHECK can't be altered in place, so older tables are rebuilt to allow
+        // template insights and the insight taxonomy
+        let insights_sql: String = self.connection.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'conversation_insights'",
+            params![],
+            |row| row.get(0),
+        )?;
+        if !insights_sql.contains("'fact_check'") {
+            let copied_columns = if insights_sql.contains("template_data") {
+                "id, session_id, text, timestamp, context_length, insight_type, template_data"
+            } else {
+                "id, session_id, text, timestamp, context_length, insight_type"
+            };
+            self.connection.execute_batch(&format!(r#"
+                BEGIN;
+                CREATE TABLE conversation_insights_new (
+                    id TEXT PRIMARY KEY,
+                    session_id TEXT NOT NULL,
+                    text TEXT NOT NULL,
+                    timestamp INTEGER NOT NULL,
+                    context_length INTEGER NOT NULL,
+                    insight_type TEXT NOT NULL CHECK(insight_type IN ('summary', 'action_item', 'question', 'fact_check', 'sentiment', 'custom', 'insight', 'welcome', 'answer', 'template')),
+                    template_data TEXT,
+                    FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
+                );
+                INSERT INTO conversation_insights_new ({columns})
+                    SELECT {columns} FROM conversation_insights;
+                DROP TABLE conversation_insights;
+                ALTER TABLE conversation_insights_new RENAME TO conversation_insights;
+                CREATE INDEX IF NOT EXISTS idx_conversation_insights_session_timestamp ON conversation_insights(session_id, timestamp);
+                CREATE INDEX IF NOT EXISTS idx_conversation_insights_type ON conversation_insights(insight_type);
+                COMMIT;
+            "#, columns = copied_columns))?;
+            println!("✅ Rebuilt conversation_insights to allow the insight taxonomy");
+        }
+
         println!("✅ Conversation tables initialized successfully");
         Ok(())
     }
@@ -159,20 +257,21 @@ impl ConversationStorage {
         if session_exists {
             // Update existing session metadata only
             tx.execute(
-                "UPDATE conversation_sessions SET name = ?, start_time = ?, end_time = ?, is_active = ? WHERE id = ?",
+                "UPDATE conversation_sessions SET name = ?, start_time = ?, end_time = ?, is_active = ?,
+                 template_id = COALESCE(?, template_id) WHERE id = ?",
                 params![
                     session.name, session.start_time, session.end_time,
-                    if session.is_active { 1 } else { 0 }, session.id
+                    if session.is_active { 1 } else { 0 }, session.template_id, session.id
                 ]
             )?;
             println!("🔄 Updated session metadata: {}", session.id);
         } else {
             // Insert new session
             tx.execute(
-                "INSERT INTO conversation_sessions (id, name, start_time, end_time, is_active) VALUES (?, ?, ?, ?, ?)",
+                "INSERT INTO conversation_sessions (id, name, start_time, end_time, is_active, template_id) VALUES (?, ?, ?, ?, ?, ?)",
                 params![
                     session.id, session.name, session.start_time, session.end_time,
-                    if session.is_active { 1 } else { 0 }
+                    if session.is_active { 1 } else { 0 }, session.template_id
                 ]
             )?;
             println!("🆕 Created new session: {}", session.id);
@@ -182,11 +281,13 @@ impl ConversationStorage {
         for message in session.messages {
             // Use INSERT OR IGNORE to avoid conflicts with concurrent individual message saves
             tx.execute(
-                "INSERT OR IGNORE INTO conversation_messages (id, session_id, type, source, content, timestamp, confidence) 
-                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                "INSERT OR IGNORE INTO conversation_messages (id, session_id, type, source, content, timestamp, confidence, active_app, active_window_title, redacted, audio_offset, audio_duration_ms) 
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     message.id, session.id, message.message_type, message.source,
-                    message.content, message.timestamp, message.confidence
+                    message.content, message.timestamp, message.confidence,
+                    message.active_app, message.active_window_title, message.redacted.unwrap_or(false),
+                    message.audio_offset, message.audio_duration_ms
                 ]
             )?;
         }
@@ -194,11 +295,11 @@ impl ConversationStorage {
         // Handle insights incrementally
         for insight in session.insights {
             tx.execute(
-                "INSERT OR REPLACE INTO conversation_insights (id, session_id, text, timestamp, context_length, insight_type)
-                 VALUES (?, ?, ?, ?, ?, ?)",
+                "INSERT OR REPLACE INTO conversation_insights (id, session_id, text, timestamp, context_length, insight_type, template_data)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
                 params![
                     insight.id, session.id, insight.text, insight.timestamp,
-                    insight.context_length, insight.insight_type
+                    insight.context_length, insight.insight_type.as_str(), template_json(&insight)
                 ]
             )?;
         }
@@ -273,7 +374,7 @@ impl ConversationStorage {
 
         // Query all sessions
         let mut session_stmt = self.connection.prepare(
-            "SELECT id, name, start_time, end_time, is_active FROM conversation_sessions ORDER BY start_time DESC"
+            "SELECT id, name, start_time, end_time, is_active, template_id FROM conversation_sessions ORDER BY start_time DESC"
         )?;
 
         let session_iter = session_stmt.query_map(params![], |row| {
@@ -283,11 +384,12 @@ impl ConversationStorage {
                 row.get::<_, i64>("start_time")?,
                 row.get::<_, Option<i64>>("end_time")?,
                 row.get::<_, i32>("is_active")? != 0,
+                row.get::<_, Option<String>>("template_id")?,
             ))
         })?;
 
         for session_result in session_iter {
-            let (id, name, start_time, end_time, is_active) = session_result?;
+            let (id, name, start_time, end_time, is_active, template_id) = session_result?;
             
             // Load messages and insights for this session
             let messages = self.load_conversation_messages(&id)?;
@@ -301,6 +403,7 @@ impl ConversationStorage {
                 is_active,
                 messages,
                 insights,
+                template_id,
             });
         }
 
@@ -308,31 +411,41 @@ impl ConversationStorage {
         Ok(LoadConversationsResponse { conversations: sessions })
     }
 
+    pub fn get_session(&self, session_id: &str) -> Result<Option<ConversationSession>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, name, start_time, end_time, is_active, template_id FROM conversation_sessions WHERE id = ?"
+        )?;
+        let mut rows = stmt.query([session_id])?;
+        let row = match rows.next()? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        let id: String = row.get("id")?;
+        let messages = self.load_conversation_messages(&id)?;
+        let insights = self.load_conversation_insights(&id)?;
+        Ok(Some(ConversationSession {
+            name: row.get("name")?,
+            start_time: row.get("start_time")?,
+            end_time: row.get("end_time")?,
+            is_active: row.get::<_, i32>("is_active")? != 0,
+            template_id: row.get("template_id")?,
+            id,
+            messages,
+            insights,
+        }))
+    }
+
     fn load_conversation_messages(&self, session_id: &str) -> Result<Vec<ConversationMessage>> {
         let mut messages = Vec::new();
 
         let mut stmt = self.connection.prepare(
-            "SELECT id, type, source, content, timestamp, confidence 
+            "SELECT id, type, source, content, timestamp, confidence, active_app, active_window_title, redacted, audio_offset, audio_duration_ms,
+                    speaker_id, (SELECT name FROM speakers WHERE speakers.id = speaker_id) AS speaker_name
              FROM conversation_messages WHERE session_id = ? ORDER BY timestamp"
         )?;
 
-        let message_iter = stmt.query_map([session_id], |row| {
-            Ok(ConversationMessage {
-                id: row.get("id")?,
-                message_type: row.get("type")?,
-                source: row.get("source")?,
-                content: row.get("content")?,
-                timestamp: row.get("timestamp")?,
-                confidence: row.get("confidence")?,
-                // Frontend-only fields set to None when loading from DB
-                is_preview: None,
-                is_typing: None,
-                persistence_state: Some("saved".to_string()),
-                retry_count: None,
-                last_save_attempt: None,
-                save_error: None,
-            })
-        })?;
+        let message_iter = stmt.query_map([session_id], message_from_row)?;
 
         for message_result in message_iter {
             messages.push(message_result?);
@@ -341,23 +454,214 @@ impl ConversationStorage {
         Ok(messages)
     }
 
-    fn load_conversation_insights(&self, session_id: &str) -> Result<Vec<ConversationInsight>> {
-        let mut insights = Vec::new();
+    /// A single message with the session it belongs to.
+    pub fn get_message(&self, message_id: &str) -> Result<Option<(String, ConversationMessage)>> {
+        let result = self.connection.query_row(
+            "SELECT id, session_id, type, source, content, timestamp, confidence, active_app, active_window_title, redacted, audio_offset, audio_duration_ms,
+                    speaker_id, (SELECT name FROM speakers WHERE speakers.id = speaker_id) AS speaker_name
+             FROM conversation_messages WHERE id = ?",
+            [message_id],
+            |row| Ok((row.get::<_, String>("session_id")?, message_from_row(row)?)),
+        );
+        match result {
+            Ok(found) => Ok(Some(found)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
 
+    /// Mark the messages in a time range (inclusive, open-ended when a bound is missing) as off the
+    /// record, or back on it. Returns how many messages changed.
+    pub fn set_range_redacted(&mut self, session_id: &str, range_start: Option<i64>, range_end: Option<i64>, redacted: bool) -> Result<usize> {
+        self.connection.execute(
+            "UPDATE conversation_messages SET redacted = ?1
+             WHERE session_id = ?2 AND redacted != ?1
+               AND (?3 IS NULL OR timestamp >= ?3) AND (?4 IS NULL OR timestamp <= ?4)",
+            params![redacted, session_id, range_start, range_end],
+        )
+    }
+
+    /// Messages spoken while an application was in the foreground, matched case-insensitively.
+    pub fn get_messages_by_app(&self, app_name: &str, session_id: Option<&str>) -> Result<Vec<ConversationAppMessage>> {
         let mut stmt = self.connection.prepare(
-            "SELECT id, text, timestamp, context_length, insight_type 
-             FROM conversation_insights WHERE session_id = ? ORDER BY timestamp"
+            "SELECT id, session_id, type, source, content, timestamp, confidence, active_app, active_window_title, redacted, audio_offset, audio_duration_ms,
+                    speaker_id, (SELECT name FROM speakers WHERE speakers.id = speaker_id) AS speaker_name
+             FROM conversation_messages
+             WHERE active_app = ?1 COLLATE NOCASE AND (?2 IS NULL OR session_id = ?2)
+             ORDER BY timestamp"
+        )?;
+
+        let rows = stmt.query_map(params![app_name, session_id], |row| {
+            Ok(ConversationAppMessage {
+                session_id: row.get("session_id")?,
+                message: message_from_row(row)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Newest messages containing any of `terms` (case-insensitive), across all conversations.
+    /// Off-the-record messages are never returned.
+    pub fn search_messages(&self, terms: &[String], limit: usize) -> Result<Vec<MessageSearchHit>> {
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let matches = vec!["m.content LIKE ?"; terms.len()].join(" OR ");
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT m.id, m.session_id, s.name, m.type, m.content, m.timestamp
+             FROM conversation_messages m JOIN conversation_sessions s ON s.id = m.session_id
+             WHERE ({}) AND m.redacted = 0
+             ORDER BY m.timestamp DESC LIMIT {}",
+            matches, limit
+        ))?;
+
+        let hits = stmt.query_map(rusqlite::params_from_iter(terms.iter().map(|term| format!("%{}%", term))), |row| {
+            let timestamp: i64 = row.get("timestamp")?;
+            Ok(MessageSearchHit {
+                session_id: row.get("session_id")?,
+                session_title: row.get("name")?,
+                message_id: row.get("id")?,
+                sender: row.get("type")?,
+                text: row.get("content")?,
+                timestamp: chrono::DateTime::from_timestamp_millis(timestamp)
+                    .map(|time| time.to_rfc3339())
+                    .unwrap_or_default(),
+                bookmarked: false,
+                reactions: Vec::new(),
+            })
+        })?;
+        hits.collect()
+    }
+
+    /// Applications that were in the foreground during conversations, most used first.
+    pub fn get_app_usage(&self, session_id: Option<&str>) -> Result<Vec<ConversationAppUsage>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT active_app, COUNT(*) AS message_count, MIN(timestamp) AS first_timestamp, MAX(timestamp) AS last_timestamp
+             FROM conversation_messages
+             WHERE active_app IS NOT NULL AND (?1 IS NULL OR session_id = ?1)
+             GROUP BY active_app COLLATE NOCASE
+             ORDER BY message_count DESC"
+        )?;
+
+        let rows = stmt.query_map(params![session_id], |row| {
+            Ok(ConversationAppUsage {
+                app_name: row.get("active_app")?,
+                message_count: row.get::<_, i64>("message_count")? as usize,
+                first_timestamp: row.get("first_timestamp")?,
+                last_timestamp: row.get("last_timestamp")?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Named speakers with their stats, most recently heard first.
+    pub fn list_speakers(&self) -> Result<Vec<Speaker>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT s.id, s.name, s.voiceprint IS NOT NULL AS has_voiceprint,
+                    COUNT(m.id) AS message_count, COUNT(DISTINCT m.session_id) AS session_count,
+                    COALESCE(SUM(m.audio_duration_ms), 0) AS speech_ms, MAX(m.timestamp) AS last_heard
+             FROM speakers s LEFT JOIN conversation_messages m ON m.speaker_id = s.id
+             GROUP BY s.id
+             ORDER BY last_heard IS NULL, last_heard DESC, s.name"
         )?;
 
-        let insight_iter = stmt.query_map([session_id], |row| {
-            Ok(ConversationInsight {
+        let rows = stmt.query_map(params![], |row| {
+            Ok(Speaker {
                 id: row.get("id")?,
-                text: row.get("text")?,
-                timestamp: row.get("timestamp")?,
-                context_length: row.get("context_length")?,
-                insight_type: row.get("insight_type")?,
+                name: row.get("name")?,
+                has_voiceprint: row.get("has_voiceprint")?,
+                message_count: row.get::<_, i64>("message_count")? as usize,
+                session_count: row.get::<_, i64>("session_count")? as usize,
+                speech_ms: row.get("speech_ms")?,
+                last_heard: row.get("last_heard")?,
             })
         })?;
+        rows.collect()
+    }
+
+    /// The speaker with this name (case-insensitive), created if there isn't one yet.
+    pub fn find_or_create_speaker(&mut self, name: &str) -> Result<String> {
+        let existing = self.connection.query_row(
+            "SELECT id FROM speakers WHERE name = ?",
+            [name],
+            |row| row.get::<_, String>(0),
+        );
+        match existing {
+            Ok(id) => Ok(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                let id = uuid::Uuid::new_v4().to_string();
+                self.connection.execute(
+                    "INSERT INTO speakers (id, name, created_at) VALUES (?, ?, ?)",
+                    params![id, name, chrono::Utc::now().timestamp_millis()],
+                )?;
+                Ok(id)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Label a session's messages from one source as a speaker, or only `message_ids` of them.
+    /// Returns how many messages changed.
+    pub fn assign_speaker(&mut self, session_id: &str, source: &str, message_ids: Option<&[String]>, speaker_id: &str) -> Result<usize> {
+        match message_ids {
+            None => self.connection.execute(
+                "UPDATE conversation_messages SET speaker_id = ? WHERE session_id = ? AND source = ?",
+                params![speaker_id, session_id, source],
+            ),
+            Some(ids) => {
+                let tx = self.connection.transaction()?;
+                let mut changed = 0;
+                for id in ids {
+                    changed += tx.execute(
+                        "UPDATE conversation_messages SET speaker_id = ? WHERE id = ? AND session_id = ? AND source = ?",
+                        params![speaker_id, id, session_id, source],
+                    )?;
+                }
+                tx.commit()?;
+                Ok(changed)
+            }
+        }
+    }
+
+    /// Speakers that have a voiceprint, as (id, name, voiceprint, fingerprints folded into it).
+    pub fn speaker_voiceprints(&self) -> Result<Vec<(String, String, Vec<u8>, i64)>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, name, voiceprint, voiceprint_samples FROM speakers WHERE voiceprint IS NOT NULL"
+        )?;
+        let rows = stmt.query_map(params![], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect()
+    }
+
+    pub fn set_speaker_voiceprint(&mut self, speaker_id: &str, voiceprint: &[u8], samples: i64) -> Result<()> {
+        self.connection.execute(
+            "UPDATE speakers SET voiceprint = ?, voiceprint_samples = ? WHERE id = ?",
+            params![voiceprint, samples, speaker_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn rename_speaker(&mut self, speaker_id: &str, name: &str) -> Result<bool> {
+        Ok(self.connection.execute("UPDATE speakers SET name = ? WHERE id = ?", params![name, speaker_id])? > 0)
+    }
+
+    /// Delete a speaker and their voiceprint; their messages go back to being unlabeled.
+    pub fn delete_speaker(&mut self, speaker_id: &str) -> Result<bool> {
+        let tx = self.connection.transaction()?;
+        tx.execute("UPDATE conversation_messages SET speaker_id = NULL WHERE speaker_id = ?", [speaker_id])?;
+        let deleted = tx.execute("DELETE FROM speakers WHERE id = ?", [speaker_id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    fn load_conversation_insights(&self, session_id: &str) -> Result<Vec<ConversationInsight>> {
+        let mut insights = Vec::new();
+
+        let mut stmt = self.connection.prepare(
+            "SELECT id, text, timestamp, context_length, insight_type, template_data 
+             FROM conversation_insights WHERE session_id = ? ORDER BY timestamp"
+        )?;
+
+        let insight_iter = stmt.query_map([session_id], insight_from_row)?;
 
         for insight_result in insight_iter {
             insights.push(insight_result?);
@@ -366,6 +670,44 @@ impl ConversationStorage {
         Ok(insights)
     }
 
+    /// The session's insights of the given types, newest first. No types means all of them.
+    pub fn get_insights_by_type(&self, session_id: &str, types: &[InsightType], limit: Option<usize>) -> Result<Vec<ConversationInsight>> {
+        let mut sql = "SELECT id, text, timestamp, context_length, insight_type, template_data
+             FROM conversation_insights WHERE session_id = ?".to_string();
+        if !types.is_empty() {
+            sql.push_str(&format!(" AND insight_type IN ({})", vec!["?"; types.len()].join(", ")));
+        }
+        sql.push_str(" ORDER BY timestamp DESC LIMIT ?");
+
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(session_id.to_string())];
+        values.extend(types.iter().map(|t| Box::new(t.as_str()) as Box<dyn rusqlite::ToSql>));
+        // SQLite treats a negative LIMIT as no limit
+        values.push(Box::new(limit.map_or(-1, |limit| limit as i64)));
+
+        let mut stmt = self.connection.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(values.iter()), insight_from_row)?;
+        rows.collect()
+    }
+
+    pub fn get_insight_counts(&self, session_id: &str) -> Result<InsightCounts> {
+        let mut by_type: HashMap<InsightType, usize> = InsightType::TAXONOMY.iter().map(|t| (*t, 0)).collect();
+        let mut stmt = self.connection.prepare(
+            "SELECT insight_type, COUNT(*) FROM conversation_insights WHERE session_id = ? GROUP BY insight_type"
+        )?;
+        let rows = stmt.query_map([session_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?;
+        for row in rows {
+            let (insight_type, count) = row?;
+            if let Some(insight_type) = InsightType::parse(&insight_type) {
+                by_type.insert(insight_type, count as usize);
+            }
+        }
+        Ok(InsightCounts {
+            session_id: session_id.to_string(),
+            total: by_type.values().sum(),
+            by_type,
+        })
+    }
+
     // Individual message operations
     pub fn save_conversation_message(&mut self, session_id: &str, message: ConversationMessage) -> Result<()> {
         println!("🔍 Attempting to save message: id={}, type={}, source={}", 
@@ -421,11 +763,13 @@ impl ConversationStorage {
         }
 
         let affected = self.connection.execute(
-            "INSERT INTO conversation_messages (id, session_id, type, source, content, timestamp, confidence) 
-             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO conversation_messages (id, session_id, type, source, content, timestamp, confidence, active_app, active_window_title, redacted, audio_offset, audio_duration_ms) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 message.id, session_id, message.message_type, message.source,
-                message.content, message.timestamp, message.confidence
+                message.content, message.timestamp, message.confidence,
+                message.active_app, message.active_window_title, message.redacted.unwrap_or(false),
+                    message.audio_offset, message.audio_duration_ms
             ]
         ).map_err(|e| {
             println!("❌ Failed to insert message: {}", e);
@@ -462,11 +806,13 @@ impl ConversationStorage {
 
             if !exists {
                 tx.execute(
-                    "INSERT INTO conversation_messages (id, session_id, type, source, content, timestamp, confidence) 
-                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    "INSERT INTO conversation_messages (id, session_id, type, source, content, timestamp, confidence, active_app, active_window_title, redacted, audio_offset, audio_duration_ms) 
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                     params![
                         message.id, session_id, message.message_type, message.source,
-                        message.content, message.timestamp, message.confidence
+                        message.content, message.timestamp, message.confidence,
+                        message.active_app, message.active_window_title, message.redacted.unwrap_or(false),
+                    message.audio_offset, message.audio_duration_ms
                     ]
                 )?;
                 saved_count += 1;
@@ -496,6 +842,14 @@ impl ConversationStorage {
             set_clauses.push("timestamp = ?");
             sql_params.push(rusqlite::types::Value::Integer(timestamp));
         }
+        if let Some(audio_offset) = updates.audio_offset {
+            set_clauses.push("audio_offset = ?");
+            sql_params.push(rusqlite::types::Value::Integer(audio_offset));
+        }
+        if let Some(audio_duration_ms) = updates.audio_duration_ms {
+            set_clauses.push("audio_duration_ms = ?");
+            sql_params.push(rusqlite::types::Value::Integer(audio_duration_ms));
+        }
 
         if set_clauses.is_empty() {
             return Ok(()); // No updates to apply
@@ -537,21 +891,138 @@ impl ConversationStorage {
 
     pub fn save_conversation_insight(&mut self, session_id: &str, insight: ConversationInsight) -> Result<()> {
         self.connection.execute(
-            "INSERT OR REPLACE INTO conversation_insights (id, session_id, text, timestamp, context_length, insight_type)
-             VALUES (?, ?, ?, ?, ?, ?)",
+            "INSERT OR REPLACE INTO conversation_insights (id, session_id, text, timestamp, context_length, insight_type, template_data)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
             params![
                 insight.id, session_id, insight.text, insight.timestamp,
-                insight.context_length, insight.insight_type
+                insight.context_length, insight.insight_type.as_str(), template_json(&insight)
             ]
         )?;
 
         Ok(())
     }
 
+    pub fn set_session_template(&mut self, session_id: &str, template_id: Option<&str>) -> Result<()> {
+        let affected = self.connection.execute(
+            "UPDATE conversation_sessions SET template_id = ? WHERE id = ?",
+            params![template_id, session_id],
+        )?;
+        if affected == 0 {
+            return Err(rusqlite::Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    pub fn list_templates(&self) -> Result<Vec<ConversationTemplate>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT id, name, description, sections FROM conversation_templates ORDER BY name"
+        )?;
+        let rows = stmt.query_map(params![], |row| {
+            let sections: String = row.get("sections")?;
+            Ok(ConversationTemplate {
+                id: row.get("id")?,
+                name: row.get("name")?,
+                description: row.get("description")?,
+                sections: serde_json::from_str(&sections).unwrap_or_default(),
+                built_in: false,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn save_template(&mut self, template: &ConversationTemplate) -> Result<()> {
+        let sections = serde_json::to_string(&template.sections)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO conversation_templates (id, name, description, sections, updated_at) VALUES (?, ?, ?, ?, ?)",
+            params![template.id, template.name, template.description, sections, chrono::Utc::now().timestamp_millis()],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_template(&mut self, template_id: &str) -> Result<bool> {
+        let affected = self.connection.execute("DELETE FROM conversation_templates WHERE id = ?", params![template_id])?;
+        Ok(affected > 0)
+    }
+
     pub fn get_conversation_insights(&self, session_id: &str) -> Result<Vec<ConversationInsight>> {
         self.load_conversation_insights(session_id)
     }
 
+    pub fn get_session_confidences(&self, session_id: &str) -> Result<Vec<f64>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT confidence FROM conversation_messages WHERE session_id = ? AND confidence IS NOT NULL"
+        )?;
+        let confidences = stmt.query_map([session_id], |row| row.get::<_, f64>(0))?;
+        confidences.collect()
+    }
+
+    pub fn save_session_quality_report(&mut self, report: &SessionQualityReport) -> Result<()> {
+        let json = serde_json::to_string(report)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO conversation_quality_reports (session_id, report, created_at) VALUES (?, ?, ?)",
+            params![report.session_id, json, report.generated_at]
+        )?;
+        Ok(())
+    }
+
+    pub fn get_session_quality_report(&self, session_id: &str) -> Result<Option<SessionQualityReport>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT report FROM conversation_quality_reports WHERE session_id = ?"
+        )?;
+        let mut rows = stmt.query([session_id])?;
+        match rows.next()? {
+            Some(row) => {
+                let json: String = row.get(0)?;
+                serde_json::from_str(&json)
+                    .map(Some)
+                    .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Build the quality report from the current capture metrics and store it with the session
+    pub fn finalize_session_quality(&mut self, session_id: &str) -> Result<SessionQualityReport> {
+        let confidences = self.get_session_confidences(session_id)?;
+        let metrics = crate::audio_loopback::quality_metrics::snapshot_for(session_id).unwrap_or_default();
+        let report = crate::audio_loopback::quality_metrics::build_report(session_id, &metrics, &confidences, true);
+        self.save_session_quality_report(&report)?;
+        println!("✅ Stored quality report for session {} ({} issues)", session_id, report.issues.len());
+        Ok(report)
+    }
+
+    pub fn save_chat_link(&mut self, link: &ConversationChatLink) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO conversation_chat_links (conversation_id, chat_id, range_start, range_end, brief, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![link.conversation_id, link.chat_id, link.range_start, link.range_end, link.brief, link.created_at]
+        )?;
+        Ok(())
+    }
+
+    /// Links for a conversation, or for a chat when `by_chat` is set.
+    pub fn get_chat_links(&self, id: &str, by_chat: bool) -> Result<Vec<ConversationChatLink>> {
+        let sql = if by_chat {
+            "SELECT conversation_id, chat_id, range_start, range_end, brief, created_at FROM conversation_chat_links WHERE chat_id = ? ORDER BY created_at DESC"
+        } else {
+            "SELECT conversation_id, chat_id, range_start, range_end, brief, created_at FROM conversation_chat_links WHERE conversation_id = ? ORDER BY created_at DESC"
+        };
+        let mut stmt = self.connection.prepare(sql)?;
+        let links = stmt.query_map([id], |row| {
+            Ok(ConversationChatLink {
+                conversation_id: row.get("conversation_id")?,
+                chat_id: row.get("chat_id")?,
+                range_start: row.get("range_start")?,
+                range_end: row.get("range_end")?,
+                brief: row.get("brief")?,
+                created_at: row.get("created_at")?,
+            })
+        })?;
+        links.collect()
+    }
+
     pub fn delete_conversation(&mut self, conversation_id: &str) -> Result<()> {
         let affected = self.connection.execute(
             "DELETE FROM conversation_sessions WHERE id = ?",
@@ -562,21 +1033,166 @@ impl ConversationStorage {
             return Err(rusqlite::Error::QueryReturnedNoRows);
         }
 
+        // Consent events have no foreign key to cascade from
+        self.connection.execute(
+            "DELETE FROM conversation_consent_events WHERE session_id = ?",
+            params![conversation_id]
+        )?;
+
         Ok(())
     }
 
     pub fn clear_all_conversations(&mut self) -> Result<()> {
         self.connection.execute("DELETE FROM conversation_sessions", params![])?;
+        self.connection.execute("DELETE FROM conversation_consent_events", params![])?;
+        Ok(())
+    }
+
+    pub fn record_consent_event(&mut self, event: &ConsentEvent) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO conversation_consent_events (session_id, event, basis, method, jurisdiction, source, note, timestamp)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![event.session_id, event.event, event.basis, event.method, event.jurisdiction, event.source, event.note, event.timestamp]
+        )?;
         Ok(())
     }
+
+    pub fn get_conversation_memory(&self, session_id: &str) -> Result<Option<ConversationMemory>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT session_id, summary, entities, covered_until, updated_at FROM conversation_memory WHERE session_id = ?"
+        )?;
+        let mut rows = stmt.query([session_id])?;
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+        let entities: String = row.get(2)?;
+        Ok(Some(ConversationMemory {
+            session_id: row.get(0)?,
+            summary: row.get(1)?,
+            entities: serde_json::from_str(&entities).unwrap_or_default(),
+            covered_until: row.get(3)?,
+            updated_at: row.get(4)?,
+        }))
+    }
+
+    pub fn save_conversation_memory(&mut self, memory: &ConversationMemory) -> Result<()> {
+        let entities = serde_json::to_string(&memory.entities)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO conversation_memory (session_id, summary, entities, covered_until, updated_at)
+             VALUES (?, ?, ?, ?, ?)",
+            params![memory.session_id, memory.summary, entities, memory.covered_until, memory.updated_at]
+        )?;
+        Ok(())
+    }
+
+    /// Forget a conversation's memory so it's rebuilt from the transcript as it now stands.
+    pub fn clear_conversation_memory(&mut self, session_id: &str) -> Result<()> {
+        self.connection.execute("DELETE FROM conversation_memory WHERE session_id = ?", params![session_id])?;
+        Ok(())
+    }
+
+    /// A session's consent events, oldest first
+    pub fn get_consent_events(&self, session_id: &str) -> Result<Vec<ConsentEvent>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT session_id, event, basis, method, jurisdiction, source, note, timestamp
+             FROM conversation_consent_events WHERE session_id = ? ORDER BY timestamp, id"
+        )?;
+        let events = stmt.query_map([session_id], |row| {
+            Ok(ConsentEvent {
+                session_id: row.get(0)?,
+                event: row.get(1)?,
+                basis: row.get(2)?,
+                method: row.get(3)?,
+                jurisdiction: row.get(4)?,
+                source: row.get(5)?,
+                note: row.get(6)?,
+                timestamp: row.get(7)?,
+            })
+        })?;
+        events.collect()
+    }
 }
 
 // Helper function to get database path
+fn insight_from_row(row: &rusqlite::Row) -> Result<ConversationInsight> {
+    let insight_type: String = row.get("insight_type")?;
+    Ok(ConversationInsight {
+        id: row.get("id")?,
+        text: row.get("text")?,
+        timestamp: row.get("timestamp")?,
+        context_length: row.get("context_length")?,
+        insight_type: InsightType::parse(&insight_type).ok_or_else(|| rusqlite::Error::FromSqlConversionFailure(
+            0,
+            rusqlite::types::Type::Text,
+            format!("Unknown insight type '{}'", insight_type).into(),
+        ))?,
+        template: row.get::<_, Option<String>>("template_data")?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+    })
+}
+
+fn template_json(insight: &ConversationInsight) -> Option<String> {
+    insight.template.as_ref().and_then(|template| serde_json::to_string(template).ok())
+}
+
+fn message_from_row(row: &rusqlite::Row) -> Result<ConversationMessage> {
+    Ok(ConversationMessage {
+        id: row.get("id")?,
+        message_type: row.get("type")?,
+        source: row.get("source")?,
+        content: row.get("content")?,
+        timestamp: row.get("timestamp")?,
+        confidence: row.get("confidence")?,
+        // Frontend-only fields set to None when loading from DB
+        is_preview: None,
+        is_typing: None,
+        persistence_state: Some("saved".to_string()),
+        retry_count: None,
+        last_save_attempt: None,
+        save_error: None,
+        active_app: row.get("active_app")?,
+        active_window_title: row.get("active_window_title")?,
+        redacted: row.get::<_, bool>("redacted")?.then_some(true),
+        audio_offset: row.get("audio_offset")?,
+        audio_duration_ms: row.get("audio_duration_ms")?,
+        speaker_id: row.get("speaker_id")?,
+        speaker_name: row.get("speaker_name")?,
+    })
+}
+
 fn get_database_path(app_handle: &AppHandle) -> std::result::Result<PathBuf, String> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    crate::data::paths::database_path(app_handle)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_db::MemoryDatabase;
+
+    #[test]
+    fn test_insights_filter_by_type_and_count_per_session() {
+        let db = MemoryDatabase::new();
+        let mut storage = ConversationStorage::open(db.path()).unwrap();
+        storage.save_or_update_session(serde_json::from_value(serde_json::json!({
+            "id": "s1", "name": "Planning", "startTime": 0, "endTime": null, "messages": [], "isActive": true
+        })).unwrap()).unwrap();
+        for (id, insight_type, timestamp) in [("a", "action_item", 1), ("b", "summary", 2), ("c", "action_item", 3)] {
+            let insight = serde_json::from_value(serde_json::json!({
+                "id": id, "text": id, "timestamp": timestamp, "contextLength": 0, "type": insight_type
+            })).unwrap();
+            storage.save_conversation_insight("s1", insight).unwrap();
+        }
+        assert!(serde_json::from_value::<ConversationInsight>(serde_json::json!({
+            "id": "d", "text": "d", "timestamp": 4, "contextLength": 0, "type": "gossip"
+        })).is_err());
 
-    Ok(app_data_dir.join("enteract_data.db"))
-}
\ No newline at end of file
+        let actions = storage.get_insights_by_type("s1", &[InsightType::ActionItem], Some(1)).unwrap();
+        assert_eq!(actions.iter().map(|i| i.id.as_str()).collect::<Vec<_>>(), vec!["c"]);
+        assert_eq!(storage.get_insights_by_type("s1", &[], None).unwrap().len(), 3);
+
+        let counts = storage.get_insight_counts("s1").unwrap();
+        assert_eq!(counts.total, 3);
+        assert_eq!(counts.by_type[&InsightType::ActionItem], 2);
+        assert_eq!(counts.by_type[&InsightType::FactCheck], 0);
+    }
+}
@@ -0,0 +1,5 @@
+// Knowledge graph storage - entities and relations linking conversations, chats and documents
+
+pub mod storage;
+
+pub use storage::*;
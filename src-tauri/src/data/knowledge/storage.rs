@@ -0,0 +1,284 @@
+// SQLite storage for the knowledge graph. Lives in the main database so mentions of deleted
+// conversations and chats can be pruned with a join; sources are referenced by id without foreign
+// keys because chat saves replace every row.
+use rusqlite::{Connection, Result, params};
+use tauri::AppHandle;
+use crate::data::types::{KnowledgeEntity, EntityMention, EntityRelation};
+use std::path::{Path, PathBuf};
+use crate::data::paths::open_database;
+
+pub struct SourceEntity {
+    pub key: String, // Normalized name, unique per entity
+    pub name: String,
+    pub entity_type: String,
+    pub snippet: String,
+}
+
+pub struct SourceRelation {
+    pub source_key: String,
+    pub relation: String,
+    pub target_key: String,
+}
+
+/// Everything extracted from one conversation, chat or document.
+pub struct SourceGraph {
+    pub source_type: String,
+    pub source_id: String,
+    pub title: String,
+    pub timestamp: i64,
+    pub content_hash: String,
+    pub entities: Vec<SourceEntity>,
+    pub relations: Vec<SourceRelation>,
+}
+
+pub struct KnowledgeStorage {
+    connection: Connection,
+}
+
+impl KnowledgeStorage {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let db_path = get_database_path(app_handle).map_err(|e| rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(e)
+        ))?;
+        Self::open(&db_path)
+    }
+
+    /// Knowledge-graph storage on the database at `db_path`, creating its tables if needed.
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let mut storage = Self { connection: open_database(db_path)? };
+        storage.initialize_knowledge_tables()?;
+        Ok(storage)
+    }
+
+    fn initialize_knowledge_tables(&mut self) -> Result<()> {
+        self.connection.execute_batch(r#"
+            -- Entities, deduplicated by normalized name
+            CREATE TABLE IF NOT EXISTS kg_entities (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                normalized_name TEXT NOT NULL UNIQUE,
+                entity_type TEXT NOT NULL
+            );
+
+            -- One row per entity per source
+            CREATE TABLE IF NOT EXISTS kg_mentions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entity_id TEXT NOT NULL,
+                source_type TEXT NOT NULL CHECK(source_type IN ('conversation', 'chat', 'document')),
+                source_id TEXT NOT NULL,
+                source_title TEXT NOT NULL,
+                snippet TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                UNIQUE(entity_id, source_type, source_id),
+                FOREIGN KEY (entity_id) REFERENCES kg_entities(id) ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS kg_relations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_entity_id TEXT NOT NULL,
+                relation TEXT NOT NULL,
+                target_entity_id TEXT NOT NULL,
+                source_type TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                UNIQUE(source_entity_id, relation, target_entity_id, source_type, source_id),
+                FOREIGN KEY (source_entity_id) REFERENCES kg_entities(id) ON DELETE CASCADE,
+                FOREIGN KEY (target_entity_id) REFERENCES kg_entities(id) ON DELETE CASCADE
+            );
+
+            -- Content hash of each source when it was last extracted, so unchanged sources are skipped
+            CREATE TABLE IF NOT EXISTS kg_indexed_sources (
+                source_type TEXT NOT NULL,
+                source_id TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                indexed_at INTEGER NOT NULL,
+                PRIMARY KEY (source_type, source_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_kg_mentions_entity_time ON kg_mentions(entity_id, timestamp);
+            CREATE INDEX IF NOT EXISTS idx_kg_mentions_source ON kg_mentions(source_type, source_id);
+            CREATE INDEX IF NOT EXISTS idx_kg_relations_source ON kg_relations(source_type, source_id);
+        "#)?;
+
+        Ok(())
+    }
+
+    pub fn indexed_hash(&self, source_type: &str, source_id: &str) -> Result<Option<String>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT content_hash FROM kg_indexed_sources WHERE source_type = ? AND source_id = ?"
+        )?;
+        let mut rows = stmt.query(params![source_type, source_id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replace whatever was previously extracted from the source.
+    pub fn replace_source_graph(&mut self, graph: &SourceGraph) -> Result<()> {
+        let tx = self.connection.transaction()?;
+
+        tx.execute(
+            "DELETE FROM kg_mentions WHERE source_type = ? AND source_id = ?",
+            params![graph.source_type, graph.source_id]
+        )?;
+        tx.execute(
+            "DELETE FROM kg_relations WHERE source_type = ? AND source_id = ?",
+            params![graph.source_type, graph.source_id]
+        )?;
+
+        for entity in &graph.entities {
+            tx.execute(
+                "INSERT INTO kg_entities (id, name, normalized_name, entity_type) VALUES (?, ?, ?, ?)
+                 ON CONFLICT(normalized_name) DO NOTHING",
+                params![uuid::Uuid::new_v4().to_string(), entity.name, entity.key, entity.entity_type]
+            )?;
+            tx.execute(
+                "INSERT OR IGNORE INTO kg_mentions (entity_id, source_type, source_id, source_title, snippet, timestamp)
+                 SELECT id, ?, ?, ?, ?, ? FROM kg_entities WHERE normalized_name = ?",
+                params![graph.source_type, graph.source_id, graph.title, entity.snippet, graph.timestamp, entity.key]
+            )?;
+        }
+
+        for relation in &graph.relations {
+            tx.execute(
+                "INSERT OR IGNORE INTO kg_relations (source_entity_id, relation, target_entity_id, source_type, source_id, timestamp)
+                 SELECT s.id, ?, t.id, ?, ?, ? FROM kg_entities s, kg_entities t
+                 WHERE s.normalized_name = ? AND t.normalized_name = ?",
+                params![relation.relation, graph.source_type, graph.source_id, graph.timestamp, relation.source_key, relation.target_key]
+            )?;
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO kg_indexed_sources (source_type, source_id, content_hash, indexed_at) VALUES (?, ?, ?, ?)",
+            params![graph.source_type, graph.source_id, graph.content_hash, chrono::Utc::now().timestamp_millis()]
+        )?;
+        tx.execute("DELETE FROM kg_entities WHERE id NOT IN (SELECT entity_id FROM kg_mentions)", params![])?;
+
+        tx.commit()
+    }
+
+    /// Drop mentions of conversations and chats that have since been deleted.
+    pub fn prune_missing_sources(&mut self) -> Result<usize> {
+        let tx = self.connection.transaction()?;
+        let mut removed = 0;
+        for (source_type, table) in [("conversation", "conversation_sessions"), ("chat", "chat_sessions")] {
+            // The source table may not exist yet on a fresh install
+            let exists: bool = tx.query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?",
+                params![table],
+                |row| row.get(0)
+            )?;
+            if !exists {
+                continue;
+            }
+            for graph_table in ["kg_mentions", "kg_relations", "kg_indexed_sources"] {
+                removed += tx.execute(
+                    &format!("DELETE FROM {} WHERE source_type = ? AND source_id NOT IN (SELECT id FROM {})", graph_table, table),
+                    params![source_type]
+                )?;
+            }
+        }
+        tx.execute("DELETE FROM kg_entities WHERE id NOT IN (SELECT entity_id FROM kg_mentions)", params![])?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Exact match on the normalized name, otherwise the most mentioned entity containing it.
+    pub fn find_entity(&self, key: &str) -> Result<Option<KnowledgeEntity>> {
+        if let Some(entity) = self.query_entities("WHERE e.normalized_name = ?1", key, 1)?.into_iter().next() {
+            return Ok(Some(entity));
+        }
+        Ok(self.search_entities(key, 1)?.into_iter().next())
+    }
+
+    pub fn search_entities(&self, key: &str, limit: usize) -> Result<Vec<KnowledgeEntity>> {
+        self.query_entities("WHERE e.normalized_name LIKE '%' || ?1 || '%'", key, limit)
+    }
+
+    fn query_entities(&self, filter: &str, key: &str, limit: usize) -> Result<Vec<KnowledgeEntity>> {
+        let mut stmt = self.connection.prepare(&format!(
+            "SELECT e.id, e.name, e.entity_type, MIN(m.timestamp), MAX(m.timestamp), COUNT(m.id)
+             FROM kg_entities e JOIN kg_mentions m ON m.entity_id = e.id
+             {}
+             GROUP BY e.id ORDER BY COUNT(m.id) DESC, MAX(m.timestamp) DESC LIMIT ?2",
+            filter
+        ))?;
+        let entities = stmt.query_map(params![key, limit as i64], |row| {
+            Ok(KnowledgeEntity {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                entity_type: row.get(2)?,
+                first_seen: row.get(3)?,
+                last_seen: row.get(4)?,
+                mention_count: row.get(5)?,
+            })
+        })?;
+        entities.collect()
+    }
+
+    /// Most mentioned entities in a time window, counted by mentions inside it.
+    pub fn top_entities_between(&self, start: i64, end: i64, limit: usize) -> Result<Vec<KnowledgeEntity>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT e.id, e.name, e.entity_type, MIN(m.timestamp), MAX(m.timestamp), COUNT(m.id)
+             FROM kg_entities e JOIN kg_mentions m ON m.entity_id = e.id
+             WHERE m.timestamp BETWEEN ?1 AND ?2
+             GROUP BY e.id ORDER BY COUNT(m.id) DESC, MAX(m.timestamp) DESC LIMIT ?3"
+        )?;
+        let entities = stmt.query_map(params![start, end, limit as i64], |row| {
+            Ok(KnowledgeEntity {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                entity_type: row.get(2)?,
+                first_seen: row.get(3)?,
+                last_seen: row.get(4)?,
+                mention_count: row.get(5)?,
+            })
+        })?;
+        entities.collect()
+    }
+
+    pub fn entity_mentions(&self, entity_id: &str) -> Result<Vec<EntityMention>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT source_type, source_id, source_title, snippet, timestamp
+             FROM kg_mentions WHERE entity_id = ? ORDER BY timestamp"
+        )?;
+        let mentions = stmt.query_map([entity_id], |row| {
+            Ok(EntityMention {
+                source_type: row.get(0)?,
+                source_id: row.get(1)?,
+                source_title: row.get(2)?,
+                snippet: row.get(3)?,
+                timestamp: row.get(4)?,
+            })
+        })?;
+        mentions.collect()
+    }
+
+    pub fn entity_relations(&self, entity_id: &str) -> Result<Vec<EntityRelation>> {
+        let mut stmt = self.connection.prepare(
+            "SELECT s.name, r.relation, t.name, r.source_type, r.source_id, r.timestamp
+             FROM kg_relations r
+             JOIN kg_entities s ON s.id = r.source_entity_id
+             JOIN kg_entities t ON t.id = r.target_entity_id
+             WHERE r.source_entity_id = ?1 OR r.target_entity_id = ?1
+             ORDER BY r.timestamp"
+        )?;
+        let relations = stmt.query_map([entity_id], |row| {
+            Ok(EntityRelation {
+                source_entity: row.get(0)?,
+                relation: row.get(1)?,
+                target_entity: row.get(2)?,
+                source_type: row.get(3)?,
+                source_id: row.get(4)?,
+                timestamp: row.get(5)?,
+            })
+        })?;
+        relations.collect()
+    }
+}
+
+fn get_database_path(app_handle: &AppHandle) -> std::result::Result<PathBuf, String> {
+    crate::data::paths::database_path(app_handle)
+}
@@ -1,7 +1,7 @@
 // Migration utilities for SQLite database initialization
 // This module handles database setup and schema creation
 
-use tauri::{AppHandle, Manager, command};
+use tauri::{AppHandle, command};
 use serde::{Serialize, Deserialize};
 use rusqlite::{Connection, params, Result as SqliteResult, Error as SqliteError};
 use std::path::PathBuf;
@@ -429,10 +429,7 @@ pub fn cleanup_legacy_files(app_handle: AppHandle, confirm: bool) -> Result<Vec<
         return Err("Confirmation required to delete legacy files".to_string());
     }
 
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let app_data_dir = crate::data::paths::user_data_dir(&app_handle)?;
 
     let mut removed_files = Vec::new();
 
@@ -532,6 +529,11 @@ fn get_database_schema() -> String {
         analysis_types TEXT, -- JSON array stored as text
         search_queries TEXT, -- JSON array stored as text
         sources TEXT, -- JSON array stored as text
+        model_tag TEXT,
+        provider TEXT,
+        generation_options TEXT, -- JSON object stored as text
+        rag_chunk_ids TEXT, -- JSON array stored as text
+        screen_context_id TEXT,
         FOREIGN KEY (message_id) REFERENCES chat_messages(id) ON DELETE CASCADE
     );
 
@@ -541,7 +543,8 @@ fn get_database_schema() -> String {
         name TEXT NOT NULL,
         start_time INTEGER NOT NULL,
         end_time INTEGER,
-        is_active INTEGER NOT NULL CHECK(is_active IN (0, 1))
+        is_active INTEGER NOT NULL CHECK(is_active IN (0, 1)),
+        template_id TEXT
     );
 
     -- Conversation messages table
@@ -553,6 +556,11 @@ fn get_database_schema() -> String {
         content TEXT NOT NULL,
         timestamp INTEGER NOT NULL,
         confidence REAL,
+        active_app TEXT,
+        active_window_title TEXT,
+        redacted INTEGER NOT NULL DEFAULT 0,
+        audio_offset INTEGER,
+        audio_duration_ms INTEGER,
         FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
     );
 
@@ -563,7 +571,8 @@ fn get_database_schema() -> String {
         text TEXT NOT NULL,
         timestamp INTEGER NOT NULL,
         context_length INTEGER NOT NULL,
-        insight_type TEXT NOT NULL CHECK(insight_type IN ('insight', 'welcome', 'question', 'answer')),
+        insight_type TEXT NOT NULL CHECK(insight_type IN ('summary', 'action_item', 'question', 'fact_check', 'sentiment', 'custom', 'insight', 'welcome', 'answer', 'template')),
+        template_data TEXT,
         FOREIGN KEY (session_id) REFERENCES conversation_sessions(id) ON DELETE CASCADE
     );
 
@@ -587,10 +596,5 @@ fn get_database_schema() -> String {
 
 // Helper function to get database path
 fn get_database_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-
-    Ok(app_data_dir.join("enteract_data.db"))
+    crate::data::paths::database_path(app_handle)
 }
\ No newline at end of file
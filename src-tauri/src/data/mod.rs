@@ -4,10 +4,14 @@
 pub mod types;           // Core data structures
 pub mod chat;            // Chat session storage (Claude conversations)
 pub mod conversation;    // Audio conversation storage
+pub mod knowledge;       // Knowledge graph of entities across conversations, chats and documents
 pub mod migration;       // Database initialization and cleanup
 pub mod errors;          // Error handling types and utilities
 pub mod connection_pool; // Database connection pooling
 pub mod logging;         // Comprehensive logging system
+pub mod paths;           // Per-OS-user data directory and database path
+pub mod settings;        // Key/value settings stored in the main database
+pub mod backup;          // Scheduled and on-demand database backups with rotation
 
 // Re-export all the commonly used types and functions
 pub use types::*;
@@ -17,6 +21,15 @@ pub use errors::*;
 pub use chat::{
     save_chat_sessions,
     load_chat_sessions,
+    get_message_variants,
+    select_message_variant,
+    get_message_provenance,
+    toggle_bookmark,
+    toggle_message_reaction,
+    list_bookmarked_messages,
+    get_chat_token_usage,
+    get_context_budget,
+    set_context_budget,
 };
 
 // Re-export conversation commands
@@ -29,10 +42,17 @@ pub use conversation::{
     batch_save_conversation_messages,
     update_conversation_message,
     delete_conversation_message,
+    get_conversation_messages_by_app,
+    get_conversation_app_usage,
+    set_conversation_range_redacted,
+    export_conversation_transcript,
     save_conversation_insight,
     get_conversation_insights,
+    get_insights_by_type,
+    get_insight_counts,
     update_session_metadata,
     update_session_active_state,
+    get_session_quality,
     ping_backend,
 };
 
@@ -44,6 +64,15 @@ pub use migration::{
     check_database_health,
 };
 
+// Re-export backup commands
+pub use backup::{
+    create_backup_now,
+    list_backups,
+    restore_backup,
+    get_backup_settings,
+    save_backup_settings,
+};
+
 // Re-export logging commands
 pub use logging::{
     get_database_logs,
@@ -0,0 +1,148 @@
+// Where the data layer keeps its files. Everything lives under the current OS user's app data
+// directory, so two people sharing a machine never open the same database. ENTERACT_DATA_DIR can
+// point the data somewhere else (e.g. a shared drive); each OS user still gets their own folder in it.
+// ENTERACT_IN_MEMORY_DB=1 keeps the database in memory instead, for demos and end-to-end runs.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+pub const DATABASE_FILE: &str = "enteract_data.db";
+const APP_IDENTIFIER: &str = "com.enteract.app"; // Must match tauri.conf.json
+
+lazy_static::lazy_static! {
+    // A shared-cache memory database lives as long as one connection to it is open
+    static ref IN_MEMORY_KEEPER: Mutex<Option<rusqlite::Connection>> = Mutex::new(None);
+}
+
+/// SQLite URI for a named in-memory database that every connection in this process can open.
+/// Storage code opens it like a file path.
+pub fn in_memory_database_uri(name: &str) -> PathBuf {
+    PathBuf::from(format!("file:{}?mode=memory&cache=shared", name))
+}
+
+pub fn in_memory_database_requested() -> bool {
+    std::env::var("ENTERACT_IN_MEMORY_DB").map_or(false, |value| value == "1" || value == "true")
+}
+
+fn shared_in_memory_database() -> Result<PathBuf, String> {
+    let uri = in_memory_database_uri("enteract");
+    let mut keeper = IN_MEMORY_KEEPER.lock().map_err(|e| e.to_string())?;
+    if keeper.is_none() {
+        println!("🧪 Using an in-memory database; nothing will be saved");
+        *keeper = Some(rusqlite::Connection::open(&uri).map_err(|e| format!("Failed to open in-memory database: {}", e))?);
+    }
+    Ok(uri)
+}
+
+/// The OS account name, reduced to characters that are safe in a path.
+pub fn os_user_name() -> String {
+    let name = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .or_else(|_| std::env::var("LOGNAME"))
+        .unwrap_or_default();
+    let safe: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+    if safe.trim_matches('.').is_empty() {
+        "default".to_string()
+    } else {
+        safe
+    }
+}
+
+/// Resolve the per-user data directory without an app handle, for use before Tauri is built.
+/// `platform_app_data_dir` is what Tauri's `app_data_dir()` would return.
+pub fn resolve_user_data_dir(platform_app_data_dir: PathBuf) -> PathBuf {
+    match std::env::var_os("ENTERACT_DATA_DIR").filter(|dir| !dir.is_empty()) {
+        Some(root) => Path::new(&root).join(os_user_name()),
+        None => platform_app_data_dir,
+    }
+}
+
+/// The per-user data directory computed the way Tauri's `app_data_dir()` does, for code that runs
+/// without an app (startup checks, the CLI).
+pub fn default_user_data_dir() -> PathBuf {
+    let platform_dir = dirs::data_dir().unwrap_or_else(std::env::temp_dir).join(APP_IDENTIFIER);
+    resolve_user_data_dir(platform_dir)
+}
+
+/// The `enteract` folder in the OS config directory, where settings files live. Created if needed.
+pub fn config_dir() -> anyhow::Result<PathBuf> {
+    let dir = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
+        .join("enteract");
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+    Ok(dir)
+}
+
+/// Path of the settings file `name` in `config_dir`.
+pub fn config_file(name: &str) -> anyhow::Result<PathBuf> {
+    Ok(config_dir()?.join(name))
+}
+
+/// Create the directory if needed; on Unix it's made private to the user.
+pub fn ensure_private_dir(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Open the database at `db_path`, which may also be an in-memory URI from `in_memory_database_uri`.
+/// A file's directory is created first. Every storage opens through here, so they all share the
+/// same foreign-key, WAL and cache settings.
+pub fn open_database(db_path: &Path) -> rusqlite::Result<rusqlite::Connection> {
+    if let Some(parent) = db_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent).map_err(|e| rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_IOERR),
+                Some(format!("Failed to create directory: {}", e)),
+            ))?;
+        }
+    }
+
+    let connection = rusqlite::Connection::open(db_path)?;
+    connection.execute("PRAGMA foreign_keys = ON", []).map_err(|e| {
+        println!("⚠️ Warning: Failed to set foreign_keys: {}", e);
+        e
+    })?;
+
+    // WAL returns the resulting mode, so it has to be read back rather than executed. In-memory
+    // databases always answer "memory".
+    match connection.query_row("PRAGMA journal_mode = WAL", [], |row| row.get::<_, String>(0)) {
+        Ok(mode) if !mode.eq_ignore_ascii_case("wal") && !mode.eq_ignore_ascii_case("memory") => println!("ℹ️ Journal mode is: {} (WAL may not be available)", mode),
+        Ok(_) => {}
+        Err(e) => println!("⚠️ Warning: Could not set journal mode: {}", e),
+    }
+
+    // Tuning only; a failure here doesn't stop the database from working
+    connection.execute("PRAGMA synchronous = NORMAL", []).ok();
+    connection.execute("PRAGMA cache_size = 10000", []).ok();
+    connection.execute("PRAGMA temp_store = memory", []).ok();
+    Ok(connection)
+}
+
+pub fn user_data_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let dir = resolve_user_data_dir(app_data_dir);
+    ensure_private_dir(&dir).map_err(|e| format!("Failed to create data directory {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+pub fn database_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    if in_memory_database_requested() {
+        return shared_in_memory_database();
+    }
+    Ok(user_data_dir(app_handle)?.join(DATABASE_FILE))
+}
@@ -0,0 +1,52 @@
+// Key/value settings kept in the main database, for settings that belong with the user's data
+// rather than in a JSON file under the config directory. Values are JSON text.
+use rusqlite::{Connection, OptionalExtension, Result, params};
+use tauri::AppHandle;
+use std::path::Path;
+use crate::data::paths::open_database;
+
+pub struct SettingsStorage {
+    connection: Connection,
+}
+
+impl SettingsStorage {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let db_path = crate::data::paths::database_path(app_handle).map_err(|e| rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(e)
+        ))?;
+        Self::open(&db_path)
+    }
+
+    /// The settings table in the database at `db_path`.
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let connection = open_database(db_path)?;
+        connection.execute_batch(r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            );
+        "#)?;
+        Ok(Self { connection })
+    }
+
+    pub fn get<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let value: Option<String> = self.connection
+            .query_row("SELECT value FROM settings WHERE key = ?", [key], |row| row.get(0))
+            .optional()?;
+        // A value that no longer parses is treated as unset rather than failing every reader
+        Ok(value.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    pub fn set<T: serde::Serialize>(&mut self, key: &str, value: &T) -> Result<()> {
+        let json = serde_json::to_string(value)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.connection.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![key, json, chrono::Utc::now().timestamp_millis()],
+        )?;
+        Ok(())
+    }
+}
@@ -21,6 +21,11 @@ pub struct ChatMessage {
     #[serde(rename = "messageType")]
     pub message_type: Option<String>,
     pub metadata: Option<MessageMetadata>,
+    // Filled in on load from message_marks; saves don't change them, the bookmark and reaction commands do
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bookmarked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +89,91 @@ pub struct MessageMetadata {
     #[serde(rename = "searchQueries")]
     pub search_queries: Option<Vec<String>>,
     pub sources: Option<Vec<String>>,
+    // Provenance: what exactly produced the message, for reproducing it later
+    #[serde(rename = "modelTag")]
+    pub model_tag: Option<String>, // Full tag as sent to the provider, e.g. "gemma3:1b-it-qat"
+    pub provider: Option<String>,
+    #[serde(rename = "generationOptions")]
+    pub generation_options: Option<serde_json::Value>, // Resolved options object sent with the request
+    #[serde(rename = "ragChunkIds")]
+    pub rag_chunk_ids: Option<Vec<String>>,
+    #[serde(rename = "screenContextId")]
+    pub screen_context_id: Option<String>,
+    // Token counts reported by the model; `tokens` is their sum
+    #[serde(rename = "promptTokens", default)]
+    pub prompt_tokens: Option<i32>,
+    #[serde(rename = "completionTokens", default)]
+    pub completion_tokens: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageProvenance {
+    #[serde(rename = "messageId")]
+    pub message_id: i32,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub sender: String,
+    pub timestamp: String,
+    pub metadata: Option<MessageMetadata>,
+    #[serde(rename = "selectedVariant")]
+    pub selected_variant: Option<MessageVariant>, // Set when the shown text came from a draft/refine or compare run
+}
+
+/// A chat or conversation message that matched a text search, with the session it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSearchHit {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "sessionTitle")]
+    pub session_title: String,
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    pub sender: String,
+    pub text: String,
+    pub timestamp: String, // ISO 8601 string
+    pub bookmarked: bool, // Always false for conversation messages, which can't be marked
+    pub reactions: Vec<String>,
+}
+
+/// A bookmarked chat message with enough of its chat to show it in a list and jump back to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkedMessage {
+    #[serde(rename = "messageId")]
+    pub message_id: i32,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "sessionTitle")]
+    pub session_title: String,
+    pub text: String,
+    pub sender: String,
+    pub timestamp: String,
+    pub reactions: Vec<String>,
+    #[serde(rename = "bookmarkedAt")]
+    pub bookmarked_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookmarkFilter {
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    pub query: Option<String>, // Case-insensitive match on the message text
+    pub reaction: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Tokens a chat has consumed, summed over the messages that recorded counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatTokenUsage {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "promptTokens")]
+    pub prompt_tokens: i64,
+    #[serde(rename = "completionTokens")]
+    pub completion_tokens: i64,
+    #[serde(rename = "totalTokens")]
+    pub total_tokens: i64,
+    #[serde(rename = "countedMessages")]
+    pub counted_messages: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -110,6 +200,42 @@ pub struct LoadChatsResponse {
     pub chats: Vec<ChatSession>,
 }
 
+// Alternative responses for one chat message (draft/refined pairs, multi-agent comparisons)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageVariant {
+    pub id: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "messageId")]
+    pub message_id: i32,
+    pub kind: String, // 'draft' | 'refined' | 'compare'
+    pub text: String,
+    pub model: Option<String>,
+    #[serde(rename = "agentType")]
+    pub agent_type: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "isSelected")]
+    pub is_selected: bool,
+}
+
+// Code block extracted from an assistant message into the snippet library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeSnippet {
+    pub id: String,
+    pub language: String,
+    pub code: String,
+    pub description: String,
+    #[serde(rename = "chatId")]
+    pub chat_id: Option<String>,
+    #[serde(rename = "messageId")]
+    pub message_id: Option<i32>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "useCount")]
+    pub use_count: i32,
+}
+
 // ============================================================================
 // CONVERSATION TYPES (Audio Conversations)
 // ============================================================================
@@ -136,6 +262,67 @@ pub struct ConversationMessage {
     pub last_save_attempt: Option<i64>,
     #[serde(rename = "saveError", skip_serializing_if = "Option::is_none")]
     pub save_error: Option<String>,
+    // Foreground application when the message was spoken
+    #[serde(rename = "activeApp", skip_serializing_if = "Option::is_none")]
+    pub active_app: Option<String>,
+    #[serde(rename = "activeWindowTitle", skip_serializing_if = "Option::is_none")]
+    pub active_window_title: Option<String>,
+    // Off the record: kept in the transcript, left out of everything derived from it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redacted: Option<bool>,
+    // Byte offset and length of the message's clip in the kept conversation audio
+    #[serde(rename = "audioOffset", skip_serializing_if = "Option::is_none")]
+    pub audio_offset: Option<i64>,
+    #[serde(rename = "audioDurationMs", skip_serializing_if = "Option::is_none")]
+    pub audio_duration_ms: Option<i64>,
+    // Named speaker, assigned through the speaker commands rather than message saves
+    #[serde(rename = "speakerId", skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<String>,
+    #[serde(rename = "speakerName", skip_serializing_if = "Option::is_none")]
+    pub speaker_name: Option<String>,
+}
+
+/// A message found by application, with the session it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationAppMessage {
+    pub session_id: String,
+    #[serde(flatten)]
+    pub message: ConversationMessage,
+}
+
+/// A named speaker with how much they have been heard across conversations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Speaker {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "hasVoiceprint")]
+    pub has_voiceprint: bool,
+    #[serde(rename = "messageCount")]
+    pub message_count: usize,
+    #[serde(rename = "sessionCount")]
+    pub session_count: usize,
+    #[serde(rename = "speechMs")]
+    pub speech_ms: i64,
+    #[serde(rename = "lastHeard")]
+    pub last_heard: Option<i64>,
+}
+
+/// A stored speaker whose voice matches one audio source of a conversation, to pre-fill its label.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerSuggestion {
+    pub source: String, // 'microphone' | 'loopback'
+    #[serde(rename = "speakerId")]
+    pub speaker_id: String,
+    pub name: String,
+    pub similarity: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationAppUsage {
+    pub app_name: String,
+    pub message_count: usize,
+    pub first_timestamp: i64,
+    pub last_timestamp: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,7 +333,101 @@ pub struct ConversationInsight {
     #[serde(rename = "contextLength")]
     pub context_length: i32,
     #[serde(rename = "type")]
-    pub insight_type: String, // 'insight' | 'welcome' | 'question' | 'answer'
+    pub insight_type: InsightType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<FilledTemplate>, // Set on 'template' insights
+}
+
+/// What an insight is. The first six are the taxonomy the insight tabs filter on; the rest are the
+/// live AI panel's own kinds and filled note templates, which predate it and stay valid.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum InsightType {
+    Summary,
+    ActionItem,
+    Question,
+    FactCheck,
+    Sentiment,
+    Custom,
+    Insight,
+    Welcome,
+    Answer,
+    Template,
+}
+
+impl InsightType {
+    pub const TAXONOMY: [InsightType; 6] = [
+        InsightType::Summary,
+        InsightType::ActionItem,
+        InsightType::Question,
+        InsightType::FactCheck,
+        InsightType::Sentiment,
+        InsightType::Custom,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InsightType::Summary => "summary",
+            InsightType::ActionItem => "action_item",
+            InsightType::Question => "question",
+            InsightType::FactCheck => "fact_check",
+            InsightType::Sentiment => "sentiment",
+            InsightType::Custom => "custom",
+            InsightType::Insight => "insight",
+            InsightType::Welcome => "welcome",
+            InsightType::Answer => "answer",
+            InsightType::Template => "template",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(value.to_string())).ok()
+    }
+}
+
+/// How many insights of each type a session has, for the insight tabs. Taxonomy types are
+/// always present, with zero when there are none.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsightCounts {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub total: usize,
+    #[serde(rename = "byType")]
+    pub by_type: std::collections::HashMap<InsightType, usize>,
+}
+
+/// A section a note template asks the summarizer to fill, e.g. decisions or blockers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateSection {
+    pub key: String,
+    pub title: String,
+    pub instructions: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub sections: Vec<TemplateSection>,
+    #[serde(rename = "builtIn", default)]
+    pub built_in: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilledSection {
+    pub key: String,
+    pub title: String,
+    pub items: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilledTemplate {
+    #[serde(rename = "templateId")]
+    pub template_id: String,
+    #[serde(rename = "templateName")]
+    pub template_name: String,
+    pub sections: Vec<FilledSection>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -162,6 +443,9 @@ pub struct ConversationSession {
     pub is_active: bool,
     #[serde(default)]
     pub insights: Vec<ConversationInsight>,
+    // Note template picked at session start, filled in when the session ends
+    #[serde(rename = "templateId", default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
 }
 
 // Request/Response types for conversation operations
@@ -175,12 +459,149 @@ pub struct LoadConversationsResponse {
     pub conversations: Vec<ConversationSession>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ConfidenceDistribution {
+    pub count: u32,
+    pub mean: Option<f64>,
+    pub low: u32,    // < 0.5
+    pub medium: u32, // 0.5 - 0.8
+    pub high: u32,   // >= 0.8
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SessionQualityReport {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "generatedAt")]
+    pub generated_at: i64,
+    #[serde(rename = "audioChunks")]
+    pub audio_chunks: u64,
+    #[serde(rename = "averageSnrDb")]
+    pub average_snr_db: Option<f64>,
+    #[serde(rename = "clippingEvents")]
+    pub clipping_events: u64,
+    #[serde(rename = "droppedChunks")]
+    pub dropped_chunks: u64,
+    #[serde(rename = "filteredTranscriptions")]
+    pub filtered_transcriptions: u64,
+    pub confidence: ConfidenceDistribution,
+    pub issues: Vec<String>,
+    #[serde(rename = "isFinal")]
+    pub is_final: bool,
+}
+
+// Recording consent confirmed, withdrawn or relied on for a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentEvent {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub event: String,                // "granted", "revoked", "recording_started" or "recording_stopped"
+    pub basis: Option<String>,        // Why a recording was allowed: "explicit", "jurisdiction" or "not_required"
+    pub method: Option<String>,       // How consent was collected, e.g. "banner" or "verbal"
+    pub jurisdiction: Option<String>,
+    pub source: Option<String>,       // Capture source of a recording event
+    pub note: Option<String>,
+    pub timestamp: i64,
+}
+
+// Rolling memory of a conversation: a summary of what was said so far and who and what came up,
+// folded forward as the conversation grows and injected into conversational AI prompts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationMemory {
+    pub session_id: String,
+    pub summary: String,
+    pub entities: Vec<MemoryEntity>,
+    pub covered_until: i64, // Timestamp of the last message folded into the memory
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEntity {
+    pub name: String,
+    pub entity_type: String,
+    pub mentions: u32, // Memory updates that found it
+    pub last_seen: i64,
+}
+
+// Link between a conversation and the chat that continues it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationChatLink {
+    #[serde(rename = "conversationId")]
+    pub conversation_id: String,
+    #[serde(rename = "chatId")]
+    pub chat_id: String,
+    #[serde(rename = "rangeStart")]
+    pub range_start: Option<i64>, // Transcript range (message timestamps) the brief was built from
+    #[serde(rename = "rangeEnd")]
+    pub range_end: Option<i64>,
+    pub brief: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+// ============================================================================
+// KNOWLEDGE GRAPH TYPES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KnowledgeEntity {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "entityType")]
+    pub entity_type: String, // person | organization | product | project | place | topic
+    #[serde(rename = "firstSeen")]
+    pub first_seen: i64,
+    #[serde(rename = "lastSeen")]
+    pub last_seen: i64,
+    #[serde(rename = "mentionCount")]
+    pub mention_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityMention {
+    #[serde(rename = "sourceType")]
+    pub source_type: String, // conversation | chat | document
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    #[serde(rename = "sourceTitle")]
+    pub source_title: String,
+    pub snippet: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityRelation {
+    #[serde(rename = "sourceEntity")]
+    pub source_entity: String,
+    pub relation: String,
+    #[serde(rename = "targetEntity")]
+    pub target_entity: String,
+    #[serde(rename = "sourceType")]
+    pub source_type: String,
+    #[serde(rename = "sourceId")]
+    pub source_id: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityTimeline {
+    pub entity: KnowledgeEntity,
+    pub mentions: Vec<EntityMention>, // Oldest first
+    pub relations: Vec<EntityRelation>,
+}
+
 // Update structures for granular operations
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConversationMessageUpdate {
     pub content: Option<String>,
     pub confidence: Option<f64>,
     pub timestamp: Option<i64>,
+    #[serde(rename = "audioOffset", default)]
+    pub audio_offset: Option<i64>,
+    #[serde(rename = "audioDurationMs", default)]
+    pub audio_duration_ms: Option<i64>,
 }
 
 // ============================================================================
@@ -0,0 +1,391 @@
+// src-tauri/src/dictation.rs
+// Dictation mode: final microphone transcript segments are typed into whatever field has focus,
+// with spoken punctuation commands and suppression while the user is typing themselves. Only the
+// user's own voice is dictated; system audio (the other side of a call) never reaches this module.
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::mcp::tools::{ComputerUseTool, KeyPressTool, TypeTool};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictationSettings {
+    pub keystroke_delay_ms: u64,
+    pub manual_typing_grace_ms: u64, // Hold dictated text while the user typed within this window
+    pub punctuation_commands: bool,
+}
+
+impl Default for DictationSettings {
+    fn default() -> Self {
+        Self {
+            keystroke_delay_ms: 5,
+            manual_typing_grace_ms: 1500,
+            punctuation_commands: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictationStatus {
+    pub active: bool,
+    pub paused: bool,
+    pub pending_segments: usize,
+    pub settings: DictationSettings,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DictationAction {
+    Text(String),
+    Key(&'static str),
+}
+
+#[derive(Default)]
+struct DictationState {
+    active: bool,
+    paused: bool,
+    settings: DictationSettings,
+    pending: Vec<String>,
+    last_synthetic_input: Option<Instant>,
+    previous_profile_id: Option<String>,
+    flush_task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+lazy_static::lazy_static! {
+    static ref DICTATION_STATE: Arc<Mutex<DictationState>> = Arc::new(Mutex::new(DictationState::default()));
+    // Serializes typing so segments never interleave keystrokes
+    static ref TYPING_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
+}
+
+// Spoken phrase -> punctuation
+const PUNCTUATION_COMMANDS: &[(&str, &str)] = &[
+    ("exclamation mark", "!"),
+    ("exclamation point", "!"),
+    ("question mark", "?"),
+    ("full stop", "."),
+    ("semicolon", ";"),
+    ("period", "."),
+    ("comma", ","),
+    ("colon", ":"),
+    ("hyphen", "-"),
+];
+
+const KEY_COMMANDS: &[(&str, &[&str])] = &[
+    ("new paragraph", &["enter", "enter"]),
+    ("new line", &["enter"]),
+];
+
+fn normalize_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Turn a transcript segment into text runs and key presses, honouring spoken punctuation.
+pub fn parse_dictation(segment: &str, punctuation_commands: bool) -> Vec<DictationAction> {
+    let words: Vec<&str> = segment.split_whitespace().collect();
+    let mut actions = Vec::new();
+    let mut current = String::new();
+
+    let flush = |current: &mut String, actions: &mut Vec<DictationAction>| {
+        if !current.is_empty() {
+            actions.push(DictationAction::Text(std::mem::take(current)));
+        }
+    };
+
+    let mut i = 0;
+    'words: while i < words.len() {
+        if punctuation_commands {
+            for (phrase, keys) in KEY_COMMANDS {
+                let len = phrase.split(' ').count();
+                if i + len <= words.len()
+                    && words[i..i + len].iter().map(|w| normalize_word(w)).collect::<Vec<_>>().join(" ") == *phrase
+                {
+                    // Whisper tends to punctuate around the command; keep the text clean
+                    let trimmed = current.trim_end_matches(|c: char| c == ' ' || c == ',').to_string();
+                    current = trimmed;
+                    flush(&mut current, &mut actions);
+                    actions.extend(keys.iter().map(|key| DictationAction::Key(*key)));
+                    i += len;
+                    continue 'words;
+                }
+            }
+
+            for (phrase, mark) in PUNCTUATION_COMMANDS {
+                let len = phrase.split(' ').count();
+                if i + len <= words.len()
+                    && words[i..i + len].iter().map(|w| normalize_word(w)).collect::<Vec<_>>().join(" ") == *phrase
+                {
+                    let trimmed = current.trim_end_matches(|c: char| c == ' ' || c.is_ascii_punctuation()).to_string();
+                    current = trimmed;
+                    current.push_str(mark);
+                    i += len;
+                    continue 'words;
+                }
+            }
+        }
+
+        let starts_run = current.is_empty() && matches!(actions.last(), Some(DictationAction::Key(_)));
+        if !current.is_empty() || (!actions.is_empty() && !starts_run) {
+            current.push(' ');
+        }
+        current.push_str(words[i]);
+        i += 1;
+    }
+
+    flush(&mut current, &mut actions);
+    actions
+}
+
+// True when real keyboard/mouse input happened recently and after our own synthetic typing
+fn user_is_typing(settings: &DictationSettings, last_synthetic_input: Option<Instant>) -> bool {
+    let idle_ms = match crate::presence::get_idle_millis() {
+        Some(idle_ms) => idle_ms,
+        None => return false,
+    };
+    if idle_ms >= settings.manual_typing_grace_ms {
+        return false;
+    }
+    match last_synthetic_input {
+        Some(last) => last.elapsed().as_millis() as u64 > idle_ms + 100,
+        None => true,
+    }
+}
+
+async fn type_actions(actions: &[DictationAction], delay_ms: u64) -> Result<(), String> {
+    let _guard = TYPING_LOCK.lock().await;
+    for action in actions {
+        let result = match action {
+            DictationAction::Text(text) => {
+                TypeTool.execute(serde_json::json!({ "text": text, "delay_ms": delay_ms }), "dictation").await?
+            }
+            DictationAction::Key(key) => {
+                KeyPressTool.execute(serde_json::json!({ "key": key }), "dictation").await?
+            }
+        };
+        if !result.success {
+            return Err(result.error.unwrap_or_else(|| "Typing failed".to_string()));
+        }
+    }
+
+    if let Ok(mut state) = DICTATION_STATE.lock() {
+        state.last_synthetic_input = Some(Instant::now());
+    }
+    Ok(())
+}
+
+async fn type_segment(app_handle: &AppHandle, segment: String, settings: &DictationSettings) -> Result<(), String> {
+    let mut actions = parse_dictation(&segment, settings.punctuation_commands);
+
+    // Separate consecutive segments with a space unless the segment starts on a new line
+    let typed_before = DICTATION_STATE
+        .lock()
+        .map(|state| state.last_synthetic_input.is_some())
+        .unwrap_or(false);
+    if let (true, Some(DictationAction::Text(first))) = (typed_before, actions.first_mut()) {
+        first.insert(0, ' ');
+    }
+
+    type_actions(&actions, settings.keystroke_delay_ms).await?;
+    let _ = app_handle.emit("dictation-typed", serde_json::json!({ "text": segment }));
+    Ok(())
+}
+
+// Wait for the user to stop typing, then type whatever dictation was held back
+fn schedule_flush(app_handle: AppHandle) {
+    let mut state = match DICTATION_STATE.lock() {
+        Ok(state) => state,
+        Err(_) => return,
+    };
+    if state.flush_task.is_some() {
+        return;
+    }
+
+    state.flush_task = Some(tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+
+            let (pending, settings) = {
+                let mut state = match DICTATION_STATE.lock() {
+                    Ok(state) => state,
+                    Err(_) => return,
+                };
+                if !state.active {
+                    state.flush_task = None;
+                    return;
+                }
+                if state.paused || user_is_typing(&state.settings, state.last_synthetic_input) {
+                    continue;
+                }
+                state.flush_task = None;
+                (std::mem::take(&mut state.pending), state.settings.clone())
+            };
+
+            for segment in pending {
+                if let Err(e) = type_segment(&app_handle, segment, &settings).await {
+                    eprintln!("Dictation flush failed: {}", e);
+                }
+            }
+            return;
+        }
+    }));
+}
+
+/// Route a final transcript segment into the focused field when dictation is active.
+pub async fn handle_final_segment(app_handle: &AppHandle, segment: &str) -> Result<bool, String> {
+    let segment = segment.trim();
+    if segment.is_empty() {
+        return Ok(false);
+    }
+
+    let settings = {
+        let mut state = DICTATION_STATE.lock().map_err(|e| e.to_string())?;
+        if !state.active {
+            return Ok(false);
+        }
+        if state.paused || user_is_typing(&state.settings, state.last_synthetic_input) || !state.pending.is_empty() {
+            state.pending.push(segment.to_string());
+            let pending = state.pending.len();
+            drop(state);
+
+            let _ = app_handle.emit("dictation-suppressed", serde_json::json!({ "pendingSegments": pending }));
+            schedule_flush(app_handle.clone());
+            return Ok(false);
+        }
+        state.settings.clone()
+    };
+
+    type_segment(app_handle, segment.to_string(), &settings).await?;
+    Ok(true)
+}
+
+fn current_status() -> Result<DictationStatus, String> {
+    let state = DICTATION_STATE.lock().map_err(|e| e.to_string())?;
+    Ok(DictationStatus {
+        active: state.active,
+        paused: state.paused,
+        pending_segments: state.pending.len(),
+        settings: state.settings.clone(),
+    })
+}
+
+fn emit_status(app_handle: &AppHandle) -> Result<DictationStatus, String> {
+    let status = current_status()?;
+    if let Err(e) = app_handle.emit("dictation-state-changed", &status) {
+        eprintln!("Failed to emit dictation state: {}", e);
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn start_dictation(
+    app_handle: AppHandle,
+    settings: Option<DictationSettings>,
+) -> Result<DictationStatus, String> {
+    // Dictation runs on the dictation profile so insights stay off while typing
+    let previous_profile_id = crate::conversation_profiles::active_profile().id;
+    crate::conversation_profiles::activate_profile(&app_handle, "dictation")?;
+
+    {
+        let mut state = DICTATION_STATE.lock().map_err(|e| e.to_string())?;
+        if !state.active {
+            state.previous_profile_id = Some(previous_profile_id);
+        }
+        state.active = true;
+        state.paused = false;
+        state.pending.clear();
+        state.last_synthetic_input = None;
+        if let Some(settings) = settings {
+            state.settings = settings;
+        }
+    }
+
+    println!("⌨️ Dictation started");
+    emit_status(&app_handle)
+}
+
+#[tauri::command]
+pub async fn stop_dictation(app_handle: AppHandle) -> Result<DictationStatus, String> {
+    let previous_profile_id = {
+        let mut state = DICTATION_STATE.lock().map_err(|e| e.to_string())?;
+        state.active = false;
+        state.paused = false;
+        state.pending.clear();
+        if let Some(task) = state.flush_task.take() {
+            task.abort();
+        }
+        state.previous_profile_id.take()
+    };
+
+    if let Some(profile_id) = previous_profile_id {
+        if let Err(e) = crate::conversation_profiles::activate_profile(&app_handle, &profile_id) {
+            eprintln!("Failed to restore conversation profile: {}", e);
+        }
+    }
+
+    println!("⌨️ Dictation stopped");
+    emit_status(&app_handle)
+}
+
+/// Bound to the pause/resume shortcut in the frontend.
+#[tauri::command]
+pub async fn toggle_dictation_pause(app_handle: AppHandle) -> Result<DictationStatus, String> {
+    let resumed_with_pending = {
+        let mut state = DICTATION_STATE.lock().map_err(|e| e.to_string())?;
+        if !state.active {
+            return Err("Dictation is not active".to_string());
+        }
+        state.paused = !state.paused;
+        !state.paused && !state.pending.is_empty()
+    };
+
+    if resumed_with_pending {
+        schedule_flush(app_handle.clone());
+    }
+    emit_status(&app_handle)
+}
+
+#[tauri::command]
+pub async fn get_dictation_status() -> Result<DictationStatus, String> {
+    current_status()
+}
+
+/// A final segment from the microphone transcription in the frontend; ignored unless dictation is on.
+#[tauri::command]
+pub async fn dictation_submit_segment(app_handle: AppHandle, text: String) -> Result<bool, String> {
+    handle_final_segment(&app_handle, &text).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_passes_through() {
+        let actions = parse_dictation("hello there world", true);
+        assert_eq!(actions, vec![DictationAction::Text("hello there world".to_string())]);
+    }
+
+    #[test]
+    fn test_spoken_punctuation() {
+        let actions = parse_dictation("hello comma how are you question mark", true);
+        assert_eq!(actions, vec![DictationAction::Text("hello, how are you?".to_string())]);
+    }
+
+    #[test]
+    fn test_new_line_command() {
+        let actions = parse_dictation("first line. New line. second line", true);
+        assert_eq!(
+            actions,
+            vec![
+                DictationAction::Text("first line.".to_string()),
+                DictationAction::Key("enter"),
+                DictationAction::Text("second line".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commands_can_be_disabled() {
+        let actions = parse_dictation("add a comma here", false);
+        assert_eq!(actions, vec![DictationAction::Text("add a comma here".to_string())]);
+    }
+}
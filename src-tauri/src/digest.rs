@@ -0,0 +1,553 @@
+// src-tauri/src/digest.rs
+// Daily/weekly digest: the period's conversations, chats, action items and most mentioned entities
+// rolled into one Markdown report, saved as a chat and optionally exported, shared or sent to a webhook.
+use crate::data::chat::ChatStorage;
+use crate::data::conversation::{redaction, ConversationStorage};
+use crate::data::knowledge::KnowledgeStorage;
+use crate::data::types::{ChatMessage, ChatSession, ConversationSession, InsightType, MessageMetadata};
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+use chrono::{DateTime, Datelike, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const DIGEST_MODEL: &str = "gemma3:4b-it-qat";
+const MAX_MATERIAL_TOKENS: usize = 6000;
+const CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
+static SCHEDULER_STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestCadence {
+    Daily,
+    Weekly,
+}
+
+impl DigestCadence {
+    fn period_millis(self) -> i64 {
+        match self {
+            DigestCadence::Daily => 24 * 60 * 60 * 1000,
+            DigestCadence::Weekly => 7 * 24 * 60 * 60 * 1000,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            DigestCadence::Daily => "Daily",
+            DigestCadence::Weekly => "Weekly",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestSection {
+    Summary,
+    ActionItems,
+    Conversations,
+    Chats,
+    Entities,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestSettings {
+    pub enabled: bool,
+    pub cadence: DigestCadence,
+    pub hour: u32,    // Local hour after which the digest is generated
+    pub weekday: u32, // Weekly digests only: 1 = Monday ... 7 = Sunday
+    pub sections: Vec<DigestSection>,
+    pub save_as_chat: bool,
+    pub export_dir: Option<String>,  // Markdown file per digest
+    pub share_dir: Option<String>,   // Signed share bundle per digest
+    pub webhook_url: Option<String>, // Digest POSTed as JSON
+    pub last_generated: Option<i64>,
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cadence: DigestCadence::Daily,
+            hour: 18,
+            weekday: 5,
+            sections: vec![
+                DigestSection::Summary,
+                DigestSection::ActionItems,
+                DigestSection::Conversations,
+                DigestSection::Chats,
+                DigestSection::Entities,
+            ],
+            save_as_chat: true,
+            export_dir: None,
+            share_dir: None,
+            webhook_url: None,
+            last_generated: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub id: String,
+    pub cadence: DigestCadence,
+    pub title: String,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub generated_at: i64,
+    pub conversation_count: usize,
+    pub chat_count: usize,
+    pub markdown: String,
+    pub chat_id: Option<String>, // Set once saved as a chat
+    #[serde(default)]
+    pub model: Option<String>, // What wrote the generated sections; None when there was nothing to summarize
+}
+
+fn get_digest_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("digest_settings.json")
+}
+
+fn load_settings() -> DigestSettings {
+    get_digest_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store_settings(settings: &DigestSettings) -> Result<(), String> {
+    let path = get_digest_settings_path()
+        .map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+/// Due once per day (or once on the chosen weekday) after the configured hour.
+pub fn is_due(settings: &DigestSettings, now: DateTime<Local>) -> bool {
+    if !settings.enabled || now.hour() < settings.hour {
+        return false;
+    }
+    if settings.cadence == DigestCadence::Weekly && now.weekday().number_from_monday() != settings.weekday {
+        return false;
+    }
+    match settings.last_generated.and_then(|ms| Local.timestamp_millis_opt(ms).single()) {
+        Some(last) => last.date_naive() != now.date_naive(),
+        None => true,
+    }
+}
+
+fn format_time(ms: i64, format: &str) -> String {
+    Local.timestamp_millis_opt(ms)
+        .single()
+        .map(|time| time.format(format).to_string())
+        .unwrap_or_default()
+}
+
+fn parse_timestamp(rfc3339: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(rfc3339).ok().map(|time| time.timestamp_millis())
+}
+
+/// Insights where there are any, otherwise the end of the transcript.
+fn conversation_material(session: &ConversationSession) -> String {
+    let mut lines = vec![format!("Conversation \"{}\":", session.name)];
+    let insights: Vec<&str> = session.insights
+        .iter()
+        .filter(|insight| matches!(insight.insight_type, InsightType::Insight | InsightType::Answer))
+        .map(|insight| insight.text.trim())
+        .collect();
+    if insights.is_empty() {
+        let start = session.messages.len().saturating_sub(20);
+        lines.extend(redaction::transcript_lines(&session.messages[start..]));
+    } else {
+        lines.extend(insights.iter().rev().take(5).map(|text| format!("- {}", text)));
+    }
+    lines.join("\n")
+}
+
+fn chat_material(chat: &ChatSession) -> String {
+    let mut lines = vec![format!("Chat \"{}\":", chat.title)];
+    lines.extend(
+        chat.history.iter()
+            .filter(|m| m.sender == "user")
+            .rev()
+            .take(3)
+            .map(|m| format!("- asked: {}", m.text.trim().chars().take(300).collect::<String>())),
+    );
+    lines.join("\n")
+}
+
+async fn generate_section(model: &str, material: &str, instruction: &str) -> Result<String, String> {
+    let system = "You write concise personal work digests from meeting notes and chat topics. Do not invent details.";
+    let prompt = format!("{}\n\n{}", material, instruction);
+    let options = build_ollama_options("digest", None, estimate_tokens(&prompt) + estimate_tokens(system));
+    crate::ollama::generate_completion(
+        model,
+        prompt,
+        Some(system.to_string()),
+        Some(serde_json::Value::Object(options)),
+    ).await.map(|text| text.trim().to_string())
+}
+
+pub async fn build_digest(
+    app_handle: &AppHandle,
+    cadence: DigestCadence,
+    period_end: i64,
+    sections: &[DigestSection],
+) -> Result<Digest, String> {
+    let period_start = period_end - cadence.period_millis();
+
+    let conversations: Vec<ConversationSession> = ConversationStorage::new(app_handle)
+        .and_then(|storage| storage.load_conversations())
+        .map_err(|e| format!("Failed to load conversations: {}", e))?
+        .conversations
+        .into_iter()
+        .filter(|c| !c.is_active && c.start_time >= period_start && c.start_time <= period_end)
+        .collect();
+    let chats: Vec<ChatSession> = ChatStorage::new(app_handle)
+        .and_then(|storage| storage.load_chat_sessions())
+        .map_err(|e| format!("Failed to load chats: {}", e))?
+        .chats
+        .into_iter()
+        // Earlier digests are chats too; leave them out
+        .filter(|chat| chat.history.iter().any(|m| m.message_type.as_deref() != Some("digest")))
+        .filter(|chat| parse_timestamp(&chat.updated_at).map_or(false, |t| t >= period_start && t <= period_end))
+        .collect();
+
+    // Shared material for the generated sections, newest first until the budget runs out
+    let mut material = Vec::new();
+    let mut tokens = 0;
+    let mut sources: Vec<(i64, String)> = conversations.iter()
+        .map(|c| (c.start_time, conversation_material(c)))
+        .chain(chats.iter().map(|c| (parse_timestamp(&c.updated_at).unwrap_or(0), chat_material(c))))
+        .collect();
+    sources.sort_by(|a, b| b.0.cmp(&a.0));
+    for (_, text) in sources {
+        let text_tokens = estimate_tokens(&text);
+        if tokens + text_tokens > MAX_MATERIAL_TOKENS {
+            break;
+        }
+        tokens += text_tokens;
+        material.push(text);
+    }
+    let material = material.join("\n\n");
+
+    let title = match cadence {
+        DigestCadence::Daily => format!("Daily digest: {}", format_time(period_end, "%a %b %-d")),
+        DigestCadence::Weekly => format!(
+            "Weekly digest: {} – {}",
+            format_time(period_start, "%b %-d"),
+            format_time(period_end, "%b %-d")
+        ),
+    };
+    let mut markdown = format!("# {}\n\n", title);
+
+    let model = if material.is_empty() {
+        markdown.push_str("Nothing was recorded in this period.\n");
+        None
+    } else {
+        Some(crate::agent_models::resolve_helper_model(DIGEST_MODEL).await)
+    };
+    if let Some(model) = model.as_deref() {
+        for section in sections {
+            match section {
+                DigestSection::Summary => {
+                    let summary = generate_section(model, &material, "Summarize what happened in a short paragraph, then the main themes as bullet points.").await?;
+                    markdown.push_str(&format!("## Summary\n\n{}\n\n", summary));
+                }
+                DigestSection::ActionItems => {
+                    let items = generate_section(model, &material, "List every action item or follow-up as a Markdown task line \"- [ ] item (owner)\". Reply with \"None\" if there are none.").await?;
+                    markdown.push_str(&format!("## Action items\n\n{}\n\n", items));
+                }
+                DigestSection::Conversations if !conversations.is_empty() => {
+                    markdown.push_str("## Conversations\n\n");
+                    for c in &conversations {
+                        let minutes = c.end_time.map(|end| (end - c.start_time) / 60_000).unwrap_or(0);
+                        markdown.push_str(&format!(
+                            "- **{}** ({}, {} min, {} messages)\n",
+                            c.name, format_time(c.start_time, "%a %H:%M"), minutes, c.messages.len()
+                        ));
+                    }
+                    markdown.push('\n');
+                }
+                DigestSection::Chats if !chats.is_empty() => {
+                    markdown.push_str("## Chats\n\n");
+                    for chat in &chats {
+                        markdown.push_str(&format!("- **{}** ({} messages)\n", chat.title, chat.history.len()));
+                    }
+                    markdown.push('\n');
+                }
+                DigestSection::Entities => {
+                    let entities = KnowledgeStorage::new(app_handle)
+                        .and_then(|storage| storage.top_entities_between(period_start, period_end, 10))
+                        .map_err(|e| format!("Failed to load entities: {}", e))?;
+                    if !entities.is_empty() {
+                        markdown.push_str("## Mentioned most\n\n");
+                        for entity in entities {
+                            markdown.push_str(&format!("- {} ({}, {} mentions)\n", entity.name, entity.entity_type, entity.mention_count));
+                        }
+                        markdown.push('\n');
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(Digest {
+        id: uuid::Uuid::new_v4().to_string(),
+        cadence,
+        title,
+        period_start,
+        period_end,
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        conversation_count: conversations.len(),
+        chat_count: chats.len(),
+        markdown: markdown.trim_end().to_string(),
+        chat_id: None,
+        model,
+    })
+}
+
+fn digest_file_stem(digest: &Digest) -> String {
+    format!("enteract-{}-digest-{}", digest.cadence.label().to_lowercase(), format_time(digest.period_end, "%Y-%m-%d"))
+}
+
+fn save_digest_chat(app_handle: &AppHandle, digest: &Digest) -> Result<ChatSession, String> {
+    let mut storage = ChatStorage::new(app_handle)
+        .map_err(|e| format!("Failed to initialize chat storage: {}", e))?;
+    let message_id = storage.next_message_id()
+        .map_err(|e| format!("Failed to allocate message id: {}", e))?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let chat = ChatSession {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: digest.title.clone(),
+        history: vec![ChatMessage {
+            id: message_id,
+            text: digest.markdown.clone(),
+            sender: "system".to_string(),
+            timestamp: now.clone(),
+            is_interim: None,
+            confidence: None,
+            source: Some("digest".to_string()),
+            attachments: None,
+            thinking: None,
+            message_type: Some("digest".to_string()),
+            metadata: Some(MessageMetadata {
+                agent_type: Some("digest".to_string()),
+                model: digest.model.clone(),
+                tokens: None,
+                processing_time: None,
+                analysis_type: None,
+                search_queries: None,
+                sources: None,
+                model_tag: digest.model.clone(),
+                provider: Some("ollama".to_string()),
+                generation_options: None,
+                rag_chunk_ids: None,
+                screen_context_id: None,
+                prompt_tokens: None,
+                completion_tokens: None,
+            }),
+            bookmarked: None,
+            reactions: None,
+        }],
+        created_at: now.clone(),
+        updated_at: now,
+        model_id: None,
+    };
+    storage.create_chat_session(chat.clone())
+        .map_err(|e| format!("Failed to save digest chat: {}", e))?;
+    Ok(chat)
+}
+
+async fn post_webhook(url: &str, digest: &Digest) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = client.post(url).json(digest).send().await
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Webhook returned {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Deliver through every configured channel; one failing channel doesn't stop the others.
+async fn deliver_digest(app_handle: &AppHandle, digest: &mut Digest, settings: &DigestSettings) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if settings.save_as_chat {
+        match save_digest_chat(app_handle, digest) {
+            Ok(chat) => {
+                digest.chat_id = Some(chat.id.clone());
+                if let Err(e) = app_handle.emit("digest-chat-created", &chat) {
+                    eprintln!("Failed to emit digest chat: {}", e);
+                }
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    if let Some(dir) = settings.export_dir.as_deref().filter(|dir| !dir.is_empty()) {
+        let path = Path::new(dir).join(format!("{}.md", digest_file_stem(digest)));
+        if let Err(e) = fs::write(&path, &digest.markdown) {
+            errors.push(format!("Failed to write {}: {}", path.display(), e));
+        }
+    }
+    if let Some(dir) = settings.share_dir.as_deref().filter(|dir| !dir.is_empty()) {
+        let path = Path::new(dir).join(format!("{}.json", digest_file_stem(digest)));
+        let result = crate::share_format::seal("digest", &*digest)
+            .and_then(|bundle| crate::share_format::write_bundle(&bundle, &path));
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    }
+    if let Some(url) = settings.webhook_url.as_deref().filter(|url| !url.is_empty()) {
+        if let Err(e) = post_webhook(url, digest).await {
+            errors.push(e);
+        }
+    }
+
+    if let Err(e) = app_handle.emit("digest-generated", &*digest) {
+        eprintln!("Failed to emit digest: {}", e);
+    }
+    errors
+}
+
+async fn run_scheduled_digest(app_handle: &AppHandle) {
+    let mut settings = load_settings();
+    if !is_due(&settings, Local::now()) {
+        return;
+    }
+    crate::presence::wait_for_background_slot().await;
+    if crate::power::should_defer_background_work() {
+        return;
+    }
+
+    println!("📰 Generating {} digest", settings.cadence.label().to_lowercase());
+    match build_digest(app_handle, settings.cadence, chrono::Utc::now().timestamp_millis(), &settings.sections).await {
+        Ok(mut digest) => {
+            // Empty periods are marked done without sending anything
+            if digest.conversation_count + digest.chat_count > 0 {
+                for error in deliver_digest(app_handle, &mut digest, &settings).await {
+                    eprintln!("Digest delivery failed: {}", error);
+                }
+                crate::notifications::notify(
+                    app_handle,
+                    crate::notifications::BackgroundNotification::new(
+                        "digest",
+                        format!("Your {} digest is ready", settings.cadence.label().to_lowercase()),
+                        digest.title.clone(),
+                        digest.chat_id.as_ref().map(|id| format!("enteract://chat/{}", id)),
+                    ),
+                );
+            }
+            settings.last_generated = Some(digest.generated_at);
+            if let Err(e) = store_settings(&settings) {
+                eprintln!("Failed to record digest run: {}", e);
+            }
+        }
+        // Left due, so the next check retries
+        Err(e) => eprintln!("Digest generation failed: {}", e),
+    }
+}
+
+pub fn start_digest_scheduler(app_handle: AppHandle) {
+    if SCHEDULER_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        loop {
+            run_scheduled_digest(&app_handle).await;
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_digest_settings() -> Result<DigestSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn save_digest_settings(settings: DigestSettings) -> Result<(), String> {
+    if settings.hour > 23 {
+        return Err("Hour must be between 0 and 23".to_string());
+    }
+    if !(1..=7).contains(&settings.weekday) {
+        return Err("Weekday must be between 1 (Monday) and 7 (Sunday)".to_string());
+    }
+    if settings.sections.is_empty() {
+        return Err("Choose at least one digest section".to_string());
+    }
+    if let Some(url) = settings.webhook_url.as_deref().filter(|url| !url.is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("Webhook URL must start with http:// or https://".to_string());
+        }
+    }
+    for dir in [&settings.export_dir, &settings.share_dir].into_iter().flatten() {
+        if !dir.is_empty() && !Path::new(dir).is_dir() {
+            return Err(format!("Folder does not exist: {}", dir));
+        }
+    }
+
+    // The run history isn't the frontend's to change
+    let mut settings = settings;
+    settings.last_generated = load_settings().last_generated;
+    store_settings(&settings)?;
+    println!("📰 Saved digest settings ({} at {}:00, enabled: {})",
+             settings.cadence.label().to_lowercase(), settings.hour, settings.enabled);
+    Ok(())
+}
+
+/// Generate a digest for the period ending now; `deliver` sends it through the configured channels.
+#[tauri::command]
+pub async fn generate_digest_now(
+    app_handle: AppHandle,
+    cadence: Option<DigestCadence>,
+    deliver: Option<bool>,
+) -> Result<Digest, String> {
+    let settings = load_settings();
+    let cadence = cadence.unwrap_or(settings.cadence);
+    let mut digest = build_digest(&app_handle, cadence, chrono::Utc::now().timestamp_millis(), &settings.sections).await?;
+
+    if deliver.unwrap_or(false) {
+        let errors = deliver_digest(&app_handle, &mut digest, &settings).await;
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+    }
+    Ok(digest)
+}
+
+#[tauri::command]
+pub async fn export_digest_markdown(digest: Digest, path: String) -> Result<(), String> {
+    fs::write(&path, &digest.markdown).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_due_once_per_day_after_hour() {
+        let settings = DigestSettings { enabled: true, ..DigestSettings::default() };
+        let before = Local.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        let after = Local.with_ymd_and_hms(2026, 10, 16, 19, 0, 0).unwrap();
+        assert!(!is_due(&settings, before));
+        assert!(is_due(&settings, after));
+
+        let ran = DigestSettings { last_generated: Some(after.timestamp_millis()), ..settings.clone() };
+        assert!(!is_due(&ran, after));
+
+        // 2026-10-16 is a Friday, the default weekday
+        let weekly = DigestSettings { cadence: DigestCadence::Weekly, ..settings };
+        assert!(is_due(&weekly, after));
+        assert!(!is_due(&weekly, Local.with_ymd_and_hms(2026, 10, 15, 19, 0, 0).unwrap()));
+    }
+}
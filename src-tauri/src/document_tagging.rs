@@ -0,0 +1,153 @@
+// src-tauri/src/document_tagging.rs
+// Topic tags for indexed documents, assigned by a small local model that may only choose from the
+// user's tag taxonomy. Tags live in the document's metadata and can filter or boost searches.
+// Tags the user sets by hand are never overwritten by the classifier.
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::enhanced_rag_commands::{get_rag_system, EnhancedRagSystemState};
+use crate::enhanced_rag_system::EnhancedRagSystem;
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+
+const TAGGING_MODEL: &str = "gemma3:1b-it-qat";
+const MAX_TAGS_PER_DOCUMENT: usize = 3;
+// The start of a document is usually enough to tell what it's about
+const EXCERPT_CHARS: usize = 4000;
+
+const TAGGING_SYSTEM_PROMPT: &str = "You classify documents by topic. Choose only from the allowed tags, copying them exactly. \
+Pick the few tags that clearly fit, or none. Reply with a JSON array of strings and nothing else.";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DocumentTags {
+    pub tags: Vec<String>,
+    pub source: String, // "auto" | "manual"
+}
+
+/// Keep only reply entries that name a taxonomy tag, spelled the taxonomy's way.
+pub fn parse_tags(response: &str, taxonomy: &[String]) -> Vec<String> {
+    let entries: Vec<String> = match (response.find('['), response.rfind(']')) {
+        (Some(start), Some(end)) if end > start => serde_json::from_str(&response[start..=end]).unwrap_or_default(),
+        _ => Vec::new(),
+    };
+
+    let mut tags: Vec<String> = Vec::new();
+    for entry in entries {
+        let entry = entry.trim().trim_start_matches('#');
+        if let Some(tag) = taxonomy.iter().find(|tag| tag.eq_ignore_ascii_case(entry)) {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+    }
+    tags.truncate(MAX_TAGS_PER_DOCUMENT);
+    tags
+}
+
+/// Classify one document against the taxonomy and store the result. Documents tagged by hand are left alone.
+pub async fn tag_document(system: &EnhancedRagSystem, document_id: &str) -> Result<Option<DocumentTags>, String> {
+    let taxonomy = system.get_tag_taxonomy().map_err(|e| e.to_string())?;
+    if taxonomy.is_empty() {
+        return Ok(None);
+    }
+    if let Some(existing) = system.get_document_tags(document_id).map_err(|e| e.to_string())? {
+        if existing.source == "manual" {
+            return Ok(Some(existing));
+        }
+    }
+
+    let (file_name, content) = system.get_document_text(document_id).map_err(|e| e.to_string())?;
+    let excerpt: String = content.chars().take(EXCERPT_CHARS).collect();
+    let prompt = format!(
+        "Allowed tags: {}\n\nDocument \"{}\":\n{}\n\nWhich allowed tags fit this document?",
+        serde_json::to_string(&taxonomy).unwrap_or_default(),
+        file_name,
+        excerpt
+    );
+    let options = build_ollama_options("tagging", None, estimate_tokens(&prompt) + estimate_tokens(TAGGING_SYSTEM_PROMPT));
+    let model = crate::agent_models::resolve_helper_model(TAGGING_MODEL).await;
+    let response = crate::ollama::generate_completion(
+        &model,
+        prompt,
+        Some(TAGGING_SYSTEM_PROMPT.to_string()),
+        Some(serde_json::Value::Object(options)),
+    ).await?;
+
+    let tags = DocumentTags {
+        tags: parse_tags(&response, &taxonomy),
+        source: "auto".to_string(),
+    };
+    system.set_document_tags(document_id, &tags).map_err(|e| e.to_string())?;
+    println!("🏷️ Tagged {} with [{}]", file_name, tags.tags.join(", "));
+    Ok(Some(tags))
+}
+
+#[tauri::command]
+pub async fn get_document_tag_taxonomy(state: State<'_, EnhancedRagSystemState>) -> Result<Vec<String>, String> {
+    get_rag_system(&state)?.get_tag_taxonomy().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_document_tag_taxonomy(
+    state: State<'_, EnhancedRagSystemState>,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut taxonomy: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|tag| tag.trim()).filter(|tag| !tag.is_empty()) {
+        if !taxonomy.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            taxonomy.push(tag.to_string());
+        }
+    }
+    get_rag_system(&state)?.set_tag_taxonomy(&taxonomy).map_err(|e| e.to_string())?;
+    Ok(taxonomy)
+}
+
+#[tauri::command]
+pub async fn get_document_tags(
+    state: State<'_, EnhancedRagSystemState>,
+    document_id: String,
+) -> Result<Option<DocumentTags>, String> {
+    get_rag_system(&state)?.get_document_tags(&document_id).map_err(|e| e.to_string())
+}
+
+/// Set a document's tags by hand; the classifier won't touch them afterwards.
+#[tauri::command]
+pub async fn set_document_tags(
+    state: State<'_, EnhancedRagSystemState>,
+    document_id: String,
+    tags: Vec<String>,
+) -> Result<DocumentTags, String> {
+    let tags = DocumentTags { tags, source: "manual".to_string() };
+    get_rag_system(&state)?.set_document_tags(&document_id, &tags).map_err(|e| e.to_string())?;
+    Ok(tags)
+}
+
+/// Classify documents that have no tags yet, e.g. after the taxonomy was first set up.
+/// With `retag` every automatically tagged document is classified again.
+#[tauri::command]
+pub async fn classify_documents(
+    state: State<'_, EnhancedRagSystemState>,
+    retag: Option<bool>,
+) -> Result<usize, String> {
+    let system = get_rag_system(&state)?;
+    let document_ids = system
+        .documents_to_tag(retag.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+    let queued = document_ids.len();
+    for document_id in document_ids {
+        system.queue_tagging(&document_id);
+    }
+    Ok(queued)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tags_only_accepts_taxonomy() {
+        let taxonomy = vec!["Finance".to_string(), "Hiring".to_string(), "Legal".to_string()];
+        let response = "Here you go: [\"finance\", \"#Legal\", \"Marketing\", \"Finance\"]";
+        assert_eq!(parse_tags(response, &taxonomy), vec!["Finance", "Legal"]);
+        assert!(parse_tags("none of them", &taxonomy).is_empty());
+    }
+}
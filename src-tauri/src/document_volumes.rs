@@ -0,0 +1,186 @@
+// src-tauri/src/document_volumes.rs
+// Which volume a document's source file lives on, and whether that volume is reachable right now.
+// Documents on removable drives and network shares go unavailable while their volume is away,
+// rather than being reported as deleted, and come back when it reappears.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+// Filesystem calls on a dropped network share can block for a long time
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VolumeIdentity {
+    pub root: String,       // Mount point, drive root or UNC share
+    pub id: Option<String>, // Volume serial on Windows, device number elsewhere
+    pub network: bool,
+}
+
+/// Identify the volume holding `path`. Symlinks are followed, so a link on the system drive that
+/// points at a USB stick is attributed to the stick.
+pub fn identify(path: &Path) -> Option<VolumeIdentity> {
+    let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    platform::identify(&resolved)
+}
+
+/// Ok when the recorded volume is mounted, otherwise a reason suitable for showing the user.
+pub async fn probe(volume: &VolumeIdentity) -> Result<(), String> {
+    let owned = volume.clone();
+    let check = tokio::task::spawn_blocking(move || platform::probe(&owned));
+    match tokio::time::timeout(PROBE_TIMEOUT, check).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => Err(format!("Volume check failed: {}", e)),
+        Err(_) if volume.network => Err(format!("Network share {} is not responding", volume.root)),
+        Err(_) => Err(format!("Volume {} is not responding", volume.root)),
+    }
+}
+
+fn not_mounted(volume: &VolumeIdentity) -> String {
+    if volume.network {
+        format!("Network share {} is not connected", volume.root)
+    } else {
+        format!("Volume {} is not mounted", volume.root)
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::PathBuf;
+
+    fn device(path: &Path) -> Option<u64> {
+        std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+    }
+
+    // Walk up while the device stays the same; the topmost directory on it is the mount point
+    fn mount_point(path: &Path) -> Option<PathBuf> {
+        let dev = device(path)?;
+        let mut root = path.to_path_buf();
+        for ancestor in path.ancestors().skip(1) {
+            if device(ancestor) != Some(dev) {
+                break;
+            }
+            root = ancestor.to_path_buf();
+        }
+        Some(root)
+    }
+
+    fn is_mount_point(root: &Path) -> bool {
+        match (device(root), root.parent()) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(dev), Some(parent)) => device(parent) != Some(dev),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn is_network_mount(root: &Path) -> bool {
+        const NETWORK_FILESYSTEMS: [&str; 6] = ["nfs", "nfs4", "cifs", "smb3", "smbfs", "fuse.sshfs"];
+        let Ok(mounts) = std::fs::read_to_string("/proc/mounts") else {
+            return false;
+        };
+        let root = root.to_string_lossy();
+        mounts.lines().any(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // Spaces in mount points are escaped as \040
+            fields.len() > 2 && fields[1].replace("\\040", " ") == root && NETWORK_FILESYSTEMS.contains(&fields[2])
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn is_network_mount(_root: &Path) -> bool {
+        false
+    }
+
+    pub fn identify(path: &Path) -> Option<VolumeIdentity> {
+        let root = mount_point(path)?;
+        Some(VolumeIdentity {
+            id: device(&root).map(|dev| format!("dev:{}", dev)),
+            network: is_network_mount(&root),
+            root: root.to_string_lossy().into_owned(),
+        })
+    }
+
+    pub fn probe(volume: &VolumeIdentity) -> Result<(), String> {
+        // An unmounted drive often leaves its empty mount directory behind, so existing isn't enough.
+        // Device numbers can change between mounts, so they aren't compared.
+        if is_mount_point(Path::new(&volume.root)) {
+            Ok(())
+        } else {
+            Err(not_mounted(volume))
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::*;
+    use std::path::{Component, Prefix};
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{GetDriveTypeW, GetVolumeInformationW};
+
+    const DRIVE_REMOTE: u32 = 4;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    // Canonical paths come back verbatim (\\?\C:\...), so report roots in the familiar form
+    fn root_of(path: &Path) -> Option<(String, bool)> {
+        let Some(Component::Prefix(prefix)) = path.components().next() else {
+            return None;
+        };
+        match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => Some((format!("{}:\\", letter as char), false)),
+            Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => Some((
+                format!("\\\\{}\\{}\\", server.to_string_lossy(), share.to_string_lossy()),
+                true,
+            )),
+            _ => None,
+        }
+    }
+
+    fn volume_serial(root: &str) -> Option<String> {
+        let root = wide(root);
+        let mut serial = 0u32;
+        unsafe { GetVolumeInformationW(PCWSTR(root.as_ptr()), None, Some(&mut serial), None, None, None) }.ok()?;
+        Some(format!("{:08X}", serial))
+    }
+
+    pub fn identify(path: &Path) -> Option<VolumeIdentity> {
+        let (root, unc) = root_of(path)?;
+        let network = unc || unsafe { GetDriveTypeW(PCWSTR(wide(&root).as_ptr())) } == DRIVE_REMOTE;
+        Some(VolumeIdentity {
+            id: volume_serial(&root),
+            network,
+            root,
+        })
+    }
+
+    pub fn probe(volume: &VolumeIdentity) -> Result<(), String> {
+        let Some(serial) = volume_serial(&volume.root) else {
+            return Err(not_mounted(volume));
+        };
+        // Drive letters get reused, so make sure it's the same disk that was indexed
+        match &volume.id {
+            Some(id) if *id != serial => Err(format!("A different volume is mounted at {}", volume.root)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identify_finds_a_mounted_root() {
+        let volume = identify(&std::env::temp_dir()).unwrap();
+        assert!(std::env::temp_dir().canonicalize().unwrap().starts_with(&volume.root));
+        assert!(platform::probe(&volume).is_ok());
+
+        let gone = VolumeIdentity { root: "/enteract-missing-volume".to_string(), id: None, network: false };
+        assert_eq!(platform::probe(&gone), Err("Volume /enteract-missing-volume is not mounted".to_string()));
+    }
+}
@@ -0,0 +1,143 @@
+// src-tauri/src/draft_refine.rs
+// Two-stage generation: a small model streams an instant draft, then an optional larger model
+// rewrites it in the background. Both versions are kept as variants of the chat message.
+use crate::data::chat::ChatStorage;
+use crate::data::types::MessageVariant;
+use crate::generation_options::{build_ollama_options, estimate_tokens, GenerationOptions};
+use crate::ollama::ChatContextMessage;
+use crate::system_prompts::ENTERACT_AGENT_PROMPT;
+use tauri::{AppHandle, Emitter};
+
+const DRAFT_MODEL: &str = "gemma3:1b-it-qat";
+const DEFAULT_REFINE_MODEL: &str = "gemma3:4b-it-qat";
+
+/// Save a response as a variant of a chat message, returning the variant id.
+pub(crate) fn store_variant(
+    app_handle: &AppHandle,
+    chat_id: &str,
+    message_id: i32,
+    kind: &str,
+    text: &str,
+    model: &str,
+    agent_type: &str,
+    is_selected: bool,
+) -> Option<String> {
+    let variant = MessageVariant {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id: chat_id.to_string(),
+        message_id,
+        kind: kind.to_string(),
+        text: text.to_string(),
+        model: Some(model.to_string()),
+        agent_type: Some(agent_type.to_string()),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        is_selected,
+    };
+
+    let result = ChatStorage::new(app_handle).and_then(|mut storage| storage.save_message_variant(&variant));
+    match result {
+        Ok(()) => Some(variant.id),
+        Err(e) => {
+            eprintln!("Failed to store {} variant: {}", kind, e);
+            None
+        }
+    }
+}
+
+/// Stream a draft from the small model on `ollama-stream-{session_id}`, then refine it in the
+/// background and emit `refined-response` when the better version is ready.
+#[tauri::command]
+pub async fn generate_draft_and_refine(
+    app_handle: AppHandle,
+    prompt: String,
+    context: Option<Vec<ChatContextMessage>>,
+    session_id: String,
+    chat_id: Option<String>,
+    message_id: Option<i32>,
+    refine: Option<bool>,
+    refine_model: Option<String>,
+    options: Option<GenerationOptions>,
+) -> Result<(), String> {
+    let window_limit = crate::generation_options::prompt_token_limit("enteract", options.as_ref());
+    let context = crate::context_window::fit_context(&app_handle, ENTERACT_AGENT_PROMPT, &prompt, context, Some(window_limit)).await;
+    let full_prompt = crate::ollama::build_prompt_with_context(prompt, context);
+
+    let draft_model = crate::agent_models::resolve_helper_model(DRAFT_MODEL).await;
+    let draft = crate::ollama::generate_agent_response_stream(
+        app_handle.clone(),
+        draft_model.clone(),
+        full_prompt.clone(),
+        ENTERACT_AGENT_PROMPT.to_string(),
+        None,
+        session_id.clone(),
+        "enteract".to_string(),
+        options.clone(),
+    ).await?;
+
+    let target = chat_id.zip(message_id);
+    let draft_variant_id = target
+        .as_ref()
+        .and_then(|(chat_id, message_id)| store_variant(&app_handle, chat_id, *message_id, "draft", &draft, &draft_model, "enteract", true));
+
+    if !refine.unwrap_or(true) || draft.trim().is_empty() {
+        return Ok(());
+    }
+    if crate::power::should_defer_background_work() {
+        println!("🔋 Skipping draft refinement to save power (session: {})", session_id);
+        return Ok(());
+    }
+
+    let refine_model = crate::agent_models::resolve_helper_model(refine_model.as_deref().unwrap_or(DEFAULT_REFINE_MODEL)).await;
+    tauri::async_runtime::spawn(async move {
+        let refine_prompt = format!(
+            "{}\n\nDraft answer:\n{}\n\nRewrite the draft into a better final answer: fix mistakes, fill in anything missing and keep it concise. Reply with the improved answer only.",
+            full_prompt, draft
+        );
+        let prompt_tokens = estimate_tokens(&refine_prompt) + estimate_tokens(ENTERACT_AGENT_PROMPT);
+        let request_options = build_ollama_options("refine", options.as_ref(), prompt_tokens);
+
+        println!("✨ Refining draft with {} (session: {})", refine_model, session_id);
+        let result = crate::ollama::generate_completion(
+            &refine_model,
+            refine_prompt,
+            Some(ENTERACT_AGENT_PROMPT.to_string()),
+            Some(serde_json::Value::Object(request_options)),
+        ).await;
+
+        let payload = match result {
+            Ok(refined) => {
+                let refined = refined.trim().to_string();
+                let refined_variant_id = target.as_ref().and_then(|(chat_id, message_id)| {
+                    store_variant(&app_handle, chat_id, *message_id, "refined", &refined, &refine_model, "enteract", false)
+                });
+                serde_json::json!({
+                    "sessionId": session_id,
+                    "chatId": target.as_ref().map(|(chat_id, _)| chat_id),
+                    "messageId": target.as_ref().map(|(_, message_id)| message_id),
+                    "model": refine_model,
+                    "draft": draft,
+                    "refined": refined,
+                    "draftVariantId": draft_variant_id,
+                    "refinedVariantId": refined_variant_id,
+                })
+            }
+            Err(e) => {
+                eprintln!("Draft refinement failed: {}", e);
+                serde_json::json!({
+                    "sessionId": session_id,
+                    "chatId": target.as_ref().map(|(chat_id, _)| chat_id),
+                    "messageId": target.as_ref().map(|(_, message_id)| message_id),
+                    "model": refine_model,
+                    "draft": draft,
+                    "error": e,
+                })
+            }
+        };
+
+        if let Err(e) = app_handle.emit("refined-response", payload) {
+            eprintln!("Failed to emit refined response: {}", e);
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,292 @@
+// src-tauri/src/drag_resize.rs
+// Magnetic snapping for dragged Enteract windows plus named, persisted window arrangements.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, Window};
+
+const DEFAULT_SNAP_THRESHOLD: i32 = 16;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y + self.height as i32
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SnapTargetKind {
+    ScreenEdge,
+    Window,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapTarget {
+    pub kind: SnapTargetKind,
+    pub label: Option<String>,
+    pub edge: String,
+    pub distance: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapResult {
+    pub x: i32,
+    pub y: i32,
+    pub snapped_x: Option<SnapTarget>,
+    pub snapped_y: Option<SnapTarget>,
+    pub nearby: Vec<SnapTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowLayout {
+    pub name: String,
+    pub windows: HashMap<String, Rect>,
+    pub saved_at: i64,
+}
+
+struct Candidate {
+    value: i32,
+    target: SnapTarget,
+}
+
+fn ranges_overlap(a_start: i32, a_end: i32, b_start: i32, b_end: i32, slack: i32) -> bool {
+    a_start <= b_end + slack && b_start <= a_end + slack
+}
+
+fn best_candidate(candidates: Vec<Candidate>, threshold: i32) -> Option<Candidate> {
+    candidates
+        .into_iter()
+        .filter(|c| c.target.distance <= threshold)
+        .min_by_key(|c| c.target.distance)
+}
+
+/// Snap a proposed window rect to the closest screen edge or sibling window edge within `threshold`.
+pub fn compute_snap(rect: Rect, screens: &[Rect], others: &[(String, Rect)], threshold: i32) -> SnapResult {
+    let mut x_candidates = Vec::new();
+    let mut y_candidates = Vec::new();
+
+    let push = |list: &mut Vec<Candidate>, value: i32, current: i32, kind: SnapTargetKind, label: Option<String>, edge: &str| {
+        list.push(Candidate {
+            value,
+            target: SnapTarget {
+                kind,
+                label,
+                edge: edge.to_string(),
+                distance: (value - current).abs(),
+            },
+        });
+    };
+
+    for screen in screens {
+        push(&mut x_candidates, screen.x, rect.x, SnapTargetKind::ScreenEdge, None, "left");
+        push(&mut x_candidates, screen.right() - rect.width as i32, rect.x, SnapTargetKind::ScreenEdge, None, "right");
+        push(&mut y_candidates, screen.y, rect.y, SnapTargetKind::ScreenEdge, None, "top");
+        push(&mut y_candidates, screen.bottom() - rect.height as i32, rect.y, SnapTargetKind::ScreenEdge, None, "bottom");
+    }
+
+    for (label, other) in others {
+        // Only dock side-by-side when the windows actually share a row/column
+        if ranges_overlap(rect.y, rect.bottom(), other.y, other.bottom(), threshold) {
+            push(&mut x_candidates, other.right(), rect.x, SnapTargetKind::Window, Some(label.clone()), "right");
+            push(&mut x_candidates, other.x - rect.width as i32, rect.x, SnapTargetKind::Window, Some(label.clone()), "left");
+            push(&mut x_candidates, other.x, rect.x, SnapTargetKind::Window, Some(label.clone()), "align-left");
+        }
+        if ranges_overlap(rect.x, rect.right(), other.x, other.right(), threshold) {
+            push(&mut y_candidates, other.bottom(), rect.y, SnapTargetKind::Window, Some(label.clone()), "bottom");
+            push(&mut y_candidates, other.y - rect.height as i32, rect.y, SnapTargetKind::Window, Some(label.clone()), "top");
+            push(&mut y_candidates, other.y, rect.y, SnapTargetKind::Window, Some(label.clone()), "align-top");
+        }
+    }
+
+    // Anything within twice the threshold is reported so the UI can preview snap zones
+    let nearby: Vec<SnapTarget> = x_candidates
+        .iter()
+        .chain(y_candidates.iter())
+        .filter(|c| c.target.distance <= threshold * 2)
+        .map(|c| c.target.clone())
+        .collect();
+
+    let snapped_x = best_candidate(x_candidates, threshold);
+    let snapped_y = best_candidate(y_candidates, threshold);
+
+    SnapResult {
+        x: snapped_x.as_ref().map(|c| c.value).unwrap_or(rect.x),
+        y: snapped_y.as_ref().map(|c| c.value).unwrap_or(rect.y),
+        snapped_x: snapped_x.map(|c| c.target),
+        snapped_y: snapped_y.map(|c| c.target),
+        nearby,
+    }
+}
+
+fn screen_rects(app_handle: &AppHandle) -> Vec<Rect> {
+    app_handle
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| Rect {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect()
+}
+
+fn window_rects(app_handle: &AppHandle, exclude: Option<&str>) -> Vec<(String, Rect)> {
+    app_handle
+        .webview_windows()
+        .into_iter()
+        .filter(|(label, window)| Some(label.as_str()) != exclude && window.is_visible().unwrap_or(false))
+        .filter_map(|(label, window)| {
+            let position = window.outer_position().ok()?;
+            let size = window.outer_size().ok()?;
+            Some((label, Rect { x: position.x, y: position.y, width: size.width, height: size.height }))
+        })
+        .collect()
+}
+
+fn get_layouts_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("window_layouts.json")
+}
+
+fn load_layouts() -> Result<HashMap<String, WindowLayout>, String> {
+    let path = get_layouts_path().map_err(|e| format!("Failed to get layouts path: {}", e))?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read layouts file: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse layouts: {}", e))
+}
+
+fn store_layouts(layouts: &HashMap<String, WindowLayout>) -> Result<(), String> {
+    let path = get_layouts_path().map_err(|e| format!("Failed to get layouts path: {}", e))?;
+    let json = serde_json::to_string_pretty(layouts).map_err(|e| format!("Failed to serialize layouts: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write layouts file: {}", e))
+}
+
+#[tauri::command]
+pub async fn drag_window_with_snap(
+    window: Window,
+    app_handle: AppHandle,
+    x: i32,
+    y: i32,
+    threshold: Option<i32>,
+) -> Result<SnapResult, String> {
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let rect = Rect { x, y, width: size.width, height: size.height };
+
+    let screens = screen_rects(&app_handle);
+    let others = window_rects(&app_handle, Some(window.label()));
+    let result = compute_snap(rect, &screens, &others, threshold.unwrap_or(DEFAULT_SNAP_THRESHOLD).max(0));
+
+    window
+        .set_position(PhysicalPosition::new(result.x, result.y))
+        .map_err(|e| e.to_string())?;
+
+    if let Err(e) = app_handle.emit("window-snap-zones", serde_json::json!({
+        "label": window.label(),
+        "nearby": &result.nearby,
+        "snapped": result.snapped_x.is_some() || result.snapped_y.is_some()
+    })) {
+        eprintln!("Failed to emit snap zones: {}", e);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn save_window_layout(app_handle: AppHandle, name: String) -> Result<WindowLayout, String> {
+    let mut layouts = load_layouts()?;
+    let layout = WindowLayout {
+        name: name.clone(),
+        windows: window_rects(&app_handle, None).into_iter().collect(),
+        saved_at: chrono::Utc::now().timestamp_millis(),
+    };
+
+    layouts.insert(name, layout.clone());
+    store_layouts(&layouts)?;
+    Ok(layout)
+}
+
+#[tauri::command]
+pub async fn apply_window_layout(app_handle: AppHandle, name: String) -> Result<Vec<String>, String> {
+    let layouts = load_layouts()?;
+    let layout = layouts.get(&name).ok_or_else(|| format!("No window layout named: {}", name))?;
+
+    let mut applied = Vec::new();
+    for (label, rect) in &layout.windows {
+        // Windows that aren't open right now are skipped rather than recreated
+        if let Some(window) = app_handle.get_webview_window(label) {
+            window.set_position(PhysicalPosition::new(rect.x, rect.y)).map_err(|e| e.to_string())?;
+            window.set_size(PhysicalSize::new(rect.width, rect.height)).map_err(|e| e.to_string())?;
+            applied.push(label.clone());
+        }
+    }
+
+    Ok(applied)
+}
+
+#[tauri::command]
+pub async fn list_window_layouts() -> Result<Vec<WindowLayout>, String> {
+    let mut layouts: Vec<WindowLayout> = load_layouts()?.into_values().collect();
+    layouts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(layouts)
+}
+
+#[tauri::command]
+pub async fn delete_window_layout(name: String) -> Result<(), String> {
+    let mut layouts = load_layouts()?;
+    if layouts.remove(&name).is_none() {
+        return Err(format!("No window layout named: {}", name));
+    }
+    store_layouts(&layouts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> Vec<Rect> {
+        vec![Rect { x: 0, y: 0, width: 1920, height: 1080 }]
+    }
+
+    #[test]
+    fn test_snaps_to_screen_edge_within_threshold() {
+        let rect = Rect { x: 10, y: 500, width: 300, height: 60 };
+        let result = compute_snap(rect, &screen(), &[], 16);
+        assert_eq!(result.x, 0);
+        assert_eq!(result.y, 500);
+        assert_eq!(result.snapped_x.unwrap().kind, SnapTargetKind::ScreenEdge);
+    }
+
+    #[test]
+    fn test_snaps_beside_other_window() {
+        let others = vec![("main".to_string(), Rect { x: 400, y: 400, width: 320, height: 60 })];
+        let rect = Rect { x: 730, y: 405, width: 200, height: 60 };
+        let result = compute_snap(rect, &screen(), &others, 16);
+        assert_eq!(result.x, 720);
+        assert_eq!(result.y, 400);
+        assert_eq!(result.snapped_x.unwrap().label.as_deref(), Some("main"));
+    }
+
+    #[test]
+    fn test_no_snap_outside_threshold() {
+        let rect = Rect { x: 200, y: 300, width: 300, height: 60 };
+        let result = compute_snap(rect, &screen(), &[], 16);
+        assert_eq!((result.x, result.y), (200, 300));
+        assert!(result.snapped_x.is_none() && result.snapped_y.is_none());
+    }
+}
@@ -0,0 +1,280 @@
+// src-tauri/src/embedding_pipeline.rs
+// Scheduler for embedding jobs: bounded concurrency, chunks-per-second throttling,
+// pause/resume and per-job progress with ETA so large imports don't saturate the machine.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingPipelineConfig {
+    pub max_concurrent_jobs: usize,
+    pub max_chunks_per_second: f32, // 0 disables throttling
+    pub batch_size: usize,
+}
+
+impl Default for EmbeddingPipelineConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_jobs: 2,
+            max_chunks_per_second: 20.0,
+            batch_size: 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingJobProgress {
+    pub job_id: String,
+    pub document_id: String,
+    pub status: String, // "queued", "running", "paused", "completed", "failed"
+    pub total_chunks: usize,
+    pub processed_chunks: usize,
+    pub chunks_per_second: f32,
+    pub eta_seconds: Option<u64>,
+    pub started_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingPipelineStatus {
+    pub paused: bool,
+    pub running_jobs: usize,
+    pub config: EmbeddingPipelineConfig,
+    pub jobs: Vec<EmbeddingJobProgress>,
+}
+
+#[derive(Default)]
+struct PipelineState {
+    config: EmbeddingPipelineConfig,
+    paused: bool,
+    running_jobs: usize,
+    jobs: HashMap<String, EmbeddingJobProgress>,
+    app_handle: Option<AppHandle>,
+}
+
+lazy_static::lazy_static! {
+    static ref PIPELINE_STATE: Arc<Mutex<PipelineState>> = Arc::new(Mutex::new(PipelineState::default()));
+}
+
+pub fn init_embedding_pipeline(app_handle: AppHandle) {
+    if let Ok(mut state) = PIPELINE_STATE.lock() {
+        state.app_handle = Some(app_handle);
+    }
+}
+
+pub fn batch_size() -> usize {
+    PIPELINE_STATE
+        .lock()
+        .map(|state| state.config.batch_size)
+        .unwrap_or(8)
+        .max(1)
+}
+
+fn is_paused() -> bool {
+    PIPELINE_STATE.lock().map(|state| state.paused).unwrap_or(false)
+}
+
+fn emit_progress(progress: &EmbeddingJobProgress) {
+    let app_handle = PIPELINE_STATE.lock().ok().and_then(|state| state.app_handle.clone());
+    if let Some(app_handle) = app_handle {
+        // Running updates are rate limited; status changes always go out
+        if progress.status == "running" {
+            crate::event_throttle::emit_throttled(&app_handle, "embedding-job-progress", progress);
+        } else if let Err(e) = crate::event_throttle::emit_critical(&app_handle, "embedding-job-progress", progress) {
+            eprintln!("Failed to emit embedding progress: {}", e);
+        }
+    }
+}
+
+fn update_job<F: FnOnce(&mut EmbeddingJobProgress)>(job_id: &str, update: F) {
+    let progress = match PIPELINE_STATE.lock() {
+        Ok(mut state) => match state.jobs.get_mut(job_id) {
+            Some(job) => {
+                update(job);
+                job.clone()
+            }
+            None => return,
+        },
+        Err(_) => return,
+    };
+    emit_progress(&progress);
+}
+
+/// Handle for one document's embedding run. Dropping it frees the concurrency slot.
+pub struct EmbeddingJob {
+    job_id: String,
+    started: Instant,
+    last_batch: Instant,
+    finished: bool,
+}
+
+impl EmbeddingJob {
+    /// Register a job and wait until a concurrency slot is free and the pipeline isn't paused.
+    pub async fn start(document_id: &str, total_chunks: usize) -> Self {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let progress = EmbeddingJobProgress {
+            job_id: job_id.clone(),
+            document_id: document_id.to_string(),
+            status: "queued".to_string(),
+            total_chunks,
+            processed_chunks: 0,
+            chunks_per_second: 0.0,
+            eta_seconds: None,
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Ok(mut state) = PIPELINE_STATE.lock() {
+            state.jobs.insert(job_id.clone(), progress.clone());
+        }
+        emit_progress(&progress);
+
+        loop {
+            let acquired = match PIPELINE_STATE.lock() {
+                Ok(mut state) => {
+                    if !state.paused && state.running_jobs < state.config.max_concurrent_jobs.max(1) {
+                        state.running_jobs += 1;
+                        true
+                    } else {
+                        false
+                    }
+                }
+                Err(_) => true,
+            };
+            if acquired {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+
+        update_job(&job_id, |job| job.status = "running".to_string());
+        Self {
+            job_id,
+            started: Instant::now(),
+            last_batch: Instant::now(),
+            finished: false,
+        }
+    }
+
+    /// Hold the next batch while paused, then sleep long enough to respect the chunks/second limit.
+    pub async fn before_batch(&mut self, batch_len: usize) {
+        if is_paused() {
+            update_job(&self.job_id, |job| job.status = "paused".to_string());
+            while is_paused() {
+                tokio::time::sleep(Duration::from_millis(250)).await;
+            }
+            update_job(&self.job_id, |job| job.status = "running".to_string());
+        }
+
+        let max_rate = PIPELINE_STATE
+            .lock()
+            .map(|state| state.config.max_chunks_per_second)
+            .unwrap_or(0.0);
+        if max_rate > 0.0 {
+            let budget = Duration::from_secs_f32(batch_len as f32 / max_rate);
+            let elapsed = self.last_batch.elapsed();
+            if elapsed < budget {
+                tokio::time::sleep(budget - elapsed).await;
+            }
+        }
+        self.last_batch = Instant::now();
+    }
+
+    pub fn record_progress(&self, processed_chunks: usize) {
+        let elapsed = self.started.elapsed().as_secs_f32();
+        update_job(&self.job_id, |job| {
+            job.processed_chunks = processed_chunks.min(job.total_chunks);
+            job.chunks_per_second = if elapsed > 0.0 { job.processed_chunks as f32 / elapsed } else { 0.0 };
+            let remaining = job.total_chunks - job.processed_chunks;
+            job.eta_seconds = if job.chunks_per_second > 0.0 {
+                Some((remaining as f32 / job.chunks_per_second).ceil() as u64)
+            } else {
+                None
+            };
+        });
+    }
+
+    pub fn finish(mut self, success: bool) {
+        self.finished = true;
+        update_job(&self.job_id, |job| {
+            job.status = if success { "completed" } else { "failed" }.to_string();
+            job.eta_seconds = Some(0);
+        });
+        self.release();
+    }
+
+    fn release(&self) {
+        if let Ok(mut state) = PIPELINE_STATE.lock() {
+            state.running_jobs = state.running_jobs.saturating_sub(1);
+            // Keep finished jobs around briefly for the UI, but don't grow without bound
+            if state.jobs.len() > 100 {
+                state.jobs.retain(|_, job| job.status != "completed" && job.status != "failed");
+            }
+        }
+    }
+}
+
+impl Drop for EmbeddingJob {
+    fn drop(&mut self) {
+        if !self.finished {
+            update_job(&self.job_id, |job| job.status = "failed".to_string());
+            self.release();
+        }
+    }
+}
+
+fn current_status() -> Result<EmbeddingPipelineStatus, String> {
+    let state = PIPELINE_STATE.lock().map_err(|e| e.to_string())?;
+    let mut jobs: Vec<EmbeddingJobProgress> = state.jobs.values().cloned().collect();
+    jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(EmbeddingPipelineStatus {
+        paused: state.paused,
+        running_jobs: state.running_jobs,
+        config: state.config.clone(),
+        jobs,
+    })
+}
+
+fn set_paused(app_handle: &AppHandle, paused: bool) -> Result<EmbeddingPipelineStatus, String> {
+    {
+        let mut state = PIPELINE_STATE.lock().map_err(|e| e.to_string())?;
+        state.paused = paused;
+    }
+    println!("🧮 Embedding pipeline {}", if paused { "paused" } else { "resumed" });
+
+    let status = current_status()?;
+    if let Err(e) = app_handle.emit("embedding-pipeline-state", &status) {
+        eprintln!("Failed to emit embedding pipeline state: {}", e);
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn get_embedding_pipeline_status() -> Result<EmbeddingPipelineStatus, String> {
+    current_status()
+}
+
+#[tauri::command]
+pub async fn pause_embedding_pipeline(app_handle: AppHandle) -> Result<EmbeddingPipelineStatus, String> {
+    set_paused(&app_handle, true)
+}
+
+#[tauri::command]
+pub async fn resume_embedding_pipeline(app_handle: AppHandle) -> Result<EmbeddingPipelineStatus, String> {
+    set_paused(&app_handle, false)
+}
+
+#[tauri::command]
+pub async fn update_embedding_pipeline_config(config: EmbeddingPipelineConfig) -> Result<EmbeddingPipelineStatus, String> {
+    if config.max_concurrent_jobs == 0 || config.batch_size == 0 {
+        return Err("Concurrency and batch size must be at least 1".to_string());
+    }
+    if config.max_chunks_per_second < 0.0 {
+        return Err("Chunks per second cannot be negative".to_string());
+    }
+
+    {
+        let mut state = PIPELINE_STATE.lock().map_err(|e| e.to_string())?;
+        state.config = config;
+    }
+    current_status()
+}
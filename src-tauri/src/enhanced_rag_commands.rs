@@ -1,8 +1,8 @@
-use crate::enhanced_rag_system::{EnhancedRagSystem, EnhancedDocument, EnhancedDocumentChunk, EnhancedRagSettings};
+use crate::enhanced_rag_system::{EnhancedRagSystem, EnhancedDocument, EnhancedDocumentChunk, EnhancedRagSettings, DocumentCollection, AdvancedSearchQuery, RelatedDocument, DocumentRemovalReport, RagDiagnostics, SearchExplanation, RagCollectionExport, RagCollectionImport, RAG_COLLECTION_SHARE_KIND, StaleDocument, StaleReindexReport, UnavailableDocument};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tauri::State;
+use tauri::{AppHandle, State};
 
 // Global enhanced RAG system instance
 #[derive(Clone)]
@@ -37,6 +37,7 @@ pub async fn upload_enhanced_document(
     file_name: String,
     file_content: Vec<u8>,
     file_type: String,
+    source_path: Option<String>, // Original file on disk, when known, so edits to it can be detected
     state: State<'_, EnhancedRagSystemState>,
 ) -> Result<EnhancedDocument, String> {
     let system = {
@@ -47,9 +48,13 @@ pub async fn upload_enhanced_document(
         }
     }?;
     
-    system.upload_document(file_name, file_content, file_type)
+    let document = system.upload_document(file_name, file_content, file_type)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    if let Some(source_path) = source_path {
+        system.track_document_source(&document.id, &source_path).map_err(|e| e.to_string())?;
+    }
+    Ok(document)
 }
 
 #[tauri::command]
@@ -353,4 +358,265 @@ pub async fn validate_enhanced_file_upload(
         }
         None => Err("Enhanced RAG system not initialized".to_string())
     }
-}
\ No newline at end of file
+}
+#[derive(Debug, serde::Serialize)]
+pub struct ConversationalSearchResult {
+    pub condensed_query: crate::query_condensation::CondensedQuery,
+    pub results: Vec<EnhancedDocumentChunk>,
+}
+
+#[tauri::command]
+pub async fn search_enhanced_documents_with_history(
+    query: String,
+    history: Vec<crate::ollama::ChatContextMessage>,
+    context_document_ids: Vec<String>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<ConversationalSearchResult, String> {
+    let system = {
+        let rag_state = state.0.lock().map_err(|e| e.to_string())?;
+        match &*rag_state {
+            Some(sys) => Ok(sys.clone()),
+            None => Err("Enhanced RAG system not initialized".to_string())
+        }
+    }?;
+    
+    // Rewrite follow-ups into standalone queries before retrieval
+    let condensed_query = crate::query_condensation::condense_query(&query, &history).await;
+    
+    let results = system.search_documents(&condensed_query.standalone_query, context_document_ids)
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    Ok(ConversationalSearchResult {
+        condensed_query,
+        results,
+    })
+}
+
+pub(crate) fn get_rag_system(state: &State<'_, EnhancedRagSystemState>) -> Result<EnhancedRagSystem, String> {
+    let rag_state = state.0.lock().map_err(|e| e.to_string())?;
+    match &*rag_state {
+        Some(sys) => Ok(sys.clone()),
+        None => Err("Enhanced RAG system not initialized".to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn create_document_collection(
+    name: String,
+    description: Option<String>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<DocumentCollection, String> {
+    let system = get_rag_system(&state)?;
+    system.create_collection(&name, description).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn rename_document_collection(
+    collection_id: String,
+    new_name: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<(), String> {
+    let system = get_rag_system(&state)?;
+    system.rename_collection(&collection_id, &new_name).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_document_collection(
+    collection_id: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<(), String> {
+    let system = get_rag_system(&state)?;
+    system.delete_collection(&collection_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_document_collections(
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<Vec<DocumentCollection>, String> {
+    let system = get_rag_system(&state)?;
+    system.list_collections().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn assign_documents_to_collection(
+    collection_id: String,
+    document_ids: Vec<String>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<usize, String> {
+    let system = get_rag_system(&state)?;
+    system.assign_documents_to_collection(&collection_id, &document_ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_documents_from_collection(
+    collection_id: String,
+    document_ids: Vec<String>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<usize, String> {
+    let system = get_rag_system(&state)?;
+    system.remove_documents_from_collection(&collection_id, &document_ids).map_err(|e| e.to_string())
+}
+
+/// Write a collection to a share file. Returns the number of documents exported.
+#[tauri::command]
+pub async fn export_rag_collection(
+    collection: String,
+    path: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<usize, String> {
+    let system = get_rag_system(&state)?;
+    let export = system.export_collection(&collection).map_err(|e| e.to_string())?;
+    let count = export.documents.len();
+    let bundle = crate::share_format::seal(RAG_COLLECTION_SHARE_KIND, &export)?;
+    crate::share_format::write_bundle(&bundle, std::path::Path::new(&path))?;
+    println!("Exported collection {} ({} documents) to {}", export.name, count, path);
+    Ok(count)
+}
+
+#[tauri::command]
+pub async fn import_rag_collection(
+    app_handle: AppHandle,
+    path: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<RagCollectionImport, String> {
+    let system = get_rag_system(&state)?;
+    let bundle = crate::share_format::read_bundle(std::path::Path::new(&path))?;
+    let export: RagCollectionExport = crate::share_format::open(&app_handle, bundle, RAG_COLLECTION_SHARE_KIND).await?;
+    system.import_collection(export).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_chat_collection_scope(
+    chat_id: String,
+    collection_ids: Vec<String>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<(), String> {
+    let system = get_rag_system(&state)?;
+    system.set_chat_collection_scope(&chat_id, &collection_ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_chat_collection_scope(
+    chat_id: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<Vec<String>, String> {
+    let system = get_rag_system(&state)?;
+    system.get_chat_collection_scope(&chat_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn search_enhanced_documents_advanced(
+    search: AdvancedSearchQuery,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<Vec<EnhancedDocumentChunk>, String> {
+    let system = get_rag_system(&state)?;
+    system.advanced_search(&search).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_related_documents(
+    document_id: String,
+    limit: Option<usize>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<Vec<RelatedDocument>, String> {
+    let system = get_rag_system(&state)?;
+    system.get_related_documents(&document_id, limit.unwrap_or(5)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_enhanced_document(
+    document_id: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<DocumentRemovalReport, String> {
+    let system = get_rag_system(&state)?;
+    system.remove_document(&document_id).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_document_chunks(
+    document_id: String,
+    chunk_ids: Vec<String>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<usize, String> {
+    let system = get_rag_system(&state)?;
+    system.remove_chunks(&document_id, &chunk_ids).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn track_document_source(
+    document_id: String,
+    source_path: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<(), String> {
+    let system = get_rag_system(&state)?;
+    system.track_document_source(&document_id, &source_path).map_err(|e| e.to_string())
+}
+
+/// Documents whose source files changed since indexing; `recheck` looks at the files first.
+#[tauri::command]
+pub async fn get_stale_documents(
+    recheck: Option<bool>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<Vec<StaleDocument>, String> {
+    let system = get_rag_system(&state)?;
+    if recheck.unwrap_or(false) {
+        system.check_stale_documents().map_err(|e| e.to_string())?;
+    }
+    system.get_stale_documents().map_err(|e| e.to_string())
+}
+
+/// Documents excluded from retrieval because their drive or network share is offline.
+#[tauri::command]
+pub async fn get_unavailable_documents(
+    recheck: Option<bool>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<Vec<UnavailableDocument>, String> {
+    let system = get_rag_system(&state)?;
+    if recheck.unwrap_or(false) {
+        system.check_volume_availability().await.map_err(|e| e.to_string())?;
+    }
+    system.get_unavailable_documents().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reindex_stale(
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<StaleReindexReport, String> {
+    let system = get_rag_system(&state)?;
+    system.reindex_stale().await.map_err(|e| e.to_string())
+}
+
+/// Pack the pinned documents into one context within a token budget, with the decisions behind it.
+#[tauri::command]
+pub async fn pack_document_context(
+    query: String,
+    document_ids: Vec<String>,
+    constraints: Option<crate::context_packing::PackingConstraints>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<crate::context_packing::ContextPacking, String> {
+    let system = get_rag_system(&state)?;
+    system
+        .pack_context(&query, &document_ids, &constraints.unwrap_or_default())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_enhanced_rag_diagnostics(
+    repair: Option<bool>,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<RagDiagnostics, String> {
+    let system = get_rag_system(&state)?;
+    system.run_diagnostics(repair.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn explain_search(
+    query: String,
+    doc_id: String,
+    state: State<'_, EnhancedRagSystemState>,
+) -> Result<SearchExplanation, String> {
+    let system = get_rag_system(&state)?;
+    system.explain_search(&query, &doc_id).await.map_err(|e| e.to_string())
+}
@@ -2,17 +2,20 @@ use anyhow::{Result, anyhow};
 use rusqlite::{Connection, params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::fs;
 use chrono::Utc;
 use uuid::Uuid;
-use tauri::Manager;
 use sha2::{Sha256, Digest};
 
-use crate::simple_embedding_service::{SimpleEmbeddingService as EmbeddingService, EmbeddingConfig};
-use crate::search_service::{SearchService, SearchConfig, SearchResult};
+use crate::simple_embedding_service::{SimpleEmbeddingService as EmbeddingService, EmbeddingConfig, EmbeddingProvider, cosine_similarity, normalize_embedding};
+use crate::ollama_embedding_service::OllamaEmbeddingService;
+use crate::search_service::{SearchService, SearchConfig, SearchResult, ChunkScoreExplanation};
 use crate::chunking_service::{ChunkingService, ChunkingConfig, TextChunk, extract_text_from_pdf, clean_text};
+use crate::document_volumes::{self, VolumeIdentity};
+use crate::context_packing::{self, ContextPacking, PackingCandidate, PackingConstraints};
+use crate::document_tagging::DocumentTags;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct EnhancedDocument {
@@ -77,6 +80,173 @@ impl Default for EnhancedRagSettings {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DocumentCollection {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub document_count: i64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// How often tracked source files are checked for changes after the startup check
+const STALE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+// Score multiplier for chunks from documents carrying a boosted tag
+const TAG_BOOST: f32 = 1.25;
+// Removable drives and network shares come and go, so their volumes are probed more often
+const VOLUME_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// A document whose source file changed or disappeared since it was indexed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StaleDocument {
+    pub document_id: String,
+    pub file_name: String,
+    pub source_path: String,
+    pub reason: String, // "modified" | "missing"
+    pub detected_at: String,
+}
+
+/// A document excluded from retrieval because the volume holding its source is offline.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnavailableDocument {
+    pub document_id: String,
+    pub file_name: String,
+    pub source_path: String,
+    pub volume_root: String,
+    pub reason: String,
+    pub since: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VolumeAvailabilityChange {
+    pub went_unavailable: Vec<UnavailableDocument>,
+    pub restored: Vec<String>, // Document ids
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StaleReindexReport {
+    pub reindexed: Vec<String>,
+    pub skipped_missing: Vec<String>,
+    pub failed: Vec<(String, String)>, // Document id and error
+}
+
+/// Share-file kind for exported collections
+pub const RAG_COLLECTION_SHARE_KIND: &str = "rag_collection";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedChunk {
+    pub chunk_index: i32,
+    pub content: String,
+    pub start_char: i32,
+    pub end_char: i32,
+    pub token_count: i32,
+    pub embedding: Option<String>, // Little-endian f32s, base64
+    pub metadata: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedDocument {
+    pub file_name: String,
+    pub file_type: String,
+    pub file_size: i64,
+    pub content: String,
+    pub created_at: String,
+    pub metadata: Option<String>,
+    pub content_hash: Option<String>,
+    pub chunks: Vec<ExportedChunk>,
+}
+
+/// A collection with everything needed to search it on another machine without re-embedding.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RagCollectionExport {
+    pub name: String,
+    pub description: Option<String>,
+    pub embedding_model: String,
+    pub embedding_dimension: usize,
+    pub documents: Vec<ExportedDocument>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RagCollectionImport {
+    pub collection: DocumentCollection,
+    pub documents_imported: usize,
+    pub documents_already_present: usize,
+    pub chunks_imported: usize,
+    pub embeddings_reused: bool, // False when the embedding model differs and documents were queued for re-embedding
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AdvancedSearchQuery {
+    pub query: String,
+    pub document_ids: Option<Vec<String>>,
+    pub collections: Option<Vec<String>>,
+    pub chat_id: Option<String>,
+    pub limit: Option<usize>,
+    pub tags: Option<Vec<String>>,       // Only documents with at least one of these tags
+    pub boost_tags: Option<Vec<String>>, // Rank chunks from documents with these tags higher
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelatedDocument {
+    pub document_id: String,
+    pub file_name: String,
+    pub similarity: f32,
+    pub relationship: String, // "near_duplicate", "same_topic", "related"
+}
+
+// Centroid similarity thresholds used to classify related documents
+const NEAR_DUPLICATE_THRESHOLD: f32 = 0.97;
+const SAME_TOPIC_THRESHOLD: f32 = 0.75;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DocumentRemovalReport {
+    pub document_id: String,
+    pub chunks_removed: usize,
+    pub centroids_removed: usize,
+    pub collection_links_removed: usize,
+    pub queue_entries_removed: usize,
+    pub cache_entries_evicted: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RagDiagnostics {
+    pub document_count: i64,
+    pub chunk_count: i64,
+    pub orphaned_chunks: i64,
+    pub orphaned_centroids: i64,
+    pub orphaned_collection_links: i64,
+    pub orphaned_chat_scopes: i64,
+    pub orphaned_queue_entries: i64,
+    pub ghost_index_documents: Vec<String>,
+    pub orphaned_storage_dirs: Vec<String>,
+    pub repaired: bool,
+}
+
+impl RagDiagnostics {
+    pub fn has_dangling_references(&self) -> bool {
+        self.orphaned_chunks > 0
+            || self.orphaned_centroids > 0
+            || self.orphaned_collection_links > 0
+            || self.orphaned_chat_scopes > 0
+            || self.orphaned_queue_entries > 0
+            || !self.ghost_index_documents.is_empty()
+            || !self.orphaned_storage_dirs.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SearchExplanation {
+    pub query: String,
+    pub document_id: String,
+    pub file_name: Option<String>,
+    pub query_embedding_available: bool,
+    pub reranking_enabled: bool,
+    pub rerank_delta: f32, // No reranker runs yet, so this stays 0 until one does
+    pub result_rank: Option<usize>,
+    pub chunks: Vec<ChunkScoreExplanation>,
+}
+
 #[derive(Clone)]
 pub struct EnhancedRagSystem {
     db_path: PathBuf,
@@ -85,6 +255,7 @@ pub struct EnhancedRagSystem {
     cache_path: PathBuf,
     settings: Arc<Mutex<EnhancedRagSettings>>,
     embedding_service: Arc<EmbeddingService>,
+    ollama_embeddings: OllamaEmbeddingService,
     search_service: Arc<SearchService>,
     chunking_service: Arc<Mutex<ChunkingService>>,
 }
@@ -99,7 +270,15 @@ pub struct DocumentValidationResult {
 
 impl EnhancedRagSystem {
     pub async fn new(app_handle: &tauri::AppHandle) -> Result<Self> {
-        let app_dir = app_handle.path().app_data_dir()?;
+        let app_dir = crate::data::paths::user_data_dir(app_handle).map_err(|e| anyhow!(e))?;
+        let system = Self::open(&app_dir)?;
+        system.start_stale_watcher(app_handle.clone());
+        Ok(system)
+    }
+
+    /// Open the document store in `app_dir` without an app, e.g. from the CLI. Nothing watches
+    /// for stale documents or offline volumes.
+    pub fn open(app_dir: &Path) -> Result<Self> {
         let db_path = app_dir.join("enhanced_rag_documents.db");
         let storage_path = app_dir.join("document_storage");
         let index_path = app_dir.join("tantivy_index");
@@ -134,6 +313,7 @@ impl EnhancedRagSystem {
             cache_path,
             settings,
             embedding_service,
+            ollama_embeddings: OllamaEmbeddingService::new(&cache_path),
             search_service,
             chunking_service,
         };
@@ -141,6 +321,10 @@ impl EnhancedRagSystem {
         // Initialize database and services
         system.initialize_database()?;
         system.search_service.initialize_writer()?;
+        if system.search_service.take_needs_reindex() {
+            let reindexed = system.rebuild_search_index()?;
+            println!("Rebuilt search index from {} stored chunks", reindexed);
+        }
         
         // Initialize embedding service in background
         let embedding_service_clone = system.embedding_service.clone();
@@ -186,6 +370,15 @@ impl EnhancedRagSystem {
             [],
         );
         
+        // Where the document came from on disk, for stale detection; NULL for pasted/uploaded bytes
+        for column in [
+            "source_path TEXT", "source_mtime INTEGER", "stale_reason TEXT", "stale_since TEXT",
+            "volume_root TEXT", "volume_id TEXT", "volume_network INTEGER NOT NULL DEFAULT 0",
+            "unavailable_reason TEXT", "unavailable_since TEXT",
+        ] {
+            let _ = conn.execute(&format!("ALTER TABLE enhanced_documents ADD COLUMN {}", column), []);
+        }
+        
         // Create enhanced document_chunks table
         conn.execute(
             "CREATE TABLE IF NOT EXISTS enhanced_document_chunks (
@@ -230,6 +423,53 @@ impl EnhancedRagSystem {
             [],
         )?;
         
+        // Document-level centroid embeddings (mean of chunk embeddings) for related-document lookup
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_embeddings (
+                document_id TEXT PRIMARY KEY,
+                centroid BLOB NOT NULL,
+                chunk_count INTEGER NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (document_id) REFERENCES enhanced_documents(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        
+        // Named collections that group documents for scoped search
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS document_collections (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                description TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS collection_documents (
+                collection_id TEXT NOT NULL,
+                document_id TEXT NOT NULL,
+                added_at TEXT NOT NULL,
+                PRIMARY KEY (collection_id, document_id),
+                FOREIGN KEY (collection_id) REFERENCES document_collections(id) ON DELETE CASCADE,
+                FOREIGN KEY (document_id) REFERENCES enhanced_documents(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        
+        // Per-chat restriction of context suggestions to selected collections
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS chat_collection_scopes (
+                chat_id TEXT NOT NULL,
+                collection_id TEXT NOT NULL,
+                PRIMARY KEY (chat_id, collection_id),
+                FOREIGN KEY (collection_id) REFERENCES document_collections(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+        
         // Create indexes for better performance
         let indexes = vec![
             "CREATE INDEX IF NOT EXISTS idx_enhanced_document_chunks_document_id ON enhanced_document_chunks(document_id)",
@@ -237,6 +477,7 @@ impl EnhancedRagSystem {
             "CREATE INDEX IF NOT EXISTS idx_enhanced_documents_embedding_status ON enhanced_documents(embedding_status)",
             "CREATE INDEX IF NOT EXISTS idx_processing_queue_status ON processing_queue(status)",
             "CREATE INDEX IF NOT EXISTS idx_processing_queue_document_id ON processing_queue(document_id)",
+            "CREATE INDEX IF NOT EXISTS idx_collection_documents_document_id ON collection_documents(document_id)",
         ];
         
         for index_sql in indexes {
@@ -293,10 +534,7 @@ impl EnhancedRagSystem {
         file_type: String,
     ) -> Result<EnhancedDocument> {
         // Calculate content hash for duplicate detection
-        let mut hasher = Sha256::new();
-        hasher.update(&file_content);
-        hasher.update(file_name.as_bytes());
-        let content_hash = format!("{:x}", hasher.finalize());
+        let content_hash = Self::document_hash(&file_content, &file_name);
         
         // Check for duplicates
         let existing_doc = self.check_duplicate(&content_hash)?;
@@ -327,7 +565,7 @@ impl EnhancedRagSystem {
         fs::write(&file_path, &file_content)?;
         
         // Extract and clean text content
-        let raw_text = self.extract_text_content(&file_content, &file_type)?;
+        let raw_text = self.extract_text_content(&file_content, &file_type).await?;
         let clean_content = clean_text(&raw_text);
         
         // Create document chunks
@@ -361,13 +599,32 @@ impl EnhancedRagSystem {
         if auto_embedding {
             self.queue_embedding_generation(&doc_id).await?;
         }
+        self.queue_tagging(&doc_id);
         
         println!("Document uploaded: {} with {} chunks", file_name, chunks.len());
         
         Ok(document)
     }
     
-    fn extract_text_content(&self, file_content: &[u8], file_type: &str) -> Result<String> {
+    fn document_hash(file_content: &[u8], file_name: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(file_content);
+        hasher.update(file_name.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+    
+    async fn extract_text_content(&self, file_content: &[u8], file_type: &str) -> Result<String> {
+        // An approved plugin parser that claims the exact type wins over the built-in guesses. It
+        // runs a process for up to its timeout, so it's kept off the async workers
+        if crate::plugins::claims_file_type(file_type) {
+            let (content, claimed_type) = (file_content.to_vec(), file_type.to_string());
+            let parsed = tokio::task::spawn_blocking(move || crate::plugins::parse_document(&claimed_type, &content))
+                .await
+                .map_err(|e| anyhow!("Plugin parser task failed: {}", e))?;
+            if let Some(parsed) = parsed {
+                return parsed.map_err(|e| anyhow!(e));
+            }
+        }
         match file_type {
             t if t.contains("text") || t.contains("plain") => {
                 Ok(String::from_utf8_lossy(file_content).to_string())
@@ -461,10 +718,11 @@ impl EnhancedRagSystem {
             params![queue_id, document_id, now],
         )?;
         
-        // Process in background
+        // Process in background, deferring while the user is busy
         let system_clone = self.clone();
         let document_id_clone = document_id.to_string();
         tokio::spawn(async move {
+            crate::presence::wait_for_background_slot().await;
             if let Err(e) = system_clone.process_embeddings(&document_id_clone).await {
                 eprintln!("Failed to process embeddings for document {}: {}", document_id_clone, e);
             }
@@ -497,6 +755,21 @@ impl EnhancedRagSystem {
         Ok(())
     }
     
+    /// Embed with the provider the embedding settings select
+    async fn embed_texts(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        let config = self.settings.lock().unwrap().embedding_config.clone();
+        match config.provider {
+            EmbeddingProvider::Simple => self.embedding_service.embed_documents(texts),
+            EmbeddingProvider::Ollama => self.ollama_embeddings.embed(&config.ollama_model, config.normalize_embeddings, texts).await,
+        }
+    }
+    
+    async fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+        self.embed_texts(vec![query.to_string()]).await?
+            .pop()
+            .ok_or_else(|| anyhow!("No embedding returned for the query"))
+    }
+    
     async fn process_embeddings(&self, document_id: &str) -> Result<()> {
         // Wait for embedding service to be ready
         while !self.embedding_service.is_initialized() {
@@ -512,13 +785,34 @@ impl EnhancedRagSystem {
             return Err(anyhow!("No chunks found for document {}", document_id));
         }
         
-        // Generate embeddings for chunks
-        let chunk_texts: Vec<String> = chunks.iter().map(|c| c.content.clone()).collect();
+        // Generate embeddings batch by batch through the throttled pipeline
+        let mut job = crate::embedding_pipeline::EmbeddingJob::start(document_id, chunks.len()).await;
+        let batch_size = crate::embedding_pipeline::batch_size();
+        let mut embedding_result: Result<Vec<Vec<f32>>> = Ok(Vec::with_capacity(chunks.len()));
+        
+        for batch in chunks.chunks(batch_size) {
+            job.before_batch(batch.len()).await;
+            let batch_texts: Vec<String> = batch.iter().map(|c| c.content.clone()).collect();
+            match self.embed_texts(batch_texts).await {
+                Ok(batch_embeddings) => {
+                    if let Ok(embeddings) = embedding_result.as_mut() {
+                        embeddings.extend(batch_embeddings);
+                        job.record_progress(embeddings.len());
+                    }
+                }
+                Err(e) => {
+                    embedding_result = Err(e);
+                    break;
+                }
+            }
+        }
+        job.finish(embedding_result.is_ok());
         
-        match self.embedding_service.embed_documents(chunk_texts) {
+        match embedding_result {
             Ok(embeddings) => {
                 // Save embeddings to database and search index
                 self.save_embeddings_to_db(document_id, &chunks, &embeddings)?;
+                self.save_document_centroid(document_id, &embeddings)?;
                 self.index_chunks_for_search(document_id, &chunks, &embeddings).await?;
                 
                 // Update document status
@@ -582,6 +876,79 @@ impl EnhancedRagSystem {
         Ok(())
     }
     
+    fn compute_centroid(embeddings: &[Vec<f32>]) -> Option<Vec<f32>> {
+        let dimension = embeddings.first()?.len();
+        let mut centroid = vec![0.0f32; dimension];
+        let mut count = 0;
+        
+        for embedding in embeddings.iter().filter(|e| e.len() == dimension) {
+            for (sum, value) in centroid.iter_mut().zip(embedding.iter()) {
+                *sum += value;
+            }
+            count += 1;
+        }
+        
+        if count == 0 {
+            return None;
+        }
+        for value in centroid.iter_mut() {
+            *value /= count as f32;
+        }
+        normalize_embedding(&mut centroid);
+        Some(centroid)
+    }
+    
+    fn save_document_centroid(&self, document_id: &str, embeddings: &[Vec<f32>]) -> Result<()> {
+        let centroid = match Self::compute_centroid(embeddings) {
+            Some(centroid) => centroid,
+            None => return Ok(()),
+        };
+        
+        let centroid_bytes = centroid.iter()
+            .flat_map(|&f| f.to_le_bytes().to_vec())
+            .collect::<Vec<u8>>();
+        
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO document_embeddings (document_id, centroid, chunk_count, updated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![document_id, centroid_bytes, embeddings.len() as i64, Utc::now().to_rfc3339()],
+        )?;
+        
+        Ok(())
+    }
+    
+    fn decode_embedding(bytes: &[u8]) -> Vec<f32> {
+        bytes.chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+    
+    // Documents embedded before centroids existed get theirs built from stored chunk embeddings
+    fn backfill_document_centroids(&self) -> Result<()> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id FROM enhanced_documents
+             WHERE embedding_status = 'completed'
+               AND id NOT IN (SELECT document_id FROM document_embeddings)"
+        )?;
+        let missing = stmt.query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let mut chunk_stmt = conn.prepare(
+            "SELECT embedding FROM enhanced_document_chunks WHERE document_id = ?1 AND embedding IS NOT NULL"
+        )?;
+        for document_id in missing {
+            let embeddings = chunk_stmt.query_map([&document_id], |row| row.get::<_, Vec<u8>>(0))?
+                .filter_map(|bytes| bytes.ok())
+                .map(|bytes| Self::decode_embedding(&bytes))
+                .collect::<Vec<_>>();
+            self.save_document_centroid(&document_id, &embeddings)?;
+        }
+        
+        Ok(())
+    }
+    
     async fn index_chunks_for_search(&self, document_id: &str, chunks: &[EnhancedDocumentChunk], embeddings: &[Vec<f32>]) -> Result<()> {
         let search_chunks: Vec<crate::search_service::DocumentChunk> = chunks.iter()
             .zip(embeddings.iter())
@@ -630,7 +997,7 @@ impl EnhancedRagSystem {
         
         // Generate query embedding
         let query_embedding = if self.embedding_service.is_initialized() {
-            match self.embedding_service.embed_query(query) {
+            match self.embed_query(query).await {
                 Ok(emb) => Some(emb),
                 Err(e) => {
                     eprintln!("Failed to generate query embedding: {}", e);
@@ -650,14 +1017,12 @@ impl EnhancedRagSystem {
             self.search_service.search_bm25(query, 20)?
         };
         
-        // Filter by context documents if specified
-        let filtered_results = if context_document_ids.is_empty() {
-            search_results
-        } else {
-            search_results.into_iter()
-                .filter(|result| context_document_ids.contains(&result.document_id))
-                .collect()
-        };
+        // Filter by context documents if specified, and drop documents whose volume is offline
+        let unavailable = self.unavailable_document_ids()?;
+        let filtered_results: Vec<SearchResult> = search_results.into_iter()
+            .filter(|result| context_document_ids.is_empty() || context_document_ids.contains(&result.document_id))
+            .filter(|result| !unavailable.contains(&result.document_id))
+            .collect();
         
         // Convert search results to enhanced document chunks
         let enhanced_chunks = self.convert_search_results_to_chunks(filtered_results)?;
@@ -753,13 +1118,38 @@ impl EnhancedRagSystem {
     }
     
     pub async fn delete_document(&self, document_id: &str) -> Result<()> {
-        // Delete from search index
+        self.remove_document(document_id).await.map(|_| ())
+    }
+    
+    /// Remove a document and everything derived from it: chunks, embeddings, index entries,
+    /// cached embeddings and collection references.
+    pub async fn remove_document(&self, document_id: &str) -> Result<DocumentRemovalReport> {
+        let chunk_texts: Vec<String> = self.get_document_chunks(document_id)?
+            .into_iter()
+            .map(|chunk| chunk.content)
+            .collect();
+        
+        // Foreign keys aren't enforced on these connections, so every table is cleaned explicitly
+        let mut conn = Connection::open(&self.db_path)?;
+        let tx = conn.transaction()?;
+        let mut report = DocumentRemovalReport {
+            document_id: document_id.to_string(),
+            chunks_removed: tx.execute("DELETE FROM enhanced_document_chunks WHERE document_id = ?1", params![document_id])?,
+            centroids_removed: tx.execute("DELETE FROM document_embeddings WHERE document_id = ?1", params![document_id])?,
+            collection_links_removed: tx.execute("DELETE FROM collection_documents WHERE document_id = ?1", params![document_id])?,
+            queue_entries_removed: tx.execute("DELETE FROM processing_queue WHERE document_id = ?1", params![document_id])?,
+            cache_entries_evicted: 0,
+        };
+        let removed = tx.execute("DELETE FROM enhanced_documents WHERE id = ?1", params![document_id])?;
+        if removed == 0 && report.chunks_removed == 0 {
+            return Err(anyhow!("Document not found: {}", document_id));
+        }
+        tx.commit()?;
+        
+        // Index and cache are cleaned after the database commit; diagnostics catch anything left behind
         self.search_service.delete_document(document_id)?;
         self.search_service.commit()?;
-        
-        // Delete from database (cascades to chunks)
-        let conn = Connection::open(&self.db_path)?;
-        conn.execute("DELETE FROM enhanced_documents WHERE id = ?1", params![document_id])?;
+        report.cache_entries_evicted = self.embedding_service.evict_cached(&chunk_texts);
         
         // Delete files from storage
         let doc_path = self.storage_path.join(document_id);
@@ -767,7 +1157,150 @@ impl EnhancedRagSystem {
             fs::remove_dir_all(doc_path)?;
         }
         
-        Ok(())
+        println!("Removed document {} ({} chunks)", document_id, report.chunks_removed);
+        Ok(report)
+    }
+    
+    /// Remove individual chunks from a document, keeping the rest of it searchable
+    pub async fn remove_chunks(&self, document_id: &str, chunk_ids: &[String]) -> Result<usize> {
+        let chunk_texts: Vec<String> = self.get_document_chunks(document_id)?
+            .into_iter()
+            .filter(|chunk| chunk_ids.contains(&chunk.id))
+            .map(|chunk| chunk.content)
+            .collect();
+        
+        let mut conn = Connection::open(&self.db_path)?;
+        let tx = conn.transaction()?;
+        let mut removed = 0;
+        for chunk_id in chunk_ids {
+            removed += tx.execute(
+                "DELETE FROM enhanced_document_chunks WHERE id = ?1 AND document_id = ?2",
+                params![chunk_id, document_id],
+            )?;
+        }
+        tx.execute(
+            "UPDATE enhanced_documents
+             SET chunk_count = (SELECT COUNT(*) FROM enhanced_document_chunks WHERE document_id = ?1), updated_at = ?2
+             WHERE id = ?1",
+            params![document_id, Utc::now().to_rfc3339()],
+        )?;
+        tx.execute("DELETE FROM document_embeddings WHERE document_id = ?1", params![document_id])?;
+        tx.commit()?;
+        
+        self.search_service.delete_chunks(chunk_ids)?;
+        self.search_service.commit()?;
+        self.embedding_service.evict_cached(&chunk_texts);
+        
+        // The centroid is rebuilt lazily from the remaining chunk embeddings
+        self.backfill_document_centroids()?;
+        
+        Ok(removed)
+    }
+    
+    fn load_chunks_with_embeddings(&self) -> Result<Vec<crate::search_service::DocumentChunk>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.document_id, c.content, c.embedding, c.metadata
+             FROM enhanced_document_chunks c
+             JOIN enhanced_documents d ON d.id = c.document_id
+             WHERE c.embedding IS NOT NULL"
+        )?;
+        
+        let chunks = stmt.query_map([], |row| {
+            Ok(crate::search_service::DocumentChunk {
+                id: row.get(0)?,
+                document_id: row.get(1)?,
+                content: row.get(2)?,
+                embedding: Some(Self::decode_embedding(&row.get::<_, Vec<u8>>(3)?)),
+                metadata: row.get(4)?,
+            })
+        })?;
+        
+        Ok(chunks.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    /// Repopulate the search index from chunks stored in the database
+    pub fn rebuild_search_index(&self) -> Result<usize> {
+        let chunks = self.load_chunks_with_embeddings()?;
+        let count = chunks.len();
+        
+        self.search_service.clear_index()?;
+        self.search_service.add_documents(chunks)?;
+        self.search_service.commit()?;
+        
+        Ok(count)
+    }
+    
+    /// Look for rows, index entries and files that point at documents which no longer exist.
+    /// With `repair`, dangling rows are deleted and ghost index entries removed.
+    pub fn run_diagnostics(&self, repair: bool) -> Result<RagDiagnostics> {
+        let conn = Connection::open(&self.db_path)?;
+        let count = |sql: &str| -> Result<i64> { Ok(conn.query_row(sql, [], |row| row.get(0))?) };
+        
+        let mut diagnostics = RagDiagnostics {
+            document_count: count("SELECT COUNT(*) FROM enhanced_documents")?,
+            chunk_count: count("SELECT COUNT(*) FROM enhanced_document_chunks")?,
+            orphaned_chunks: count(
+                "SELECT COUNT(*) FROM enhanced_document_chunks WHERE document_id NOT IN (SELECT id FROM enhanced_documents)"
+            )?,
+            orphaned_centroids: count(
+                "SELECT COUNT(*) FROM document_embeddings WHERE document_id NOT IN (SELECT id FROM enhanced_documents)"
+            )?,
+            orphaned_collection_links: count(
+                "SELECT COUNT(*) FROM collection_documents
+                 WHERE document_id NOT IN (SELECT id FROM enhanced_documents)
+                    OR collection_id NOT IN (SELECT id FROM document_collections)"
+            )?,
+            orphaned_chat_scopes: count(
+                "SELECT COUNT(*) FROM chat_collection_scopes WHERE collection_id NOT IN (SELECT id FROM document_collections)"
+            )?,
+            orphaned_queue_entries: count(
+                "SELECT COUNT(*) FROM processing_queue WHERE document_id NOT IN (SELECT id FROM enhanced_documents)"
+            )?,
+            ..Default::default()
+        };
+        
+        let mut stmt = conn.prepare("SELECT id FROM enhanced_documents")?;
+        let known_ids: std::collections::HashSet<String> = stmt.query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<_, _>>()?;
+        
+        diagnostics.ghost_index_documents = self.search_service.indexed_document_ids()?
+            .into_iter()
+            .filter(|id| !known_ids.contains(id))
+            .collect();
+        
+        if let Ok(entries) = fs::read_dir(&self.storage_path) {
+            diagnostics.orphaned_storage_dirs = entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| !known_ids.contains(name))
+                .collect();
+        }
+        
+        if repair && diagnostics.has_dangling_references() {
+            conn.execute_batch(
+                "DELETE FROM enhanced_document_chunks WHERE document_id NOT IN (SELECT id FROM enhanced_documents);
+                 DELETE FROM document_embeddings WHERE document_id NOT IN (SELECT id FROM enhanced_documents);
+                 DELETE FROM collection_documents
+                  WHERE document_id NOT IN (SELECT id FROM enhanced_documents)
+                     OR collection_id NOT IN (SELECT id FROM document_collections);
+                 DELETE FROM chat_collection_scopes WHERE collection_id NOT IN (SELECT id FROM document_collections);
+                 DELETE FROM processing_queue WHERE document_id NOT IN (SELECT id FROM enhanced_documents);"
+            )?;
+            
+            for document_id in &diagnostics.ghost_index_documents {
+                self.search_service.delete_document(document_id)?;
+            }
+            self.search_service.commit()?;
+            
+            for dir in &diagnostics.orphaned_storage_dirs {
+                let _ = fs::remove_dir_all(self.storage_path.join(dir));
+            }
+            diagnostics.repaired = true;
+        }
+        
+        Ok(diagnostics)
     }
     
     pub async fn generate_embeddings(&self, document_id: &str) -> Result<String> {
@@ -799,6 +1332,7 @@ impl EnhancedRagSystem {
         let conn = Connection::open(&self.db_path)?;
         conn.execute("UPDATE enhanced_document_chunks SET embedding = NULL", [])?;
         conn.execute("UPDATE enhanced_documents SET is_cached = 0, embedding_status = 'pending'", [])?;
+        self.ollama_embeddings.clear_cache()?;
         
         Ok("Embedding cache cleared successfully".to_string())
     }
@@ -871,7 +1405,7 @@ impl EnhancedRagSystem {
         let settings = self.settings.lock().unwrap();
         stats.insert("max_cached_documents".to_string(), serde_json::json!(settings.max_cached_documents));
         stats.insert("max_document_size_mb".to_string(), serde_json::json!(settings.max_document_size_mb));
-        stats.insert("embedding_model".to_string(), serde_json::json!(settings.embedding_config.model_name));
+        stats.insert("embedding_model".to_string(), serde_json::json!(settings.embedding_config.active_model()));
         stats.insert("reranking_enabled".to_string(), serde_json::json!(settings.reranking_enabled));
         
         Ok(stats)
@@ -1005,6 +1539,7 @@ impl EnhancedRagSystem {
         let validation_result = self.validate_documents_for_search(document_ids).await?;
         
         let mut status_map = HashMap::new();
+        let unavailable = self.unavailable_document_ids()?;
         
         for doc_id in &validation_result.ready_documents {
             status_map.insert(doc_id.clone(), "ready".to_string());
@@ -1022,6 +1557,1024 @@ impl EnhancedRagSystem {
             status_map.insert(doc_id.clone(), "embedding_retry_queued".to_string());
         }
         
+        for doc_id in document_ids.iter().filter(|id| unavailable.contains(*id)) {
+            status_map.insert(doc_id.clone(), "unavailable".to_string());
+        }
+        
         Ok(status_map)
     }
+    
+    // Document collections
+    
+    pub fn create_collection(&self, name: &str, description: Option<String>) -> Result<DocumentCollection> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Collection name cannot be empty"));
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        let id = Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+        
+        conn.execute(
+            "INSERT INTO document_collections (id, name, description, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![id, name, description, now],
+        ).map_err(|e| anyhow!("Failed to create collection '{}': {}", name, e))?;
+        
+        Ok(DocumentCollection {
+            id,
+            name: name.to_string(),
+            description,
+            document_count: 0,
+            created_at: now.clone(),
+            updated_at: now,
+        })
+    }
+    
+    pub fn rename_collection(&self, collection_id: &str, new_name: &str) -> Result<()> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            return Err(anyhow!("Collection name cannot be empty"));
+        }
+        
+        let conn = Connection::open(&self.db_path)?;
+        let updated = conn.execute(
+            "UPDATE document_collections SET name = ?1, updated_at = ?2 WHERE id = ?3",
+            params![new_name, Utc::now().to_rfc3339(), collection_id],
+        )?;
+        
+        if updated == 0 {
+            return Err(anyhow!("Collection not found: {}", collection_id));
+        }
+        Ok(())
+    }
+    
+    pub fn delete_collection(&self, collection_id: &str) -> Result<()> {
+        // Documents themselves are kept; only the grouping goes away
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute("DELETE FROM collection_documents WHERE collection_id = ?1", params![collection_id])?;
+        conn.execute("DELETE FROM chat_collection_scopes WHERE collection_id = ?1", params![collection_id])?;
+        let deleted = conn.execute("DELETE FROM document_collections WHERE id = ?1", params![collection_id])?;
+        
+        if deleted == 0 {
+            return Err(anyhow!("Collection not found: {}", collection_id));
+        }
+        Ok(())
+    }
+    
+    pub fn list_collections(&self) -> Result<Vec<DocumentCollection>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.name, c.description, c.created_at, c.updated_at, COUNT(cd.document_id)
+             FROM document_collections c
+             LEFT JOIN collection_documents cd ON cd.collection_id = c.id
+             GROUP BY c.id
+             ORDER BY c.name"
+        )?;
+        
+        let collections = stmt.query_map([], |row| {
+            Ok(DocumentCollection {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+                document_count: row.get(5)?,
+            })
+        })?;
+        
+        Ok(collections.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    pub fn assign_documents_to_collection(&self, collection_id: &str, document_ids: &[String]) -> Result<usize> {
+        let conn = Connection::open(&self.db_path)?;
+        let now = Utc::now().to_rfc3339();
+        let mut assigned = 0;
+        
+        for doc_id in document_ids {
+            assigned += conn.execute(
+                "INSERT OR IGNORE INTO collection_documents (collection_id, document_id, added_at)
+                 SELECT ?1, id, ?3 FROM enhanced_documents WHERE id = ?2",
+                params![collection_id, doc_id, now],
+            )?;
+        }
+        
+        conn.execute(
+            "UPDATE document_collections SET updated_at = ?1 WHERE id = ?2",
+            params![now, collection_id],
+        )?;
+        
+        Ok(assigned)
+    }
+    
+    pub fn remove_documents_from_collection(&self, collection_id: &str, document_ids: &[String]) -> Result<usize> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut removed = 0;
+        
+        for doc_id in document_ids {
+            removed += conn.execute(
+                "DELETE FROM collection_documents WHERE collection_id = ?1 AND document_id = ?2",
+                params![collection_id, doc_id],
+            )?;
+        }
+        
+        Ok(removed)
+    }
+    
+    /// Resolve collection ids or names to the documents they contain
+    pub fn get_collection_document_ids(&self, collections: &[String]) -> Result<Vec<String>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT cd.document_id
+             FROM collection_documents cd
+             JOIN document_collections c ON c.id = cd.collection_id
+             WHERE c.id = ?1 OR c.name = ?1"
+        )?;
+        
+        let mut document_ids = Vec::new();
+        for collection in collections {
+            let ids = stmt.query_map([collection], |row| row.get::<_, String>(0))?;
+            for id in ids {
+                let id = id?;
+                if !document_ids.contains(&id) {
+                    document_ids.push(id);
+                }
+            }
+        }
+        
+        Ok(document_ids)
+    }
+    
+    // Stale documents
+    
+    /// Remember the file a document was read from so later edits to it can be detected
+    pub fn track_document_source(&self, document_id: &str, source_path: &str) -> Result<()> {
+        let mtime = Self::file_mtime(std::path::Path::new(source_path));
+        let volume = document_volumes::identify(std::path::Path::new(source_path));
+        let conn = Connection::open(&self.db_path)?;
+        let updated = conn.execute(
+            "UPDATE enhanced_documents
+             SET source_path = ?1, source_mtime = ?2, stale_reason = NULL, stale_since = NULL,
+                 volume_root = ?3, volume_id = ?4, volume_network = ?5, unavailable_reason = NULL, unavailable_since = NULL
+             WHERE id = ?6",
+            params![
+                source_path,
+                mtime,
+                volume.as_ref().map(|v| v.root.clone()),
+                volume.as_ref().and_then(|v| v.id.clone()),
+                volume.as_ref().map(|v| v.network).unwrap_or(false),
+                document_id,
+            ],
+        )?;
+        if updated == 0 {
+            return Err(anyhow!("Document not found: {}", document_id));
+        }
+        Ok(())
+    }
+    
+    fn file_mtime(path: &std::path::Path) -> Option<i64> {
+        fs::metadata(path).ok()?
+            .modified().ok()?
+            .duration_since(std::time::UNIX_EPOCH).ok()
+            .map(|elapsed| elapsed.as_secs() as i64)
+    }
+    
+    /// Compare tracked source files with the hashes they were indexed with. Files are only re-hashed
+    /// when their modification time moved. Returns the documents newly found stale.
+    pub fn check_stale_documents(&self) -> Result<Vec<StaleDocument>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_name, source_path, source_mtime, content_hash, stale_reason
+             FROM enhanced_documents WHERE source_path IS NOT NULL AND unavailable_reason IS NULL"
+        )?;
+        let tracked = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        let now = Utc::now().to_rfc3339();
+        let mut newly_stale = Vec::new();
+        for (id, file_name, source_path, stored_mtime, content_hash, stale_reason) in tracked {
+            let path = std::path::Path::new(&source_path);
+            let reason = if !path.exists() {
+                Some("missing")
+            } else {
+                let mtime = Self::file_mtime(path);
+                if mtime == stored_mtime && stale_reason.as_deref() != Some("missing") {
+                    continue;
+                }
+                let changed = match fs::read(path) {
+                    Ok(bytes) => content_hash.as_deref() != Some(Self::document_hash(&bytes, &file_name).as_str()),
+                    Err(e) => {
+                        eprintln!("Could not read {} for stale check: {}", source_path, e);
+                        continue;
+                    }
+                };
+                // A touched-but-identical file only needs its mtime refreshed
+                conn.execute("UPDATE enhanced_documents SET source_mtime = ?1 WHERE id = ?2", params![mtime, id])?;
+                changed.then_some("modified")
+            };
+            
+            match reason {
+                Some(reason) if stale_reason.as_deref() != Some(reason) => {
+                    conn.execute(
+                        "UPDATE enhanced_documents SET stale_reason = ?1, stale_since = COALESCE(stale_since, ?2) WHERE id = ?3",
+                        params![reason, now, id],
+                    )?;
+                    newly_stale.push(StaleDocument {
+                        document_id: id,
+                        file_name,
+                        source_path,
+                        reason: reason.to_string(),
+                        detected_at: now.clone(),
+                    });
+                }
+                Some(_) => {}
+                None if stale_reason.is_some() => {
+                    conn.execute("UPDATE enhanced_documents SET stale_reason = NULL, stale_since = NULL WHERE id = ?1", params![id])?;
+                }
+                None => {}
+            }
+        }
+        
+        Ok(newly_stale)
+    }
+    
+    pub fn get_stale_documents(&self) -> Result<Vec<StaleDocument>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_name, source_path, stale_reason, stale_since
+             FROM enhanced_documents WHERE stale_reason IS NOT NULL
+             ORDER BY stale_since"
+        )?;
+        let stale = stmt.query_map([], |row| {
+            Ok(StaleDocument {
+                document_id: row.get(0)?,
+                file_name: row.get(1)?,
+                source_path: row.get(2)?,
+                reason: row.get(3)?,
+                detected_at: row.get(4)?,
+            })
+        })?;
+        Ok(stale.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    /// Check on startup and then periodically, telling the frontend when documents go stale
+    /// or their volumes go offline and come back
+    fn start_stale_watcher(&self, app_handle: tauri::AppHandle) {
+        use tauri::Emitter;
+        
+        let system = self.clone();
+        tokio::spawn(async move {
+            let mut last_stale_check: Option<std::time::Instant> = None;
+            loop {
+                let mut restored_any = false;
+                match system.check_volume_availability().await {
+                    Ok(change) => {
+                        if !change.went_unavailable.is_empty() {
+                            println!("{} documents unavailable while their volume is offline", change.went_unavailable.len());
+                            let _ = app_handle.emit("rag-documents-unavailable", &change.went_unavailable);
+                        }
+                        if !change.restored.is_empty() {
+                            println!("{} documents available again", change.restored.len());
+                            let _ = app_handle.emit("rag-documents-restored", &change.restored);
+                            restored_any = true;
+                        }
+                    }
+                    Err(e) => eprintln!("Volume availability check failed: {}", e),
+                }
+                
+                // Files on a volume that just came back may have been edited elsewhere
+                let stale_check_due = restored_any
+                    || last_stale_check.map_or(true, |checked| checked.elapsed() >= STALE_CHECK_INTERVAL);
+                if stale_check_due {
+                    crate::presence::wait_for_background_slot().await;
+                    match system.check_stale_documents() {
+                        Ok(newly_stale) if !newly_stale.is_empty() => {
+                            println!("{} indexed documents changed on disk", newly_stale.len());
+                            let _ = app_handle.emit("rag-documents-stale", &newly_stale);
+                        }
+                        Ok(_) => {}
+                        Err(e) => eprintln!("Stale document check failed: {}", e),
+                    }
+                    last_stale_check = Some(std::time::Instant::now());
+                }
+                tokio::time::sleep(VOLUME_CHECK_INTERVAL).await;
+            }
+        });
+    }
+    
+    // Volume availability
+    
+    fn unavailable_document_ids(&self) -> Result<std::collections::HashSet<String>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare("SELECT id FROM enhanced_documents WHERE unavailable_reason IS NOT NULL")?;
+        let ids = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        Ok(ids.collect::<Result<_, _>>()?)
+    }
+    
+    /// Probe each volume that holds tracked sources once, marking its documents unavailable while it
+    /// is offline and restoring them when it's back.
+    pub async fn check_volume_availability(&self) -> Result<VolumeAvailabilityChange> {
+        let volumes: Vec<VolumeIdentity> = {
+            let conn = Connection::open(&self.db_path)?;
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT volume_root, volume_id, volume_network
+                 FROM enhanced_documents WHERE volume_root IS NOT NULL"
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok(VolumeIdentity {
+                    root: row.get(0)?,
+                    id: row.get(1)?,
+                    network: row.get::<_, i32>(2)? != 0,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        
+        let mut change = VolumeAvailabilityChange::default();
+        let now = Utc::now().to_rfc3339();
+        for volume in volumes {
+            let status = document_volumes::probe(&volume).await;
+            let conn = Connection::open(&self.db_path)?;
+            match status {
+                Ok(()) => {
+                    let mut stmt = conn.prepare(
+                        "UPDATE enhanced_documents SET unavailable_reason = NULL, unavailable_since = NULL
+                         WHERE volume_root = ?1 AND volume_id IS ?2 AND unavailable_reason IS NOT NULL
+                         RETURNING id"
+                    )?;
+                    let restored = stmt.query_map(params![volume.root, volume.id], |row| row.get::<_, String>(0))?;
+                    change.restored.extend(restored.collect::<Result<Vec<_>, _>>()?);
+                }
+                Err(reason) => {
+                    let mut stmt = conn.prepare(
+                        "UPDATE enhanced_documents SET unavailable_reason = ?1, unavailable_since = ?2
+                         WHERE volume_root = ?3 AND volume_id IS ?4 AND unavailable_reason IS NULL
+                         RETURNING id, file_name, source_path"
+                    )?;
+                    let affected = stmt.query_map(params![reason, now, volume.root, volume.id], |row| {
+                        Ok(UnavailableDocument {
+                            document_id: row.get(0)?,
+                            file_name: row.get(1)?,
+                            source_path: row.get(2)?,
+                            volume_root: volume.root.clone(),
+                            reason: reason.clone(),
+                            since: now.clone(),
+                        })
+                    })?;
+                    change.went_unavailable.extend(affected.collect::<Result<Vec<_>, _>>()?);
+                }
+            }
+        }
+        
+        Ok(change)
+    }
+    
+    pub fn get_unavailable_documents(&self) -> Result<Vec<UnavailableDocument>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_name, source_path, volume_root, unavailable_reason, unavailable_since
+             FROM enhanced_documents WHERE unavailable_reason IS NOT NULL
+             ORDER BY volume_root, file_name"
+        )?;
+        let documents = stmt.query_map([], |row| {
+            Ok(UnavailableDocument {
+                document_id: row.get(0)?,
+                file_name: row.get(1)?,
+                source_path: row.get(2)?,
+                volume_root: row.get(3)?,
+                reason: row.get(4)?,
+                since: row.get(5)?,
+            })
+        })?;
+        Ok(documents.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    /// Re-read a modified source file and replace the document's chunks, embeddings and index entries
+    async fn reindex_document(&self, document_id: &str, file_name: &str, file_type: &str, source_path: &str) -> Result<()> {
+        let file_content = fs::read(source_path)?;
+        let content = clean_text(&self.extract_text_content(&file_content, file_type).await?);
+        let chunks = self.create_document_chunks(document_id, &content).await?;
+        let old_chunk_texts: Vec<String> = self.get_document_chunks(document_id)?
+            .into_iter()
+            .map(|chunk| chunk.content)
+            .collect();
+        
+        {
+            let mut conn = Connection::open(&self.db_path)?;
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM enhanced_document_chunks WHERE document_id = ?1", params![document_id])?;
+            tx.execute("DELETE FROM document_embeddings WHERE document_id = ?1", params![document_id])?;
+            tx.execute(
+                "UPDATE enhanced_documents
+                 SET content = ?1, file_size = ?2, content_hash = ?3, chunk_count = ?4, updated_at = ?5,
+                     embedding_status = 'pending', is_cached = 0, source_mtime = ?6, stale_reason = NULL, stale_since = NULL
+                 WHERE id = ?7",
+                params![
+                    content,
+                    file_content.len() as i64,
+                    Self::document_hash(&file_content, file_name),
+                    chunks.len() as i32,
+                    Utc::now().to_rfc3339(),
+                    Self::file_mtime(std::path::Path::new(source_path)),
+                    document_id,
+                ],
+            )?;
+            tx.commit()?;
+        }
+        self.save_chunks_to_db(document_id, &chunks)?;
+        
+        self.search_service.delete_document(document_id)?;
+        self.search_service.commit()?;
+        self.embedding_service.evict_cached(&old_chunk_texts);
+        
+        // Keep the stored copy in step with the source
+        let stored_path = self.storage_path.join(document_id).join(file_name);
+        fs::create_dir_all(stored_path.parent().unwrap())?;
+        fs::write(&stored_path, &file_content)?;
+        
+        self.queue_embedding_generation(document_id).await
+    }
+    
+    /// Reindex every document whose source was modified; missing sources are left for the user
+    pub async fn reindex_stale(&self) -> Result<StaleReindexReport> {
+        let stale: Vec<(String, String, String, String, String)> = {
+            let conn = Connection::open(&self.db_path)?;
+            let mut stmt = conn.prepare(
+                "SELECT id, file_name, file_type, source_path, stale_reason
+                 FROM enhanced_documents WHERE stale_reason IS NOT NULL AND unavailable_reason IS NULL"
+            )?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        
+        let mut report = StaleReindexReport::default();
+        for (id, file_name, file_type, source_path, reason) in stale {
+            if reason == "missing" {
+                report.skipped_missing.push(id);
+                continue;
+            }
+            match self.reindex_document(&id, &file_name, &file_type, &source_path).await {
+                Ok(()) => report.reindexed.push(id),
+                Err(e) => report.failed.push((id, e.to_string())),
+            }
+        }
+        
+        println!("Reindexed {} stale documents ({} missing, {} failed)",
+                 report.reindexed.len(), report.skipped_missing.len(), report.failed.len());
+        Ok(report)
+    }
+    
+    fn find_collection(&self, collection: &str) -> Result<DocumentCollection> {
+        self.list_collections()?
+            .into_iter()
+            .find(|c| c.id == collection || c.name == collection)
+            .ok_or_else(|| anyhow!("Collection not found: {}", collection))
+    }
+    
+    /// Bundle a collection's documents, chunks and embeddings for another machine
+    pub fn export_collection(&self, collection: &str) -> Result<RagCollectionExport> {
+        use base64::Engine;
+        
+        let collection = self.find_collection(collection)?;
+        let document_ids = self.get_collection_document_ids(&[collection.id.clone()])?;
+        let embedding_config = self.settings.lock().unwrap().embedding_config.clone();
+        
+        let conn = Connection::open(&self.db_path)?;
+        let mut chunk_stmt = conn.prepare(
+            "SELECT chunk_index, content, start_char, end_char, token_count, embedding, metadata
+             FROM enhanced_document_chunks
+             WHERE document_id = ?1
+             ORDER BY chunk_index"
+        )?;
+        
+        let mut documents = Vec::new();
+        for document in self.get_all_documents()?.into_iter().filter(|d| document_ids.contains(&d.id)) {
+            let chunks = chunk_stmt.query_map([&document.id], |row| {
+                Ok(ExportedChunk {
+                    chunk_index: row.get(0)?,
+                    content: row.get(1)?,
+                    start_char: row.get(2)?,
+                    end_char: row.get(3)?,
+                    token_count: row.get(4)?,
+                    embedding: row.get::<_, Option<Vec<u8>>>(5)?
+                        .map(|bytes| base64::engine::general_purpose::STANDARD.encode(bytes)),
+                    metadata: row.get(6)?,
+                })
+            })?.collect::<Result<Vec<_>, _>>()?;
+            
+            documents.push(ExportedDocument {
+                file_name: document.file_name,
+                file_type: document.file_type,
+                file_size: document.file_size,
+                content: document.content,
+                created_at: document.created_at,
+                metadata: document.metadata,
+                content_hash: document.content_hash,
+                chunks,
+            });
+        }
+        
+        Ok(RagCollectionExport {
+            name: collection.name,
+            description: collection.description,
+            embedding_model: embedding_config.active_model().to_string(),
+            embedding_dimension: embedding_config.embedding_dimension,
+            documents,
+        })
+    }
+    
+    /// Add an exported collection's documents, merging into a local collection with the same name.
+    /// Embeddings are reused only when they came from the same embedding model and dimension;
+    /// otherwise the documents are queued for embedding like fresh uploads.
+    pub async fn import_collection(&self, export: RagCollectionExport) -> Result<RagCollectionImport> {
+        use base64::Engine;
+        
+        let embedding_config = self.settings.lock().unwrap().embedding_config.clone();
+        let embeddings_reused = export.embedding_model == embedding_config.active_model()
+            && export.embedding_dimension == embedding_config.embedding_dimension;
+        if !embeddings_reused {
+            println!("Embedding model mismatch ({} / {} dims locally vs {} / {} dims in the export); documents will be re-embedded",
+                     embedding_config.active_model(), embedding_config.embedding_dimension,
+                     export.embedding_model, export.embedding_dimension);
+        }
+        
+        let collection = match self.find_collection(&export.name) {
+            Ok(collection) => collection,
+            Err(_) => self.create_collection(&export.name, export.description.clone())?,
+        };
+        
+        let mut collection_document_ids = Vec::new();
+        let mut documents_imported = 0;
+        let mut documents_already_present = 0;
+        let mut chunks_imported = 0;
+        
+        for exported in export.documents {
+            if let Some(hash) = &exported.content_hash {
+                if let Some(existing) = self.check_duplicate(hash)? {
+                    collection_document_ids.push(existing.id);
+                    documents_already_present += 1;
+                    continue;
+                }
+            }
+            
+            let embeddings: Vec<Option<Vec<f32>>> = exported.chunks
+                .iter()
+                .map(|chunk| {
+                    chunk.embedding.as_ref()
+                        .filter(|_| embeddings_reused)
+                        .and_then(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded).ok())
+                        .map(|bytes| Self::decode_embedding(&bytes))
+                        .filter(|embedding| embedding.len() == embedding_config.embedding_dimension)
+                })
+                .collect();
+            let fully_embedded = !exported.chunks.is_empty() && embeddings.iter().all(|e| e.is_some());
+            
+            let doc_id = Uuid::new_v4().to_string();
+            let now = Utc::now().to_rfc3339();
+            let document = EnhancedDocument {
+                id: doc_id.clone(),
+                file_name: exported.file_name,
+                // The original file stays on the exporting machine; search only needs the text
+                file_path: String::new(),
+                file_type: exported.file_type,
+                file_size: exported.file_size,
+                content: exported.content,
+                created_at: exported.created_at,
+                updated_at: now.clone(),
+                access_count: 0,
+                last_accessed: None,
+                is_cached: fully_embedded,
+                embedding_status: if fully_embedded { "completed" } else { "pending" }.to_string(),
+                chunk_count: exported.chunks.len() as i32,
+                metadata: exported.metadata,
+                content_hash: exported.content_hash,
+            };
+            self.save_document_to_db(&document)?;
+            
+            let conn = Connection::open(&self.db_path)?;
+            let mut stored_chunks = Vec::new();
+            for (chunk, embedding) in exported.chunks.into_iter().zip(embeddings) {
+                let chunk_id = Uuid::new_v4().to_string();
+                let embedding_bytes = embedding.as_ref().filter(|_| fully_embedded).map(|embedding| {
+                    embedding.iter().flat_map(|&f| f.to_le_bytes().to_vec()).collect::<Vec<u8>>()
+                });
+                conn.execute(
+                    "INSERT INTO enhanced_document_chunks (
+                        id, document_id, chunk_index, content, start_char, end_char,
+                        token_count, embedding, metadata, created_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        chunk_id, doc_id, chunk.chunk_index, chunk.content, chunk.start_char,
+                        chunk.end_char, chunk.token_count, embedding_bytes, chunk.metadata, now,
+                    ],
+                )?;
+                stored_chunks.push((EnhancedDocumentChunk {
+                    id: chunk_id,
+                    document_id: doc_id.clone(),
+                    chunk_index: chunk.chunk_index,
+                    content: chunk.content,
+                    start_char: chunk.start_char,
+                    end_char: chunk.end_char,
+                    token_count: chunk.token_count,
+                    embedding: None,
+                    similarity_score: None,
+                    bm25_score: None,
+                    metadata: chunk.metadata,
+                }, embedding));
+            }
+            drop(conn);
+            chunks_imported += stored_chunks.len();
+            
+            if fully_embedded {
+                let (chunks, embeddings): (Vec<_>, Vec<_>) = stored_chunks
+                    .into_iter()
+                    .map(|(chunk, embedding)| (chunk, embedding.unwrap_or_default()))
+                    .unzip();
+                self.save_document_centroid(&doc_id, &embeddings)?;
+                self.index_chunks_for_search(&doc_id, &chunks, &embeddings).await?;
+            } else {
+                self.queue_embedding_generation(&doc_id).await?;
+            }
+            
+            collection_document_ids.push(doc_id);
+            documents_imported += 1;
+        }
+        
+        self.assign_documents_to_collection(&collection.id, &collection_document_ids)?;
+        let collection = self.find_collection(&collection.id)?;
+        println!("Imported {} documents ({} already present) into collection {}",
+                 documents_imported, documents_already_present, collection.name);
+        
+        Ok(RagCollectionImport {
+            collection,
+            documents_imported,
+            documents_already_present,
+            chunks_imported,
+            embeddings_reused,
+        })
+    }
+    
+    pub fn set_chat_collection_scope(&self, chat_id: &str, collection_ids: &[String]) -> Result<()> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute("DELETE FROM chat_collection_scopes WHERE chat_id = ?1", params![chat_id])?;
+        
+        for collection_id in collection_ids {
+            conn.execute(
+                "INSERT OR IGNORE INTO chat_collection_scopes (chat_id, collection_id) VALUES (?1, ?2)",
+                params![chat_id, collection_id],
+            )?;
+        }
+        
+        Ok(())
+    }
+    
+    pub fn get_chat_collection_scope(&self, chat_id: &str) -> Result<Vec<String>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare("SELECT collection_id FROM chat_collection_scopes WHERE chat_id = ?1")?;
+        let ids = stmt.query_map([chat_id], |row| row.get::<_, String>(0))?;
+        Ok(ids.collect::<Result<Vec<_>, _>>()?)
+    }
+    
+    pub async fn advanced_search(&self, search: &AdvancedSearchQuery) -> Result<Vec<EnhancedDocumentChunk>> {
+        // An explicit collections filter wins; otherwise fall back to the chat's saved scope
+        let collections = match (&search.collections, &search.chat_id) {
+            (Some(collections), _) if !collections.is_empty() => collections.clone(),
+            (_, Some(chat_id)) => self.get_chat_collection_scope(chat_id)?,
+            _ => Vec::new(),
+        };
+        
+        let mut scope: Vec<String> = search.document_ids.clone().unwrap_or_default();
+        if !collections.is_empty() {
+            let collection_docs = self.get_collection_document_ids(&collections)?;
+            scope = if scope.is_empty() {
+                collection_docs
+            } else {
+                scope.into_iter().filter(|id| collection_docs.contains(id)).collect()
+            };
+            
+            // A scope that resolves to nothing must not silently widen to every document
+            if scope.is_empty() {
+                return Ok(Vec::new());
+            }
+        }
+        
+        if let Some(tags) = search.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            let tagged = self.document_ids_with_tags(tags)?;
+            scope = if scope.is_empty() {
+                tagged
+            } else {
+                scope.into_iter().filter(|id| tagged.contains(id)).collect()
+            };
+            if scope.is_empty() {
+                return Ok(Vec::new());
+            }
+        }
+        
+        let mut results = self.search_documents(&search.query, scope).await?;
+        if let Some(boost_tags) = search.boost_tags.as_ref().filter(|tags| !tags.is_empty()) {
+            let boosted = self.document_ids_with_tags(boost_tags)?;
+            for chunk in results.iter_mut().filter(|chunk| boosted.contains(&chunk.document_id)) {
+                chunk.similarity_score = chunk.similarity_score.map(|score| score * TAG_BOOST);
+            }
+            results.sort_by(|a, b| {
+                b.similarity_score.unwrap_or(0.0).partial_cmp(&a.similarity_score.unwrap_or(0.0)).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+        if let Some(limit) = search.limit {
+            results.truncate(limit);
+        }
+        Ok(results)
+    }
+    
+    // Document tags
+    
+    pub fn get_tag_taxonomy(&self) -> Result<Vec<String>> {
+        let conn = Connection::open(&self.db_path)?;
+        let taxonomy: Option<String> = conn.query_row(
+            "SELECT value FROM enhanced_user_settings WHERE key = 'document_tag_taxonomy'",
+            [],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(taxonomy.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+    }
+    
+    pub fn set_tag_taxonomy(&self, taxonomy: &[String]) -> Result<()> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO enhanced_user_settings (key, value, updated_at)
+             VALUES ('document_tag_taxonomy', ?1, ?2)",
+            params![serde_json::to_string(taxonomy)?, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+    
+    fn document_metadata(&self, conn: &Connection, document_id: &str) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let metadata: Option<String> = conn.query_row(
+            "SELECT metadata FROM enhanced_documents WHERE id = ?1",
+            [document_id],
+            |row| row.get(0),
+        ).optional()?
+            .ok_or_else(|| anyhow!("Document not found: {}", document_id))?;
+        Ok(metadata.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+    }
+    
+    fn tags_from_metadata(metadata: &serde_json::Map<String, serde_json::Value>) -> Option<DocumentTags> {
+        let tags = serde_json::from_value(metadata.get("tags")?.clone()).ok()?;
+        let source = metadata.get("tags_source").and_then(|s| s.as_str()).unwrap_or("auto").to_string();
+        Some(DocumentTags { tags, source })
+    }
+    
+    pub fn get_document_tags(&self, document_id: &str) -> Result<Option<DocumentTags>> {
+        let conn = Connection::open(&self.db_path)?;
+        Ok(Self::tags_from_metadata(&self.document_metadata(&conn, document_id)?))
+    }
+    
+    /// Store tags in the document's metadata JSON, keeping any other metadata keys
+    pub fn set_document_tags(&self, document_id: &str, tags: &DocumentTags) -> Result<()> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut metadata = self.document_metadata(&conn, document_id)?;
+        metadata.insert("tags".to_string(), serde_json::json!(tags.tags));
+        metadata.insert("tags_source".to_string(), serde_json::json!(tags.source));
+        conn.execute(
+            "UPDATE enhanced_documents SET metadata = ?1 WHERE id = ?2",
+            params![serde_json::Value::Object(metadata).to_string(), document_id],
+        )?;
+        Ok(())
+    }
+    
+    pub fn get_document_text(&self, document_id: &str) -> Result<(String, String)> {
+        let conn = Connection::open(&self.db_path)?;
+        conn.query_row(
+            "SELECT file_name, content FROM enhanced_documents WHERE id = ?1",
+            [document_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?
+            .ok_or_else(|| anyhow!("Document not found: {}", document_id))
+    }
+    
+    fn all_document_tags(&self) -> Result<Vec<(String, Option<DocumentTags>)>> {
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare("SELECT id, metadata FROM enhanced_documents")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?)))?;
+        let mut documents = Vec::new();
+        for row in rows {
+            let (id, metadata) = row?;
+            let metadata: serde_json::Map<String, serde_json::Value> = metadata
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            documents.push((id, Self::tags_from_metadata(&metadata)));
+        }
+        Ok(documents)
+    }
+    
+    /// Documents carrying any of the tags, compared case-insensitively
+    pub fn document_ids_with_tags(&self, tags: &[String]) -> Result<Vec<String>> {
+        Ok(self.all_document_tags()?
+            .into_iter()
+            .filter(|(_, document_tags)| {
+                document_tags.as_ref().is_some_and(|document_tags| {
+                    document_tags.tags.iter().any(|tag| tags.iter().any(|wanted| wanted.eq_ignore_ascii_case(tag)))
+                })
+            })
+            .map(|(id, _)| id)
+            .collect())
+    }
+    
+    /// Untagged documents, plus automatically tagged ones when `retag` is set
+    pub fn documents_to_tag(&self, retag: bool) -> Result<Vec<String>> {
+        Ok(self.all_document_tags()?
+            .into_iter()
+            .filter(|(_, tags)| match tags {
+                None => true,
+                Some(tags) => retag && tags.source != "manual",
+            })
+            .map(|(id, _)| id)
+            .collect())
+    }
+    
+    /// Classify the document in the background once the user is idle
+    pub fn queue_tagging(&self, document_id: &str) {
+        let system_clone = self.clone();
+        let document_id = document_id.to_string();
+        tokio::spawn(async move {
+            crate::presence::wait_for_background_slot().await;
+            if let Err(e) = crate::document_tagging::tag_document(&system_clone, &document_id).await {
+                eprintln!("Failed to tag document {}: {}", document_id, e);
+            }
+        });
+    }
+    
+    // In-memory documents
+    
+    /// Extract and chunk a file the same way uploads are, without storing or indexing anything
+    pub async fn chunk_file_in_memory(&self, file_content: &[u8], file_type: &str) -> Result<Vec<TextChunk>> {
+        let max_size_mb = self.settings.lock().unwrap().max_document_size_mb;
+        let file_size_mb = file_content.len() as f64 / (1024.0 * 1024.0);
+        if file_size_mb > max_size_mb {
+            return Err(anyhow!("File size {:.2}MB exceeds limit of {:.2}MB", file_size_mb, max_size_mb));
+        }
+        let content = clean_text(&self.extract_text_content(file_content, file_type).await?);
+        let chunking_service = self.chunking_service.lock().unwrap();
+        chunking_service.chunk_text(&content)
+    }
+    
+    /// Relevance of each chunk to the query: embedding similarity when the model is loaded,
+    /// query word overlap otherwise
+    pub async fn score_chunks_in_memory(&self, query: &str, chunks: &[TextChunk]) -> Vec<f32> {
+        if self.embedding_service.is_initialized() {
+            let texts = chunks.iter().map(|chunk| chunk.content.clone()).collect();
+            match (self.embed_query(query).await, self.embed_texts(texts).await) {
+                (Ok(query_embedding), Ok(embeddings)) => {
+                    return embeddings.iter().map(|embedding| cosine_similarity(&query_embedding, embedding)).collect();
+                }
+                (Err(e), _) | (_, Err(e)) => eprintln!("In-memory embedding failed, using word overlap: {}", e),
+            }
+        }
+        chunks.iter().map(|chunk| context_packing::lexical_relevance(query, &chunk.content)).collect()
+    }
+    
+    /// Score every chunk of the pinned documents against the query and pack the best mix into the
+    /// token budget. Chunks without embeddings fall back to query word overlap.
+    pub async fn pack_context(&self, query: &str, document_ids: &[String], constraints: &PackingConstraints) -> Result<ContextPacking> {
+        self.update_document_access(document_ids)?;
+        let query_embedding = if self.embedding_service.is_initialized() {
+            self.embed_query(query).await.ok()
+        } else {
+            None
+        };
+        
+        let unavailable = self.unavailable_document_ids()?;
+        let conn = Connection::open(&self.db_path)?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.document_id, d.file_name, c.chunk_index, c.content, c.token_count, c.embedding
+             FROM enhanced_document_chunks c
+             JOIN enhanced_documents d ON d.id = c.document_id
+             WHERE c.document_id = ?1"
+        )?;
+        
+        let mut candidates = Vec::new();
+        for document_id in document_ids.iter().filter(|id| !unavailable.contains(*id)) {
+            let rows = stmt.query_map([document_id], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i32>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, i32>(5)?,
+                    row.get::<_, Option<Vec<u8>>>(6)?,
+                ))
+            })?;
+            for row in rows {
+                let (chunk_id, document_id, file_name, chunk_index, content, token_count, embedding) = row?;
+                let embedding = embedding.map(|bytes| Self::decode_embedding(&bytes));
+                let relevance = match (&query_embedding, &embedding) {
+                    (Some(query), Some(chunk)) => cosine_similarity(query, chunk).max(0.0),
+                    _ => context_packing::lexical_relevance(query, &content),
+                };
+                candidates.push(PackingCandidate {
+                    chunk_id,
+                    document_id,
+                    file_name,
+                    chunk_index,
+                    content,
+                    tokens: token_count.max(1) as usize,
+                    relevance,
+                    embedding,
+                });
+            }
+        }
+        
+        Ok(context_packing::pack(candidates, constraints))
+    }
+    
+    /// Nearest neighbours of a document by cosine similarity of centroid embeddings
+    pub fn get_related_documents(&self, document_id: &str, limit: usize) -> Result<Vec<RelatedDocument>> {
+        self.backfill_document_centroids()?;
+        
+        let conn = Connection::open(&self.db_path)?;
+        let target: Vec<u8> = conn.query_row(
+            "SELECT centroid FROM document_embeddings WHERE document_id = ?1",
+            [document_id],
+            |row| row.get(0),
+        ).optional()?
+            .ok_or_else(|| anyhow!("Document {} has no embeddings yet", document_id))?;
+        let target = Self::decode_embedding(&target);
+        
+        let mut stmt = conn.prepare(
+            "SELECT e.document_id, d.file_name, e.centroid
+             FROM document_embeddings e
+             JOIN enhanced_documents d ON d.id = e.document_id
+             WHERE e.document_id != ?1"
+        )?;
+        let candidates = stmt.query_map([document_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Vec<u8>>(2)?))
+        })?;
+        
+        let mut related = Vec::new();
+        for candidate in candidates {
+            let (other_id, file_name, centroid) = candidate?;
+            let similarity = cosine_similarity(&target, &Self::decode_embedding(&centroid));
+            let relationship = if similarity >= NEAR_DUPLICATE_THRESHOLD {
+                "near_duplicate"
+            } else if similarity >= SAME_TOPIC_THRESHOLD {
+                "same_topic"
+            } else {
+                "related"
+            };
+            
+            related.push(RelatedDocument {
+                document_id: other_id,
+                file_name,
+                similarity,
+                relationship: relationship.to_string(),
+            });
+        }
+        
+        related.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+        related.truncate(limit);
+        Ok(related)
+    }
+    
+    /// Full scoring breakdown for one document, for tuning search weights when results look wrong
+    pub async fn explain_search(&self, query: &str, document_id: &str) -> Result<SearchExplanation> {
+        let conn = Connection::open(&self.db_path)?;
+        let file_name: Option<String> = conn.query_row(
+            "SELECT file_name FROM enhanced_documents WHERE id = ?1",
+            [document_id],
+            |row| row.get(0),
+        ).optional()?;
+        
+        let query_embedding = if self.embedding_service.is_initialized() {
+            self.embed_query(query).await.ok()
+        } else {
+            None
+        };
+        
+        let chunks = self.search_service.explain_document(query, document_id, query_embedding.as_deref(), 20)?;
+        
+        // Where the document lands in the same search the chat would run
+        let results = self.search_documents(query, Vec::new()).await?;
+        let result_rank = results.iter().position(|chunk| chunk.document_id == document_id);
+        
+        Ok(SearchExplanation {
+            query: query.to_string(),
+            document_id: document_id.to_string(),
+            file_name,
+            query_embedding_available: query_embedding.is_some(),
+            reranking_enabled: self.get_settings().reranking_enabled,
+            rerank_delta: 0.0,
+            result_rank,
+            chunks,
+        })
+    }
 }
\ No newline at end of file
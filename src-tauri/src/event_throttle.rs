@@ -0,0 +1,207 @@
+// src-tauri/src/event_throttle.rs
+// Rate limiting for high-frequency events so fast token streams and live captions can't flood the
+// webview. Each channel gets a minimum interval between emits; what arrives in between is either
+// coalesced into one event (stream chunks, whose text is appended) or replaced by the newest event
+// (full-state snapshots like caption frames). A trailing flush makes sure the last state always
+// arrives. Critical events (complete, error, cancel, ...) are never held back: they flush whatever
+// is pending on their channel first, so ordering is preserved.
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Policy {
+    Coalesce, // Merge consecutive chunk events, appending their "text"
+    Latest,   // Keep only the newest event; older ones are dropped
+    Always,   // Not throttled
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ChannelMetrics {
+    pub emitted: u64,
+    pub coalesced: u64,
+    pub dropped: u64,
+    pub critical: u64,
+}
+
+struct ChannelState {
+    last_emit: Option<Instant>,
+    pending: Option<Value>,
+    flush_scheduled: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref CHANNELS: Mutex<HashMap<String, ChannelState>> = Mutex::new(HashMap::new());
+    static ref METRICS: Mutex<HashMap<String, ChannelMetrics>> = Mutex::new(HashMap::new());
+}
+
+// Per-session channels are reported together under their family name
+fn family(event: &str) -> &str {
+    if event.starts_with("ollama-stream-") {
+        "ollama-stream"
+    } else {
+        event
+    }
+}
+
+fn policy_for(event: &str) -> (Policy, Duration) {
+    match family(event) {
+        "ollama-stream" => (Policy::Coalesce, Duration::from_millis(33)),
+        "caption-frame" => (Policy::Latest, Duration::from_millis(50)),
+        "embedding-job-progress" => (Policy::Latest, Duration::from_millis(250)),
+        _ => (Policy::Always, Duration::ZERO),
+    }
+}
+
+fn record(event: &str, update: impl FnOnce(&mut ChannelMetrics)) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        update(metrics.entry(family(event).to_string()).or_default());
+    }
+}
+
+/// Fold a newer chunk event into a pending one: text is appended, every other field takes the newer value.
+fn merge_chunk(pending: &mut Value, next: Value) {
+    let (Some(pending_fields), Value::Object(next_fields)) = (pending.as_object_mut(), next) else {
+        return;
+    };
+    for (key, value) in next_fields {
+        match (key.as_str(), pending_fields.get_mut(&key), &value) {
+            ("text", Some(Value::String(text)), Value::String(more)) => text.push_str(more),
+            _ => {
+                pending_fields.insert(key, value);
+            }
+        }
+    }
+}
+
+fn emit_now(app_handle: &AppHandle, event: &str, state: &mut ChannelState, payload: Value) -> tauri::Result<()> {
+    state.last_emit = Some(Instant::now());
+    record(event, |m| m.emitted += 1);
+    app_handle.emit(event, payload)
+}
+
+fn flush_pending(app_handle: &AppHandle, event: &str) {
+    let Ok(mut channels) = CHANNELS.lock() else {
+        return;
+    };
+    let Some(state) = channels.get_mut(event) else {
+        return;
+    };
+    state.flush_scheduled = false;
+    if let Some(payload) = state.pending.take() {
+        if let Err(e) = emit_now(app_handle, event, state, payload) {
+            eprintln!("Failed to emit {}: {}", event, e);
+        }
+    }
+}
+
+/// Emit a high-frequency event, subject to its channel's rate limit.
+pub fn emit_throttled<S: Serialize>(app_handle: &AppHandle, event: &str, payload: S) {
+    let (policy, interval) = policy_for(event);
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            eprintln!("Failed to serialize {}: {}", event, e);
+            return;
+        }
+    };
+    // Screen-reader announcements follow every chunk, not just the ones that survive throttling
+    if crate::accessibility::wants(event) {
+        crate::accessibility::observe_stream_event(app_handle, event, &payload);
+    }
+    if policy == Policy::Always {
+        record(event, |m| m.emitted += 1);
+        if let Err(e) = app_handle.emit(event, payload) {
+            eprintln!("Failed to emit {}: {}", event, e);
+        }
+        return;
+    }
+
+    let Ok(mut channels) = CHANNELS.lock() else {
+        return;
+    };
+    let state = channels.entry(event.to_string()).or_insert(ChannelState {
+        last_emit: None,
+        pending: None,
+        flush_scheduled: false,
+    });
+
+    let elapsed = state.last_emit.map(|at| at.elapsed());
+    if state.pending.is_none() && elapsed.map_or(true, |elapsed| elapsed >= interval) {
+        if let Err(e) = emit_now(app_handle, event, state, payload) {
+            eprintln!("Failed to emit {}: {}", event, e);
+        }
+        return;
+    }
+
+    match (policy, state.pending.as_mut()) {
+        (Policy::Coalesce, Some(pending)) => {
+            merge_chunk(pending, payload);
+            record(event, |m| m.coalesced += 1);
+        }
+        (_, Some(pending)) => {
+            *pending = payload;
+            record(event, |m| m.dropped += 1);
+        }
+        (_, None) => state.pending = Some(payload),
+    }
+
+    if !state.flush_scheduled {
+        state.flush_scheduled = true;
+        let wait = interval.saturating_sub(elapsed.unwrap_or(interval));
+        let app_handle = app_handle.clone();
+        let event = event.to_string();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(wait).await;
+            flush_pending(&app_handle, &event);
+        });
+    }
+}
+
+/// Emit an event that must not be delayed or dropped. Anything pending on the channel goes out first.
+pub fn emit_critical<S: Serialize>(app_handle: &AppHandle, event: &str, payload: S) -> tauri::Result<()> {
+    record(event, |m| m.critical += 1);
+    if crate::accessibility::wants(event) {
+        if let Ok(value) = serde_json::to_value(&payload) {
+            crate::accessibility::observe_stream_event(app_handle, event, &value);
+        }
+    }
+    let Ok(mut channels) = CHANNELS.lock() else {
+        return app_handle.emit(event, payload);
+    };
+    // Finished streams don't come back, so their channel state goes away with the flush
+    if let Some(mut state) = channels.remove(event) {
+        if let Some(pending) = state.pending.take() {
+            if let Err(e) = emit_now(app_handle, event, &mut state, pending) {
+                eprintln!("Failed to emit {}: {}", event, e);
+            }
+        }
+    }
+    app_handle.emit(event, payload)
+}
+
+#[tauri::command]
+pub async fn get_event_metrics() -> Result<HashMap<String, ChannelMetrics>, String> {
+    METRICS.lock().map(|metrics| metrics.clone()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reset_event_metrics() -> Result<(), String> {
+    METRICS.lock().map(|mut metrics| metrics.clear()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_chunk_appends_text_and_keeps_latest_fields() {
+        let mut pending = serde_json::json!({ "type": "chunk", "text": "Hel", "done": false, "chunk_count": 1 });
+        merge_chunk(&mut pending, serde_json::json!({ "type": "chunk", "text": "lo", "done": false, "chunk_count": 2 }));
+        merge_chunk(&mut pending, serde_json::json!({ "type": "chunk", "text": "!", "done": true, "chunk_count": 3 }));
+        assert_eq!(pending, serde_json::json!({ "type": "chunk", "text": "Hello!", "done": true, "chunk_count": 3 }));
+    }
+}
@@ -0,0 +1,175 @@
+// src-tauri/src/file_qa.rs
+// One-off questions about a file that isn't in the document index. The file is parsed, chunked and
+// ranked in memory, the answer streams like any agent response, and nothing is stored unless the
+// user promotes the file into the index afterwards.
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::enhanced_rag_commands::{get_rag_system, EnhancedRagSystemState};
+use crate::enhanced_rag_system::EnhancedDocument;
+
+const CITED_EXCERPTS: usize = 5;
+const EXCERPT_PREVIEW_CHARS: usize = 240;
+
+const FILE_QA_SYSTEM_PROMPT: &str = "You answer questions about a single file using only the numbered excerpts you are given. \
+Cite the excerpts you rely on inline like [1] or [2][3]. If the excerpts don't contain the answer, say so instead of guessing.";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileCitation {
+    pub index: usize, // The [n] label used in the answer
+    pub chunk_index: usize,
+    pub start_char: usize,
+    pub end_char: usize,
+    pub excerpt: String,
+    pub relevance: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileAnswer {
+    pub file_name: String,
+    pub answer: String,
+    pub citations: Vec<FileCitation>,
+    pub cited: Vec<usize>, // Citation labels the answer actually used
+}
+
+/// MIME type from the extension, matching what uploads from the webview arrive with.
+pub(crate) fn file_type_for_path(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("md") | Some("markdown") => "text/markdown",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => "text/plain",
+    }
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string_lossy().into_owned())
+}
+
+/// Labels like [2] in the answer that refer to one of the given citations, in first-use order.
+pub(crate) fn cited_labels(answer: &str, citation_count: usize) -> Vec<usize> {
+    let mut cited = Vec::new();
+    for part in answer.split('[').skip(1) {
+        let Some((label, _)) = part.split_once(']') else {
+            continue;
+        };
+        if let Ok(index) = label.trim().parse::<usize>() {
+            if (1..=citation_count).contains(&index) && !cited.contains(&index) {
+                cited.push(index);
+            }
+        }
+    }
+    cited
+}
+
+/// Answer a question about a file without indexing it. Citations are emitted on
+/// `file-qa-citations-{session_id}` before the answer streams on the usual `ollama-stream-{session_id}`.
+#[tauri::command]
+pub async fn ask_about_file(
+    app_handle: AppHandle,
+    state: State<'_, EnhancedRagSystemState>,
+    path: String,
+    question: String,
+    session_id: String,
+) -> Result<FileAnswer, String> {
+    if question.trim().is_empty() {
+        return Err("Question is empty".to_string());
+    }
+    let system = get_rag_system(&state)?;
+    let path = Path::new(&path);
+    let file_name = file_name_of(path);
+    let file_content = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", file_name, e))?;
+
+    let chunks = system
+        .chunk_file_in_memory(&file_content, file_type_for_path(path))
+        .await
+        .map_err(|e| format!("Failed to parse {}: {}", file_name, e))?;
+    if chunks.is_empty() {
+        return Err(format!("No text could be extracted from {}", file_name));
+    }
+
+    let scores = system.score_chunks_in_memory(&question, &chunks).await;
+    let mut ranked: Vec<usize> = (0..chunks.len()).collect();
+    ranked.sort_by(|a, b| scores[*b].partial_cmp(&scores[*a]).unwrap_or(std::cmp::Ordering::Equal));
+    ranked.truncate(CITED_EXCERPTS);
+    // Present excerpts in reading order so the model sees them in context
+    ranked.sort_by_key(|&i| chunks[i].chunk_index);
+
+    let citations: Vec<FileCitation> = ranked
+        .iter()
+        .enumerate()
+        .map(|(position, &i)| FileCitation {
+            index: position + 1,
+            chunk_index: chunks[i].chunk_index,
+            start_char: chunks[i].start_char,
+            end_char: chunks[i].end_char,
+            excerpt: chunks[i].content.chars().take(EXCERPT_PREVIEW_CHARS).collect(),
+            relevance: scores[i],
+        })
+        .collect();
+    if let Err(e) = app_handle.emit(&format!("file-qa-citations-{}", session_id), &citations) {
+        eprintln!("Failed to emit file citations: {}", e);
+    }
+
+    let excerpts: Vec<String> = ranked
+        .iter()
+        .enumerate()
+        .map(|(position, &i)| format!("[{}] {}", position + 1, chunks[i].content.trim()))
+        .collect();
+    let prompt = format!(
+        "Excerpts from \"{}\":\n\n{}\n\nQuestion: {}",
+        file_name, excerpts.join("\n\n"), question.trim()
+    );
+
+    let model = crate::agent_models::resolve_agent_model(&app_handle, "enteract").await?;
+    println!("📄 Answering a question about {} from {} excerpts", file_name, citations.len());
+    let answer = crate::ollama::generate_agent_response_stream(
+        app_handle.clone(),
+        model,
+        prompt,
+        FILE_QA_SYSTEM_PROMPT.to_string(),
+        None,
+        session_id,
+        "enteract".to_string(),
+        None,
+    ).await?;
+
+    Ok(FileAnswer {
+        file_name,
+        cited: cited_labels(&answer, citations.len()),
+        answer,
+        citations,
+    })
+}
+
+/// Add a file that was asked about into the persistent index, tracking it for changes.
+#[tauri::command]
+pub async fn promote_file_to_index(
+    state: State<'_, EnhancedRagSystemState>,
+    path: String,
+) -> Result<EnhancedDocument, String> {
+    let system = get_rag_system(&state)?;
+    let source = Path::new(&path);
+    let file_content = std::fs::read(source).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    let document = system
+        .upload_document(file_name_of(source), file_content, file_type_for_path(source).to_string())
+        .await
+        .map_err(|e| e.to_string())?;
+    system.track_document_source(&document.id, &path).map_err(|e| e.to_string())?;
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cited_labels_ignore_unknown_and_repeats() {
+        let answer = "Revenue grew [2]. Costs fell [1][2], see also [7] and [note].";
+        assert_eq!(cited_labels(answer, 3), vec![2, 1]);
+    }
+}
@@ -0,0 +1,200 @@
+// src-tauri/src/follow_window.rs
+// Keeps the assistant overlay docked to whichever application window currently has focus.
+use crate::window_manager::{get_foreground_window_info, ForegroundWindowInfo};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DockCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowWindowConfig {
+    pub window_label: String,
+    pub corner: DockCorner,
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub debounce_ms: u64,
+    pub poll_interval_ms: u64,
+}
+
+impl Default for FollowWindowConfig {
+    fn default() -> Self {
+        Self {
+            window_label: "main".to_string(),
+            corner: DockCorner::TopRight,
+            offset_x: -16,
+            offset_y: 16,
+            debounce_ms: 300,
+            poll_interval_ms: 150,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FollowWindowStatus {
+    pub enabled: bool,
+    pub config: Option<FollowWindowConfig>,
+    pub following: Option<ForegroundWindowInfo>,
+}
+
+#[derive(Default)]
+struct FollowWindowState {
+    status: FollowWindowStatus,
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+lazy_static::lazy_static! {
+    static ref FOLLOW_STATE: Arc<Mutex<FollowWindowState>> = Arc::new(Mutex::new(FollowWindowState::default()));
+}
+
+// Compute where the overlay should go, clamped to the monitor that holds the target's centre
+fn docked_position(
+    app_handle: &AppHandle,
+    target: &ForegroundWindowInfo,
+    overlay_size: (u32, u32),
+    config: &FollowWindowConfig,
+) -> (i32, i32) {
+    let (overlay_w, overlay_h) = (overlay_size.0 as i32, overlay_size.1 as i32);
+    let target_right = target.x + target.width as i32;
+    let target_bottom = target.y + target.height as i32;
+
+    let (mut x, mut y) = match config.corner {
+        DockCorner::TopLeft => (target.x + config.offset_x, target.y + config.offset_y),
+        DockCorner::TopRight => (target_right - overlay_w + config.offset_x, target.y + config.offset_y),
+        DockCorner::BottomLeft => (target.x + config.offset_x, target_bottom - overlay_h + config.offset_y),
+        DockCorner::BottomRight => (target_right - overlay_w + config.offset_x, target_bottom - overlay_h + config.offset_y),
+    };
+
+    let center = (target.x + target.width as i32 / 2, target.y + target.height as i32 / 2);
+    let monitors = app_handle.available_monitors().unwrap_or_default();
+    let monitor = monitors.iter().find(|m| {
+        let pos = m.position();
+        let size = m.size();
+        center.0 >= pos.x
+            && center.0 < pos.x + size.width as i32
+            && center.1 >= pos.y
+            && center.1 < pos.y + size.height as i32
+    });
+
+    if let Some(monitor) = monitor {
+        let pos = monitor.position();
+        let size = monitor.size();
+        x = x.clamp(pos.x, (pos.x + size.width as i32 - overlay_w).max(pos.x));
+        y = y.clamp(pos.y, (pos.y + size.height as i32 - overlay_h).max(pos.y));
+    }
+
+    (x, y)
+}
+
+async fn follow_loop(app_handle: AppHandle, config: FollowWindowConfig) {
+    let own_pid = std::process::id();
+    let debounce = Duration::from_millis(config.debounce_ms);
+    let mut pending: Option<(ForegroundWindowInfo, Instant)> = None;
+    let mut applied: Option<ForegroundWindowInfo> = None;
+
+    loop {
+        tokio::time::sleep(Duration::from_millis(config.poll_interval_ms)).await;
+
+        // The lookup runs external commands on Linux and macOS, so it stays off the async workers
+        let Ok(foreground) = tauri::async_runtime::spawn_blocking(get_foreground_window_info).await else {
+            continue;
+        };
+        // Ignore our own windows so focusing the overlay doesn't make it chase itself
+        let current = match foreground {
+            Some(info) if info.process_id != own_pid && info.width > 0 && info.height > 0 => info,
+            _ => continue,
+        };
+
+        if applied.as_ref() == Some(&current) {
+            pending = None;
+            continue;
+        }
+
+        match &pending {
+            Some((candidate, since)) if *candidate == current => {
+                if since.elapsed() < debounce {
+                    continue;
+                }
+            }
+            _ => {
+                pending = Some((current, Instant::now()));
+                continue;
+            }
+        }
+
+        let Some(window) = app_handle.get_webview_window(&config.window_label) else {
+            continue;
+        };
+        let Ok(size) = window.outer_size() else {
+            continue;
+        };
+
+        let (x, y) = docked_position(&app_handle, &current, (size.width, size.height), &config);
+        if let Err(e) = window.set_position(PhysicalPosition::new(x, y)) {
+            eprintln!("Failed to follow active window: {}", e);
+            continue;
+        }
+
+        if let Ok(mut state) = FOLLOW_STATE.lock() {
+            state.status.following = Some(current.clone());
+        }
+        let _ = app_handle.emit("follow-window-moved", serde_json::json!({
+            "target": &current,
+            "x": x,
+            "y": y
+        }));
+
+        applied = Some(current);
+        pending = None;
+    }
+}
+
+#[tauri::command]
+pub async fn start_follow_active_window(
+    app_handle: AppHandle,
+    config: Option<FollowWindowConfig>,
+) -> Result<FollowWindowStatus, String> {
+    let config = config.unwrap_or_default();
+    crate::window_manager::foreground_window_backend()
+        .map_err(|e| format!("Following the active window isn't supported here: {}", e))?;
+    let mut state = FOLLOW_STATE.lock().map_err(|e| e.to_string())?;
+
+    // Restarting applies new offsets/corner immediately
+    if let Some(task) = state.task.take() {
+        task.abort();
+    }
+
+    state.task = Some(tauri::async_runtime::spawn(follow_loop(app_handle, config.clone())));
+    state.status = FollowWindowStatus {
+        enabled: true,
+        config: Some(config),
+        following: None,
+    };
+
+    println!("🧲 Follow-active-window mode enabled");
+    Ok(state.status.clone())
+}
+
+#[tauri::command]
+pub async fn stop_follow_active_window() -> Result<(), String> {
+    let mut state = FOLLOW_STATE.lock().map_err(|e| e.to_string())?;
+    if let Some(task) = state.task.take() {
+        task.abort();
+    }
+    state.status = FollowWindowStatus::default();
+    println!("🧲 Follow-active-window mode disabled");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_follow_window_status() -> Result<FollowWindowStatus, String> {
+    let state = FOLLOW_STATE.lock().map_err(|e| e.to_string())?;
+    Ok(state.status.clone())
+}
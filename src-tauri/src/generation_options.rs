@@ -0,0 +1,272 @@
+// src-tauri/src/generation_options.rs
+// Sampling/generation parameters for Ollama requests. Each agent has built-in defaults that can be
+// overridden per agent in settings and again per request; num_ctx is sized from the prompt when unset.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GenerationOptions {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub top_k: Option<u32>,
+    pub repeat_penalty: Option<f32>,
+    pub num_predict: Option<i32>,
+    pub num_ctx: Option<u32>, // None sizes the context window from the prompt
+    pub seed: Option<i64>,
+    pub stop: Option<Vec<String>>,
+}
+
+impl GenerationOptions {
+    /// Fields set on `overrides` win over the ones set here.
+    pub fn overlay(self, overrides: &GenerationOptions) -> GenerationOptions {
+        GenerationOptions {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            top_k: overrides.top_k.or(self.top_k),
+            repeat_penalty: overrides.repeat_penalty.or(self.repeat_penalty),
+            num_predict: overrides.num_predict.or(self.num_predict),
+            num_ctx: overrides.num_ctx.or(self.num_ctx),
+            seed: overrides.seed.or(self.seed),
+            stop: overrides.stop.clone().or(self.stop),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                return Err("temperature must be between 0 and 2".to_string());
+            }
+        }
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err("top_p must be between 0 and 1".to_string());
+            }
+        }
+        if let Some(num_ctx) = self.num_ctx {
+            if !(MIN_NUM_CTX..=MAX_NUM_CTX).contains(&num_ctx) {
+                return Err(format!("num_ctx must be between {} and {}", MIN_NUM_CTX, MAX_NUM_CTX));
+            }
+        }
+        if let Some(stop) = &self.stop {
+            if stop.len() > 8 || stop.iter().any(|s| s.is_empty()) {
+                return Err("Up to 8 non-empty stop sequences are allowed".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+const MIN_NUM_CTX: u32 = 512;
+const MAX_NUM_CTX: u32 = 131_072;
+// Ollama reloads the model whenever num_ctx changes, so auto sizing snaps to a few fixed windows
+const CONTEXT_WINDOWS: &[u32] = &[2048, 4096, 8192, 16_384, 32_768, 65_536, 131_072];
+// Room for the reply when num_predict is unlimited (-1) or unset
+const DEFAULT_RESPONSE_TOKENS: usize = 1024;
+const CONTEXT_MARGIN_TOKENS: usize = 256;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerationSettings {
+    pub agents: HashMap<String, GenerationOptions>,
+    pub max_auto_num_ctx: u32, // Upper bound for auto sizing; larger windows need more (V)RAM
+}
+
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        Self {
+            agents: HashMap::new(),
+            max_auto_num_ctx: 8192,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref TOKENIZER: Option<tiktoken_rs::CoreBPE> = tiktoken_rs::cl100k_base().ok();
+}
+
+/// Approximate token count; model tokenizers differ but cl100k is close enough for budgeting.
+pub fn estimate_tokens(text: &str) -> usize {
+    match TOKENIZER.as_ref() {
+        Some(tokenizer) => tokenizer.encode_with_special_tokens(text).len(),
+        None => text.len() / 4,
+    }
+}
+
+/// Built-in defaults, matching what each agent used before options were configurable.
+pub fn agent_defaults(agent_type: &str) -> GenerationOptions {
+    let (num_predict, temperature, repeat_penalty) = match agent_type {
+        "conversational_ai" => (Some(2048), Some(0.7), Some(1.05)),
+        "coding" => (Some(1024), Some(0.2), Some(1.1)),
+        "refine" => (Some(2048), Some(0.3), Some(1.1)),
+        "handoff" => (Some(1024), Some(0.3), Some(1.1)),
+        "knowledge" => (Some(1024), Some(0.0), None),
+        "digest" => (Some(1024), Some(0.3), Some(1.1)),
+        "templates" => (Some(1024), Some(0.2), Some(1.1)),
+        "tagging" => (Some(64), Some(0.0), None),
+        "vision" => (Some(1024), Some(0.5), None),
+        "mcp" => (None, Some(0.7), Some(1.1)),
+        "enteract" | "research" => (Some(1024), Some(0.7), Some(1.1)),
+        // Raw model calls leave sampling to the model's own defaults
+        _ => return GenerationOptions::default(),
+    };
+
+    GenerationOptions {
+        temperature,
+        top_p: Some(0.9),
+        repeat_penalty,
+        num_predict,
+        ..GenerationOptions::default()
+    }
+}
+
+fn get_generation_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("generation_settings.json")
+}
+
+fn load_settings() -> GenerationSettings {
+    get_generation_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store_settings(settings: &GenerationSettings) -> Result<(), String> {
+    let path = get_generation_settings_path()
+        .map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+/// Smallest standard window that fits the prompt plus the reply, capped at `max_auto`.
+pub fn auto_num_ctx(prompt_tokens: usize, num_predict: Option<i32>, max_auto: u32) -> u32 {
+    let response_tokens = match num_predict {
+        Some(n) if n > 0 => n as usize,
+        _ => DEFAULT_RESPONSE_TOKENS,
+    };
+    let needed = prompt_tokens + response_tokens + CONTEXT_MARGIN_TOKENS;
+    let max_auto = max_auto.clamp(CONTEXT_WINDOWS[0], MAX_NUM_CTX);
+
+    CONTEXT_WINDOWS
+        .iter()
+        .copied()
+        .find(|&window| window as usize >= needed)
+        .unwrap_or(MAX_NUM_CTX)
+        .min(max_auto)
+}
+
+fn resolve(agent_type: &str, request: Option<&GenerationOptions>, settings: &GenerationSettings) -> GenerationOptions {
+    let mut resolved = agent_defaults(agent_type);
+    if let Some(saved) = settings.agents.get(agent_type) {
+        resolved = resolved.overlay(saved);
+    }
+    if let Some(request) = request {
+        resolved = resolved.overlay(request);
+    }
+    resolved
+}
+
+/// Most prompt tokens (system prompt, history and request) an agent's request can carry before
+/// Ollama starts truncating: the largest window it may get, less room for the reply.
+pub fn prompt_token_limit(agent_type: &str, request: Option<&GenerationOptions>) -> usize {
+    let settings = load_settings();
+    let resolved = resolve(agent_type, request, &settings);
+    let window = resolved
+        .num_ctx
+        .unwrap_or_else(|| settings.max_auto_num_ctx.clamp(CONTEXT_WINDOWS[0], MAX_NUM_CTX));
+    let response_tokens = match resolved.num_predict {
+        Some(n) if n > 0 => n as usize,
+        _ => DEFAULT_RESPONSE_TOKENS,
+    };
+    (window as usize).saturating_sub(response_tokens + CONTEXT_MARGIN_TOKENS)
+}
+
+/// Resolve defaults -> saved per-agent options -> request options into an Ollama `options` object.
+pub fn build_ollama_options(
+    agent_type: &str,
+    request: Option<&GenerationOptions>,
+    prompt_tokens: usize,
+) -> serde_json::Map<String, serde_json::Value> {
+    let settings = load_settings();
+    let resolved = resolve(agent_type, request, &settings);
+
+    let num_ctx = resolved
+        .num_ctx
+        .unwrap_or_else(|| auto_num_ctx(prompt_tokens, resolved.num_predict, settings.max_auto_num_ctx));
+    if prompt_tokens + CONTEXT_MARGIN_TOKENS > num_ctx as usize {
+        println!("⚠️ Prompt (~{} tokens) may not fit num_ctx {} for {} agent; Ollama will truncate it",
+                 prompt_tokens, num_ctx, agent_type);
+    }
+
+    let mut options = serde_json::Map::new();
+    options.insert("num_ctx".to_string(), num_ctx.into());
+    if let Some(temperature) = resolved.temperature {
+        options.insert("temperature".to_string(), temperature.into());
+    }
+    if let Some(top_p) = resolved.top_p {
+        options.insert("top_p".to_string(), top_p.into());
+    }
+    if let Some(top_k) = resolved.top_k {
+        options.insert("top_k".to_string(), top_k.into());
+    }
+    if let Some(repeat_penalty) = resolved.repeat_penalty {
+        options.insert("repeat_penalty".to_string(), repeat_penalty.into());
+    }
+    if let Some(num_predict) = resolved.num_predict {
+        options.insert("num_predict".to_string(), num_predict.into());
+    }
+    if let Some(seed) = resolved.seed {
+        options.insert("seed".to_string(), seed.into());
+    }
+    if let Some(stop) = resolved.stop.filter(|stop| !stop.is_empty()) {
+        options.insert("stop".to_string(), stop.into());
+    }
+    options
+}
+
+#[tauri::command]
+pub async fn get_generation_settings() -> Result<GenerationSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn get_agent_generation_options(agent_type: String) -> Result<GenerationOptions, String> {
+    let mut resolved = agent_defaults(&agent_type);
+    if let Some(saved) = load_settings().agents.get(&agent_type) {
+        resolved = resolved.overlay(saved);
+    }
+    Ok(resolved)
+}
+
+#[tauri::command]
+pub async fn save_agent_generation_options(
+    agent_type: String,
+    options: Option<GenerationOptions>,
+) -> Result<(), String> {
+    let mut settings = load_settings();
+    match options {
+        Some(options) => {
+            options.validate()?;
+            settings.agents.insert(agent_type.clone(), options);
+        }
+        // None resets the agent to its built-in defaults
+        None => {
+            settings.agents.remove(&agent_type);
+        }
+    }
+    store_settings(&settings)?;
+    println!("🎚️ Saved generation options for {} agent", agent_type);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_max_auto_num_ctx(max_auto_num_ctx: u32) -> Result<(), String> {
+    if !(CONTEXT_WINDOWS[0]..=MAX_NUM_CTX).contains(&max_auto_num_ctx) {
+        return Err(format!("Context limit must be between {} and {}", CONTEXT_WINDOWS[0], MAX_NUM_CTX));
+    }
+    let mut settings = load_settings();
+    settings.max_auto_num_ctx = max_auto_num_ctx;
+    store_settings(&settings)
+}
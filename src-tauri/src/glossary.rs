@@ -0,0 +1,284 @@
+// src-tauri/src/glossary.rs
+// Pronunciation/name glossary. Terms bias Whisper towards the right spelling (initial prompt),
+// fix casing in transcripts, and carry phonetic hints that replace the term in text sent to TTS.
+// The default glossary always applies; one project glossary can be active on top of it.
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+pub const DEFAULT_PROJECT: &str = "default";
+// Whisper reads at most 224 prompt tokens; stay well under so the prefix isn't cut mid-term
+const MAX_PROMPT_CHARS: usize = 600;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GlossaryTerm {
+    pub term: String,
+    pub phonetic: Option<String>,         // Spoken form for TTS, e.g. "EN-ter-act"
+    pub preferred_casing: Option<String>, // Spelling transcripts should use, e.g. "iOS"
+}
+
+impl GlossaryTerm {
+    fn written(&self) -> &str {
+        self.preferred_casing.as_deref().filter(|c| !c.is_empty()).unwrap_or(&self.term)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryStore {
+    pub active_project: String,
+    pub projects: BTreeMap<String, Vec<GlossaryTerm>>,
+}
+
+impl Default for GlossaryStore {
+    fn default() -> Self {
+        Self {
+            active_project: DEFAULT_PROJECT.to_string(),
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+impl GlossaryStore {
+    /// Default terms plus the active project's, with project entries winning.
+    pub fn active_terms(&self) -> Vec<GlossaryTerm> {
+        let mut terms: Vec<GlossaryTerm> = self.projects.get(DEFAULT_PROJECT).cloned().unwrap_or_default();
+        if self.active_project != DEFAULT_PROJECT {
+            for term in self.projects.get(&self.active_project).into_iter().flatten() {
+                terms.retain(|t| !t.term.eq_ignore_ascii_case(&term.term));
+                terms.push(term.clone());
+            }
+        }
+        terms
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryProjects {
+    pub active_project: String,
+    pub projects: Vec<(String, usize)>, // Name and term count
+}
+
+fn get_glossary_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("glossary.json")
+}
+
+pub fn load_glossary() -> GlossaryStore {
+    get_glossary_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn store_glossary(store: &GlossaryStore) -> Result<(), String> {
+    let path = get_glossary_path()
+        .map_err(|e| format!("Failed to get glossary path: {}", e))?;
+    let json = serde_json::to_string_pretty(store)
+        .map_err(|e| format!("Failed to serialize glossary: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write glossary file: {}", e))
+}
+
+/// Whisper initial prompt listing the glossary's spellings, or None when the glossary is empty.
+pub fn whisper_prompt() -> Option<String> {
+    let terms = load_glossary().active_terms();
+    let mut prompt = String::from("Glossary:");
+    for term in &terms {
+        let written = term.written();
+        if prompt.len() + written.len() + 2 > MAX_PROMPT_CHARS {
+            break;
+        }
+        if prompt.len() > "Glossary:".len() {
+            prompt.push(',');
+        }
+        prompt.push(' ');
+        prompt.push_str(written);
+    }
+    (prompt.len() > "Glossary:".len()).then(|| format!("{}.", prompt))
+}
+
+fn replace_terms(text: &str, terms: &[GlossaryTerm], replacement: impl Fn(&GlossaryTerm) -> Option<&str>) -> String {
+    let mut result = text.to_string();
+    // Longer terms first so "Enteract Pro" wins over "Enteract"
+    let mut terms: Vec<&GlossaryTerm> = terms.iter().collect();
+    terms.sort_by_key(|term| std::cmp::Reverse(term.term.len()));
+
+    for term in terms {
+        let Some(replacement) = replacement(term).filter(|r| !r.is_empty()) else {
+            continue;
+        };
+        // Word boundaries only make sense next to word characters ("C++" ends in punctuation)
+        let word = term.term.trim();
+        let boundary = |c: Option<char>| if c.map_or(false, char::is_alphanumeric) { r"\b" } else { "" };
+        let pattern = format!("{}{}{}", boundary(word.chars().next()), regex::escape(word), boundary(word.chars().last()));
+        if let Ok(re) = RegexBuilder::new(&pattern).case_insensitive(true).build() {
+            result = re.replace_all(&result, regex::NoExpand(replacement)).into_owned();
+        }
+    }
+    result
+}
+
+/// Rewrite glossary terms in a transcript with their preferred casing.
+pub fn apply_casing(text: &str, terms: &[GlossaryTerm]) -> String {
+    replace_terms(text, terms, |term| term.preferred_casing.as_deref())
+}
+
+/// Swap glossary terms for their phonetic hints before text is spoken.
+pub fn apply_pronunciations(text: &str, terms: &[GlossaryTerm]) -> String {
+    replace_terms(text, terms, |term| term.phonetic.as_deref())
+}
+
+/// Minimal CSV: `term,phonetic,casing` per line, quoted fields allowed, optional header row.
+pub fn parse_glossary_csv(csv: &str) -> Vec<GlossaryTerm> {
+    let mut terms = Vec::new();
+    for (index, line) in csv.lines().enumerate() {
+        let fields = split_csv_line(line);
+        let Some(term) = fields.first().map(|f| f.trim()).filter(|f| !f.is_empty()) else {
+            continue;
+        };
+        if index == 0 && term.eq_ignore_ascii_case("term") {
+            continue;
+        }
+        let optional = |i: usize| fields.get(i).map(|f| f.trim().to_string()).filter(|f| !f.is_empty());
+        terms.push(GlossaryTerm {
+            term: term.to_string(),
+            phonetic: optional(1),
+            preferred_casing: optional(2),
+        });
+    }
+    terms
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn project_name(project: Option<String>) -> String {
+    project
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| DEFAULT_PROJECT.to_string())
+}
+
+/// Upsert terms into a project, matching existing ones case-insensitively.
+fn merge_terms(list: &mut Vec<GlossaryTerm>, terms: Vec<GlossaryTerm>) {
+    for term in terms {
+        match list.iter_mut().find(|t| t.term.eq_ignore_ascii_case(&term.term)) {
+            Some(existing) => *existing = term,
+            None => list.push(term),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_glossary(project: Option<String>) -> Result<Vec<GlossaryTerm>, String> {
+    Ok(load_glossary().projects.get(&project_name(project)).cloned().unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn list_glossary_projects() -> Result<GlossaryProjects, String> {
+    let store = load_glossary();
+    Ok(GlossaryProjects {
+        active_project: store.active_project.clone(),
+        projects: store.projects.iter().map(|(name, terms)| (name.clone(), terms.len())).collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn save_glossary_term(project: Option<String>, term: GlossaryTerm) -> Result<(), String> {
+    if term.term.trim().is_empty() {
+        return Err("Glossary term is empty".to_string());
+    }
+    let mut store = load_glossary();
+    merge_terms(store.projects.entry(project_name(project)).or_default(), vec![term]);
+    store_glossary(&store)
+}
+
+#[tauri::command]
+pub async fn delete_glossary_term(project: Option<String>, term: String) -> Result<bool, String> {
+    let mut store = load_glossary();
+    let Some(list) = store.projects.get_mut(&project_name(project)) else {
+        return Ok(false);
+    };
+    let before = list.len();
+    list.retain(|t| !t.term.eq_ignore_ascii_case(&term));
+    let removed = list.len() != before;
+    store_glossary(&store)?;
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn set_active_glossary_project(project: Option<String>) -> Result<(), String> {
+    let mut store = load_glossary();
+    store.active_project = project_name(project);
+    store_glossary(&store)?;
+    println!("📖 Active glossary project: {}", store.active_project);
+    Ok(())
+}
+
+/// Import `term,phonetic,casing` rows into a project. Returns the number of terms imported.
+#[tauri::command]
+pub async fn import_glossary_csv(project: Option<String>, path: String, replace: Option<bool>) -> Result<usize, String> {
+    let csv = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let terms = parse_glossary_csv(&csv);
+    if terms.is_empty() {
+        return Err("No glossary terms found in the file".to_string());
+    }
+
+    let count = terms.len();
+    let mut store = load_glossary();
+    let list = store.projects.entry(project_name(project)).or_default();
+    if replace.unwrap_or(false) {
+        list.clear();
+    }
+    merge_terms(list, terms);
+    store_glossary(&store)?;
+    println!("📖 Imported {} glossary terms", count);
+    Ok(count)
+}
+
+/// Text with glossary pronunciations applied, for the TTS engine to speak.
+#[tauri::command]
+pub async fn prepare_tts_text(text: String) -> Result<String, String> {
+    Ok(apply_pronunciations(&text, &load_glossary().active_terms()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_glossary_csv() {
+        let csv = "term,phonetic,casing\nenteract,EN-ter-act,Enteract\n\"Nguyen, T\",win,\nios,,iOS\n";
+        let terms = parse_glossary_csv(csv);
+        assert_eq!(terms.len(), 3);
+        assert_eq!(terms[1].term, "Nguyen, T");
+        assert_eq!(terms[1].preferred_casing, None);
+        assert_eq!(terms[2].phonetic, None);
+    }
+
+    #[test]
+    fn test_casing_and_pronunciation() {
+        let terms = parse_glossary_csv("ios,eye oh ess,iOS\nenteract,EN-ter-act,Enteract");
+        assert_eq!(apply_casing("the IOS build of enteract", &terms), "the iOS build of Enteract");
+        assert_eq!(apply_pronunciations("Enteract on iOS", &terms), "EN-ter-act on eye oh ess");
+        assert_eq!(apply_casing("biosphere", &terms), "biosphere");
+    }
+}
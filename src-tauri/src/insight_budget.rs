@@ -0,0 +1,234 @@
+// src-tauri/src/insight_budget.rs
+// Cost control for live insight generation. Each conversation session may run a limited number of
+// insight generations per hour and spend a limited number of tokens per day (both rolling
+// windows), so a long meeting can't keep the machine busy with the model. Usage goes out on the
+// `insight-budget` event after every check and generation. A manual boost lifts the limits for a
+// few minutes when the user wants insights regardless.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+const HOUR_MS: i64 = 60 * 60 * 1000;
+const DAY_MS: i64 = 24 * HOUR_MS;
+// Insights requested without a conversation share this key
+const DEFAULT_SESSION: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InsightBudgetSettings {
+    pub enabled: bool,
+    pub max_generations_per_hour: usize,
+    pub max_tokens_per_day: usize,
+    pub boost_minutes: u32,
+}
+
+impl Default for InsightBudgetSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_generations_per_hour: 30,
+            max_tokens_per_day: 200_000,
+            boost_minutes: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsightBudgetUsage {
+    pub session: String,
+    pub generations_last_hour: usize,
+    pub max_generations_per_hour: usize,
+    pub tokens_today: usize,
+    pub max_tokens_per_day: usize,
+    pub boosted_until: Option<i64>, // Unix ms
+    pub blocked: Option<String>,    // Why the next generation would be refused
+}
+
+#[derive(Default)]
+struct SessionUsage {
+    generations: VecDeque<i64>,       // Unix ms of each generation in the last hour
+    tokens: VecDeque<(i64, usize)>,   // (Unix ms, tokens) in the last day
+}
+
+impl SessionUsage {
+    fn prune(&mut self, now: i64) {
+        while self.generations.front().is_some_and(|&at| now - at >= HOUR_MS) {
+            self.generations.pop_front();
+        }
+        while self.tokens.front().is_some_and(|&(at, _)| now - at >= DAY_MS) {
+            self.tokens.pop_front();
+        }
+    }
+
+    fn tokens_today(&self) -> usize {
+        self.tokens.iter().map(|(_, tokens)| tokens).sum()
+    }
+}
+
+#[derive(Default)]
+struct Budgets {
+    sessions: HashMap<String, SessionUsage>,
+    boosted_until: Option<i64>,
+}
+
+impl Budgets {
+    fn usage(&mut self, settings: &InsightBudgetSettings, session: &str, now: i64) -> InsightBudgetUsage {
+        let boosted_until = self.boosted_until.filter(|&until| until > now);
+        let usage = self.sessions.entry(session.to_string()).or_default();
+        usage.prune(now);
+        let generations_last_hour = usage.generations.len();
+        let tokens_today = usage.tokens_today();
+
+        let blocked = if !settings.enabled || boosted_until.is_some() {
+            None
+        } else if generations_last_hour >= settings.max_generations_per_hour {
+            Some(format!("{} insights in the last hour (limit {})", generations_last_hour, settings.max_generations_per_hour))
+        } else if tokens_today >= settings.max_tokens_per_day {
+            Some(format!("{} tokens in the last day (limit {})", tokens_today, settings.max_tokens_per_day))
+        } else {
+            None
+        };
+        InsightBudgetUsage {
+            session: session.to_string(),
+            generations_last_hour,
+            max_generations_per_hour: settings.max_generations_per_hour,
+            tokens_today,
+            max_tokens_per_day: settings.max_tokens_per_day,
+            boosted_until,
+            blocked,
+        }
+    }
+
+    fn record(&mut self, session: &str, tokens: usize, now: i64) {
+        let usage = self.sessions.entry(session.to_string()).or_default();
+        usage.generations.push_back(now);
+        usage.tokens.push_back((now, tokens));
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref BUDGETS: Mutex<Budgets> = Mutex::new(Budgets::default());
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("insight_budget.json")
+}
+
+fn load_settings() -> InsightBudgetSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn session_key(conversation_id: Option<&str>) -> &str {
+    conversation_id.unwrap_or(DEFAULT_SESSION)
+}
+
+fn current_usage(conversation_id: Option<&str>) -> Result<InsightBudgetUsage, String> {
+    let settings = load_settings();
+    let mut budgets = BUDGETS.lock().map_err(|e| e.to_string())?;
+    Ok(budgets.usage(&settings, session_key(conversation_id), chrono::Utc::now().timestamp_millis()))
+}
+
+fn emit_usage(app_handle: &AppHandle, usage: &InsightBudgetUsage) {
+    if let Err(e) = app_handle.emit("insight-budget", usage) {
+        eprintln!("Failed to emit insight budget: {}", e);
+    }
+}
+
+/// Refuse the next insight generation for this conversation if it's over budget.
+pub fn check(app_handle: &AppHandle, conversation_id: Option<&str>) -> Result<(), String> {
+    let usage = current_usage(conversation_id)?;
+    emit_usage(app_handle, &usage);
+    match usage.blocked {
+        Some(reason) => {
+            println!("⏸️ Insight budget reached for {}: {}", usage.session, reason);
+            Err(format!("Insight budget reached: {}. Boost insights to continue.", reason))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Count a finished insight generation against the conversation's budget.
+pub fn record(app_handle: &AppHandle, conversation_id: Option<&str>, tokens: usize) {
+    let settings = load_settings();
+    let usage = match BUDGETS.lock() {
+        Ok(mut budgets) => {
+            let now = chrono::Utc::now().timestamp_millis();
+            budgets.record(session_key(conversation_id), tokens, now);
+            budgets.usage(&settings, session_key(conversation_id), now)
+        }
+        Err(_) => return,
+    };
+    emit_usage(app_handle, &usage);
+}
+
+#[tauri::command]
+pub fn get_insight_budget_usage(conversation_id: Option<String>) -> Result<InsightBudgetUsage, String> {
+    current_usage(conversation_id.as_deref())
+}
+
+/// Lift the insight limits for the configured number of minutes.
+#[tauri::command]
+pub fn boost_insights(app_handle: AppHandle, conversation_id: Option<String>) -> Result<InsightBudgetUsage, String> {
+    let minutes = load_settings().boost_minutes;
+    {
+        let mut budgets = BUDGETS.lock().map_err(|e| e.to_string())?;
+        budgets.boosted_until = Some(chrono::Utc::now().timestamp_millis() + minutes as i64 * 60 * 1000);
+    }
+    println!("🚀 Insight limits lifted for {} minutes", minutes);
+    let usage = current_usage(conversation_id.as_deref())?;
+    emit_usage(&app_handle, &usage);
+    Ok(usage)
+}
+
+#[tauri::command]
+pub fn get_insight_budget_settings() -> Result<InsightBudgetSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_insight_budget_settings(settings: InsightBudgetSettings) -> Result<(), String> {
+    if settings.max_generations_per_hour == 0 || settings.max_tokens_per_day == 0 || settings.boost_minutes == 0 {
+        return Err("Insight limits and the boost length must be at least 1".to_string());
+    }
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("💾 Saved insight budget: {}/hour, {} tokens/day", settings.max_generations_per_hour, settings.max_tokens_per_day);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_use_rolling_windows_and_boost_lifts_them() {
+        let settings = InsightBudgetSettings { max_generations_per_hour: 2, max_tokens_per_day: 1000, ..Default::default() };
+        let mut budgets = Budgets::default();
+        let start = 1_700_000_000_000;
+
+        budgets.record("meeting", 100, start);
+        budgets.record("meeting", 100, start + 1000);
+        assert!(budgets.usage(&settings, "meeting", start + 2000).blocked.is_some());
+        assert!(budgets.usage(&settings, "other", start + 2000).blocked.is_none());
+        // An hour later the generations have aged out, but the day's tokens still count
+        let usage = budgets.usage(&settings, "meeting", start + HOUR_MS + 1000);
+        assert_eq!(usage.generations_last_hour, 0);
+        assert_eq!(usage.tokens_today, 200);
+        assert!(usage.blocked.is_none());
+
+        budgets.record("meeting", 900, start + HOUR_MS + 2000);
+        assert!(budgets.usage(&settings, "meeting", start + HOUR_MS + 3000).blocked.unwrap().contains("tokens"));
+        budgets.boosted_until = Some(start + HOUR_MS + 10 * 60 * 1000);
+        assert!(budgets.usage(&settings, "meeting", start + HOUR_MS + 3000).blocked.is_none());
+    }
+}
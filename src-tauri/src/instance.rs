@@ -0,0 +1,372 @@
+// src-tauri/src/instance.rs
+// Single-instance guard. The instance that owns the user's data directory holds a lock file there
+// naming a localhost port; later launches by the same OS user find it, forward their command-line
+// arguments over that port and exit, so only one process ever writes the SQLite WAL. A launch with
+// `--takeover` (or the `takeover_instance` command) asks the owner to hand off and exit instead.
+// Lock files left behind by a crash are detected because nobody, or some unrelated process that
+// doesn't know the lock's token, answers on their port. A live owner that won't hand off blocks startup.
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const LOCK_FILE: &str = "enteract.lock";
+const TAKEOVER_FLAG: &str = "--takeover";
+const IPC_TIMEOUT: Duration = Duration::from_secs(3);
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct LockInfo {
+    pid: u32,
+    port: u16,
+    token: String, // Only someone who can read the user's data directory can talk to the owner
+    started_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum IpcMessage {
+    Activate { token: String, args: Vec<String> },
+    Handoff { token: String },
+}
+
+pub enum Startup {
+    Owner,
+    Forwarded,
+    Refused,
+}
+
+enum ClaimError {
+    // A live instance still holds the lock; starting would put two writers on the same database
+    OwnerStillRunning(String),
+    // The lock itself can't be created, e.g. a read-only data directory
+    Unavailable(String),
+}
+
+impl std::fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClaimError::OwnerStillRunning(message) | ClaimError::Unavailable(message) => f.write_str(message),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref OWNED_TOKEN: Mutex<Option<String>> = Mutex::new(None);
+    static ref APP_HANDLE: Mutex<Option<AppHandle>> = Mutex::new(None);
+    static ref PENDING_ARGS: Mutex<Vec<Vec<String>>> = Mutex::new(Vec::new());
+}
+
+fn lock_path() -> PathBuf {
+    crate::data::paths::default_user_data_dir().join(LOCK_FILE)
+}
+
+fn read_lock(path: &PathBuf) -> Option<LockInfo> {
+    fs::read_to_string(path).ok().and_then(|json| serde_json::from_str(&json).ok())
+}
+
+fn owns_lock() -> bool {
+    OWNED_TOKEN.lock().map(|token| token.is_some()).unwrap_or(false)
+}
+
+/// Send one message to the owning instance; true only when it accepted it.
+fn send(info: &LockInfo, message: &IpcMessage) -> bool {
+    match exchange(info, message) {
+        Some(reply) if reply == "ok" => true,
+        Some(reply) => {
+            // The owner always accepts the token from its own lock file, so whatever answered is not it
+            println!("⚠️ Process on port {} answered {:?} instead of the instance lock owner", info.port, reply);
+            false
+        }
+        None => false,
+    }
+}
+
+fn exchange(info: &LockInfo, message: &IpcMessage) -> Option<String> {
+    let address = SocketAddr::from(([127, 0, 0, 1], info.port));
+    let mut stream = TcpStream::connect_timeout(&address, IPC_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT)).ok()?;
+    let mut line = serde_json::to_string(message).ok()?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).ok()?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).ok()?;
+    Some(reply.trim().to_string()).filter(|reply| !reply.is_empty())
+}
+
+fn app_handle_when_ready() -> Option<AppHandle> {
+    let deadline = Instant::now() + HANDOFF_TIMEOUT;
+    while Instant::now() < deadline {
+        if let Some(app_handle) = APP_HANDLE.lock().ok().and_then(|handle| handle.clone()) {
+            return Some(app_handle);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    None
+}
+
+fn activate(app_handle: &AppHandle, args: Vec<String>) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    if let Err(e) = app_handle.emit("instance-invoked", serde_json::json!({ "args": args })) {
+        eprintln!("Failed to emit instance invocation: {}", e);
+    }
+}
+
+fn release_lock() {
+    let Ok(mut owned) = OWNED_TOKEN.lock() else {
+        return;
+    };
+    let Some(token) = owned.take() else {
+        return;
+    };
+    let path = lock_path();
+    // Only remove the file if it's still ours
+    if read_lock(&path).map_or(false, |info| info.token == token) {
+        let _ = fs::remove_file(&path);
+    }
+}
+
+fn handle_connection(stream: TcpStream, token: &str) {
+    let _ = stream.set_read_timeout(Some(IPC_TIMEOUT));
+    let mut line = String::new();
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    if BufReader::new(stream).read_line(&mut line).is_err() {
+        return;
+    }
+
+    match serde_json::from_str::<IpcMessage>(&line) {
+        Ok(IpcMessage::Activate { token: sent, args }) if sent == token => {
+            let _ = writer.write_all(b"ok\n");
+            match APP_HANDLE.lock().ok().and_then(|handle| handle.clone()) {
+                Some(app_handle) => activate(&app_handle, args),
+                None => {
+                    if let Ok(mut pending) = PENDING_ARGS.lock() {
+                        pending.push(args);
+                    }
+                }
+            }
+        }
+        Ok(IpcMessage::Handoff { token: sent }) if sent == token => {
+            println!("🔁 Another instance is taking over, shutting down");
+            let _ = writer.write_all(b"ok\n");
+            // The lock is only let go once this instance has stopped writing: a normal exit runs
+            // the shutdown sequence, which releases it as its last step
+            if let Some(app_handle) = app_handle_when_ready() {
+                app_handle.exit(0);
+            } else {
+                release_lock();
+                std::process::exit(0);
+            }
+        }
+        _ => {
+            let _ = writer.write_all(b"denied\n");
+        }
+    }
+}
+
+/// Take the lock if nobody holds it. The listener is bound first so the port in the file always answers.
+fn try_claim(path: &PathBuf) -> std::io::Result<bool> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let info = LockInfo {
+        pid: std::process::id(),
+        port: listener.local_addr()?.port(),
+        token: uuid::Uuid::new_v4().to_string(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    file.write_all(serde_json::to_string(&info).unwrap_or_default().as_bytes())?;
+
+    if let Ok(mut owned) = OWNED_TOKEN.lock() {
+        *owned = Some(info.token.clone());
+    }
+    let token = info.token;
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            handle_connection(stream, &token);
+        }
+    });
+    Ok(true)
+}
+
+/// Claim the lock, asking the current owner to hand off first when `takeover` is set.
+/// Ok(false) means another live instance owns it and was left alone.
+fn claim(takeover: bool, args: &[String]) -> Result<bool, ClaimError> {
+    claim_at(&lock_path(), takeover, args)
+}
+
+fn claim_at(path: &PathBuf, takeover: bool, args: &[String]) -> Result<bool, ClaimError> {
+    if let Some(dir) = path.parent() {
+        crate::data::paths::ensure_private_dir(dir)
+            .map_err(|e| ClaimError::Unavailable(format!("Failed to create data directory: {}", e)))?;
+    }
+
+    for _ in 0..3 {
+        if try_claim(path).map_err(|e| ClaimError::Unavailable(format!("Failed to create lock file: {}", e)))? {
+            return Ok(true);
+        }
+        let Some(info) = read_lock(path) else {
+            // Half-written by an instance starting right now, or garbage; give it a moment
+            std::thread::sleep(Duration::from_millis(200));
+            if read_lock(path).is_none() {
+                let _ = fs::remove_file(path);
+            }
+            continue;
+        };
+
+        if takeover {
+            if send(&info, &IpcMessage::Handoff { token: info.token.clone() }) {
+                // The owner may still be starting up, and then runs its whole shutdown first
+                let deadline = Instant::now() + HANDOFF_TIMEOUT + crate::shutdown::MAX_DURATION;
+                while path.exists() && Instant::now() < deadline {
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                if path.exists() {
+                    return Err(ClaimError::OwnerStillRunning("The running instance did not hand off in time".to_string()));
+                }
+                continue;
+            }
+        } else if send(&info, &IpcMessage::Activate { token: info.token.clone(), args: args.to_vec() }) {
+            return Ok(false);
+        }
+
+        println!("🔓 Removing stale instance lock left by process {}", info.pid);
+        let _ = fs::remove_file(path);
+    }
+    // Someone kept re-creating the lock between attempts, so another instance is starting right now
+    Err(ClaimError::OwnerStillRunning("Could not acquire the instance lock".to_string()))
+}
+
+/// Called first thing in `run()`. A second launch forwards its arguments to the running instance.
+pub fn claim_at_startup() -> Startup {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let takeover = args.iter().any(|arg| arg == TAKEOVER_FLAG);
+    match claim(takeover, &args) {
+        Ok(true) => Startup::Owner,
+        Ok(false) => {
+            println!("👋 Enteract is already running for this user, forwarded the launch to it");
+            Startup::Forwarded
+        }
+        Err(ClaimError::OwnerStillRunning(e)) => {
+            eprintln!("❌ Another Enteract instance is still running for this user, not starting: {}", e);
+            Startup::Refused
+        }
+        // Without a lock file nobody else can hold it either, so running unguarded is safe enough
+        Err(ClaimError::Unavailable(e)) => {
+            eprintln!("⚠️ Instance lock unavailable, starting anyway: {}", e);
+            Startup::Owner
+        }
+    }
+}
+
+/// Give up the lock on the way out so the next launch doesn't have to detect a stale one.
+pub fn release() {
+    release_lock();
+}
+
+/// Give the IPC listener the app handle once Tauri is up, and replay launches that arrived before it.
+pub fn attach(app_handle: AppHandle) {
+    if let Ok(mut handle) = APP_HANDLE.lock() {
+        *handle = Some(app_handle.clone());
+    }
+    let pending: Vec<Vec<String>> = PENDING_ARGS.lock().map(|mut pending| pending.drain(..).collect()).unwrap_or_default();
+    for args in pending {
+        activate(&app_handle, args);
+    }
+}
+
+/// Ask the instance that owns this user's data to hand off, then take ownership.
+/// Returns false when this instance already owned it.
+#[tauri::command]
+pub async fn takeover_instance(app_handle: AppHandle) -> Result<bool, String> {
+    if owns_lock() {
+        return Ok(false);
+    }
+    let claimed = tauri::async_runtime::spawn_blocking(|| claim(true, &[]))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    if claimed {
+        attach(app_handle);
+    }
+    Ok(claimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_lock(path: &PathBuf, port: u16) -> LockInfo {
+        let info = LockInfo {
+            pid: 1,
+            port,
+            token: "other-token".to_string(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+        };
+        fs::write(path, serde_json::to_string(&info).unwrap()).unwrap();
+        info
+    }
+
+    #[test]
+    fn test_claim_removes_stale_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        // Bind and drop so the port is very likely closed
+        let port = TcpListener::bind(("127.0.0.1", 0)).unwrap().local_addr().unwrap().port();
+        write_lock(&path, port);
+
+        assert!(matches!(claim_at(&path, false, &[]), Ok(true)));
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+    }
+
+    #[test]
+    fn test_claim_treats_denied_reply_as_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        write_lock(&path, listener.local_addr().unwrap().port());
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut line = String::new();
+                let _ = BufReader::new(stream.try_clone().unwrap()).read_line(&mut line);
+                let _ = stream.write_all(b"denied\n");
+            }
+        });
+
+        assert!(matches!(claim_at(&path, false, &[]), Ok(true)));
+        assert_eq!(read_lock(&path).unwrap().pid, std::process::id());
+    }
+
+    #[test]
+    fn test_second_launch_forwards_to_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        assert!(matches!(claim_at(&path, false, &[]), Ok(true)));
+        let owner = read_lock(&path).unwrap();
+
+        let args = vec!["--quick-ask".to_string(), "forward-test".to_string()];
+        assert!(matches!(claim_at(&path, false, &args), Ok(false)));
+        assert_eq!(read_lock(&path).unwrap().token, owner.token);
+        // No app handle in tests, so the owner queues the launch for attach() right after replying
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while !PENDING_ARGS.lock().unwrap().contains(&args) {
+            assert!(Instant::now() < deadline, "forwarded launch was never queued");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
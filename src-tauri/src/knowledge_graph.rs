@@ -0,0 +1,372 @@
+// src-tauri/src/knowledge_graph.rs
+// Insight knowledge graph: a small model extracts entities and relations from finished
+// conversations, chats and documents, so one entity can be followed across all of them over time.
+use crate::data::chat::ChatStorage;
+use crate::data::conversation::{redaction, ConversationStorage};
+use crate::data::knowledge::{KnowledgeStorage, SourceEntity, SourceGraph, SourceRelation};
+use crate::data::types::{ChatSession, ConversationSession, EntityTimeline, KnowledgeEntity};
+use crate::enhanced_rag_commands::EnhancedRagSystemState;
+use crate::generation_options::{build_ollama_options, estimate_tokens};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+
+const EXTRACTION_MODEL: &str = "gemma3:1b-it-qat";
+// Long sources are split into windows; past this many only the beginning is read
+const WINDOW_TOKENS: usize = 3000;
+const MAX_WINDOWS: usize = 3;
+const MAX_ENTITIES_PER_WINDOW: usize = 20;
+pub const ENTITY_TYPES: &[&str] = &["person", "organization", "product", "project", "place", "topic"];
+
+const EXTRACTION_SYSTEM_PROMPT: &str = "You extract a knowledge graph from text. List the specific named people, organizations, \
+products, projects and places mentioned, plus important topics, and how they relate. Reply with JSON only, in the form \
+{\"entities\":[{\"name\":\"...\",\"type\":\"person|organization|product|project|place|topic\"}],\
+\"relations\":[{\"source\":\"...\",\"relation\":\"...\",\"target\":\"...\"}]}. Use names exactly as written in the text.";
+
+lazy_static::lazy_static! {
+    // Sources queued or being extracted, so repeated saves don't queue the same work twice
+    static ref PENDING: Mutex<HashSet<(String, String)>> = Mutex::new(HashSet::new());
+}
+
+#[derive(Debug, Clone)]
+pub struct KnowledgeSource {
+    pub source_type: &'static str,
+    pub source_id: String,
+    pub title: String,
+    pub text: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExtractedGraph {
+    #[serde(default)]
+    pub entities: Vec<ExtractedEntity>,
+    #[serde(default)]
+    pub relations: Vec<ExtractedRelation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtractedEntity {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub entity_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtractedRelation {
+    pub source: String,
+    pub relation: String,
+    pub target: String,
+}
+
+/// Case, spacing and surrounding punctuation don't make a different entity.
+pub fn normalize_entity_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Pull the JSON object out of a model reply, tolerating code fences and chatter around it.
+pub fn parse_extraction(response: &str) -> ExtractedGraph {
+    let (Some(start), Some(end)) = (response.find('{'), response.rfind('}')) else {
+        return ExtractedGraph::default();
+    };
+    if end <= start {
+        return ExtractedGraph::default();
+    }
+    serde_json::from_str(&response[start..=end]).unwrap_or_default()
+}
+
+/// The first line of the text mentioning the entity; entities the model made up have none.
+fn mention_snippet(text: &str, name: &str) -> Option<String> {
+    let needle = name.to_lowercase();
+    text.lines()
+        .find(|line| line.to_lowercase().contains(&needle))
+        .map(|line| line.trim().chars().take(200).collect())
+}
+
+fn split_windows(text: &str) -> Vec<String> {
+    let mut windows = Vec::new();
+    let mut current = String::new();
+    let mut tokens = 0;
+    for line in text.lines() {
+        let line_tokens = estimate_tokens(line) + 1;
+        if tokens + line_tokens > WINDOW_TOKENS && !current.is_empty() {
+            windows.push(std::mem::take(&mut current));
+            tokens = 0;
+            if windows.len() == MAX_WINDOWS {
+                return windows;
+            }
+        }
+        current.push_str(line);
+        current.push('\n');
+        tokens += line_tokens;
+    }
+    if !current.trim().is_empty() {
+        windows.push(current);
+    }
+    windows
+}
+
+fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+pub fn conversation_source(session: &ConversationSession) -> KnowledgeSource {
+    let text = redaction::transcript_lines(&session.messages).join("\n");
+    KnowledgeSource {
+        source_type: "conversation",
+        source_id: session.id.clone(),
+        title: session.name.clone(),
+        text,
+        timestamp: session.start_time,
+    }
+}
+
+pub fn chat_source(chat: &ChatSession) -> KnowledgeSource {
+    let text = chat.history
+        .iter()
+        .filter(|m| (m.sender == "user" || m.sender == "assistant") && m.is_interim != Some(true))
+        .map(|m| format!("{}: {}", m.sender, m.text.trim()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    KnowledgeSource {
+        source_type: "chat",
+        source_id: chat.id.clone(),
+        title: chat.title.clone(),
+        text,
+        timestamp: parse_timestamp(&chat.created_at),
+    }
+}
+
+fn parse_timestamp(rfc3339: &str) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .map(|time| time.timestamp_millis())
+        .unwrap_or_else(|_| chrono::Utc::now().timestamp_millis())
+}
+
+async fn extract_source(source: &KnowledgeSource, content_hash: String) -> Result<SourceGraph, String> {
+    let mut entities: Vec<SourceEntity> = Vec::new();
+    let mut relations: Vec<SourceRelation> = Vec::new();
+
+    let model = crate::agent_models::resolve_helper_model(EXTRACTION_MODEL).await;
+    for window in split_windows(&source.text) {
+        let prompt = format!("Text:\n{}\nExtract the knowledge graph.", window);
+        let options = build_ollama_options(
+            "knowledge",
+            None,
+            estimate_tokens(&prompt) + estimate_tokens(EXTRACTION_SYSTEM_PROMPT),
+        );
+        let response = crate::ollama::generate_completion(
+            &model,
+            prompt,
+            Some(EXTRACTION_SYSTEM_PROMPT.to_string()),
+            Some(serde_json::Value::Object(options)),
+        ).await?;
+
+        let extracted = parse_extraction(&response);
+        for entity in extracted.entities.into_iter().take(MAX_ENTITIES_PER_WINDOW) {
+            let name = entity.name.trim().to_string();
+            let key = normalize_entity_name(&name);
+            if key.chars().count() < 2 || name.chars().count() > 80 || entities.iter().any(|e| e.key == key) {
+                continue;
+            }
+            let Some(snippet) = mention_snippet(&source.text, &name) else {
+                continue;
+            };
+            let entity_type = entity.entity_type.to_lowercase();
+            let entity_type = if ENTITY_TYPES.contains(&entity_type.as_str()) { entity_type } else { "topic".to_string() };
+            entities.push(SourceEntity { key, name, entity_type, snippet });
+        }
+        for relation in extracted.relations {
+            let relation_name = relation.relation.trim().to_lowercase();
+            if relation_name.is_empty() || relation_name.chars().count() > 60 {
+                continue;
+            }
+            relations.push(SourceRelation {
+                source_key: normalize_entity_name(&relation.source),
+                relation: relation_name,
+                target_key: normalize_entity_name(&relation.target),
+            });
+        }
+    }
+
+    // Relations only count between entities that were actually found in the text
+    relations.retain(|r| {
+        r.source_key != r.target_key
+            && entities.iter().any(|e| e.key == r.source_key)
+            && entities.iter().any(|e| e.key == r.target_key)
+    });
+
+    Ok(SourceGraph {
+        source_type: source.source_type.to_string(),
+        source_id: source.source_id.clone(),
+        title: source.title.clone(),
+        timestamp: source.timestamp,
+        content_hash,
+        entities,
+        relations,
+    })
+}
+
+/// Extract the sources in the background, one at a time whenever background work is allowed.
+/// Sources whose content hasn't changed since they were last extracted are skipped.
+pub fn queue_sources(app_handle: &AppHandle, sources: Vec<KnowledgeSource>) {
+    let sources: Vec<KnowledgeSource> = {
+        let mut pending = PENDING.lock().unwrap();
+        sources
+            .into_iter()
+            .filter(|s| !s.text.trim().is_empty())
+            .filter(|s| pending.insert((s.source_type.to_string(), s.source_id.clone())))
+            .collect()
+    };
+    if sources.is_empty() {
+        return;
+    }
+
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut remaining = sources.into_iter();
+        while let Some(source) = remaining.next() {
+            crate::presence::wait_for_background_slot().await;
+            if crate::power::should_defer_background_work() {
+                // Let the next save or rebuild queue them again
+                let mut pending = PENDING.lock().unwrap();
+                pending.remove(&(source.source_type.to_string(), source.source_id.clone()));
+                for skipped in remaining {
+                    pending.remove(&(skipped.source_type.to_string(), skipped.source_id));
+                }
+                return;
+            }
+
+            if let Err(e) = index_source(&handle, &source).await {
+                eprintln!("Knowledge graph extraction failed for {} {}: {}", source.source_type, source.source_id, e);
+            }
+            PENDING.lock().unwrap().remove(&(source.source_type.to_string(), source.source_id.clone()));
+        }
+    });
+}
+
+async fn index_source(app_handle: &AppHandle, source: &KnowledgeSource) -> Result<(), String> {
+    let hash = content_hash(&source.text);
+    let known = KnowledgeStorage::new(app_handle)
+        .and_then(|storage| storage.indexed_hash(source.source_type, &source.source_id))
+        .map_err(|e| format!("Failed to open knowledge storage: {}", e))?;
+    if known.as_deref() == Some(hash.as_str()) {
+        return Ok(());
+    }
+
+    let graph = extract_source(source, hash).await?;
+    KnowledgeStorage::new(app_handle)
+        .and_then(|mut storage| storage.replace_source_graph(&graph))
+        .map_err(|e| format!("Failed to store knowledge graph: {}", e))?;
+
+    println!("🕸️ Indexed {} entities and {} relations from {} \"{}\"",
+             graph.entities.len(), graph.relations.len(), source.source_type, source.title);
+    if let Err(e) = app_handle.emit("knowledge-graph-updated", serde_json::json!({
+        "sourceType": source.source_type,
+        "sourceId": source.source_id,
+        "entities": graph.entities.len(),
+    })) {
+        eprintln!("Failed to emit knowledge graph update: {}", e);
+    }
+    Ok(())
+}
+
+/// Every conversation, chat and document mentioning the entity, oldest first, with its relations.
+#[tauri::command]
+pub async fn get_entity_timeline(app_handle: AppHandle, name: String) -> Result<EntityTimeline, String> {
+    let key = normalize_entity_name(&name);
+    if key.is_empty() {
+        return Err("Entity name is empty".to_string());
+    }
+
+    let storage = KnowledgeStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to open knowledge storage: {}", e))?;
+    let entity = storage.find_entity(&key)
+        .map_err(|e| format!("Failed to look up entity: {}", e))?
+        .ok_or_else(|| format!("No entity named \"{}\"", name))?;
+    let mentions = storage.entity_mentions(&entity.id)
+        .map_err(|e| format!("Failed to load entity mentions: {}", e))?;
+    let relations = storage.entity_relations(&entity.id)
+        .map_err(|e| format!("Failed to load entity relations: {}", e))?;
+
+    Ok(EntityTimeline { entity, mentions, relations })
+}
+
+#[tauri::command]
+pub async fn search_knowledge_entities(
+    app_handle: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<KnowledgeEntity>, String> {
+    KnowledgeStorage::new(&app_handle)
+        .and_then(|storage| storage.search_entities(&normalize_entity_name(&query), limit.unwrap_or(20).min(200)))
+        .map_err(|e| format!("Failed to search entities: {}", e))
+}
+
+/// Queue every conversation, chat and document; unchanged ones are skipped. Returns how many were queued.
+#[tauri::command]
+pub async fn rebuild_knowledge_graph(
+    app_handle: AppHandle,
+    rag_state: State<'_, EnhancedRagSystemState>,
+) -> Result<usize, String> {
+    let removed = KnowledgeStorage::new(&app_handle)
+        .and_then(|mut storage| storage.prune_missing_sources())
+        .map_err(|e| format!("Failed to prune knowledge graph: {}", e))?;
+    if removed > 0 {
+        println!("🧹 Removed {} knowledge graph rows for deleted sources", removed);
+    }
+
+    let mut sources = Vec::new();
+    let conversations = ConversationStorage::new(&app_handle)
+        .and_then(|storage| storage.load_conversations())
+        .map_err(|e| format!("Failed to load conversations: {}", e))?;
+    sources.extend(conversations.conversations.iter().filter(|c| !c.is_active).map(conversation_source));
+
+    let chats = ChatStorage::new(&app_handle)
+        .and_then(|storage| storage.load_chat_sessions())
+        .map_err(|e| format!("Failed to load chats: {}", e))?;
+    sources.extend(chats.chats.iter().map(chat_source));
+
+    // Documents are only available once the RAG system has been initialized
+    let documents = match &*rag_state.0.lock().map_err(|e| e.to_string())? {
+        Some(system) => system.get_all_documents().map_err(|e| format!("Failed to load documents: {}", e))?,
+        None => Vec::new(),
+    };
+    sources.extend(documents.into_iter().map(|document| KnowledgeSource {
+        source_type: "document",
+        timestamp: parse_timestamp(&document.created_at),
+        source_id: document.id,
+        title: document.file_name,
+        text: document.content,
+    }));
+
+    let queued = sources.len();
+    queue_sources(&app_handle, sources);
+    Ok(queued)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extraction_tolerates_fences() {
+        let response = "```json\n{\"entities\":[{\"name\":\"Acme Corp\",\"type\":\"organization\"}],\"relations\":[]}\n```";
+        let graph = parse_extraction(response);
+        assert_eq!(graph.entities.len(), 1);
+        assert_eq!(graph.entities[0].name, "Acme Corp");
+        assert!(parse_extraction("no json here").entities.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_entity_name() {
+        assert_eq!(normalize_entity_name("  Acme   Corp. "), "acme corp");
+        assert_eq!(normalize_entity_name("\"ACME Corp\""), "acme corp");
+    }
+}
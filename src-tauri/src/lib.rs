@@ -9,7 +9,10 @@ mod window_manager;
 mod eye_tracking;
 mod speech;
 mod ollama;
+mod ollama_retry; // Retries with backoff and a circuit breaker for Ollama requests
+mod ollama_http; // Per-endpoint-class HTTP clients, timeouts and concurrency for Ollama
 mod screenshot;
+mod screen_inventory; // Structured (JSON) screen reading by the vision agent
 mod file_handler;
 mod data; // Data storage module (JSON, SQLite, migration, hybrid)
 mod audio_loopback; // New audio loopback module
@@ -18,17 +21,90 @@ mod system_info; // System information module
 mod rag_system; // RAG document system module
 mod rag_commands; // RAG command handlers
 mod simple_embedding_service; // Simple embedding service
+mod ollama_embedding_service; // Ollama embedding models for RAG, with an on-disk cache
 mod search_service; // Tantivy search service
 mod chunking_service; // Enhanced text chunking service
 mod enhanced_rag_system; // Enhanced RAG system
 mod enhanced_rag_commands; // Enhanced RAG command handlers
+mod query_condensation; // Follow-up question rewriting for RAG retrieval
+mod embedding_pipeline; // Throttled, pausable embedding job scheduler
 mod mcp; // MCP module for multi-command processing
+mod screen_share; // Screen share detection for hiding sensitive overlays
+mod drag_resize; // Window snapping and saved window layouts
+mod follow_window; // Dock the overlay to the focused application window
+mod presence; // Idle/presence detection for pausing background work
+mod power; // Battery/power-source awareness and throttling policies
+mod share_format; // Signed JSON envelope for shareable files
+mod prompt_templates; // Custom prompt templates
+mod conversation_profiles; // Per-scenario capture/transcription/insight pipeline settings
+mod dictation; // Type final transcript segments into the focused application
+mod voice_commands; // Spoken app-control commands matched on the user's transcript
+mod stt_provider; // Per-stream speech-to-text providers (local Whisper, whisper.cpp server, cloud)
+mod llm_provider; // Per-agent LLM backends (Ollama, OpenAI-compatible servers, Anthropic)
+mod agent_tools; // Lets agents call MCP tools through Ollama function calling
+mod agent_models; // Which Ollama model each built-in agent runs, with installed-model fallback
+mod model_warmup; // Preloads agent models at launch in predicted-use order
+mod model_residency; // keep_alive for Ollama requests and unloading of idle vision models
+mod model_benchmarks; // Measured model speed and memory, and the model recommendation grounded in it
+mod insight_budget; // Hourly/daily limits on live insight generation with a manual boost
+mod generation_options; // Per-agent/per-request Ollama sampling options and num_ctx sizing
+mod context_window; // Trims chat history to the model's window and summarizes what was dropped
+mod draft_refine; // Instant small-model drafts refined by a larger model in the background
+mod multi_agent; // Side-by-side comparison of several agents/models on one prompt
+mod conversation_handoff; // Seed a chat with a brief of a conversation and link the two
+mod conversation_templates; // Structured note templates filled in when a conversation ends
+mod snippet_library; // Code blocks from assistant messages, searchable and reusable
+mod knowledge_graph; // Entities and relations linking conversations, chats and documents
+mod digest; // Scheduled daily/weekly digest reports
+mod glossary; // Name/term glossary for Whisper biasing and TTS pronunciation
+mod captions; // Word-timed caption feed for the overlay window
+mod accessibility; // Sentence-batched screen-reader announcements of responses and captions
+mod document_volumes; // Volume identity and availability for documents on removable/network drives
+mod context_packing; // Token-budgeted packing of chunks from several pinned documents
+mod file_qa; // In-memory Q&A over a single file that isn't in the index
+mod ask_enteract; // One question routed over chats, conversations, documents and screen context
+mod document_tagging; // Topic tags for documents from the user's taxonomy, chosen by a local model
+mod conversation_audio; // Optional kept audio per conversation, clipped per message for playback
+mod speakers; // Named conversation speakers, recognized across sessions by voiceprint
+mod voice_fingerprint; // Lightweight spectral voice fingerprints for speaker recognition
+mod event_throttle; // Rate limiting, coalescing and drop metrics for high-frequency events
+mod notifications; // Native OS notifications for background results, with deep links back into the app
+mod instance; // Single instance per OS user: data directory lock file and launch forwarding
+mod shutdown; // Ordered, time-limited cleanup of every subsystem when the app exits
+mod startup; // Launch at login and background-agent mode (tray icon, global capture hotkeys)
+mod consent; // Recording consent policy per conversation, consent audit trail and the recording indicator
+mod plugins; // User plugins (WASM or scripts) adding MCP tools, RAG parsers and insight generators
+mod conversation_memory; // Rolling summary and named entities per conversation, injected into insight prompts
+mod response_cache; // Opt-in disk cache of non-streaming Ollama replies, keyed by model, prompts, images and options
+#[cfg(feature = "cli")]
+pub mod cli; // Headless enteract-cli entry point sharing the app's data directory
+#[cfg(test)]
+mod test_support; // Mock Ollama server, synthetic audio source and in-memory databases for tests
 
 // Re-export the commands from modules
 use transparency::{set_window_transparency, emergency_restore_window, toggle_transparency};
 use window_manager::{
     move_window_to_position, get_window_position, get_window_size, get_screen_size,
-    get_virtual_desktop_size, get_monitor_layout, set_window_bounds
+    get_virtual_desktop_size, get_monitor_layout, set_window_bounds,
+    list_child_window_profiles, spawn_child_window, close_child_window, get_foreground_window, get_active_app
+};
+use follow_window::{start_follow_active_window, stop_follow_active_window, get_follow_window_status};
+use presence::{get_presence_status, update_presence_settings, set_background_work_override};
+use power::{get_power_status, update_power_policy};
+use conversation_profiles::{
+    list_conversation_profiles, save_conversation_profile, delete_conversation_profile,
+    get_active_conversation_profile, set_active_conversation_profile
+};
+use dictation::{
+    start_dictation, stop_dictation, toggle_dictation_pause, get_dictation_status, dictation_submit_segment
+};
+use voice_commands::{
+    confirm_voice_command, cancel_voice_command,
+    get_voice_command_settings, update_voice_command_settings
+};
+use prompt_templates::{
+    save_prompt_template, list_prompt_templates, delete_prompt_template,
+    export_prompt_template, import_prompt_template
 };
 use eye_tracking::{
     start_ml_eye_tracking, stop_ml_eye_tracking, get_ml_gaze_data, calibrate_ml_eye_tracking,
@@ -38,17 +114,69 @@ use speech::{
     initialize_whisper_model, transcribe_audio_base64, transcribe_audio_file,
     check_whisper_model_availability, download_whisper_model, list_available_models
 };
+use stt_provider::{get_stt_settings, save_stt_settings};
+use llm_provider::{get_llm_settings, save_llm_settings};
+use agent_models::{get_agent_models, set_agent_model};
+use model_warmup::{warm_up_models, get_model_warmup_status, get_warmup_settings, save_warmup_settings};
+use model_residency::{get_model_residency_settings, save_model_residency_settings, get_ollama_loaded_models};
+use insight_budget::{get_insight_budget_usage, boost_insights, get_insight_budget_settings, save_insight_budget_settings};
+use ollama_retry::{get_ollama_retry_settings, save_ollama_retry_settings};
+use ollama_http::{get_ollama_http_settings, save_ollama_http_settings, test_ollama_connection};
+use generation_options::{
+    get_generation_settings, get_agent_generation_options, save_agent_generation_options, set_max_auto_num_ctx
+};
+use context_window::{get_context_window_settings, save_context_window_settings};
+use draft_refine::generate_draft_and_refine;
+use multi_agent::generate_multi_agent;
+use file_qa::{ask_about_file, promote_file_to_index};
+use ask_enteract::ask_enteract;
+use document_tagging::{
+    get_document_tag_taxonomy, set_document_tag_taxonomy, get_document_tags, set_document_tags, classify_documents
+};
+use conversation_handoff::{continue_conversation_in_chat, get_conversation_chat_links};
+use conversation_audio::{start_conversation_audio, stop_conversation_audio, get_message_audio};
+use speakers::{list_speakers, name_conversation_speaker, suggest_conversation_speakers, rename_speaker, delete_speaker};
+use event_throttle::{get_event_metrics, reset_event_metrics};
+use notifications::{get_notification_settings, save_notification_settings, show_background_notification};
+use instance::takeover_instance;
+use shutdown::{complete_shutdown_flush, get_last_shutdown_report};
+use startup::{register_autostart, unregister_autostart, get_autostart_status, get_startup_settings, save_startup_settings};
+use conversation_templates::{
+    list_conversation_templates, save_conversation_template, delete_conversation_template,
+    set_conversation_template, fill_conversation_template
+};
+use snippet_library::{search_snippets, get_snippet_languages, delete_snippet, insert_snippet_into_chat};
+use knowledge_graph::{get_entity_timeline, search_knowledge_entities, rebuild_knowledge_graph};
+use digest::{get_digest_settings, save_digest_settings, generate_digest_now, export_digest_markdown};
+use captions::{get_caption_frame, push_caption_text, clear_captions, get_caption_settings, save_caption_settings};
+use accessibility::{get_accessibility_settings, save_accessibility_settings};
+use model_benchmarks::get_model_benchmarks;
+use consent::{
+    grant_recording_consent, revoke_recording_consent, get_recording_consent, set_microphone_recording,
+    get_recording_indicator, get_consent_audit, get_consent_settings, save_consent_settings
+};
+use conversation_memory::{get_conversation_memory, update_conversation_memory, reset_conversation_memory};
+use response_cache::{get_response_cache_settings, save_response_cache_settings, get_response_cache_stats, clear_response_cache};
+use plugins::{
+    list_plugins, reload_plugins, approve_plugin, revoke_plugin, generate_plugin_insights, get_plugin_audit,
+};
+use glossary::{
+    get_glossary, list_glossary_projects, save_glossary_term, delete_glossary_term,
+    set_active_glossary_project, import_glossary_csv, prepare_tts_text,
+};
 use ollama::{
-    get_ollama_models, get_ollama_status, pull_ollama_model, delete_ollama_model,
-    generate_ollama_response, generate_ollama_response_stream, get_ollama_model_info,
+    get_ollama_models, get_ollama_status, preload_ollama_model, unload_ollama_model, pull_ollama_model, delete_ollama_model,
+    generate_ollama_response, generate_ollama_response_stream, generate_ollama_structured, get_ollama_model_info,
     generate_enteract_agent_response, generate_vision_analysis, generate_deep_research,
-    generate_conversational_ai, generate_coding_agent_response, cancel_ai_response,
-    get_gpu_acceleration_status,
+    generate_conversational_ai, generate_coding_agent_response, cancel_ai_response, cancel_ollama_stream,
+    get_gpu_acceleration_status, benchmark_ollama_model, create_ollama_model, copy_ollama_model, list_custom_models,
+    get_agent_modelfile,
 
     // MCP enhanced commands
     generate_mcp_enabled_response, create_mcp_session_for_ai, get_mcp_session_for_ai
 };
-use screenshot::{capture_screenshot, capture_screenshot_area};
+use screenshot::{capture_screenshot, capture_screenshot_area, capture_window, list_capturable_windows};
+use screen_inventory::analyze_screen_structured;
 use file_handler::{
     upload_file_base64, validate_file_upload, get_file_upload_config,
     process_clipboard_image, cleanup_temp_files
@@ -61,7 +189,13 @@ use audio_loopback::{
     save_audio_settings, load_audio_settings, save_general_settings, load_general_settings,
     start_audio_loopback_capture, stop_audio_loopback_capture, process_audio_for_transcription
 };
+use audio_loopback::latency::get_pipeline_latency_report;
 use system_info::get_system_info;
+use drag_resize::{
+    drag_window_with_snap, save_window_layout, apply_window_layout, list_window_layouts, delete_window_layout
+};
+use screen_share::{start_screen_share_monitor, stop_screen_share_monitor, get_screen_share_status};
+use share_format::{list_trusted_share_signers, remove_trusted_share_signer};
 
 // Import RAG commands
 use rag_commands::{
@@ -77,30 +211,47 @@ use enhanced_rag_commands::{
     generate_enhanced_embeddings, clear_enhanced_embedding_cache, update_enhanced_rag_settings,
     get_enhanced_rag_settings, get_enhanced_storage_stats, get_embedding_status,
     validate_enhanced_file_upload, check_document_duplicate, get_document_embedding_status,
-    ensure_documents_ready_for_search, generate_embeddings_for_selection
+    ensure_documents_ready_for_search, generate_embeddings_for_selection,
+    search_enhanced_documents_with_history, create_document_collection, rename_document_collection,
+    delete_document_collection, list_document_collections, assign_documents_to_collection,
+    remove_documents_from_collection, set_chat_collection_scope, get_chat_collection_scope,
+    search_enhanced_documents_advanced, get_related_documents, remove_enhanced_document,
+    remove_document_chunks, get_enhanced_rag_diagnostics, explain_search,
+    export_rag_collection, import_rag_collection, track_document_source, get_stale_documents, reindex_stale,
+    get_unavailable_documents, pack_document_context
+};
+use embedding_pipeline::{
+    get_embedding_pipeline_status, pause_embedding_pipeline, resume_embedding_pipeline,
+    update_embedding_pipeline_config
 };
 
 // Import MCP commands
 use mcp::{
     start_mcp_session, end_mcp_session, get_mcp_session_info, list_mcp_tools,
     execute_mcp_tool, respond_to_mcp_approval, get_mcp_session_logs, 
-    list_active_mcp_sessions, create_mcp_session_manager, get_mcp_tool_schema,
+    list_active_mcp_sessions, list_pending_mcp_approvals, create_mcp_session_manager, get_mcp_tool_schema,
     get_mcp_session_status, create_execution_plan, approve_execution_plan,
-    execute_approved_plan, MCPSessionManager
+    execute_approved_plan, export_plan, import_plan, export_mcp_session,
+    list_mcp_approval_rules, revoke_mcp_approval_rule, get_automation_capabilities, MCPSessionManager
 };
 
 // Import SQLite data storage commands
 use data::{
     // Database initialization and management
     initialize_database, get_database_info, cleanup_legacy_files, check_database_health,
+    create_backup_now, list_backups, restore_backup, get_backup_settings, save_backup_settings,
     // Chat operations (Claude conversations)
-    save_chat_sessions, load_chat_sessions,
+    save_chat_sessions, load_chat_sessions, get_message_variants, select_message_variant, get_message_provenance,
+    toggle_bookmark, toggle_message_reaction, list_bookmarked_messages,
+    get_chat_token_usage, get_context_budget, set_context_budget,
     // Conversation operations (Audio conversations)
     save_conversations, load_conversations, delete_conversation, clear_all_conversations,
     save_conversation_message, batch_save_conversation_messages,
     update_conversation_message, delete_conversation_message,
-    save_conversation_insight, get_conversation_insights,
-    update_session_metadata, update_session_active_state, ping_backend,
+    get_conversation_messages_by_app, get_conversation_app_usage,
+    set_conversation_range_redacted, export_conversation_transcript,
+    save_conversation_insight, get_conversation_insights, get_insights_by_type, get_insight_counts,
+    update_session_metadata, update_session_active_state, get_session_quality, ping_backend,
     // Logging commands
     get_database_logs, get_database_logs_by_operation, get_database_logs_by_level,
     get_database_log_stats, clear_database_logs
@@ -113,8 +264,15 @@ fn greet(name: &str) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Only one instance may own this user's data; a second launch hands its arguments over and exits
+    match crate::instance::claim_at_startup() {
+        crate::instance::Startup::Owner => {}
+        crate::instance::Startup::Forwarded | crate::instance::Startup::Refused => return,
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
         .manage(RagSystemState(std::sync::Arc::new(std::sync::Mutex::new(None))))
         .manage(EnhancedRagSystemState(std::sync::Arc::new(std::sync::Mutex::new(None))))
         .setup(|app| {
@@ -129,8 +287,38 @@ pub fn run() {
                 // For now, we'll rely on window-level keyboard shortcuts
             }
             
+            // Launches forwarded by later instances arrive through the instance lock's listener
+            crate::instance::attach(app.handle().clone());
+            
+            // Tray icon and global capture hotkeys in background-agent mode; login launches stay hidden
+            crate::startup::init_startup(app)?;
+            
             // Audio loopback functionality is initialized on-demand
             
+            // Track idle/presence so background jobs can back off while the user is busy
+            crate::presence::start_presence_monitor(app.handle().clone());
+            
+            // Watch power source so capture and inference can throttle on battery
+            crate::power::start_power_monitor(app.handle().clone());
+            
+            // Preload the agents' models most likely to be needed, within the memory budget
+            crate::model_warmup::start_model_warmup(app.handle().clone());
+            
+            // Idle vision models give their VRAM back (to Whisper, mostly)
+            crate::model_residency::start_residency_monitor(app.handle().clone());
+            
+            // Periodic database backups with rotation
+            crate::data::backup::start_backup_scheduler(app.handle().clone());
+            
+            // Ollama outages are announced once through an app event
+            crate::ollama_retry::init_ollama_retry(app.handle().clone());
+            
+            // Embedding jobs report progress through app events
+            crate::embedding_pipeline::init_embedding_pipeline(app.handle().clone());
+            
+            // Daily/weekly digests run in the background once enabled in settings
+            crate::digest::start_digest_scheduler(app.handle().clone());
+            
             // Enhanced RAG system will be initialized on-demand from frontend
             
             // Keep legacy RAG system for compatibility
@@ -216,6 +404,46 @@ pub fn run() {
             get_virtual_desktop_size,
             get_monitor_layout,
             set_window_bounds,
+            list_child_window_profiles,
+            spawn_child_window,
+            close_child_window,
+            
+            // Window snapping and layouts
+            drag_window_with_snap,
+            save_window_layout,
+            apply_window_layout,
+            list_window_layouts,
+            delete_window_layout,
+            
+            // Follow-active-window mode
+            get_foreground_window,
+            get_active_app,
+            start_follow_active_window,
+            stop_follow_active_window,
+            get_follow_window_status,
+            
+            // Presence / background work
+            get_presence_status,
+            update_presence_settings,
+            set_background_work_override,
+            
+            // Power awareness
+            get_power_status,
+            update_power_policy,
+            
+            // Prompt templates
+            save_prompt_template,
+            list_prompt_templates,
+            delete_prompt_template,
+            export_prompt_template,
+            import_prompt_template,
+            list_trusted_share_signers,
+            remove_trusted_share_signer,
+            
+            // Screen share detection
+            start_screen_share_monitor,
+            stop_screen_share_monitor,
+            get_screen_share_status,
             
             // Eye tracking
             start_ml_eye_tracking,
@@ -234,14 +462,86 @@ pub fn run() {
             check_whisper_model_availability,
             download_whisper_model,
             list_available_models,
+            get_stt_settings,
+            save_stt_settings,
+            get_llm_settings,
+            save_llm_settings,
+            get_agent_models,
+            set_agent_model,
+            warm_up_models,
+            get_ollama_retry_settings,
+            save_ollama_retry_settings,
+            get_ollama_http_settings,
+            save_ollama_http_settings,
+            test_ollama_connection,
+            get_model_warmup_status,
+            get_warmup_settings,
+            save_warmup_settings,
+            get_model_residency_settings,
+            save_model_residency_settings,
+            get_ollama_loaded_models,
+            get_insight_budget_usage,
+            boost_insights,
+            get_insight_budget_settings,
+            save_insight_budget_settings,
+            get_glossary,
+            list_glossary_projects,
+            save_glossary_term,
+            delete_glossary_term,
+            set_active_glossary_project,
+            import_glossary_csv,
+            prepare_tts_text,
+            get_caption_frame,
+            get_accessibility_settings,
+            save_accessibility_settings,
+            grant_recording_consent,
+            revoke_recording_consent,
+            get_recording_consent,
+            set_microphone_recording,
+            get_recording_indicator,
+            get_consent_audit,
+            get_consent_settings,
+            save_consent_settings,
+            list_plugins,
+            reload_plugins,
+            approve_plugin,
+            revoke_plugin,
+            generate_plugin_insights,
+            get_plugin_audit,
+            get_response_cache_settings,
+            save_response_cache_settings,
+            get_response_cache_stats,
+            clear_response_cache,
+            get_conversation_memory,
+            update_conversation_memory,
+            reset_conversation_memory,
+            push_caption_text,
+            clear_captions,
+            get_caption_settings,
+            save_caption_settings,
             
             // Ollama AI
             get_ollama_models,
             get_ollama_status,
+            preload_ollama_model,
+            unload_ollama_model,
             pull_ollama_model,
             delete_ollama_model,
+            create_ollama_model,
+            copy_ollama_model,
+            list_custom_models,
+            get_agent_modelfile,
+            get_generation_settings,
+            get_agent_generation_options,
+            save_agent_generation_options,
+            set_max_auto_num_ctx,
+            get_context_window_settings,
+            save_context_window_settings,
+            generate_draft_and_refine,
+            generate_multi_agent,
             generate_ollama_response,
             generate_ollama_response_stream,
+            generate_ollama_structured,
             get_ollama_model_info,
             generate_enteract_agent_response,
             generate_vision_analysis,
@@ -249,11 +549,17 @@ pub fn run() {
             generate_conversational_ai,
             generate_coding_agent_response,
             cancel_ai_response,
+            cancel_ollama_stream,
             get_gpu_acceleration_status,
+            benchmark_ollama_model,
+            get_model_benchmarks,
             
             // Screenshot
             capture_screenshot,
             capture_screenshot_area,
+            capture_window,
+            list_capturable_windows,
+            analyze_screen_structured,
             
             // File handling
             upload_file_base64,
@@ -267,10 +573,28 @@ pub fn run() {
             get_database_info,
             cleanup_legacy_files,
             check_database_health,
+            create_backup_now,
+            list_backups,
+            restore_backup,
+            get_backup_settings,
+            save_backup_settings,
             
             // Chat data storage (Claude conversations)
             save_chat_sessions,
             load_chat_sessions,
+            get_message_variants,
+            select_message_variant,
+            get_message_provenance,
+            toggle_bookmark,
+            toggle_message_reaction,
+            list_bookmarked_messages,
+            get_chat_token_usage,
+            get_context_budget,
+            set_context_budget,
+            search_snippets,
+            get_snippet_languages,
+            delete_snippet,
+            insert_snippet_into_chat,
             
             // Conversation data storage (Audio conversations)
             save_conversations,
@@ -289,6 +613,27 @@ pub fn run() {
             start_audio_loopback_capture,
             stop_audio_loopback_capture,
             process_audio_for_transcription,
+            get_pipeline_latency_report,
+            
+            // Conversation profiles
+            list_conversation_profiles,
+            save_conversation_profile,
+            delete_conversation_profile,
+            get_active_conversation_profile,
+            set_active_conversation_profile,
+            
+            // Dictation mode
+            start_dictation,
+            stop_dictation,
+            toggle_dictation_pause,
+            get_dictation_status,
+            dictation_submit_segment,
+            
+            // Voice commands
+            confirm_voice_command,
+            cancel_voice_command,
+            get_voice_command_settings,
+            update_voice_command_settings,
             
             // System info
             get_system_info,
@@ -298,6 +643,10 @@ pub fn run() {
             batch_save_conversation_messages,
             update_conversation_message,
             delete_conversation_message,
+            get_conversation_messages_by_app,
+            get_conversation_app_usage,
+            set_conversation_range_redacted,
+            export_conversation_transcript,
             update_session_metadata,
             update_session_active_state,
             ping_backend,
@@ -305,6 +654,31 @@ pub fn run() {
             // Conversation insights
             save_conversation_insight,
             get_conversation_insights,
+            get_insights_by_type,
+            get_insight_counts,
+            get_session_quality,
+            continue_conversation_in_chat,
+            get_conversation_chat_links,
+            start_conversation_audio,
+            stop_conversation_audio,
+            get_message_audio,
+            list_speakers,
+            name_conversation_speaker,
+            suggest_conversation_speakers,
+            rename_speaker,
+            delete_speaker,
+            list_conversation_templates,
+            save_conversation_template,
+            delete_conversation_template,
+            set_conversation_template,
+            fill_conversation_template,
+            get_entity_timeline,
+            search_knowledge_entities,
+            rebuild_knowledge_graph,
+            get_digest_settings,
+            save_digest_settings,
+            generate_digest_now,
+            export_digest_markdown,
             
             // RAG system commands (legacy)
             initialize_rag_system,
@@ -335,6 +709,42 @@ pub fn run() {
             get_document_embedding_status,
             ensure_documents_ready_for_search,
             generate_embeddings_for_selection,
+            search_enhanced_documents_with_history,
+            create_document_collection,
+            rename_document_collection,
+            delete_document_collection,
+            list_document_collections,
+            assign_documents_to_collection,
+            remove_documents_from_collection,
+            export_rag_collection,
+            import_rag_collection,
+            set_chat_collection_scope,
+            get_chat_collection_scope,
+            search_enhanced_documents_advanced,
+            get_related_documents,
+            remove_enhanced_document,
+            remove_document_chunks,
+            get_enhanced_rag_diagnostics,
+            track_document_source,
+            get_stale_documents,
+            reindex_stale,
+            get_unavailable_documents,
+            pack_document_context,
+            ask_about_file,
+            ask_enteract,
+            promote_file_to_index,
+            get_document_tag_taxonomy,
+            set_document_tag_taxonomy,
+            get_document_tags,
+            set_document_tags,
+            classify_documents,
+            explain_search,
+            
+            // Embedding pipeline controls
+            get_embedding_pipeline_status,
+            pause_embedding_pipeline,
+            resume_embedding_pipeline,
+            update_embedding_pipeline_config,
 
             // MCP commands
             start_mcp_session,
@@ -345,6 +755,7 @@ pub fn run() {
             respond_to_mcp_approval,
             get_mcp_session_logs,
             list_active_mcp_sessions,
+            list_pending_mcp_approvals,
             get_mcp_tool_schema,
             get_mcp_session_status,
             
@@ -352,6 +763,12 @@ pub fn run() {
             create_execution_plan,
             approve_execution_plan,
             execute_approved_plan,
+            export_plan,
+            import_plan,
+            export_mcp_session,
+            list_mcp_approval_rules,
+            revoke_mcp_approval_rule,
+            get_automation_capabilities,
             // Enhanced AI commands with MCP
             generate_mcp_enabled_response,
             create_mcp_session_for_ai,
@@ -370,6 +787,27 @@ pub fn run() {
             // Backend connectivity
             ping_backend,
             
+            // Event emission metrics
+            get_event_metrics,
+            reset_event_metrics,
+            
+            // Native notifications
+            get_notification_settings,
+            save_notification_settings,
+            show_background_notification,
+            
+            // Single-instance handoff
+            takeover_instance,
+            register_autostart,
+            unregister_autostart,
+            get_autostart_status,
+            get_startup_settings,
+            save_startup_settings,
+            
+            // Shutdown coordination
+            complete_shutdown_flush,
+            get_last_shutdown_report,
+            
             // Database logging
             get_database_logs,
             get_database_logs_by_operation,
@@ -378,6 +816,14 @@ pub fn run() {
             clear_database_logs,
 
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Hold the first exit until every subsystem has shut down cleanly
+            if let tauri::RunEvent::ExitRequested { api, .. } = event {
+                if !crate::shutdown::on_exit_requested(app_handle) {
+                    api.prevent_exit();
+                }
+            }
+        });
 }
\ No newline at end of file
@@ -0,0 +1,708 @@
+// src-tauri/src/llm_provider.rs
+// LLM provider abstraction. Each agent type picks a backend in settings: the local Ollama server,
+// an OpenAI-compatible server (LM Studio, vLLM, llama.cpp server, OpenAI) or Anthropic. Every
+// provider streams onto the same ollama-stream-{session} events so the frontend doesn't change.
+// API keys live in the OS keychain, one entry per agent type, never in the settings file, and the
+// frontend only ever sees them masked.
+use crate::generation_options::{build_ollama_options, estimate_tokens, GenerationOptions};
+use crate::ollama::{build_chat_messages, ChatContextMessage, ChatMessage, NdjsonLines};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio_util::sync::CancellationToken;
+
+const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+// Anthropic requires max_tokens; used when the agent's options don't set num_predict
+const DEFAULT_MAX_TOKENS: i64 = 2048;
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LlmProviderConfig {
+    /// Ollama (the default). Without overrides the agent uses the built-in streaming path;
+    /// `base_url`/`model` point it at another Ollama server or model.
+    Ollama {
+        #[serde(default)]
+        base_url: Option<String>,
+        #[serde(default)]
+        model: Option<String>,
+    },
+    /// OpenAI-compatible /v1/chat/completions (LM Studio, vLLM, llama.cpp server, OpenAI, ...)
+    OpenAiCompatible {
+        base_url: String,
+        api_key: Option<String>,
+        model: String,
+    },
+    /// Anthropic Messages API
+    Anthropic {
+        api_key: String,
+        model: String,
+        #[serde(default)]
+        base_url: Option<String>,
+    },
+}
+
+impl Default for LlmProviderConfig {
+    fn default() -> Self {
+        LlmProviderConfig::Ollama { base_url: None, model: None }
+    }
+}
+
+impl LlmProviderConfig {
+    fn is_builtin_ollama(&self) -> bool {
+        matches!(self, LlmProviderConfig::Ollama { base_url: None, model: None })
+    }
+
+    fn api_key(&self) -> Option<&str> {
+        let key = match self {
+            LlmProviderConfig::Ollama { .. } => None,
+            LlmProviderConfig::OpenAiCompatible { api_key, .. } => api_key.as_deref(),
+            LlmProviderConfig::Anthropic { api_key, .. } => Some(api_key.as_str()),
+        };
+        key.filter(|key| !key.is_empty())
+    }
+
+    fn set_api_key(&mut self, key: Option<String>) {
+        match self {
+            LlmProviderConfig::Ollama { .. } => {}
+            LlmProviderConfig::OpenAiCompatible { api_key, .. } => *api_key = key,
+            LlmProviderConfig::Anthropic { api_key, .. } => *api_key = key.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmSettings {
+    pub agents: HashMap<String, LlmProviderConfig>, // Agent type -> provider; missing agents use Ollama
+    pub request_timeout_secs: u64,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            agents: HashMap::new(),
+            request_timeout_secs: 180,
+        }
+    }
+}
+
+/// One streamed chat turn.
+pub struct ChatTurn<'a> {
+    pub messages: &'a [ChatMessage],
+    pub options: &'a serde_json::Map<String, serde_json::Value>, // Ollama-style options, translated per provider
+    pub cancel: &'a CancellationToken,
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &str;
+    /// The model this provider will run; Ollama falls back to the agent's own model.
+    fn model<'a>(&'a self, agent_model: &'a str) -> &'a str;
+    /// Stream a reply, passing each piece of text to `on_text`. Returns the whole reply, which is
+    /// partial if the turn was cancelled.
+    async fn stream_chat(
+        &self,
+        agent_model: &str,
+        turn: ChatTurn<'_>,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, String>;
+}
+
+pub struct OllamaProvider {
+    base_url: String,
+    model: Option<String>,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &str { "ollama" }
+
+    fn model<'a>(&'a self, agent_model: &'a str) -> &'a str {
+        self.model.as_deref().unwrap_or(agent_model)
+    }
+
+    async fn stream_chat(
+        &self,
+        agent_model: &str,
+        turn: ChatTurn<'_>,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, String> {
+        let body = serde_json::json!({
+            "model": self.model(agent_model),
+            "messages": turn.messages,
+            "stream": true,
+            "options": turn.options,
+        });
+        let mut request = http_client(self.timeout)?
+            .post(format!("{}/api/chat", self.base_url.trim_end_matches('/')))
+            .json(&body);
+        // The saved token belongs to the app's Ollama server, not to one configured here
+        if self.base_url.trim_end_matches('/') == crate::ollama::ollama_base_url() {
+            request = request.headers(crate::ollama_http::auth_headers());
+        }
+        read_stream(self.name(), request, turn.cancel, on_text, parse_ollama_line).await
+    }
+}
+
+pub struct OpenAiCompatibleProvider {
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str { "openai_compatible" }
+
+    fn model<'a>(&'a self, _agent_model: &'a str) -> &'a str {
+        &self.model
+    }
+
+    async fn stream_chat(
+        &self,
+        _agent_model: &str,
+        turn: ChatTurn<'_>,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, String> {
+        let messages: Vec<serde_json::Value> = turn.messages
+            .iter()
+            .map(|message| serde_json::json!({ "role": message.role, "content": message.content }))
+            .collect();
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": true,
+        });
+        copy_option(turn.options, "temperature", &mut body, "temperature");
+        copy_option(turn.options, "top_p", &mut body, "top_p");
+        copy_option(turn.options, "num_predict", &mut body, "max_tokens");
+
+        let mut request = http_client(self.timeout)?
+            .post(format!("{}/v1/chat/completions", self.base_url.trim_end_matches('/')))
+            .json(&body);
+        if let Some(api_key) = self.api_key.as_ref().filter(|key| !key.is_empty()) {
+            request = request.bearer_auth(api_key);
+        }
+        read_stream(self.name(), request, turn.cancel, on_text, parse_openai_line).await
+    }
+}
+
+pub struct AnthropicProvider {
+    base_url: String,
+    api_key: String,
+    model: String,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &str { "anthropic" }
+
+    fn model<'a>(&'a self, _agent_model: &'a str) -> &'a str {
+        &self.model
+    }
+
+    async fn stream_chat(
+        &self,
+        _agent_model: &str,
+        turn: ChatTurn<'_>,
+        on_text: &mut (dyn FnMut(&str) + Send),
+    ) -> Result<String, String> {
+        let (system, messages) = anthropic_messages(turn.messages);
+        let max_tokens = turn.options
+            .get("num_predict")
+            .and_then(|value| value.as_i64())
+            .filter(|tokens| *tokens > 0)
+            .unwrap_or(DEFAULT_MAX_TOKENS);
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "max_tokens": max_tokens,
+            "messages": messages,
+            "stream": true,
+        });
+        if !system.is_empty() {
+            body["system"] = system.into();
+        }
+        copy_option(turn.options, "temperature", &mut body, "temperature");
+        copy_option(turn.options, "top_p", &mut body, "top_p");
+
+        let request = http_client(self.timeout)?
+            .post(format!("{}/v1/messages", self.base_url.trim_end_matches('/')))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body);
+        read_stream(self.name(), request, turn.cancel, on_text, parse_anthropic_line).await
+    }
+}
+
+enum StreamLine {
+    Text(String),
+    Done,
+    Skip,
+}
+
+fn parse_ollama_line(line: &str) -> Result<StreamLine, String> {
+    let value: serde_json::Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(_) => return Ok(StreamLine::Skip),
+    };
+    if let Some(error) = value["error"].as_str() {
+        return Err(error.to_string());
+    }
+    if value["done"].as_bool() == Some(true) {
+        return Ok(StreamLine::Done);
+    }
+    Ok(value["message"]["content"].as_str().map_or(StreamLine::Skip, |text| StreamLine::Text(text.to_string())))
+}
+
+// Server-sent events; only the `data:` lines carry anything we need
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(str::trim)
+}
+
+fn parse_openai_line(line: &str) -> Result<StreamLine, String> {
+    let Some(data) = sse_data(line) else {
+        return Ok(StreamLine::Skip);
+    };
+    if data == "[DONE]" {
+        return Ok(StreamLine::Done);
+    }
+    let value: serde_json::Value = serde_json::from_str(data).unwrap_or_default();
+    if let Some(error) = value["error"]["message"].as_str() {
+        return Err(error.to_string());
+    }
+    Ok(value["choices"][0]["delta"]["content"].as_str().map_or(StreamLine::Skip, |text| StreamLine::Text(text.to_string())))
+}
+
+fn parse_anthropic_line(line: &str) -> Result<StreamLine, String> {
+    let Some(data) = sse_data(line) else {
+        return Ok(StreamLine::Skip);
+    };
+    let value: serde_json::Value = serde_json::from_str(data).unwrap_or_default();
+    match value["type"].as_str() {
+        Some("content_block_delta") => {
+            Ok(value["delta"]["text"].as_str().map_or(StreamLine::Skip, |text| StreamLine::Text(text.to_string())))
+        }
+        Some("message_stop") => Ok(StreamLine::Done),
+        Some("error") => Err(value["error"]["message"].as_str().unwrap_or("Unknown error").to_string()),
+        _ => Ok(StreamLine::Skip),
+    }
+}
+
+/// Anthropic takes the system prompt separately and only user/assistant turns, which must alternate.
+fn anthropic_messages(messages: &[ChatMessage]) -> (String, Vec<serde_json::Value>) {
+    let mut system = Vec::new();
+    let mut turns: Vec<(&str, String)> = Vec::new();
+    for message in messages {
+        let (role, content) = match message.role.as_str() {
+            "system" => {
+                system.push(message.content.as_str());
+                continue;
+            }
+            "assistant" => ("assistant", message.content.clone()),
+            "user" => ("user", message.content.clone()),
+            other => ("user", format!("{}: {}", other, message.content)),
+        };
+        match turns.last_mut() {
+            Some((last_role, last_content)) if *last_role == role => {
+                last_content.push_str("\n\n");
+                last_content.push_str(&content);
+            }
+            _ => turns.push((role, content)),
+        }
+    }
+    // The conversation has to open with a user turn
+    if turns.first().is_some_and(|(role, _)| *role == "assistant") {
+        turns.insert(0, ("user", "(conversation continues)".to_string()));
+    }
+
+    let turns = turns
+        .into_iter()
+        .map(|(role, content)| serde_json::json!({ "role": role, "content": content }))
+        .collect();
+    (system.join("\n\n"), turns)
+}
+
+fn copy_option(
+    options: &serde_json::Map<String, serde_json::Value>,
+    from: &str,
+    body: &mut serde_json::Value,
+    to: &str,
+) {
+    if let Some(value) = options.get(from) {
+        body[to] = value.clone();
+    }
+}
+
+fn http_client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// Send the request and feed each streamed line through `parse` until the provider says it's done
+async fn read_stream(
+    provider: &str,
+    request: reqwest::RequestBuilder,
+    cancel: &CancellationToken,
+    on_text: &mut (dyn FnMut(&str) + Send),
+    parse: fn(&str) -> Result<StreamLine, String>,
+) -> Result<String, String> {
+    let response = tokio::select! {
+        _ = cancel.cancelled() => return Ok(String::new()),
+        response = request.send() => response.map_err(|e| format!("{} request failed: {}", provider, e))?,
+    };
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("{} returned HTTP {}: {}", provider, status, body));
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut lines = NdjsonLines::default();
+    let mut text = String::new();
+    loop {
+        let chunk = tokio::select! {
+            _ = cancel.cancelled() => return Ok(text),
+            chunk = tokio::time::timeout(CHUNK_TIMEOUT, stream.next()) => chunk,
+        };
+        let bytes = match chunk {
+            Err(_) => return Err(format!("{} stopped responding", provider)),
+            Ok(None) => return Ok(text),
+            Ok(Some(Err(e))) => return Err(format!("{} stream error: {}", provider, e)),
+            Ok(Some(Ok(bytes))) => bytes,
+        };
+        for line in lines.push(&bytes) {
+            match parse(line.trim())? {
+                StreamLine::Text(piece) if !piece.is_empty() => {
+                    on_text(&piece);
+                    text.push_str(&piece);
+                }
+                StreamLine::Done => return Ok(text),
+                _ => {}
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SETTINGS: Mutex<Option<LlmSettings>> = Mutex::new(None);
+}
+
+fn get_llm_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("llm_settings.json")
+}
+
+fn key_entry(agent_type: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(crate::ollama_http::KEYCHAIN_SERVICE, &format!("llm-api-key-{}", agent_type))
+        .map_err(|e| format!("Failed to open the system keychain: {}", e))
+}
+
+fn load_api_key(agent_type: &str) -> Option<String> {
+    let entry = key_entry(agent_type).map_err(|e| eprintln!("{}", e)).ok()?;
+    match entry.get_password() {
+        Ok(key) => Some(key),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            eprintln!("Failed to read the {} API key from the keychain: {}", agent_type, e);
+            None
+        }
+    }
+}
+
+fn store_api_key(agent_type: &str, key: Option<&str>) -> Result<(), String> {
+    let entry = key_entry(agent_type)?;
+    let result = match key {
+        Some(key) => entry.set_password(key),
+        None => match entry.delete_password() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            result => result,
+        },
+    };
+    result.map_err(|e| format!("Failed to store the {} API key in the system keychain: {}", agent_type, e))
+}
+
+// Everything but the API keys, which go to the keychain
+fn write_settings_file(settings: &LlmSettings) -> Result<PathBuf, String> {
+    let mut settings = settings.clone();
+    for config in settings.agents.values_mut() {
+        config.set_api_key(None);
+    }
+    let path = get_llm_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize LLM settings: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save LLM settings: {}", e))?;
+    Ok(path)
+}
+
+// Read once and kept: every generation asks, and the keychain can be slow
+fn load_settings() -> LlmSettings {
+    let Ok(mut cached) = SETTINGS.lock() else {
+        return read_settings();
+    };
+    cached.get_or_insert_with(read_settings).clone()
+}
+
+fn read_settings() -> LlmSettings {
+    let mut settings: LlmSettings = get_llm_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    // Earlier builds saved the keys in the file; move them to the keychain
+    let in_file: Vec<(String, String)> = settings.agents.iter()
+        .filter_map(|(agent_type, config)| Some((agent_type.clone(), config.api_key()?.to_string())))
+        .collect();
+    if !in_file.is_empty() {
+        let moved = in_file.iter()
+            .try_for_each(|(agent_type, key)| store_api_key(agent_type, Some(key)))
+            .and_then(|_| write_settings_file(&settings));
+        match moved {
+            Ok(_) => println!("🔐 Moved {} LLM API keys from the settings file to the system keychain", in_file.len()),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    for (agent_type, config) in settings.agents.iter_mut() {
+        if matches!(config, LlmProviderConfig::Ollama { .. }) || config.api_key().is_some() {
+            continue;
+        }
+        config.set_api_key(load_api_key(agent_type));
+    }
+    settings
+}
+
+pub fn build_provider(config: &LlmProviderConfig, timeout: Duration) -> Box<dyn LlmProvider> {
+    match config {
+        LlmProviderConfig::Ollama { base_url, model } => Box::new(OllamaProvider {
+            base_url: base_url.clone().unwrap_or_else(crate::ollama::ollama_base_url),
+            model: model.clone(),
+            timeout,
+        }),
+        LlmProviderConfig::OpenAiCompatible { base_url, api_key, model } => Box::new(OpenAiCompatibleProvider {
+            base_url: base_url.clone(),
+            api_key: api_key.clone(),
+            model: model.clone(),
+            timeout,
+        }),
+        LlmProviderConfig::Anthropic { api_key, model, base_url } => Box::new(AnthropicProvider {
+            base_url: base_url.clone().unwrap_or_else(|| ANTHROPIC_BASE_URL.to_string()),
+            api_key: api_key.clone(),
+            model: model.clone(),
+            timeout,
+        }),
+    }
+}
+
+/// The provider configured for an agent type, or None when it uses the built-in Ollama path.
+pub fn provider_for_agent(agent_type: &str) -> Option<Box<dyn LlmProvider>> {
+    let settings = load_settings();
+    let config = settings.agents.get(agent_type).filter(|config| !config.is_builtin_ollama())?;
+    Some(build_provider(config, Duration::from_secs(settings.request_timeout_secs)))
+}
+
+/// Stream an agent reply from a configured provider onto the ollama-stream-{session} channel.
+pub(crate) async fn stream_agent_reply(
+    app_handle: &AppHandle,
+    provider: &dyn LlmProvider,
+    agent_model: &str,
+    prompt: String,
+    system_prompt: &str,
+    context: Option<Vec<ChatContextMessage>>,
+    session_id: &str,
+    agent_type: &str,
+    options: Option<GenerationOptions>,
+) -> Result<String, String> {
+    let channel = format!("ollama-stream-{}", session_id);
+    let messages = build_chat_messages(system_prompt, prompt, context);
+    let prompt_tokens = messages.iter().map(|message| estimate_tokens(&message.content)).sum();
+    let options = build_ollama_options(agent_type, options.as_ref(), prompt_tokens);
+    let model = provider.model(agent_model);
+
+    println!("🤖 Starting {} agent ({}) streaming from {} for session: {}", agent_type, model, provider.name(), session_id);
+    crate::event_throttle::emit_critical(app_handle, &channel, serde_json::json!({
+        "type": "start",
+        "model": model,
+        "agent_type": agent_type,
+        "endpoint": provider.name(),
+        "provenance": {
+            "provider": provider.name(),
+            "modelTag": model,
+            "generationOptions": &options,
+        }
+    })).map_err(|e| format!("Failed to emit start event: {}", e))?;
+
+    let cancel = crate::ollama::register_session(session_id);
+    let turn = ChatTurn { messages: &messages, options: &options, cancel: &cancel };
+    let mut on_text = |text: &str| {
+        crate::event_throttle::emit_throttled(app_handle, &channel, serde_json::json!({
+            "type": "chunk",
+            "text": text,
+            "done": false
+        }));
+    };
+    let result = provider.stream_chat(agent_model, turn, &mut on_text).await;
+    crate::ollama::cleanup_session(session_id);
+
+    let event = match &result {
+        Ok(_) if cancel.is_cancelled() => serde_json::json!({ "type": "cancelled", "message": "Response cancelled by user" }),
+        Ok(_) => {
+            println!("✅ {} agent streaming completed via {} for session: {}", agent_type, provider.name(), session_id);
+            serde_json::json!({ "type": "complete" })
+        }
+        Err(e) => {
+            eprintln!("❌ {} agent streaming via {} failed: {}", agent_type, provider.name(), e);
+            serde_json::json!({ "type": "error", "error": e })
+        }
+    };
+    if let Err(e) = crate::event_throttle::emit_critical(app_handle, &channel, event) {
+        eprintln!("Failed to emit final stream event: {}", e);
+    }
+    result
+}
+
+fn validate(agent_type: &str, config: &LlmProviderConfig) -> Result<(), String> {
+    let blank = |value: &str| value.trim().is_empty();
+    match config {
+        LlmProviderConfig::Ollama { base_url, model } => {
+            if base_url.as_deref().is_some_and(blank) || model.as_deref().is_some_and(blank) {
+                return Err(format!("{}: leave Ollama overrides unset instead of empty", agent_type));
+            }
+        }
+        LlmProviderConfig::OpenAiCompatible { base_url, model, .. } => {
+            if blank(base_url) || blank(model) {
+                return Err(format!("{}: an OpenAI-compatible provider needs a base URL and a model", agent_type));
+            }
+        }
+        LlmProviderConfig::Anthropic { api_key, model, .. } => {
+            if blank(api_key) || blank(model) {
+                return Err(format!("{}: Anthropic needs an API key and a model", agent_type));
+            }
+        }
+    }
+    Ok(())
+}
+
+// A masked key from get_llm_settings coming back unchanged means the saved one
+fn unmask_keys(settings: &mut LlmSettings, current: &LlmSettings) {
+    for (agent_type, config) in settings.agents.iter_mut() {
+        let saved = current.agents.get(agent_type).and_then(|config| config.api_key());
+        if let (Some(key), Some(saved)) = (config.api_key(), saved) {
+            if key == crate::ollama_http::mask_token(saved) {
+                config.set_api_key(Some(saved.to_string()));
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_llm_settings() -> Result<LlmSettings, String> {
+    let mut settings = load_settings();
+    for config in settings.agents.values_mut() {
+        let masked = config.api_key().map(crate::ollama_http::mask_token);
+        config.set_api_key(masked);
+    }
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn save_llm_settings(mut settings: LlmSettings) -> Result<(), String> {
+    if settings.request_timeout_secs == 0 {
+        return Err("Request timeout must be at least one second".to_string());
+    }
+    let current = load_settings();
+    unmask_keys(&mut settings, &current);
+    for (agent_type, config) in &settings.agents {
+        validate(agent_type, config)?;
+    }
+
+    for (agent_type, config) in &settings.agents {
+        store_api_key(agent_type, config.api_key())?;
+    }
+    // Agents that had a key and were dropped from the settings
+    for (agent_type, config) in &current.agents {
+        if config.api_key().is_some() && !settings.agents.contains_key(agent_type) {
+            store_api_key(agent_type, None)?;
+        }
+    }
+    let path = write_settings_file(&settings)?;
+    if let Ok(mut cached) = SETTINGS.lock() {
+        *cached = Some(settings);
+    }
+
+    println!("💾 LLM provider settings saved to: {:?}", path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_lines_from_each_provider() {
+        let openai = "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}";
+        assert!(matches!(parse_openai_line(openai), Ok(StreamLine::Text(text)) if text == "Hel"));
+        assert!(matches!(parse_openai_line("data: [DONE]"), Ok(StreamLine::Done)));
+        assert!(matches!(parse_openai_line(": keep-alive"), Ok(StreamLine::Skip)));
+
+        let anthropic = "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"lo\"}}";
+        assert!(matches!(parse_anthropic_line(anthropic), Ok(StreamLine::Text(text)) if text == "lo"));
+        assert!(matches!(parse_anthropic_line("event: message_stop"), Ok(StreamLine::Skip)));
+        assert!(matches!(parse_anthropic_line("data: {\"type\":\"message_stop\"}"), Ok(StreamLine::Done)));
+        assert!(parse_anthropic_line("data: {\"type\":\"error\",\"error\":{\"message\":\"overloaded\"}}").is_err());
+    }
+
+    #[test]
+    fn test_anthropic_messages_split_system_and_merge_turns() {
+        let messages = vec![
+            ChatMessage::new("system", "Be brief."),
+            ChatMessage::new("assistant", "Earlier answer"),
+            ChatMessage::new("tool", "42"),
+            ChatMessage::new("user", "And now?"),
+        ];
+        let (system, turns) = anthropic_messages(&messages);
+
+        assert_eq!(system, "Be brief.");
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[0]["role"], "user");
+        assert_eq!(turns[1]["role"], "assistant");
+        assert_eq!(turns[2]["content"], "tool: 42\n\nAnd now?");
+    }
+
+    #[test]
+    fn test_masked_keys_keep_the_saved_key() {
+        let anthropic = |api_key: &str| LlmProviderConfig::Anthropic {
+            api_key: api_key.to_string(),
+            model: "claude".to_string(),
+            base_url: None,
+        };
+        let current = LlmSettings {
+            agents: HashMap::from([("coding".to_string(), anthropic("sk-ant-0123456789wxyz"))]),
+            ..Default::default()
+        };
+
+        let mut unchanged = LlmSettings {
+            agents: HashMap::from([("coding".to_string(), anthropic("••••••••wxyz"))]),
+            ..Default::default()
+        };
+        unmask_keys(&mut unchanged, &current);
+        assert_eq!(unchanged.agents["coding"].api_key(), Some("sk-ant-0123456789wxyz"));
+
+        let mut replaced = LlmSettings {
+            agents: HashMap::from([("coding".to_string(), anthropic("sk-ant-new-key-5678"))]),
+            ..Default::default()
+        };
+        unmask_keys(&mut replaced, &current);
+        assert_eq!(replaced.agents["coding"].api_key(), Some("sk-ant-new-key-5678"));
+    }
+}
@@ -0,0 +1,150 @@
+// src-tauri/src/mcp/capabilities.rs
+// Which computer use tools actually work in the current desktop session, so the planner and the
+// UI can avoid offering tools that would only fail or be simulated.
+use crate::mcp::types::*;
+
+struct Backends {
+    session_type: String,
+    screenshot: Option<String>,
+    input: Option<String>,
+    cursor: bool,
+    ocr: bool,
+    windows: bool,
+    accessibility: bool,
+    missing_input: &'static str,
+    missing_screenshot: &'static str,
+}
+
+#[cfg(target_os = "windows")]
+fn detect_backends() -> Backends {
+    Backends {
+        session_type: "windows".to_string(),
+        screenshot: Some("xcap".to_string()),
+        input: Some("sendinput".to_string()),
+        cursor: true,
+        ocr: true,
+        windows: true,
+        accessibility: true,
+        missing_input: "",
+        missing_screenshot: "",
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_backends() -> Backends {
+    use crate::mcp::linux::{self, DisplaySession};
+    
+    let session = linux::display_session();
+    let screenshot = match session {
+        DisplaySession::Headless => None,
+        DisplaySession::Wayland => Some("xdg-desktop-portal".to_string()),
+        DisplaySession::X11 => Some("xcap".to_string()),
+    };
+    Backends {
+        session_type: match session {
+            DisplaySession::X11 => "x11",
+            DisplaySession::Wayland => "wayland",
+            DisplaySession::Headless => "headless",
+        }.to_string(),
+        // xcap's portal path asks the user on first use; the CLI fallback is reported alongside it
+        screenshot: screenshot.map(|primary| match linux::screenshot_fallback() {
+            Some(fallback) => format!("{} (fallback: {})", primary, fallback),
+            None => primary,
+        }),
+        input: linux::input_backend().map(|backend| format!("{:?}", backend).to_lowercase()),
+        cursor: linux::cursor_available(),
+        ocr: false,
+        windows: session == DisplaySession::X11 && linux::command_available("wmctrl"),
+        accessibility: false,
+        missing_input: match session {
+            DisplaySession::Wayland => "Wayland needs the RemoteDesktop portal, or ydotool with ydotoold running or /dev/uinput access",
+            _ => "Install xdotool (or ydotool) to send input",
+        },
+        missing_screenshot: "No graphical session to capture",
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_backends() -> Backends {
+    Backends {
+        session_type: "macos".to_string(),
+        screenshot: Some("coregraphics".to_string()),
+        input: crate::mcp::macos::input_permitted().then(|| "cgevent".to_string()),
+        cursor: true,
+        ocr: true,
+        windows: false,
+        accessibility: crate::mcp::macos::input_permitted(),
+        missing_input: "Grant Accessibility permission in System Settings › Privacy & Security › Accessibility to send input",
+        missing_screenshot: "",
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn detect_backends() -> Backends {
+    Backends {
+        session_type: std::env::consts::OS.to_string(),
+        screenshot: Some("xcap".to_string()),
+        input: None,
+        cursor: false,
+        ocr: false,
+        windows: false,
+        accessibility: false,
+        missing_input: "Input is only simulated on this platform",
+        missing_screenshot: "",
+    }
+}
+
+pub fn automation_capabilities(tool_names: &[String]) -> AutomationCapabilities {
+    let backends = detect_backends();
+    let ocr_missing = "OCR is only supported on Windows and macOS currently";
+    let missing_windows = if cfg!(target_os = "linux") {
+        "Window management needs wmctrl in an X11 session"
+    } else {
+        "Window management isn't supported on this platform yet"
+    };
+    let missing_accessibility = if cfg!(target_os = "macos") {
+        "Grant Accessibility permission in System Settings › Privacy & Security › Accessibility to read other apps' controls"
+    } else {
+        "The accessibility tree is only available on Windows and macOS currently"
+    };
+    
+    let tools = tool_names.iter().map(|name| {
+        let needs_input = matches!(name.as_str(), "click" | "type" | "scroll" | "key_press" | "drag" | "move_mouse" | "click_at" | "click_on_text" | "click_and_type" | "click_element");
+        let needs_screenshot = matches!(name.as_str(), "take_screenshot" | "find_text" | "wait_for_text" | "debug_ocr" | "click_on_text" | "click_and_type");
+        let needs_ocr = matches!(name.as_str(), "find_text" | "wait_for_text" | "debug_ocr" | "click_on_text" | "click_and_type");
+        let needs_cursor = name == "get_cursor_position";
+        let needs_windows = matches!(name.as_str(), "list_windows" | "focus_window" | "move_resize_window");
+        let needs_accessibility = matches!(name.as_str(), "get_ui_elements" | "click_element");
+        
+        let reason = if needs_screenshot && backends.screenshot.is_none() {
+            Some(backends.missing_screenshot)
+        } else if needs_ocr && !backends.ocr {
+            Some(ocr_missing)
+        } else if needs_input && backends.input.is_none() {
+            Some(backends.missing_input)
+        } else if needs_cursor && !backends.cursor {
+            Some("The pointer position isn't exposed in this session")
+        } else if needs_windows && !backends.windows {
+            Some(missing_windows)
+        } else if needs_accessibility && !backends.accessibility {
+            Some(missing_accessibility)
+        } else {
+            None
+        };
+        
+        ToolCapability {
+            tool_name: name.clone(),
+            functional: reason.is_none(),
+            reason: reason.map(str::to_string),
+        }
+    }).collect();
+    
+    AutomationCapabilities {
+        platform: std::env::consts::OS.to_string(),
+        session_type: backends.session_type,
+        screenshot_backend: backends.screenshot,
+        input_backend: backends.input,
+        ocr_available: backends.ocr,
+        tools,
+    }
+}
@@ -92,9 +92,13 @@ pub async fn execute_mcp_tool(
     parameters: serde_json::Value,
     sessions: State<'_, MCPSessionManager>,
 ) -> Result<ToolExecutionResult, String> {
-    let sessions_guard = sessions.lock().await;
-    let session = sessions_guard.get(&session_id)
-        .ok_or(format!("Session not found: {}", session_id))?;
+    // Don't hold the session map while the tool runs; approvals need it
+    let session = {
+        let sessions_guard = sessions.lock().await;
+        sessions_guard.get(&session_id)
+            .cloned()
+            .ok_or(format!("Session not found: {}", session_id))?
+    };
     
     session.execute_tool(&tool_name, parameters).await
 }
@@ -102,8 +106,10 @@ pub async fn execute_mcp_tool(
 #[tauri::command]
 pub async fn respond_to_mcp_approval(
     session_id: String,
+    approval_id: String,
     approved: bool,
     reason: Option<String>,
+    remember: Option<ApprovalScope>,
     sessions: State<'_, MCPSessionManager>,
 ) -> Result<(), String> {
     let sessions_guard = sessions.lock().await;
@@ -111,9 +117,11 @@ pub async fn respond_to_mcp_approval(
         .ok_or(format!("Session not found: {}", session_id))?;
     
     let response = ToolApprovalResponse {
+        approval_id,
         session_id: session_id.clone(),
         approved,
         reason,
+        remember: remember.unwrap_or_default(),
     };
     
     session.handle_approval_response(response).await
@@ -146,6 +154,24 @@ pub async fn list_active_mcp_sessions(
     Ok(session_infos)
 }
 
+/// Approvals still waiting for an answer, across every session. Lets a window opened after the
+/// mcp_approval_request event catch up.
+#[tauri::command]
+pub async fn list_pending_mcp_approvals(
+    sessions: State<'_, MCPSessionManager>,
+) -> Result<Vec<ToolApprovalRequest>, String> {
+    let sessions_guard = sessions.lock().await;
+    let mut requests = Vec::new();
+    
+    for session in sessions_guard.values() {
+        let pending = session.pending_approvals.lock().await;
+        requests.extend(pending.values().map(|approval| approval.request.clone()));
+    }
+    
+    requests.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    Ok(requests)
+}
+
 #[tauri::command]
 pub async fn get_mcp_tool_schema(
     session_id: String,
@@ -184,9 +210,13 @@ pub async fn create_execution_plan(
     app_handle: AppHandle,
     sessions: State<'_, MCPSessionManager>,
 ) -> Result<ToolExecutionPlan, String> {
-    let sessions_guard = sessions.lock().await;
-    let session = sessions_guard.get(&session_id)
-        .ok_or(format!("Session not found: {}", session_id))?;
+    // Planning calls the LLM and vision models, so don't hold the session map meanwhile
+    let session = {
+        let sessions_guard = sessions.lock().await;
+        sessions_guard.get(&session_id)
+            .cloned()
+            .ok_or(format!("Session not found: {}", session_id))?
+    };
     
     // Get available tools for the LLM to plan with
     let available_tools = session.get_available_tools().await;
@@ -205,17 +235,188 @@ pub async fn approve_execution_plan(
     Ok(())
 }
 
+// Read-only steps that can run side by side when max_parallel isn't given
+const DEFAULT_PLAN_PARALLELISM: usize = 3;
+
 #[tauri::command]
 pub async fn execute_approved_plan(
     plan_id: String,
+    max_parallel: Option<usize>,
+    app_handle: AppHandle,
     sessions: State<'_, MCPSessionManager>,
 ) -> Result<Vec<ToolExecutionResult>, String> {
-    // Execute the approved plan step by step
     println!("🚀 Executing plan: {}", plan_id);
     
-    // TODO: Implement step-by-step execution with context passing
-    Ok(vec![])
+    // Don't hold the session map while the plan runs; approvals need it
+    let (session, plan) = {
+        let sessions_guard = sessions.lock().await;
+        let mut found = None;
+        for session in sessions_guard.values() {
+            if let Some(plan) = session.get_plan(&plan_id).await {
+                found = Some((session.clone(), plan));
+                break;
+            }
+        }
+        found.ok_or(format!("Plan not found: {}", plan_id))?
+    };
+    
+    let results = session.execute_plan(&plan, max_parallel.unwrap_or(DEFAULT_PLAN_PARALLELISM)).await;
+    let failed = results.iter().filter(|r| !r.success).count();
+    println!("🏁 Plan {} finished: {} steps, {} failed", plan_id, results.len(), failed);
+    let body = if failed == 0 {
+        format!("All {} steps completed.", results.len())
+    } else {
+        format!("{} of {} steps failed.", failed, results.len())
+    };
+    crate::notifications::notify(
+        &app_handle,
+        crate::notifications::BackgroundNotification::new(
+            "plan",
+            "Plan finished",
+            body,
+            Some(format!("enteract://plan/{}", plan_id)),
+        ),
+    );
+    Ok(results)
+}
+// Find a stored plan in any active session
+async fn find_plan(
+    plan_id: &str,
+    sessions: &State<'_, MCPSessionManager>,
+) -> Result<ToolExecutionPlan, String> {
+    let sessions_guard = sessions.lock().await;
+    for session in sessions_guard.values() {
+        if let Some(plan) = session.get_plan(plan_id).await {
+            return Ok(plan);
+        }
+    }
+    Err(format!("Plan not found: {}", plan_id))
+}
+
+#[tauri::command]
+pub async fn export_plan(
+    plan_id: String,
+    path: String,
+    sessions: State<'_, MCPSessionManager>,
+) -> Result<String, String> {
+    let plan = find_plan(&plan_id, &sessions).await?;
+    let bundle = crate::share_format::seal("automation_plan", &plan)?;
+    crate::share_format::write_bundle(&bundle, std::path::Path::new(&path))?;
+    
+    println!("📤 Exported plan {} to {}", plan_id, path);
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn import_plan(
+    app_handle: AppHandle,
+    path: String,
+    session_id: String,
+    sessions: State<'_, MCPSessionManager>,
+) -> Result<ToolExecutionPlan, String> {
+    let bundle = crate::share_format::read_bundle(std::path::Path::new(&path))?;
+    let mut plan: ToolExecutionPlan = crate::share_format::open(&app_handle, bundle, "automation_plan").await?;
+    
+    let sessions_guard = sessions.lock().await;
+    let session = sessions_guard.get(&session_id)
+        .ok_or(format!("Session not found: {}", session_id))?;
+    
+    // Imported plans belong to the importing session and always need fresh approval
+    plan.plan_id = uuid::Uuid::new_v4().to_string();
+    plan.session_id = session_id.clone();
+    plan.created_at = chrono::Utc::now().to_rfc3339();
+    plan.requires_approval = true;
+    
+    // The file's danger levels are replaced with the tools' own, and the plan's risk follows them
+    crate::mcp::server::validate_plan(&mut plan.steps, &session.get_available_tools().await)?;
+    plan.overall_risk = crate::mcp::server::plan_risk(&plan.steps);
+    
+    session.store_plan(plan.clone()).await;
+    session.log(
+        LogLevel::Info,
+        format!("Imported execution plan with {} steps from {}", plan.steps.len(), path),
+        None,
+    ).await;
+    
+    Ok(plan)
 }
+
+#[tauri::command]
+pub async fn export_mcp_session(
+    session_id: String,
+    path: String,
+    sessions: State<'_, MCPSessionManager>,
+) -> Result<String, String> {
+    let session = {
+        let sessions_guard = sessions.lock().await;
+        sessions_guard.get(&session_id)
+            .cloned()
+            .ok_or(format!("Session not found: {}", session_id))?
+    };
+    
+    let report = session.build_report().await;
+    crate::mcp::report::write_report(&report, std::path::Path::new(&path))?;
+    
+    println!("📤 Exported MCP session {} ({} steps) to {}", session_id, report.steps.len(), path);
+    Ok(path)
+}
+
+/// Remembered approval rules: persisted ones plus the given session's (or every session's).
+#[tauri::command]
+pub async fn list_mcp_approval_rules(
+    session_id: Option<String>,
+    sessions: State<'_, MCPSessionManager>,
+) -> Result<Vec<ApprovalRule>, String> {
+    let mut rules = crate::mcp::policy::load_persisted_rules();
+    
+    let sessions_guard = sessions.lock().await;
+    for session in sessions_guard.values() {
+        if session_id.as_ref().map_or(true, |id| id == &session.id) {
+            rules.extend(session.approval_rules.lock().await.iter().cloned());
+        }
+    }
+    
+    Ok(rules)
+}
+
+#[tauri::command]
+pub async fn revoke_mcp_approval_rule(
+    rule_id: String,
+    sessions: State<'_, MCPSessionManager>,
+) -> Result<bool, String> {
+    {
+        let sessions_guard = sessions.lock().await;
+        for session in sessions_guard.values() {
+            let mut rules = session.approval_rules.lock().await;
+            let before = rules.len();
+            rules.retain(|rule| rule.id != rule_id);
+            if rules.len() != before {
+                println!("🔒 Revoked session approval rule {}", rule_id);
+                return Ok(true);
+            }
+        }
+    }
+    
+    let revoked = crate::mcp::policy::revoke_persisted(&rule_id)?;
+    if revoked {
+        println!("🔒 Revoked approval rule {}", rule_id);
+    }
+    Ok(revoked)
+}
+
+/// Which tools work in the current desktop session (session type, screenshot and input backends).
+#[tauri::command]
+pub async fn get_automation_capabilities() -> Result<AutomationCapabilities, String> {
+    let mut tool_names: Vec<String> = crate::mcp::tools::builtin_tools().into_keys().collect();
+    tool_names.extend(crate::plugins::mcp_tools().into_iter().map(|(name, _)| name));
+    tool_names.sort();
+    // The portal answers over D-Bus, so it's probed here rather than in the synchronous detection
+    #[cfg(target_os = "linux")]
+    crate::mcp::linux_portal::available().await;
+    
+    Ok(crate::mcp::capabilities::automation_capabilities(&tool_names))
+}
+
 // Initialize the MCP session manager
 pub fn create_mcp_session_manager() -> MCPSessionManager {
     Arc::new(Mutex::new(HashMap::new()))
@@ -0,0 +1,449 @@
+// src-tauri/src/mcp/files.rs
+// Filesystem tools for execution plans ("save this to a file"). They only exist in sessions whose
+// config lists root directories, and every path is resolved (following symlinks) and checked to be
+// inside one of those roots before it's touched. Reads and writes are capped at max_file_bytes.
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+
+use crate::mcp::tools::ComputerUseTool;
+use crate::mcp::types::*;
+
+// Entries returned by one list_directory call
+const MAX_LISTED_ENTRIES: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct FileSandbox {
+    roots: Vec<PathBuf>,
+    max_bytes: u64,
+}
+
+impl FileSandbox {
+    /// The session's sandbox, or None when it has no usable root directories.
+    pub fn from_config(config: &MCPSessionConfig) -> Option<Self> {
+        let roots: Vec<PathBuf> = config.file_roots.iter()
+            .filter_map(|root| match Path::new(root).canonicalize() {
+                Ok(path) if path.is_dir() => Some(path),
+                _ => {
+                    log::warn!("Ignoring MCP file root {} (not an existing directory)", root);
+                    None
+                }
+            })
+            .collect();
+        if roots.is_empty() {
+            return None;
+        }
+        Some(Self { roots, max_bytes: config.max_file_bytes.max(1) })
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    /// `path` as an absolute path inside a root. Relative paths are taken from the first root. A
+    /// path that doesn't exist yet must name a file in an existing folder, and not be a symlink.
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let path = path.trim();
+        if path.is_empty() {
+            return Err("No path given".to_string());
+        }
+        let requested = Path::new(path);
+        let requested = if requested.is_absolute() { requested.to_path_buf() } else { self.roots[0].join(requested) };
+
+        let resolved = if requested.exists() {
+            requested.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", path, e))?
+        } else {
+            // A dangling symlink doesn't exist() either, but creating the file would follow it
+            // wherever it points
+            if requested.symlink_metadata().is_ok() {
+                return Err(format!("{} is a link to a file that doesn't exist", path));
+            }
+            let name = match requested.components().next_back() {
+                Some(Component::Normal(name)) => name.to_os_string(),
+                _ => return Err(format!("{} doesn't name a file", path)),
+            };
+            let parent = requested.parent()
+                .and_then(|parent| parent.canonicalize().ok())
+                .ok_or_else(|| format!("The folder for {} doesn't exist", path))?;
+            parent.join(name)
+        };
+
+        if !self.contains(&resolved) {
+            return Err(format!("{} is outside the folders this session may use", path));
+        }
+        Ok(resolved)
+    }
+
+    fn roots_display(&self) -> Vec<String> {
+        self.roots.iter().map(|root| root.display().to_string()).collect()
+    }
+}
+
+/// The filesystem tools for a session, keyed by name; empty when the session has no file roots.
+pub fn filesystem_tools(config: &MCPSessionConfig) -> HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> {
+    let mut tools: HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> = HashMap::new();
+    if let Some(sandbox) = FileSandbox::from_config(config) {
+        tools.insert("read_file".to_string(), Box::new(ReadFileTool { sandbox: sandbox.clone() }));
+        tools.insert("write_file".to_string(), Box::new(WriteFileTool { sandbox: sandbox.clone() }));
+        tools.insert("list_directory".to_string(), Box::new(ListDirectoryTool { sandbox }));
+    }
+    tools
+}
+
+fn success(tool_name: &str, start_time: Instant, result: serde_json::Value) -> ToolExecutionResult {
+    ToolExecutionResult {
+        success: true,
+        result,
+        error: None,
+        execution_time_ms: start_time.elapsed().as_millis() as u64,
+        tool_name: tool_name.to_string(),
+    }
+}
+
+fn failure(tool_name: &str, start_time: Instant, error_msg: String) -> ToolExecutionResult {
+    ToolExecutionResult {
+        success: false,
+        result: serde_json::json!({"success": false, "error": error_msg}),
+        error: Some(error_msg),
+        execution_time_ms: start_time.elapsed().as_millis() as u64,
+        tool_name: tool_name.to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReadFileParams {
+    path: String,
+}
+
+#[derive(Clone)]
+pub struct ReadFileTool {
+    sandbox: FileSandbox,
+}
+
+impl ReadFileTool {
+    fn read(&self, path: &str) -> Result<(PathBuf, String), String> {
+        let path = self.sandbox.resolve(path)?;
+        let metadata = std::fs::metadata(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if !metadata.is_file() {
+            return Err(format!("{} is not a file", path.display()));
+        }
+        if metadata.len() > self.sandbox.max_bytes {
+            return Err(format!("{} is {} bytes; the limit is {}", path.display(), metadata.len(), self.sandbox.max_bytes));
+        }
+        let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let content = String::from_utf8(bytes).map_err(|_| format!("{} is not a text file", path.display()))?;
+        Ok((path, content))
+    }
+}
+
+#[async_trait]
+impl ComputerUseTool for ReadFileTool {
+    fn name(&self) -> &str { "read_file" }
+
+    fn description(&self) -> String {
+        format!("Read a UTF-8 text file inside the allowed folders ({})", self.sandbox.roots_display().join(", "))
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Medium }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File path, absolute or relative to the first allowed folder"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "content": { "type": "string" },
+                "bytes": { "type": "integer" }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        let params: ReadFileParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for read_file: {}", e))?;
+
+        log::info!("Session {}: Reading file {}", session_id, params.path);
+
+        Ok(match self.read(&params.path) {
+            Ok((path, content)) => success(self.name(), start_time, serde_json::json!({
+                "success": true,
+                "path": path.display().to_string(),
+                "bytes": content.len(),
+                "content": content
+            })),
+            Err(e) => failure(self.name(), start_time, e),
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Deserialize)]
+struct WriteFileParams {
+    path: String,
+    content: String,
+    #[serde(default)]
+    append: bool,
+}
+
+#[derive(Clone)]
+pub struct WriteFileTool {
+    sandbox: FileSandbox,
+}
+
+impl WriteFileTool {
+    fn write(&self, params: &WriteFileParams) -> Result<PathBuf, String> {
+        use std::io::Write;
+
+        let path = self.sandbox.resolve(&params.path)?;
+        if path.is_dir() {
+            return Err(format!("{} is a folder", path.display()));
+        }
+        let existing = if params.append { std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0) } else { 0 };
+        let total = existing + params.content.len() as u64;
+        if total > self.sandbox.max_bytes {
+            return Err(format!("The file would be {} bytes; the limit is {}", total, self.sandbox.max_bytes));
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(params.append)
+            .truncate(!params.append)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        file.write_all(params.content.as_bytes())
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl ComputerUseTool for WriteFileTool {
+    fn name(&self) -> &str { "write_file" }
+
+    fn description(&self) -> String {
+        format!("Create, overwrite or append to a text file inside the allowed folders ({})", self.sandbox.roots_display().join(", "))
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Critical }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File path, absolute or relative to the first allowed folder; its folder must exist"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text to write"
+                },
+                "append": {
+                    "type": "boolean",
+                    "description": "Add to the end of the file instead of replacing it",
+                    "default": false
+                }
+            },
+            "required": ["path", "content"]
+        })
+    }
+
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "bytes_written": { "type": "integer" }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        let params: WriteFileParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for write_file: {}", e))?;
+
+        log::info!("Session {}: Writing {} bytes to {} (append: {})", session_id, params.content.len(), params.path, params.append);
+
+        Ok(match self.write(&params) {
+            Ok(path) => success(self.name(), start_time, serde_json::json!({
+                "success": true,
+                "path": path.display().to_string(),
+                "bytes_written": params.content.len(),
+                "message": format!("Wrote {} bytes to {}", params.content.len(), path.display())
+            })),
+            Err(e) => failure(self.name(), start_time, e),
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Deserialize)]
+struct ListDirectoryParams {
+    path: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct ListDirectoryTool {
+    sandbox: FileSandbox,
+}
+
+impl ListDirectoryTool {
+    fn list(&self, path: Option<&str>) -> Result<serde_json::Value, String> {
+        // Without a path, the allowed folders themselves are the listing
+        let Some(path) = path else {
+            let roots: Vec<serde_json::Value> = self.sandbox.roots.iter()
+                .map(|root| serde_json::json!({ "name": root.display().to_string(), "path": root.display().to_string(), "is_dir": true }))
+                .collect();
+            return Ok(serde_json::json!({ "success": true, "entries": roots, "truncated": false }));
+        };
+
+        let dir = self.sandbox.resolve(path)?;
+        let mut entries: Vec<serde_json::Value> = Vec::new();
+        let mut truncated = false;
+        for entry in std::fs::read_dir(&dir).map_err(|e| format!("Failed to list {}: {}", dir.display(), e))?.flatten() {
+            if entries.len() == MAX_LISTED_ENTRIES {
+                truncated = true;
+                break;
+            }
+            let metadata = entry.metadata().ok();
+            entries.push(serde_json::json!({
+                "name": entry.file_name().to_string_lossy(),
+                "path": entry.path().display().to_string(),
+                "is_dir": metadata.as_ref().map_or(false, |m| m.is_dir()),
+                "size": metadata.as_ref().filter(|m| m.is_file()).map(|m| m.len()),
+                "modified": metadata.and_then(|m| m.modified().ok())
+                    .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339()),
+            }));
+        }
+        entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        Ok(serde_json::json!({
+            "success": true,
+            "path": dir.display().to_string(),
+            "entries": entries,
+            "truncated": truncated
+        }))
+    }
+}
+
+#[async_trait]
+impl ComputerUseTool for ListDirectoryTool {
+    fn name(&self) -> &str { "list_directory" }
+
+    fn description(&self) -> String {
+        format!("List the files and folders in a folder inside the allowed folders ({})", self.sandbox.roots_display().join(", "))
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Folder path, absolute or relative to the first allowed folder (omit to list the allowed folders)"
+                }
+            }
+        })
+    }
+
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "entries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "path": { "type": "string" },
+                            "is_dir": { "type": "boolean" },
+                            "size": { "type": "integer" },
+                            "modified": { "type": "string" }
+                        }
+                    }
+                },
+                "truncated": { "type": "boolean" }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        let params: ListDirectoryParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for list_directory: {}", e))?;
+
+        log::info!("Session {}: Listing directory {:?}", session_id, params.path);
+
+        Ok(match self.list(params.path.as_deref()) {
+            Ok(result) => success(self.name(), start_time, result),
+            Err(e) => failure(self.name(), start_time, e),
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sandbox_keeps_paths_inside_its_roots() {
+        let base = std::env::temp_dir().join(format!("enteract-files-test-{}", uuid::Uuid::new_v4()));
+        let root = base.join("notes");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(base.join("secret.txt"), "outside").unwrap();
+
+        let config = MCPSessionConfig {
+            file_roots: vec![root.display().to_string(), base.join("missing").display().to_string()],
+            ..MCPSessionConfig::default()
+        };
+        let sandbox = FileSandbox::from_config(&config).unwrap();
+        assert_eq!(sandbox.roots.len(), 1);
+
+        let root = root.canonicalize().unwrap();
+        assert_eq!(sandbox.resolve("todo.md").unwrap(), root.join("todo.md"));
+        assert!(sandbox.resolve("../secret.txt").is_err());
+        assert!(sandbox.resolve(&base.join("secret.txt").display().to_string()).is_err());
+        assert!(sandbox.resolve("sub/new.md").is_err()); // folder doesn't exist
+        assert!(sandbox.resolve("..").is_err());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(base.join("planted.txt"), root.join("link.txt")).unwrap();
+            assert!(sandbox.resolve("link.txt").is_err());
+            assert!(!base.join("planted.txt").exists());
+        }
+
+        assert!(FileSandbox::from_config(&MCPSessionConfig::default()).is_none());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
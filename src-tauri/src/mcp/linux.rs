@@ -0,0 +1,723 @@
+// src-tauri/src/mcp/linux.rs
+// Linux backends for the computer use tools. Screenshots go through xcap (X11, or the XDG desktop
+// portal on Wayland) with grim/gnome-screenshot/import as fallbacks. Input is injected with
+// xdotool on X11; on Wayland it goes through the RemoteDesktop portal (libei, see linux_portal.rs)
+// when the desktop offers it, and through ydotool (uinput) where that is permitted otherwise.
+// The cursor position on Wayland comes from compositors that expose it over IPC.
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+use crate::mcp::linux_portal::{self, PortalEvent};
+use crate::mcp::types::*;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplaySession {
+    X11,
+    Wayland,
+    Headless,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InputBackend {
+    Xdotool,
+    Portal,
+    Ydotool,
+}
+
+pub fn display_session() -> DisplaySession {
+    let session_type = std::env::var("XDG_SESSION_TYPE").unwrap_or_default().to_lowercase();
+    if session_type == "wayland" || std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        DisplaySession::Wayland
+    } else if session_type == "x11" || std::env::var_os("DISPLAY").is_some() {
+        DisplaySession::X11
+    } else {
+        DisplaySession::Headless
+    }
+}
+
+pub fn command_available(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(name).is_file()))
+        .unwrap_or(false)
+}
+
+/// ydotool needs its daemon's socket, or write access to /dev/uinput to talk to the kernel directly.
+fn ydotool_permitted() -> bool {
+    let socket = std::env::var("YDOTOOL_SOCKET").unwrap_or_else(|_| "/tmp/.ydotool_socket".to_string());
+    Path::new(&socket).exists()
+        || std::fs::OpenOptions::new().write(true).open("/dev/uinput").is_ok()
+}
+
+/// xdotool only reaches X11 clients (XWayland windows on Wayland), so Wayland sessions need the
+/// portal or ydotool. The portal is only reported once `linux_portal::available` has probed it.
+pub fn input_backend() -> Option<InputBackend> {
+    let ydotool = || command_available("ydotool") && ydotool_permitted();
+    match display_session() {
+        DisplaySession::X11 if command_available("xdotool") => Some(InputBackend::Xdotool),
+        DisplaySession::Wayland if linux_portal::known_available() => Some(InputBackend::Portal),
+        DisplaySession::X11 | DisplaySession::Wayland if ydotool() => Some(InputBackend::Ydotool),
+        _ => None,
+    }
+}
+
+/// Whether this session reports the pointer position: xdotool on X11, hyprctl on Hyprland.
+pub fn cursor_available() -> bool {
+    match display_session() {
+        DisplaySession::X11 => command_available("xdotool"),
+        DisplaySession::Wayland => std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() && command_available("hyprctl"),
+        DisplaySession::Headless => false,
+    }
+}
+
+/// Command-line screenshot tool for when xcap can't capture, in order of preference for the session.
+pub fn screenshot_fallback() -> Option<&'static str> {
+    let candidates: &[&'static str] = match display_session() {
+        DisplaySession::Wayland => &["grim", "gnome-screenshot"],
+        DisplaySession::X11 => &["import", "gnome-screenshot"],
+        DisplaySession::Headless => &[],
+    };
+    candidates.iter().copied().find(|tool| command_available(tool))
+}
+
+async fn require_input_backend() -> Result<InputBackend, String> {
+    if display_session() == DisplaySession::Wayland {
+        linux_portal::available().await;
+    }
+    input_backend().ok_or_else(|| match display_session() {
+        DisplaySession::Wayland => "Input injection on Wayland needs the RemoteDesktop portal, or ydotool with ydotoold running or /dev/uinput access".to_string(),
+        DisplaySession::X11 => "Input injection on X11 needs xdotool (or ydotool)".to_string(),
+        DisplaySession::Headless => "No graphical session to send input to".to_string(),
+    })
+}
+
+async fn run(program: &str, args: &[String]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|a| a.to_string()).collect()
+}
+
+pub async fn click(x: i32, y: i32, button: MouseButton, double_click: bool) -> Result<(), String> {
+    let repeat = if double_click { "2" } else { "1" };
+    match require_input_backend().await? {
+        InputBackend::Xdotool => {
+            let button = match button {
+                MouseButton::Left => "1",
+                MouseButton::Middle => "2",
+                MouseButton::Right => "3",
+            };
+            run("xdotool", &args(&["mousemove", &x.to_string(), &y.to_string(), "click", "--repeat", repeat, button])).await?;
+        }
+        InputBackend::Ydotool => {
+            // 0xC0 = press + release of button 0 (left); 0xC1 right, 0xC2 middle
+            let code = match button {
+                MouseButton::Left => "0xC0",
+                MouseButton::Right => "0xC1",
+                MouseButton::Middle => "0xC2",
+            };
+            run("ydotool", &args(&["mousemove", "--absolute", "-x", &x.to_string(), "-y", &y.to_string()])).await?;
+            run("ydotool", &args(&["click", "--repeat", repeat, code])).await?;
+        }
+        InputBackend::Portal => {
+            let code = portal_button(button);
+            let mut events = vec![PortalEvent::MoveTo(x, y)];
+            for _ in 0..if double_click { 2 } else { 1 } {
+                events.extend([PortalEvent::Button(code, true), PortalEvent::Button(code, false)]);
+            }
+            linux_portal::send(&events).await?;
+        }
+    }
+    Ok(())
+}
+
+fn portal_button(button: MouseButton) -> i32 {
+    match button {
+        MouseButton::Left => linux_portal::BTN_LEFT,
+        MouseButton::Right => linux_portal::BTN_RIGHT,
+        MouseButton::Middle => linux_portal::BTN_MIDDLE,
+    }
+}
+
+/// Move the pointer through `path`, pausing `interval` before each point.
+pub async fn move_pointer(path: &[(i32, i32)], interval: std::time::Duration) -> Result<(), String> {
+    match require_input_backend().await? {
+        InputBackend::Xdotool => {
+            let pause = format!("{:.3}", interval.as_secs_f64());
+            let mut chain = Vec::new();
+            for (x, y) in path {
+                if !interval.is_zero() {
+                    chain.extend(args(&["sleep", &pause]));
+                }
+                chain.extend(args(&["mousemove", &x.to_string(), &y.to_string()]));
+            }
+            run("xdotool", &chain).await?;
+        }
+        InputBackend::Ydotool => {
+            for (x, y) in path {
+                tokio::time::sleep(interval).await;
+                run("ydotool", &args(&["mousemove", "--absolute", "-x", &x.to_string(), "-y", &y.to_string()])).await?;
+            }
+        }
+        InputBackend::Portal => {
+            let events: Vec<PortalEvent> = path
+                .iter()
+                .flat_map(|&(x, y)| [PortalEvent::Pause(interval), PortalEvent::MoveTo(x, y)])
+                .collect();
+            linux_portal::send(&events).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Press `button` at `from`, move through `path` pausing `interval` before each point, and
+/// release at the last point. The button is released even if a move fails.
+pub async fn drag(from: (i32, i32), path: &[(i32, i32)], interval: std::time::Duration, button: MouseButton) -> Result<(), String> {
+    let (x, y) = (from.0.to_string(), from.1.to_string());
+    match require_input_backend().await? {
+        InputBackend::Xdotool => {
+            let button = match button {
+                MouseButton::Left => "1",
+                MouseButton::Middle => "2",
+                MouseButton::Right => "3",
+            };
+            // One chained invocation, so the gesture isn't slowed by a process per point
+            let pause = format!("{:.3}", interval.as_secs_f64());
+            let mut chain = args(&["mousemove", &x, &y, "mousedown", button]);
+            for (x, y) in path {
+                chain.extend(args(&["sleep", &pause, "mousemove", &x.to_string(), &y.to_string()]));
+            }
+            chain.extend(args(&["mouseup", button]));
+            if let Err(e) = run("xdotool", &chain).await {
+                let _ = run("xdotool", &args(&["mouseup", button])).await;
+                return Err(e);
+            }
+        }
+        InputBackend::Ydotool => {
+            // 0x40 = press and 0x80 = release of button 0 (left); + 1 right, + 2 middle
+            let offset = match button {
+                MouseButton::Left => 0,
+                MouseButton::Right => 1,
+                MouseButton::Middle => 2,
+            };
+            let move_to = |x: i32, y: i32| args(&["mousemove", "--absolute", "-x", &x.to_string(), "-y", &y.to_string()]);
+            run("ydotool", &move_to(from.0, from.1)).await?;
+            run("ydotool", &args(&["click", &format!("{:#04x}", 0x40 + offset)])).await?;
+            let mut moved = Ok(String::new());
+            for &(x, y) in path {
+                tokio::time::sleep(interval).await;
+                moved = run("ydotool", &move_to(x, y)).await;
+                if moved.is_err() {
+                    break;
+                }
+            }
+            let released = run("ydotool", &args(&["click", &format!("{:#04x}", 0x80 + offset)])).await;
+            moved?;
+            released?;
+        }
+        InputBackend::Portal => {
+            let code = portal_button(button);
+            linux_portal::send(&[PortalEvent::MoveTo(from.0, from.1), PortalEvent::Button(code, true)]).await?;
+            let moves: Vec<PortalEvent> = path
+                .iter()
+                .flat_map(|&(x, y)| [PortalEvent::Pause(interval), PortalEvent::MoveTo(x, y)])
+                .collect();
+            let moved = linux_portal::send(&moves).await;
+            let released = linux_portal::send(&[PortalEvent::Button(code, false)]).await;
+            moved?;
+            released?;
+        }
+    }
+    Ok(())
+}
+
+/// wmctrl drives the window manager over EWMH, which only X11 (and XWayland windows) offer.
+fn require_window_backend() -> Result<(), String> {
+    match display_session() {
+        DisplaySession::X11 if command_available("wmctrl") => Ok(()),
+        DisplaySession::X11 => Err("Window management on X11 needs wmctrl".to_string()),
+        DisplaySession::Wayland => Err("Wayland doesn't let applications manage other windows".to_string()),
+        DisplaySession::Headless => Err("No graphical session to manage windows in".to_string()),
+    }
+}
+
+pub fn process_name(process_id: u32) -> Option<String> {
+    std::fs::read_to_string(format!("/proc/{}/comm", process_id)).ok().map(|name| name.trim().to_string())
+}
+
+/// A line of `wmctrl -lpG`: id, desktop, pid, x, y, width, height, host, then the title.
+fn parse_wmctrl_window(line: &str) -> Option<DesktopWindow> {
+    let mut rest = line;
+    let mut fields = Vec::with_capacity(8);
+    for _ in 0..8 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    // Desktop -1 holds sticky panels and the desktop itself rather than application windows
+    if fields[1] == "-1" {
+        return None;
+    }
+    let process_id: u32 = fields[2].parse().ok()?;
+    Some(DesktopWindow {
+        id: u64::from_str_radix(fields[0].trim_start_matches("0x"), 16).ok()?,
+        title: rest.trim().to_string(),
+        process_id,
+        process_name: process_name(process_id),
+        x: fields[3].parse().ok()?,
+        y: fields[4].parse().ok()?,
+        width: fields[5].parse().ok()?,
+        height: fields[6].parse().ok()?,
+        minimized: false,
+    })
+}
+
+pub async fn list_windows() -> Result<Vec<DesktopWindow>, String> {
+    require_window_backend()?;
+    let output = run("wmctrl", &args(&["-lpG"])).await?;
+    // wmctrl lists in stacking order bottom to top; tools expect front to back
+    let own_process = std::process::id();
+    Ok(output.lines()
+        .rev()
+        .filter_map(parse_wmctrl_window)
+        .filter(|window| window.process_id != own_process && !window.title.is_empty())
+        .collect())
+}
+
+/// `hyprctl activewindow -j`: address, position, size, title and pid of the focused window.
+fn parse_hyprctl_active_window(json: &str) -> Option<DesktopWindow> {
+    let window: serde_json::Value = serde_json::from_str(json).ok()?;
+    let pair = |key: &str| {
+        let values = window.get(key)?.as_array()?;
+        Some((values.first()?.as_i64()?, values.get(1)?.as_i64()?))
+    };
+    let (x, y) = pair("at")?;
+    let (width, height) = pair("size")?;
+    let process_id = u32::try_from(window.get("pid")?.as_i64()?).ok()?;
+    Some(DesktopWindow {
+        id: window.get("address")
+            .and_then(|address| address.as_str())
+            .and_then(|address| u64::from_str_radix(address.trim_start_matches("0x"), 16).ok())
+            .unwrap_or(0),
+        title: window.get("title")?.as_str()?.to_string(),
+        process_id,
+        process_name: process_name(process_id),
+        x: x as i32,
+        y: y as i32,
+        width: width.max(0) as u32,
+        height: height.max(0) as u32,
+        minimized: false,
+    })
+}
+
+fn run_blocking(program: &str, arguments: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new(program)
+        .args(arguments)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether the focused window can be read: xdotool and wmctrl on X11, hyprctl on Hyprland.
+pub fn require_active_window_backend() -> Result<(), String> {
+    match display_session() {
+        DisplaySession::X11 if command_available("xdotool") && command_available("wmctrl") => Ok(()),
+        DisplaySession::X11 => Err("Finding the focused window on X11 needs xdotool and wmctrl".to_string()),
+        DisplaySession::Wayland if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() && command_available("hyprctl") => Ok(()),
+        DisplaySession::Wayland => Err("This Wayland compositor doesn't report the focused window (Hyprland does, through hyprctl)".to_string()),
+        DisplaySession::Headless => Err("No graphical session to read the focused window from".to_string()),
+    }
+}
+
+/// The focused window, including our own. On X11 xdotool names it and the wmctrl list supplies
+/// the rest. Synchronous, like `cursor_position`, for the window tracking that polls it.
+pub fn active_window() -> Result<DesktopWindow, String> {
+    require_active_window_backend()?;
+    if display_session() == DisplaySession::Wayland {
+        let output = run_blocking("hyprctl", &["activewindow", "-j"])?;
+        return parse_hyprctl_active_window(&output).ok_or_else(|| "No window has focus".to_string());
+    }
+    let id: u64 = run_blocking("xdotool", &["getactivewindow"])?
+        .trim()
+        .parse()
+        .map_err(|_| "No window has focus".to_string())?;
+    run_blocking("wmctrl", &["-lpG"])?
+        .lines()
+        .filter_map(parse_wmctrl_window)
+        .find(|window| window.id == id)
+        .ok_or_else(|| "The focused window isn't an application window".to_string())
+}
+
+pub async fn focus_window(id: u64) -> Result<(), String> {
+    require_window_backend()?;
+    run("wmctrl", &args(&["-ia", &format!("{:#x}", id)])).await?;
+    Ok(())
+}
+
+pub async fn place_window(id: u64, x: i32, y: i32, width: u32, height: u32) -> Result<(), String> {
+    require_window_backend()?;
+    let id = format!("{:#x}", id);
+    // A maximized window ignores new geometry until it's unmaximized
+    run("wmctrl", &args(&["-ir", &id, "-b", "remove,maximized_vert,maximized_horz"])).await?;
+    run("wmctrl", &args(&["-ir", &id, "-e", &format!("0,{},{},{},{}", x, y, width, height)])).await?;
+    Ok(())
+}
+
+pub async fn type_text(text: &str, delay_ms: u64) -> Result<(), String> {
+    let delay = delay_ms.to_string();
+    match require_input_backend().await? {
+        InputBackend::Xdotool => run("xdotool", &args(&["type", "--delay", &delay, "--", text])).await?,
+        InputBackend::Ydotool => run("ydotool", &args(&["type", "--key-delay", &delay, "--", text])).await?,
+        InputBackend::Portal => {
+            let pause = std::time::Duration::from_millis(delay_ms);
+            let events: Vec<PortalEvent> = text
+                .chars()
+                .map(linux_portal::char_keysym)
+                .flat_map(|keysym| [PortalEvent::Keysym(keysym, true), PortalEvent::Keysym(keysym, false), PortalEvent::Pause(pause)])
+                .collect();
+            linux_portal::send(&events).await?;
+            String::new()
+        }
+    };
+    Ok(())
+}
+
+pub async fn scroll(params: &ScrollParams) -> Result<(), String> {
+    let backend = require_input_backend().await?;
+
+    if let (Some(x), Some(y)) = (params.x, params.y) {
+        match backend {
+            InputBackend::Xdotool => run("xdotool", &args(&["mousemove", &x.to_string(), &y.to_string()])).await?,
+            InputBackend::Ydotool => run("ydotool", &args(&["mousemove", "--absolute", "-x", &x.to_string(), "-y", &y.to_string()])).await?,
+            InputBackend::Portal => {
+                linux_portal::send(&[PortalEvent::MoveTo(x, y)]).await?;
+                String::new()
+            }
+        };
+    }
+
+    // No backend here has sub-notch wheel events, so a smooth scroll is one notch at a time
+    let (deltas, interval) = params.wheel_steps(1, 1);
+    match backend {
+        InputBackend::Xdotool => {
+            // X11 maps the wheel to buttons 4-7
+            let button = match params.direction {
+                ScrollDirection::Up => "4",
+                ScrollDirection::Down => "5",
+                ScrollDirection::Left => "6",
+                ScrollDirection::Right => "7",
+            };
+            let repeat = deltas.iter().sum::<i32>().to_string();
+            let delay = interval.as_millis().to_string();
+            let mut click = vec!["click", "--repeat", repeat.as_str()];
+            if !interval.is_zero() {
+                click.extend(["--delay", delay.as_str()]);
+            }
+            click.push(button);
+            run("xdotool", &args(&click)).await?;
+        }
+        InputBackend::Ydotool => {
+            for (i, delta) in deltas.iter().enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(interval).await;
+                }
+                let (dx, dy) = match params.direction {
+                    ScrollDirection::Up => (0, *delta),
+                    ScrollDirection::Down => (0, -delta),
+                    ScrollDirection::Left => (-delta, 0),
+                    ScrollDirection::Right => (*delta, 0),
+                };
+                run("ydotool", &args(&["mousemove", "--wheel", "-x", &dx.to_string(), "-y", &dy.to_string()])).await?;
+            }
+        }
+        InputBackend::Portal => {
+            // Discrete axis steps follow the wheel: positive is down and right
+            let (vertical, sign) = match params.direction {
+                ScrollDirection::Up => (true, -1),
+                ScrollDirection::Down => (true, 1),
+                ScrollDirection::Left => (false, -1),
+                ScrollDirection::Right => (false, 1),
+            };
+            let mut events = Vec::new();
+            for (i, delta) in deltas.iter().enumerate() {
+                if i > 0 {
+                    events.push(PortalEvent::Pause(interval));
+                }
+                events.push(PortalEvent::Scroll { vertical, steps: sign * delta });
+            }
+            linux_portal::send(&events).await?;
+        }
+    }
+    Ok(())
+}
+
+fn xdotool_key_name(key: &str) -> Result<String, String> {
+    let name = match key.to_lowercase().as_str() {
+        "return" | "enter" => "Return",
+        "delete" | "del" => "Delete",
+        "backspace" | "back" => "BackSpace",
+        "tab" => "Tab",
+        "escape" | "esc" => "Escape",
+        "space" => "space",
+        "left" | "leftarrow" => "Left",
+        "right" | "rightarrow" => "Right",
+        "up" | "uparrow" => "Up",
+        "down" | "downarrow" => "Down",
+        "home" => "Home",
+        "end" => "End",
+        "pageup" => "Prior",
+        "pagedown" => "Next",
+        lower if lower.len() > 1 && lower.starts_with('f') && lower[1..].parse::<u8>().map_or(false, |n| (1..=12).contains(&n)) => {
+            return Ok(lower.to_uppercase());
+        }
+        _ if key.chars().count() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric()) => return Ok(key.to_lowercase()),
+        _ => return Err(format!("Unsupported key: {}", key)),
+    };
+    Ok(name.to_string())
+}
+
+// Linux input event codes (linux/input-event-codes.h), which is what ydotool and the portal send
+fn evdev_key_code(key: &str) -> Result<u16, String> {
+    const LETTERS: [u16; 26] = [
+        30, 48, 46, 32, 18, 33, 34, 35, 23, 36, 37, 38, 50, 49, 24, 25, 16, 19, 31, 20, 22, 47, 17, 45, 21, 44,
+    ];
+    const DIGITS: [u16; 10] = [11, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+    let lower = key.to_lowercase();
+    let code = match lower.as_str() {
+        "return" | "enter" => 28,
+        "delete" | "del" => 111,
+        "backspace" | "back" => 14,
+        "tab" => 15,
+        "escape" | "esc" => 1,
+        "space" => 57,
+        "left" | "leftarrow" => 105,
+        "right" | "rightarrow" => 106,
+        "up" | "uparrow" => 103,
+        "down" | "downarrow" => 108,
+        "home" => 102,
+        "end" => 107,
+        "pageup" => 104,
+        "pagedown" => 109,
+        "f11" => 87,
+        "f12" => 88,
+        f if f.len() > 1 && f.starts_with('f') && f[1..].parse::<u16>().map_or(false, |n| (1..=10).contains(&n)) => {
+            58 + f[1..].parse::<u16>().unwrap()
+        }
+        single if single.len() == 1 => {
+            let c = single.chars().next().unwrap();
+            if c.is_ascii_lowercase() {
+                LETTERS[(c as u8 - b'a') as usize]
+            } else if c.is_ascii_digit() {
+                DIGITS[(c as u8 - b'0') as usize]
+            } else {
+                return Err(format!("Unsupported key: {}", key));
+            }
+        }
+        _ => return Err(format!("Unsupported key: {}", key)),
+    };
+    Ok(code)
+}
+
+pub async fn press_key(key: &str, modifiers: &[KeyModifier]) -> Result<(), String> {
+    match require_input_backend().await? {
+        InputBackend::Xdotool => {
+            let mut combo: Vec<String> = modifiers.iter().map(|m| match m {
+                KeyModifier::Ctrl => "ctrl",
+                KeyModifier::Alt => "alt",
+                KeyModifier::Shift => "shift",
+                KeyModifier::Meta => "super",
+            }.to_string()).collect();
+            combo.push(xdotool_key_name(key)?);
+            run("xdotool", &args(&["key", &combo.join("+")])).await?;
+        }
+        InputBackend::Ydotool => {
+            let mut codes: Vec<u16> = modifiers.iter().map(|m| match m {
+                KeyModifier::Ctrl => 29,
+                KeyModifier::Alt => 56,
+                KeyModifier::Shift => 42,
+                KeyModifier::Meta => 125,
+            }).collect();
+            codes.push(evdev_key_code(key)?);
+            // Press everything in order, release in reverse
+            let mut events: Vec<String> = codes.iter().map(|c| format!("{}:1", c)).collect();
+            events.extend(codes.iter().rev().map(|c| format!("{}:0", c)));
+            let mut command = vec!["key".to_string()];
+            command.extend(events);
+            run("ydotool", &command).await?;
+        }
+        InputBackend::Portal => {
+            let mut codes: Vec<i32> = modifiers.iter().map(|m| match m {
+                KeyModifier::Ctrl => 29,
+                KeyModifier::Alt => 56,
+                KeyModifier::Shift => 42,
+                KeyModifier::Meta => 125,
+            }).collect();
+            codes.push(evdev_key_code(key)? as i32);
+            let mut events: Vec<PortalEvent> = codes.iter().map(|&c| PortalEvent::Key(c, true)).collect();
+            events.extend(codes.iter().rev().map(|&c| PortalEvent::Key(c, false)));
+            linux_portal::send(&events).await?;
+        }
+    }
+    Ok(())
+}
+
+/// `hyprctl cursorpos` prints "x, y" in global layout coordinates.
+fn parse_hyprctl_cursor(output: &str) -> Option<(i32, i32)> {
+    let (x, y) = output.trim().split_once(',')?;
+    Some((x.trim().parse::<f64>().ok()?.round() as i32, y.trim().parse::<f64>().ok()?.round() as i32))
+}
+
+/// Wayland keeps the pointer position from clients, so only compositors with an IPC that reports
+/// it (Hyprland) can answer. XWayland's position is left alone: it goes stale over native windows.
+fn wayland_cursor_position() -> Result<(i32, i32), String> {
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_none() || !command_available("hyprctl") {
+        return Err("This Wayland compositor doesn't report the cursor position (Hyprland does, through hyprctl)".to_string());
+    }
+    let output = std::process::Command::new("hyprctl")
+        .arg("cursorpos")
+        .output()
+        .map_err(|e| format!("Failed to run hyprctl: {}", e))?;
+    parse_hyprctl_cursor(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "Unexpected hyprctl output".to_string())
+}
+
+/// Synchronous because the tools read the cursor from non-async helpers; xdotool and hyprctl
+/// answer instantly.
+pub fn cursor_position() -> Result<(i32, i32), String> {
+    if display_session() == DisplaySession::Wayland {
+        return wayland_cursor_position();
+    }
+    if display_session() != DisplaySession::X11 || !command_available("xdotool") {
+        return Err("Cursor position on X11 needs xdotool".to_string());
+    }
+    let output = std::process::Command::new("xdotool")
+        .args(["getmouselocation", "--shell"])
+        .output()
+        .map_err(|e| format!("Failed to run xdotool: {}", e))?;
+    let output = String::from_utf8_lossy(&output.stdout);
+    let value = |name: &str| {
+        output.lines()
+            .find_map(|line| line.strip_prefix(name).and_then(|v| v.strip_prefix('=')))
+            .and_then(|v| v.trim().parse::<i32>().ok())
+    };
+    match (value("X"), value("Y")) {
+        (Some(x), Some(y)) => Ok((x, y)),
+        _ => Err("Unexpected xdotool output".to_string()),
+    }
+}
+
+/// Capture with xcap first, then with whichever screenshot tool the session supports.
+pub async fn screenshot(region: Option<ScreenRegion>) -> Result<ScreenshotResult, String> {
+    let captured = match &region {
+        Some(r) => crate::screenshot::capture_screenshot_area(r.x, r.y, r.width, r.height).await,
+        None => crate::screenshot::capture_screenshot().await,
+    };
+    let xcap_error = match captured {
+        Ok(shot) => {
+            return Ok(ScreenshotResult {
+                image_base64: shot.image_base64,
+                width: shot.width,
+                height: shot.height,
+                format: shot.format,
+            });
+        }
+        Err(e) => e,
+    };
+
+    let tool = screenshot_fallback()
+        .ok_or_else(|| format!("Screenshot failed ({}) and no grim/gnome-screenshot/import fallback is installed", xcap_error))?;
+    log::warn!("xcap capture failed ({}), falling back to {}", xcap_error, tool);
+
+    let file = tempfile::Builder::new()
+        .suffix(".png")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let path = file.path().to_string_lossy().to_string();
+    let geometry = region.as_ref().map(|r| (r.x, r.y, r.width, r.height));
+
+    let command = match (tool, geometry) {
+        ("grim", Some((x, y, w, h))) => args(&["-g", &format!("{},{} {}x{}", x, y, w, h), &path]),
+        ("import", Some((x, y, w, h))) => args(&["-window", "root", "-crop", &format!("{}x{}+{}+{}", w, h, x, y), &path]),
+        ("gnome-screenshot", _) => args(&["-f", &path]),
+        ("import", None) => args(&["-window", "root", &path]),
+        _ => args(&[&path]),
+    };
+    run(tool, &command).await?;
+
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read screenshot: {}", e))?;
+    let mut image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode screenshot: {}", e))?;
+    // gnome-screenshot has no region option, so crop afterwards
+    if let (Some((x, y, w, h)), "gnome-screenshot") = (geometry, tool) {
+        image = image.crop_imm(x.max(0) as u32, y.max(0) as u32, w, h);
+    }
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode screenshot: {}", e))?;
+
+    Ok(ScreenshotResult {
+        image_base64: base64::engine::general_purpose::STANDARD.encode(&png),
+        width: image.width(),
+        height: image.height(),
+        format: "png".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_name_mapping() {
+        assert_eq!(xdotool_key_name("enter").unwrap(), "Return");
+        assert_eq!(xdotool_key_name("f5").unwrap(), "F5");
+        assert_eq!(evdev_key_code("a").unwrap(), 30);
+        assert_eq!(evdev_key_code("0").unwrap(), 11);
+        assert_eq!(evdev_key_code("F1").unwrap(), 59);
+        assert!(evdev_key_code("PrintScreenPlease").is_err());
+    }
+
+    #[test]
+    fn test_parse_hyprctl_cursor() {
+        assert_eq!(parse_hyprctl_cursor("1280, 720\n"), Some((1280, 720)));
+        assert_eq!(parse_hyprctl_cursor("-1920, 12.6"), Some((-1920, 13)));
+        assert_eq!(parse_hyprctl_cursor("error: no such command"), None);
+    }
+
+    #[test]
+    fn test_parse_hyprctl_active_window() {
+        let json = r#"{"address": "0x55d0c1a2b3c0", "at": [1930, 40], "size": [1260, 700], "title": "Notes", "pid": 4242, "class": "notes"}"#;
+        let window = parse_hyprctl_active_window(json).unwrap();
+        assert_eq!((window.x, window.y, window.width, window.height), (1930, 40, 1260, 700));
+        assert_eq!((window.title.as_str(), window.process_id), ("Notes", 4242));
+        assert!(parse_hyprctl_active_window("Invalid").is_none());
+    }
+
+    #[test]
+    fn test_parse_wmctrl_window() {
+        let window = parse_wmctrl_window("0x03a00007  0 4242   120 80   1280 720  host Notes  -  draft 2").unwrap();
+        assert_eq!(window.id, 0x03a00007);
+        assert_eq!(window.process_id, 4242);
+        assert_eq!((window.x, window.y, window.width, window.height), (120, 80, 1280, 720));
+        assert_eq!(window.title, "Notes  -  draft 2");
+        assert!(parse_wmctrl_window("0x01000003 -1 900 0 0 1920 32 host Top panel").is_none());
+    }
+}
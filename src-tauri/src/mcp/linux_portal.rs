@@ -0,0 +1,230 @@
+// src-tauri/src/mcp/linux_portal.rs
+// Input injection through the XDG RemoteDesktop portal, which the compositor serves over libei on
+// current GNOME and KDE. This is the sanctioned way to send input on Wayland: no root, no uinput,
+// and the user is asked once. The portal returns a restore token for the grant, which is kept in
+// the config directory so later sessions start without asking again until the user revokes it.
+use ashpd::desktop::remote_desktop::{Axis, DeviceType, KeyState, RemoteDesktop};
+use ashpd::desktop::screencast::{CursorMode, Screencast, SourceType};
+use ashpd::desktop::{PersistMode, Session};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::{Mutex, OnceCell};
+
+// linux/input-event-codes.h
+pub const BTN_LEFT: i32 = 0x110;
+pub const BTN_RIGHT: i32 = 0x111;
+pub const BTN_MIDDLE: i32 = 0x112;
+
+pub enum PortalEvent {
+    MoveTo(i32, i32),
+    Button(i32, bool),
+    Key(i32, bool),
+    Keysym(i32, bool),
+    Scroll { vertical: bool, steps: i32 },
+    Pause(Duration),
+}
+
+// One monitor of the screencast the session was started with, in global layout coordinates
+struct Output {
+    node_id: u32,
+    position: (i32, i32),
+    size: (i32, i32),
+}
+
+struct PortalSession {
+    proxy: RemoteDesktop<'static>,
+    session: Session<'static, RemoteDesktop<'static>>,
+    outputs: Vec<Output>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PortalSettings {
+    restore_token: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref AVAILABLE: OnceCell<bool> = OnceCell::new();
+    static ref SESSION: Mutex<Option<PortalSession>> = Mutex::new(None);
+}
+
+/// Whether the session's portal offers keyboard and pointer control. Probed once per run.
+pub async fn available() -> bool {
+    *AVAILABLE
+        .get_or_init(|| async {
+            let Ok(proxy) = RemoteDesktop::new().await else {
+                return false;
+            };
+            proxy
+                .available_device_types()
+                .await
+                .map_or(false, |types| types.contains(DeviceType::Keyboard | DeviceType::Pointer))
+        })
+        .await
+}
+
+/// The probe result without probing, for synchronous callers; false until `available` has run.
+pub fn known_available() -> bool {
+    AVAILABLE.get().copied().unwrap_or(false)
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("remote_desktop_portal.json")
+}
+
+fn load_settings() -> PortalSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_restore_token(token: &str) {
+    let settings = PortalSettings { restore_token: Some(token.to_string()) };
+    let result = get_settings_path()
+        .map_err(|e| e.to_string())
+        .and_then(|path| {
+            let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+            fs::write(path, json).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("Failed to save the remote desktop portal grant: {}", e);
+    }
+}
+
+async fn open_session() -> ashpd::Result<PortalSession> {
+    let proxy = RemoteDesktop::new().await?;
+    let screencast = Screencast::new().await?;
+    let session = proxy.create_session().await?;
+
+    let restore_token = load_settings().restore_token;
+    proxy
+        .select_devices(
+            &session,
+            DeviceType::Keyboard | DeviceType::Pointer,
+            restore_token.as_deref(),
+            PersistMode::ExplicitlyRevoked,
+        )
+        .await?;
+    // Absolute pointer motion is addressed to a screencast stream, so every monitor is selected too
+    screencast
+        .select_sources(&session, CursorMode::Hidden, SourceType::Monitor.into(), true, None, PersistMode::DoNot)
+        .await?;
+
+    let selected = proxy.start(&session, None).await?.response()?;
+    if let Some(token) = selected.restore_token() {
+        save_restore_token(token);
+    }
+    let outputs = selected
+        .streams()
+        .unwrap_or_default()
+        .iter()
+        .map(|stream| Output {
+            node_id: stream.pipe_wire_node_id(),
+            position: stream.position().unwrap_or((0, 0)),
+            size: stream.size().unwrap_or((0, 0)),
+        })
+        .collect();
+
+    println!("🖱️ Remote desktop portal session started");
+    Ok(PortalSession { proxy, session, outputs })
+}
+
+/// Map a global point to the stream that shows it and the point's position inside that stream.
+fn locate(outputs: &[Output], x: i32, y: i32) -> Option<(u32, f64, f64)> {
+    let contains = |output: &&Output| {
+        let (left, top) = output.position;
+        let (width, height) = output.size;
+        x >= left && y >= top && x < left + width && y < top + height
+    };
+    // A single stream without a reported layout covers the whole desktop
+    let output = outputs.iter().find(contains).or(match outputs {
+        [only] if only.size == (0, 0) => Some(only),
+        _ => None,
+    })?;
+    Some((output.node_id, (x - output.position.0) as f64, (y - output.position.1) as f64))
+}
+
+/// X keysym for a character: Latin-1 maps to itself, everything else to the Unicode keysym range.
+pub fn char_keysym(c: char) -> i32 {
+    match c {
+        '\n' => 0xff0d,
+        '\t' => 0xff09,
+        '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as i32,
+        _ => 0x0100_0000 | c as i32,
+    }
+}
+
+async fn send_event(portal: &PortalSession, event: &PortalEvent) -> Result<(), String> {
+    let state = |pressed: bool| if pressed { KeyState::Pressed } else { KeyState::Released };
+    let session = &portal.session;
+    let result = match *event {
+        PortalEvent::MoveTo(x, y) => {
+            let (node_id, local_x, local_y) = locate(&portal.outputs, x, y)
+                .ok_or_else(|| format!("({}, {}) is outside every shared screen", x, y))?;
+            portal.proxy.notify_pointer_motion_absolute(session, node_id, local_x, local_y).await
+        }
+        PortalEvent::Button(code, pressed) => portal.proxy.notify_pointer_button(session, code, state(pressed)).await,
+        PortalEvent::Key(code, pressed) => portal.proxy.notify_keyboard_keycode(session, code, state(pressed)).await,
+        PortalEvent::Keysym(keysym, pressed) => portal.proxy.notify_keyboard_keysym(session, keysym, state(pressed)).await,
+        PortalEvent::Scroll { vertical, steps } => {
+            let axis = if vertical { Axis::Vertical } else { Axis::Horizontal };
+            portal.proxy.notify_pointer_axis_discrete(session, axis, steps).await
+        }
+        PortalEvent::Pause(duration) => {
+            tokio::time::sleep(duration).await;
+            Ok(())
+        }
+    };
+    result.map_err(|e| format!("Remote desktop portal rejected input: {}", e))
+}
+
+/// Send `events` in order over the shared session, starting it (and asking the user, the first
+/// time) when there isn't one yet.
+pub async fn send(events: &[PortalEvent]) -> Result<(), String> {
+    let mut guard = SESSION.lock().await;
+    if guard.is_none() {
+        let portal = open_session()
+            .await
+            .map_err(|e| format!("Remote desktop portal session was not started: {}", e))?;
+        *guard = Some(portal);
+    }
+    let Some(portal) = guard.as_ref() else {
+        return Err("Remote desktop portal session was not started".to_string());
+    };
+
+    for event in events {
+        if let Err(e) = send_event(portal, event).await {
+            // The user may have revoked access or the compositor closed the session; start over next time
+            *guard = None;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate_picks_the_stream_under_the_point() {
+        let outputs = vec![
+            Output { node_id: 40, position: (0, 0), size: (1920, 1080) },
+            Output { node_id: 41, position: (1920, 0), size: (2560, 1440) },
+        ];
+        assert_eq!(locate(&outputs, 100, 200), Some((40, 100.0, 200.0)));
+        assert_eq!(locate(&outputs, 2000, 50), Some((41, 80.0, 50.0)));
+        assert_eq!(locate(&outputs, 100, 1200), None);
+    }
+
+    #[test]
+    fn test_char_keysym() {
+        assert_eq!(char_keysym('a'), 0x61);
+        assert_eq!(char_keysym('é'), 0xe9);
+        assert_eq!(char_keysym('\n'), 0xff0d);
+        assert_eq!(char_keysym('€'), 0x0100_20ac);
+    }
+}
@@ -0,0 +1,686 @@
+// src-tauri/src/mcp/macos.rs
+// macOS backends for the computer use tools: CoreGraphics display capture, Vision text recognition
+// CGEvent input and the AXUIElement accessibility tree. Screenshots keep Retina pixels for OCR
+// accuracy; text locations are converted back to points, which is the coordinate space clicks and
+// accessibility bounds use. Posting input and reading other apps' elements need the Accessibility
+// permission.
+use base64::Engine;
+use core_foundation::array::CFArray;
+use core_foundation::base::{CFType, CFTypeRef, TCFType};
+use core_foundation::boolean::CFBoolean;
+use core_foundation::string::{CFString, CFStringRef};
+use core_graphics::display::CGDisplay;
+use core_graphics::event::{
+    CGEvent, CGEventFlags, CGEventTapLocation, CGEventType, CGKeyCode, CGMouseButton, EventField, ScrollEventUnit,
+};
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+use core_graphics::image::CGImage;
+
+use crate::mcp::types::*;
+
+#[link(name = "Vision", kind = "framework")]
+extern "C" {}
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+    fn AXUIElementCreateSystemWide() -> CFTypeRef;
+    fn AXUIElementCopyAttributeValue(element: CFTypeRef, attribute: CFStringRef, value: *mut CFTypeRef) -> i32;
+    fn AXValueGetValue(value: CFTypeRef, value_type: u32, value_ptr: *mut std::ffi::c_void) -> bool;
+}
+
+// AXValueType
+const AX_VALUE_CG_POINT: u32 = 1;
+const AX_VALUE_CG_SIZE: u32 = 2;
+
+const INPUT_PERMISSION: &str = "Sending input needs Accessibility permission in System Settings › Privacy & Security › Accessibility";
+
+/// A line of recognized text; the box is normalized to 0-1 with a top-left origin.
+#[derive(Debug, Clone)]
+pub struct RecognizedText {
+    pub text: String,
+    pub confidence: f32,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+fn encode_png(image: &CGImage) -> Result<ScreenshotResult, String> {
+    let width = image.width();
+    let height = image.height();
+    let bytes_per_row = image.bytes_per_row();
+    let data = image.data();
+    let bytes = data.bytes();
+
+    // CoreGraphics hands back BGRA rows, padded to bytes_per_row
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        let start = row * bytes_per_row;
+        let line = bytes.get(start..start + width * 4).ok_or("Screenshot buffer is shorter than expected")?;
+        for pixel in line.chunks_exact(4) {
+            rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], 255]);
+        }
+    }
+
+    let buffer = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+        .ok_or("Failed to build screenshot image")?;
+    let mut png = Vec::new();
+    buffer
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode screenshot: {}", e))?;
+
+    Ok(ScreenshotResult {
+        image_base64: base64::engine::general_purpose::STANDARD.encode(&png),
+        width: width as u32,
+        height: height as u32,
+        format: "png".to_string(),
+    })
+}
+
+/// Capture the main display, or a region given in points of the global display space.
+/// The image is in pixels, so on Retina displays it is larger than the region.
+pub fn screenshot(region: Option<ScreenRegion>) -> Result<ScreenshotResult, String> {
+    let image = match region {
+        None => CGDisplay::main().image(),
+        Some(r) => {
+            use core_graphics::window::{kCGNullWindowID, kCGWindowImageDefault, kCGWindowListOptionOnScreenOnly};
+            let bounds = CGRect::new(
+                &CGPoint::new(r.x as f64, r.y as f64),
+                &CGSize::new(r.width as f64, r.height as f64),
+            );
+            CGDisplay::screenshot(bounds, kCGWindowListOptionOnScreenOnly, kCGNullWindowID, kCGWindowImageDefault)
+        }
+    };
+    // CoreGraphics returns nothing when Screen Recording permission hasn't been granted
+    let image = image.ok_or("Screen capture failed; grant Screen Recording permission in System Settings › Privacy & Security")?;
+    encode_png(&image)
+}
+
+/// Bounds of the main display in points, which is what a full screenshot covers.
+pub fn main_display_bounds() -> CGRect {
+    CGDisplay::main().bounds()
+}
+
+/// Run Vision text recognition over a PNG. With a target, only lines containing it are returned
+/// and their boxes are narrowed to the matched characters.
+pub fn recognize_text(png: &[u8], target: Option<&str>, case_sensitive: bool) -> Result<Vec<RecognizedText>, String> {
+    use objc::runtime::{Object, BOOL, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_void};
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct NSRange {
+        location: usize,
+        length: usize,
+    }
+
+    let fold = |s: &str| if case_sensitive { s.to_string() } else { s.to_lowercase() };
+    let needle = target.map(fold);
+
+    unsafe {
+        let pool: *mut Object = msg_send![class!(NSAutoreleasePool), new];
+
+        let data: *mut Object = msg_send![class!(NSData), dataWithBytes: png.as_ptr() as *const c_void length: png.len()];
+        let options: *mut Object = msg_send![class!(NSDictionary), dictionary];
+        let handler: *mut Object = msg_send![class!(VNImageRequestHandler), alloc];
+        let handler: *mut Object = msg_send![handler, initWithData: data options: options];
+        let request: *mut Object = msg_send![class!(VNRecognizeTextRequest), alloc];
+        let request: *mut Object = msg_send![request, init];
+        let _: () = msg_send![request, setRecognitionLevel: 0isize]; // VNRequestTextRecognitionLevelAccurate
+        let _: () = msg_send![request, setUsesLanguageCorrection: YES];
+
+        let requests: *mut Object = msg_send![class!(NSArray), arrayWithObject: request];
+        let mut error: *mut Object = std::ptr::null_mut();
+        let succeeded: BOOL = msg_send![handler, performRequests: requests error: &mut error as *mut *mut Object];
+
+        let mut results = Vec::new();
+        let outcome = if succeeded != YES {
+            let message = if error.is_null() {
+                "unknown error".to_string()
+            } else {
+                let description: *mut Object = msg_send![error, localizedDescription];
+                let utf8: *const c_char = msg_send![description, UTF8String];
+                CStr::from_ptr(utf8).to_string_lossy().into_owned()
+            };
+            Err(format!("Vision text recognition failed: {}", message))
+        } else {
+            let observations: *mut Object = msg_send![request, results];
+            let count: usize = msg_send![observations, count];
+            for index in 0..count {
+                let observation: *mut Object = msg_send![observations, objectAtIndex: index];
+                let candidates: *mut Object = msg_send![observation, topCandidates: 1usize];
+                let candidate: *mut Object = msg_send![candidates, firstObject];
+                if candidate.is_null() {
+                    continue;
+                }
+                let string: *mut Object = msg_send![candidate, string];
+                let utf8: *const c_char = msg_send![string, UTF8String];
+                let text = CStr::from_ptr(utf8).to_string_lossy().into_owned();
+                let confidence: f32 = msg_send![candidate, confidence];
+                let mut bbox: CGRect = msg_send![observation, boundingBox];
+
+                if let Some(needle) = &needle {
+                    let haystack = fold(&text);
+                    let Some(byte_index) = haystack.find(needle.as_str()) else {
+                        continue;
+                    };
+                    // NSString ranges count UTF-16 units
+                    let range = NSRange {
+                        location: haystack[..byte_index].encode_utf16().count(),
+                        length: needle.encode_utf16().count(),
+                    };
+                    let mut range_error: *mut Object = std::ptr::null_mut();
+                    let narrowed: *mut Object = msg_send![candidate, boundingBoxForRange: range error: &mut range_error as *mut *mut Object];
+                    if !narrowed.is_null() {
+                        bbox = msg_send![narrowed, boundingBox];
+                    }
+                }
+
+                // Vision boxes are normalized with a bottom-left origin
+                results.push(RecognizedText {
+                    text,
+                    confidence,
+                    x: bbox.origin.x,
+                    y: 1.0 - bbox.origin.y - bbox.size.height,
+                    width: bbox.size.width,
+                    height: bbox.size.height,
+                });
+            }
+            Ok(())
+        };
+
+        let _: () = msg_send![request, release];
+        let _: () = msg_send![handler, release];
+        let _: () = msg_send![pool, drain];
+
+        outcome.map(|_| results)
+    }
+}
+
+/// Map normalized Vision boxes onto the main display, in points, as the OCR tools report them.
+pub fn to_text_locations(recognized: Vec<RecognizedText>) -> Vec<TextLocation> {
+    let display = main_display_bounds();
+    recognized
+        .into_iter()
+        .map(|r| {
+            let x = (display.origin.x + r.x * display.size.width).round() as i32;
+            let y = (display.origin.y + r.y * display.size.height).round() as i32;
+            let width = (r.width * display.size.width).round() as i32;
+            let height = (r.height * display.size.height).round() as i32;
+            TextLocation {
+                text: r.text,
+                confidence: r.confidence,
+                bounding_box: TextBoundingBox { x, y, width, height },
+                center_x: x + width / 2,
+                center_y: y + height / 2,
+            }
+        })
+        .collect()
+}
+
+/// Every active display in points, the main one first.
+pub fn screen_info() -> Result<ScreenInfo, String> {
+    let main = CGDisplay::main().id;
+    let displays = CGDisplay::active_displays().map_err(|e| format!("Failed to list displays (CoreGraphics error {})", e))?;
+    let monitors = displays.into_iter().enumerate().map(|(index, id)| {
+        let display = CGDisplay::new(id);
+        let bounds = display.bounds();
+        let region = ScreenRegion {
+            x: bounds.origin.x.round() as i32,
+            y: bounds.origin.y.round() as i32,
+            width: bounds.size.width.round() as u32,
+            height: bounds.size.height.round() as u32,
+        };
+        MonitorDetails {
+            index,
+            name: if id == main { "Main display".to_string() } else { format!("Display {}", index + 1) },
+            x: region.x,
+            y: region.y,
+            width: region.width,
+            height: region.height,
+            // The menu bar and Dock aren't queried yet, so the work area is the whole display
+            work_area: region,
+            scale_factor: if bounds.size.width > 0.0 { display.pixels_wide() as f64 / bounds.size.width } else { 1.0 },
+            is_primary: id == main,
+        }
+    }).collect();
+    Ok(ScreenInfo { monitors })
+}
+
+// ========== INPUT ==========
+
+/// Whether the app may post input events (System Settings › Privacy & Security › Accessibility).
+pub fn input_permitted() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+fn new_source() -> Result<CGEventSource, String> {
+    CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "Failed to create a CoreGraphics event source".to_string())
+}
+
+// Events and sources aren't Send, so each is built and posted without crossing an await
+fn input_source() -> Result<CGEventSource, String> {
+    if !input_permitted() {
+        return Err(INPUT_PERMISSION.to_string());
+    }
+    new_source()
+}
+
+fn point(x: i32, y: i32) -> CGPoint {
+    CGPoint::new(x as f64, y as f64)
+}
+
+/// Down, up and dragged event types for a button.
+fn mouse_events(button: MouseButton) -> (CGEventType, CGEventType, CGEventType, CGMouseButton) {
+    match button {
+        MouseButton::Left => (CGEventType::LeftMouseDown, CGEventType::LeftMouseUp, CGEventType::LeftMouseDragged, CGMouseButton::Left),
+        MouseButton::Right => (CGEventType::RightMouseDown, CGEventType::RightMouseUp, CGEventType::RightMouseDragged, CGMouseButton::Right),
+        MouseButton::Middle => (CGEventType::OtherMouseDown, CGEventType::OtherMouseUp, CGEventType::OtherMouseDragged, CGMouseButton::Center),
+    }
+}
+
+fn post_mouse(event_type: CGEventType, at: CGPoint, button: CGMouseButton, click_state: i64) -> Result<(), String> {
+    let event = CGEvent::new_mouse_event(input_source()?, event_type, at, button)
+        .map_err(|_| "Failed to create mouse event".to_string())?;
+    if click_state > 1 {
+        event.set_integer_value_field(EventField::MOUSE_EVENT_CLICK_STATE, click_state);
+    }
+    event.post(CGEventTapLocation::HID);
+    Ok(())
+}
+
+pub async fn click(x: i32, y: i32, button: MouseButton, double_click: bool) -> Result<(), String> {
+    let (down, up, _, cg_button) = mouse_events(button);
+    let at = point(x, y);
+    post_mouse(CGEventType::MouseMoved, at, cg_button, 1)?;
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    // The second click of a double click carries click state 2, which is what apps check
+    for click_state in 1..=(if double_click { 2 } else { 1 }) {
+        post_mouse(down, at, cg_button, click_state)?;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        post_mouse(up, at, cg_button, click_state)?;
+    }
+    Ok(())
+}
+
+pub async fn move_pointer(path: &[(i32, i32)], interval: std::time::Duration) -> Result<(), String> {
+    for &(x, y) in path {
+        if !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        post_mouse(CGEventType::MouseMoved, point(x, y), CGMouseButton::Left, 1)?;
+    }
+    Ok(())
+}
+
+/// Press `button` at `from`, move through `path` pausing `interval` before each point, and
+/// release at the last point. The button is released even if a move fails.
+pub async fn drag(from: (i32, i32), path: &[(i32, i32)], interval: std::time::Duration, button: MouseButton) -> Result<(), String> {
+    let (down, up, dragged, cg_button) = mouse_events(button);
+    post_mouse(CGEventType::MouseMoved, point(from.0, from.1), cg_button, 1)?;
+    post_mouse(down, point(from.0, from.1), cg_button, 1)?;
+    let mut moved = Ok(());
+    for &(x, y) in path {
+        tokio::time::sleep(interval).await;
+        moved = post_mouse(dragged, point(x, y), cg_button, 1);
+        if moved.is_err() {
+            break;
+        }
+    }
+    let end = path.last().copied().unwrap_or(from);
+    let released = post_mouse(up, point(end.0, end.1), cg_button, 1);
+    moved.and(released)
+}
+
+pub fn cursor_position() -> Result<(i32, i32), String> {
+    let event = CGEvent::new(new_source()?).map_err(|_| "Failed to read the pointer position".to_string())?;
+    let location = event.location();
+    Ok((location.x.round() as i32, location.y.round() as i32))
+}
+
+// NSScrollView's default line scroll, for turning lines into pixel steps
+const PIXELS_PER_LINE: i32 = 10;
+
+fn post_scroll(smooth: bool, vertical: i32, horizontal: i32) -> Result<(), String> {
+    let unit = if smooth { ScrollEventUnit::PIXEL } else { ScrollEventUnit::LINE };
+    let event = CGEvent::new_scroll_event(input_source()?, unit, 2, vertical, horizontal, 0)
+        .map_err(|_| "Failed to create scroll event".to_string())?;
+    event.post(CGEventTapLocation::HID);
+    Ok(())
+}
+
+pub async fn scroll(params: &ScrollParams) -> Result<(), String> {
+    if let (Some(x), Some(y)) = (params.x, params.y) {
+        post_mouse(CGEventType::MouseMoved, point(x, y), CGMouseButton::Left, 1)?;
+    }
+    // Smooth scrolls go in pixels so each step can be a fraction of a line
+    let smooth = params.smooth.unwrap_or(false);
+    let (deltas, interval) = if smooth {
+        params.wheel_steps(PIXELS_PER_LINE, 2)
+    } else {
+        params.wheel_steps(1, 1)
+    };
+    for (i, delta) in deltas.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(interval).await;
+        }
+        // Positive wheel values scroll up (vertical) and left (horizontal)
+        let (vertical, horizontal) = match params.direction {
+            ScrollDirection::Up => (*delta, 0),
+            ScrollDirection::Down => (-delta, 0),
+            ScrollDirection::Left => (0, *delta),
+            ScrollDirection::Right => (0, -delta),
+        };
+        post_scroll(smooth, vertical, horizontal)?;
+    }
+    Ok(())
+}
+
+// Virtual key codes (HIToolbox Events.h); letters, digits and punctuation are ANSI positions
+fn key_code(key: &str) -> Result<CGKeyCode, String> {
+    let lower = key.to_lowercase();
+    let code = match lower.as_str() {
+        "return" | "enter" => 36,
+        "tab" => 48,
+        "space" => 49,
+        "backspace" | "back" => 51,
+        "escape" | "esc" => 53,
+        "delete" | "del" => 117,
+        "home" => 115,
+        "end" => 119,
+        "pageup" => 116,
+        "pagedown" => 121,
+        "left" | "leftarrow" => 123,
+        "right" | "rightarrow" => 124,
+        "down" | "downarrow" => 125,
+        "up" | "uparrow" => 126,
+        "meta" | "cmd" | "command" => 55,
+        "shift" => 56,
+        "capslock" => 57,
+        "alt" | "option" => 58,
+        "ctrl" | "control" => 59,
+        "f1" => 122, "f2" => 120, "f3" => 99, "f4" => 118, "f5" => 96, "f6" => 97,
+        "f7" => 98, "f8" => 100, "f9" => 101, "f10" => 109, "f11" => 103, "f12" => 111,
+        "f13" => 105, "f14" => 107, "f15" => 113, "f16" => 106, "f17" => 64, "f18" => 79,
+        "f19" => 80, "f20" => 90,
+        _ => {
+            let mut chars = lower.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return Err(format!("Unsupported key: {}", key));
+            };
+            match c {
+                'a' => 0, 's' => 1, 'd' => 2, 'f' => 3, 'h' => 4, 'g' => 5, 'z' => 6, 'x' => 7,
+                'c' => 8, 'v' => 9, 'b' => 11, 'q' => 12, 'w' => 13, 'e' => 14, 'r' => 15,
+                'y' => 16, 't' => 17, '1' => 18, '2' => 19, '3' => 20, '4' => 21, '6' => 22,
+                '5' => 23, '=' => 24, '9' => 25, '7' => 26, '-' => 27, '8' => 28, '0' => 29,
+                ']' => 30, 'o' => 31, 'u' => 32, '[' => 33, 'i' => 34, 'p' => 35, 'l' => 37,
+                'j' => 38, '\'' => 39, 'k' => 40, ';' => 41, '\\' => 42, ',' => 43, '/' => 44,
+                'n' => 45, 'm' => 46, '.' => 47, '`' => 50,
+                _ => return Err(format!("Unsupported key: {}", key)),
+            }
+        }
+    };
+    Ok(code)
+}
+
+fn modifier_key(modifier: &KeyModifier) -> (CGKeyCode, CGEventFlags) {
+    match modifier {
+        KeyModifier::Ctrl => (59, CGEventFlags::CGEventFlagControl),
+        KeyModifier::Alt => (58, CGEventFlags::CGEventFlagAlternate),
+        KeyModifier::Shift => (56, CGEventFlags::CGEventFlagShift),
+        KeyModifier::Meta => (55, CGEventFlags::CGEventFlagCommand),
+    }
+}
+
+fn post_key(code: CGKeyCode, down: bool, flags: CGEventFlags) -> Result<(), String> {
+    let event = CGEvent::new_keyboard_event(input_source()?, code, down)
+        .map_err(|_| "Failed to create key event".to_string())?;
+    event.set_flags(flags);
+    event.post(CGEventTapLocation::HID);
+    Ok(())
+}
+
+/// Modifiers go down in order and stay held (and flagged on the key) across the key, then come up
+/// in reverse.
+pub fn press_key(key: &str, modifiers: &[KeyModifier]) -> Result<(), String> {
+    let code = key_code(key)?;
+    let mut held: Vec<(CGKeyCode, CGEventFlags)> = Vec::new();
+    for modifier in modifiers.iter().map(modifier_key) {
+        if !held.contains(&modifier) {
+            held.push(modifier);
+        }
+    }
+    let flags_of = |keys: &[(CGKeyCode, CGEventFlags)]| keys.iter().fold(CGEventFlags::empty(), |acc, (_, flag)| acc | *flag);
+
+    let mut pressed = Ok(());
+    for i in 0..held.len() {
+        pressed = post_key(held[i].0, true, flags_of(&held[..=i]));
+        if pressed.is_err() {
+            break;
+        }
+    }
+    let pressed = pressed
+        .and_then(|_| post_key(code, true, flags_of(&held)))
+        .and_then(|_| post_key(code, false, flags_of(&held)));
+    // Release even after a failure so no modifier is left stuck down
+    for i in (0..held.len()).rev() {
+        let _ = post_key(held[i].0, false, flags_of(&held[..i]));
+    }
+    pressed
+}
+
+/// Type `text` as Unicode strings attached to key events, so it doesn't depend on the keyboard
+/// layout. Line breaks and tabs press Return and Tab.
+pub async fn type_text(text: &str, delay_ms: u64) -> Result<(), String> {
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\r' | '\n' => {
+                if ch == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                post_key(36, true, CGEventFlags::empty())?;
+                post_key(36, false, CGEventFlags::empty())?;
+            }
+            '\t' => {
+                post_key(48, true, CGEventFlags::empty())?;
+                post_key(48, false, CGEventFlags::empty())?;
+            }
+            _ => type_char(ch)?,
+        }
+        if delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        }
+    }
+    Ok(())
+}
+
+fn type_char(ch: char) -> Result<(), String> {
+    let text = ch.to_string();
+    for down in [true, false] {
+        let event = CGEvent::new_keyboard_event(input_source()?, 0, down)
+            .map_err(|_| "Failed to create key event".to_string())?;
+        event.set_flags(CGEventFlags::empty());
+        event.set_string(&text);
+        event.post(CGEventTapLocation::HID);
+    }
+    Ok(())
+}
+
+// ========== WINDOWS ==========
+
+/// The frontmost application window, from the window server's on-screen list (front to back).
+/// Needs no permission; titles of other apps' windows are only filled in with Screen Recording.
+pub fn frontmost_window() -> Option<DesktopWindow> {
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::number::CFNumber;
+    use core_graphics::window::{
+        copy_window_info, kCGNullWindowID, kCGWindowListExcludeDesktopElements, kCGWindowListOptionOnScreenOnly,
+    };
+
+    let windows = copy_window_info(kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements, kCGNullWindowID)?;
+    windows.iter().find_map(|item| {
+        let info: CFDictionary<CFString, CFType> = unsafe { CFDictionary::wrap_under_get_rule(*item as CFDictionaryRef) };
+        let value = |key: &'static str| info.find(CFString::from_static_string(key));
+        let number = |key: &'static str| value(key)?.downcast::<CFNumber>()?.to_i64();
+        let text = |key: &'static str| value(key)?.downcast::<CFString>().map(|text| text.to_string());
+
+        // Layer 0 holds application windows; the menu bar, Dock and overlays sit above it
+        if number("kCGWindowLayer")? != 0 {
+            return None;
+        }
+        let bounds = CGRect::from_dict_representation(&value("kCGWindowBounds")?.downcast::<CFDictionary>()?)?;
+        Some(DesktopWindow {
+            id: number("kCGWindowNumber")? as u64,
+            title: text("kCGWindowName").unwrap_or_default(),
+            process_id: number("kCGWindowOwnerPID")? as u32,
+            process_name: text("kCGWindowOwnerName"),
+            x: bounds.origin.x.round() as i32,
+            y: bounds.origin.y.round() as i32,
+            width: bounds.size.width.max(0.0).round() as u32,
+            height: bounds.size.height.max(0.0).round() as u32,
+            minimized: false,
+        })
+    })
+}
+
+// ========== ACCESSIBILITY TREE ==========
+
+// An owned attribute value, or None when the element doesn't have the attribute
+fn ax_attribute(element: CFTypeRef, attribute: &str) -> Option<CFType> {
+    let attribute = CFString::new(attribute);
+    let mut value: CFTypeRef = std::ptr::null();
+    let status = unsafe { AXUIElementCopyAttributeValue(element, attribute.as_concrete_TypeRef(), &mut value) };
+    (status == 0 && !value.is_null()).then(|| unsafe { CFType::wrap_under_create_rule(value) })
+}
+
+fn ax_string(element: CFTypeRef, attribute: &str) -> Option<String> {
+    ax_attribute(element, attribute)?
+        .downcast::<CFString>()
+        .map(|value| value.to_string())
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn ax_bounds(element: CFTypeRef) -> ScreenRegion {
+    let mut origin = CGPoint::new(0.0, 0.0);
+    let mut size = CGSize::new(0.0, 0.0);
+    if let Some(value) = ax_attribute(element, "AXPosition") {
+        unsafe { AXValueGetValue(value.as_CFTypeRef(), AX_VALUE_CG_POINT, &mut origin as *mut CGPoint as *mut _) };
+    }
+    if let Some(value) = ax_attribute(element, "AXSize") {
+        unsafe { AXValueGetValue(value.as_CFTypeRef(), AX_VALUE_CG_SIZE, &mut size as *mut CGSize as *mut _) };
+    }
+    ScreenRegion {
+        x: origin.x.round() as i32,
+        y: origin.y.round() as i32,
+        width: size.width.max(0.0).round() as u32,
+        height: size.height.max(0.0).round() as u32,
+    }
+}
+
+// AX roles in the vocabulary the Windows backend uses, so plans work on both
+fn ax_role(role: &str) -> String {
+    match role {
+        "AXTextField" | "AXTextArea" | "AXSearchField" | "AXSecureTextField" => "edit".to_string(),
+        "AXStaticText" => "text".to_string(),
+        "AXPopUpButton" | "AXComboBox" => "combobox".to_string(),
+        "AXLink" => "hyperlink".to_string(),
+        "AXRow" | "AXCell" => "listitem".to_string(),
+        "AXTabGroup" => "tab".to_string(),
+        "AXScrollArea" | "AXSplitGroup" | "AXLayoutArea" => "pane".to_string(),
+        "AXWebArea" => "document".to_string(),
+        other => other.trim_start_matches("AX").to_lowercase(),
+    }
+}
+
+struct AxWalk {
+    remaining: usize,
+    count: usize,
+    truncated: bool,
+}
+
+impl AxWalk {
+    fn element(&mut self, element: CFTypeRef, depth: usize) -> UiElement {
+        self.remaining -= 1;
+        self.count += 1;
+        let mut node = UiElement {
+            // Buttons carry a title, icons a description and fields a value
+            name: ax_string(element, "AXTitle")
+                .or_else(|| ax_string(element, "AXDescription"))
+                .or_else(|| ax_string(element, "AXValue"))
+                .unwrap_or_default(),
+            role: ax_string(element, "AXRole").map_or("unknown".to_string(), |role| ax_role(&role)),
+            bounds: ax_bounds(element),
+            enabled: ax_attribute(element, "AXEnabled")
+                .and_then(|value| value.downcast::<CFBoolean>())
+                .map_or(true, bool::from),
+            children: Vec::new(),
+        };
+
+        // The array owns the children, so it stays alive while they are walked
+        let Some(children) = ax_attribute(element, "AXChildren").and_then(|value| value.downcast::<CFArray>()) else {
+            return node;
+        };
+        let children = children.get_all_values();
+        if depth == 0 {
+            self.truncated |= !children.is_empty();
+            return node;
+        }
+        for child in children {
+            if self.remaining == 0 {
+                self.truncated = true;
+                break;
+            }
+            node.children.push(self.element(child, depth - 1));
+        }
+        node
+    }
+}
+
+/// The element tree of the focused window of the frontmost app, at most `max_depth` levels below
+/// the window and `max_elements` elements in all. Windows can't be picked by id on macOS yet.
+pub fn ui_tree(window: Option<u64>, max_depth: usize, max_elements: usize) -> Result<UiTree, String> {
+    if window.is_some() {
+        return Err("Picking a window isn't supported on macOS; focus it and leave the window out".to_string());
+    }
+    if !input_permitted() {
+        return Err("Reading other apps' controls needs Accessibility permission in System Settings › Privacy & Security › Accessibility".to_string());
+    }
+    let system = unsafe { CFType::wrap_under_create_rule(AXUIElementCreateSystemWide()) };
+    let app = ax_attribute(system.as_CFTypeRef(), "AXFocusedApplication")
+        .ok_or("Couldn't find the frontmost application")?;
+    let window = ax_attribute(app.as_CFTypeRef(), "AXFocusedWindow")
+        .or_else(|| ax_attribute(app.as_CFTypeRef(), "AXMainWindow"))
+        .ok_or("The frontmost application has no focused window")?;
+
+    let mut walk = AxWalk { remaining: max_elements.max(1), count: 0, truncated: false };
+    let root = walk.element(window.as_CFTypeRef(), max_depth);
+    Ok(UiTree {
+        window_title: root.name.clone(),
+        root,
+        element_count: walk.count,
+        truncated: walk.truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_codes() {
+        assert_eq!(key_code("Enter").unwrap(), 36);
+        assert_eq!(key_code("a").unwrap(), 0);
+        assert_eq!(key_code("Z").unwrap(), 6);
+        assert_eq!(key_code("0").unwrap(), 29);
+        assert_eq!(key_code("F5").unwrap(), 96);
+        assert_eq!(key_code("left").unwrap(), 123);
+        assert!(key_code("F24").is_err());
+        assert!(key_code("é").is_err());
+    }
+}
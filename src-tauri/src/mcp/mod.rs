@@ -2,7 +2,22 @@
 pub mod types;
 pub mod server;
 pub mod tools;
+pub mod files;
+pub mod shell;
 pub mod commands;
+pub mod report;
+pub mod policy;
+pub mod capabilities;
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(target_os = "linux")]
+pub mod linux_portal;
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(target_os = "windows")]
+pub mod windows_input;
+#[cfg(target_os = "windows")]
+pub mod windows_uia;
 
 // Re-export commonly used types and functions
 pub use types::*;
@@ -0,0 +1,123 @@
+// src-tauri/src/mcp/policy.rs
+// Remembered approval decisions. Session rules live on the MCPSession; exact tool+parameter
+// rules are persisted here and checked before any approval request is emitted.
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::mcp::types::*;
+
+lazy_static::lazy_static! {
+    // The persisted rules, read from disk once and kept in sync by store_persisted_rules
+    static ref PERSISTED_RULES: Mutex<Option<Vec<ApprovalRule>>> = Mutex::new(None);
+}
+
+fn get_approval_rules_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("mcp_approval_rules.json")
+}
+
+pub fn load_persisted_rules() -> Vec<ApprovalRule> {
+    let mut cached = match PERSISTED_RULES.lock() {
+        Ok(cached) => cached,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    cached.get_or_insert_with(|| {
+        get_approval_rules_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }).clone()
+}
+
+pub fn store_persisted_rules(rules: &[ApprovalRule]) -> Result<(), String> {
+    let path = get_approval_rules_path()
+        .map_err(|e| format!("Failed to get approval rules path: {}", e))?;
+    let json = serde_json::to_string_pretty(rules)
+        .map_err(|e| format!("Failed to serialize approval rules: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write approval rules: {}", e))?;
+    if let Ok(mut cached) = PERSISTED_RULES.lock() {
+        *cached = Some(rules.to_vec());
+    }
+    Ok(())
+}
+
+// Parameters with null-valued keys dropped at every level, so an explicit null and a missing
+// optional parameter compare equal
+fn canonical_parameters(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), canonical_parameters(value)))
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.iter().map(canonical_parameters).collect()),
+        other => other.clone(),
+    }
+}
+
+/// The call's parameters must be exactly the rule's: an extra flag (append: true) or a changed
+/// value (another cwd) is a different call and needs its own approval.
+fn parameters_match(rule: &serde_json::Value, parameters: &serde_json::Value) -> bool {
+    canonical_parameters(rule) == canonical_parameters(parameters)
+}
+
+impl ApprovalRule {
+    pub fn new(tool_name: &str, parameters: Option<serde_json::Value>, session_id: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool_name: tool_name.to_string(),
+            parameters,
+            session_id,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+    
+    pub fn matches(&self, tool_name: &str, parameters: &serde_json::Value) -> bool {
+        self.tool_name == tool_name
+            && self.parameters.as_ref().map_or(true, |expected| parameters_match(expected, parameters))
+    }
+}
+
+/// Persist an exact tool+parameters rule, unless an identical one already exists.
+pub fn remember_exact(tool_name: &str, parameters: &serde_json::Value) -> Result<ApprovalRule, String> {
+    let mut rules = load_persisted_rules();
+    if let Some(existing) = rules.iter().find(|r| r.tool_name == tool_name && r.parameters.as_ref().map_or(false, |p| parameters_match(p, parameters))) {
+        return Ok(existing.clone());
+    }
+    let rule = ApprovalRule::new(tool_name, Some(parameters.clone()), None);
+    rules.push(rule.clone());
+    store_persisted_rules(&rules)?;
+    Ok(rule)
+}
+
+/// Remove a persisted rule. Returns whether it existed.
+pub fn revoke_persisted(rule_id: &str) -> Result<bool, String> {
+    let mut rules = load_persisted_rules();
+    let before = rules.len();
+    rules.retain(|r| r.id != rule_id);
+    if rules.len() == before {
+        return Ok(false);
+    }
+    store_persisted_rules(&rules)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_rule_matching() {
+        let any = ApprovalRule::new("click_at", None, Some("s1".to_string()));
+        assert!(any.matches("click_at", &serde_json::json!({"x": 1, "y": 2})));
+        assert!(!any.matches("click", &serde_json::json!({})));
+        
+        let exact = ApprovalRule::new("write_file", Some(serde_json::json!({"path": "notes.md", "content": "hi"})), None);
+        assert!(exact.matches("write_file", &serde_json::json!({"content": "hi", "path": "notes.md", "append": null})));
+        assert!(!exact.matches("write_file", &serde_json::json!({"path": "notes.md", "content": "hi", "append": true})));
+        assert!(!exact.matches("write_file", &serde_json::json!({"path": "notes.md"})));
+        assert!(!exact.matches("type", &serde_json::json!({"path": "notes.md", "content": "hi"})));
+    }
+}
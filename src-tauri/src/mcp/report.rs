@@ -0,0 +1,133 @@
+// src-tauri/src/mcp/report.rs
+// Session export: the full automation run as JSON, or as a single HTML page with the screenshots
+// inlined so it can be opened or shared without the app.
+use std::fs;
+use std::path::Path;
+
+use crate::mcp::types::*;
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    escape_html(&serde_json::to_string_pretty(value).unwrap_or_default())
+}
+
+fn approval_label(approval: &Option<StepApproval>) -> String {
+    match approval {
+        None => "—".to_string(),
+        Some(a) if !a.required => "not required".to_string(),
+        Some(a) => {
+            let verdict = if a.approved { "approved" } else { "denied" };
+            match &a.reason {
+                Some(reason) => format!("{} ({})", verdict, escape_html(reason)),
+                None => verdict.to_string(),
+            }
+        }
+    }
+}
+
+fn render_step(step: &RecordedStep) -> String {
+    let outcome = match (&step.result, &step.error) {
+        (Some(result), _) if result.success => "✅ success".to_string(),
+        (Some(result), _) => format!("❌ {}", escape_html(result.error.as_deref().unwrap_or("failed"))),
+        (None, Some(error)) => format!("❌ {}", escape_html(error)),
+        (None, None) => "—".to_string(),
+    };
+
+    let mut html = format!(
+        "<section class=\"step\">\n<h3>#{} <code>{}</code></h3>\n\
+         <p>Started {} · {} ms · approval: {} · {}</p>\n\
+         <h4>Parameters</h4><pre>{}</pre>\n",
+        step.index + 1,
+        escape_html(&step.tool_name),
+        escape_html(&step.started_at),
+        step.duration_ms,
+        approval_label(&step.approval),
+        outcome,
+        pretty_json(&step.parameters),
+    );
+    if let Some(result) = &step.result {
+        html.push_str(&format!("<h4>Result</h4><pre>{}</pre>\n", pretty_json(&result.result)));
+    }
+    if step.screenshot_before.is_some() || step.screenshot_after.is_some() {
+        html.push_str("<div class=\"shots\">\n");
+        for (label, shot) in [("Before", &step.screenshot_before), ("After", &step.screenshot_after)] {
+            if let Some(uri) = shot {
+                html.push_str(&format!("<figure><img src=\"{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>\n", uri, label, label));
+            }
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</section>\n");
+    html
+}
+
+pub fn render_html(report: &MCPSessionReport) -> String {
+    let mut body = format!(
+        "<h1>Automation run {}</h1>\n<p>Session started {} · exported {} · {} steps</p>\n",
+        escape_html(&report.session.id),
+        escape_html(&report.session.created_at),
+        escape_html(&report.exported_at),
+        report.steps.len(),
+    );
+
+    for plan in &report.plans {
+        body.push_str(&format!("<h2>Plan: {}</h2>\n<ol>\n", escape_html(&plan.user_request)));
+        for step in &plan.steps {
+            body.push_str(&format!(
+                "<li><code>{}</code> — {} <pre>{}</pre></li>\n",
+                escape_html(&step.tool_name),
+                escape_html(&step.description),
+                pretty_json(&step.parameters),
+            ));
+        }
+        body.push_str("</ol>\n");
+    }
+
+    body.push_str("<h2>Steps</h2>\n");
+    for step in &report.steps {
+        body.push_str(&render_step(step));
+    }
+
+    body.push_str("<h2>Log</h2>\n<pre>");
+    for entry in &report.logs {
+        body.push_str(&escape_html(&format!("{} [{:?}] {}\n", entry.timestamp, entry.level, entry.message)));
+    }
+    body.push_str("</pre>\n");
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Automation run {}</title>\n<style>\n\
+         body {{ font-family: system-ui, sans-serif; max-width: 1100px; margin: 2rem auto; padding: 0 1rem; }}\n\
+         pre {{ background: #f4f4f5; padding: .5rem; overflow-x: auto; }}\n\
+         .step {{ border-top: 1px solid #ddd; padding-top: .5rem; }}\n\
+         .shots {{ display: flex; gap: 1rem; }}\n\
+         .shots img {{ max-width: 100%; border: 1px solid #ccc; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(&report.session.id),
+        body,
+    )
+}
+
+/// Write the report as HTML for `.html`/`.htm` paths and as JSON otherwise.
+pub fn write_report(report: &MCPSessionReport, path: &Path) -> Result<(), String> {
+    let is_html = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+
+    let contents = if is_html {
+        render_html(report)
+    } else {
+        serde_json::to_string_pretty(report).map_err(|e| format!("Failed to serialize report: {}", e))?
+    };
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Failed to write report: {}", e))
+}
@@ -20,6 +20,9 @@ pub struct MCPSession {
     pub log_entries: Arc<Mutex<Vec<MCPLogEntry>>>,
     pub status: Arc<Mutex<SessionStatus>>,
     pub tools: Arc<Mutex<HashMap<String, Box<dyn ComputerUseTool + Send + Sync>>>>,
+    pub plans: Arc<Mutex<HashMap<String, ToolExecutionPlan>>>,
+    pub recorded_steps: Arc<Mutex<Vec<RecordedStep>>>,
+    pub approval_rules: Arc<Mutex<Vec<ApprovalRule>>>, // Session-scoped; persisted rules live in policy.rs
 }
 
 impl MCPSession {
@@ -29,25 +32,13 @@ impl MCPSession {
         
         log::info!("🚀 Creating new MCP session: {}", session_id);
         
-        let mut tools: HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> = HashMap::new();
-        
-        // Register computer use tools
-        tools.insert("click".to_string(), Box::new(crate::mcp::tools::ClickTool));
-        tools.insert("type".to_string(), Box::new(crate::mcp::tools::TypeTool));
-        tools.insert("scroll".to_string(), Box::new(crate::mcp::tools::ScrollTool));
-        tools.insert("key_press".to_string(), Box::new(crate::mcp::tools::KeyPressTool));
-        tools.insert("get_cursor_position".to_string(), Box::new(crate::mcp::tools::GetCursorPositionTool));
-        tools.insert("get_screen_info".to_string(), Box::new(crate::mcp::tools::GetScreenInfoTool));
-        tools.insert("take_screenshot".to_string(), Box::new(crate::mcp::tools::ScreenshotTool));
-        
-        // Register new atomic OCR tools
-        tools.insert("find_text".to_string(), Box::new(crate::mcp::tools::FindTextTool));
-        tools.insert("click_at".to_string(), Box::new(crate::mcp::tools::ClickAtTool));
-        tools.insert("debug_ocr".to_string(), Box::new(crate::mcp::tools::DebugOcrTool));
-        
-        // Register compound tools (require approval)
-        tools.insert("click_on_text".to_string(), Box::new(crate::mcp::tools::ClickOnTextTool));
-        tools.insert("click_and_type".to_string(), Box::new(crate::mcp::tools::ClickAndTypeTool));
+        let mut tools = crate::mcp::tools::builtin_tools();
+        // Approved plugin tools sit alongside the built-ins, behind the same approvals
+        tools.extend(crate::plugins::mcp_tools());
+        // File access only exists in sessions configured with folders to sandbox it to
+        tools.extend(crate::mcp::files::filesystem_tools(&config));
+        tools.extend(crate::mcp::shell::shell_tools(&config));
+        
         Self {
             id: session_id,
             config,
@@ -57,6 +48,9 @@ impl MCPSession {
             log_entries: Arc::new(Mutex::new(Vec::new())),
             status: Arc::new(Mutex::new(SessionStatus::Initializing)),
             tools: Arc::new(Mutex::new(tools)),
+            plans: Arc::new(Mutex::new(HashMap::new())),
+            recorded_steps: Arc::new(Mutex::new(Vec::new())),
+            approval_rules: Arc::new(Mutex::new(Vec::new())),
         }
     }
     
@@ -92,6 +86,7 @@ impl MCPSession {
                     danger_level: tool.danger_level(),
                     requires_approval: tool.requires_approval(),
                     parameters_schema: tool.parameters_schema(),
+                    result_schema: tool.result_schema(),
                 });
             }
             
@@ -157,15 +152,32 @@ impl MCPSession {
         tool_description: &str,
         parameters: &serde_json::Value,
         danger_level: DangerLevel,
-    ) -> Result<bool, String> {
-        if !self.config.require_approval {
-            return Ok(true);
+    ) -> Result<StepApproval, String> {
+        let not_required = StepApproval { required: false, approved: true, reason: None, decided_at: None };
+        // Critical tools (file writes, shell commands) ask even in sessions that skip approvals
+        if !self.config.require_approval && !matches!(danger_level, DangerLevel::Critical) {
+            return Ok(not_required);
         }
         
         // Check if tool requires approval based on danger level
         let requires_approval = matches!(danger_level, DangerLevel::Medium | DangerLevel::High | DangerLevel::Critical);
         if !requires_approval {
-            return Ok(true);
+            return Ok(not_required);
+        }
+        
+        // A remembered decision skips the prompt entirely
+        if let Some(rule) = self.find_matching_rule(tool_name, parameters, danger_level).await {
+            self.log(
+                LogLevel::Info,
+                format!("Auto-approved by remembered rule {}", rule.id),
+                Some(tool_name.to_string()),
+            ).await;
+            return Ok(StepApproval {
+                required: true,
+                approved: true,
+                reason: Some(format!("Remembered rule {}", rule.id)),
+                decided_at: Some(Utc::now().to_rfc3339()),
+            });
         }
         
         // Update session status
@@ -178,6 +190,7 @@ impl MCPSession {
         let (response_sender, response_receiver) = oneshot::channel();
         
         let request = ToolApprovalRequest {
+            approval_id: approval_id.clone(),
             session_id: self.id.clone(),
             tool_name: tool_name.to_string(),
             tool_description: tool_description.to_string(),
@@ -233,9 +246,19 @@ impl MCPSession {
                     Some(tool_name.to_string()),
                 ).await;
                 
-                Ok(response.approved)
+                if response.approved {
+                    self.remember_approval(tool_name, parameters, danger_level, response.remember).await;
+                }
+                
+                Ok(StepApproval {
+                    required: true,
+                    approved: response.approved,
+                    reason: response.reason,
+                    decided_at: Some(Utc::now().to_rfc3339()),
+                })
             }
             Ok(Err(_)) => {
+                self.recover_status().await;
                 self.log(
                     LogLevel::Error,
                     "Approval response channel closed".to_string(),
@@ -249,6 +272,7 @@ impl MCPSession {
                     let mut pending = self.pending_approvals.lock().await;
                     pending.remove(&approval_id);
                 }
+                self.recover_status().await;
                 
                 self.log(
                     LogLevel::Warning,
@@ -261,21 +285,65 @@ impl MCPSession {
         }
     }
     
+    async fn find_matching_rule(&self, tool_name: &str, parameters: &serde_json::Value, danger_level: DangerLevel) -> Option<ApprovalRule> {
+        // Critical calls (shell commands, file writes) are only covered by a rule for exactly
+        // these parameters, never by a tool-wide one
+        let critical = matches!(danger_level, DangerLevel::Critical);
+        let session_rules = self.approval_rules.lock().await;
+        session_rules.iter()
+            .filter(|rule| !critical || rule.parameters.is_some())
+            .find(|rule| rule.matches(tool_name, parameters))
+            .cloned()
+            .or_else(|| {
+                crate::mcp::policy::load_persisted_rules()
+                    .into_iter()
+                    .find(|rule| rule.matches(tool_name, parameters))
+            })
+    }
+    
+    async fn remember_approval(&self, tool_name: &str, parameters: &serde_json::Value, danger_level: DangerLevel, scope: ApprovalScope) {
+        let rule = match scope {
+            ApprovalScope::Once => return,
+            ApprovalScope::Session if matches!(danger_level, DangerLevel::Critical) => {
+                self.log(
+                    LogLevel::Warning,
+                    "Critical tools can't be approved for a whole session; the approval covers this call only".to_string(),
+                    Some(tool_name.to_string()),
+                ).await;
+                return;
+            }
+            ApprovalScope::Session => {
+                let rule = ApprovalRule::new(tool_name, None, Some(self.id.clone()));
+                self.approval_rules.lock().await.push(rule.clone());
+                Ok(rule)
+            }
+            ApprovalScope::ExactParameters => crate::mcp::policy::remember_exact(tool_name, parameters),
+        };
+        
+        match rule {
+            Ok(rule) => self.log(
+                LogLevel::Info,
+                format!("Remembering approval as rule {} ({:?})", rule.id, scope),
+                Some(tool_name.to_string()),
+            ).await,
+            Err(e) => self.log(
+                LogLevel::Warning,
+                format!("Failed to remember approval: {}", e),
+                Some(tool_name.to_string()),
+            ).await,
+        }
+    }
+    
     pub async fn handle_approval_response(&self, response: ToolApprovalResponse) -> Result<(), String> {
         let mut pending = self.pending_approvals.lock().await;
         
-        // Find the pending approval (we only expect one at a time for this demo)
-        let approval_id = pending.keys().next().cloned();
-        
-        if let Some(id) = approval_id {
-            if let Some(pending_approval) = pending.remove(&id) {
+        // Several prompts can be open at once; the response answers only the one it names
+        match pending.remove(&response.approval_id) {
+            Some(pending_approval) => {
                 let _ = pending_approval.response_sender.send(response);
                 Ok(())
-            } else {
-                Err("No pending approval found".to_string())
             }
-        } else {
-            Err("No pending approvals".to_string())
+            None => Err(format!("No pending approval {} (it may have timed out)", response.approval_id)),
         }
     }
     
@@ -296,26 +364,60 @@ impl MCPSession {
         };
         
         if let Some(tool) = tool {
+            let started = std::time::Instant::now();
+            let mut step = RecordedStep {
+                index: 0,
+                tool_name: tool_name.to_string(),
+                parameters: parameters.clone(),
+                started_at: Utc::now().to_rfc3339(),
+                duration_ms: 0,
+                approval: None,
+                result: None,
+                error: None,
+                screenshot_before: None,
+                screenshot_after: None,
+            };
+            
             // Request approval if required
-            let approved = self.request_approval(
+            let approval = match self.request_approval(
                 tool_name,
                 &tool.description(),
                 &parameters,
                 tool.danger_level(),
-            ).await?;
+            ).await {
+                Ok(approval) => approval,
+                Err(e) => {
+                    step.error = Some(e.clone());
+                    self.record_step(step, started).await;
+                    return Err(e);
+                }
+            };
+            step.approval = Some(approval.clone());
             
-            if !approved {
-                return Ok(ToolExecutionResult {
+            if !approval.approved {
+                let denied = ToolExecutionResult {
                     success: false,
                     result: serde_json::json!({"error": "User denied approval"}),
                     error: Some("User denied approval".to_string()),
                     execution_time_ms: 0,
                     tool_name: tool_name.to_string(),
-                });
+                };
+                step.result = Some(denied.clone());
+                self.record_step(step, started).await;
+                return Ok(denied);
+            }
+            
+            // Read-only tools don't change the screen, so only actions get before/after captures
+            let acts_on_screen = !matches!(tool.danger_level(), DangerLevel::Low);
+            if acts_on_screen {
+                step.screenshot_before = capture_step_screenshot().await;
             }
             
-            // Execute tool
-            let result = tool.execute(parameters, &self.id).await;
+            let result = self.run_with_watchdog(tool, parameters).await;
+            
+            if acts_on_screen {
+                step.screenshot_after = capture_step_screenshot().await;
+            }
             
             // Log the result
             if let Ok(ref exec_result) = result {
@@ -332,6 +434,12 @@ impl MCPSession {
                 log_entries.push(log_entry);
             }
             
+            match &result {
+                Ok(exec_result) => step.result = Some(exec_result.clone()),
+                Err(e) => step.error = Some(e.clone()),
+            }
+            self.record_step(step, started).await;
+            
             result
         } else {
             let error_msg = format!("Unknown tool: {}", tool_name);
@@ -340,6 +448,75 @@ impl MCPSession {
         }
     }
     
+    /// Run the tool on its own task so a hung tool can't block the session, and give up on it
+    /// after the danger level's timeout with a Timeout result.
+    async fn run_with_watchdog(
+        &self,
+        tool: Box<dyn ComputerUseTool + Send + Sync>,
+        parameters: serde_json::Value,
+    ) -> Result<ToolExecutionResult, String> {
+        let tool_name = tool.name().to_string();
+        let limit = self.config.tool_timeouts.for_level(tool.danger_level());
+        let session_id = self.id.clone();
+        let mut handle = tokio::spawn(async move { tool.execute(parameters, &session_id).await });
+        
+        match tokio::time::timeout(limit, &mut handle).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                self.recover_status().await;
+                Err(format!("Tool {} crashed: {}", tool_name, e))
+            }
+            Err(_) => {
+                // Aborting only takes effect at the tool's next await; the session moves on regardless
+                handle.abort();
+                self.recover_status().await;
+                let error = format!("Timeout: {} did not finish within {} ms", tool_name, limit.as_millis());
+                self.log(LogLevel::Warning, error.clone(), Some(tool_name.clone())).await;
+                let _ = self.app_handle.emit("mcp_tool_timeout", serde_json::json!({
+                    "session_id": self.id,
+                    "tool_name": tool_name,
+                    "timeout_ms": limit.as_millis() as u64,
+                }));
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"timed_out": true, "error": error}),
+                    error: Some(error),
+                    execution_time_ms: limit.as_millis() as u64,
+                    tool_name,
+                })
+            }
+        }
+    }
+    
+    /// Put the session back to Active after a timeout or failure left it mid-approval.
+    async fn recover_status(&self) {
+        let mut status = self.status.lock().await;
+        if matches!(*status, SessionStatus::WaitingForApproval | SessionStatus::Error(_)) {
+            *status = SessionStatus::Active;
+        }
+    }
+    
+    async fn record_step(&self, mut step: RecordedStep, started: std::time::Instant) {
+        step.duration_ms = started.elapsed().as_millis() as u64;
+        let mut steps = self.recorded_steps.lock().await;
+        step.index = steps.len();
+        steps.push(step);
+    }
+    
+    /// Everything the session did, for export_mcp_session.
+    pub async fn build_report(&self) -> MCPSessionReport {
+        let mut plans: Vec<ToolExecutionPlan> = self.plans.lock().await.values().cloned().collect();
+        plans.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        
+        MCPSessionReport {
+            session: self.get_info().await,
+            plans,
+            steps: self.recorded_steps.lock().await.clone(),
+            logs: self.log_entries.lock().await.clone(),
+            exported_at: Utc::now().to_rfc3339(),
+        }
+    }
+    
     pub async fn get_available_tools(&self) -> Vec<ToolInfo> {
         let tools_guard = self.tools.lock().await;
         let mut tool_infos = Vec::new();
@@ -351,6 +528,7 @@ impl MCPSession {
                 danger_level: tool.danger_level(),
                 requires_approval: tool.requires_approval(),
                 parameters_schema: tool.parameters_schema(),
+                result_schema: tool.result_schema(),
             });
         }
         
@@ -362,19 +540,101 @@ impl MCPSession {
         user_request: &str,
         available_tools: Vec<ToolInfo>,
     ) -> Result<ToolExecutionPlan, String> {
-        use uuid::Uuid;
-        
-        // For now, create a simple demo plan
-        // TODO: Replace with actual LLM call to generate intelligent plan
         let plan_id = Uuid::new_v4().to_string();
         
-        // Basic keyword-based planning (will be replaced with LLM)
+        // The model plans with a schema-constrained reply; keyword rules cover for it when Ollama
+        // is unavailable or the plan it returns doesn't validate
+        let steps = match self.plan_with_model(user_request, &available_tools).await {
+            Ok(mut steps) if !steps.is_empty() => match validate_plan(&mut steps, &available_tools) {
+                Ok(()) => steps,
+                Err(e) => {
+                    self.log(LogLevel::Warning, format!("Model plan rejected, using keyword planning: {}", e), None).await;
+                    self.keyword_plan(user_request, &available_tools).await
+                }
+            },
+            Ok(_) => self.keyword_plan(user_request, &available_tools).await,
+            Err(e) => {
+                self.log(LogLevel::Warning, format!("Model planning unavailable, using keyword planning: {}", e), None).await;
+                self.keyword_plan(user_request, &available_tools).await
+            }
+        };
+        
+        let mut steps = steps;
+        validate_plan(&mut steps, &available_tools)?;
+        
+        let overall_risk = plan_risk(&steps);
+        let requires_approval = steps.iter().any(|s| matches!(s.danger_level, DangerLevel::Medium | DangerLevel::High | DangerLevel::Critical));
+        
+        let plan = ToolExecutionPlan {
+            session_id: self.id.clone(),
+            plan_id,
+            user_request: user_request.to_string(),
+            steps,
+            overall_risk,
+            requires_approval,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        
+        self.log(
+            LogLevel::Info,
+            format!("Generated execution plan with {} steps", plan.steps.len()),
+            None,
+        ).await;
+        
+        self.store_plan(plan.clone()).await;
+        
+        Ok(plan)
+    }
+    
+    /// Ask the session's model for a plan, constrained to a JSON schema that only admits the
+    /// available tools.
+    async fn plan_with_model(&self, user_request: &str, available_tools: &[ToolInfo]) -> Result<Vec<ToolStep>, String> {
+        let model = crate::agent_models::resolve_agent_model(&self.app_handle, "enteract").await?;
+        let tool_list = available_tools.iter()
+            .map(|tool| format!("- {}: {} Parameters: {} Result: {}", tool.name, tool.description, tool.parameters_schema, tool.result_schema))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let system = format!(
+            "You plan computer-use actions. Break the user's request into the fewest steps that accomplish it, \
+             using only these tools:\n{}\n\n\
+             Give each step a short id. A step that needs an earlier step's result sets depends_on to that step's id \
+             and refers to fields of that result as \"$result.<path>\" parameter values. \
+             After a step that opens or switches to an application, wait for it (wait_for_text when you know text \
+             that will appear, otherwise wait) before acting on its window.",
+            tool_list
+        );
+        let options = crate::generation_options::build_ollama_options(
+            "enteract",
+            None,
+            crate::generation_options::estimate_tokens(&system) + crate::generation_options::estimate_tokens(user_request),
+        );
+        let reply = crate::ollama::generate_structured(
+            &model,
+            user_request.to_string(),
+            Some(system),
+            plan_schema(available_tools),
+            Some(serde_json::Value::Object(options)),
+        ).await?;
+        steps_from_model_plan(&reply, available_tools)
+    }
+    
+    /// Keyword rules for the common requests, used when the model can't plan.
+    async fn keyword_plan(&self, user_request: &str, available_tools: &[ToolInfo]) -> Vec<ToolStep> {
         let mut steps = Vec::new();
         let request_lower = user_request.to_lowercase();
         
+        // Without quoted text, read the screen and target the element the request names
+        let wants_target = request_lower.contains("click") || (request_lower.contains("find") && request_lower.contains("text"));
+        let screen_target = match self.extract_quoted_text(user_request) {
+            None if wants_target => self.screen_target(user_request).await,
+            _ => None,
+        };
+        
         if request_lower.contains("find") && request_lower.contains("text") {
             if let Some(_) = available_tools.iter().find(|t| t.name == "find_text") {
-                let text_to_find = self.extract_quoted_text(user_request).unwrap_or_else(|| "Submit".to_string());
+                let text_to_find = self.extract_quoted_text(user_request)
+                    .or_else(|| screen_target.as_ref().map(|(text, _)| text.clone()))
+                    .unwrap_or_else(|| "Submit".to_string());
                 
                 steps.push(ToolStep {
                     step_id: Uuid::new_v4().to_string(),
@@ -390,11 +650,22 @@ impl MCPSession {
         
         if request_lower.contains("click") {
             if let Some(_) = available_tools.iter().find(|t| t.name == "click") {
+                // Feed the best find_text match into the click instead of clicking wherever the cursor is
+                let parameters = match steps.last() {
+                    Some(step) if step.tool_name == "find_text" => serde_json::json!({
+                        "x": "$result.text_locations.0.center_x",
+                        "y": "$result.text_locations.0.center_y"
+                    }),
+                    _ => match screen_target.as_ref().and_then(|(_, center)| *center) {
+                        Some((x, y)) => serde_json::json!({ "x": x, "y": y }),
+                        None => serde_json::json!({}),
+                    },
+                };
                 steps.push(ToolStep {
                     step_id: Uuid::new_v4().to_string(),
                     tool_name: "click".to_string(),
                     description: "Click on the found text location".to_string(),
-                    parameters: serde_json::json!({}),
+                    parameters,
                     depends_on: steps.last().map(|s| s.step_id.clone()),
                     danger_level: DangerLevel::Medium,
                     estimated_duration_ms: Some(500),
@@ -416,35 +687,141 @@ impl MCPSession {
             }
         }
         
-        let overall_risk = steps.iter()
-            .map(|s| s.danger_level)
-            .max_by_key(|&level| match level {
-                DangerLevel::Low => 1,
-                DangerLevel::Medium => 2,
-                DangerLevel::High => 3,
-                DangerLevel::Critical => 4,
-            })
-            .unwrap_or(DangerLevel::Low);
+        steps
+    }
+    
+    /// Run a plan's steps, starting each once its dependency has succeeded. Read-only steps that
+    /// are ready together run concurrently (at most `max_parallel` at a time); steps that act on
+    /// the screen always run alone. Results come back in plan order.
+    pub async fn execute_plan(&self, plan: &ToolExecutionPlan, max_parallel: usize) -> Vec<ToolExecutionResult> {
+        let total = plan.steps.len();
+        // Concurrency follows the registered tools, never the levels a plan declares for itself
+        let read_only: Vec<bool> = {
+            let tools = self.tools.lock().await;
+            plan.steps.iter()
+                .map(|step| tools.get(&step.tool_name).is_some_and(|tool| matches!(tool.danger_level(), DangerLevel::Low)))
+                .collect()
+        };
+        let mut results: Vec<Option<ToolExecutionResult>> = vec![None; total];
+        let index_of: HashMap<&str, usize> = plan.steps.iter()
+            .enumerate()
+            .map(|(i, step)| (step.step_id.as_str(), i))
+            .collect();
         
-        let requires_approval = steps.iter().any(|s| matches!(s.danger_level, DangerLevel::Medium | DangerLevel::High | DangerLevel::Critical));
+        while results.iter().any(|r| r.is_none()) {
+            let mut ready = Vec::new();
+            for (i, step) in plan.steps.iter().enumerate() {
+                if results[i].is_some() {
+                    continue;
+                }
+                let dependency = step.depends_on.as_deref().and_then(|id| index_of.get(id).copied());
+                match dependency.map(|d| results[d].as_ref().map(|r| r.success)) {
+                    Some(None) => continue, // Dependency still pending
+                    Some(Some(false)) => {
+                        results[i] = Some(skipped_result(&step.tool_name, "dependency failed"));
+                    }
+                    _ => ready.push(i),
+                }
+            }
+            if ready.is_empty() {
+                // Only a dependency cycle or a dangling reference gets here
+                for (i, step) in plan.steps.iter().enumerate() {
+                    if results[i].is_none() {
+                        results[i] = Some(skipped_result(&step.tool_name, "dependency never ran"));
+                    }
+                }
+                break;
+            }
+            
+            let (parallel, serial): (Vec<usize>, Vec<usize>) = ready.into_iter()
+                .partition(|&i| read_only[i]);
+            
+            for chunk in parallel.chunks(max_parallel.max(1)) {
+                let runs = chunk.iter().map(|&i| self.execute_plan_step(plan, i, &results));
+                let outcomes = futures_util::future::join_all(runs).await;
+                for (&i, outcome) in chunk.iter().zip(outcomes) {
+                    results[i] = Some(outcome);
+                }
+                self.emit_plan_progress(plan, &results);
+            }
+            for i in serial {
+                let outcome = self.execute_plan_step(plan, i, &results).await;
+                results[i] = Some(outcome);
+                self.emit_plan_progress(plan, &results);
+            }
+        }
         
-        let plan = ToolExecutionPlan {
-            session_id: self.id.clone(),
-            plan_id,
-            user_request: user_request.to_string(),
-            steps,
-            overall_risk,
-            requires_approval,
-            created_at: chrono::Utc::now().to_rfc3339(),
-        };
+        results.into_iter().flatten().collect()
+    }
+    
+    async fn execute_plan_step(
+        &self,
+        plan: &ToolExecutionPlan,
+        index: usize,
+        results: &[Option<ToolExecutionResult>],
+    ) -> ToolExecutionResult {
+        let step = &plan.steps[index];
+        let dependency_result = step.depends_on.as_deref()
+            .and_then(|id| plan.steps.iter().position(|s| s.step_id == id))
+            .and_then(|d| results[d].as_ref())
+            .map(|r| &r.result);
         
-        self.log(
-            LogLevel::Info,
-            format!("Generated execution plan with {} steps", plan.steps.len()),
-            None,
-        ).await;
+        let parameters = match resolve_result_references(&step.parameters, dependency_result) {
+            Ok(parameters) => parameters,
+            Err(e) => return skipped_result(&step.tool_name, &e),
+        };
         
-        Ok(plan)
+        match self.execute_tool(&step.tool_name, parameters).await {
+            Ok(result) => result,
+            Err(e) => ToolExecutionResult {
+                success: false,
+                result: serde_json::json!({"error": e}),
+                error: Some(e),
+                execution_time_ms: 0,
+                tool_name: step.tool_name.clone(),
+            },
+        }
+    }
+    
+    fn emit_plan_progress(&self, plan: &ToolExecutionPlan, results: &[Option<ToolExecutionResult>]) {
+        let completed = results.iter().filter(|r| r.is_some()).count();
+        let failed = results.iter().flatten().filter(|r| !r.success).count();
+        let _ = self.app_handle.emit("mcp_plan_progress", serde_json::json!({
+            "session_id": self.id,
+            "plan_id": plan.plan_id,
+            "completed": completed,
+            "failed": failed,
+            "total": results.len(),
+            "step_status": results.iter().map(|r| match r {
+                None => "pending",
+                Some(r) if r.success => "succeeded",
+                Some(_) => "failed",
+            }).collect::<Vec<_>>(),
+        }));
+    }
+    
+    pub async fn store_plan(&self, plan: ToolExecutionPlan) {
+        let mut plans = self.plans.lock().await;
+        plans.insert(plan.plan_id.clone(), plan);
+    }
+    
+    pub async fn get_plan(&self, plan_id: &str) -> Option<ToolExecutionPlan> {
+        let plans = self.plans.lock().await;
+        plans.get(plan_id).cloned()
+    }
+    
+    /// The on-screen element a request refers to, by its text, with its center when the vision
+    /// model gave a region. None when the screen can't be read or nothing on it is mentioned.
+    async fn screen_target(&self, user_request: &str) -> Option<(String, Option<(i32, i32)>)> {
+        let inventory = match crate::screen_inventory::capture_inventory(&self.app_handle).await {
+            Ok(inventory) => inventory,
+            Err(e) => {
+                self.log(LogLevel::Warning, format!("Could not read the screen for planning: {}", e), None).await;
+                return None;
+            }
+        };
+        let element = inventory.element_named_in(user_request)?;
+        Some((element.text.clone(), inventory.center_of(element)))
     }
     
     fn extract_quoted_text(&self, text: &str) -> Option<String> {
@@ -461,6 +838,25 @@ impl MCPSession {
         }
         None
     }
+    /// What the session was in the middle of: its plans, unanswered approvals and the steps it ran.
+    pub async fn snapshot(&self) -> serde_json::Value {
+        let status = self.status.lock().await.clone();
+        let plans: Vec<ToolExecutionPlan> = self.plans.lock().await.values().cloned().collect();
+        let pending: Vec<ToolApprovalRequest> = self.pending_approvals.lock().await
+            .values()
+            .map(|approval| approval.request.clone())
+            .collect();
+        let recorded_steps = self.recorded_steps.lock().await.clone();
+        serde_json::json!({
+            "id": self.id,
+            "createdAt": self.created_at,
+            "status": status,
+            "plans": plans,
+            "pendingApprovals": pending,
+            "recordedSteps": recorded_steps,
+        })
+    }
+    
     pub async fn cleanup(&self) -> Result<(), String> {
         self.log(LogLevel::Info, "Cleaning up session".to_string(), None).await;
         
@@ -478,4 +874,233 @@ impl MCPSession {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+async fn capture_step_screenshot() -> Option<String> {
+    match crate::screenshot::capture_screenshot().await {
+        Ok(shot) => Some(format!("data:image/{};base64,{}", shot.format, shot.image_base64)),
+        Err(e) => {
+            log::warn!("Failed to capture step screenshot: {}", e);
+            None
+        }
+    }
+}
+
+// Prefix for step parameters that take a value from the depends_on step's result,
+// e.g. "$result.text_locations.0.center_x"
+const RESULT_REFERENCE_PREFIX: &str = "$result.";
+
+fn skipped_result(tool_name: &str, why: &str) -> ToolExecutionResult {
+    let error = format!("Skipped: {}", why);
+    ToolExecutionResult {
+        success: false,
+        result: serde_json::json!({"error": error}),
+        error: Some(error),
+        execution_time_ms: 0,
+        tool_name: tool_name.to_string(),
+    }
+}
+
+/// Replace `$result.` parameter values with the matching value from the dependency's result.
+fn resolve_result_references(
+    parameters: &serde_json::Value,
+    dependency_result: Option<&serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    let mut resolved = parameters.clone();
+    let Some(fields) = resolved.as_object_mut() else {
+        return Ok(resolved);
+    };
+    for (name, value) in fields.iter_mut() {
+        let Some(path) = value.as_str().and_then(|v| v.strip_prefix(RESULT_REFERENCE_PREFIX)) else {
+            continue;
+        };
+        let source = dependency_result
+            .ok_or(format!("'{}' references a result but the step has no dependency", name))?;
+        let found = path.split('.').try_fold(source, |node, segment| match segment.parse::<usize>() {
+            Ok(index) if node.is_array() => node.get(index),
+            _ => node.get(segment),
+        });
+        *value = found.cloned().ok_or(format!("Previous result has no value at '{}'", path))?;
+    }
+    Ok(resolved)
+}
+
+/// Walk a dotted path through a JSON schema; numeric segments index into arrays.
+fn schema_at_path<'a>(schema: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.split('.').try_fold(schema, |node, segment| {
+        match node["type"].as_str() {
+            Some("array") if segment.parse::<usize>().is_ok() => node.get("items"),
+            _ => node.get("properties").and_then(|props| props.get(segment)),
+        }
+    })
+}
+
+/// JSON schema for a model-written plan: steps naming one of `available_tools` each.
+fn plan_schema(available_tools: &[ToolInfo]) -> serde_json::Value {
+    let tool_names: Vec<&str> = available_tools.iter().map(|tool| tool.name.as_str()).collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "steps": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "tool_name": { "type": "string", "enum": tool_names },
+                        "description": { "type": "string" },
+                        "parameters": { "type": "object" },
+                        "depends_on": { "type": ["string", "null"] }
+                    },
+                    "required": ["id", "tool_name", "description", "parameters"]
+                }
+            }
+        },
+        "required": ["steps"]
+    })
+}
+
+#[derive(serde::Deserialize)]
+struct ModelPlanStep {
+    id: String,
+    tool_name: String,
+    description: String,
+    #[serde(default)]
+    parameters: serde_json::Value,
+    depends_on: Option<String>,
+}
+
+/// Turn the model's plan into steps: its short ids become step ids, and the danger level comes
+/// from the tool rather than the model.
+fn steps_from_model_plan(reply: &serde_json::Value, available_tools: &[ToolInfo]) -> Result<Vec<ToolStep>, String> {
+    let planned: Vec<ModelPlanStep> = serde_json::from_value(reply["steps"].clone())
+        .map_err(|e| format!("Model plan doesn't match the plan schema: {}", e))?;
+    let mut step_ids: HashMap<String, String> = HashMap::new();
+    let mut steps = Vec::with_capacity(planned.len());
+    for step in planned {
+        let tool = available_tools.iter().find(|tool| tool.name == step.tool_name)
+            .ok_or(format!("Model plan uses an unknown tool: {}", step.tool_name))?;
+        let depends_on = match step.depends_on.filter(|id| !id.is_empty()) {
+            Some(id) => Some(step_ids.get(&id).cloned()
+                .ok_or(format!("Step '{}' depends on '{}', which is not an earlier step", step.description, id))?),
+            None => None,
+        };
+        let step_id = Uuid::new_v4().to_string();
+        step_ids.insert(step.id, step_id.clone());
+        steps.push(ToolStep {
+            step_id,
+            tool_name: step.tool_name,
+            description: step.description,
+            parameters: if step.parameters.is_null() { serde_json::json!({}) } else { step.parameters },
+            depends_on,
+            danger_level: tool.danger_level,
+            estimated_duration_ms: None,
+        });
+    }
+    Ok(steps)
+}
+
+/// The riskiest step's danger level.
+pub fn plan_risk(steps: &[ToolStep]) -> DangerLevel {
+    steps.iter()
+        .map(|s| s.danger_level)
+        .max_by_key(|&level| match level {
+            DangerLevel::Low => 1,
+            DangerLevel::Medium => 2,
+            DangerLevel::High => 3,
+            DangerLevel::Critical => 4,
+        })
+        .unwrap_or(DangerLevel::Low)
+}
+
+/// Check every step uses a known tool, depends only on earlier steps, and that each
+/// `$result.` reference resolves in the dependency's result schema with a compatible type.
+/// Each step's danger level is reset to its tool's, whatever the plan claimed.
+pub fn validate_plan(steps: &mut [ToolStep], available_tools: &[ToolInfo]) -> Result<(), String> {
+    let mut earlier: HashMap<String, &ToolInfo> = HashMap::new();
+    
+    for step in steps.iter_mut() {
+        let tool = available_tools.iter().find(|t| t.name == step.tool_name)
+            .ok_or(format!("Plan uses a tool this build doesn't provide: {}", step.tool_name))?;
+        step.danger_level = tool.danger_level;
+        let dependency = match &step.depends_on {
+            Some(id) => Some(*earlier.get(id.as_str())
+                .ok_or(format!("Step '{}' depends on '{}', which is not an earlier step", step.description, id))?),
+            None => None,
+        };
+        
+        for (name, value) in step.parameters.as_object().into_iter().flatten() {
+            let Some(path) = value.as_str().and_then(|v| v.strip_prefix(RESULT_REFERENCE_PREFIX)) else {
+                continue;
+            };
+            let source = dependency
+                .ok_or(format!("Step '{}' uses {} without depending on another step", step.description, value))?;
+            let provided = schema_at_path(&source.result_schema, path)
+                .ok_or(format!("{} result has no field '{}' (needed by step '{}')", source.name, path, step.description))?;
+            
+            // Only compare types both sides declare; integers are acceptable numbers
+            let expected = tool.parameters_schema["properties"][name]["type"].as_str();
+            let actual = provided["type"].as_str();
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                if expected != actual && !(expected == "number" && actual == "integer") {
+                    return Err(format!(
+                        "Step '{}' expects {} for '{}' but {} provides {} at '{}'",
+                        step.description, expected, name, source.name, actual, path
+                    ));
+                }
+            }
+        }
+        
+        earlier.insert(step.step_id.clone(), tool);
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str, danger_level: DangerLevel) -> ToolInfo {
+        ToolInfo {
+            name: name.to_string(),
+            description: String::new(),
+            danger_level,
+            requires_approval: false,
+            parameters_schema: serde_json::json!({}),
+            result_schema: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_model_plan_ids_become_step_ids_and_danger_comes_from_the_tool() {
+        let tools = vec![tool("find_text", DangerLevel::Low), tool("click", DangerLevel::Medium)];
+        let reply = serde_json::json!({ "steps": [
+            { "id": "find", "tool_name": "find_text", "description": "Find Submit", "parameters": { "text": "Submit" } },
+            { "id": "press", "tool_name": "click", "description": "Click it", "parameters": {}, "depends_on": "find" }
+        ]});
+
+        let steps = steps_from_model_plan(&reply, &tools).unwrap();
+        assert_eq!(steps[1].depends_on.as_deref(), Some(steps[0].step_id.as_str()));
+        assert!(matches!(steps[1].danger_level, DangerLevel::Medium));
+
+        let forward = serde_json::json!({ "steps": [
+            { "id": "press", "tool_name": "click", "description": "Click it", "parameters": {}, "depends_on": "find" }
+        ]});
+        assert!(steps_from_model_plan(&forward, &tools).is_err());
+        assert!(plan_schema(&tools)["properties"]["steps"]["items"]["properties"]["tool_name"]["enum"] == serde_json::json!(["find_text", "click"]));
+    }
+
+    #[test]
+    fn test_validate_plan_replaces_declared_danger_with_the_tools() {
+        let tools = vec![tool("run_command", DangerLevel::Critical)];
+        // An imported file claiming a shell command is harmless
+        let mut steps: Vec<ToolStep> = serde_json::from_value(serde_json::json!([
+            { "step_id": "a", "tool_name": "run_command", "description": "Run it", "parameters": {}, "depends_on": null, "danger_level": "Low", "estimated_duration_ms": null }
+        ])).unwrap();
+
+        validate_plan(&mut steps, &tools).unwrap();
+        assert!(matches!(steps[0].danger_level, DangerLevel::Critical));
+        assert!(matches!(plan_risk(&steps), DangerLevel::Critical));
+    }
+}
@@ -0,0 +1,343 @@
+// src-tauri/src/mcp/shell.rs
+// Shell command tool for execution plans. It only exists in sessions created with
+// allow_shell_commands, is Critical so every run goes through MCPSession::request_approval, and
+// hands the model at most MAX_OUTPUT_CHARS of each output stream. At most MAX_CAPTURE_BYTES of
+// each stream is held in memory while the command runs; the rest is read and dropped.
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+use crate::mcp::tools::ComputerUseTool;
+use crate::mcp::types::*;
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const MAX_OUTPUT_CHARS: usize = 8_000;
+const MAX_CAPTURE_BYTES: usize = 256 * 1024;
+
+/// The shell tools for a session, keyed by name; empty unless the session allows shell commands.
+pub fn shell_tools(config: &MCPSessionConfig) -> HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> {
+    let mut tools: HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> = HashMap::new();
+    if config.allow_shell_commands {
+        // Leave the session's watchdog a moment to collect the result after a timeout
+        let max_timeout_ms = config.tool_timeouts.critical_ms.saturating_sub(1_000).max(1_000);
+        tools.insert("run_command".to_string(), Box::new(RunCommandTool { max_timeout_ms }));
+    }
+    tools
+}
+
+/// At most `max` characters of `text`, keeping the start and the end (where errors usually are).
+/// Returns whether anything was cut.
+fn truncate_output(text: &str, max: usize) -> (String, bool) {
+    let total = text.chars().count();
+    if total <= max {
+        return (text.to_string(), false);
+    }
+    let head: String = text.chars().take(max * 2 / 3).collect();
+    let tail: String = text.chars().skip(total - (max - max * 2 / 3)).collect();
+    (format!("{}\n… [{} characters omitted] …\n{}", head, total - max, tail), true)
+}
+
+/// Read a pipe to the end, keeping at most `max` bytes: the first and last halves, with a marker
+/// for what was dropped in between. Returns whether anything was dropped.
+async fn read_capped(mut pipe: impl AsyncRead + Unpin, max: usize) -> std::io::Result<(String, bool)> {
+    let keep_head = max / 2;
+    let keep_tail = max - keep_head;
+    let mut head = Vec::new();
+    let mut tail = VecDeque::new();
+    let mut total = 0usize;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = pipe.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        total += n;
+        let mut chunk = &buf[..n];
+        if head.len() < keep_head {
+            let take = (keep_head - head.len()).min(chunk.len());
+            head.extend_from_slice(&chunk[..take]);
+            chunk = &chunk[take..];
+        }
+        tail.extend(chunk.iter().copied());
+        if tail.len() > keep_tail {
+            tail.drain(..tail.len() - keep_tail);
+        }
+    }
+
+    let dropped = total - head.len() - tail.len();
+    let tail: Vec<u8> = tail.into_iter().collect();
+    if dropped == 0 {
+        head.extend_from_slice(&tail);
+        return Ok((String::from_utf8_lossy(&head).into_owned(), false));
+    }
+    Ok((format!("{}\n… [{} bytes omitted] …\n{}", String::from_utf8_lossy(&head), dropped, String::from_utf8_lossy(&tail)), true))
+}
+
+/// Wait for the command while draining both pipes under the capture cap.
+async fn capture(mut child: tokio::process::Child) -> Result<(std::process::ExitStatus, (String, bool), (String, bool)), String> {
+    let stdout = child.stdout.take().ok_or("Command has no stdout pipe")?;
+    let stderr = child.stderr.take().ok_or("Command has no stderr pipe")?;
+    tokio::try_join!(
+        child.wait(),
+        read_capped(stdout, MAX_CAPTURE_BYTES),
+        read_capped(stderr, MAX_CAPTURE_BYTES),
+    )
+    .map_err(|e| format!("Failed to run command: {}", e))
+}
+
+#[cfg(windows)]
+fn shell(command_line: &str) -> Command {
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut command = Command::new("cmd");
+    command.args(["/C", command_line]);
+    command.creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell(command_line: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.args(["-c", command_line]);
+    // Its own process group, so ProcessTree can signal everything the command starts
+    command.process_group(0);
+    command
+}
+
+/// Everything a command started: its process group on Unix, a kill-on-close job object on
+/// Windows. Dropping it kills the lot, so nothing the shell spawned outlives a timeout, a
+/// watchdog abort, or leftovers the command put in the background.
+struct ProcessTree {
+    #[cfg(unix)]
+    process_group: libc::pid_t,
+    #[cfg(windows)]
+    job: windows::Win32::Foundation::HANDLE,
+}
+
+impl ProcessTree {
+    #[cfg(unix)]
+    fn new(child: &tokio::process::Child) -> Result<Self, String> {
+        let pid = child.id().ok_or("The command exited before it could be tracked")?;
+        Ok(Self { process_group: pid as libc::pid_t })
+    }
+
+    #[cfg(windows)]
+    fn new(child: &tokio::process::Child) -> Result<Self, String> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::HANDLE;
+        use windows::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        let process = child.raw_handle().ok_or("The command exited before it could be tracked")?;
+        unsafe {
+            let job = CreateJobObjectW(None, PCWSTR::null()).map_err(|e| format!("Failed to create a job object: {}", e))?;
+            // Owned from here, so an error below still closes it
+            let tree = Self { job };
+            let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &limits as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ).map_err(|e| format!("Failed to configure the job object: {}", e))?;
+            AssignProcessToJobObject(job, HANDLE(process as isize))
+                .map_err(|e| format!("Failed to track the command's processes: {}", e))?;
+            Ok(tree)
+        }
+    }
+}
+
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            libc::killpg(self.process_group, libc::SIGKILL);
+        }
+        #[cfg(windows)]
+        unsafe {
+            let _ = windows::Win32::System::JobObjects::TerminateJobObject(self.job, 1);
+            let _ = windows::Win32::Foundation::CloseHandle(self.job);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RunCommandParams {
+    command: String,
+    cwd: Option<String>,
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Clone)]
+pub struct RunCommandTool {
+    max_timeout_ms: u64,
+}
+
+#[async_trait]
+impl ComputerUseTool for RunCommandTool {
+    fn name(&self) -> &str { "run_command" }
+
+    fn description(&self) -> String {
+        let shell = if cfg!(windows) { "cmd" } else { "sh" };
+        format!("Run a shell command with {} and return its exit code, stdout and stderr (long output is truncated)", shell)
+    }
+
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Critical }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "Command line to run"
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Working directory (default: the app's)"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "description": format!("Kill the command after this long (default: {}, max: {})", DEFAULT_TIMEOUT_MS.min(self.max_timeout_ms), self.max_timeout_ms),
+                    "default": DEFAULT_TIMEOUT_MS.min(self.max_timeout_ms)
+                }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "exit_code": { "type": "integer" },
+                "stdout": { "type": "string" },
+                "stderr": { "type": "string" },
+                "truncated": { "type": "boolean" },
+                "timed_out": { "type": "boolean" }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        let params: RunCommandParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for run_command: {}", e))?;
+        if params.command.trim().is_empty() {
+            return Err("No command given".to_string());
+        }
+        let timeout_ms = params.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS).clamp(1, self.max_timeout_ms);
+
+        log::info!("Session {}: Running command `{}` (timeout {}ms)", session_id, params.command, timeout_ms);
+
+        let mut command = shell(&params.command);
+        if let Some(cwd) = &params.cwd {
+            command.current_dir(cwd);
+        }
+        command
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+
+        // The tree is dropped, killing whatever is still running, on every way out of here,
+        // including the watchdog dropping this future
+        let started = command.spawn()
+            .map_err(|e| format!("Failed to start command: {}", e))
+            .and_then(|child| ProcessTree::new(&child).map(|tree| (child, tree)));
+        let output = match started {
+            Ok((child, _tree)) => tokio::time::timeout(Duration::from_millis(timeout_ms), capture(child)).await,
+            Err(e) => Ok(Err(e)),
+        };
+        let execution_time = start_time.elapsed().as_millis() as u64;
+
+        let output = match output {
+            Ok(Ok(output)) => output,
+            Ok(Err(error_msg)) => {
+                return Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                });
+            }
+            Err(_) => {
+                let error_msg = format!("Command did not finish within {} ms and was killed", timeout_ms);
+                return Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "timed_out": true, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                });
+            }
+        };
+
+        let (status, (stdout, stdout_capped), (stderr, stderr_capped)) = output;
+        let (stdout, stdout_cut) = truncate_output(&stdout, MAX_OUTPUT_CHARS);
+        let (stderr, stderr_cut) = truncate_output(&stderr, MAX_OUTPUT_CHARS);
+        let exit_code = status.code();
+        let success = status.success();
+
+        Ok(ToolExecutionResult {
+            success,
+            result: serde_json::json!({
+                "success": success,
+                "exit_code": exit_code,
+                "stdout": stdout,
+                "stderr": stderr,
+                "truncated": stdout_cut || stderr_cut || stdout_capped || stderr_capped,
+                "timed_out": false
+            }),
+            error: (!success).then(|| match exit_code {
+                Some(code) => format!("Command exited with code {}", code),
+                None => "Command was terminated by a signal".to_string(),
+            }),
+            execution_time_ms: execution_time,
+            tool_name: self.name().to_string(),
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_output_keeps_its_start_and_end() {
+        assert_eq!(truncate_output("short", 10), ("short".to_string(), false));
+
+        let text: String = (0..100).map(|i| char::from(b'a' + (i % 26) as u8)).collect();
+        let (cut, truncated) = truncate_output(&text, 30);
+        assert!(truncated);
+        assert!(cut.starts_with(&text[..20]));
+        assert!(cut.ends_with(&text[90..]));
+        assert!(cut.contains("[70 characters omitted]"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_keeps_at_most_the_cap() {
+        let (text, capped) = read_capped(&b"fits"[..], 16).await.unwrap();
+        assert_eq!((text.as_str(), capped), ("fits", false));
+
+        let flood = vec![b'x'; 100_000];
+        let mut stream = b"start".to_vec();
+        stream.extend_from_slice(&flood);
+        stream.extend_from_slice(b"end");
+        let (text, capped) = read_capped(&stream[..], 64).await.unwrap();
+        assert!(capped);
+        assert!(text.starts_with("start"));
+        assert!(text.ends_with("end"));
+        assert!(text.contains(&format!("[{} bytes omitted]", stream.len() - 64)));
+    }
+}
@@ -13,10 +13,52 @@ pub trait ComputerUseTool: Send + Sync {
         matches!(self.danger_level(), DangerLevel::Medium | DangerLevel::High | DangerLevel::Critical)
     }
     fn parameters_schema(&self) -> serde_json::Value;
+    // Shape of ToolExecutionResult.result on success, used to check data flow between plan steps
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({ "type": "object" })
+    }
     async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String>;
     fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync>;
 }
 
+/// Every computer use tool, keyed by name.
+pub fn builtin_tools() -> std::collections::HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> {
+    let mut tools: std::collections::HashMap<String, Box<dyn ComputerUseTool + Send + Sync>> = std::collections::HashMap::new();
+    
+    // Register computer use tools
+    tools.insert("click".to_string(), Box::new(ClickTool));
+    tools.insert("type".to_string(), Box::new(TypeTool));
+    tools.insert("scroll".to_string(), Box::new(ScrollTool));
+    tools.insert("drag".to_string(), Box::new(DragTool));
+    tools.insert("move_mouse".to_string(), Box::new(MoveMouseTool));
+    tools.insert("key_press".to_string(), Box::new(KeyPressTool));
+    tools.insert("get_cursor_position".to_string(), Box::new(GetCursorPositionTool));
+    tools.insert("get_screen_info".to_string(), Box::new(GetScreenInfoTool));
+    tools.insert("take_screenshot".to_string(), Box::new(ScreenshotTool));
+    tools.insert("list_windows".to_string(), Box::new(ListWindowsTool));
+    tools.insert("focus_window".to_string(), Box::new(FocusWindowTool));
+    tools.insert("move_resize_window".to_string(), Box::new(MoveResizeWindowTool));
+    tools.insert("get_ui_elements".to_string(), Box::new(GetUiElementsTool));
+    tools.insert("click_element".to_string(), Box::new(ClickElementTool));
+    
+    // Register launch tools (open_app is Critical, so it always asks, even when approvals are off)
+    tools.insert("open_app".to_string(), Box::new(OpenAppTool));
+    tools.insert("open_url".to_string(), Box::new(OpenUrlTool));
+    
+    // Register new atomic OCR tools
+    tools.insert("find_text".to_string(), Box::new(FindTextTool));
+    tools.insert("click_at".to_string(), Box::new(ClickAtTool));
+    tools.insert("debug_ocr".to_string(), Box::new(DebugOcrTool));
+    tools.insert("wait".to_string(), Box::new(WaitTool));
+    tools.insert("wait_for_text".to_string(), Box::new(WaitForTextTool));
+    
+    // Register compound tools (require approval)
+    tools.insert("click_on_text".to_string(), Box::new(ClickOnTextTool));
+    tools.insert("click_and_type".to_string(), Box::new(ClickAndTypeTool));
+    
+    tools
+}
+
 // Click tool implementation
 #[derive(Clone)]
 pub struct ClickTool;
@@ -43,6 +85,18 @@ impl ComputerUseTool for ClickTool {
                     "type": "integer", 
                     "description": "Y coordinate (optional, uses current position if not provided)"
                 },
+                "x_pct": {
+                    "type": "number",
+                    "description": "X as a percentage (0-100) of the target monitor's width; overrides x"
+                },
+                "y_pct": {
+                    "type": "number",
+                    "description": "Y as a percentage (0-100) of the target monitor's height; overrides y"
+                },
+                "monitor": {
+                    "type": "integer",
+                    "description": "Monitor index from get_screen_info for percentage coordinates (default: primary)"
+                },
                 "button": {
                     "type": "string",
                     "enum": ["left", "right", "middle"],
@@ -53,9 +107,10 @@ impl ComputerUseTool for ClickTool {
         })
     }
     
-    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+    async fn execute(&self, mut params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
+        resolve_percentage_coordinates(&mut params)?;
         let click_params: ClickParams = serde_json::from_value(params)
             .map_err(|e| format!("Invalid parameters for click: {}", e))?;
         
@@ -192,7 +247,7 @@ impl ComputerUseTool for ScrollTool {
     fn name(&self) -> &str { "scroll" }
     
     fn description(&self) -> String {
-        "Scroll in a specified direction".to_string()
+        "Scroll up, down, left or right, optionally smoothly in small steps for pages that react to scroll speed".to_string()
     }
     
     fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
@@ -211,6 +266,16 @@ impl ComputerUseTool for ScrollTool {
                     "description": "Amount to scroll (default: 3)",
                     "default": 3
                 },
+                "smooth": {
+                    "type": "boolean",
+                    "description": "Send many small wheel deltas over duration_ms instead of one jump",
+                    "default": false
+                },
+                "duration_ms": {
+                    "type": "integer",
+                    "description": "Length of a smooth scroll in milliseconds (default: 300, max: 5000)",
+                    "default": 300
+                },
                 "x": {
                     "type": "integer",
                     "description": "X coordinate for scroll location (optional)"
@@ -218,15 +283,28 @@ impl ComputerUseTool for ScrollTool {
                 "y": {
                     "type": "integer",
                     "description": "Y coordinate for scroll location (optional)"
+                },
+                "x_pct": {
+                    "type": "number",
+                    "description": "X as a percentage (0-100) of the target monitor's width; overrides x"
+                },
+                "y_pct": {
+                    "type": "number",
+                    "description": "Y as a percentage (0-100) of the target monitor's height; overrides y"
+                },
+                "monitor": {
+                    "type": "integer",
+                    "description": "Monitor index from get_screen_info for percentage coordinates (default: primary)"
                 }
             },
             "required": ["direction"]
         })
     }
     
-    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+    async fn execute(&self, mut params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
+        resolve_percentage_coordinates(&mut params)?;
         let scroll_params: ScrollParams = serde_json::from_value(params)
             .map_err(|e| format!("Invalid parameters for scroll: {}", e))?;
         
@@ -243,6 +321,7 @@ impl ComputerUseTool for ScrollTool {
                         "success": true,
                         "direction": scroll_params.direction,
                         "amount": scroll_params.amount.unwrap_or(3),
+                        "smooth": scroll_params.smooth.unwrap_or(false),
                         "message": "Successfully scrolled"
                     }),
                     error: None,
@@ -268,6 +347,272 @@ impl ComputerUseTool for ScrollTool {
     }
 }
 
+// Drag tool implementation
+#[derive(Clone)]
+pub struct DragTool;
+
+#[async_trait]
+impl ComputerUseTool for DragTool {
+    fn name(&self) -> &str { "drag" }
+    
+    fn description(&self) -> String {
+        "Press a mouse button at one point, move smoothly to another and release it there (move windows, drag sliders, select text)".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Medium }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "x1": {
+                    "type": "integer",
+                    "description": "X coordinate where the button is pressed"
+                },
+                "y1": {
+                    "type": "integer",
+                    "description": "Y coordinate where the button is pressed"
+                },
+                "x2": {
+                    "type": "integer",
+                    "description": "X coordinate where the button is released"
+                },
+                "y2": {
+                    "type": "integer",
+                    "description": "Y coordinate where the button is released"
+                },
+                "x1_pct": {
+                    "type": "number",
+                    "description": "Start X as a percentage (0-100) of the target monitor's width; overrides x1"
+                },
+                "y1_pct": {
+                    "type": "number",
+                    "description": "Start Y as a percentage (0-100) of the target monitor's height; overrides y1"
+                },
+                "x2_pct": {
+                    "type": "number",
+                    "description": "End X as a percentage (0-100) of the target monitor's width; overrides x2"
+                },
+                "y2_pct": {
+                    "type": "number",
+                    "description": "End Y as a percentage (0-100) of the target monitor's height; overrides y2"
+                },
+                "monitor": {
+                    "type": "integer",
+                    "description": "Monitor index from get_screen_info for percentage coordinates (default: primary)"
+                },
+                "duration_ms": {
+                    "type": "integer",
+                    "description": "How long the move from start to end takes in milliseconds (default: 500, max: 10000)",
+                    "default": 500
+                },
+                "button": {
+                    "type": "string",
+                    "enum": ["left", "right", "middle"],
+                    "default": "left",
+                    "description": "Mouse button to hold during the drag"
+                }
+            },
+            "required": ["x1", "y1", "x2", "y2"]
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "from": position_schema(),
+                "to": position_schema(),
+                "duration_ms": { "type": "integer" }
+            }
+        })
+    }
+    
+    async fn execute(&self, mut params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        resolve_percentage_fields(&mut params, "x1", "y1")?;
+        resolve_percentage_fields(&mut params, "x2", "y2")?;
+        let drag_params: DragParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for drag: {}", e))?;
+        
+        let from = (drag_params.x1, drag_params.y1);
+        let to = (drag_params.x2, drag_params.y2);
+        let duration_ms = drag_params.duration_ms.unwrap_or(DEFAULT_DRAG_DURATION_MS).min(MAX_DRAG_DURATION_MS);
+        let button = drag_params.button.unwrap_or(MouseButton::Left);
+        
+        log::info!("Session {}: Dragging from {:?} to {:?} with {:?} button over {}ms", session_id, from, to, button, duration_ms);
+        
+        let result = perform_drag(from, to, duration_ms, button).await;
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(_) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "from": { "x": from.0, "y": from.1 },
+                        "to": { "x": to.0, "y": to.1 },
+                        "button": button,
+                        "duration_ms": duration_ms,
+                        "message": format!("Successfully dragged from ({}, {}) to ({}, {})", from.0, from.1, to.0, to.1)
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to drag: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+// Move mouse tool implementation
+#[derive(Clone)]
+pub struct MoveMouseTool;
+
+#[async_trait]
+impl ComputerUseTool for MoveMouseTool {
+    fn name(&self) -> &str { "move_mouse" }
+    
+    fn description(&self) -> String {
+        "Move the cursor to coordinates without clicking, optionally easing there and waiting, to hover over tooltips and menus".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "x": {
+                    "type": "integer",
+                    "description": "X coordinate to move to"
+                },
+                "y": {
+                    "type": "integer",
+                    "description": "Y coordinate to move to"
+                },
+                "x_pct": {
+                    "type": "number",
+                    "description": "X as a percentage (0-100) of the target monitor's width; overrides x"
+                },
+                "y_pct": {
+                    "type": "number",
+                    "description": "Y as a percentage (0-100) of the target monitor's height; overrides y"
+                },
+                "monitor": {
+                    "type": "integer",
+                    "description": "Monitor index from get_screen_info for percentage coordinates (default: primary)"
+                },
+                "duration_ms": {
+                    "type": "integer",
+                    "description": "Ease from the current position over this many milliseconds (default: 0, jump straight there; max: 10000)",
+                    "default": 0
+                },
+                "jitter_px": {
+                    "type": "integer",
+                    "description": "Wobble the eased path by up to this many pixels so it looks hand-made (default: 0, max: 20)",
+                    "default": 0
+                },
+                "dwell_ms": {
+                    "type": "integer",
+                    "description": "Wait this long after arriving, e.g. for a tooltip or menu to appear (default: 0, max: 10000)",
+                    "default": 0
+                }
+            },
+            "required": ["x", "y"]
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "x": { "type": "integer" },
+                "y": { "type": "integer" },
+                "dwell_ms": { "type": "integer" }
+            }
+        })
+    }
+    
+    async fn execute(&self, mut params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        resolve_percentage_coordinates(&mut params)?;
+        let move_params: MoveMouseParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for move_mouse: {}", e))?;
+        
+        let to = (move_params.x, move_params.y);
+        let duration_ms = move_params.duration_ms.unwrap_or(0).min(MAX_DRAG_DURATION_MS);
+        let jitter_px = move_params.jitter_px.unwrap_or(0).min(MAX_JITTER_PX);
+        let dwell_ms = move_params.dwell_ms.unwrap_or(0).min(MAX_DWELL_MS);
+        
+        // Without a known starting point there's nothing to ease from, so the cursor just jumps
+        let (path, interval) = match get_cursor_position() {
+            Ok(from) if duration_ms > 0 => {
+                let (path, interval) = drag_path(from, to, duration_ms);
+                (jitter_path(path, jitter_px), interval)
+            }
+            _ => (vec![to], std::time::Duration::ZERO),
+        };
+        
+        log::info!("Session {}: Moving cursor to {:?} over {}ms, dwelling {}ms", session_id, to, duration_ms, dwell_ms);
+        
+        let result = perform_mouse_move(&path, interval).await;
+        if result.is_ok() && dwell_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(dwell_ms)).await;
+        }
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(_) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "x": to.0,
+                        "y": to.1,
+                        "dwell_ms": dwell_ms,
+                        "message": format!("Moved cursor to ({}, {})", to.0, to.1)
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to move cursor: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
 #[derive(Clone)]
 pub struct KeyPressTool;
 
@@ -366,6 +711,17 @@ impl ComputerUseTool for GetCursorPositionTool {
         })
     }
     
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "x": { "type": "integer" },
+                "y": { "type": "integer" },
+                "message": { "type": "string" }
+            }
+        })
+    }
+    
     async fn execute(&self, _params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
@@ -404,15 +760,16 @@ impl ComputerUseTool for GetCursorPositionTool {
     }
 }
 
+// Window management tools
 #[derive(Clone)]
-pub struct GetScreenInfoTool;
+pub struct ListWindowsTool;
 
 #[async_trait]
-impl ComputerUseTool for GetScreenInfoTool {
-    fn name(&self) -> &str { "get_screen_info" }
+impl ComputerUseTool for ListWindowsTool {
+    fn name(&self) -> &str { "list_windows" }
     
     fn description(&self) -> String {
-        "Get screen information (width, height, scale factor)".to_string()
+        "List open application windows (front to back) with their id, title, process, position and size, optionally filtered by title or process".to_string()
     }
     
     fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
@@ -420,32 +777,67 @@ impl ComputerUseTool for GetScreenInfoTool {
     fn parameters_schema(&self) -> serde_json::Value {
         serde_json::json!({
             "type": "object",
-            "properties": {}
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "description": "Only windows whose title contains this text (case-insensitive)"
+                },
+                "process": {
+                    "type": "string",
+                    "description": "Only windows of processes whose name contains this text (case-insensitive)"
+                }
+            }
         })
     }
     
-    async fn execute(&self, _params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "windows": {
+                    "type": "array",
+                    "items": window_schema()
+                },
+                "count": { "type": "integer" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
-        log::info!("Session {}: Getting screen info", session_id);
+        let query: WindowQuery = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for list_windows: {}", e))?;
         
-        match get_screen_info() {
-            Ok(screen_info) => {
+        log::info!("Session {}: Listing windows", session_id);
+        
+        let result = list_windows().await;
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(windows) => {
+                let windows: Vec<DesktopWindow> = windows.into_iter()
+                    .filter(|window| window_matches(window, &query))
+                    .collect();
                 Ok(ToolExecutionResult {
                     success: true,
-                    result: serde_json::to_value(screen_info).unwrap(),
+                    result: serde_json::json!({
+                        "success": true,
+                        "count": windows.len(),
+                        "windows": windows
+                    }),
                     error: None,
-                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    execution_time_ms: execution_time,
                     tool_name: self.name().to_string(),
                 })
             }
             Err(e) => {
-                let error_msg = format!("Failed to get screen info: {}", e);
+                let error_msg = format!("Failed to list windows: {}", e);
                 Ok(ToolExecutionResult {
                     success: false,
                     result: serde_json::json!({"success": false, "error": error_msg}),
                     error: Some(error_msg),
-                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    execution_time_ms: execution_time,
                     tool_name: self.name().to_string(),
                 })
             }
@@ -458,49 +850,698 @@ impl ComputerUseTool for GetScreenInfoTool {
 }
 
 #[derive(Clone)]
-pub struct ScreenshotTool;
+pub struct FocusWindowTool;
 
 #[async_trait]
-impl ComputerUseTool for ScreenshotTool {
-    fn name(&self) -> &str { "take_screenshot" }
+impl ComputerUseTool for FocusWindowTool {
+    fn name(&self) -> &str { "focus_window" }
     
     fn description(&self) -> String {
-        "Take a screenshot of the screen or a region".to_string()
+        "Bring a window to the foreground (restoring it if minimized), picked by id from list_windows or by title/process".to_string()
     }
     
     fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
     
     fn parameters_schema(&self) -> serde_json::Value {
+        window_query_schema(serde_json::Map::new())
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
         serde_json::json!({
             "type": "object",
             "properties": {
-                "format": {
-                    "type": "string",
-                    "enum": ["png", "jpeg"],
-                    "default": "png",
-                    "description": "Image format"
-                },
-                "quality": {
-                    "type": "integer",
-                    "minimum": 1,
-                    "maximum": 100,
-                    "default": 90,
-                    "description": "JPEG quality (1-100)"
-                },
-                "region": {
-                    "type": "object",
-                    "properties": {
+                "window": window_schema()
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let query: WindowQuery = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for focus_window: {}", e))?;
+        
+        log::info!("Session {}: Focusing window {:?}", session_id, query);
+        
+        let result = match list_windows().await.and_then(|windows| select_window(windows, &query)) {
+            Ok(window) => focus_window(window.id).await.map(|_| window),
+            Err(e) => Err(e),
+        };
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(window) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "message": format!("Focused '{}'", window.title),
+                        "window": window
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to focus window: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct MoveResizeWindowTool;
+
+#[async_trait]
+impl ComputerUseTool for MoveResizeWindowTool {
+    fn name(&self) -> &str { "move_resize_window" }
+    
+    fn description(&self) -> String {
+        "Move and/or resize a window (un-maximizing it first); omitted position or size values are kept".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Medium }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        let mut placement = serde_json::Map::new();
+        placement.insert("x".to_string(), serde_json::json!({ "type": "integer", "description": "New left edge in virtual desktop coordinates" }));
+        placement.insert("y".to_string(), serde_json::json!({ "type": "integer", "description": "New top edge in virtual desktop coordinates" }));
+        placement.insert("width".to_string(), serde_json::json!({ "type": "integer", "description": "New width in pixels" }));
+        placement.insert("height".to_string(), serde_json::json!({ "type": "integer", "description": "New height in pixels" }));
+        window_query_schema(placement)
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "window": window_schema()
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let move_params: MoveResizeWindowParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for move_resize_window: {}", e))?;
+        if move_params.x.is_none() && move_params.y.is_none() && move_params.width.is_none() && move_params.height.is_none() {
+            return Err("Give at least one of x, y, width or height".to_string());
+        }
+        if move_params.width == Some(0) || move_params.height == Some(0) {
+            return Err("Window width and height must be at least 1".to_string());
+        }
+        
+        log::info!("Session {}: Moving/resizing window {:?}", session_id, move_params.window);
+        
+        let result = match list_windows().await.and_then(|windows| select_window(windows, &move_params.window)) {
+            Ok(mut window) => {
+                window.x = move_params.x.unwrap_or(window.x);
+                window.y = move_params.y.unwrap_or(window.y);
+                window.width = move_params.width.unwrap_or(window.width);
+                window.height = move_params.height.unwrap_or(window.height);
+                place_window(window.id, window.x, window.y, window.width, window.height).await.map(|_| window)
+            }
+            Err(e) => Err(e),
+        };
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(window) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "message": format!("Placed '{}' at ({}, {}) as {}x{}", window.title, window.x, window.y, window.width, window.height),
+                        "window": window
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to move/resize window: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+// Accessibility tree tools
+#[derive(Clone)]
+pub struct GetUiElementsTool;
+
+#[async_trait]
+impl ComputerUseTool for GetUiElementsTool {
+    fn name(&self) -> &str { "get_ui_elements" }
+    
+    fn description(&self) -> String {
+        "Read a window's controls from the accessibility tree (UI Automation / AX): names, roles, enabled state and screen bounds, nested as in the UI. Defaults to the foreground window".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        let mut limits = serde_json::Map::new();
+        limits.insert("max_depth".to_string(), serde_json::json!({
+            "type": "integer",
+            "description": format!("Levels below the window to read (default: {}, max: {})", DEFAULT_UI_DEPTH, MAX_UI_DEPTH),
+            "default": DEFAULT_UI_DEPTH
+        }));
+        limits.insert("max_elements".to_string(), serde_json::json!({
+            "type": "integer",
+            "description": format!("Stop after this many elements (default: {}, max: {})", DEFAULT_UI_ELEMENTS, MAX_UI_ELEMENTS),
+            "default": DEFAULT_UI_ELEMENTS
+        }));
+        window_query_schema(limits)
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "window_title": { "type": "string" },
+                "root": ui_element_schema(),
+                "element_count": { "type": "integer" },
+                "truncated": { "type": "boolean" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let params: GetUiElementsParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for get_ui_elements: {}", e))?;
+        let max_depth = params.max_depth.unwrap_or(DEFAULT_UI_DEPTH).min(MAX_UI_DEPTH);
+        let max_elements = params.max_elements.unwrap_or(DEFAULT_UI_ELEMENTS).clamp(1, MAX_UI_ELEMENTS);
+        
+        log::info!("Session {}: Reading UI elements of {:?}", session_id, params.window);
+        
+        let result = match target_window(&params.window).await {
+            Ok(window) => ui_tree(window.map(|w| w.id), max_depth, max_elements).await,
+            Err(e) => Err(e),
+        };
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(tree) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "window_title": tree.window_title,
+                        "element_count": tree.element_count,
+                        "truncated": tree.truncated,
+                        "root": tree.root
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to read UI elements: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct ClickElementTool;
+
+#[async_trait]
+impl ComputerUseTool for ClickElementTool {
+    fn name(&self) -> &str { "click_element" }
+    
+    fn description(&self) -> String {
+        "Click a control by its accessible name (and optionally role) from the accessibility tree, without OCR. Exact name matches win over partial ones".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Medium }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        let mut target = serde_json::Map::new();
+        target.insert("name".to_string(), serde_json::json!({
+            "type": "string",
+            "description": "Accessible name of the control, as reported by get_ui_elements (case-insensitive)"
+        }));
+        target.insert("role".to_string(), serde_json::json!({
+            "type": "string",
+            "description": "Only controls with this role, e.g. \"button\", \"edit\", \"checkbox\", \"menuitem\""
+        }));
+        target.insert("index".to_string(), serde_json::json!({
+            "type": "integer",
+            "default": 0,
+            "description": "Which match to click when several controls match (0 = the best)"
+        }));
+        target.insert("button".to_string(), serde_json::json!({
+            "type": "string",
+            "enum": ["left", "right", "middle"],
+            "default": "left",
+            "description": "Mouse button to click"
+        }));
+        target.insert("double_click".to_string(), serde_json::json!({
+            "type": "boolean",
+            "default": false,
+            "description": "Whether to perform a double-click"
+        }));
+        let mut schema = window_query_schema(target);
+        schema["required"] = serde_json::json!(["name"]);
+        schema
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "element": ui_element_schema(),
+                "clicked_at": position_schema(),
+                "matches": { "type": "integer" },
+                "message": { "type": "string" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let params: ClickElementParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for click_element: {}", e))?;
+        if params.name.trim().is_empty() {
+            return Err("No element name given".to_string());
+        }
+        let button = params.button.clone().unwrap_or_else(|| "left".to_string());
+        let double_click = params.double_click.unwrap_or(false);
+        
+        log::info!("Session {}: Clicking element '{}' ({:?})", session_id, params.name, params.role);
+        
+        let result: Result<(UiElement, CursorPosition, usize), String> = async {
+            // The click lands on whatever is on top, so a named window is brought forward first
+            let window = target_window(&params.window).await?;
+            if let Some(window) = &window {
+                focus_window(window.id).await?;
+            }
+            let tree = ui_tree(window.map(|w| w.id), MAX_UI_DEPTH, MAX_UI_ELEMENTS).await?;
+            let matches = find_ui_elements(&tree.root, &params.name, params.role.as_deref());
+            let index = params.index.unwrap_or(0);
+            let Some(element) = matches.get(index) else {
+                return Err(match matches.len() {
+                    0 => format!("No enabled, visible element named '{}' in '{}'", params.name, tree.window_title),
+                    n => format!("Only {} elements match '{}'", n, params.name),
+                });
+            };
+            let element = UiElement { children: Vec::new(), ..(*element).clone() };
+            let x = element.bounds.x + (element.bounds.width / 2) as i32;
+            let y = element.bounds.y + (element.bounds.height / 2) as i32;
+            click_at_coordinates(x, y, &button, double_click).await?;
+            Ok((element, CursorPosition { x, y }, matches.len()))
+        }.await;
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok((element, clicked_at, matches)) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "message": format!("Clicked {} '{}' at ({}, {})", element.role, element.name, clicked_at.x, clicked_at.y),
+                        "element": element,
+                        "clicked_at": clicked_at,
+                        "matches": matches
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to click element: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+// Launch tools
+#[derive(Clone)]
+pub struct OpenAppTool;
+
+#[async_trait]
+impl ComputerUseTool for OpenAppTool {
+    fn name(&self) -> &str { "open_app" }
+    
+    fn description(&self) -> String {
+        "Start an application by name (e.g. \"notepad\", \"firefox\") or executable path, optionally with arguments".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Critical }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "app": {
+                    "type": "string",
+                    "description": "Application name or path to its executable (shells and script interpreters are refused; use run_command)"
+                },
+                "args": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Command-line arguments passed to the application"
+                }
+            },
+            "required": ["app"]
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "app": { "type": "string" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let open_params: OpenAppParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for open_app: {}", e))?;
+        let app = open_params.app.trim().to_string();
+        if app.is_empty() {
+            return Err("Give the application to open".to_string());
+        }
+        refuse_command_runner(&app)?;
+        let args = open_params.args.unwrap_or_default();
+        
+        log::info!("Session {}: Opening application '{}' with {} arguments", session_id, app, args.len());
+        
+        let result = launch_app(&app, &args).await;
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(_) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "app": app,
+                        "message": format!("Started {}", app)
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to open {}: {}", app, e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct OpenUrlTool;
+
+#[async_trait]
+impl ComputerUseTool for OpenUrlTool {
+    fn name(&self) -> &str { "open_url" }
+    
+    fn description(&self) -> String {
+        "Open a web page (http/https) or mailto link in the default browser or mail client".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::High }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "Absolute http, https or mailto URL"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let open_params: OpenUrlParams = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for open_url: {}", e))?;
+        let url = validate_open_url(&open_params.url)?;
+        
+        log::info!("Session {}: Opening URL {}", session_id, url);
+        
+        let result = tauri_plugin_opener::open_url(url.as_str(), None::<&str>).map_err(|e| e.to_string());
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        match result {
+            Ok(_) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::json!({
+                        "success": true,
+                        "url": url.as_str(),
+                        "message": format!("Opened {}", url)
+                    }),
+                    error: None,
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to open URL: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: execution_time,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct GetScreenInfoTool;
+
+#[async_trait]
+impl ComputerUseTool for GetScreenInfoTool {
+    fn name(&self) -> &str { "get_screen_info" }
+    
+    fn description(&self) -> String {
+        "Get every monitor's position, size, work area (excluding taskbars/docks), scale factor and primary flag".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "monitors": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "index": { "type": "integer" },
+                            "name": { "type": "string" },
+                            "x": { "type": "integer" },
+                            "y": { "type": "integer" },
+                            "width": { "type": "integer" },
+                            "height": { "type": "integer" },
+                            "work_area": {
+                                "type": "object",
+                                "properties": {
+                                    "x": { "type": "integer" },
+                                    "y": { "type": "integer" },
+                                    "width": { "type": "integer" },
+                                    "height": { "type": "integer" }
+                                }
+                            },
+                            "scale_factor": { "type": "number" },
+                            "is_primary": { "type": "boolean" }
+                        }
+                    }
+                }
+            }
+        })
+    }
+    
+    async fn execute(&self, _params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        log::info!("Session {}: Getting screen info", session_id);
+        
+        match get_screen_info() {
+            Ok(screen_info) => {
+                Ok(ToolExecutionResult {
+                    success: true,
+                    result: serde_json::to_value(screen_info).unwrap(),
+                    error: None,
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    tool_name: self.name().to_string(),
+                })
+            }
+            Err(e) => {
+                let error_msg = format!("Failed to get screen info: {}", e);
+                Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg}),
+                    error: Some(error_msg),
+                    execution_time_ms: start_time.elapsed().as_millis() as u64,
+                    tool_name: self.name().to_string(),
+                })
+            }
+        }
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct ScreenshotTool;
+
+#[async_trait]
+impl ComputerUseTool for ScreenshotTool {
+    fn name(&self) -> &str { "take_screenshot" }
+    
+    fn description(&self) -> String {
+        "Take a screenshot of the screen or a region".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "format": {
+                    "type": "string",
+                    "enum": ["png", "jpeg"],
+                    "default": "png",
+                    "description": "Image format"
+                },
+                "quality": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 100,
+                    "default": 90,
+                    "description": "JPEG quality (1-100)"
+                },
+                "region": {
+                    "type": "object",
+                    "properties": {
                         "x": {"type": "integer"},
                         "y": {"type": "integer"},
                         "width": {"type": "integer"},
                         "height": {"type": "integer"}
                     },
                     "description": "Region to capture (full screen if not specified)"
+                },
+                "window": {
+                    "type": "string",
+                    "description": "Window id or title to capture instead of the screen; other windows are left out"
                 }
             }
         })
     }
     
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "image_base64": { "type": "string" },
+                "width": { "type": "integer" },
+                "height": { "type": "integer" },
+                "format": { "type": "string" }
+            }
+        })
+    }
+    
     async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
@@ -509,11 +1550,19 @@ impl ComputerUseTool for ScreenshotTool {
                 format: Some("png".to_string()),
                 quality: Some(90),
                 region: None,
+                window: None,
             });
         
         log::info!("Session {}: Taking screenshot", session_id);
         
-        let result = if let Some(region) = screenshot_params.region {
+        let result = if let Some(window) = screenshot_params.window {
+            crate::screenshot::capture_window(window).await.map(|capture| ScreenshotResult {
+                image_base64: capture.image_base64,
+                width: capture.width,
+                height: capture.height,
+                format: capture.format,
+            })
+        } else if let Some(region) = screenshot_params.region {
             take_screenshot_region(region, screenshot_params.format, screenshot_params.quality).await
         } else {
             take_screenshot_full(screenshot_params.format, screenshot_params.quality).await
@@ -580,184 +1629,214 @@ async fn perform_click(x: i32, y: i32, button: MouseButton) -> Result<(), String
 }
 
 #[cfg(target_os = "windows")]
-fn get_cursor_position() -> Result<(i32, i32), String> {
-    use winapi::um::winuser::GetCursorPos;
-    use winapi::shared::windef::POINT;
+async fn launch_app(app: &str, args: &[String]) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::shellapi::ShellExecuteW;
+    use winapi::um::winuser::SW_SHOWNORMAL;
+    
+    let wide = |text: &str| std::ffi::OsStr::new(text).encode_wide().chain(Some(0)).collect::<Vec<u16>>();
+    let parameters = args.iter().map(|arg| quote_windows_arg(arg)).collect::<Vec<_>>().join(" ");
+    
+    // ShellExecute resolves registered app names (App Paths) as well as executables on PATH. It can
+    // block on slow shares or app startup, so it runs off the async workers.
+    let (verb, file, parameters) = (wide("open"), wide(app), wide(&parameters));
+    let instance = tokio::task::spawn_blocking(move || unsafe {
+        ShellExecuteW(std::ptr::null_mut(), verb.as_ptr(), file.as_ptr(), parameters.as_ptr(), std::ptr::null(), SW_SHOWNORMAL) as isize
+    })
+    .await
+    .map_err(|e| format!("ShellExecute failed: {}", e))?;
+    // Values up to 32 are error codes
+    if instance <= 32 {
+        return Err(format!("ShellExecute failed with code {}", instance));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+async fn list_windows() -> Result<Vec<DesktopWindow>, String> {
+    use winapi::shared::minwindef::{BOOL, LPARAM, TRUE};
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::um::winuser::{
+        EnumWindows, GetWindowLongW, GetWindowRect, GetWindowTextLengthW, GetWindowTextW,
+        GetWindowThreadProcessId, IsIconic, IsWindowVisible, GWL_EXSTYLE, WS_EX_TOOLWINDOW
+    };
     
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        (*(lparam as *mut Vec<HWND>)).push(hwnd);
+        TRUE
+    }
+    
+    let mut handles: Vec<HWND> = Vec::new();
     unsafe {
-        let mut point = POINT { x: 0, y: 0 };
-        if GetCursorPos(&mut point) != 0 {
-            Ok((point.x, point.y))
-        } else {
-            Err("Failed to get cursor position".to_string())
+        if EnumWindows(Some(collect), &mut handles as *mut Vec<HWND> as LPARAM) == 0 {
+            return Err("Failed to enumerate windows".to_string());
         }
     }
+    
+    // Visible, titled windows that aren't tool palettes or our own overlay, in z-order
+    let own_process = std::process::id();
+    let windows = handles.into_iter().filter_map(|hwnd| unsafe {
+        if IsWindowVisible(hwnd) == 0 || (GetWindowLongW(hwnd, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW) != 0 {
+            return None;
+        }
+        let len = GetWindowTextLengthW(hwnd);
+        if len <= 0 {
+            return None;
+        }
+        let mut title_buf = vec![0u16; len as usize + 1];
+        let copied = GetWindowTextW(hwnd, title_buf.as_mut_ptr(), title_buf.len() as i32);
+        
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut process_id);
+        if process_id == own_process {
+            return None;
+        }
+        
+        let mut rect: RECT = std::mem::zeroed();
+        GetWindowRect(hwnd, &mut rect);
+        Some(DesktopWindow {
+            id: hwnd as usize as u64,
+            title: String::from_utf16_lossy(&title_buf[..copied.max(0) as usize]),
+            process_id,
+            process_name: crate::window_manager::process_name(process_id),
+            x: rect.left,
+            y: rect.top,
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+            minimized: IsIconic(hwnd) != 0,
+        })
+    }).collect();
+    
+    Ok(windows)
 }
 
 #[cfg(target_os = "windows")]
-async fn type_text(text: &str, delay_ms: u64) -> Result<(), String> {
-    use winapi::um::winuser::{SendInput, INPUT, INPUT_KEYBOARD, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE, VkKeyScanA, MapVirtualKeyA, MAPVK_VK_TO_VSC};
-    use winapi::um::winuser::{KEYBDINPUT};
-    use std::mem;
+async fn focus_window(id: u64) -> Result<(), String> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        IsIconic, IsWindow, SetForegroundWindow, ShowWindow, keybd_event, KEYEVENTF_KEYUP, SW_RESTORE, VK_MENU
+    };
     
-    for ch in text.chars() {
-        unsafe {
-            // For Unicode characters, use KEYEVENTF_UNICODE
-            if ch as u32 > 127 {
-                // Unicode input
-                let mut inputs = [INPUT {
-                    type_: INPUT_KEYBOARD,
-                    u: mem::zeroed(),
-                }; 2];
-                
-                *inputs[0].u.ki_mut() = KEYBDINPUT {
-                    wVk: 0,
-                    wScan: ch as u16,
-                    dwFlags: KEYEVENTF_UNICODE,
-                    time: 0,
-                    dwExtraInfo: 0,
-                };
-                
-                *inputs[1].u.ki_mut() = KEYBDINPUT {
-                    wVk: 0,
-                    wScan: ch as u16,
-                    dwFlags: KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
-                    time: 0,
-                    dwExtraInfo: 0,
-                };
-                
-                let result = SendInput(2, inputs.as_mut_ptr(), mem::size_of::<INPUT>() as i32);
-                if result != 2 {
-                    return Err(format!("Failed to send unicode input for character '{}'", ch));
-                }
-            } else {
-                // ASCII character - use virtual key code
-                let ascii_byte = ch as u8;
-                let vk_code = VkKeyScanA(ascii_byte as i8);
-                
-                if vk_code == -1 {
-                    // Character cannot be represented, try unicode method
-                    let mut inputs = [INPUT {
-                        type_: INPUT_KEYBOARD,
-                        u: mem::zeroed(),
-                    }; 2];
-                    
-                    *inputs[0].u.ki_mut() = KEYBDINPUT {
-                        wVk: 0,
-                        wScan: ch as u16,
-                        dwFlags: KEYEVENTF_UNICODE,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    };
-                    
-                    *inputs[1].u.ki_mut() = KEYBDINPUT {
-                        wVk: 0,
-                        wScan: ch as u16,
-                        dwFlags: KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    };
-                    
-                    let result = SendInput(2, inputs.as_mut_ptr(), mem::size_of::<INPUT>() as i32);
-                    if result != 2 {
-                        return Err(format!("Failed to send unicode input for character '{}'", ch));
-                    }
-                } else {
-                    let virtual_key = (vk_code & 0xFF) as u16;
-                    let scan_code = MapVirtualKeyA(virtual_key as u32, MAPVK_VK_TO_VSC) as u16;
-                    
-                    // Check if shift is needed
-                    let shift_needed = (vk_code & 0x100) != 0;
-                    
-                    let mut inputs = Vec::new();
-                    
-                    // Press shift if needed
-                    if shift_needed {
-                        inputs.push(INPUT {
-                            type_: INPUT_KEYBOARD,
-                            u: {
-                                let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                                *input_union.ki_mut() = KEYBDINPUT {
-                                    wVk: 0x10, // VK_SHIFT
-                                    wScan: MapVirtualKeyA(0x10, MAPVK_VK_TO_VSC) as u16,
-                                    dwFlags: 0,
-                                    time: 0,
-                                    dwExtraInfo: 0,
-                                };
-                                input_union
-                            },
-                        });
-                    }
-                    
-                    // Press key
-                    inputs.push(INPUT {
-                        type_: INPUT_KEYBOARD,
-                        u: {
-                            let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                            *input_union.ki_mut() = KEYBDINPUT {
-                                wVk: virtual_key,
-                                wScan: scan_code,
-                                dwFlags: 0,
-                                time: 0,
-                                dwExtraInfo: 0,
-                            };
-                            input_union
-                        },
-                    });
-                    
-                    // Release key
-                    inputs.push(INPUT {
-                        type_: INPUT_KEYBOARD,
-                        u: {
-                            let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                            *input_union.ki_mut() = KEYBDINPUT {
-                                wVk: virtual_key,
-                                wScan: scan_code,
-                                dwFlags: KEYEVENTF_KEYUP,
-                                time: 0,
-                                dwExtraInfo: 0,
-                            };
-                            input_union
-                        },
-                    });
-                    
-                    // Release shift if needed
-                    if shift_needed {
-                        inputs.push(INPUT {
-                            type_: INPUT_KEYBOARD,
-                            u: {
-                                let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                                *input_union.ki_mut() = KEYBDINPUT {
-                                    wVk: 0x10, // VK_SHIFT
-                                    wScan: MapVirtualKeyA(0x10, MAPVK_VK_TO_VSC) as u16,
-                                    dwFlags: KEYEVENTF_KEYUP,
-                                    time: 0,
-                                    dwExtraInfo: 0,
-                                };
-                                input_union
-                            },
-                        });
-                    }
-                    
-                    let result = SendInput(inputs.len() as u32, inputs.as_mut_ptr(), mem::size_of::<INPUT>() as i32);
-                    if result != inputs.len() as u32 {
-                        return Err(format!("Failed to send input for character '{}', sent {}/{} inputs", ch, result, inputs.len()));
-                    }
-                }
+    let hwnd = id as usize as HWND;
+    unsafe {
+        if IsWindow(hwnd) == 0 {
+            return Err("The window no longer exists".to_string());
+        }
+        if IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        if SetForegroundWindow(hwnd) == 0 {
+            // Windows only hands focus to the process that received the last input; a synthetic Alt tap counts
+            keybd_event(VK_MENU as u8, 0, 0, 0);
+            keybd_event(VK_MENU as u8, 0, KEYEVENTF_KEYUP, 0);
+            if SetForegroundWindow(hwnd) == 0 {
+                return Err("Windows refused to bring the window to the foreground".to_string());
             }
         }
-        
-        // Add delay between characters
-        if delay_ms > 0 {
-            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+async fn place_window(id: u64, x: i32, y: i32, width: u32, height: u32) -> Result<(), String> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{
+        IsIconic, IsWindow, IsZoomed, SetWindowPos, ShowWindow, SWP_NOACTIVATE, SWP_NOZORDER, SW_RESTORE
+    };
+    
+    let hwnd = id as usize as HWND;
+    unsafe {
+        if IsWindow(hwnd) == 0 {
+            return Err("The window no longer exists".to_string());
+        }
+        // A maximized or minimized window ignores its normal bounds until restored
+        if IsZoomed(hwnd) != 0 || IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        if SetWindowPos(hwnd, std::ptr::null_mut(), x, y, width as i32, height as i32, SWP_NOZORDER | SWP_NOACTIVATE) == 0 {
+            return Err("Failed to move the window".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+async fn perform_mouse_move(path: &[(i32, i32)], interval: std::time::Duration) -> Result<(), String> {
+    use winapi::um::winuser::SetCursorPos;
+    
+    for &(x, y) in path {
+        if !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        if unsafe { SetCursorPos(x, y) } == 0 {
+            return Err("Failed to move cursor".to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+async fn perform_drag(from: (i32, i32), to: (i32, i32), duration_ms: u64, button: MouseButton) -> Result<(), String> {
+    use winapi::um::winuser::{
+        SetCursorPos, mouse_event, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+        MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP
+    };
+    
+    let (down_event, up_event) = match button {
+        MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+        MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+        MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+    };
+    let (path, interval) = drag_path(from, to, duration_ms);
+    
+    unsafe {
+        if SetCursorPos(from.0, from.1) == 0 {
+            return Err("Failed to move cursor".to_string());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        mouse_event(down_event, 0, 0, 0, 0);
+    }
+    
+    // The button is released even if a move fails, so it's never left held down
+    let mut moved = Ok(());
+    for (x, y) in path {
+        tokio::time::sleep(interval).await;
+        if unsafe { SetCursorPos(x, y) } == 0 {
+            moved = Err("Failed to move cursor".to_string());
+            break;
+        }
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    unsafe {
+        mouse_event(up_event, 0, 0, 0, 0);
+    }
+    
+    moved
+}
+
+#[cfg(target_os = "windows")]
+fn get_cursor_position() -> Result<(i32, i32), String> {
+    use winapi::um::winuser::GetCursorPos;
+    use winapi::shared::windef::POINT;
+    
+    unsafe {
+        let mut point = POINT { x: 0, y: 0 };
+        if GetCursorPos(&mut point) != 0 {
+            Ok((point.x, point.y))
+        } else {
+            Err("Failed to get cursor position".to_string())
         }
     }
-    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+async fn type_text(text: &str, delay_ms: u64) -> Result<(), String> {
+    crate::mcp::windows_input::type_text(text, delay_ms).await
 }
 
 #[cfg(target_os = "windows")]
 async fn perform_scroll(params: ScrollParams) -> Result<(), String> {
-    use winapi::um::winuser::{mouse_event, MOUSEEVENTF_WHEEL, WHEEL_DELTA};
+    use winapi::um::winuser::{mouse_event, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_WHEEL, WHEEL_DELTA};
     
     // Move to position if specified
     if let (Some(x), Some(y)) = (params.x, params.y) {
@@ -767,171 +1846,30 @@ async fn perform_scroll(params: ScrollParams) -> Result<(), String> {
         }
     }
     
-    let amount = params.amount.unwrap_or(3);
-    let delta = match params.direction {
-        ScrollDirection::Up => (WHEEL_DELTA as i32) * amount,
-        ScrollDirection::Down => -(WHEEL_DELTA as i32) * amount,
-        ScrollDirection::Left | ScrollDirection::Right => {
-            // Horizontal scrolling would need MOUSEEVENTF_HWHEEL
-            return Err("Horizontal scrolling not yet implemented".to_string());
-        }
-    };
-    
-    unsafe {
-        mouse_event(MOUSEEVENTF_WHEEL, 0, 0, delta as u32, 0);
-    }
-    
-    Ok(())
-}
-
-#[cfg(target_os = "windows")]
-async fn press_key(key: &str, modifiers: Vec<KeyModifier>) -> Result<(), String> {
-    use winapi::um::winuser::{SendInput, INPUT, INPUT_KEYBOARD, KEYEVENTF_KEYUP, MapVirtualKeyA, MAPVK_VK_TO_VSC, VK_RETURN, VK_DELETE, VK_BACK, VK_TAB, VK_ESCAPE, VK_SPACE, VK_LEFT, VK_RIGHT, VK_UP, VK_DOWN, VK_CONTROL, VK_MENU, VK_SHIFT, VK_LWIN};
-    use winapi::um::winuser::{KEYBDINPUT};
-    use std::mem;
-    
-    // Map key names to virtual key codes
-    let virtual_key = match key.to_lowercase().as_str() {
-        "return" | "enter" => VK_RETURN as u32,
-        "delete" | "del" => VK_DELETE as u32,
-        "backspace" | "back" => VK_BACK as u32,
-        "tab" => VK_TAB as u32,
-        "escape" | "esc" => VK_ESCAPE as u32,
-        "space" => VK_SPACE as u32,
-        "left" | "leftarrow" => VK_LEFT as u32,
-        "right" | "rightarrow" => VK_RIGHT as u32,
-        "up" | "uparrow" => VK_UP as u32,
-        "down" | "downarrow" => VK_DOWN as u32,
-        "ctrl" | "control" => VK_CONTROL as u32,
-        "alt" => VK_MENU as u32,
-        "shift" => VK_SHIFT as u32,
-        "meta" | "win" | "windows" => VK_LWIN as u32,
-        // Function keys
-        "f1" => 0x70,
-        "f2" => 0x71,
-        "f3" => 0x72,
-        "f4" => 0x73,
-        "f5" => 0x74,
-        "f6" => 0x75,
-        "f7" => 0x76,
-        "f8" => 0x77,
-        "f9" => 0x78,
-        "f10" => 0x79,
-        "f11" => 0x7A,
-        "f12" => 0x7B,
-        // Single character keys
-        _ if key.len() == 1 => {
-            let ch = key.chars().next().unwrap().to_ascii_uppercase();
-            if ch.is_ascii_alphabetic() {
-                ch as u32
-            } else {
-                return Err(format!("Unsupported key: {}", key));
-            }
-        }
-        _ => return Err(format!("Unsupported key: {}", key)),
+    // Positive deltas scroll up and right; smooth scrolls send quarter notches, which apps that
+    // support high-resolution wheels accumulate
+    let (flag, sign) = match params.direction {
+        ScrollDirection::Up => (MOUSEEVENTF_WHEEL, 1),
+        ScrollDirection::Down => (MOUSEEVENTF_WHEEL, -1),
+        ScrollDirection::Left => (MOUSEEVENTF_HWHEEL, -1),
+        ScrollDirection::Right => (MOUSEEVENTF_HWHEEL, 1),
     };
-    
-    // Map modifier enums to virtual key codes
-    let modifier_vks: Vec<u32> = modifiers.iter().map(|m| match m {
-        KeyModifier::Ctrl => VK_CONTROL as u32,
-        KeyModifier::Alt => VK_MENU as u32,
-        KeyModifier::Shift => VK_SHIFT as u32,
-        KeyModifier::Meta => VK_LWIN as u32,
-    }).collect();
-    
-    unsafe {
-        let mut inputs = Vec::new();
-        
-        // Press modifiers
-        for &modifier_vk in &modifier_vks {
-            inputs.push(INPUT {
-                type_: INPUT_KEYBOARD,
-                u: {
-                    let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                    *input_union.ki_mut() = KEYBDINPUT {
-                        wVk: modifier_vk as u16,
-                        wScan: MapVirtualKeyA(modifier_vk, MAPVK_VK_TO_VSC) as u16,
-                        dwFlags: 0,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    };
-                    input_union
-                },
-            });
-        }
-        
-        // Press the main key
-        inputs.push(INPUT {
-            type_: INPUT_KEYBOARD,
-            u: {
-                let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                *input_union.ki_mut() = KEYBDINPUT {
-                    wVk: virtual_key as u16,
-                    wScan: MapVirtualKeyA(virtual_key, MAPVK_VK_TO_VSC) as u16,
-                    dwFlags: 0,
-                    time: 0,
-                    dwExtraInfo: 0,
-                };
-                input_union
-            },
-        });
-        
-        // Release the main key
-        inputs.push(INPUT {
-            type_: INPUT_KEYBOARD,
-            u: {
-                let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                *input_union.ki_mut() = KEYBDINPUT {
-                    wVk: virtual_key as u16,
-                    wScan: MapVirtualKeyA(virtual_key, MAPVK_VK_TO_VSC) as u16,
-                    dwFlags: KEYEVENTF_KEYUP,
-                    time: 0,
-                    dwExtraInfo: 0,
-                };
-                input_union
-            },
-        });
-        
-        // Release modifiers (in reverse order)
-        for &modifier_vk in modifier_vks.iter().rev() {
-            inputs.push(INPUT {
-                type_: INPUT_KEYBOARD,
-                u: {
-                    let mut input_union: winapi::um::winuser::INPUT_u = mem::zeroed();
-                    *input_union.ki_mut() = KEYBDINPUT {
-                        wVk: modifier_vk as u16,
-                        wScan: MapVirtualKeyA(modifier_vk, MAPVK_VK_TO_VSC) as u16,
-                        dwFlags: KEYEVENTF_KEYUP,
-                        time: 0,
-                        dwExtraInfo: 0,
-                    };
-                    input_union
-                },
-            });
+    let (deltas, interval) = params.wheel_steps(WHEEL_DELTA as i32, WHEEL_DELTA as i32 / 4);
+    for (i, delta) in deltas.iter().enumerate() {
+        if i > 0 {
+            tokio::time::sleep(interval).await;
         }
-        
-        let result = SendInput(inputs.len() as u32, inputs.as_mut_ptr(), mem::size_of::<INPUT>() as i32);
-        if result != inputs.len() as u32 {
-            return Err(format!("Failed to send key press input, sent {}/{} inputs", result, inputs.len()));
+        unsafe {
+            mouse_event(flag, 0, 0, (sign * delta) as u32, 0);
         }
     }
+    
     Ok(())
 }
 
 #[cfg(target_os = "windows")]
-fn get_screen_info() -> Result<ScreenInfo, String> {
-    use winapi::um::winuser::{GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
-    
-    unsafe {
-        let width = GetSystemMetrics(SM_CXSCREEN) as u32;
-        let height = GetSystemMetrics(SM_CYSCREEN) as u32;
-        
-        Ok(ScreenInfo {
-            width,
-            height,
-            scale_factor: 1.0, // Would need proper DPI detection
-        })
-    }
+async fn press_key(key: &str, modifiers: Vec<KeyModifier>) -> Result<(), String> {
+    crate::mcp::windows_input::press_key(key, &modifiers).await
 }
 
 #[cfg(target_os = "windows")]
@@ -962,66 +1900,472 @@ async fn take_screenshot_region(region: ScreenRegion, _format: Option<String>, _
     }
 }
 
+// CoreGraphics reports displays in points, the space macOS clicks and text locations use
+#[cfg(target_os = "macos")]
+fn get_screen_info() -> Result<ScreenInfo, String> {
+    crate::mcp::macos::screen_info()
+}
+
+#[cfg(not(target_os = "macos"))]
+fn get_screen_info() -> Result<ScreenInfo, String> {
+    let monitors = xcap::Monitor::all().map_err(|e| format!("Failed to enumerate monitors: {}", e))?;
+    
+    let monitors = monitors.iter().enumerate().map(|(index, monitor)| {
+        let x = monitor.x().unwrap_or(0);
+        let y = monitor.y().unwrap_or(0);
+        let width = monitor.width().unwrap_or(0);
+        let height = monitor.height().unwrap_or(0);
+        let bounds = ScreenRegion { x, y, width, height };
+        MonitorDetails {
+            index,
+            name: monitor.name().unwrap_or_else(|_| format!("Display {}", index + 1)),
+            x,
+            y,
+            width,
+            height,
+            work_area: monitor_work_area(&bounds).unwrap_or_else(|| bounds.clone()),
+            scale_factor: monitor.scale_factor().map(f64::from).unwrap_or(1.0),
+            is_primary: monitor.is_primary().unwrap_or(false),
+        }
+    }).collect();
+    
+    Ok(ScreenInfo { monitors })
+}
+
+#[cfg(target_os = "windows")]
+fn monitor_work_area(bounds: &ScreenRegion) -> Option<ScreenRegion> {
+    use windows::Win32::Foundation::POINT;
+    use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromPoint, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+    
+    unsafe {
+        let center = POINT {
+            x: bounds.x + bounds.width as i32 / 2,
+            y: bounds.y + bounds.height as i32 / 2,
+        };
+        let handle = MonitorFromPoint(center, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(handle, &mut info).as_bool() {
+            return None;
+        }
+        let work = info.rcWork;
+        Some(ScreenRegion {
+            x: work.left,
+            y: work.top,
+            width: (work.right - work.left).max(0) as u32,
+            height: (work.bottom - work.top).max(0) as u32,
+        })
+    }
+}
+
+// Taskbar/dock insets aren't queried on other platforms yet, so the work area is the full monitor
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn monitor_work_area(_bounds: &ScreenRegion) -> Option<ScreenRegion> {
+    None
+}
+
 // Fallback implementations for non-Windows platforms
 #[cfg(not(target_os = "windows"))]
 async fn perform_click(x: i32, y: i32, button: MouseButton) -> Result<(), String> {
-    log::info!("Simulated click at ({}, {}) with {:?} button - not implemented for this platform", x, y, button);
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::click(x, y, button, false).await
+    }
+    #[cfg(target_os = "macos")]
+    {
+        crate::mcp::macos::click(x, y, button, false).await
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        log::info!("Simulated click at ({}, {}) with {:?} button - not implemented for this platform", x, y, button);
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn launch_app(app: &str, args: &[String]) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        // `open -a` finds bundles by name in /Applications; a path is run directly
+        let mut command = if app.contains('/') { std::process::Command::new(app) } else {
+            let mut open = std::process::Command::new("open");
+            open.args(["-a", app]);
+            if !args.is_empty() {
+                open.arg("--args");
+            }
+            open
+        };
+        command.args(args);
+        command
+    };
+    #[cfg(not(target_os = "macos"))]
+    let mut command = {
+        // Desktop entries (e.g. "org.gnome.TextEditor") launch through gtk-launch when there's no such executable
+        let on_path = app.contains('/') || std::env::var_os("PATH")
+            .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(app).is_file()))
+            .unwrap_or(false);
+        let mut command = if on_path { std::process::Command::new(app) } else {
+            let mut launch = std::process::Command::new("gtk-launch");
+            launch.arg(app);
+            launch
+        };
+        command.args(args);
+        command
+    };
+    
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    // Reap the process when it exits so it doesn't linger as a zombie
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
     Ok(())
 }
 
+#[cfg(not(target_os = "windows"))]
+async fn list_windows() -> Result<Vec<DesktopWindow>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::list_windows().await
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err("Window management isn't supported on this platform yet".to_string())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn focus_window(id: u64) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::focus_window(id).await
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = id;
+        Err("Window management isn't supported on this platform yet".to_string())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn place_window(id: u64, x: i32, y: i32, width: u32, height: u32) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::place_window(id, x, y, width, height).await
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (id, x, y, width, height);
+        Err("Window management isn't supported on this platform yet".to_string())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn perform_mouse_move(path: &[(i32, i32)], interval: std::time::Duration) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::move_pointer(path, interval).await
+    }
+    #[cfg(target_os = "macos")]
+    {
+        crate::mcp::macos::move_pointer(path, interval).await
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        log::info!("Simulated cursor move to {:?} - not implemented for this platform", path.last());
+        let _ = interval;
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn perform_drag(from: (i32, i32), to: (i32, i32), duration_ms: u64, button: MouseButton) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let (path, interval) = drag_path(from, to, duration_ms);
+        crate::mcp::linux::drag(from, &path, interval, button).await
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let (path, interval) = drag_path(from, to, duration_ms);
+        crate::mcp::macos::drag(from, &path, interval, button).await
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        log::info!("Simulated drag from {:?} to {:?} over {}ms with {:?} button - not implemented for this platform", from, to, duration_ms, button);
+        Ok(())
+    }
+}
+
 #[cfg(not(target_os = "windows"))]
 fn get_cursor_position() -> Result<(i32, i32), String> {
-    Ok((800, 600)) // Return center of screen as fallback
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::cursor_position()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        crate::mcp::macos::cursor_position()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Ok((800, 600)) // Return center of screen as fallback
+    }
 }
 
 #[cfg(not(target_os = "windows"))]
 async fn type_text(text: &str, delay_ms: u64) -> Result<(), String> {
-    log::info!("Simulated typing: '{}' - not implemented for this platform", text);
-    Ok(())
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::type_text(text, delay_ms).await
+    }
+    #[cfg(target_os = "macos")]
+    {
+        crate::mcp::macos::type_text(text, delay_ms).await
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        log::info!("Simulated typing: '{}' - not implemented for this platform", text);
+        Ok(())
+    }
 }
 
 #[cfg(not(target_os = "windows"))]
 async fn perform_scroll(params: ScrollParams) -> Result<(), String> {
-    log::info!("Simulated scroll {:?} - not implemented for this platform", params.direction);
-    Ok(())
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::scroll(&params).await
+    }
+    #[cfg(target_os = "macos")]
+    {
+        crate::mcp::macos::scroll(&params).await
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        log::info!("Simulated scroll {:?} - not implemented for this platform", params.direction);
+        Ok(())
+    }
 }
 
 #[cfg(not(target_os = "windows"))]
 async fn press_key(_key: &str, _modifiers: Vec<KeyModifier>) -> Result<(), String> {
-    log::info!("Simulated key press: '{}' with modifiers: {:?} - not implemented for this platform", _key, _modifiers);
-    Ok(())
+    #[cfg(target_os = "linux")]
+    {
+        crate::mcp::linux::press_key(_key, &_modifiers).await
+    }
+    #[cfg(target_os = "macos")]
+    {
+        crate::mcp::macos::press_key(_key, &_modifiers)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        log::info!("Simulated key press: '{}' with modifiers: {:?} - not implemented for this platform", _key, _modifiers);
+        Ok(())
+    }
 }
 
-#[cfg(not(target_os = "windows"))]
-fn get_screen_info() -> Result<ScreenInfo, String> {
-    Ok(ScreenInfo {
-        width: 1920,
-        height: 1080,
-        scale_factor: 1.0,
-    })
+#[cfg(target_os = "linux")]
+async fn take_screenshot_full(_format: Option<String>, _quality: Option<u8>) -> Result<ScreenshotResult, String> {
+    crate::mcp::linux::screenshot(None).await
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(target_os = "linux")]
+async fn take_screenshot_region(region: ScreenRegion, _format: Option<String>, _quality: Option<u8>) -> Result<ScreenshotResult, String> {
+    crate::mcp::linux::screenshot(Some(region)).await
+}
+
+#[cfg(target_os = "macos")]
+async fn take_screenshot_full(_format: Option<String>, _quality: Option<u8>) -> Result<ScreenshotResult, String> {
+    crate::mcp::macos::screenshot(None)
+}
+
+#[cfg(target_os = "macos")]
+async fn take_screenshot_region(region: ScreenRegion, _format: Option<String>, _quality: Option<u8>) -> Result<ScreenshotResult, String> {
+    crate::mcp::macos::screenshot(Some(region))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
 async fn take_screenshot_full(_format: Option<String>, _quality: Option<u8>) -> Result<ScreenshotResult, String> {
     Err("Screenshot not implemented for this platform".to_string())
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
 async fn take_screenshot_region(_region: ScreenRegion, _format: Option<String>, _quality: Option<u8>) -> Result<ScreenshotResult, String> {
     Err("Screenshot not implemented for this platform".to_string())
 }
 
-// ========== NEW ATOMIC OCR TOOLS ==========
-
+// ========== NEW ATOMIC OCR TOOLS ==========
+
+#[derive(Clone)]
+pub struct FindTextTool;
+
+#[async_trait]
+impl ComputerUseTool for FindTextTool {
+    fn name(&self) -> &str { "find_text" }
+    
+    fn description(&self) -> String {
+        "Find text on screen using OCR and return its location and confidence".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "The text to search for on screen"
+                },
+                "confidence_threshold": {
+                    "type": "number",
+                    "default": 0.8,
+                    "description": "Minimum confidence level (0.0-1.0) for text recognition"
+                },
+                "case_sensitive": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Whether to perform case-sensitive matching"
+                }
+            },
+            "required": ["text"]
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text_locations": {
+                    "type": "array",
+                    "description": "Matches, most confident first",
+                    "items": text_location_schema()
+                },
+                "search_text": { "type": "string" },
+                "confidence_threshold": { "type": "number" },
+                "matches_found": { "type": "integer" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, _session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let text_to_find = params["text"].as_str()
+            .ok_or("Missing required parameter: text")?;
+        let confidence_threshold = params["confidence_threshold"].as_f64().unwrap_or(0.8);
+        let case_sensitive = params["case_sensitive"].as_bool().unwrap_or(false);
+        
+        // Take screenshot first
+        let screenshot_result = take_screenshot_full(Some("png".to_string()), Some(80)).await?;
+        
+        // Perform OCR on the screenshot
+        let text_locations = find_text_in_image(&screenshot_result.image_base64, text_to_find, confidence_threshold, case_sensitive).await?;
+        
+        let execution_time = start_time.elapsed().as_millis() as u64;
+        
+        let result = FindTextResult {
+            matches_found: text_locations.len(),
+            text_locations,
+            search_text: text_to_find.to_string(),
+            confidence_threshold,
+        };
+        
+        Ok(ToolExecutionResult {
+            success: true,
+            result: serde_json::to_value(result).map_err(|e| format!("Failed to serialize find_text result: {}", e))?,
+            error: None,
+            execution_time_ms: execution_time,
+            tool_name: "find_text".to_string(),
+        })
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+// Waits stay under the default watchdog for low danger tools (30s)
+const MAX_WAIT_MS: u64 = 25_000;
+const DEFAULT_TEXT_WAIT_MS: u64 = 10_000;
+const MIN_TEXT_POLL_MS: u64 = 200;
+
+#[derive(Clone)]
+pub struct WaitTool;
+
+#[async_trait]
+impl ComputerUseTool for WaitTool {
+    fn name(&self) -> &str { "wait" }
+    
+    fn description(&self) -> String {
+        "Pause for a number of milliseconds, e.g. to let an application finish starting".to_string()
+    }
+    
+    fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
+    
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "ms": {
+                    "type": "integer",
+                    "description": format!("How long to wait in milliseconds (max: {})", MAX_WAIT_MS)
+                }
+            },
+            "required": ["ms"]
+        })
+    }
+    
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "waited_ms": { "type": "integer" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let start_time = Instant::now();
+        
+        let ms = params["ms"].as_u64().ok_or("Missing required parameter: ms")?.min(MAX_WAIT_MS);
+        
+        log::info!("Session {}: Waiting {}ms", session_id, ms);
+        
+        tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+        
+        Ok(ToolExecutionResult {
+            success: true,
+            result: serde_json::json!({
+                "success": true,
+                "waited_ms": ms
+            }),
+            error: None,
+            execution_time_ms: start_time.elapsed().as_millis() as u64,
+            tool_name: self.name().to_string(),
+        })
+    }
+    
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
 #[derive(Clone)]
-pub struct FindTextTool;
+pub struct WaitForTextTool;
 
 #[async_trait]
-impl ComputerUseTool for FindTextTool {
-    fn name(&self) -> &str { "find_text" }
+impl ComputerUseTool for WaitForTextTool {
+    fn name(&self) -> &str { "wait_for_text" }
     
     fn description(&self) -> String {
-        "Find text on screen using OCR and return its location and confidence".to_string()
+        "Wait until text appears on screen (checked with OCR every interval) or the timeout passes; use before clicking in a window that is still loading".to_string()
     }
     
     fn danger_level(&self) -> DangerLevel { DangerLevel::Low }
@@ -1032,7 +2376,17 @@ impl ComputerUseTool for FindTextTool {
             "properties": {
                 "text": {
                     "type": "string",
-                    "description": "The text to search for on screen"
+                    "description": "The text to wait for"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "default": DEFAULT_TEXT_WAIT_MS,
+                    "description": format!("Give up after this many milliseconds (max: {})", MAX_WAIT_MS)
+                },
+                "interval_ms": {
+                    "type": "integer",
+                    "default": 500,
+                    "description": format!("Time between checks in milliseconds (min: {})", MIN_TEXT_POLL_MS)
                 },
                 "confidence_threshold": {
                     "type": "number",
@@ -1049,34 +2403,73 @@ impl ComputerUseTool for FindTextTool {
         })
     }
     
-    async fn execute(&self, params: serde_json::Value, _session_id: &str) -> Result<ToolExecutionResult, String> {
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text_locations": {
+                    "type": "array",
+                    "description": "Matches, most confident first",
+                    "items": text_location_schema()
+                },
+                "search_text": { "type": "string" },
+                "confidence_threshold": { "type": "number" },
+                "matches_found": { "type": "integer" },
+                "waited_ms": { "type": "integer" },
+                "attempts": { "type": "integer" }
+            }
+        })
+    }
+    
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
         let text_to_find = params["text"].as_str()
             .ok_or("Missing required parameter: text")?;
+        let timeout = std::time::Duration::from_millis(params["timeout_ms"].as_u64().unwrap_or(DEFAULT_TEXT_WAIT_MS).min(MAX_WAIT_MS));
+        let interval = std::time::Duration::from_millis(params["interval_ms"].as_u64().unwrap_or(500).max(MIN_TEXT_POLL_MS));
         let confidence_threshold = params["confidence_threshold"].as_f64().unwrap_or(0.8);
         let case_sensitive = params["case_sensitive"].as_bool().unwrap_or(false);
         
-        // Take screenshot first
-        let screenshot_result = take_screenshot_full(Some("png".to_string()), Some(80)).await?;
-        
-        // Perform OCR on the screenshot
-        let text_locations = find_text_in_image(&screenshot_result.image_base64, text_to_find, confidence_threshold, case_sensitive).await?;
-        
-        let execution_time = start_time.elapsed().as_millis() as u64;
+        log::info!("Session {}: Waiting up to {:?} for '{}'", session_id, timeout, text_to_find);
         
-        Ok(ToolExecutionResult {
-            success: true,
-            result: serde_json::json!({
-                "text_locations": text_locations,
-                "search_text": text_to_find,
-                "confidence_threshold": confidence_threshold,
-                "matches_found": text_locations.len()
-            }),
-            error: None,
-            execution_time_ms: execution_time,
-            tool_name: "find_text".to_string(),
-        })
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let screenshot = take_screenshot_full(Some("png".to_string()), Some(80)).await?;
+            let text_locations = find_text_in_image(&screenshot.image_base64, text_to_find, confidence_threshold, case_sensitive).await?;
+            let waited = start_time.elapsed();
+            
+            if !text_locations.is_empty() {
+                let mut result = serde_json::to_value(FindTextResult {
+                    matches_found: text_locations.len(),
+                    text_locations,
+                    search_text: text_to_find.to_string(),
+                    confidence_threshold,
+                }).map_err(|e| format!("Failed to serialize wait_for_text result: {}", e))?;
+                result["waited_ms"] = serde_json::json!(waited.as_millis() as u64);
+                result["attempts"] = serde_json::json!(attempts);
+                return Ok(ToolExecutionResult {
+                    success: true,
+                    result,
+                    error: None,
+                    execution_time_ms: waited.as_millis() as u64,
+                    tool_name: self.name().to_string(),
+                });
+            }
+            
+            if waited + interval > timeout {
+                let error_msg = format!("'{}' did not appear within {} ms", text_to_find, timeout.as_millis());
+                return Ok(ToolExecutionResult {
+                    success: false,
+                    result: serde_json::json!({"success": false, "error": error_msg, "attempts": attempts}),
+                    error: Some(error_msg),
+                    execution_time_ms: waited.as_millis() as u64,
+                    tool_name: self.name().to_string(),
+                });
+            }
+            tokio::time::sleep(interval).await;
+        }
     }
     
     fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
@@ -1109,6 +2502,18 @@ impl ComputerUseTool for ClickAtTool {
                     "type": "integer",
                     "description": "Y coordinate to click"
                 },
+                "x_pct": {
+                    "type": "number",
+                    "description": "X as a percentage (0-100) of the target monitor's width; overrides x"
+                },
+                "y_pct": {
+                    "type": "number",
+                    "description": "Y as a percentage (0-100) of the target monitor's height; overrides y"
+                },
+                "monitor": {
+                    "type": "integer",
+                    "description": "Monitor index from get_screen_info for percentage coordinates (default: primary)"
+                },
                 "button": {
                     "type": "string",
                     "enum": ["left", "right", "middle"],
@@ -1120,16 +2525,28 @@ impl ComputerUseTool for ClickAtTool {
                     "default": false,
                     "description": "Whether to perform a double-click"
                 }
-            },
-            "required": ["x", "y"]
+            }
         })
     }
     
-    async fn execute(&self, params: serde_json::Value, _session_id: &str) -> Result<ToolExecutionResult, String> {
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "clicked_at": position_schema(),
+                "button": { "type": "string" },
+                "double_click": { "type": "boolean" },
+                "message": { "type": "string" }
+            }
+        })
+    }
+    
+    async fn execute(&self, mut params: serde_json::Value, _session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
-        let x = params["x"].as_i64().ok_or("Missing required parameter: x")? as i32;
-        let y = params["y"].as_i64().ok_or("Missing required parameter: y")? as i32;
+        resolve_percentage_coordinates(&mut params)?;
+        let x = params["x"].as_i64().ok_or("Missing required parameter: x (or x_pct)")? as i32;
+        let y = params["y"].as_i64().ok_or("Missing required parameter: y (or y_pct)")? as i32;
         let button = params["button"].as_str().unwrap_or("left");
         let double_click = params["double_click"].as_bool().unwrap_or(false);
         
@@ -1138,14 +2555,16 @@ impl ComputerUseTool for ClickAtTool {
         
         let execution_time = start_time.elapsed().as_millis() as u64;
         
+        let result = ClickAtResult {
+            clicked_at: CursorPosition { x, y },
+            button: button.to_string(),
+            double_click,
+            message: format!("Successfully clicked at ({}, {})", x, y),
+        };
+        
         Ok(ToolExecutionResult {
             success: true,
-            result: serde_json::json!({
-                "clicked_at": {"x": x, "y": y},
-                "button": button,
-                "double_click": double_click,
-                "message": format!("Successfully clicked at ({}, {})", x, y)
-            }),
+            result: serde_json::to_value(result).map_err(|e| format!("Failed to serialize click_at result: {}", e))?,
             error: None,
             execution_time_ms: execution_time,
             tool_name: "click_at".to_string(),
@@ -1196,6 +2615,18 @@ impl ComputerUseTool for ClickOnTextTool {
         })
     }
     
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "text_found": { "type": "string" },
+                "location": position_schema(),
+                "confidence": { "type": "number" },
+                "click_result": ClickAtTool.result_schema()
+            }
+        })
+    }
+    
     async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
@@ -1216,10 +2647,10 @@ impl ComputerUseTool for ClickOnTextTool {
             });
         }
         
-        let text_locations = find_result.result["text_locations"].as_array()
-            .ok_or("Invalid find_text result format")?;
+        let found: FindTextResult = serde_json::from_value(find_result.result)
+            .map_err(|e| format!("Invalid find_text result: {}", e))?;
             
-        if text_locations.is_empty() {
+        let Some(best_match) = found.text_locations.first() else {
             return Ok(ToolExecutionResult {
                 success: false,
                 result: serde_json::json!({
@@ -1230,17 +2661,13 @@ impl ComputerUseTool for ClickOnTextTool {
                 execution_time_ms: start_time.elapsed().as_millis() as u64,
                 tool_name: "click_on_text".to_string(),
             });
-        }
-        
-        // Use the first (most confident) match
-        let best_match = &text_locations[0];
-        let x = best_match["center_x"].as_i64().ok_or("Invalid text location format")? as i32;
-        let y = best_match["center_y"].as_i64().ok_or("Invalid text location format")? as i32;
+        };
         
-        // Step 2: Click at the found location
+        // Step 2: Click at the first (most confident) match
+        let location = CursorPosition { x: best_match.center_x, y: best_match.center_y };
         let click_params = serde_json::json!({
-            "x": x,
-            "y": y,
+            "x": location.x,
+            "y": location.y,
             "button": params["button"].as_str().unwrap_or("left")
         });
         
@@ -1249,14 +2676,18 @@ impl ComputerUseTool for ClickOnTextTool {
         
         let execution_time = start_time.elapsed().as_millis() as u64;
         
+        let clicked: ClickAtResult = serde_json::from_value(click_result.result)
+            .map_err(|e| format!("Invalid click_at result: {}", e))?;
+        let result = ClickOnTextResult {
+            text_found: text_to_find.to_string(),
+            location,
+            confidence: best_match.confidence,
+            click_result: clicked,
+        };
+        
         Ok(ToolExecutionResult {
             success: click_result.success,
-            result: serde_json::json!({
-                "text_found": text_to_find,
-                "location": {"x": x, "y": y},
-                "confidence": best_match["confidence"],
-                "click_result": click_result.result
-            }),
+            result: serde_json::to_value(result).map_err(|e| format!("Failed to serialize click_on_text result: {}", e))?,
             error: click_result.error,
             execution_time_ms: execution_time,
             tool_name: "click_on_text".to_string(),
@@ -1431,6 +2862,21 @@ impl ComputerUseTool for ClickAndTypeTool {
         })
     }
     
+    fn result_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "click_target": { "type": "string" },
+                "text_to_type": { "type": "string" },
+                "click_location": position_schema(),
+                "characters_typed": { "type": "integer" },
+                "cleared_existing": { "type": "boolean" },
+                "pressed_enter": { "type": "boolean" },
+                "message": { "type": "string" }
+            }
+        })
+    }
+    
     async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
         let start_time = Instant::now();
         
@@ -1470,6 +2916,9 @@ impl ComputerUseTool for ClickAndTypeTool {
             });
         }
         
+        let clicked: ClickOnTextResult = serde_json::from_value(click_result.result)
+            .map_err(|e| format!("Invalid click_on_text result: {}", e))?;
+        
         // Small delay to ensure the click registered and focus changed
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         
@@ -1498,7 +2947,7 @@ impl ComputerUseTool for ClickAndTypeTool {
                     "click_target": click_target,
                     "text_to_type": text_to_type,
                     "step_failed": "type",
-                    "click_location": click_result.result["location"],
+                    "click_location": clicked.location,
                     "error": format!("Failed to type text: {}", e)
                 }),
                 error: Some(format!("Failed to type text: {}", e)),
@@ -1518,17 +2967,20 @@ impl ComputerUseTool for ClickAndTypeTool {
         
         let execution_time = start_time.elapsed().as_millis() as u64;
         
+        let characters_typed = text_to_type.chars().count();
+        let result = ClickAndTypeResult {
+            click_target: click_target.to_string(),
+            text_to_type: text_to_type.to_string(),
+            click_location: clicked.location,
+            characters_typed,
+            cleared_existing: clear_existing,
+            pressed_enter: press_enter,
+            message: format!("Successfully clicked '{}' and typed {} characters", click_target, characters_typed),
+        };
+        
         Ok(ToolExecutionResult {
             success: true,
-            result: serde_json::json!({
-                "click_target": click_target,
-                "text_to_type": text_to_type,
-                "click_location": click_result.result["location"],
-                "characters_typed": text_to_type.chars().count(),
-                "cleared_existing": clear_existing,
-                "pressed_enter": press_enter,
-                "message": format!("Successfully clicked '{}' and typed {} characters", click_target, text_to_type.chars().count())
-            }),
+            result: serde_json::to_value(result).map_err(|e| format!("Failed to serialize click_and_type result: {}", e))?,
             error: None,
             execution_time_ms: execution_time,
             tool_name: "click_and_type".to_string(),
@@ -1540,25 +2992,350 @@ impl ComputerUseTool for ClickAndTypeTool {
     }
 }
 
-// ========== OCR HELPER FUNCTIONS ==========
+// ========== PERCENTAGE COORDINATES ==========
+
+/// Map a 0-100 percentage onto a monitor axis, clamped to the last pixel.
+fn percentage_to_pixel(pct: f64, origin: i32, extent: u32) -> Result<i32, String> {
+    if !(0.0..=100.0).contains(&pct) {
+        return Err(format!("Percentage coordinate out of range (0-100): {}", pct));
+    }
+    let offset = (pct / 100.0 * extent as f64).round() as i32;
+    Ok(origin + offset.min(extent.saturating_sub(1) as i32))
+}
+
+/// Rewrite x_pct/y_pct into absolute x/y against the targeted monitor, so saved plans
+/// keep working when the resolution or monitor layout changes.
+fn resolve_percentage_coordinates(params: &mut serde_json::Value) -> Result<(), String> {
+    resolve_percentage_fields(params, "x", "y")
+}
+
+/// Like `resolve_percentage_coordinates`, for a coordinate pair named `x_key`/`y_key` (with
+/// `<key>_pct` percentages), so tools taking several points can accept percentages for each.
+fn resolve_percentage_fields(params: &mut serde_json::Value, x_key: &str, y_key: &str) -> Result<(), String> {
+    let x_pct = params.get(format!("{}_pct", x_key)).and_then(|v| v.as_f64());
+    let y_pct = params.get(format!("{}_pct", y_key)).and_then(|v| v.as_f64());
+    if x_pct.is_none() && y_pct.is_none() {
+        return Ok(());
+    }
+    
+    let screen = get_screen_info()?;
+    let monitor = screen.target(params.get("monitor").and_then(|v| v.as_u64()).map(|i| i as usize))?;
+    
+    if let Some(pct) = x_pct {
+        params[x_key] = serde_json::json!(percentage_to_pixel(pct, monitor.x, monitor.width)?);
+    }
+    if let Some(pct) = y_pct {
+        params[y_key] = serde_json::json!(percentage_to_pixel(pct, monitor.y, monitor.height)?);
+    }
+    Ok(())
+}
+
+// ========== POINTER PATHS ==========
+
+const DEFAULT_DRAG_DURATION_MS: u64 = 500;
+const MAX_DRAG_DURATION_MS: u64 = 10_000;
+const MAX_JITTER_PX: u32 = 20;
+const MAX_DWELL_MS: u64 = 10_000;
+// Roughly one pointer update per display frame
+const DRAG_STEP_MS: u64 = 16;
+const MAX_DRAG_STEPS: u64 = 200;
+
+/// The points a drag from `from` moves through, ending at `to`, and the pause before each. The
+/// pointer eases in and out so targets that track velocity (sliders, window snapping) see a
+/// natural gesture rather than a jump.
+fn drag_path(from: (i32, i32), to: (i32, i32), duration_ms: u64) -> (Vec<(i32, i32)>, std::time::Duration) {
+    let steps = (duration_ms / DRAG_STEP_MS).clamp(2, MAX_DRAG_STEPS);
+    let path = (1..=steps)
+        .map(|i| {
+            let t = i as f64 / steps as f64;
+            let eased = t * t * (3.0 - 2.0 * t);
+            (
+                from.0 + ((to.0 - from.0) as f64 * eased).round() as i32,
+                from.1 + ((to.1 - from.1) as f64 * eased).round() as i32,
+            )
+        })
+        .collect();
+    (path, std::time::Duration::from_millis(duration_ms / steps))
+}
+
+/// Nudge the points of an eased path by up to `jitter_px`, most in the middle and not at all at
+/// the end, so the cursor still lands exactly on its target.
+fn jitter_path(mut path: Vec<(i32, i32)>, jitter_px: u32) -> Vec<(i32, i32)> {
+    use rand::Rng;
+    if jitter_px == 0 || path.len() < 2 {
+        return path;
+    }
+    let mut rng = rand::thread_rng();
+    let steps = path.len();
+    for (i, point) in path.iter_mut().enumerate().take(steps - 1) {
+        let reach = (std::f64::consts::PI * (i + 1) as f64 / steps as f64).sin() * jitter_px as f64;
+        point.0 += (rng.gen_range(-1.0..=1.0) * reach).round() as i32;
+        point.1 += (rng.gen_range(-1.0..=1.0) * reach).round() as i32;
+    }
+    path
+}
+
+// ========== LAUNCHING ==========
+
+// Programs that run whatever command line their arguments hold. open_app refuses them, so
+// arbitrary commands only go through run_command and its allow_shell_commands opt-in.
+const COMMAND_RUNNERS: &[&str] = &[
+    "cmd", "powershell", "powershell_ise", "pwsh", "wsl", "bash", "sh", "zsh", "fish", "dash", "ksh", "csh", "tcsh",
+    "python", "python3", "pythonw", "py", "node", "deno", "bun", "perl", "ruby", "php", "lua", "osascript",
+    "wscript", "cscript", "mshta", "rundll32", "regsvr32", "msiexec", "env", "sudo", "doas", "pkexec", "nohup",
+    "xargs", "timeout", "open", "xdg-open", "start", "gtk-launch", "terminal", "gnome-terminal", "konsole",
+    "xterm", "wt", "conhost",
+];
+
+fn refuse_command_runner(app: &str) -> Result<(), String> {
+    let file_name = app.rsplit(['/', '\\']).next().unwrap_or(app).to_lowercase();
+    let name = [".exe", ".com", ".bat", ".cmd", ".ps1", ".app"].iter()
+        .find_map(|ext| file_name.strip_suffix(ext))
+        .unwrap_or(file_name.as_str());
+    // Versioned interpreters: python3.12, pwsh7, node18
+    let base = name.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    // Scripts, plus the file types ShellExecute runs code from: HTML apps, shortcuts, installers,
+    // screensavers and the like
+    let scripts = [
+        ".bat", ".cmd", ".ps1", ".psm1", ".sh", ".py", ".vbs", ".vbe", ".js", ".jse", ".wsf", ".wsh", ".jar",
+        ".hta", ".lnk", ".url", ".appref-ms", ".msi", ".msp", ".scr", ".pif", ".cpl", ".reg", ".inf",
+    ];
+    if COMMAND_RUNNERS.contains(&name) || COMMAND_RUNNERS.contains(&base) || scripts.iter().any(|ext| file_name.ends_with(ext)) {
+        return Err(format!("'{}' runs arbitrary commands; open_app only starts applications (use run_command where it's enabled)", app));
+    }
+    Ok(())
+}
+
+/// One argument quoted the way CommandLineToArgvW splits a command line: backslashes are only
+/// special before a quote, so those (and the ones before the closing quote) are doubled.
+#[cfg(any(target_os = "windows", test))]
+fn quote_windows_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || c == '"') {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// `url` if it's an absolute web or mail link. Other schemes (file:, javascript:, custom app
+/// protocols) could run or open anything, so they're refused.
+fn validate_open_url(url: &str) -> Result<tauri::Url, String> {
+    let parsed = tauri::Url::parse(url.trim()).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+    match parsed.scheme() {
+        "http" | "https" if parsed.host_str().is_some() => Ok(parsed),
+        "mailto" => Ok(parsed),
+        scheme => Err(format!("Only http, https and mailto URLs can be opened, not {}:", scheme)),
+    }
+}
+
+// ========== WINDOW SELECTION ==========
+
+fn window_matches(window: &DesktopWindow, query: &WindowQuery) -> bool {
+    let contains = |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+    query.window_id.map_or(true, |id| window.id == id)
+        && query.title.as_deref().map_or(true, |title| contains(&window.title, title))
+        && query.process.as_deref().map_or(true, |process| {
+            window.process_name.as_deref().map_or(false, |name| contains(name, process))
+        })
+}
+
+/// The window `query` picks out of `windows` (front to back). Several matches go to the frontmost,
+/// unless one's title matches exactly.
+fn select_window(windows: Vec<DesktopWindow>, query: &WindowQuery) -> Result<DesktopWindow, String> {
+    if query.window_id.is_none() && query.title.is_none() && query.process.is_none() {
+        return Err("Give a window_id, title or process to pick a window".to_string());
+    }
+    let mut matches: Vec<DesktopWindow> = windows.into_iter().filter(|window| window_matches(window, query)).collect();
+    if matches.is_empty() {
+        return Err(format!("No open window matches {:?}", query));
+    }
+    let exact = query.title.as_deref()
+        .and_then(|title| matches.iter().position(|window| window.title.eq_ignore_ascii_case(title)))
+        .unwrap_or(0);
+    Ok(matches.swap_remove(exact))
+}
+
+// ========== ACCESSIBILITY TREE ==========
+
+const DEFAULT_UI_DEPTH: usize = 8;
+const MAX_UI_DEPTH: usize = 25;
+const DEFAULT_UI_ELEMENTS: usize = 300;
+const MAX_UI_ELEMENTS: usize = 2_000;
+
+/// The accessibility tree of window `window` (an id from list_windows), or of the foreground window.
+async fn ui_tree(window: Option<u64>, max_depth: usize, max_elements: usize) -> Result<UiTree, String> {
+    tokio::task::spawn_blocking(move || {
+        #[cfg(target_os = "windows")]
+        {
+            crate::mcp::windows_uia::ui_tree(window, max_depth, max_elements)
+        }
+        #[cfg(target_os = "macos")]
+        {
+            crate::mcp::macos::ui_tree(window, max_depth, max_elements)
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        {
+            let _ = (window, max_depth, max_elements);
+            Err("The accessibility tree is only available on Windows and macOS currently".to_string())
+        }
+    })
+    .await
+    .map_err(|e| format!("Accessibility query failed: {}", e))?
+}
+
+// The window a query picks, or None (the foreground window) when it names none
+async fn target_window(query: &WindowQuery) -> Result<Option<DesktopWindow>, String> {
+    if query.window_id.is_none() && query.title.is_none() && query.process.is_none() {
+        return Ok(None);
+    }
+    list_windows().await.and_then(|windows| select_window(windows, query)).map(Some)
+}
+
+/// Clickable elements under `root` whose name contains `name` (case-insensitive), optionally with
+/// the given role, in tree order with exact name matches first. Disabled and zero-size elements
+/// are left out.
+fn find_ui_elements<'a>(root: &'a UiElement, name: &str, role: Option<&str>) -> Vec<&'a UiElement> {
+    let name = name.trim().to_lowercase();
+    let mut exact = Vec::new();
+    let mut partial = Vec::new();
+    let mut stack = vec![root];
+    while let Some(element) = stack.pop() {
+        stack.extend(element.children.iter().rev());
+        if !element.enabled || element.bounds.width == 0 || element.bounds.height == 0 {
+            continue;
+        }
+        if role.map_or(false, |role| !element.role.eq_ignore_ascii_case(role.trim())) {
+            continue;
+        }
+        let element_name = element.name.trim().to_lowercase();
+        if element_name == name {
+            exact.push(element);
+        } else if !name.is_empty() && element_name.contains(&name) {
+            partial.push(element);
+        }
+    }
+    exact.extend(partial);
+    exact
+}
+
+// ========== RESULT SCHEMA FRAGMENTS ==========
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct TextLocation {
-    text: String,
-    confidence: f32,
-    bounding_box: TextBoundingBox,
-    center_x: i32,
-    center_y: i32,
+fn position_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "x": { "type": "integer" },
+            "y": { "type": "integer" }
+        }
+    })
+}
+
+fn window_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer" },
+            "title": { "type": "string" },
+            "process_id": { "type": "integer" },
+            "process_name": { "type": "string" },
+            "x": { "type": "integer" },
+            "y": { "type": "integer" },
+            "width": { "type": "integer" },
+            "height": { "type": "integer" },
+            "minimized": { "type": "boolean" }
+        }
+    })
+}
+
+// Children have the same shape as their parent
+fn ui_element_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "role": { "type": "string" },
+            "bounds": {
+                "type": "object",
+                "properties": {
+                    "x": { "type": "integer" },
+                    "y": { "type": "integer" },
+                    "width": { "type": "integer" },
+                    "height": { "type": "integer" }
+                }
+            },
+            "enabled": { "type": "boolean" },
+            "children": {
+                "type": "array",
+                "items": { "type": "object" }
+            }
+        }
+    })
+}
+
+// Parameters picking one window, plus any tool-specific `extra` properties
+fn window_query_schema(extra: serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
+    let mut properties = serde_json::Map::new();
+    properties.insert("window_id".to_string(), serde_json::json!({
+        "type": "integer",
+        "description": "Window id from list_windows"
+    }));
+    properties.insert("title".to_string(), serde_json::json!({
+        "type": "string",
+        "description": "Text in the window title (case-insensitive); the frontmost match is used"
+    }));
+    properties.insert("process".to_string(), serde_json::json!({
+        "type": "string",
+        "description": "Text in the process name, e.g. \"chrome\" or \"code\" (case-insensitive)"
+    }));
+    properties.extend(extra);
+    serde_json::json!({
+        "type": "object",
+        "properties": properties
+    })
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct TextBoundingBox {
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
+fn text_location_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "text": { "type": "string" },
+            "confidence": { "type": "number" },
+            "bounding_box": {
+                "type": "object",
+                "properties": {
+                    "x": { "type": "integer" },
+                    "y": { "type": "integer" },
+                    "width": { "type": "integer" },
+                    "height": { "type": "integer" }
+                }
+            },
+            "center_x": { "type": "integer" },
+            "center_y": { "type": "integer" }
+        }
+    })
 }
 
+// ========== OCR HELPER FUNCTIONS ==========
+
+
 async fn find_text_in_image(
     base64_image: &str,
     target_text: &str,
@@ -1569,9 +3346,20 @@ async fn find_text_in_image(
     {
         windows_ocr_find_text(base64_image, target_text, confidence_threshold, case_sensitive).await
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "macos")]
+    {
+        let png = decode_ocr_image(base64_image)?;
+        let recognized = crate::mcp::macos::recognize_text(&png, Some(target_text), case_sensitive)?;
+        let mut locations: Vec<TextLocation> = crate::mcp::macos::to_text_locations(recognized)
+            .into_iter()
+            .filter(|l| l.confidence >= confidence_threshold as f32)
+            .collect();
+        locations.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(locations)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
     {
-        Err("OCR is only supported on Windows currently".to_string())
+        Err("OCR is only supported on Windows and macOS currently".to_string())
     }
 }
 
@@ -1584,12 +3372,34 @@ async fn debug_ocr_scan(
     {
         windows_ocr_debug_scan(base64_image, confidence_threshold, show_all).await
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(target_os = "macos")]
     {
-        Err("OCR is only supported on Windows currently".to_string())
+        let png = decode_ocr_image(base64_image)?;
+        let recognized = crate::mcp::macos::recognize_text(&png, None, false)?;
+        let mut locations: Vec<TextLocation> = crate::mcp::macos::to_text_locations(recognized)
+            .into_iter()
+            .filter(|l| show_all || l.confidence >= confidence_threshold as f32)
+            .collect();
+        locations.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.bounding_box.y.cmp(&b.bounding_box.y))
+            .then_with(|| a.bounding_box.x.cmp(&b.bounding_box.x)));
+        Ok(locations)
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        Err("OCR is only supported on Windows and macOS currently".to_string())
     }
 }
 
+#[cfg(target_os = "macos")]
+fn decode_ocr_image(base64_image: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(base64_image)
+        .map_err(|e| format!("Failed to decode base64 image: {}", e))
+}
+
 #[cfg(target_os = "windows")]
 async fn windows_ocr_find_text(
     base64_image: &str,
@@ -1847,7 +3657,23 @@ async fn click_at_coordinates(x: i32, y: i32, button: &str, double_click: bool)
     {
         windows_click_at(x, y, button, double_click).await
     }
-    #[cfg(not(target_os = "windows"))]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let button = match button {
+            "right" => MouseButton::Right,
+            "middle" => MouseButton::Middle,
+            _ => MouseButton::Left,
+        };
+        #[cfg(target_os = "linux")]
+        {
+            crate::mcp::linux::click(x, y, button, double_click).await
+        }
+        #[cfg(target_os = "macos")]
+        {
+            crate::mcp::macos::click(x, y, button, double_click).await
+        }
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     {
         Err("Click not implemented for this platform".to_string())
     }
@@ -1886,4 +3712,124 @@ async fn windows_click_at(x: i32, y: i32, button: &str, double_click: bool) -> R
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_path_eases_from_start_to_end() {
+        let (path, interval) = drag_path((100, 200), (500, 200), 500);
+        assert_eq!(path.len(), 31);
+        assert_eq!(interval, std::time::Duration::from_millis(16));
+        assert_eq!(*path.last().unwrap(), (500, 200));
+        // Slow at the ends, fast through the middle
+        let first_step = path[0].0 - 100;
+        let middle_step = path[16].0 - path[15].0;
+        assert!(first_step < middle_step);
+        assert!(path.windows(2).all(|w| w[0].0 <= w[1].0 && w[1].1 == 200));
+
+        // A zero duration still presses, moves and releases in a couple of steps
+        let (path, _) = drag_path((0, 0), (10, 10), 0);
+        assert_eq!(path, vec![(5, 5), (10, 10)]);
+
+        let (path, _) = drag_path((0, 0), (300, 300), 400);
+        let jittered = jitter_path(path.clone(), 5);
+        assert_eq!(jittered.last(), Some(&(300, 300)));
+        assert!(jittered.iter().zip(&path).all(|(a, b)| (a.0 - b.0).abs() <= 5 && (a.1 - b.1).abs() <= 5));
+    }
+
+    #[test]
+    fn test_smooth_scroll_splits_the_wheel_delta_over_time() {
+        let scroll = |amount: i32, smooth: bool, duration_ms: Option<u64>| ScrollParams {
+            x: None, y: None, direction: ScrollDirection::Down, amount: Some(amount), smooth: Some(smooth), duration_ms,
+        };
+        assert_eq!(scroll(3, false, None).wheel_steps(120, 30), (vec![360], std::time::Duration::ZERO));
+
+        let (deltas, interval) = scroll(3, true, None).wheel_steps(120, 30);
+        assert_eq!(deltas, vec![30; 12]);
+        assert_eq!(interval, std::time::Duration::from_millis(25));
+
+        // A short scroll takes fewer, bigger steps that still add up
+        let (deltas, interval) = scroll(5, true, Some(40)).wheel_steps(1, 1);
+        assert_eq!(deltas, vec![2, 1, 1, 1]);
+        assert_eq!(interval, std::time::Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_open_app_refuses_command_runners_and_quotes_arguments() {
+        assert!(refuse_command_runner("notepad").is_ok());
+        assert!(refuse_command_runner("/usr/bin/firefox").is_ok());
+        assert!(refuse_command_runner("C:\\Windows\\System32\\cmd.exe").is_err());
+        assert!(refuse_command_runner("PowerShell").is_err());
+        assert!(refuse_command_runner("/usr/bin/python3.12").is_err());
+        assert!(refuse_command_runner("setup.bat").is_err());
+        for file in ["payload.hta", "C:\\Users\\me\\Desktop\\Tool.LNK", "site.url", "setup.msi", "saver.scr"] {
+            assert!(refuse_command_runner(file).is_err(), "{} should be refused", file);
+        }
+
+        assert_eq!(quote_windows_arg("plain"), "plain");
+        assert_eq!(quote_windows_arg("two words"), "\"two words\"");
+        assert_eq!(quote_windows_arg("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(quote_windows_arg("C:\\My Dir\\"), "\"C:\\My Dir\\\\\"");
+        assert_eq!(quote_windows_arg("a\\\"b"), "\"a\\\\\\\"b\"");
+    }
+
+    #[test]
+    fn test_only_web_and_mail_urls_open() {
+        assert_eq!(validate_open_url(" https://example.com/a?b=1 ").unwrap().as_str(), "https://example.com/a?b=1");
+        assert!(validate_open_url("mailto:dana@example.com").is_ok());
+        assert!(validate_open_url("file:///etc/passwd").is_err());
+        assert!(validate_open_url("javascript:alert(1)").is_err());
+        assert!(validate_open_url("example.com").is_err());
+    }
+
+    fn window(id: u64, title: &str, process: &str) -> DesktopWindow {
+        DesktopWindow {
+            id, title: title.to_string(), process_id: id as u32, process_name: Some(process.to_string()),
+            x: 0, y: 0, width: 800, height: 600, minimized: false,
+        }
+    }
+
+    #[test]
+    fn test_select_window_prefers_exact_title_then_frontmost() {
+        let windows = vec![
+            window(1, "Inbox - Outlook", "OUTLOOK"),
+            window(2, "Notes - Visual Studio Code", "Code"),
+            window(3, "Notes", "notepad"),
+        ];
+        let by_title = |title: &str| WindowQuery { title: Some(title.to_string()), ..Default::default() };
+        assert_eq!(select_window(windows.clone(), &by_title("notes")).unwrap().id, 3);
+        assert_eq!(select_window(windows.clone(), &by_title("o")).unwrap().id, 1);
+        let by_process = WindowQuery { process: Some("code".to_string()), ..Default::default() };
+        assert_eq!(select_window(windows.clone(), &by_process).unwrap().id, 2);
+        assert!(select_window(windows.clone(), &WindowQuery { window_id: Some(9), ..Default::default() }).is_err());
+        assert!(select_window(windows, &WindowQuery::default()).is_err());
+    }
+
+    fn element(name: &str, role: &str, width: u32, enabled: bool, children: Vec<UiElement>) -> UiElement {
+        UiElement {
+            name: name.to_string(), role: role.to_string(), enabled, children,
+            bounds: ScreenRegion { x: 0, y: 0, width, height: 20 },
+        }
+    }
+
+    #[test]
+    fn test_find_ui_elements_ranks_exact_names_and_skips_unclickable() {
+        let root = element("Settings", "window", 800, true, vec![
+            element("Save as…", "menuitem", 100, true, vec![]),
+            element("", "pane", 700, true, vec![
+                element("Save", "button", 80, false, vec![]),
+                element("save", "button", 80, true, vec![]),
+                element("Save", "text", 0, true, vec![]),
+            ]),
+        ]);
+        let names = |found: Vec<&UiElement>| found.iter().map(|e| (e.name.clone(), e.role.clone())).collect::<Vec<_>>();
+        assert_eq!(names(find_ui_elements(&root, "Save", None)), vec![
+            ("save".to_string(), "button".to_string()),
+            ("Save as…".to_string(), "menuitem".to_string()),
+        ]);
+        assert_eq!(find_ui_elements(&root, "save", Some("MenuItem")).len(), 1);
+        assert!(find_ui_elements(&root, "Open", None).is_empty());
+    }
+}
@@ -9,6 +9,20 @@ pub struct MCPSessionConfig {
     pub enable_logging: bool,
     pub server_name: String,
     pub server_version: String,
+    #[serde(default)]
+    pub tool_timeouts: ToolTimeouts,
+    // Folders the filesystem tools may read and write; with none they aren't offered
+    #[serde(default)]
+    pub file_roots: Vec<String>,
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+    // Offers run_command; each run still needs approval
+    #[serde(default)]
+    pub allow_shell_commands: bool,
+}
+
+fn default_max_file_bytes() -> u64 {
+    1024 * 1024
 }
 
 impl Default for MCPSessionConfig {
@@ -19,12 +33,49 @@ impl Default for MCPSessionConfig {
             enable_logging: true,
             server_name: "enteract-mcp-server".to_string(),
             server_version: "1.0.0".to_string(),
+            tool_timeouts: ToolTimeouts::default(),
+            file_roots: Vec::new(),
+            max_file_bytes: default_max_file_bytes(),
+            allow_shell_commands: false,
+        }
+    }
+}
+
+// Watchdog limits for a single tool execution, by danger level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolTimeouts {
+    pub low_ms: u64,
+    pub medium_ms: u64,
+    pub high_ms: u64,
+    pub critical_ms: u64,
+}
+
+impl Default for ToolTimeouts {
+    fn default() -> Self {
+        Self {
+            low_ms: 30_000, // OCR over a large screenshot is the slowest read
+            medium_ms: 20_000,
+            high_ms: 60_000,
+            critical_ms: 60_000,
         }
     }
 }
 
+impl ToolTimeouts {
+    pub fn for_level(&self, level: DangerLevel) -> std::time::Duration {
+        let ms = match level {
+            DangerLevel::Low => self.low_ms,
+            DangerLevel::Medium => self.medium_ms,
+            DangerLevel::High => self.high_ms,
+            DangerLevel::Critical => self.critical_ms,
+        };
+        std::time::Duration::from_millis(ms.max(1))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolApprovalRequest {
+    pub approval_id: String, // Echoed back in the response so each prompt answers only its own call
     pub session_id: String,
     pub tool_name: String,
     pub tool_description: String,
@@ -35,9 +86,31 @@ pub struct ToolApprovalRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolApprovalResponse {
+    pub approval_id: String,
     pub session_id: String,
     pub approved: bool,
     pub reason: Option<String>,
+    #[serde(default)]
+    pub remember: ApprovalScope,
+}
+
+// How long an approval should be remembered for
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalScope {
+    #[default]
+    Once,
+    Session,         // This tool with any parameters, until the session ends (not for Critical tools)
+    ExactParameters, // This tool with these parameters, persisted across sessions
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub id: String,
+    pub tool_name: String,
+    pub parameters: Option<serde_json::Value>, // None matches any parameters; objects match as a subset
+    pub session_id: Option<String>,            // Set for session-scoped rules, which aren't persisted
+    pub created_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +148,7 @@ pub struct ToolInfo {
     pub danger_level: DangerLevel,
     pub requires_approval: bool,
     pub parameters_schema: serde_json::Value,
+    pub result_schema: serde_json::Value,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -124,6 +198,38 @@ pub struct ContextData {
     pub result: serde_json::Value,
     pub success: bool,
 }
+// Session recording, exported with export_mcp_session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepApproval {
+    pub required: bool,
+    pub approved: bool,
+    pub reason: Option<String>,
+    pub decided_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedStep {
+    pub index: usize,
+    pub tool_name: String,
+    pub parameters: serde_json::Value,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub approval: Option<StepApproval>,
+    pub result: Option<ToolExecutionResult>,
+    pub error: Option<String>,
+    pub screenshot_before: Option<String>, // data: URI, only for tools that act on the screen
+    pub screenshot_after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MCPSessionReport {
+    pub session: MCPSessionInfo,
+    pub plans: Vec<ToolExecutionPlan>,
+    pub steps: Vec<RecordedStep>,
+    pub logs: Vec<MCPLogEntry>,
+    pub exported_at: String,
+}
+
 // Internal types for approval workflow
 pub struct PendingApproval {
     pub request: ToolApprovalRequest,
@@ -156,6 +262,25 @@ pub struct ClickParams {
     pub button: Option<MouseButton>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DragParams {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+    pub duration_ms: Option<u64>,
+    pub button: Option<MouseButton>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveMouseParams {
+    pub x: i32,
+    pub y: i32,
+    pub duration_ms: Option<u64>,
+    pub jitter_px: Option<u32>,
+    pub dwell_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeParams {
     pub text: String,
@@ -167,7 +292,25 @@ pub struct ScrollParams {
     pub x: Option<i32>,
     pub y: Option<i32>,
     pub direction: ScrollDirection,
-    pub amount: Option<i32>,
+    pub amount: Option<i32>,      // Wheel notches (lines on macOS)
+    pub smooth: Option<bool>,     // Many small wheel deltas instead of one
+    pub duration_ms: Option<u64>, // How long a smooth scroll takes
+}
+
+impl ScrollParams {
+    /// The wheel deltas to send, as magnitudes, and the pause between them. A notch is
+    /// `units_per_notch` in the backend's units; a smooth scroll splits the total into steps of at
+    /// least `min_step` units, no closer than 10 ms apart, over `duration_ms` (default 300).
+    pub fn wheel_steps(&self, units_per_notch: i32, min_step: i32) -> (Vec<i32>, std::time::Duration) {
+        let total = self.amount.unwrap_or(3).max(1) * units_per_notch;
+        if !self.smooth.unwrap_or(false) {
+            return (vec![total], std::time::Duration::ZERO);
+        }
+        let duration_ms = self.duration_ms.unwrap_or(300).min(5_000);
+        let steps = (total / min_step.max(1)).clamp(1, 100).min((duration_ms / 10).max(1) as i32);
+        let deltas = (0..steps).map(|i| total / steps + i32::from(i < total % steps)).collect();
+        (deltas, std::time::Duration::from_millis(duration_ms / steps as u64))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -199,17 +342,122 @@ pub enum KeyModifier {
     Meta,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct CursorPosition {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ScreenInfo {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAppParams {
+    pub app: String,
+    pub args: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenUrlParams {
+    pub url: String,
+}
+
+/// A top-level application window on the desktop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesktopWindow {
+    pub id: u64, // HWND on Windows, X11 window id on Linux
+    pub title: String,
+    pub process_id: u32,
+    pub process_name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub minimized: bool,
+}
+
+/// Picks a window by id, or by case-insensitive title and/or process name substrings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowQuery {
+    pub window_id: Option<u64>,
+    pub title: Option<String>,
+    pub process: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveResizeWindowParams {
+    #[serde(flatten)]
+    pub window: WindowQuery,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// A control from a window's accessibility tree (UI Automation / AXUIElement), with its bounds
+/// in screen coordinates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiElement {
+    pub name: String,
+    pub role: String, // "button", "edit", "checkbox", ...
+    pub bounds: ScreenRegion,
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<UiElement>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiTree {
+    pub window_title: String,
+    pub root: UiElement,
+    pub element_count: usize,
+    pub truncated: bool, // Depth or element limit reached
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetUiElementsParams {
+    #[serde(flatten)]
+    pub window: WindowQuery, // Empty: the foreground window
+    pub max_depth: Option<usize>,
+    pub max_elements: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickElementParams {
+    #[serde(flatten)]
+    pub window: WindowQuery,
+    pub name: String,
+    pub role: Option<String>,
+    pub index: Option<usize>, // Which match to click, 0-based
+    pub button: Option<String>,
+    pub double_click: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitorDetails {
+    pub index: usize,
+    pub name: String,
+    pub x: i32, // Offset within the virtual desktop
+    pub y: i32,
     pub width: u32,
     pub height: u32,
+    pub work_area: ScreenRegion, // Excludes taskbars and docks
     pub scale_factor: f64,
+    pub is_primary: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenInfo {
+    pub monitors: Vec<MonitorDetails>,
+}
+
+impl ScreenInfo {
+    /// The monitor at `index`, or the primary one when no index is given.
+    pub fn target(&self, index: Option<usize>) -> Result<&MonitorDetails, String> {
+        match index {
+            Some(index) => self.monitors.get(index)
+                .ok_or(format!("Monitor {} not found ({} available)", index, self.monitors.len())),
+            None => self.monitors.iter().find(|m| m.is_primary).or(self.monitors.first())
+                .ok_or("No monitors found".to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -217,6 +465,7 @@ pub struct ScreenshotParams {
     pub format: Option<String>, // "png", "jpeg"
     pub quality: Option<u8>,    // 1-100 for jpeg
     pub region: Option<ScreenRegion>,
+    pub window: Option<String>, // Capture one window by id or title instead of the screen
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -233,4 +482,75 @@ pub struct ScreenshotResult {
     pub width: u32,
     pub height: u32,
     pub format: String,
-}
\ No newline at end of file
+}
+
+// Typed tool results. Tools serialize these into ToolExecutionResult.result and compound tools
+// deserialize them back instead of indexing into raw JSON; result_schema() describes the same shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextBoundingBox {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextLocation {
+    pub text: String,
+    pub confidence: f32,
+    pub bounding_box: TextBoundingBox,
+    pub center_x: i32,
+    pub center_y: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindTextResult {
+    pub text_locations: Vec<TextLocation>,
+    pub search_text: String,
+    pub confidence_threshold: f64,
+    pub matches_found: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickAtResult {
+    pub clicked_at: CursorPosition,
+    pub button: String,
+    pub double_click: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickOnTextResult {
+    pub text_found: String,
+    pub location: CursorPosition,
+    pub confidence: f32,
+    pub click_result: ClickAtResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClickAndTypeResult {
+    pub click_target: String,
+    pub text_to_type: String,
+    pub click_location: CursorPosition,
+    pub characters_typed: usize,
+    pub cleared_existing: bool,
+    pub pressed_enter: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCapability {
+    pub tool_name: String,
+    pub functional: bool,
+    pub reason: Option<String>, // Why the tool won't work here, when it won't
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationCapabilities {
+    pub platform: String,
+    pub session_type: String, // windows | macos | x11 | wayland | headless
+    pub screenshot_backend: Option<String>,
+    pub input_backend: Option<String>,
+    pub ocr_available: bool,
+    pub tools: Vec<ToolCapability>,
+}
@@ -0,0 +1,265 @@
+// src-tauri/src/mcp/windows_input.rs
+// Windows keyboard backend for the computer use tools, on SendInput. Text goes in as UTF-16 code
+// units with KEYEVENTF_UNICODE, so it doesn't depend on the keyboard layout (AltGr characters,
+// emoji as surrogate pairs); key presses use virtual keys, with the extended flag on the keys that
+// need it so arrows and navigation keys aren't read as their numpad twins.
+use std::mem;
+use winapi::um::winuser::{
+    MapVirtualKeyW, SendInput, INPUT, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP,
+    KEYEVENTF_UNICODE, MAPVK_VK_TO_VSC,
+};
+
+use crate::mcp::types::KeyModifier;
+
+const VK_SHIFT: u16 = 0x10;
+const VK_CONTROL: u16 = 0x11;
+const VK_MENU: u16 = 0x12;
+const VK_LWIN: u16 = 0x5B;
+const VK_RETURN: u16 = 0x0D;
+const VK_TAB: u16 = 0x09;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualKey {
+    pub code: u16,
+    pub extended: bool,
+}
+
+const fn key(code: u16) -> VirtualKey {
+    VirtualKey { code, extended: false }
+}
+
+const fn extended(code: u16) -> VirtualKey {
+    VirtualKey { code, extended: true }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyEvent {
+    Down(VirtualKey),
+    Up(VirtualKey),
+    // One UTF-16 code unit, pressed and released
+    Unicode(u16),
+}
+
+/// The virtual key for a key name: letters, digits, US punctuation, F1-F24, navigation, editing,
+/// modifier, numpad and media keys.
+pub fn virtual_key(name: &str) -> Result<VirtualKey, String> {
+    let lower = name.to_lowercase();
+    let vk = match lower.as_str() {
+        "return" | "enter" => key(VK_RETURN),
+        "tab" => key(VK_TAB),
+        "escape" | "esc" => key(0x1B),
+        "space" => key(0x20),
+        "backspace" | "back" => key(0x08),
+        "delete" | "del" => extended(0x2E),
+        "insert" | "ins" => extended(0x2D),
+        "home" => extended(0x24),
+        "end" => extended(0x23),
+        "pageup" | "page_up" | "pgup" => extended(0x21),
+        "pagedown" | "page_down" | "pgdn" => extended(0x22),
+        "left" | "leftarrow" => extended(0x25),
+        "up" | "uparrow" => extended(0x26),
+        "right" | "rightarrow" => extended(0x27),
+        "down" | "downarrow" => extended(0x28),
+        "shift" => key(VK_SHIFT),
+        "ctrl" | "control" => key(VK_CONTROL),
+        "alt" => key(VK_MENU),
+        "meta" | "win" | "windows" | "super" | "cmd" => extended(VK_LWIN),
+        "menu" | "apps" | "contextmenu" => extended(0x5D),
+        "capslock" => key(0x14),
+        "numlock" => extended(0x90),
+        "scrolllock" => key(0x91),
+        "printscreen" | "prtsc" => extended(0x2C),
+        "pause" => key(0x13),
+        "volumemute" => extended(0xAD),
+        "volumedown" => extended(0xAE),
+        "volumeup" => extended(0xAF),
+        "medianext" => extended(0xB0),
+        "mediaprev" | "mediaprevious" => extended(0xB1),
+        "mediastop" => extended(0xB2),
+        "mediaplaypause" | "playpause" => extended(0xB3),
+        "multiply" => key(0x6A),
+        "add" => key(0x6B),
+        "subtract" => key(0x6D),
+        "decimal" => key(0x6E),
+        "divide" => extended(0x6F),
+        f if f.len() > 1 && f.starts_with('f') && f[1..].parse::<u16>().map_or(false, |n| (1..=24).contains(&n)) => {
+            key(0x70 + f[1..].parse::<u16>().unwrap_or(1) - 1)
+        }
+        n if n.starts_with("numpad") && n.len() == 7 && n.as_bytes()[6].is_ascii_digit() => {
+            key(0x60 + (n.as_bytes()[6] - b'0') as u16)
+        }
+        _ => {
+            let mut chars = lower.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_lowercase() => key(c.to_ascii_uppercase() as u16),
+                (Some(c), None) if c.is_ascii_digit() => key(c as u16),
+                // US layout positions of the OEM keys
+                (Some(c), None) => match c {
+                    ';' => key(0xBA),
+                    '=' => key(0xBB),
+                    ',' => key(0xBC),
+                    '-' => key(0xBD),
+                    '.' => key(0xBE),
+                    '/' => key(0xBF),
+                    '`' => key(0xC0),
+                    '[' => key(0xDB),
+                    '\\' => key(0xDC),
+                    ']' => key(0xDD),
+                    '\'' => key(0xDE),
+                    _ => return Err(format!("Unsupported key: {}", name)),
+                },
+                _ => return Err(format!("Unsupported key: {}", name)),
+            }
+        }
+    };
+    Ok(vk)
+}
+
+fn modifier_key(modifier: &KeyModifier) -> VirtualKey {
+    match modifier {
+        KeyModifier::Ctrl => key(VK_CONTROL),
+        KeyModifier::Alt => key(VK_MENU),
+        KeyModifier::Shift => key(VK_SHIFT),
+        KeyModifier::Meta => extended(VK_LWIN),
+    }
+}
+
+/// Modifiers go down in order and stay held across the key, then come up in reverse. A modifier
+/// listed twice, or that is the key itself, is only pressed once.
+pub fn key_events(key: VirtualKey, modifiers: &[KeyModifier]) -> Vec<KeyEvent> {
+    let mut held: Vec<VirtualKey> = Vec::new();
+    for modifier in modifiers.iter().map(modifier_key) {
+        if modifier != key && !held.contains(&modifier) {
+            held.push(modifier);
+        }
+    }
+    let mut events: Vec<KeyEvent> = held.iter().map(|&vk| KeyEvent::Down(vk)).collect();
+    events.push(KeyEvent::Down(key));
+    events.push(KeyEvent::Up(key));
+    events.extend(held.iter().rev().map(|&vk| KeyEvent::Up(vk)));
+    events
+}
+
+/// The events that type `text`, one entry per character. Line breaks (\n, \r\n or \r) press Enter
+/// and tabs press Tab, since many controls ignore them as Unicode input.
+pub fn text_events(text: &str) -> Vec<Vec<KeyEvent>> {
+    let mut typed = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        typed.push(match ch {
+            '\r' | '\n' => {
+                if ch == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                key_events(key(VK_RETURN), &[])
+            }
+            '\t' => key_events(key(VK_TAB), &[]),
+            _ => {
+                let mut units = [0u16; 2];
+                ch.encode_utf16(&mut units).iter().map(|&unit| KeyEvent::Unicode(unit)).collect()
+            }
+        });
+    }
+    typed
+}
+
+fn keyboard_input(vk: u16, scan: u16, flags: u32) -> INPUT {
+    unsafe {
+        let mut input = INPUT { type_: INPUT_KEYBOARD, u: mem::zeroed() };
+        *input.u.ki_mut() = KEYBDINPUT { wVk: vk, wScan: scan, dwFlags: flags, time: 0, dwExtraInfo: 0 };
+        input
+    }
+}
+
+fn to_inputs(events: &[KeyEvent]) -> Vec<INPUT> {
+    let mut inputs = Vec::with_capacity(events.len() * 2);
+    for event in events {
+        match *event {
+            KeyEvent::Down(vk) | KeyEvent::Up(vk) => {
+                let scan = unsafe { MapVirtualKeyW(vk.code as u32, MAPVK_VK_TO_VSC) } as u16;
+                let mut flags = if vk.extended { KEYEVENTF_EXTENDEDKEY } else { 0 };
+                if matches!(event, KeyEvent::Up(_)) {
+                    flags |= KEYEVENTF_KEYUP;
+                }
+                inputs.push(keyboard_input(vk.code, scan, flags));
+            }
+            KeyEvent::Unicode(unit) => {
+                inputs.push(keyboard_input(0, unit, KEYEVENTF_UNICODE));
+                inputs.push(keyboard_input(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+            }
+        }
+    }
+    inputs
+}
+
+// One SendInput call, so nothing the user types can land between the events
+fn send(events: &[KeyEvent]) -> Result<(), String> {
+    let mut inputs = to_inputs(events);
+    let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_mut_ptr(), mem::size_of::<INPUT>() as i32) };
+    if sent != inputs.len() as u32 {
+        return Err(format!("SendInput accepted {}/{} events (blocked by a higher-integrity window?)", sent, inputs.len()));
+    }
+    Ok(())
+}
+
+pub async fn type_text(text: &str, delay_ms: u64) -> Result<(), String> {
+    for events in text_events(text) {
+        send(&events)?;
+        if delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+        }
+    }
+    Ok(())
+}
+
+pub async fn press_key(key: &str, modifiers: &[KeyModifier]) -> Result<(), String> {
+    send(&key_events(virtual_key(key)?, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_map() {
+        assert_eq!(virtual_key("Enter").unwrap(), key(0x0D));
+        assert_eq!(virtual_key("a").unwrap(), key(0x41));
+        assert_eq!(virtual_key("7").unwrap(), key(0x37));
+        assert_eq!(virtual_key("F24").unwrap(), key(0x87));
+        assert_eq!(virtual_key("numpad3").unwrap(), key(0x63));
+        assert_eq!(virtual_key("PageDown").unwrap(), extended(0x22));
+        assert_eq!(virtual_key("left").unwrap(), extended(0x25));
+        assert_eq!(virtual_key("/").unwrap(), key(0xBF));
+        assert!(virtual_key("F25").is_err());
+        assert!(virtual_key("hyper").is_err());
+    }
+
+    #[test]
+    fn test_modifiers_are_held_across_the_key() {
+        let t = virtual_key("t").unwrap();
+        let events = key_events(t, &[KeyModifier::Ctrl, KeyModifier::Shift, KeyModifier::Ctrl]);
+        assert_eq!(events, vec![
+            KeyEvent::Down(key(VK_CONTROL)),
+            KeyEvent::Down(key(VK_SHIFT)),
+            KeyEvent::Down(t),
+            KeyEvent::Up(t),
+            KeyEvent::Up(key(VK_SHIFT)),
+            KeyEvent::Up(key(VK_CONTROL)),
+        ]);
+
+        // Win+Left keeps both keys extended; Shift as both key and modifier is pressed once
+        let events = key_events(virtual_key("left").unwrap(), &[KeyModifier::Meta]);
+        assert_eq!(events[0], KeyEvent::Down(extended(VK_LWIN)));
+        assert_eq!(key_events(key(VK_SHIFT), &[KeyModifier::Shift]).len(), 2);
+    }
+
+    #[test]
+    fn test_text_is_typed_as_unicode_with_real_line_breaks() {
+        let typed = text_events("é\r\n😀\t");
+        assert_eq!(typed.len(), 4);
+        assert_eq!(typed[0], vec![KeyEvent::Unicode(0xE9)]);
+        assert_eq!(typed[1], key_events(key(VK_RETURN), &[]));
+        assert_eq!(typed[2], vec![KeyEvent::Unicode(0xD83D), KeyEvent::Unicode(0xDE00)]);
+        assert_eq!(typed[3], key_events(key(VK_TAB), &[]));
+    }
+}
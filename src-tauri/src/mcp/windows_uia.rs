@@ -0,0 +1,98 @@
+// src-tauri/src/mcp/windows_uia.rs
+// Windows accessibility backend for get_ui_elements and click_element. Walks a window's UI
+// Automation control view (the elements a user can see and interact with, without the raw layout
+// panes). UI Automation calls block on the target app, so callers run this on a blocking thread.
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED};
+use windows::Win32::UI::Accessibility::{CUIAutomation, IUIAutomation, IUIAutomationElement, IUIAutomationTreeWalker};
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+use crate::mcp::types::*;
+
+// UIA_*ControlTypeId values, 50000 onwards
+const CONTROL_TYPES: [&str; 39] = [
+    "button", "calendar", "checkbox", "combobox", "edit", "hyperlink", "image", "listitem", "list", "menu",
+    "menubar", "menuitem", "progressbar", "radiobutton", "scrollbar", "slider", "spinner", "statusbar", "tab",
+    "tabitem", "text", "toolbar", "tooltip", "tree", "treeitem", "custom", "group", "thumb", "datagrid",
+    "dataitem", "document", "splitbutton", "window", "pane", "header", "headeritem", "table", "titlebar",
+    "separator",
+];
+
+fn control_type_name(id: i64) -> &'static str {
+    usize::try_from(id - 50_000).ok().and_then(|i| CONTROL_TYPES.get(i)).copied().unwrap_or("unknown")
+}
+
+struct Walk {
+    walker: IUIAutomationTreeWalker,
+    remaining: usize,
+    count: usize,
+    truncated: bool,
+}
+
+impl Walk {
+    unsafe fn element(&mut self, element: &IUIAutomationElement, depth: usize) -> UiElement {
+        self.remaining -= 1;
+        self.count += 1;
+        let rect = element.CurrentBoundingRectangle().unwrap_or(RECT::default());
+        let mut node = UiElement {
+            name: element.CurrentName().map(|name| name.to_string()).unwrap_or_default(),
+            role: element.CurrentControlType().map_or("unknown", |id| control_type_name(id.0 as i64)).to_string(),
+            bounds: ScreenRegion {
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left).max(0) as u32,
+                height: (rect.bottom - rect.top).max(0) as u32,
+            },
+            enabled: element.CurrentIsEnabled().map_or(true, |enabled| enabled.as_bool()),
+            children: Vec::new(),
+        };
+
+        // The walker reports "no more elements" as an error
+        let mut child = self.walker.GetFirstChildElement(element).ok();
+        if depth == 0 {
+            self.truncated |= child.is_some();
+            return node;
+        }
+        while let Some(current) = child {
+            if self.remaining == 0 {
+                self.truncated = true;
+                break;
+            }
+            node.children.push(self.element(&current, depth - 1));
+            child = self.walker.GetNextSiblingElement(&current).ok();
+        }
+        node
+    }
+}
+
+/// The control tree of window `window` (an HWND from list_windows), or of the foreground window,
+/// at most `max_depth` levels below the window and `max_elements` elements in all.
+pub fn ui_tree(window: Option<u64>, max_depth: usize, max_elements: usize) -> Result<UiTree, String> {
+    unsafe {
+        // Fails harmlessly when this thread already joined an apartment
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let automation: IUIAutomation = CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)
+            .map_err(|e| format!("UI Automation is unavailable: {}", e))?;
+
+        let hwnd = match window {
+            Some(id) => HWND(id as isize),
+            None => GetForegroundWindow(),
+        };
+        if hwnd.0 == 0 {
+            return Err("There is no foreground window".to_string());
+        }
+        let root = automation.ElementFromHandle(hwnd)
+            .map_err(|e| format!("Failed to read the window's accessibility tree: {}", e))?;
+        let walker = automation.ControlViewWalker()
+            .map_err(|e| format!("Failed to read the window's accessibility tree: {}", e))?;
+
+        let mut walk = Walk { walker, remaining: max_elements.max(1), count: 0, truncated: false };
+        let root = walk.element(&root, max_depth);
+        Ok(UiTree {
+            window_title: root.name.clone(),
+            root,
+            element_count: walk.count,
+            truncated: walk.truncated,
+        })
+    }
+}
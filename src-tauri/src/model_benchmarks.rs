@@ -0,0 +1,224 @@
+// src-tauri/src/model_benchmarks.rs
+// Measured model performance. `benchmark_ollama_model` (in ollama.rs) runs a fixed prompt set and
+// the results land in the model_benchmarks table here. The GPU acceleration status uses the
+// latest run per model to recommend a model from measured speed and memory rather than from VRAM
+// size alone: the largest model that still generates at an interactive rate on this machine.
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::AppHandle;
+
+// Below this a streamed reply visibly lags behind reading speed
+pub const INTERACTIVE_TOKENS_PER_SECOND: f64 = 15.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptBenchmark {
+    pub prompt_id: String,
+    pub time_to_first_token_ms: u64,
+    pub tokens_per_second: f64,                // Generation speed, from Ollama's eval timings
+    pub prompt_tokens_per_second: Option<f64>, // Prompt processing speed, when Ollama reports it
+    pub output_tokens: u32,
+    pub total_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBenchmark {
+    pub id: Option<i64>,
+    pub model: String,
+    pub gpu: Option<String>,          // GPU the run was measured on, so hardware changes show up
+    pub load_ms: u64,
+    pub tokens_per_second: f64,       // Mean over the prompts
+    pub time_to_first_token_ms: u64,  // Mean over the prompts
+    pub memory_bytes: Option<u64>,    // Resident size from /api/ps
+    pub vram_bytes: Option<u64>,      // ...of which in GPU memory
+    pub prompts: Vec<PromptBenchmark>,
+    pub created_at: i64,              // Unix ms
+}
+
+impl ModelBenchmark {
+    /// Whether the whole model ran from GPU memory.
+    pub fn fully_on_gpu(&self) -> bool {
+        matches!((self.memory_bytes, self.vram_bytes), (Some(total), Some(vram)) if total > 0 && vram >= total)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MeasuredRecommendation {
+    pub model: String,
+    pub tokens_per_second: f64,
+    pub time_to_first_token_ms: u64,
+    pub fully_on_gpu: bool,
+    pub reason: String,
+}
+
+fn open(db_path: &Path) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(db_path)?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS model_benchmarks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            model TEXT NOT NULL,
+            gpu TEXT,
+            load_ms INTEGER NOT NULL,
+            tokens_per_second REAL NOT NULL,
+            time_to_first_token_ms INTEGER NOT NULL,
+            memory_bytes INTEGER,
+            vram_bytes INTEGER,
+            prompts TEXT NOT NULL,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_model_benchmarks_model_created ON model_benchmarks(model, created_at DESC)",
+        [],
+    )?;
+    Ok(conn)
+}
+
+fn insert(conn: &Connection, benchmark: &ModelBenchmark) -> rusqlite::Result<i64> {
+    let prompts = serde_json::to_string(&benchmark.prompts)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    conn.execute(
+        "INSERT INTO model_benchmarks (model, gpu, load_ms, tokens_per_second, time_to_first_token_ms, memory_bytes, vram_bytes, prompts, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            benchmark.model,
+            benchmark.gpu,
+            benchmark.load_ms as i64,
+            benchmark.tokens_per_second,
+            benchmark.time_to_first_token_ms as i64,
+            benchmark.memory_bytes.map(|bytes| bytes as i64),
+            benchmark.vram_bytes.map(|bytes| bytes as i64),
+            prompts,
+            benchmark.created_at,
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn benchmark_from_row(row: &rusqlite::Row) -> rusqlite::Result<ModelBenchmark> {
+    let prompts: String = row.get("prompts")?;
+    Ok(ModelBenchmark {
+        id: row.get("id")?,
+        model: row.get("model")?,
+        gpu: row.get("gpu")?,
+        load_ms: row.get::<_, i64>("load_ms")? as u64,
+        tokens_per_second: row.get("tokens_per_second")?,
+        time_to_first_token_ms: row.get::<_, i64>("time_to_first_token_ms")? as u64,
+        memory_bytes: row.get::<_, Option<i64>>("memory_bytes")?.map(|bytes| bytes as u64),
+        vram_bytes: row.get::<_, Option<i64>>("vram_bytes")?.map(|bytes| bytes as u64),
+        prompts: serde_json::from_str(&prompts).unwrap_or_default(),
+        created_at: row.get("created_at")?,
+    })
+}
+
+// Every run, newest first, optionally for one model
+fn list(conn: &Connection, model: Option<&str>) -> rusqlite::Result<Vec<ModelBenchmark>> {
+    let mut stmt = conn.prepare(
+        "SELECT * FROM model_benchmarks WHERE ?1 IS NULL OR model = ?1 ORDER BY created_at DESC, id DESC",
+    )?;
+    let rows = stmt.query_map(params![model], benchmark_from_row)?;
+    rows.collect()
+}
+
+// The newest run of each model
+fn latest(conn: &Connection) -> rusqlite::Result<Vec<ModelBenchmark>> {
+    let mut seen = std::collections::HashSet::new();
+    Ok(list(conn, None)?.into_iter().filter(|benchmark| seen.insert(benchmark.model.clone())).collect())
+}
+
+/// The model to recommend from measured runs: the largest one still at an interactive speed, or
+/// the fastest when none is. None until something has been benchmarked.
+pub fn recommend(benchmarks: &[ModelBenchmark]) -> Option<MeasuredRecommendation> {
+    let interactive = benchmarks
+        .iter()
+        .filter(|benchmark| benchmark.tokens_per_second >= INTERACTIVE_TOKENS_PER_SECOND)
+        .max_by(|a, b| {
+            a.memory_bytes.unwrap_or(0).cmp(&b.memory_bytes.unwrap_or(0))
+                .then(a.tokens_per_second.total_cmp(&b.tokens_per_second))
+        });
+    let (chosen, reason) = match interactive {
+        Some(benchmark) => (benchmark, format!(
+            "Largest benchmarked model generating at least {} tokens/s on this machine",
+            INTERACTIVE_TOKENS_PER_SECOND
+        )),
+        None => (
+            benchmarks.iter().max_by(|a, b| a.tokens_per_second.total_cmp(&b.tokens_per_second))?,
+            format!("No benchmarked model reaches {} tokens/s; this is the fastest", INTERACTIVE_TOKENS_PER_SECOND),
+        ),
+    };
+    Some(MeasuredRecommendation {
+        model: chosen.model.clone(),
+        tokens_per_second: chosen.tokens_per_second,
+        time_to_first_token_ms: chosen.time_to_first_token_ms,
+        fully_on_gpu: chosen.fully_on_gpu(),
+        reason,
+    })
+}
+
+fn open_for(app_handle: &AppHandle) -> Result<Connection, String> {
+    let db_path = crate::data::paths::database_path(app_handle)?;
+    open(&db_path).map_err(|e| format!("Failed to open model benchmarks: {}", e))
+}
+
+pub fn save_benchmark(app_handle: &AppHandle, benchmark: &ModelBenchmark) -> Result<i64, String> {
+    let conn = open_for(app_handle)?;
+    insert(&conn, benchmark).map_err(|e| format!("Failed to store benchmark: {}", e))
+}
+
+/// Newest benchmark of each model.
+pub fn latest_benchmarks(app_handle: &AppHandle) -> Result<Vec<ModelBenchmark>, String> {
+    let conn = open_for(app_handle)?;
+    latest(&conn).map_err(|e| format!("Failed to read benchmarks: {}", e))
+}
+
+#[tauri::command]
+pub fn get_model_benchmarks(app_handle: AppHandle, model: Option<String>) -> Result<Vec<ModelBenchmark>, String> {
+    let conn = open_for(&app_handle)?;
+    list(&conn, model.as_deref()).map_err(|e| format!("Failed to read benchmarks: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_db::MemoryDatabase;
+
+    fn run(model: &str, tokens_per_second: f64, memory_gb: u64, created_at: i64) -> ModelBenchmark {
+        ModelBenchmark {
+            id: None,
+            model: model.to_string(),
+            gpu: Some("RTX 4070".to_string()),
+            load_ms: 1200,
+            tokens_per_second,
+            time_to_first_token_ms: 180,
+            memory_bytes: Some(memory_gb << 30),
+            vram_bytes: Some(memory_gb << 30),
+            prompts: vec![],
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_latest_runs_ground_the_recommendation() {
+        let db = MemoryDatabase::new();
+        let conn = open(db.path()).unwrap();
+        insert(&conn, &run("qwen2.5:14b", 31.0, 9, 1)).unwrap();
+        // A later run on the same model replaces the earlier measurement
+        insert(&conn, &run("qwen2.5:14b", 9.0, 9, 2)).unwrap();
+        insert(&conn, &run("gemma3:4b", 58.0, 4, 3)).unwrap();
+        insert(&conn, &run("qwen2.5:7b", 24.0, 6, 4)).unwrap();
+
+        let latest = latest(&conn).unwrap();
+        assert_eq!(latest.len(), 3);
+        let recommendation = recommend(&latest).unwrap();
+        assert_eq!(recommendation.model, "qwen2.5:7b");
+        assert!(recommendation.fully_on_gpu);
+
+        let slow = vec![run("llama3.1:70b", 3.5, 40, 5), run("qwen2.5:32b", 7.0, 20, 6)];
+        assert_eq!(recommend(&slow).unwrap().model, "qwen2.5:32b");
+        assert!(recommend(&[]).is_none());
+    }
+}
@@ -0,0 +1,254 @@
+// src-tauri/src/model_residency.rs
+// How long Ollama keeps models in memory. Generation requests carry the configured keep_alive
+// (None leaves Ollama's default), and a background policy unloads vision models that have sat
+// idle for a while: they are the largest models the app uses and their VRAM is better spent on
+// Whisper during a meeting. Use is noted whenever the app sends a model a request; a model found
+// loaded without any noted use (loaded by something else) is timed from when it was first seen.
+// What is actually resident comes from Ollama's /api/ps, polled and broadcast on the
+// `ollama-loaded-models` event so the UI can show each model's VRAM/RAM split and expiry.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const LOADED_MODELS_INTERVAL: Duration = Duration::from_secs(5);
+const LOADED_MODELS_EVENT: &str = "ollama-loaded-models";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResidencySettings {
+    pub keep_alive: Option<String>, // Sent with generation requests, e.g. "10m" or "-1"
+    pub unload_idle_vision_models: bool,
+    pub vision_idle_minutes: u64,
+}
+
+impl Default for ResidencySettings {
+    fn default() -> Self {
+        Self {
+            keep_alive: None,
+            unload_idle_vision_models: true,
+            vision_idle_minutes: 10,
+        }
+    }
+}
+
+/// One model Ollama has in memory, as /api/ps reports it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedModel {
+    pub name: String,
+    pub size_bytes: u64,
+    pub vram_bytes: u64,
+    pub ram_bytes: u64,                 // The part that didn't fit in VRAM and runs on the CPU
+    pub gpu_percent: u8,
+    pub expires_at: Option<String>,     // When Ollama unloads it unless it's used again (RFC 3339)
+    pub parameter_size: Option<String>,
+    pub quantization: Option<String>,
+    pub context_length: Option<u64>,
+}
+
+impl LoadedModel {
+    fn from_ps(entry: &serde_json::Value) -> Option<Self> {
+        let name = entry["name"].as_str().or_else(|| entry["model"].as_str())?.to_string();
+        let size_bytes = entry["size"].as_u64().unwrap_or(0);
+        let vram_bytes = entry["size_vram"].as_u64().unwrap_or(0).min(size_bytes);
+        let text = |value: &serde_json::Value| value.as_str().filter(|s| !s.is_empty()).map(str::to_string);
+        Some(Self {
+            name,
+            size_bytes,
+            vram_bytes,
+            ram_bytes: size_bytes - vram_bytes,
+            gpu_percent: if size_bytes > 0 { (vram_bytes * 100 / size_bytes) as u8 } else { 0 },
+            expires_at: text(&entry["expires_at"]),
+            parameter_size: text(&entry["details"]["parameter_size"]),
+            quantization: text(&entry["details"]["quantization_level"]),
+            context_length: entry["context_length"].as_u64(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedModelsStatus {
+    pub reachable: bool, // False when Ollama didn't answer; `models` is then empty
+    pub models: Vec<LoadedModel>,
+    pub checked_at: String,
+}
+
+lazy_static::lazy_static! {
+    static ref LAST_USE: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+static MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("model_residency.json")
+}
+
+fn load_settings() -> ResidencySettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// keep_alive for generation requests; None leaves it to Ollama.
+pub fn keep_alive() -> Option<String> {
+    load_settings().keep_alive
+}
+
+/// Note that the app just sent `model` a request.
+pub fn note_model_use(model: &str) {
+    if let Ok(mut last_use) = LAST_USE.lock() {
+        last_use.insert(model.to_string(), Instant::now());
+    }
+}
+
+/// Loaded vision models idle for at least `idle`. Loaded models never seen before start their
+/// idle clock now.
+fn idle_vision_models(loaded: &[String], last_use: &mut HashMap<String, Instant>, idle: Duration, now: Instant) -> Vec<String> {
+    loaded
+        .iter()
+        .filter(|model| crate::agent_models::is_vision_model(model))
+        .filter(|model| {
+            let used = *last_use.entry(model.to_string()).or_insert(now);
+            now.saturating_duration_since(used) >= idle
+        })
+        .cloned()
+        .collect()
+}
+
+async fn unload_idle_models(app_handle: &AppHandle, settings: &ResidencySettings) {
+    let loaded = match crate::ollama::get_loaded_models().await {
+        Ok(loaded) => loaded,
+        Err(_) => return, // Ollama isn't running; nothing is resident
+    };
+    let idle = Duration::from_secs(settings.vision_idle_minutes.max(1) * 60);
+    let to_unload = match LAST_USE.lock() {
+        Ok(mut last_use) => {
+            // Forget vision models Ollama has already let go of
+            last_use.retain(|model, _| loaded.contains(model) || !crate::agent_models::is_vision_model(model));
+            idle_vision_models(&loaded, &mut last_use, idle, Instant::now())
+        }
+        Err(_) => return,
+    };
+    for model in to_unload {
+        match crate::ollama::unload_ollama_model(model.clone()).await {
+            Ok(()) => {
+                println!("💤 Unloaded idle vision model {} after {} minutes", model, settings.vision_idle_minutes);
+                if let Ok(mut last_use) = LAST_USE.lock() {
+                    last_use.remove(&model);
+                }
+                let _ = app_handle.emit("model-unloaded", serde_json::json!({ "model": model, "reason": "idle" }));
+            }
+            Err(e) => eprintln!("Failed to unload idle model {}: {}", model, e),
+        }
+    }
+}
+
+/// What Ollama has in memory right now.
+pub async fn loaded_models_status() -> LoadedModelsStatus {
+    let (reachable, models) = match crate::ollama::running_models().await {
+        Ok(entries) => (true, entries.iter().filter_map(LoadedModel::from_ps).collect()),
+        Err(_) => (false, vec![]),
+    };
+    LoadedModelsStatus { reachable, models, checked_at: chrono::Utc::now().to_rfc3339() }
+}
+
+/// Check for idle vision models in the background for as long as the app runs, and broadcast
+/// the loaded models every few seconds.
+pub fn start_residency_monitor(app_handle: AppHandle) {
+    if MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let status_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let status = loaded_models_status().await;
+            if let Err(e) = status_handle.emit(LOADED_MODELS_EVENT, &status) {
+                eprintln!("Failed to emit loaded models: {}", e);
+            }
+            tokio::time::sleep(LOADED_MODELS_INTERVAL).await;
+        }
+    });
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let settings = load_settings();
+            if settings.unload_idle_vision_models {
+                unload_idle_models(&app_handle, &settings).await;
+            }
+        }
+    });
+}
+
+/// Models Ollama has in memory, with their VRAM/RAM split and expiry.
+#[tauri::command]
+pub async fn get_ollama_loaded_models() -> Result<LoadedModelsStatus, String> {
+    Ok(loaded_models_status().await)
+}
+
+#[tauri::command]
+pub fn get_model_residency_settings() -> Result<ResidencySettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_model_residency_settings(settings: ResidencySettings) -> Result<(), String> {
+    let settings = ResidencySettings {
+        keep_alive: settings.keep_alive.map(|keep_alive| keep_alive.trim().to_string()).filter(|keep_alive| !keep_alive.is_empty()),
+        ..settings
+    };
+    if settings.vision_idle_minutes == 0 {
+        return Err("Vision idle time must be at least 1 minute".to_string());
+    }
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("💾 Saved model residency settings (keep_alive: {:?})", settings.keep_alive);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_idle_vision_models_are_picked() {
+        let now = Instant::now();
+        let idle = Duration::from_secs(600);
+        let loaded = vec!["qwen2.5vl:3b".to_string(), "llava:7b".to_string(), "gemma3:1b-it-qat".to_string()];
+        let mut last_use = HashMap::new();
+        last_use.insert("qwen2.5vl:3b".to_string(), now);
+        last_use.insert("gemma3:1b-it-qat".to_string(), now);
+
+        // llava was never seen before, so its clock starts at the first check
+        assert_eq!(idle_vision_models(&loaded, &mut last_use, idle, now + idle), vec!["qwen2.5vl:3b".to_string()]);
+        assert_eq!(idle_vision_models(&loaded, &mut last_use, idle, now + idle * 2), vec!["qwen2.5vl:3b".to_string(), "llava:7b".to_string()]);
+    }
+
+    #[test]
+    fn test_ps_entries_report_the_vram_ram_split() {
+        let entry = serde_json::json!({
+            "name": "qwen2.5:14b",
+            "model": "qwen2.5:14b",
+            "size": 10_000_000_000u64,
+            "size_vram": 7_500_000_000u64,
+            "expires_at": "2026-10-16T14:05:00.123456789+02:00",
+            "details": { "parameter_size": "14.8B", "quantization_level": "Q4_K_M" },
+        });
+        let model = LoadedModel::from_ps(&entry).unwrap();
+        assert_eq!(model.ram_bytes, 2_500_000_000);
+        assert_eq!(model.gpu_percent, 75);
+        assert_eq!(model.quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(model.context_length, None);
+        assert!(LoadedModel::from_ps(&serde_json::json!({ "size": 1 })).is_none());
+    }
+}
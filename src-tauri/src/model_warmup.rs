@@ -0,0 +1,346 @@
+// src-tauri/src/model_warmup.rs
+// Warm-up of agent models at launch. Agents are ordered by predicted use (agent_models keeps a
+// decaying count of requests per agent), and their Ollama models are preloaded one at a time with
+// a pause in between, until the memory budget or model limit is reached. Nothing loads on battery
+// unless the user allows it. Each agent's readiness is reported so the UI can show
+// "coding agent ready".
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+// Let the window and the rest of startup settle before loading gigabytes from disk
+const STARTUP_DELAY: Duration = Duration::from_secs(10);
+const DEFAULT_MEMORY_BUDGET_MB: u64 = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmupSettings {
+    pub enabled: bool,
+    pub skip_on_battery: bool,
+    pub max_models: usize,
+    pub memory_budget_mb: Option<u64>, // None: half of physical memory when it can be read
+    pub stagger_secs: u64,             // Pause between two model loads
+    pub keep_alive: String,            // How long Ollama keeps a warmed model, e.g. "30m"
+}
+
+impl Default for WarmupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            skip_on_battery: true,
+            max_models: 2,
+            memory_budget_mb: None,
+            stagger_secs: 5,
+            keep_alive: "30m".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarmupState {
+    Pending,
+    Loading,
+    Ready,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentWarmup {
+    pub agent_type: String,
+    pub model: String,
+    pub state: WarmupState,
+    pub detail: Option<String>, // Why an agent was skipped or failed
+}
+
+lazy_static::lazy_static! {
+    static ref WARMUP_STATUS: Mutex<Vec<AgentWarmup>> = Mutex::new(Vec::new());
+}
+
+static WARMUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Decision {
+    AlreadyLoaded,
+    Load,
+    Skip(String),
+}
+
+fn matching_name<'a>(model: &str, names: impl IntoIterator<Item = &'a String>) -> Option<&'a String> {
+    names.into_iter().find(|name| *name == model || **name == format!("{}:latest", model))
+}
+
+/// Decide what to do for each (agent, model) in priority order. Models already in memory count
+/// against the budget; agents sharing a model share its decision.
+fn plan(
+    models: &[String],
+    sizes: &HashMap<String, u64>,
+    loaded: &[String],
+    budget_bytes: u64,
+    max_models: usize,
+) -> Vec<Decision> {
+    let mut used: u64 = loaded
+        .iter()
+        .filter_map(|name| sizes.get(name))
+        .sum();
+    let mut loads = 0;
+    let mut decided: HashMap<&str, Decision> = HashMap::new();
+
+    models
+        .iter()
+        .map(|model| {
+            if let Some(decision) = decided.get(model.as_str()) {
+                return decision.clone();
+            }
+            let decision = if matching_name(model, loaded).is_some() {
+                Decision::AlreadyLoaded
+            } else {
+                match matching_name(model, sizes.keys()).map(|name| sizes[name]) {
+                    None => Decision::Skip("model is not installed".to_string()),
+                    Some(_) if loads >= max_models => Decision::Skip("warm-up model limit reached".to_string()),
+                    Some(size) if used + size > budget_bytes => Decision::Skip("not enough memory".to_string()),
+                    Some(size) => {
+                        used += size;
+                        loads += 1;
+                        Decision::Load
+                    }
+                }
+            };
+            decided.insert(model, decision.clone());
+            decision
+        })
+        .collect()
+}
+
+fn get_warmup_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("model_warmup.json")
+}
+
+fn load_settings() -> WarmupSettings {
+    get_warmup_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+async fn memory_budget_bytes(settings: &WarmupSettings) -> u64 {
+    let budget_mb = match settings.memory_budget_mb {
+        Some(budget_mb) => budget_mb,
+        None => {
+            let memory_gb = tokio::task::spawn_blocking(crate::system_info::get_system_info)
+                .await
+                .ok()
+                .and_then(|info| info.ok())
+                .map_or(0.0, |info| info.memory_gb);
+            if memory_gb > 0.0 { (memory_gb * 1024.0 / 2.0) as u64 } else { DEFAULT_MEMORY_BUDGET_MB }
+        }
+    };
+    budget_mb * 1024 * 1024
+}
+
+fn on_battery(settings: &WarmupSettings) -> bool {
+    settings.skip_on_battery && crate::power::current_power_status().state.on_battery
+}
+
+// Record and announce new states for every agent running `model`
+fn update(app_handle: &AppHandle, model: &str, state: WarmupState, detail: Option<String>) {
+    let changed: Vec<AgentWarmup> = match WARMUP_STATUS.lock() {
+        Ok(mut status) => status
+            .iter_mut()
+            .filter(|agent| agent.model == model && agent.state != WarmupState::Skipped)
+            .map(|agent| {
+                agent.state = state;
+                agent.detail = detail.clone();
+                agent.clone()
+            })
+            .collect(),
+        Err(_) => return,
+    };
+    for agent in changed {
+        if let Err(e) = app_handle.emit("model-warmup-status", &agent) {
+            eprintln!("Failed to emit warm-up status: {}", e);
+        }
+    }
+}
+
+fn publish(app_handle: &AppHandle, agents: Vec<AgentWarmup>) {
+    for agent in &agents {
+        if let Err(e) = app_handle.emit("model-warmup-status", agent) {
+            eprintln!("Failed to emit warm-up status: {}", e);
+        }
+    }
+    if let Ok(mut status) = WARMUP_STATUS.lock() {
+        *status = agents;
+    }
+}
+
+fn agent_status(agent_type: &str, model: &str, state: WarmupState, detail: Option<String>) -> AgentWarmup {
+    AgentWarmup {
+        agent_type: agent_type.to_string(),
+        model: model.to_string(),
+        state,
+        detail,
+    }
+}
+
+async fn run_warmup(app_handle: AppHandle) {
+    let settings = load_settings();
+    let agent_types = crate::agent_models::predicted_agent_order(&app_handle);
+
+    let installed = match crate::ollama::get_ollama_models().await {
+        Ok(models) => models,
+        Err(e) => {
+            println!("⏭️ Skipping model warm-up: {}", e);
+            publish(&app_handle, agent_types.iter().map(|agent_type| {
+                agent_status(agent_type, "", WarmupState::Failed, Some("Ollama is not running".to_string()))
+            }).collect());
+            return;
+        }
+    };
+    let sizes: HashMap<String, u64> = installed.into_iter().map(|model| (model.name, model.size)).collect();
+    let installed_names: Vec<String> = sizes.keys().cloned().collect();
+    let loaded = crate::ollama::get_loaded_models().await.unwrap_or_default();
+
+    // Agents served by another provider have nothing to warm locally
+    let mut agents = Vec::new();
+    let mut remote = Vec::new();
+    for agent_type in agent_types {
+        let model = crate::agent_models::planned_agent_model(&app_handle, agent_type, Some(&installed_names))
+            .unwrap_or_default();
+        match crate::llm_provider::provider_for_agent(agent_type) {
+            Some(provider) => remote.push(agent_status(
+                agent_type, &model, WarmupState::Skipped, Some(format!("served by {}", provider.name())),
+            )),
+            None => agents.push((agent_type, model)),
+        }
+    }
+
+    let models: Vec<String> = agents.iter().map(|(_, model)| model.clone()).collect();
+    let decisions = plan(&models, &sizes, &loaded, memory_budget_bytes(&settings).await, settings.max_models);
+    let battery = on_battery(&settings);
+    let mut statuses: Vec<AgentWarmup> = agents
+        .iter()
+        .zip(&decisions)
+        .map(|((agent_type, model), decision)| match decision {
+            Decision::AlreadyLoaded => agent_status(agent_type, model, WarmupState::Ready, None),
+            Decision::Load if battery => agent_status(agent_type, model, WarmupState::Skipped, Some("on battery".to_string())),
+            Decision::Load => agent_status(agent_type, model, WarmupState::Pending, None),
+            Decision::Skip(reason) => agent_status(agent_type, model, WarmupState::Skipped, Some(reason.clone())),
+        })
+        .collect();
+    statuses.extend(remote);
+    publish(&app_handle, statuses);
+
+    let mut to_load: Vec<&String> = Vec::new();
+    for (model, decision) in models.iter().zip(&decisions) {
+        if *decision == Decision::Load && !battery && !to_load.contains(&model) {
+            to_load.push(model);
+        }
+    }
+    println!("🔥 Warming up {} model(s): {:?}", to_load.len(), to_load);
+
+    for (index, model) in to_load.into_iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(Duration::from_secs(settings.stagger_secs)).await;
+        }
+        // Unplugging during warm-up stops the remaining loads
+        if on_battery(&settings) {
+            update(&app_handle, model, WarmupState::Skipped, Some("on battery".to_string()));
+            continue;
+        }
+        update(&app_handle, model, WarmupState::Loading, None);
+        match crate::ollama::preload_ollama_model(model.clone(), Some(settings.keep_alive.clone())).await {
+            Ok(()) => update(&app_handle, model, WarmupState::Ready, None),
+            Err(e) => {
+                eprintln!("Failed to warm up {}: {}", model, e);
+                update(&app_handle, model, WarmupState::Failed, Some(e));
+            }
+        }
+    }
+}
+
+fn spawn_warmup(app_handle: AppHandle, delay: Duration) -> bool {
+    if WARMUP_RUNNING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        run_warmup(app_handle).await;
+        WARMUP_RUNNING.store(false, Ordering::SeqCst);
+    });
+    true
+}
+
+/// Warm up agent models in the background shortly after launch, if enabled.
+pub fn start_model_warmup(app_handle: AppHandle) {
+    if !load_settings().enabled {
+        return;
+    }
+    spawn_warmup(app_handle, STARTUP_DELAY);
+}
+
+/// Run the warm-up now, e.g. after changing agent models. Returns false if one is already running.
+#[tauri::command]
+pub fn warm_up_models(app_handle: AppHandle) -> Result<bool, String> {
+    Ok(spawn_warmup(app_handle, Duration::ZERO))
+}
+
+#[tauri::command]
+pub fn get_model_warmup_status() -> Result<Vec<AgentWarmup>, String> {
+    WARMUP_STATUS.lock().map(|status| status.clone()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_warmup_settings() -> Result<WarmupSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_warmup_settings(settings: WarmupSettings) -> Result<(), String> {
+    if settings.keep_alive.trim().is_empty() {
+        return Err("Keep-alive duration is empty".to_string());
+    }
+    let path = get_warmup_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize warm-up settings: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save warm-up settings: {}", e))?;
+
+    println!("💾 Model warm-up settings saved to: {:?}", path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_respects_memory_limit_and_shared_models() {
+        const GB: u64 = 1024 * 1024 * 1024;
+        let sizes: HashMap<String, u64> = [
+            ("gemma3:1b-it-qat".to_string(), GB),
+            ("qwen2.5-coder:1.5b".to_string(), GB),
+            ("deepseek-r1:1.5b".to_string(), 2 * GB),
+            ("qwen2.5vl:3b".to_string(), 3 * GB),
+        ].into_iter().collect();
+        let models: Vec<String> = ["gemma3:1b-it-qat", "gemma3:1b-it-qat", "deepseek-r1:1.5b", "qwen2.5vl:3b", "qwen2.5-coder:1.5b", "mistral"]
+            .iter()
+            .map(|model| model.to_string())
+            .collect();
+
+        let decisions = plan(&models, &sizes, &["qwen2.5-coder:1.5b".to_string()], 3 * GB, 3);
+
+        assert_eq!(decisions[0], Decision::Load);
+        assert_eq!(decisions[1], Decision::Load);
+        assert_eq!(decisions[2], Decision::Skip("not enough memory".to_string()));
+        assert_eq!(decisions[3], Decision::Skip("not enough memory".to_string()));
+        assert_eq!(decisions[4], Decision::AlreadyLoaded);
+        assert_eq!(decisions[5], Decision::Skip("model is not installed".to_string()));
+    }
+}
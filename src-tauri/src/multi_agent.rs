@@ -0,0 +1,124 @@
+// src-tauri/src/multi_agent.rs
+// Compare mode: the same prompt fanned out to several agents/models at once. Each run streams on
+// its own `ollama-stream-{session_id}-{index}` channel and the results are stored as sibling variants.
+use crate::generation_options::GenerationOptions;
+use crate::ollama::ChatContextMessage;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+// More than this gets queued behind the request semaphore anyway and just delays every column
+const MAX_COMPARE_AGENTS: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentTarget {
+    pub agent_type: String,
+    pub model: Option<String>, // Overrides the agent's default model
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentRunResult {
+    pub agent_type: String,
+    pub model: String,
+    pub channel: String,
+    pub text: Option<String>,
+    pub error: Option<String>,
+    pub variant_id: Option<String>,
+}
+
+#[tauri::command]
+pub async fn generate_multi_agent(
+    app_handle: AppHandle,
+    prompt: String,
+    agents: Vec<AgentTarget>,
+    context: Option<Vec<ChatContextMessage>>,
+    session_id: String,
+    chat_id: Option<String>,
+    message_id: Option<i32>,
+    options: Option<GenerationOptions>,
+) -> Result<Vec<AgentRunResult>, String> {
+    if agents.len() < 2 {
+        return Err("Compare mode needs at least two agents".to_string());
+    }
+    if agents.len() > MAX_COMPARE_AGENTS {
+        return Err(format!("Compare mode supports up to {} agents", MAX_COMPARE_AGENTS));
+    }
+
+    // Resolve every target before starting so a typo doesn't leave half the columns running
+    let mut runs = Vec::new();
+    for (index, target) in agents.iter().enumerate() {
+        if target.agent_type == "vision" {
+            return Err("The vision agent needs a screenshot and can't be used in compare mode".to_string());
+        }
+        let (_, system_prompt) = crate::ollama::agent_model_and_prompt(&target.agent_type)
+            .ok_or_else(|| format!("Unknown agent type: {}", target.agent_type))?;
+        let model = match target.model.clone() {
+            Some(model) => model,
+            None => crate::agent_models::resolve_agent_model(&app_handle, &target.agent_type).await?,
+        };
+        let channel = format!("{}-{}", session_id, index);
+        runs.push((target.agent_type.clone(), model, system_prompt, channel));
+    }
+
+    if let Err(e) = app_handle.emit("multi-agent-start", serde_json::json!({
+        "sessionId": session_id,
+        "runs": runs.iter().map(|(agent_type, model, _, channel)| serde_json::json!({
+            "agentType": agent_type,
+            "model": model,
+            "channel": channel,
+        })).collect::<Vec<_>>(),
+    })) {
+        eprintln!("Failed to emit multi-agent start: {}", e);
+    }
+
+    // One shared prompt, so it has to fit the smallest window among the agents
+    let window_limit = runs.iter()
+        .map(|(agent_type, ..)| crate::generation_options::prompt_token_limit(agent_type, options.as_ref()))
+        .min();
+    let longest_system_prompt = runs.iter().map(|(_, _, system_prompt, _)| *system_prompt).max_by_key(|prompt| prompt.len()).unwrap_or("");
+    let context = crate::context_window::fit_context(&app_handle, longest_system_prompt, &prompt, context, window_limit).await;
+    let full_prompt = crate::ollama::build_prompt_with_context(prompt, context);
+    println!("🧑‍🤝‍🧑 Comparing {} agents for session: {}", runs.len(), session_id);
+
+    // Runs share the request semaphore, so at most its permit count stream at the same time
+    let outputs = join_all(runs.iter().map(|(agent_type, model, system_prompt, channel)| {
+        crate::ollama::generate_agent_response_stream(
+            app_handle.clone(),
+            model.clone(),
+            full_prompt.clone(),
+            system_prompt.to_string(),
+            None,
+            channel.clone(),
+            agent_type.clone(),
+            options.clone(),
+        )
+    })).await;
+
+    let target = chat_id.zip(message_id);
+    let results: Vec<AgentRunResult> = runs
+        .into_iter()
+        .zip(outputs)
+        .map(|((agent_type, model, _, channel), output)| {
+            let (text, error) = match output {
+                Ok(text) => (Some(text), None),
+                Err(e) => (None, Some(e)),
+            };
+            let variant_id = match (&target, &text) {
+                (Some((chat_id, message_id)), Some(text)) if !text.trim().is_empty() => {
+                    crate::draft_refine::store_variant(&app_handle, chat_id, *message_id, "compare", text, &model, &agent_type, false)
+                }
+                _ => None,
+            };
+            AgentRunResult { agent_type, model, channel, text, error, variant_id }
+        })
+        .collect();
+
+    if let Err(e) = app_handle.emit("multi-agent-complete", serde_json::json!({
+        "sessionId": session_id,
+        "results": &results,
+    })) {
+        eprintln!("Failed to emit multi-agent completion: {}", e);
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,274 @@
+// src-tauri/src/notifications.rs
+// Native OS notifications for results that finish in the background (a conversation summary, an
+// executed plan, an imported file's transcription). Clicking a notification or one of its buttons
+// brings the main window forward and emits `notification-activated` with the notification's deep
+// link, e.g. `enteract://conversation/<id>`, so the UI can open the right session.
+// While the user is in a meeting, notifications are held and delivered once it ends.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Held notifications beyond this are dropped oldest-first
+const MAX_HELD: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub do_not_disturb_in_meetings: bool,
+    pub only_when_unfocused: bool, // Skip notifications while Enteract itself is focused
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            do_not_disturb_in_meetings: true,
+            only_when_unfocused: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationAction {
+    pub id: String,
+    pub label: String,
+    pub deep_link: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackgroundNotification {
+    pub kind: String, // "summary" | "plan" | "transcription" | "digest"
+    pub title: String,
+    pub body: String,
+    pub deep_link: Option<String>, // Opened when the notification itself is clicked
+    #[serde(default)]
+    pub actions: Vec<NotificationAction>,
+}
+
+impl BackgroundNotification {
+    pub fn new(kind: &str, title: impl Into<String>, body: impl Into<String>, deep_link: Option<String>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            title: title.into(),
+            body: body.into(),
+            deep_link,
+            actions: Vec::new(),
+        }
+    }
+
+    pub fn with_action(mut self, id: &str, label: &str, deep_link: String) -> Self {
+        self.actions.push(NotificationAction {
+            id: id.to_string(),
+            label: label.to_string(),
+            deep_link,
+        });
+        self
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref HELD_NOTIFICATIONS: Mutex<Vec<BackgroundNotification>> = Mutex::new(Vec::new());
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("notification_settings.json")
+}
+
+fn load_settings() -> NotificationSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn main_window_focused(app_handle: &AppHandle) -> bool {
+    app_handle
+        .get_webview_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// Called from the notification's click or button handler, on whatever thread the OS uses.
+fn activate(app_handle: &AppHandle, deep_link: Option<String>, action_id: Option<String>) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    if let Err(e) = app_handle.emit("notification-activated", serde_json::json!({
+        "deepLink": deep_link,
+        "action": action_id,
+    })) {
+        eprintln!("Failed to emit notification activation: {}", e);
+    }
+}
+
+/// Raise a notification for a background result, unless settings or a meeting say otherwise.
+pub fn notify(app_handle: &AppHandle, mut notification: BackgroundNotification) {
+    let settings = load_settings();
+    if !settings.enabled || (settings.only_when_unfocused && main_window_focused(app_handle)) {
+        return;
+    }
+    if settings.do_not_disturb_in_meetings && crate::presence::is_in_meeting() {
+        if let Ok(mut held) = HELD_NOTIFICATIONS.lock() {
+            held.push(notification);
+            let overflow = held.len().saturating_sub(MAX_HELD);
+            held.drain(..overflow);
+        }
+        return;
+    }
+    // Whoever is watching the shared screen shouldn't see transcript or chat text
+    if crate::screen_share::is_screen_share_active() {
+        notification.body = "Open Enteract to see the result.".to_string();
+    }
+
+    let app_handle = app_handle.clone();
+    // Showing a notification and waiting for its buttons blocks on some platforms
+    std::thread::spawn(move || {
+        if let Err(e) = platform::show(&app_handle, &notification) {
+            eprintln!("Failed to show notification: {}", e);
+        }
+    });
+}
+
+/// Deliver notifications held back during a meeting. The presence monitor calls this once the meeting is over.
+pub fn release_held(app_handle: &AppHandle) {
+    let held: Vec<BackgroundNotification> = match HELD_NOTIFICATIONS.lock() {
+        Ok(mut held) if !held.is_empty() => held.drain(..).collect(),
+        _ => return,
+    };
+    println!("🔔 Delivering {} notifications held during the meeting", held.len());
+    if held.len() == 1 {
+        notify(app_handle, held.into_iter().next().unwrap());
+        return;
+    }
+    let titles: Vec<String> = held.iter().map(|n| n.title.clone()).collect();
+    notify(
+        app_handle,
+        BackgroundNotification::new("held", format!("{} results finished during your meeting", held.len()), titles.join("\n"), None),
+    );
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use tauri_winrt_notification::Toast;
+
+    pub fn show(app_handle: &AppHandle, notification: &BackgroundNotification) -> Result<(), String> {
+        let mut toast = Toast::new(&app_handle.config().identifier)
+            .title(&notification.title)
+            .text1(&notification.body);
+        for action in &notification.actions {
+            // Toast buttons hand back their argument; the action id is enough to find the link again
+            toast = toast.add_button(&action.label, &action.id);
+        }
+
+        let handle = app_handle.clone();
+        let deep_link = notification.deep_link.clone();
+        let actions = notification.actions.clone();
+        toast
+            .on_activated(move |argument| {
+                let action = argument.and_then(|id| actions.iter().find(|a| a.id == id).cloned());
+                match action {
+                    Some(action) => activate(&handle, Some(action.deep_link), Some(action.id)),
+                    None => activate(&handle, deep_link.clone(), None),
+                }
+                Ok(())
+            })
+            .show()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use mac_notification_sys::{send_notification, set_application, MainButton, Notification, NotificationResponse};
+
+    pub fn show(app_handle: &AppHandle, notification: &BackgroundNotification) -> Result<(), String> {
+        let _ = set_application(&app_handle.config().identifier);
+        let labels: Vec<&str> = notification.actions.iter().map(|a| a.label.as_str()).collect();
+        let mut options = Notification::new();
+        options.wait_for_click(true);
+        match labels.as_slice() {
+            [] => {}
+            [label] => {
+                options.main_button(MainButton::SingleAction(label));
+            }
+            [first, rest @ ..] => {
+                options.main_button(MainButton::DropdownActions(first, rest));
+            }
+        }
+
+        // Blocks until the notification is clicked or dismissed
+        let response = send_notification(&notification.title, None, &notification.body, Some(&options))
+            .map_err(|e| e.to_string())?;
+        match response {
+            NotificationResponse::ActionButton(label) => {
+                if let Some(action) = notification.actions.iter().find(|a| a.label == label) {
+                    activate(app_handle, Some(action.deep_link.clone()), Some(action.id.clone()));
+                }
+            }
+            NotificationResponse::Click => activate(app_handle, notification.deep_link.clone(), None),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::*;
+
+    pub fn show(app_handle: &AppHandle, notification: &BackgroundNotification) -> Result<(), String> {
+        let mut builder = notify_rust::Notification::new();
+        builder
+            .appname("Enteract")
+            .summary(&notification.title)
+            .body(&notification.body)
+            .action("default", "Open");
+        for action in &notification.actions {
+            builder.action(&action.id, &action.label);
+        }
+
+        // Blocks until the notification is clicked, a button is pressed or it closes
+        let handle = builder.show().map_err(|e| e.to_string())?;
+        handle.wait_for_action(|action_id| match action_id {
+            "default" => activate(app_handle, notification.deep_link.clone(), None),
+            "__closed" => {}
+            id => {
+                if let Some(action) = notification.actions.iter().find(|a| a.id == id) {
+                    activate(app_handle, Some(action.deep_link.clone()), Some(action.id.clone()));
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_notification_settings() -> Result<NotificationSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn save_notification_settings(settings: NotificationSettings) -> Result<(), String> {
+    let settings_path = get_settings_path()
+        .map_err(|e| format!("Failed to get notification settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize notification settings: {}", e))?;
+    fs::write(&settings_path, json)
+        .map_err(|e| format!("Failed to save notification settings: {}", e))?;
+    Ok(())
+}
+
+/// Raise a notification from the frontend, e.g. for results it tracks itself.
+#[tauri::command]
+pub async fn show_background_notification(app_handle: AppHandle, notification: BackgroundNotification) -> Result<(), String> {
+    notify(&app_handle, notification);
+    Ok(())
+}
@@ -2,13 +2,12 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use reqwest;
 use std::collections::HashMap;
-use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 use futures_util::StreamExt;
 use lazy_static::lazy_static;
-use tokio::sync::Semaphore;
 use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
 use std::sync::Mutex;
 use crate::system_prompts::{
     ENTERACT_AGENT_PROMPT, 
@@ -18,25 +17,34 @@ use crate::system_prompts::{
     CODING_AGENT_PROMPT
 };
 use crate::system_info::get_gpu_info;
+use crate::generation_options::{GenerationOptions, estimate_tokens};
+use crate::ollama_retry::send_with_retry;
+use crate::ollama_http::{client, generation_permits, EndpointClass};
+use crate::model_benchmarks::{latest_benchmarks, recommend, save_benchmark, ModelBenchmark, PromptBenchmark};
+use crate::response_cache::CacheRequest;
 use regex;
 
-// Shared HTTP client for better connection pooling and memory efficiency
+// HTTP clients (per endpoint class) and the generation concurrency limit live in ollama_http
 lazy_static! {
-    static ref HTTP_CLIENT: Arc<reqwest::Client> = Arc::new(
-        reqwest::Client::builder()
-            .pool_max_idle_per_host(16)  // More idle connections for faster reuse
-            .pool_idle_timeout(Duration::from_secs(60))
-            .tcp_keepalive(Some(Duration::from_secs(60)))
-            .timeout(Duration::from_secs(60))  // Shorter timeout to fail fast
-            .build()
-            .expect("Failed to create HTTP client")
-    );
+    // Active streaming sessions; cancelling a session's token stops its stream
+    static ref ACTIVE_SESSIONS: Mutex<HashMap<String, CancellationToken>> = Mutex::new(HashMap::new());
     
-    // Semaphore to limit concurrent AI model requests (memory safety)
-    static ref REQUEST_SEMAPHORE: Arc<Semaphore> = Arc::new(Semaphore::new(4)); // Slightly higher concurrency
+    // Set by the test harness's mock server
+    static ref BASE_URL_OVERRIDE: Mutex<Option<String>> = Mutex::new(None);
     
-    // Track active streaming sessions for cancellation
-    static ref ACTIVE_SESSIONS: Mutex<HashMap<String, bool>> = Mutex::new(HashMap::new());
+    // When each conversation last got an insight, for the profile's insight interval
+    static ref LAST_INSIGHT_AT: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+// Claim the next insight for a conversation unless the profile's interval hasn't passed yet
+fn claim_insight_slot(key: &str, interval: Duration) -> bool {
+    let mut last = LAST_INSIGHT_AT.lock().unwrap();
+    if last.get(key).is_some_and(|at| at.elapsed() < interval) {
+        return false;
+    }
+    last.retain(|_, at| at.elapsed() < Duration::from_secs(3600));
+    last.insert(key.to_string(), Instant::now());
+    true
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -91,6 +99,8 @@ pub struct GenerateRequest {
     pub images: Option<Vec<String>>,
     pub system: Option<String>,
     pub options: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>, // How long Ollama keeps the model loaded afterwards
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -108,7 +118,135 @@ pub struct GenerateResponse {
     pub eval_duration: Option<u64>,
 }
 
-const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+/// One turn of an /api/chat request. Ollama's chat roles are system, user, assistant and tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<String>>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+            images: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub stream: Option<bool>,
+    pub options: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+}
+
+/// A streamed line from either endpoint: /api/generate sends `response`, /api/chat sends `message`.
+#[derive(Debug, Deserialize)]
+struct StreamLine {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    message: Option<ChatMessage>,
+    #[serde(default)]
+    done: bool,
+    // Only the final line carries the counts
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    eval_count: Option<u32>,
+}
+
+impl StreamLine {
+    fn text(&self) -> &str {
+        match &self.message {
+            Some(message) => &message.content,
+            None => &self.response,
+        }
+    }
+
+    fn usage(&self) -> Option<TokenUsage> {
+        TokenUsage::from_counts(self.prompt_eval_count, self.eval_count)
+    }
+}
+
+/// Tokens Ollama reports for a finished response, sent with the "complete" event.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl TokenUsage {
+    pub(crate) fn from_counts(prompt_eval_count: Option<u32>, eval_count: Option<u32>) -> Option<Self> {
+        if prompt_eval_count.is_none() && eval_count.is_none() {
+            return None;
+        }
+        let prompt_tokens = prompt_eval_count.unwrap_or(0);
+        let completion_tokens = eval_count.unwrap_or(0);
+        Some(Self { prompt_tokens, completion_tokens, total_tokens: prompt_tokens + completion_tokens })
+    }
+}
+
+const CHAT_ROLES: &[&str] = &["system", "user", "assistant", "tool"];
+
+const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Where Ollama is served. ENTERACT_OLLAMA_URL wins over the server saved in the Ollama HTTP
+/// settings, which wins over localhost.
+pub(crate) fn ollama_base_url() -> String {
+    if let Some(url) = BASE_URL_OVERRIDE.lock().ok().and_then(|url| url.clone()) {
+        return url;
+    }
+    env_base_url()
+        .or_else(crate::ollama_http::configured_base_url)
+        .unwrap_or_else(|| DEFAULT_OLLAMA_URL.to_string())
+}
+
+/// The server named by ENTERACT_OLLAMA_URL, if it's set.
+pub(crate) fn env_base_url() -> Option<String> {
+    std::env::var("ENTERACT_OLLAMA_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| url.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+pub(crate) fn set_base_url_override(url: Option<String>) {
+    if let Ok(mut current) = BASE_URL_OVERRIDE.lock() {
+        *current = url;
+    }
+}
+
+/// Splits a streamed response body into lines. A JSON line can arrive split across network chunks.
+#[derive(Default)]
+pub(crate) struct NdjsonLines {
+    buffer: Vec<u8>,
+}
+
+impl NdjsonLines {
+    /// Complete, non-empty lines now available; the rest waits for more bytes.
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut lines = Vec::new();
+        while let Some(newline_pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line = self.buffer.drain(..=newline_pos).collect::<Vec<u8>>();
+            let line_str = String::from_utf8_lossy(&line[..line.len() - 1]).into_owned();
+            if !line_str.trim().is_empty() {
+                lines.push(line_str);
+            }
+        }
+        lines
+    }
+}
 
 // Stream state tracking for timeouts and pattern detection
 #[derive(Debug)]
@@ -120,6 +258,7 @@ struct StreamState {
     repeat_count: usize,
     consecutive_empty_count: usize, // Changed: track consecutive empty chunks
     total_empty_count: usize,       // Added: track total for debugging
+    text: String,                   // Everything streamed so far, for callers that keep the response
 }
 
 #[derive(Debug)]
@@ -139,6 +278,7 @@ impl StreamState {
             repeat_count: 0,
             consecutive_empty_count: 0,
             total_empty_count: 0,
+            text: String::new(),
         }
     }
 
@@ -250,7 +390,7 @@ impl Default for StreamConfig {
 }
 
 // Helper function to build prompt with chat context
-fn build_prompt_with_context(current_prompt: String, context: Option<Vec<ChatContextMessage>>) -> String {
+pub(crate) fn build_prompt_with_context(current_prompt: String, context: Option<Vec<ChatContextMessage>>) -> String {
     match context {
         Some(messages) if !messages.is_empty() => {
             let mut full_prompt = String::new();
@@ -278,6 +418,25 @@ fn build_prompt_with_context(current_prompt: String, context: Option<Vec<ChatCon
     }
 }
 
+// Role-structured history for /api/chat: the system prompt, the earlier turns, then the request
+pub(crate) fn build_chat_messages(system_prompt: &str, current_prompt: String, context: Option<Vec<ChatContextMessage>>) -> Vec<ChatMessage> {
+    let mut messages = vec![ChatMessage::new("system", system_prompt)];
+    for message in context.unwrap_or_default() {
+        if message.content.trim().is_empty() {
+            continue;
+        }
+        if CHAT_ROLES.contains(&message.role.as_str()) {
+            messages.push(ChatMessage::new(&message.role, message.content));
+        } else {
+            // Ollama rejects unknown roles, so keep the label and send it as a user turn
+            messages.push(ChatMessage::new("user", format!("{}: {}", message.role, message.content)));
+        }
+    }
+    messages.push(ChatMessage::new("user", current_prompt));
+    println!("📊 Built {} chat messages", messages.len());
+    messages
+}
+
 // Detect GPU and determine optimal layer count for GPU acceleration
 fn detect_gpu_layers() -> i32 {
     // Try to get GPU info
@@ -346,13 +505,20 @@ fn detect_gpu_layers() -> i32 {
     }
 }
 
-// Get GPU acceleration status
+// Get GPU acceleration status. Once models have been benchmarked, the recommendation comes from
+// their measured speed and memory use on this machine
 #[tauri::command]
-pub fn get_gpu_acceleration_status() -> serde_json::Value {
+pub fn get_gpu_acceleration_status(app_handle: AppHandle) -> serde_json::Value {
     let gpu_layers = detect_gpu_layers();
     let gpus = get_gpu_info().unwrap_or_else(|_| vec![]);
+    let benchmarks = latest_benchmarks(&app_handle).unwrap_or_else(|e| {
+        println!("⚠️ Could not read model benchmarks: {}", e);
+        vec![]
+    });
     
     serde_json::json!({
+        "recommendation": recommend(&benchmarks),
+        "benchmarks": benchmarks,
         "enabled": gpu_layers > 0,
         "layers": gpu_layers,
         "gpus": gpus.iter().map(|gpu| {
@@ -366,48 +532,93 @@ pub fn get_gpu_acceleration_status() -> serde_json::Value {
     })
 }
 
-// Cancel a streaming session
+/// Abort a session's in-flight stream. The stream ends with a "cancelled" event on its
+/// ollama-stream-{session} channel; returns false when the session has no stream running.
 #[tauri::command]
-pub fn cancel_ai_response(session_id: String) -> Result<(), String> {
-    let mut sessions = ACTIVE_SESSIONS.lock().unwrap();
-    sessions.insert(session_id.clone(), true);
+pub fn cancel_ollama_stream(session_id: String) -> Result<bool, String> {
+    let sessions = ACTIVE_SESSIONS.lock().unwrap();
+    let Some(token) = sessions.get(&session_id) else {
+        return Ok(false);
+    };
+    token.cancel();
     println!("🛑 Cancellation requested for session: {}", session_id);
-    Ok(())
+    Ok(true)
 }
 
-// Check if a session is cancelled
-fn is_session_cancelled(session_id: &str) -> bool {
+// Older name for cancel_ollama_stream
+#[tauri::command]
+pub fn cancel_ai_response(session_id: String) -> Result<(), String> {
+    cancel_ollama_stream(session_id).map(|_| ())
+}
+
+/// Cancel every stream in flight, e.g. at shutdown. Returns how many there were.
+pub(crate) fn cancel_all_sessions() -> usize {
     let sessions = ACTIVE_SESSIONS.lock().unwrap();
-    sessions.get(session_id).copied().unwrap_or(false)
+    sessions.values().for_each(CancellationToken::cancel);
+    sessions.len()
+}
+
+pub(crate) fn active_session_count() -> usize {
+    ACTIVE_SESSIONS.lock().map(|sessions| sessions.len()).unwrap_or(0)
+}
+
+// Register a stream as active and hand back the token that cancels it
+pub(crate) fn register_session(session_id: &str) -> CancellationToken {
+    let token = CancellationToken::new();
+    ACTIVE_SESSIONS.lock().unwrap().insert(session_id.to_string(), token.clone());
+    token
 }
 
 // Clean up cancelled session
-fn cleanup_session(session_id: &str) {
+pub(crate) fn cleanup_session(session_id: &str) {
     let mut sessions = ACTIVE_SESSIONS.lock().unwrap();
     sessions.remove(session_id);
 }
 
 // Enhanced streaming logic with timeout and pattern detection
-async fn stream_ollama_response_enhanced(
+async fn stream_ollama_response_enhanced<R: Serialize>(
     app_handle: AppHandle,
     url: String,
-    request: GenerateRequest,
+    request: R,
     session_id: String,
     config: StreamConfig,
 ) -> Result<(), String> {
-    // Register the session as active
-    {
-        let mut sessions = ACTIVE_SESSIONS.lock().unwrap();
-        sessions.insert(session_id.clone(), false);
-    }
+    stream_ollama_response_collecting(app_handle, url, request, session_id, config).await.map(|_| ())
+}
 
-    let client = Arc::clone(&HTTP_CLIENT);
+// Same as stream_ollama_response_enhanced, but returns the text that was streamed. Works for both
+// /api/generate and /api/chat requests.
+async fn stream_ollama_response_collecting<R: Serialize>(
+    app_handle: AppHandle,
+    url: String,
+    request: R,
+    session_id: String,
+    config: StreamConfig,
+) -> Result<String, String> {
+    let cancel_token = register_session(&session_id);
+
+    let client = client(EndpointClass::Generation);
     
-    // Make request with timeout
-    let response = timeout(Duration::from_secs(30), client.post(&url).json(&request).send())
-        .await
-        .map_err(|_| "Request timeout".to_string())?
-        .map_err(|e| format!("Request failed: {}", e))?;
+    // Make request with timeout; a cancel while waiting for the model to load drops the request
+    let response = tokio::select! {
+        _ = cancel_token.cancelled() => {
+            emit_cancelled(&app_handle, &session_id).await;
+            cleanup_session(&session_id);
+            return Ok(String::new());
+        }
+        response = timeout(Duration::from_secs(30), send_with_retry(EndpointClass::Generation, client.post(&url).json(&request))) => response,
+    };
+    let response = match response {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            cleanup_session(&session_id);
+            return Err(e);
+        }
+        Err(_) => {
+            cleanup_session(&session_id);
+            return Err("Request timeout".to_string());
+        }
+    };
 
     if !response.status().is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
@@ -419,37 +630,30 @@ async fn stream_ollama_response_enhanced(
     }
 
     let mut stream = response.bytes_stream();
-    let mut buffer = Vec::new();
+    let mut lines = NdjsonLines::default();
     let mut state = StreamState::new();
 
     // Emit a tiny nudge to UI so it can render quickly even before first chunk
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    crate::event_throttle::emit_throttled(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "chunk",
         "text": "",
         "done": false
-    })) {
-        eprintln!("Failed to emit priming chunk: {}", e);
-    }
+    }));
 
     loop {
-        // Check for cancellation first
-        if is_session_cancelled(&session_id) {
+        // Check for cancellation first; returning drops the body stream, which closes the connection
+        if cancel_token.is_cancelled() {
             println!("🛑 Session cancelled: {}", session_id);
-            if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
-                "type": "cancelled",
-                "message": "Response cancelled by user"
-            })) {
-                eprintln!("Failed to emit cancellation event: {}", e);
-            }
+            emit_cancelled(&app_handle, &session_id).await;
             cleanup_session(&session_id);
-            return Ok(());
+            return Ok(state.text);
         }
 
         // Check timeouts
         if let Some(timeout_reason) = state.should_timeout(config.max_total_duration, config.max_chunk_gap) {
             println!("⏰ Stream timeout: {}", timeout_reason);
             emit_timeout(&app_handle, &session_id, &timeout_reason).await;
-            emit_complete(&app_handle, &session_id).await;
+            emit_complete(&app_handle, &session_id, None).await;
             cleanup_session(&session_id);
             return Err(timeout_reason);
         }
@@ -458,28 +662,31 @@ async fn stream_ollama_response_enhanced(
         if let Some(pattern_reason) = state.should_terminate_patterns(config.max_consecutive_repeats, config.max_consecutive_empty_chunks) {
             println!("🔁 Pattern termination: {}", pattern_reason);
             emit_error(&app_handle, &session_id, &pattern_reason).await;
-            emit_complete(&app_handle, &session_id).await;
+            emit_complete(&app_handle, &session_id, None).await;
             cleanup_session(&session_id);
             return Err(pattern_reason);
         }
 
-        // Read next chunk with timeout
-        let chunk_result = timeout(config.chunk_timeout, stream.next()).await;
+        // Read next chunk with timeout, waking straight away if the session is cancelled
+        let chunk_result = tokio::select! {
+            _ = cancel_token.cancelled() => continue,
+            chunk_result = timeout(config.chunk_timeout, stream.next()) => chunk_result,
+        };
         
         let chunk_result = match chunk_result {
             Ok(Some(chunk_result)) => chunk_result,
             Ok(None) => {
                 // Stream ended naturally
                 println!("✅ Stream completed naturally for session: {}", session_id);
-                emit_complete(&app_handle, &session_id).await;
+                emit_complete(&app_handle, &session_id, None).await;
                 cleanup_session(&session_id);
-                return Ok(());
+                return Ok(state.text);
             }
             Err(_) => {
                 let error_msg = format!("Chunk read timeout after {:?}", config.chunk_timeout);
                 println!("⏰ {}", error_msg);
                 emit_timeout(&app_handle, &session_id, &error_msg).await;
-                emit_complete(&app_handle, &session_id).await;
+                emit_complete(&app_handle, &session_id, None).await;
                 cleanup_session(&session_id);
                 return Err(error_msg);
             }
@@ -487,21 +694,12 @@ async fn stream_ollama_response_enhanced(
 
         match chunk_result {
             Ok(chunk) => {
-                buffer.extend_from_slice(&chunk);
-
-                // Process complete lines from buffer
-                while let Some(newline_pos) = buffer.iter().position(|&b| b == b'\n') {
-                    let line = buffer.drain(..=newline_pos).collect::<Vec<u8>>();
-                    let line_str = String::from_utf8_lossy(&line[..line.len()-1]);
-
-                    if line_str.trim().is_empty() {
-                        continue;
-                    }
-
-                    match serde_json::from_str::<GenerateResponse>(&line_str) {
+                for line_str in lines.push(&chunk) {
+                    match serde_json::from_str::<StreamLine>(&line_str) {
                         Ok(response_chunk) => {
+                            let chunk_text = response_chunk.text();
                             // Check patterns and update state
-                            match state.update_chunk(&response_chunk.response) {
+                            match state.update_chunk(chunk_text) {
                                 ChunkResult::Continue => { 
                                     // Process chunk normally
                                 }
@@ -510,36 +708,36 @@ async fn stream_ollama_response_enhanced(
                                 emit_termination(&app_handle, &session_id, &reason, state.chunk_count, state.repeat_count).await;
                                 
                                 // 2. Send completion event to reset UI state  
-                                emit_complete(&app_handle, &session_id).await;
+                                emit_complete(&app_handle, &session_id, None).await;
                                 
                                 // 3. Clean up session
                                 cleanup_session(&session_id);
                                 
-                                return Ok(());
+                                return Ok(state.text);
                                 }
                             }
 
                             // Skip empty chunks to reduce UI overhead but still emit important ones
-                            if response_chunk.response.is_empty() && !response_chunk.done {
+                            if chunk_text.is_empty() && !response_chunk.done {
                                 continue;
                             }
 
-                            if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+                            state.text.push_str(chunk_text);
+                            crate::event_throttle::emit_throttled(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
                                 "type": "chunk",
-                                "text": response_chunk.response,
+                                "text": chunk_text,
                                 "done": response_chunk.done,
                                 "chunk_count": state.chunk_count,
                                 "repeat_count": state.repeat_count
-                            })) {
-                                eprintln!("Failed to emit chunk event: {}", e);
-                            }
+                            }));
 
                             if response_chunk.done {
-                                println!("✅ Agent streaming completed for session: {} (chunks: {}, repeats: {})", 
-                                         session_id, state.chunk_count, state.repeat_count);
-                                emit_complete(&app_handle, &session_id).await;
+                                let usage = response_chunk.usage();
+                                println!("✅ Agent streaming completed for session: {} (chunks: {}, repeats: {}, tokens: {:?})", 
+                                         session_id, state.chunk_count, state.repeat_count, usage.map(|u| u.total_tokens));
+                                emit_complete(&app_handle, &session_id, usage).await;
                                 cleanup_session(&session_id);
-                                return Ok(());
+                                return Ok(state.text);
                             }
                         }
                         Err(e) => {
@@ -576,7 +774,7 @@ async fn stream_ollama_response(
 
 // Helper emit functions
 async fn emit_error(app_handle: &AppHandle, session_id: &str, error: &str) {
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "error",
         "error": error
     })) {
@@ -584,8 +782,17 @@ async fn emit_error(app_handle: &AppHandle, session_id: &str, error: &str) {
     }
 }
 
+async fn emit_cancelled(app_handle: &AppHandle, session_id: &str) {
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
+        "type": "cancelled",
+        "message": "Response cancelled by user"
+    })) {
+        eprintln!("Failed to emit cancellation event: {}", e);
+    }
+}
+
 async fn emit_timeout(app_handle: &AppHandle, session_id: &str, reason: &str) {
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "timeout",
         "reason": reason
     })) {
@@ -593,16 +800,17 @@ async fn emit_timeout(app_handle: &AppHandle, session_id: &str, reason: &str) {
     }
 }
 
-async fn emit_complete(app_handle: &AppHandle, session_id: &str) {
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
-        "type": "complete"
+async fn emit_complete(app_handle: &AppHandle, session_id: &str, usage: Option<TokenUsage>) {
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
+        "type": "complete",
+        "usage": usage
     })) {
         eprintln!("Failed to emit complete: {}", e);
     }
 }
 
 async fn emit_termination(app_handle: &AppHandle, session_id: &str, reason: &str, chunk_count: usize, repeat_count: usize) {
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "terminated",
         "reason": reason,
         "chunk_count": chunk_count,
@@ -612,14 +820,35 @@ async fn emit_termination(app_handle: &AppHandle, session_id: &str, reason: &str
     }
 }
 
+// Resolve generation options for a request and add GPU acceleration settings
+fn request_options(agent_type: &str, overrides: Option<&GenerationOptions>, prompt_tokens: usize) -> Option<serde_json::Value> {
+    let mut opts = crate::generation_options::build_ollama_options(agent_type, overrides, prompt_tokens);
+    let gpu_layers = detect_gpu_layers();
+    if gpu_layers > 0 {
+        opts.insert("num_gpu".to_string(), serde_json::json!(gpu_layers));
+        opts.insert("num_thread".to_string(), serde_json::json!(4)); // Reduce CPU threads when using GPU
+    }
+    Some(serde_json::Value::Object(opts))
+}
+
+/// Provenance for the stream start event. agentService.ts merges it, with the RAG chunk ids it
+/// supplied, into the reply's metadata, which is saved with the chat.
+fn stream_provenance(model: &str, options: &Option<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "provider": "ollama",
+        "modelTag": model,
+        "generationOptions": options,
+    })
+}
+
 // All your existing Tauri commands remain the same...
 
 #[tauri::command]
 pub async fn get_ollama_models() -> Result<Vec<OllamaModel>, String> {
-    let client = Arc::clone(&HTTP_CLIENT);
-    let url = format!("{}/api/tags", OLLAMA_BASE_URL);
+    let client = client(EndpointClass::Control);
+    let url = format!("{}/api/tags", ollama_base_url());
     
-    match client.get(&url).send().await {
+    match send_with_retry(EndpointClass::Control, client.get(&url)).await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<OllamaModelsResponse>().await {
@@ -630,17 +859,97 @@ pub async fn get_ollama_models() -> Result<Vec<OllamaModel>, String> {
                 Err(format!("Ollama API error: {}", response.status()))
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}. Make sure Ollama is running.", e)),
+        Err(e) => Err(e),
     }
 }
 
+// The /api/ps entries for the models Ollama currently has in memory
+pub(crate) async fn running_models() -> Result<Vec<serde_json::Value>, String> {
+    let url = format!("{}/api/ps", ollama_base_url());
+    let response = send_with_retry(EndpointClass::Control, client(EndpointClass::Control).get(&url)).await?;
+    if !response.status().is_success() {
+        return Err(format!("Ollama API error: {}", response.status()));
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse running models: {}", e))?;
+    Ok(body["models"].as_array().cloned().unwrap_or_default())
+}
+
+/// Names of the models Ollama currently has in memory.
+pub(crate) async fn get_loaded_models() -> Result<Vec<String>, String> {
+    Ok(running_models()
+        .await?
+        .iter()
+        .filter_map(|model| model["name"].as_str().map(str::to_string))
+        .collect())
+}
+
+/// (total bytes, bytes in GPU memory) of a loaded model, None when it isn't loaded.
+async fn loaded_model_memory(model: &str) -> Result<Option<(u64, u64)>, String> {
+    Ok(running_models()
+        .await?
+        .iter()
+        .find(|loaded| loaded["name"].as_str() == Some(model) || loaded["model"].as_str() == Some(model))
+        .and_then(|loaded| Some((loaded["size"].as_u64()?, loaded["size_vram"].as_u64().unwrap_or(0)))))
+}
+
+/// Load a model into memory without generating anything, and keep it there for `keep_alive`
+/// (an Ollama duration such as "30m").
+#[tauri::command]
+pub async fn preload_ollama_model(model: String, keep_alive: Option<String>) -> Result<(), String> {
+    // Loading a large model from disk can take far longer than the shared client's timeout
+    let client = reqwest::Client::builder()
+        .default_headers(crate::ollama_http::auth_headers())
+        .timeout(Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let response = send_with_retry(EndpointClass::Generation, client
+        .post(format!("{}/api/generate", ollama_base_url()))
+        .json(&serde_json::json!({
+            "model": model,
+            "prompt": "",
+            "stream": false,
+            "keep_alive": keep_alive.unwrap_or_else(|| "30m".to_string()),
+        })))
+        .await?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Failed to load {}: {}", model, error_text));
+    }
+    crate::model_residency::note_model_use(&model);
+    println!("🔥 Preloaded model {}", model);
+    Ok(())
+}
+
+/// Drop a model from Ollama's memory now (keep_alive 0), e.g. to free VRAM for Whisper.
+#[tauri::command]
+pub async fn unload_ollama_model(model: String) -> Result<(), String> {
+    let response = send_with_retry(EndpointClass::Control, client(EndpointClass::Control)
+        .post(format!("{}/api/generate", ollama_base_url()))
+        .json(&serde_json::json!({
+            "model": model,
+            "keep_alive": 0,
+        })))
+        .await?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Failed to unload {}: {}", model, error_text));
+    }
+    println!("💤 Unloaded model {}", model);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_ollama_status() -> Result<OllamaStatus, String> {
-    let client = Arc::clone(&HTTP_CLIENT);
-    let url = format!("{}/api/version", OLLAMA_BASE_URL);
+    let client = client(EndpointClass::Control);
+    let url = format!("{}/api/version", ollama_base_url());
     
+    // Not retried: this is the probe the UI polls, and an answer closes the circuit breaker
     match client.get(&url).send().await {
         Ok(response) => {
+            crate::ollama_retry::record_success();
             if response.status().is_success() {
                 match response.json::<HashMap<String, String>>().await {
                     Ok(version_info) => Ok(OllamaStatus {
@@ -665,8 +974,8 @@ pub async fn get_ollama_status() -> Result<OllamaStatus, String> {
 
 #[tauri::command]
 pub async fn pull_ollama_model(model_name: String) -> Result<String, String> {
-    let client = Arc::clone(&HTTP_CLIENT);
-    let url = format!("{}/api/pull", OLLAMA_BASE_URL);
+    let client = client(EndpointClass::Generation);
+    let url = format!("{}/api/pull", ollama_base_url());
     
     let request = PullRequest {
         name: model_name.clone(),
@@ -674,7 +983,7 @@ pub async fn pull_ollama_model(model_name: String) -> Result<String, String> {
         stream: Some(false),
     };
     
-    match client.post(&url).json(&request).send().await {
+    match send_with_retry(EndpointClass::Generation, client.post(&url).json(&request)).await {
         Ok(response) => {
             if response.status().is_success() {
                 Ok(format!("Successfully started pulling model: {}", model_name))
@@ -683,63 +992,312 @@ pub async fn pull_ollama_model(model_name: String) -> Result<String, String> {
                 Err(format!("Failed to pull model: {}", error_text))
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(e),
     }
 }
 
 #[tauri::command]
 pub async fn delete_ollama_model(model_name: String) -> Result<String, String> {
-    let client = Arc::clone(&HTTP_CLIENT);
-    let url = format!("{}/api/delete", OLLAMA_BASE_URL);
+    let client = client(EndpointClass::Control);
+    let url = format!("{}/api/delete", ollama_base_url());
     
     let request = serde_json::json!({
         "name": model_name
     });
     
-    match client.delete(&url).json(&request).send().await {
+    match send_with_retry(EndpointClass::Control, client.delete(&url).json(&request)).await {
         Ok(response) => {
             if response.status().is_success() {
+                if let Ok(mut records) = load_custom_models() {
+                    let before = records.len();
+                    records.retain(|record| record.name != model_name);
+                    if records.len() != before {
+                        let _ = save_custom_models(&records);
+                    }
+                }
                 Ok(format!("Successfully deleted model: {}", model_name))
             } else {
                 let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
                 Err(format!("Failed to delete model: {}", error_text))
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(e),
+    }
+}
+
+// Custom models created from Modelfiles are tracked locally so they can be listed
+// separately from pulled models and recreated on another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomModelRecord {
+    pub name: String,
+    pub base_model: Option<String>,
+    pub modelfile: String,
+    pub created_at: String,
+    pub installed: bool,
+}
+
+#[derive(Debug, Default)]
+struct ParsedModelfile {
+    from: Option<String>,
+    system: Option<String>,
+    template: Option<String>,
+    parameters: serde_json::Map<String, serde_json::Value>,
+}
+
+// Parameters Ollama accepts more than once
+const REPEATABLE_PARAMETERS: &[&str] = &["stop"];
+
+fn parse_modelfile(modelfile: &str) -> Result<ParsedModelfile, String> {
+    let mut parsed = ParsedModelfile::default();
+    let mut lines = modelfile.lines();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let (instruction, rest) = trimmed.split_once(char::is_whitespace).unwrap_or((trimmed, ""));
+        let mut value = rest.trim().to_string();
+
+        // Triple-quoted values may span several lines
+        if let Some(opened) = value.strip_prefix("\"\"\"") {
+            let mut collected = opened.to_string();
+            while !collected.ends_with("\"\"\"") {
+                match lines.next() {
+                    Some(next) => {
+                        collected.push('\n');
+                        collected.push_str(next);
+                    }
+                    None => return Err(format!("Unterminated \"\"\" block in {} instruction", instruction)),
+                }
+            }
+            value = collected[..collected.len() - 3].to_string();
+        } else if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            value = value[1..value.len() - 1].to_string();
+        }
+
+        match instruction.to_uppercase().as_str() {
+            "FROM" => parsed.from = Some(value),
+            "SYSTEM" => parsed.system = Some(value),
+            "TEMPLATE" => parsed.template = Some(value),
+            "PARAMETER" => {
+                let (key, raw) = value
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| format!("PARAMETER needs a name and value: {}", trimmed))?;
+                let raw = raw.trim().trim_matches('"');
+                let parsed_value = raw
+                    .parse::<i64>()
+                    .map(serde_json::Value::from)
+                    .or_else(|_| raw.parse::<f64>().map(serde_json::Value::from))
+                    .unwrap_or_else(|_| serde_json::Value::String(raw.to_string()));
+
+                if REPEATABLE_PARAMETERS.contains(&key) {
+                    let entry = parsed
+                        .parameters
+                        .entry(key.to_string())
+                        .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+                    if let Some(values) = entry.as_array_mut() {
+                        values.push(parsed_value);
+                    }
+                } else {
+                    parsed.parameters.insert(key.to_string(), parsed_value);
+                }
+            }
+            // ADAPTER, LICENSE, MESSAGE etc. are passed through in the raw Modelfile
+            _ => {}
+        }
+    }
+
+    if parsed.from.is_none() {
+        return Err("Modelfile must contain a FROM instruction".to_string());
+    }
+    Ok(parsed)
+}
+
+fn get_custom_models_path() -> anyhow::Result<std::path::PathBuf> {
+    crate::data::paths::config_file("custom_models.json")
+}
+
+fn load_custom_models() -> Result<Vec<CustomModelRecord>, String> {
+    let path = get_custom_models_path().map_err(|e| format!("Failed to get custom models path: {}", e))?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read custom models: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse custom models: {}", e))
+}
+
+fn save_custom_models(records: &[CustomModelRecord]) -> Result<(), String> {
+    let path = get_custom_models_path().map_err(|e| format!("Failed to get custom models path: {}", e))?;
+    let json = serde_json::to_string_pretty(records)
+        .map_err(|e| format!("Failed to serialize custom models: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write custom models: {}", e))
+}
+
+fn upsert_custom_model(record: CustomModelRecord) -> Result<(), String> {
+    let mut records = load_custom_models()?;
+    records.retain(|existing| existing.name != record.name);
+    records.push(record);
+    save_custom_models(&records)
+}
+
+#[tauri::command]
+pub async fn create_ollama_model(name: String, modelfile: String) -> Result<String, String> {
+    if name.trim().is_empty() {
+        return Err("Model name cannot be empty".to_string());
+    }
+    let parsed = parse_modelfile(&modelfile)?;
+
+    // Creating can take a while when the base has to be converted, so don't use the shared 60s client
+    let client = reqwest::Client::builder()
+        .default_headers(crate::ollama_http::auth_headers())
+        .timeout(Duration::from_secs(600))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let url = format!("{}/api/create", ollama_base_url());
+
+    // Older Ollama versions read the raw Modelfile, newer ones the structured fields
+    let mut request = serde_json::json!({
+        "model": name,
+        "name": name,
+        "modelfile": modelfile,
+        "from": parsed.from,
+        "stream": false
+    });
+    if let Some(system) = &parsed.system {
+        request["system"] = serde_json::json!(system);
+    }
+    if let Some(template) = &parsed.template {
+        request["template"] = serde_json::json!(template);
+    }
+    if !parsed.parameters.is_empty() {
+        request["parameters"] = serde_json::Value::Object(parsed.parameters.clone());
+    }
+
+    let response = send_with_retry(EndpointClass::Generation, client.post(&url).json(&request)).await?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Failed to create model: {}", error_text));
+    }
+
+    upsert_custom_model(CustomModelRecord {
+        name: name.clone(),
+        base_model: parsed.from,
+        modelfile,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        installed: true,
+    })?;
+
+    println!("🧱 Created custom Ollama model: {}", name);
+    Ok(format!("Successfully created model: {}", name))
+}
+
+#[tauri::command]
+pub async fn copy_ollama_model(source: String, destination: String) -> Result<String, String> {
+    let client = client(EndpointClass::Control);
+    let url = format!("{}/api/copy", ollama_base_url());
+
+    let request = serde_json::json!({
+        "source": source,
+        "destination": destination
+    });
+
+    let response = send_with_retry(EndpointClass::Control, client.post(&url).json(&request)).await?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Failed to copy model: {}", error_text));
+    }
+
+    // A copy of a custom model is custom too; keep its Modelfile so it can be recreated
+    if let Some(original) = load_custom_models()?.into_iter().find(|record| record.name == source) {
+        upsert_custom_model(CustomModelRecord {
+            name: destination.clone(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            ..original
+        })?;
+    }
+
+    Ok(format!("Successfully copied {} to {}", source, destination))
+}
+
+#[tauri::command]
+pub async fn list_custom_models() -> Result<Vec<CustomModelRecord>, String> {
+    let mut records = load_custom_models()?;
+
+    // Flag models that were removed outside the app; leave the flag alone if Ollama is unreachable
+    if let Ok(installed) = get_ollama_models().await {
+        for record in records.iter_mut() {
+            record.installed = installed.iter().any(|model| {
+                model.name == record.name || model.name == format!("{}:latest", record.name)
+            });
+        }
+    }
+
+    records.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(records)
+}
+
+/// Default model and system prompt for each built-in agent.
+pub(crate) fn agent_model_and_prompt(agent_type: &str) -> Option<(&'static str, &'static str)> {
+    match agent_type {
+        "enteract" => Some(("gemma3:1b-it-qat", ENTERACT_AGENT_PROMPT)),
+        "vision" => Some(("qwen2.5vl:3b", VISION_ANALYSIS_PROMPT)),
+        "coding" => Some(("qwen2.5-coder:1.5b", CODING_AGENT_PROMPT)),
+        "research" => Some(("deepseek-r1:1.5b", DEEP_RESEARCH_PROMPT)),
+        "conversational" | "conversational_ai" => Some(("gemma3:1b-it-qat", CONVERSATIONAL_AI_PROMPT)),
+        _ => None,
     }
 }
 
+/// Starter Modelfile that bakes an Enteract agent's system prompt into a dedicated model.
 #[tauri::command]
-pub async fn generate_ollama_response(model: String, prompt: String) -> Result<String, String> {
-    let client = Arc::clone(&HTTP_CLIENT);
-    let url = format!("{}/api/generate", OLLAMA_BASE_URL);
+pub fn get_agent_modelfile(agent_type: String, base_model: Option<String>) -> Result<String, String> {
+    let (default_model, system_prompt) = agent_model_and_prompt(&agent_type)
+        .ok_or_else(|| format!("Unknown agent type: {}", agent_type))?;
+    let base_model = base_model.unwrap_or_else(|| default_model.to_string());
+
+    Ok(format!(
+        "FROM {}\n\nPARAMETER temperature 0.7\nPARAMETER top_p 0.9\n\nSYSTEM \"\"\"{}\"\"\"\n",
+        base_model,
+        system_prompt.trim()
+    ))
+}
+
+#[tauri::command]
+pub async fn generate_ollama_response(
+    model: String,
+    prompt: String,
+    options: Option<GenerationOptions>,
+) -> Result<String, String> {
+    let client = client(EndpointClass::Generation);
+    let url = format!("{}/api/generate", ollama_base_url());
     
-    // Detect GPU and set acceleration options
-    let gpu_layers = detect_gpu_layers();
-    let options = if gpu_layers > 0 {
-        Some(serde_json::json!({
-            "num_gpu": gpu_layers,
-            "num_thread": 4
-        }))
-    } else {
-        None
-    };
+    let options = request_options("general", options.as_ref(), estimate_tokens(&prompt));
+    let cache_request = CacheRequest { model: &model, system: None, prompt: &prompt, images: &[], format: None, options: options.as_ref() };
+    if let Some(cached) = crate::response_cache::lookup(&cache_request) {
+        return Ok(cached);
+    }
     
     let request = GenerateRequest {
-        model,
-        prompt,
+        model: model.clone(),
+        prompt: prompt.clone(),
         stream: Some(false),
         context: None,
         images: None,
         system: None,
-        options,
+        options: options.clone(),
+        keep_alive: crate::model_residency::keep_alive(),
     };
     
-    match client.post(&url).json(&request).send().await {
+    match send_with_retry(EndpointClass::Generation, client.post(&url).json(&request)).await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<GenerateResponse>().await {
-                    Ok(generate_response) => Ok(generate_response.response),
+                    Ok(generate_response) => {
+                        crate::response_cache::store(&cache_request, &generate_response.response);
+                        Ok(generate_response.response)
+                    }
                     Err(e) => Err(format!("Failed to parse response: {}", e)),
                 }
             } else {
@@ -747,7 +1305,7 @@ pub async fn generate_ollama_response(model: String, prompt: String) -> Result<S
                 Err(format!("Generation failed: {}", error_text))
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(e),
     }
 }
 
@@ -757,20 +1315,14 @@ pub async fn generate_ollama_response_stream(
     model: String,
     prompt: String,
     session_id: String,
+    options: Option<GenerationOptions>,
 ) -> Result<(), String> {
-    let url = format!("{}/api/generate", OLLAMA_BASE_URL);
+    let url = format!("{}/api/generate", ollama_base_url());
     
-    // Detect GPU and set acceleration options
-    let gpu_layers = detect_gpu_layers();
-    let options = if gpu_layers > 0 {
-        Some(serde_json::json!({
-            "num_gpu": gpu_layers,
-            "num_thread": 4
-        }))
-    } else {
-        None
-    };
+    let options = request_options("general", options.as_ref(), estimate_tokens(&prompt));
     
+    crate::model_residency::note_model_use(&model);
+    let provenance = stream_provenance(&model, &options);
     let request = GenerateRequest {
         model: model.clone(),
         prompt: prompt.clone(),
@@ -779,15 +1331,17 @@ pub async fn generate_ollama_response_stream(
         images: None,
         system: None,
         options,
+        keep_alive: crate::model_residency::keep_alive(),
     };
     
     println!("🚀 Starting streaming generation for session: {}", session_id);
     
     // Emit start event
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "start",
         "model": model,
-        "prompt": prompt
+        "prompt": prompt,
+        "provenance": provenance
     })) {
         return Err(format!("Failed to emit start event: {}", e));
     }
@@ -802,9 +1356,53 @@ pub async fn generate_enteract_agent_response(
     prompt: String,
     context: Option<Vec<ChatContextMessage>>,
     session_id: String,
+    options: Option<GenerationOptions>,
+    use_chat_api: Option<bool>, // Send role-structured history to /api/chat instead of one flattened prompt
+    mcp_session_id: Option<String>, // Let the agent use this MCP session's computer-use tools
+    mcp_sessions: tauri::State<'_, MCPSessionManager>,
 ) -> Result<(), String> {
-    let model = "gemma3:1b-it-qat".to_string();
-    generate_agent_response_stream(app_handle, model, prompt, ENTERACT_AGENT_PROMPT.to_string(), context, session_id, "enteract".to_string()).await
+    let model = crate::agent_models::resolve_agent_model(&app_handle, "enteract").await?;
+    // Tools go through Ollama's chat API, so an agent routed to another provider answers without them
+    if let Some(mcp_session_id) = mcp_session_id.filter(|_| crate::llm_provider::provider_for_agent("enteract").is_none()) {
+        // Don't hold the session map while tools run; approvals need it
+        let mcp_session = mcp_sessions.lock().await
+            .get(&mcp_session_id)
+            .cloned()
+            .ok_or_else(|| format!("MCP session not found: {}", mcp_session_id))?;
+        let window_limit = crate::generation_options::prompt_token_limit("enteract", options.as_ref());
+        let context = crate::context_window::fit_context(&app_handle, ENTERACT_AGENT_PROMPT, &prompt, context, Some(window_limit)).await;
+        return crate::agent_tools::run_agent_with_tools(
+            &app_handle, mcp_session, &model, ENTERACT_AGENT_PROMPT, prompt, context, &session_id, "enteract", options,
+        ).await.map(|_| ());
+    }
+    stream_agent_response(app_handle, model, prompt, ENTERACT_AGENT_PROMPT.to_string(), context, session_id, "enteract".to_string(), options, use_chat_api.unwrap_or(false)).await.map(|_| ())
+}
+
+const MAX_VISION_IMAGES: usize = 8;
+
+/// One image of a multi-image vision request, e.g. "before" and "after", or one per monitor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabeledImage {
+    pub label: String,
+    pub image_base64: String,
+}
+
+// Numbered legend telling the model which image is which, in the order Ollama receives them
+fn image_legend(images: &[LabeledImage]) -> String {
+    images
+        .iter()
+        .enumerate()
+        .map(|(i, image)| {
+            let label = image.label.trim();
+            if label.is_empty() {
+                format!("Image {}", i + 1)
+            } else {
+                format!("Image {}: {}", i + 1, label)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 #[tauri::command]
@@ -813,19 +1411,47 @@ pub async fn generate_vision_analysis(
     prompt: String,
     image_base64: String,
     session_id: String,
+    options: Option<GenerationOptions>,
+    window: Option<String>, // Analyze just this window (id or title) instead of `image_base64`
+    images: Option<Vec<LabeledImage>>, // Several labelled images in one request, instead of `image_base64`
 ) -> Result<(), String> {
-    let model = "qwen2.5vl:3b".to_string();
-    let full_prompt = format!("Screenshot Analysis Request:\n\n{}", prompt);
+    let images = match (images.filter(|images| !images.is_empty()), window) {
+        (Some(images), _) if images.len() > MAX_VISION_IMAGES => {
+            return Err(format!("At most {} images can be analyzed in one request", MAX_VISION_IMAGES));
+        }
+        (Some(images), _) => images,
+        (None, Some(window)) => vec![LabeledImage { label: String::new(), image_base64: crate::screenshot::capture_window(window).await?.image_base64 }],
+        (None, None) => vec![LabeledImage { label: String::new(), image_base64 }],
+    };
+    if images.iter().any(|image| image.image_base64.trim().is_empty()) {
+        return Err("An image to analyze is empty".to_string());
+    }
+    let model = crate::agent_models::resolve_agent_model(&app_handle, "vision").await?;
+    // Several images get a legend so the prompt can refer to them by label
+    let prompt = if images.len() > 1 {
+        format!("The {} images are, in order:\n{}\n\n{}", images.len(), image_legend(&images), prompt)
+    } else {
+        prompt
+    };
+    // Tell the model which application it's looking at so it doesn't have to guess from pixels
+    let full_prompt = match crate::window_manager::active_app().await {
+        Some(app) => format!(
+            "Screenshot Analysis Request:\nActive application: {} ({})\n\n{}",
+            app.app_name, app.window_title, prompt
+        ),
+        None => format!("Screenshot Analysis Request:\n\n{}", prompt),
+    };
     
     generate_agent_response_stream_with_image(
         app_handle, 
         model, 
         full_prompt, 
         VISION_ANALYSIS_PROMPT.to_string(),
-        image_base64,
+        images.into_iter().map(|image| image.image_base64).collect(),
         None, // Vision analysis doesn't use chat context
         session_id,
-        "vision".to_string()
+        "vision".to_string(),
+        options
     ).await
 }
 
@@ -835,12 +1461,14 @@ pub async fn generate_coding_agent_response(
     prompt: String,
     context: Option<Vec<ChatContextMessage>>,
     session_id: String,
+    options: Option<GenerationOptions>,
+    use_chat_api: Option<bool>,
 ) -> Result<(), String> {
-    let model = "qwen2.5-coder:1.5b".to_string();
+    let model = crate::agent_models::resolve_agent_model(&app_handle, "coding").await?;
     let full_prompt = format!("Coding Request:\n\n{}", prompt);
     
     println!("💻 CODING AGENT: Using model {} for session {}", model, session_id);
-    generate_agent_response_stream(app_handle, model, full_prompt, CODING_AGENT_PROMPT.to_string(), context, session_id, "coding".to_string()).await
+    stream_agent_response(app_handle, model, full_prompt, CODING_AGENT_PROMPT.to_string(), context, session_id, "coding".to_string(), options, use_chat_api.unwrap_or(false)).await.map(|_| ())
 }
 
 #[tauri::command]
@@ -849,12 +1477,14 @@ pub async fn generate_deep_research(
     prompt: String,
     context: Option<Vec<ChatContextMessage>>,
     session_id: String,
+    options: Option<GenerationOptions>,
+    use_chat_api: Option<bool>,
 ) -> Result<(), String> {
-    let model = "deepseek-r1:1.5b".to_string();
+    let model = crate::agent_models::resolve_agent_model(&app_handle, "research").await?;
     let full_prompt = format!("Deep Research Query:\n\n{}", prompt);
     
     println!("🧠 DEEP RESEARCH: Using model {} for session {}", model, session_id);
-    generate_agent_response_stream(app_handle, model, full_prompt, DEEP_RESEARCH_PROMPT.to_string(), context, session_id, "research".to_string()).await
+    stream_agent_response(app_handle, model, full_prompt, DEEP_RESEARCH_PROMPT.to_string(), context, session_id, "research".to_string(), options, use_chat_api.unwrap_or(false)).await.map(|_| ())
 }
 
 #[tauri::command]
@@ -862,24 +1492,78 @@ pub async fn generate_conversational_ai(
     app_handle: AppHandle,
     conversation_context: String,
     session_id: String,
+    conversation_id: Option<String>,
     _custom_system_prompt: Option<String>, // Prefixed with underscore to indicate intentionally unused
+    options: Option<GenerationOptions>,
 ) -> Result<(), String> {
-    // Fast 1B model for instant responses (quantized)
-    let model = "gemma3:1b-it-qat".to_string();
+    let profile = crate::conversation_profiles::active_profile();
+    if !profile.insights_enabled {
+        return Err(format!("Insights are disabled for the {} profile", profile.name));
+    }
+    
+    // Long meetings shouldn't keep the machine busy with insights; refused before any model work
+    crate::insight_budget::check(&app_handle, conversation_id.as_deref())?;
+    let budget_session = conversation_id.clone();
+    
+    // The frontend asks after every burst of messages; the profile decides how often that's answered
+    let interval = Duration::from_secs(profile.insight_interval_secs);
+    if !claim_insight_slot(conversation_id.as_deref().unwrap_or(&session_id), interval) {
+        println!("⏳ Skipping insight for session {}: the {} profile's {}s interval hasn't passed", session_id, profile.name, profile.insight_interval_secs);
+        return Ok(());
+    }
     
-    // Simplified prompt - just provide the conversation context
-    let full_prompt = format!("Conversation:\n{}\n\nProvide a brief summary and helpful next steps.", conversation_context);
+    // The profile can pick its own insight model; otherwise the conversational agent's model
+    let model = match profile.insight_model.clone() {
+        Some(model) => model,
+        None => crate::agent_models::resolve_agent_model(&app_handle, "conversational_ai").await?,
+    };
+    
+    let instruction = match profile.summary_style {
+        crate::conversation_profiles::SummaryStyle::Bullets => "Provide a brief bullet-point summary and helpful next steps.",
+        crate::conversation_profiles::SummaryStyle::Narrative => "Provide a brief summary and helpful next steps.",
+        crate::conversation_profiles::SummaryStyle::ActionItems => "Provide a brief summary, then list decisions and action items with owners where mentioned.",
+        crate::conversation_profiles::SummaryStyle::QuestionsAndAnswers => "Identify the most recent question asked, then suggest a concise, strong answer.",
+    };
+    
+    // Off-the-record messages never reach the insight model, whatever the frontend sent
+    let conversation_context = match &conversation_id {
+        Some(conversation_id) => {
+            let conversation = crate::data::conversation::ConversationStorage::new(&app_handle)
+                .and_then(|storage| storage.get_session(conversation_id))
+                .map_err(|e| format!("Failed to load conversation: {}", e))?;
+            match conversation {
+                Some(conversation) => crate::data::conversation::redaction::scrub_redacted(&conversation_context, &conversation.messages),
+                None => conversation_context,
+            }
+        }
+        None => conversation_context,
+    };
+    
+    // What the conversation's memory holds about everything before the recent context
+    let memory = conversation_id.as_deref()
+        .and_then(|conversation_id| crate::conversation_memory::load(&app_handle, conversation_id))
+        .and_then(|memory| crate::conversation_memory::render(&memory));
+    let full_prompt = match memory {
+        Some(memory) => format!("{}\nRecent conversation:\n{}\n\n{}", memory, conversation_context, instruction),
+        None => format!("Conversation:\n{}\n\n{}", conversation_context, instruction),
+    };
     
     // Always use the simplified system prompt
     let system_prompt = CONVERSATIONAL_AI_PROMPT.to_string();
     
     println!("💬 CONVERSATIONAL AI: Using model {} for insights, session {}", model, session_id);
     
-    generate_agent_response_stream(app_handle, model, full_prompt, system_prompt, None, session_id, "conversational_ai".to_string()).await
+    let prompt_tokens = estimate_tokens(&system_prompt) + estimate_tokens(&full_prompt);
+    let response = generate_agent_response_stream(app_handle.clone(), model, full_prompt, system_prompt, None, session_id, "conversational_ai".to_string(), options).await?;
+    crate::insight_budget::record(&app_handle, budget_session.as_deref(), prompt_tokens + estimate_tokens(&response));
+    if let Some(conversation_id) = conversation_id {
+        crate::conversation_memory::schedule_update(&app_handle, conversation_id);
+    }
+    Ok(())
 }
 
-// Helper function for streaming with system prompt
-async fn generate_agent_response_stream(
+// Helper function for streaming with system prompt; returns the streamed response text
+pub(crate) async fn generate_agent_response_stream(
     app_handle: AppHandle,
     model: String,
     prompt: String,
@@ -887,77 +1571,89 @@ async fn generate_agent_response_stream(
     context: Option<Vec<ChatContextMessage>>,
     session_id: String,
     agent_type: String,
-) -> Result<(), String> {
+    options: Option<GenerationOptions>,
+) -> Result<String, String> {
+    stream_agent_response(app_handle, model, prompt, system_prompt, context, session_id, agent_type, options, false).await
+}
+
+// Streams an agent reply from /api/generate with the history flattened into the prompt, or from
+// /api/chat with the history sent as separate turns so the model's chat template applies
+async fn stream_agent_response(
+    app_handle: AppHandle,
+    model: String,
+    prompt: String,
+    system_prompt: String,
+    context: Option<Vec<ChatContextMessage>>,
+    session_id: String,
+    agent_type: String,
+    options: Option<GenerationOptions>,
+    use_chat_api: bool,
+) -> Result<String, String> {
+    let provider = crate::llm_provider::provider_for_agent(&agent_type);
+    // Remote providers have their own, much larger windows; only the user's budget applies to them
+    let window_limit = match provider {
+        Some(_) => None,
+        None => Some(crate::generation_options::prompt_token_limit(&agent_type, options.as_ref())),
+    };
+    let context = crate::context_window::fit_context(&app_handle, &system_prompt, &prompt, context, window_limit).await;
+
+    // Agents configured for another backend in LLM settings don't go through local Ollama at all
+    if let Some(provider) = provider {
+        return crate::llm_provider::stream_agent_reply(
+            &app_handle, provider.as_ref(), &model, prompt, &system_prompt, context, &session_id, &agent_type, options,
+        ).await;
+    }
+
     // Acquire semaphore permit for memory safety (limits concurrent model loads)
-    let _permit = REQUEST_SEMAPHORE.acquire().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
+    let _permit = generation_permits().acquire_owned().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
     
     println!("🔒 Acquired request semaphore for {} agent (session: {})", agent_type, session_id);
     
-    let url = format!("{}/api/generate", OLLAMA_BASE_URL);
-    
-    // Build full prompt with context
-    let full_prompt = build_prompt_with_context(prompt, context);
-    
-    // Detect GPU and set acceleration options
-    let gpu_layers = detect_gpu_layers();
-    
-    let options = if agent_type == "conversational_ai" {
-        println!("AI agent type: {}", agent_type);
-        // Balanced for comprehensive but focused conversation coaching
-        let mut opts = serde_json::json!({
-            "num_predict": 2048,
-            "temperature": 0.7,
-            "top_p": 0.9,
-            "repeat_penalty": 1.05
-        });
-        if gpu_layers > 0 {
-            opts["num_gpu"] = serde_json::json!(gpu_layers);
-            opts["num_thread"] = serde_json::json!(4); // Reduce CPU threads when using GPU
-        }
-        Some(opts)
-    } else if agent_type == "coding" {
-        let mut opts = serde_json::json!({
-            "num_predict": 1024,
-            "temperature": 0.2,
-            "top_p": 0.9,
-            "repeat_penalty": 1.1
-        });
-        if gpu_layers > 0 {
-            opts["num_gpu"] = serde_json::json!(gpu_layers);
-            opts["num_thread"] = serde_json::json!(4);
-        }
-        Some(opts)
+    // Agent defaults, saved per-agent settings and request overrides; num_ctx sized to the prompt
+    let (endpoint, request, options) = if use_chat_api {
+        let messages = build_chat_messages(&system_prompt, prompt, context);
+        let prompt_tokens = messages.iter().map(|message| estimate_tokens(&message.content)).sum();
+        let options = request_options(&agent_type, options.as_ref(), prompt_tokens);
+        let request = ChatRequest {
+            model: model.clone(),
+            messages,
+            stream: Some(true),
+            options: options.clone(),
+            keep_alive: crate::model_residency::keep_alive(),
+        };
+        ("chat", serde_json::to_value(request), options)
     } else {
-        let mut opts = serde_json::json!({
-            "num_predict": 1024,
-            "temperature": 0.7,
-            "top_p": 0.9,
-            "repeat_penalty": 1.1
-        });
-        if gpu_layers > 0 {
-            opts["num_gpu"] = serde_json::json!(gpu_layers);
-            opts["num_thread"] = serde_json::json!(4);
-        }
-        Some(opts)
+        // Build full prompt with context
+        let full_prompt = build_prompt_with_context(prompt, context);
+        let prompt_tokens = estimate_tokens(&full_prompt) + estimate_tokens(&system_prompt);
+        let options = request_options(&agent_type, options.as_ref(), prompt_tokens);
+        let request = GenerateRequest {
+            model: model.clone(),
+            prompt: full_prompt,
+            stream: Some(true),
+            context: None,
+            images: None,
+            system: Some(system_prompt),
+            options: options.clone(),
+            keep_alive: crate::model_residency::keep_alive(),
+        };
+        ("generate", serde_json::to_value(request), options)
     };
+    let request = request.map_err(|e| format!("Failed to build request: {}", e))?;
+    let url = format!("{}/api/{}", ollama_base_url(), endpoint);
 
-    let request = GenerateRequest {
-        model: model.clone(),
-        prompt: full_prompt,
-        stream: Some(true),
-        context: None,
-        images: None,
-        system: Some(system_prompt),
-        options,
-    };
+    crate::model_residency::note_model_use(&model);
+    let provenance = stream_provenance(&model, &options);
     
-    println!("🤖 Starting {} agent ({}) streaming for session: {}", agent_type, model, session_id);
+    println!("🤖 Starting {} agent ({}) streaming from /api/{} for session: {}", agent_type, model, endpoint, session_id);
     
     // Emit start event with correct agent type
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "start",
         "model": model,
-        "agent_type": agent_type
+        "agent_type": agent_type,
+        "endpoint": endpoint,
+        "provenance": provenance
     })) {
         return Err(format!("Failed to emit start event: {}", e));
     }
@@ -972,7 +1668,7 @@ async fn generate_agent_response_stream(
     };
 
     
-    let result = stream_ollama_response_enhanced(app_handle, url, request, session_id.clone(), agent_config).await;
+    let result = stream_ollama_response_collecting(app_handle, url, request, session_id.clone(), agent_config).await;
     
     // Semaphore is automatically released when _permit goes out of scope
     println!("🔓 Released request semaphore for {} agent (session: {})", agent_type, session_id);
@@ -986,57 +1682,55 @@ async fn generate_agent_response_stream_with_image(
     model: String,
     prompt: String,
     system_prompt: String,
-    image_base64: String,
+    images: Vec<String>,
     context: Option<Vec<ChatContextMessage>>,
     session_id: String,
     agent_type: String,
+    options: Option<GenerationOptions>,
 ) -> Result<(), String> {
     // Acquire semaphore permit for memory safety (limits concurrent model loads)
-    let _permit = REQUEST_SEMAPHORE.acquire().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
+    let _permit = generation_permits().acquire_owned().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
     
     println!("🔒 Acquired request semaphore for {} agent with image (session: {})", agent_type, session_id);
     
-    let url = format!("{}/api/generate", OLLAMA_BASE_URL);
+    let url = format!("{}/api/generate", ollama_base_url());
     
     // Build full prompt with context (if provided)
     let full_prompt = build_prompt_with_context(prompt, context);
     
+    // Vision models spend roughly a thousand tokens on each image
+    let prompt_tokens = estimate_tokens(&full_prompt) + estimate_tokens(&system_prompt) + 1024 * images.len();
+    let image_count = images.len() as u64;
+    let options = request_options(&agent_type, options.as_ref(), prompt_tokens);
+    
+    crate::model_residency::note_model_use(&model);
+    let provenance = stream_provenance(&model, &options);
     let request = GenerateRequest {
         model: model.clone(),
         prompt: full_prompt,
         stream: Some(true),
         context: None,
-        images: Some(vec![image_base64]),
+        images: Some(images),
         system: Some(system_prompt),
-        options: {
-            let gpu_layers = detect_gpu_layers();
-            let mut opts = serde_json::json!({
-                "num_predict": 1024,
-                "temperature": 0.5,
-                "top_p": 0.9
-            });
-            if gpu_layers > 0 {
-                opts["num_gpu"] = serde_json::json!(gpu_layers);
-                opts["num_thread"] = serde_json::json!(4);
-            }
-            Some(opts)
-        },
+        options,
+        keep_alive: crate::model_residency::keep_alive(),
     };
     
-    println!("👁️ Starting {} vision analysis ({}) for session: {}", agent_type, model, session_id);
+    println!("👁️ Starting {} vision analysis ({}, {} images) for session: {}", agent_type, model, image_count, session_id);
     
     // Emit start event with correct agent type
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "start",
         "model": model,
-        "agent_type": agent_type
+        "agent_type": agent_type,
+        "provenance": provenance
     })) {
         return Err(format!("Failed to emit start event: {}", e));
     }
     
     // Use enhanced streaming with vision-specific config
     let vision_config = StreamConfig {
-        max_total_duration: Duration::from_secs(120), // 2 minutes for vision
+        max_total_duration: Duration::from_secs(120 + 60 * image_count.saturating_sub(1)), // 2 minutes for vision, plus one per extra image
         max_chunk_gap: Duration::from_secs(25),       // 25 seconds between chunks
         chunk_timeout: Duration::from_secs(10),       // 10 seconds per chunk
         max_consecutive_repeats: 4,                   // Max 4 consecutive repeats for vision
@@ -1053,14 +1747,14 @@ async fn generate_agent_response_stream_with_image(
 
 #[tauri::command]
 pub async fn get_ollama_model_info(model_name: String) -> Result<serde_json::Value, String> {
-    let client = Arc::clone(&HTTP_CLIENT);
-    let url = format!("{}/api/show", OLLAMA_BASE_URL);
+    let client = client(EndpointClass::Control);
+    let url = format!("{}/api/show", ollama_base_url());
     
     let request = serde_json::json!({
         "name": model_name
     });
     
-    match client.post(&url).json(&request).send().await {
+    match send_with_retry(EndpointClass::Control, client.post(&url).json(&request)).await {
         Ok(response) => {
             if response.status().is_success() {
                 match response.json::<serde_json::Value>().await {
@@ -1072,8 +1766,281 @@ pub async fn get_ollama_model_info(model_name: String) -> Result<serde_json::Val
                 Err(format!("Failed to get model info: {}", error_text))
             }
         }
-        Err(e) => Err(format!("Failed to connect to Ollama: {}", e)),
+        Err(e) => Err(e),
+    }
+}
+
+// Non-streaming completion used by backend pipelines (query rewriting, tagging, summaries)
+pub async fn generate_completion(
+    model: &str,
+    prompt: String,
+    system: Option<String>,
+    options: Option<serde_json::Value>,
+) -> Result<String, String> {
+    let _permit = generation_permits().acquire_owned().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
+    crate::model_residency::note_model_use(model);
+    
+    let client = client(EndpointClass::Generation);
+    let url = format!("{}/api/generate", ollama_base_url());
+    
+    let request = GenerateRequest {
+        model: model.to_string(),
+        prompt,
+        stream: Some(false),
+        context: None,
+        images: None,
+        system,
+        options,
+        keep_alive: crate::model_residency::keep_alive(),
+    };
+    
+    let response = send_with_retry(EndpointClass::Generation, client.post(&url).json(&request)).await?;
+    
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Generation failed: {}", error_text));
+    }
+    
+    response.json::<GenerateResponse>().await
+        .map(|generate_response| generate_response.response)
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+// Fixed prompts for benchmark_ollama_model: a short answer, a summary and some code, the kinds of
+// replies the agents give
+const BENCHMARK_PROMPTS: &[(&str, &str)] = &[
+    ("short_answer", "In two sentences, explain what a hash map is and when to use one."),
+    ("summary", "Summarize this in three bullet points: The team agreed to move the release to Friday \
+        so QA can finish regression testing. Marketing will delay the announcement by a day. The \
+        database migration runs Thursday night, and support has been asked to watch for login issues."),
+    ("code", "Write a Python function that returns the n-th Fibonacci number iteratively, with a docstring."),
+];
+const BENCHMARK_MAX_TOKENS: u32 = 128;
+
+#[derive(Deserialize)]
+struct BenchmarkLine {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    prompt_eval_count: Option<u32>,
+    #[serde(default)]
+    prompt_eval_duration: Option<u64>, // Nanoseconds
+    #[serde(default)]
+    eval_count: Option<u32>,
+    #[serde(default)]
+    eval_duration: Option<u64>,
+}
+
+fn per_second(count: Option<u32>, duration_ns: Option<u64>) -> Option<f64> {
+    match (count, duration_ns) {
+        (Some(count), Some(duration_ns)) if duration_ns > 0 => Some(count as f64 * 1e9 / duration_ns as f64),
+        _ => None,
+    }
+}
+
+// Stream one benchmark prompt, timing the first token; speeds come from Ollama's own eval timings
+async fn benchmark_prompt(model: &str, prompt_id: &str, prompt: &str) -> Result<PromptBenchmark, String> {
+    let _permit = generation_permits().acquire_owned().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
+    crate::model_residency::note_model_use(model);
+
+    let body = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "stream": true,
+        // Deterministic and capped, so runs are comparable
+        "options": { "temperature": 0, "seed": 42, "num_predict": BENCHMARK_MAX_TOKENS },
+        "keep_alive": crate::model_residency::keep_alive(),
+    });
+    let started = Instant::now();
+    let response = send_with_retry(EndpointClass::Generation, client(EndpointClass::Generation)
+        .post(format!("{}/api/generate", ollama_base_url()))
+        .json(&body))
+        .await?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Benchmark prompt {} failed: {}", prompt_id, error_text));
+    }
+
+    let mut stream = response.bytes_stream();
+    let mut lines = NdjsonLines::default();
+    let mut first_token: Option<Duration> = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Benchmark stream error: {}", e))?;
+        for line in lines.push(&chunk) {
+            let line: BenchmarkLine = serde_json::from_str(&line)
+                .map_err(|e| format!("Failed to parse benchmark stream: {}", e))?;
+            if first_token.is_none() && !line.response.is_empty() {
+                first_token = Some(started.elapsed());
+            }
+            if line.done {
+                let total = started.elapsed();
+                return Ok(PromptBenchmark {
+                    prompt_id: prompt_id.to_string(),
+                    time_to_first_token_ms: first_token.unwrap_or(total).as_millis() as u64,
+                    tokens_per_second: per_second(line.eval_count, line.eval_duration).unwrap_or(0.0),
+                    prompt_tokens_per_second: per_second(line.prompt_eval_count, line.prompt_eval_duration),
+                    output_tokens: line.eval_count.unwrap_or(0),
+                    total_ms: total.as_millis() as u64,
+                });
+            }
+        }
     }
+    Err(format!("Benchmark prompt {} ended before Ollama finished", prompt_id))
+}
+
+/// Benchmark a local model on a fixed prompt set: load time, time to first token, generation
+/// speed and memory use from /api/ps. The run is stored in model_benchmarks and grounds the
+/// model recommendation in `get_gpu_acceleration_status`. Progress goes out on
+/// `model-benchmark-progress`.
+#[tauri::command]
+pub async fn benchmark_ollama_model(app_handle: AppHandle, model: String) -> Result<ModelBenchmark, String> {
+    println!("⏱️ Benchmarking {}", model);
+    // Near zero when the model was already resident
+    let load_started = Instant::now();
+    preload_ollama_model(model.clone(), crate::model_residency::keep_alive()).await?;
+    let load_ms = load_started.elapsed().as_millis() as u64;
+
+    let mut prompts = Vec::with_capacity(BENCHMARK_PROMPTS.len());
+    for (index, (prompt_id, prompt)) in BENCHMARK_PROMPTS.iter().enumerate() {
+        let _ = app_handle.emit("model-benchmark-progress", serde_json::json!({
+            "model": model,
+            "prompt": prompt_id,
+            "index": index,
+            "total": BENCHMARK_PROMPTS.len(),
+        }));
+        prompts.push(benchmark_prompt(&model, prompt_id, prompt).await?);
+    }
+    // Measured while the model is still loaded from the last prompt
+    let memory = loaded_model_memory(&model).await.unwrap_or_else(|e| {
+        println!("⚠️ Could not read memory use for {}: {}", model, e);
+        None
+    });
+
+    let runs = prompts.len() as u64;
+    let benchmark = ModelBenchmark {
+        id: None,
+        model,
+        gpu: get_gpu_info().ok().and_then(|gpus| gpus.into_iter().next()).map(|gpu| gpu.name),
+        load_ms,
+        tokens_per_second: prompts.iter().map(|prompt| prompt.tokens_per_second).sum::<f64>() / runs as f64,
+        time_to_first_token_ms: prompts.iter().map(|prompt| prompt.time_to_first_token_ms).sum::<u64>() / runs,
+        memory_bytes: memory.map(|(size, _)| size),
+        vram_bytes: memory.map(|(_, vram)| vram),
+        prompts,
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let id = save_benchmark(&app_handle, &benchmark)?;
+    println!(
+        "📈 {}: {:.1} tokens/s, {}ms to first token, {} MB resident",
+        benchmark.model,
+        benchmark.tokens_per_second,
+        benchmark.time_to_first_token_ms,
+        benchmark.memory_bytes.unwrap_or(0) / (1024 * 1024)
+    );
+    Ok(ModelBenchmark { id: Some(id), ..benchmark })
+}
+
+/// Non-streaming image request whose reply Ollama constrains to a JSON object.
+pub async fn generate_json_with_image(
+    model: &str,
+    prompt: String,
+    system: String,
+    image_base64: String,
+    options: Option<serde_json::Value>,
+) -> Result<String, String> {
+    let images = [image_base64];
+    let json_format = serde_json::json!("json");
+    let cache_request = CacheRequest { model, system: Some(&system), prompt: &prompt, images: &images, format: Some(&json_format), options: options.as_ref() };
+    if let Some(cached) = crate::response_cache::lookup(&cache_request) {
+        return Ok(cached);
+    }
+    let _permit = generation_permits().acquire_owned().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
+    crate::model_residency::note_model_use(model);
+
+    let client = client(EndpointClass::Generation);
+    let url = format!("{}/api/generate", ollama_base_url());
+    let body = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "system": system,
+        "images": images,
+        "format": "json",
+        "stream": false,
+        "options": options,
+    });
+
+    let response = send_with_retry(EndpointClass::Generation, client.post(&url).json(&body)).await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Generation failed: {}", error_text));
+    }
+
+    let text = response.json::<GenerateResponse>().await
+        .map(|generate_response| generate_response.response)
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    crate::response_cache::store(&cache_request, &text);
+    Ok(text)
+}
+
+/// Non-streaming request whose reply Ollama constrains to JSON. `format` is either "json" (any
+/// JSON object) or a JSON schema the reply must match. Returns the parsed reply.
+pub async fn generate_structured(
+    model: &str,
+    prompt: String,
+    system: Option<String>,
+    format: serde_json::Value,
+    options: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    let cache_request = CacheRequest { model, system: system.as_deref(), prompt: &prompt, images: &[], format: Some(&format), options: options.as_ref() };
+    if let Some(cached) = crate::response_cache::lookup(&cache_request).and_then(|text| serde_json::from_str(&text).ok()) {
+        return Ok(cached);
+    }
+    let _permit = generation_permits().acquire_owned().await.map_err(|e| format!("Failed to acquire semaphore: {}", e))?;
+    crate::model_residency::note_model_use(model);
+
+    let client = client(EndpointClass::Generation);
+    let url = format!("{}/api/generate", ollama_base_url());
+    let body = serde_json::json!({
+        "model": model,
+        "prompt": prompt,
+        "system": system,
+        "format": format,
+        "stream": false,
+        "options": options,
+        "keep_alive": crate::model_residency::keep_alive(),
+    });
+
+    let response = send_with_retry(EndpointClass::Generation, client.post(&url).json(&body)).await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Generation failed: {}", error_text));
+    }
+
+    let text = response.json::<GenerateResponse>().await
+        .map(|generate_response| generate_response.response)
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    let reply = serde_json::from_str(&text).map_err(|e| format!("Model reply was not valid JSON: {}", e))?;
+    crate::response_cache::store(&cache_request, &text);
+    Ok(reply)
+}
+
+/// Structured output: the reply is constrained to `schema` (a JSON schema), or to any JSON object
+/// when no schema is given, and returned parsed.
+#[tauri::command]
+pub async fn generate_ollama_structured(
+    model: String,
+    prompt: String,
+    system: Option<String>,
+    schema: Option<serde_json::Value>,
+    options: Option<GenerationOptions>,
+) -> Result<serde_json::Value, String> {
+    let format = schema.unwrap_or_else(|| serde_json::json!("json"));
+    let options = request_options("general", options.as_ref(), estimate_tokens(&prompt));
+    generate_structured(&model, prompt, system, format, options).await
 }
 
 // Additional helper function for custom timeout streaming (for specific use cases)
@@ -1086,18 +2053,11 @@ pub async fn generate_with_custom_timeouts(
     total_timeout_secs: u64,
     chunk_gap_secs: u64,
     max_repeats: usize,
+    options: Option<GenerationOptions>,
 ) -> Result<(), String> {
-    let url = format!("{}/api/generate", OLLAMA_BASE_URL);
+    let url = format!("{}/api/generate", ollama_base_url());
     
-    let gpu_layers = detect_gpu_layers();
-    let options = if gpu_layers > 0 {
-        Some(serde_json::json!({
-            "num_gpu": gpu_layers,
-            "num_thread": 4
-        }))
-    } else {
-        None
-    };
+    let options = request_options("general", options.as_ref(), estimate_tokens(&prompt));
     
     let request = GenerateRequest {
         model: model.clone(),
@@ -1107,13 +2067,14 @@ pub async fn generate_with_custom_timeouts(
         images: None,
         system: None,
         options,
+        keep_alive: crate::model_residency::keep_alive(),
     };
     
     println!("🚀 Starting custom timeout streaming for session: {} (total: {}s, gap: {}s, repeats: {})", 
              session_id, total_timeout_secs, chunk_gap_secs, max_repeats);
     
     // Emit start event
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "start",
         "model": model
     })) {
@@ -1152,8 +2113,9 @@ pub async fn generate_mcp_enabled_response(
     session_id: String,
     mcp_session_id: Option<String>,
     mcp_sessions: tauri::State<'_, MCPSessionManager>,
+    options: Option<GenerationOptions>,
 ) -> Result<(), String> {
-    let url = format!("{}/api/generate", OLLAMA_BASE_URL);
+    let url = format!("{}/api/generate", ollama_base_url());
     
     // Build the enhanced system prompt that includes MCP capabilities
     let system_prompt = build_mcp_system_prompt(mcp_session_id.clone(), &mcp_sessions).await?;
@@ -1169,24 +2131,11 @@ pub async fn generate_mcp_enabled_response(
         build_prompt_with_context(prompt, context)
     };
     
-    // Detect GPU and set acceleration options
-    let gpu_layers = detect_gpu_layers();
-    let options = if gpu_layers > 0 {
-        Some(serde_json::json!({
-            "num_gpu": gpu_layers,
-            "num_thread": 4,
-            "temperature": 0.7,
-            "top_p": 0.9,
-            "repeat_penalty": 1.1
-        }))
-    } else {
-        Some(serde_json::json!({
-            "temperature": 0.7,
-            "top_p": 0.9,
-            "repeat_penalty": 1.1
-        }))
-    };
+    let prompt_tokens = estimate_tokens(&full_prompt) + estimate_tokens(&system_prompt);
+    let options = request_options("mcp", options.as_ref(), prompt_tokens);
     
+    crate::model_residency::note_model_use(&model);
+    let provenance = stream_provenance(&model, &options);
     let request = GenerateRequest {
         model: model.clone(),
         prompt: full_prompt,
@@ -1195,16 +2144,18 @@ pub async fn generate_mcp_enabled_response(
         images: None,
         system: Some(system_prompt),
         options,
+        keep_alive: crate::model_residency::keep_alive(),
     };
     
     println!("🤖 Starting MCP-enabled streaming for session: {} (MCP: {:?})", session_id, mcp_session_id);
     
     // Emit start event
-    if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+    if let Err(e) = crate::event_throttle::emit_critical(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
         "type": "start",
         "model": model,
         "mcp_enabled": mcp_session_id.is_some(),
-        "mcp_session_id": mcp_session_id
+        "mcp_session_id": mcp_session_id,
+        "provenance": provenance
     })) {
         return Err(format!("Failed to emit start event: {}", e));
     }
@@ -1268,19 +2219,33 @@ async fn stream_ollama_response_with_mcp(
     mcp_session_id: Option<String>,
     mcp_sessions: tauri::State<'_, MCPSessionManager>,
 ) -> Result<(), String> {
-    // Register the session as active
-    {
-        let mut sessions = ACTIVE_SESSIONS.lock().unwrap();
-        sessions.insert(session_id.clone(), false);
-    }
+    let cancel_token = register_session(&session_id);
 
-    let client = Arc::clone(&HTTP_CLIENT);
+    let client = client(EndpointClass::Generation);
     
-    // Make request with timeout
-    let response = timeout(Duration::from_secs(30), client.post(&url).json(&request).send())
-        .await
-        .map_err(|_| "Request timeout".to_string())?
-        .map_err(|e| format!("Request failed: {}", e))?;
+    // Make request with timeout; a cancel while waiting for the model to load drops the request
+    let response = tokio::select! {
+        _ = cancel_token.cancelled() => {
+            emit_cancelled(&app_handle, &session_id).await;
+            cleanup_session(&session_id);
+            return Ok(());
+        }
+        response = timeout(Duration::from_secs(30), send_with_retry(EndpointClass::Generation, client.post(&url).json(&request))) => response,
+    };
+    let response = match response {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => {
+            emit_error(&app_handle, &session_id, &e).await;
+            cleanup_session(&session_id);
+            return Err(e);
+        }
+        Err(_) => {
+            let error_msg = "Request timeout".to_string();
+            emit_error(&app_handle, &session_id, &error_msg).await;
+            cleanup_session(&session_id);
+            return Err(error_msg);
+        }
+    };
 
     if !response.status().is_success() {
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
@@ -1292,14 +2257,15 @@ async fn stream_ollama_response_with_mcp(
     }
 
     let mut stream = response.bytes_stream();
-    let mut buffer = Vec::new();
+    let mut lines = NdjsonLines::default();
     let mut state = StreamState::new();
     let mut accumulated_response = String::new();
 
     loop {
         // Check for cancellation
-        if is_session_cancelled(&session_id) {
+        if cancel_token.is_cancelled() {
             println!("🛑 Session cancelled: {}", session_id);
+            emit_cancelled(&app_handle, &session_id).await;
             cleanup_session(&session_id);
             return Ok(());
         }
@@ -1307,13 +2273,16 @@ async fn stream_ollama_response_with_mcp(
         // Check timeouts and patterns
         if let Some(timeout_reason) = state.should_timeout(Duration::from_secs(300), Duration::from_secs(30)) {
             emit_timeout(&app_handle, &session_id, &timeout_reason).await;
-            emit_complete(&app_handle, &session_id).await;
+            emit_complete(&app_handle, &session_id, None).await;
             cleanup_session(&session_id);
             return Err(timeout_reason);
         }
 
         // Read next chunk
-        let chunk_result = timeout(Duration::from_secs(10), stream.next()).await;
+        let chunk_result = tokio::select! {
+            _ = cancel_token.cancelled() => continue,
+            chunk_result = timeout(Duration::from_secs(10), stream.next()) => chunk_result,
+        };
         
         let chunk_result = match chunk_result {
             Ok(Some(chunk_result)) => chunk_result,
@@ -1323,13 +2292,13 @@ async fn stream_ollama_response_with_mcp(
                     process_tool_calls(&accumulated_response, &mcp_session_id.unwrap(), &mcp_sessions, &app_handle, &session_id).await;
                 }
                 
-                emit_complete(&app_handle, &session_id).await;
+                emit_complete(&app_handle, &session_id, None).await;
                 cleanup_session(&session_id);
                 return Ok(());
             }
             Err(_) => {
                 emit_timeout(&app_handle, &session_id, "Chunk read timeout").await;
-                emit_complete(&app_handle, &session_id).await;
+                emit_complete(&app_handle, &session_id, None).await;
                 cleanup_session(&session_id);
                 return Err("Chunk read timeout".to_string());
             }
@@ -1337,23 +2306,14 @@ async fn stream_ollama_response_with_mcp(
 
         match chunk_result {
             Ok(chunk) => {
-                buffer.extend_from_slice(&chunk);
-
-                while let Some(newline_pos) = buffer.iter().position(|&b| b == b'\n') {
-                    let line = buffer.drain(..=newline_pos).collect::<Vec<u8>>();
-                    let line_str = String::from_utf8_lossy(&line[..line.len()-1]);
-
-                    if line_str.trim().is_empty() {
-                        continue;
-                    }
-
+                for line_str in lines.push(&chunk) {
                     match serde_json::from_str::<GenerateResponse>(&line_str) {
                         Ok(response_chunk) => {
                             match state.update_chunk(&response_chunk.response) {
                                 ChunkResult::Continue => {},
                                 ChunkResult::Exit(reason) => {
                                     emit_termination(&app_handle, &session_id, &reason, state.chunk_count, state.repeat_count).await;
-                                    emit_complete(&app_handle, &session_id).await;
+                                    emit_complete(&app_handle, &session_id, None).await;
                                     cleanup_session(&session_id);
                                     return Ok(());
                                 }
@@ -1372,18 +2332,17 @@ async fn stream_ollama_response_with_mcp(
                             }
 
                             if !response_chunk.response.is_empty() || response_chunk.done {
-                                if let Err(e) = app_handle.emit(&format!("ollama-stream-{}", session_id), serde_json::json!({
+                                crate::event_throttle::emit_throttled(&app_handle, &format!("ollama-stream-{}", session_id), serde_json::json!({
                                     "type": "chunk",
                                     "text": response_chunk.response,
                                     "done": response_chunk.done,
                                     "mcp_enabled": mcp_session_id.is_some()
-                                })) {
-                                    eprintln!("Failed to emit chunk event: {}", e);
-                                }
+                                }));
                             }
 
                             if response_chunk.done {
-                                emit_complete(&app_handle, &session_id).await;
+                                let usage = TokenUsage::from_counts(response_chunk.prompt_eval_count, response_chunk.eval_count);
+                                emit_complete(&app_handle, &session_id, usage).await;
                                 cleanup_session(&session_id);
                                 return Ok(());
                             }
@@ -1486,6 +2445,7 @@ pub async fn create_mcp_session_for_ai(
         enable_logging: true,
         server_name: "enteract-ai-mcp".to_string(),
         server_version: "1.0.0".to_string(),
+        ..MCPSessionConfig::default()
     };
     
     let session_info = crate::mcp::commands::start_mcp_session(
@@ -1503,4 +2463,101 @@ pub async fn get_mcp_session_for_ai(
     mcp_sessions: tauri::State<'_, MCPSessionManager>,
 ) -> Result<crate::mcp::types::MCPSessionInfo, String> {
     crate::mcp::commands::get_mcp_session_info(mcp_session_id, mcp_sessions).await
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_ollama::{MockOllama, MOCK_MODEL};
+
+    #[tokio::test]
+    async fn test_generate_completion_against_mock_server() {
+        let mock = MockOllama::start(&["Hel", "lo"]).await;
+        let text = generate_completion(MOCK_MODEL, "Say hello".to_string(), Some("Be brief".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(text, "Hello");
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/api/generate");
+        assert_eq!(requests[0].body["stream"], false);
+        assert_eq!(requests[0].body["system"], "Be brief");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_lines_are_reassembled_across_network_chunks() {
+        let mock = MockOllama::start(&["The ", "quick ", "fox"]).await;
+        let request = GenerateRequest {
+            model: MOCK_MODEL.to_string(),
+            prompt: "Finish the sentence".to_string(),
+            stream: Some(true),
+            context: None,
+            images: None,
+            system: None,
+            options: None,
+            keep_alive: None,
+        };
+        let response = client(EndpointClass::Generation)
+            .post(format!("{}/api/generate", ollama_base_url()))
+            .json(&request)
+            .send()
+            .await
+            .unwrap();
+
+        let mut stream = response.bytes_stream();
+        let mut lines = NdjsonLines::default();
+        let mut state = StreamState::new();
+        let mut done = false;
+        while let Some(chunk) = stream.next().await {
+            for line in lines.push(&chunk.unwrap()) {
+                let chunk: GenerateResponse = serde_json::from_str(&line).unwrap();
+                assert!(matches!(state.update_chunk(&chunk.response), ChunkResult::Continue));
+                state.text.push_str(&chunk.response);
+                done |= chunk.done;
+            }
+        }
+        assert!(done);
+        assert_eq!(state.text, "The quick fox");
+        assert_eq!(mock.requests()[0].body["prompt"], "Finish the sentence");
+    }
+
+    #[tokio::test]
+    async fn test_chat_endpoint_gets_role_structured_history() {
+        let mock = MockOllama::start(&["Paris", " it is"]).await;
+        let context = vec![
+            ChatContextMessage { role: "user".to_string(), content: "Capital of France?".to_string() },
+            ChatContextMessage { role: "assistant".to_string(), content: "Paris.".to_string() },
+            ChatContextMessage { role: "narrator".to_string(), content: "Later that day".to_string() },
+        ];
+        let request = ChatRequest {
+            model: MOCK_MODEL.to_string(),
+            messages: build_chat_messages("Be brief", "Are you sure?".to_string(), Some(context)),
+            stream: Some(true),
+            options: None,
+            keep_alive: None,
+        };
+        let response = client(EndpointClass::Generation)
+            .post(format!("{}/api/chat", ollama_base_url()))
+            .json(&request)
+            .send()
+            .await
+            .unwrap();
+
+        let mut stream = response.bytes_stream();
+        let mut lines = NdjsonLines::default();
+        let mut text = String::new();
+        while let Some(chunk) = stream.next().await {
+            for line in lines.push(&chunk.unwrap()) {
+                let line: StreamLine = serde_json::from_str(&line).unwrap();
+                text.push_str(line.text());
+            }
+        }
+        assert_eq!(text, "Paris it is");
+
+        let body = &mock.requests()[0].body;
+        let roles: Vec<&str> = body["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "user", "user"]);
+        assert_eq!(body["messages"][3]["content"], "narrator: Later that day");
+        assert_eq!(body["messages"][4]["content"], "Are you sure?");
+    }
+}
@@ -0,0 +1,191 @@
+// src-tauri/src/ollama_embedding_service.rs
+// Real embeddings from an Ollama embedding model (nomic-embed-text by default) for the RAG
+// pipeline, selected with the `provider` toggle in the RAG embedding settings. Texts go to
+// /api/embed in batches; servers too old for it get one /api/embeddings request per text.
+// Embeddings are cached on disk keyed by model and a hash of the content, so re-indexing a
+// document or repeating a query doesn't ask the model again.
+use anyhow::{Result, anyhow};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use crate::ollama_http::EndpointClass;
+
+// Texts per /api/embed request
+const OLLAMA_BATCH_SIZE: usize = 32;
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Deserialize)]
+struct LegacyEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+#[derive(Clone)]
+pub struct OllamaEmbeddingService {
+    cache_db: PathBuf,
+}
+
+fn content_hash(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(text.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn encode(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn decode(bytes: &[u8]) -> Vec<f32> {
+    bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect()
+}
+
+impl OllamaEmbeddingService {
+    pub fn new(cache_dir: &Path) -> Self {
+        Self { cache_db: cache_dir.join("ollama_embeddings.db") }
+    }
+
+    fn open_cache(&self) -> Result<Connection> {
+        let conn = Connection::open(&self.cache_db)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS embedding_cache (
+                model TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (model, content_hash)
+            )",
+            [],
+        )?;
+        Ok(conn)
+    }
+
+    fn cached(&self, conn: &Connection, model: &str, hash: &str) -> Result<Option<Vec<f32>>> {
+        let bytes: Option<Vec<u8>> = conn.query_row(
+            "SELECT embedding FROM embedding_cache WHERE model = ?1 AND content_hash = ?2",
+            params![model, hash],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(bytes.map(|bytes| decode(&bytes)))
+    }
+
+    fn store(&self, conn: &Connection, model: &str, hash: &str, embedding: &[f32]) -> Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO embedding_cache (model, content_hash, embedding, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![model, hash, encode(embedding), chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Embed `texts` with `model`, in order. Cached texts aren't sent to Ollama.
+    pub async fn embed(&self, model: &str, normalize: bool, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        let hashes: Vec<String> = texts.iter().map(|text| content_hash(text)).collect();
+        let mut embeddings: Vec<Option<Vec<f32>>> = {
+            let conn = self.open_cache()?;
+            hashes.iter().map(|hash| self.cached(&conn, model, hash)).collect::<Result<_>>()?
+        };
+
+        let missing: Vec<usize> = (0..texts.len()).filter(|&i| embeddings[i].is_none()).collect();
+        for batch in missing.chunks(OLLAMA_BATCH_SIZE) {
+            let batch_texts: Vec<&str> = batch.iter().map(|&i| texts[i].as_str()).collect();
+            let batch_embeddings = request_embeddings(model, &batch_texts).await?;
+            if batch_embeddings.len() != batch.len() {
+                return Err(anyhow!("Ollama returned {} embeddings for {} texts", batch_embeddings.len(), batch.len()));
+            }
+            let conn = self.open_cache()?;
+            for (&i, mut embedding) in batch.iter().zip(batch_embeddings) {
+                if normalize {
+                    crate::simple_embedding_service::normalize_embedding(&mut embedding);
+                }
+                self.store(&conn, model, &hashes[i], &embedding)?;
+                embeddings[i] = Some(embedding);
+            }
+        }
+        if !missing.is_empty() {
+            crate::model_residency::note_model_use(model);
+        }
+
+        Ok(embeddings.into_iter().map(|embedding| embedding.unwrap_or_default()).collect())
+    }
+
+    /// Forget every cached embedding, returning how many there were.
+    pub fn clear_cache(&self) -> Result<usize> {
+        let conn = self.open_cache()?;
+        Ok(conn.execute("DELETE FROM embedding_cache", [])?)
+    }
+}
+
+async fn request_embeddings(model: &str, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    let client = crate::ollama_http::client(EndpointClass::Generation);
+    let request = client
+        .post(format!("{}/api/embed", crate::ollama::ollama_base_url()))
+        .json(&serde_json::json!({
+            "model": model,
+            "input": texts,
+            "keep_alive": crate::model_residency::keep_alive(),
+        }));
+    let response = crate::ollama_retry::send_with_retry(EndpointClass::Generation, request)
+        .await
+        .map_err(|e| anyhow!(e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        let body = response.text().await.unwrap_or_default();
+        // A missing model is also a 404; only fall back when the endpoint itself is missing
+        if body.contains("model") {
+            return Err(anyhow!("Embedding model {} is not installed. Pull it with `ollama pull {}`.", model, model));
+        }
+        return request_embeddings_one_by_one(model, texts).await;
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(anyhow!("Ollama embedding request failed ({}): {}", status, body));
+    }
+    let parsed: EmbedResponse = response.json().await?;
+    Ok(parsed.embeddings)
+}
+
+// For Ollama versions before /api/embed, which embed one prompt per request
+async fn request_embeddings_one_by_one(model: &str, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    let client = crate::ollama_http::client(EndpointClass::Generation);
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for text in texts {
+        let request = client
+            .post(format!("{}/api/embeddings", crate::ollama::ollama_base_url()))
+            .json(&serde_json::json!({ "model": model, "prompt": text }));
+        let response = crate::ollama_retry::send_with_retry(EndpointClass::Generation, request)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Ollama embedding request failed ({}): {}", status, body));
+        }
+        let parsed: LegacyEmbeddingResponse = response.json().await?;
+        embeddings.push(parsed.embedding);
+    }
+    Ok(embeddings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_cache_is_keyed_by_model_and_content() {
+        let temp_dir = tempdir().unwrap();
+        let service = OllamaEmbeddingService::new(temp_dir.path());
+        let conn = service.open_cache().unwrap();
+        let hash = content_hash("Quarterly revenue grew 12%");
+
+        service.store(&conn, "nomic-embed-text", &hash, &[0.25, -0.5, 1.0]).unwrap();
+        assert_eq!(service.cached(&conn, "nomic-embed-text", &hash).unwrap(), Some(vec![0.25, -0.5, 1.0]));
+        assert_eq!(service.cached(&conn, "mxbai-embed-large", &hash).unwrap(), None);
+        assert_eq!(service.cached(&conn, "nomic-embed-text", &content_hash("Quarterly revenue grew 13%")).unwrap(), None);
+        assert_eq!(service.clear_cache().unwrap(), 1);
+    }
+}
@@ -0,0 +1,460 @@
+// src-tauri/src/ollama_http.rs
+// HTTP clients for Ollama, one per endpoint class. Control-plane calls (model lists, status,
+// model info, delete/copy) should answer in seconds and fail fast; generation calls (completions,
+// streams, pulls, model loads) can legitimately run for minutes and get no overall timeout, since
+// the streaming code has its own chunk timeouts. Pool sizes, timeouts, retry policy and the number
+// of concurrent generations are settings, re-read when the settings file changes on disk or is
+// saved, so tuning doesn't need a restart.
+// The same settings say where Ollama is: a remote machine or a Docker container instead of
+// localhost, optionally behind a proxy that wants a bearer token on every request. The token lives
+// in the OS keychain, never in the settings file, and the frontend only ever sees it masked.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use tokio::sync::Semaphore;
+
+// How often a request may look at the settings file's modification time
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+pub(crate) const KEYCHAIN_SERVICE: &str = "enteract";
+const KEYCHAIN_TOKEN_ACCOUNT: &str = "ollama-bearer-token";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointClass {
+    Control,
+    Generation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EndpointSettings {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    pub request_timeout_secs: Option<u64>, // None: no overall limit
+    pub max_retries: u32,                  // Retries of a request that couldn't connect
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for EndpointSettings {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 4,
+            pool_idle_timeout_secs: 60,
+            connect_timeout_secs: 3,
+            request_timeout_secs: Some(10),
+            max_retries: 3,
+            initial_backoff_ms: 250,
+            max_backoff_ms: 4000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OllamaHttpSettings {
+    pub control: EndpointSettings,
+    pub generation: EndpointSettings,
+    pub max_concurrent_generations: usize,
+    pub base_url: Option<String>,     // None: ENTERACT_OLLAMA_URL, or Ollama on localhost
+    pub bearer_token: Option<String>, // Sent as `Authorization: Bearer` with every Ollama request; kept in the keychain
+}
+
+// What get_ollama_http_settings shows: the token masked, and where requests actually go
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaHttpSettingsView {
+    #[serde(flatten)]
+    pub settings: OllamaHttpSettings,
+    pub env_base_url: Option<String>, // ENTERACT_OLLAMA_URL, which overrides base_url while it's set
+    pub effective_base_url: String,
+}
+
+impl Default for OllamaHttpSettings {
+    fn default() -> Self {
+        Self {
+            control: EndpointSettings::default(),
+            generation: EndpointSettings {
+                pool_max_idle_per_host: 16,
+                connect_timeout_secs: 5,
+                request_timeout_secs: None,
+                max_retries: 2,
+                initial_backoff_ms: 500,
+                ..EndpointSettings::default()
+            },
+            max_concurrent_generations: 4,
+            base_url: None,
+            bearer_token: None,
+        }
+    }
+}
+
+impl OllamaHttpSettings {
+    pub fn endpoint(&self, class: EndpointClass) -> &EndpointSettings {
+        match class {
+            EndpointClass::Control => &self.control,
+            EndpointClass::Generation => &self.generation,
+        }
+    }
+}
+
+struct Clients {
+    settings: OllamaHttpSettings,
+    modified: Option<SystemTime>,
+    checked_at: Instant,
+    control: Arc<reqwest::Client>,
+    generation: Arc<reqwest::Client>,
+    generation_permits: Arc<Semaphore>,
+}
+
+lazy_static::lazy_static! {
+    static ref CLIENTS: Mutex<Option<Clients>> = Mutex::new(None);
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("ollama_http.json")
+}
+
+fn settings_modified() -> Option<SystemTime> {
+    get_settings_path().ok().and_then(|path| fs::metadata(path).ok()).and_then(|metadata| metadata.modified().ok())
+}
+
+fn token_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_TOKEN_ACCOUNT).map_err(|e| format!("Failed to open the system keychain: {}", e))
+}
+
+fn load_token() -> Option<String> {
+    let entry = token_entry().map_err(|e| eprintln!("{}", e)).ok()?;
+    match entry.get_password() {
+        Ok(token) => Some(token),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            eprintln!("Failed to read the Ollama token from the keychain: {}", e);
+            None
+        }
+    }
+}
+
+fn store_token(token: Option<&str>) -> Result<(), String> {
+    let entry = token_entry()?;
+    let result = match token {
+        Some(token) => entry.set_password(token),
+        None => match entry.delete_password() {
+            Err(keyring::Error::NoEntry) => Ok(()),
+            result => result,
+        },
+    };
+    result.map_err(|e| format!("Failed to store the Ollama token in the system keychain: {}", e))
+}
+
+// Everything but the token, which goes to the keychain
+fn write_settings_file(settings: &OllamaHttpSettings) -> Result<(), String> {
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&OllamaHttpSettings { bearer_token: None, ..settings.clone() })
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+fn load_settings() -> OllamaHttpSettings {
+    let mut settings: OllamaHttpSettings = get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    match settings.bearer_token.clone() {
+        // Earlier builds saved the token in the file; move it to the keychain
+        Some(token) => match store_token(Some(&token)).and_then(|_| write_settings_file(&settings)) {
+            Ok(()) => println!("🔐 Moved the Ollama token from the settings file to the system keychain"),
+            Err(e) => eprintln!("{}", e),
+        },
+        None => settings.bearer_token = load_token(),
+    }
+    settings
+}
+
+/// Enough of a token to recognize it: the last four characters.
+pub(crate) fn mask_token(token: &str) -> String {
+    let chars: Vec<char> = token.chars().collect();
+    let tail: String = if chars.len() > 8 { chars[chars.len() - 4..].iter().collect() } else { String::new() };
+    format!("••••••••{}", tail)
+}
+
+// The masked token from get_ollama_http_settings coming back unchanged means the saved one
+fn unmask(token: Option<String>) -> Option<String> {
+    let current = with_clients(|clients| clients.settings.bearer_token.clone());
+    if token.is_some() && token == current.as_deref().map(mask_token) {
+        current
+    } else {
+        token
+    }
+}
+
+/// An Ollama server URL without the trailing slash, e.g. "http://192.168.1.20:11434".
+fn normalize_base_url(url: &str) -> Result<String, String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid Ollama URL '{}': {}", url.trim(), e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Ollama URL must use http or https, not {}", parsed.scheme()));
+    }
+    if parsed.host_str().is_none() {
+        return Err("Ollama URL has no host".to_string());
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err("Ollama URL can't have a query or fragment".to_string());
+    }
+    Ok(parsed.as_str().trim_end_matches('/').to_string())
+}
+
+// Blank strings from the settings form mean "not set"
+fn normalize(settings: OllamaHttpSettings) -> Result<OllamaHttpSettings, String> {
+    let base_url = match settings.base_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+        Some(url) => Some(normalize_base_url(url)?),
+        None => None,
+    };
+    let bearer_token = settings.bearer_token.map(|token| token.trim().to_string()).filter(|token| !token.is_empty());
+    Ok(OllamaHttpSettings { base_url, bearer_token, ..settings })
+}
+
+fn auth_header_map(bearer_token: Option<&str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(token) = bearer_token {
+        match HeaderValue::from_str(&format!("Bearer {}", token)) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                headers.insert(AUTHORIZATION, value);
+            }
+            Err(_) => eprintln!("Ollama bearer token contains characters not allowed in a header; not sending it"),
+        }
+    }
+    headers
+}
+
+fn validate(settings: &OllamaHttpSettings) -> Result<(), String> {
+    for (name, endpoint) in [("control", &settings.control), ("generation", &settings.generation)] {
+        if endpoint.connect_timeout_secs == 0 || endpoint.request_timeout_secs == Some(0) {
+            return Err(format!("{} timeouts must be at least 1 second", name));
+        }
+        if endpoint.initial_backoff_ms > endpoint.max_backoff_ms {
+            return Err(format!("{} initial backoff is longer than the maximum backoff", name));
+        }
+    }
+    if settings.max_concurrent_generations == 0 {
+        return Err("At least one generation must be allowed at a time".to_string());
+    }
+    Ok(())
+}
+
+fn build_client(settings: &EndpointSettings, bearer_token: Option<&str>) -> reqwest::Client {
+    let mut builder = reqwest::Client::builder()
+        .default_headers(auth_header_map(bearer_token))
+        .pool_max_idle_per_host(settings.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_timeout_secs))
+        .tcp_keepalive(Some(Duration::from_secs(60)))
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs.max(1)));
+    if let Some(timeout) = settings.request_timeout_secs {
+        builder = builder.timeout(Duration::from_secs(timeout.max(1)));
+    }
+    builder.build().unwrap_or_else(|e| {
+        eprintln!("Failed to build Ollama HTTP client, using defaults: {}", e);
+        reqwest::Client::new()
+    })
+}
+
+fn build(settings: OllamaHttpSettings, modified: Option<SystemTime>, previous: Option<&Clients>) -> Clients {
+    // Only rebuild what changed, so pooled connections survive unrelated edits. A new server or
+    // token replaces both clients: their pools and default headers belong to the old one.
+    let reuse = |class: EndpointClass| {
+        previous
+            .filter(|previous| previous.settings.endpoint(class) == settings.endpoint(class))
+            .filter(|previous| previous.settings.base_url == settings.base_url && previous.settings.bearer_token == settings.bearer_token)
+            .map(|previous| match class {
+                EndpointClass::Control => previous.control.clone(),
+                EndpointClass::Generation => previous.generation.clone(),
+            })
+            .unwrap_or_else(|| Arc::new(build_client(settings.endpoint(class), settings.bearer_token.as_deref())))
+    };
+    // Generations already running keep their permit on the old semaphore until they finish
+    let generation_permits = previous
+        .filter(|previous| previous.settings.max_concurrent_generations == settings.max_concurrent_generations)
+        .map(|previous| previous.generation_permits.clone())
+        .unwrap_or_else(|| Arc::new(Semaphore::new(settings.max_concurrent_generations.max(1))));
+    Clients {
+        control: reuse(EndpointClass::Control),
+        generation: reuse(EndpointClass::Generation),
+        generation_permits,
+        settings,
+        modified,
+        checked_at: Instant::now(),
+    }
+}
+
+// Run `read` on the current clients, reloading the settings first if the file changed
+fn with_clients<T>(read: impl FnOnce(&Clients) -> T) -> T {
+    let mut clients = CLIENTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let stale = match clients.as_ref() {
+        None => true,
+        Some(current) if current.checked_at.elapsed() >= RELOAD_CHECK_INTERVAL => {
+            let modified = settings_modified();
+            if modified != current.modified {
+                println!("🔄 Ollama HTTP settings changed on disk; reloading");
+                true
+            } else {
+                false
+            }
+        }
+        Some(_) => false,
+    };
+    if stale {
+        let modified = settings_modified();
+        *clients = Some(build(load_settings(), modified, clients.as_ref()));
+    } else if let Some(current) = clients.as_mut() {
+        if current.checked_at.elapsed() >= RELOAD_CHECK_INTERVAL {
+            current.checked_at = Instant::now();
+        }
+    }
+    read(clients.as_ref().expect("clients were just built"))
+}
+
+/// The shared client for an endpoint class.
+pub fn client(class: EndpointClass) -> Arc<reqwest::Client> {
+    with_clients(|clients| match class {
+        EndpointClass::Control => clients.control.clone(),
+        EndpointClass::Generation => clients.generation.clone(),
+    })
+}
+
+/// Pool, timeout and retry settings currently in effect for an endpoint class.
+pub fn endpoint_settings(class: EndpointClass) -> EndpointSettings {
+    with_clients(|clients| clients.settings.endpoint(class).clone())
+}
+
+/// Limits how many generations run against Ollama at once (memory safety).
+pub fn generation_permits() -> Arc<Semaphore> {
+    with_clients(|clients| clients.generation_permits.clone())
+}
+
+/// The Ollama server saved in the settings, if one is.
+pub fn configured_base_url() -> Option<String> {
+    with_clients(|clients| clients.settings.base_url.clone())
+}
+
+/// Authorization for clients built outside this module (long-running model loads and creates).
+pub fn auth_headers() -> HeaderMap {
+    with_clients(|clients| auth_header_map(clients.settings.bearer_token.as_deref()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaConnectionTest {
+    pub url: String,
+    pub version: Option<String>,
+    pub model_count: usize,
+    pub latency_ms: u64,
+}
+
+/// Check an Ollama server before saving it: it must answer /api/version and list its models with
+/// the given token. Leaving both out tests the server the app currently uses.
+#[tauri::command]
+pub async fn test_ollama_connection(base_url: Option<String>, bearer_token: Option<String>) -> Result<OllamaConnectionTest, String> {
+    let bearer_token = unmask(bearer_token);
+    let (url, token) = match base_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+        Some(url) => (normalize_base_url(url)?, bearer_token.map(|token| token.trim().to_string()).filter(|token| !token.is_empty())),
+        None => (
+            crate::ollama::ollama_base_url(),
+            bearer_token.or_else(|| with_clients(|clients| clients.settings.bearer_token.clone())),
+        ),
+    };
+    let client = reqwest::Client::builder()
+        .default_headers(auth_header_map(token.as_deref()))
+        .connect_timeout(Duration::from_secs(5))
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let started = Instant::now();
+    let get = |path: &str| client.get(format!("{}{}", url, path)).send();
+    let version = get("/api/version").await.map_err(|e| format!("Could not reach Ollama at {}: {}", url, e))?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let check = |response: &reqwest::Response| match response.status() {
+        status if status.is_success() => Ok(()),
+        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+            Err(format!("{} refused the request ({}); check the bearer token", url, response.status()))
+        }
+        status => Err(format!("{} answered with {}; is this an Ollama server?", url, status)),
+    };
+    check(&version)?;
+    let version: serde_json::Value = version.json().await.map_err(|_| format!("{} didn't answer like an Ollama server", url))?;
+
+    let tags = get("/api/tags").await.map_err(|e| format!("Failed to list models at {}: {}", url, e))?;
+    check(&tags)?;
+    let tags: serde_json::Value = tags.json().await.map_err(|e| format!("Failed to parse the model list: {}", e))?;
+
+    println!("🔌 Ollama at {} answered in {}ms", url, latency_ms);
+    Ok(OllamaConnectionTest {
+        version: version["version"].as_str().map(str::to_string),
+        model_count: tags["models"].as_array().map_or(0, |models| models.len()),
+        latency_ms,
+        url,
+    })
+}
+
+#[tauri::command]
+pub fn get_ollama_http_settings() -> Result<OllamaHttpSettingsView, String> {
+    let mut settings = with_clients(|clients| clients.settings.clone());
+    settings.bearer_token = settings.bearer_token.as_deref().map(mask_token);
+    Ok(OllamaHttpSettingsView {
+        settings,
+        env_base_url: crate::ollama::env_base_url(),
+        effective_base_url: crate::ollama::ollama_base_url(),
+    })
+}
+
+#[tauri::command]
+pub fn save_ollama_http_settings(settings: OllamaHttpSettings) -> Result<(), String> {
+    let settings = normalize(OllamaHttpSettings { bearer_token: unmask(settings.bearer_token.clone()), ..settings })?;
+    validate(&settings)?;
+    store_token(settings.bearer_token.as_deref())?;
+    write_settings_file(&settings)?;
+
+    let mut clients = CLIENTS.lock().map_err(|e| e.to_string())?;
+    *clients = Some(build(settings, settings_modified(), clients.as_ref()));
+    println!("💾 Saved Ollama HTTP settings (server: {})", settings.base_url.as_deref().unwrap_or("default"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unchanged_classes_keep_their_client_across_reloads() {
+        let first = build(OllamaHttpSettings::default(), None, None);
+        let mut settings = OllamaHttpSettings::default();
+        settings.control.request_timeout_secs = Some(5);
+        let second = build(settings.clone(), None, Some(&first));
+
+        assert!(!Arc::ptr_eq(&first.control, &second.control));
+        assert!(Arc::ptr_eq(&first.generation, &second.generation));
+        assert!(Arc::ptr_eq(&first.generation_permits, &second.generation_permits));
+
+        // A new token rebuilds both clients, so no pooled connection carries the old header
+        let third = build(OllamaHttpSettings { bearer_token: Some("t0k3n".to_string()), ..settings.clone() }, None, Some(&second));
+        assert!(!Arc::ptr_eq(&second.generation, &third.generation));
+
+        settings.max_concurrent_generations = 0;
+        assert!(validate(&settings).is_err());
+        assert!(validate(&OllamaHttpSettings::default()).is_ok());
+
+        let remote = OllamaHttpSettings { base_url: Some(" http://gpu-box.local:11434/ ".to_string()), bearer_token: Some(" ".to_string()), ..Default::default() };
+        let remote = normalize(remote).unwrap();
+        assert_eq!(remote.base_url.as_deref(), Some("http://gpu-box.local:11434"));
+        assert_eq!(remote.bearer_token, None);
+        assert!(normalize_base_url("ftp://gpu-box.local").is_err());
+        assert!(normalize_base_url("localhost:11434").is_err());
+
+        assert_eq!(mask_token("sk-proxy-0123456789abcd"), "••••••••abcd");
+        assert_eq!(mask_token("short"), "••••••••");
+    }
+}
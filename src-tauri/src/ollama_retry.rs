@@ -0,0 +1,227 @@
+// src-tauri/src/ollama_retry.rs
+// Retries and a circuit breaker for requests to Ollama. A request that can't connect is retried
+// with exponential backoff (the policy is per endpoint class, see ollama_http); once enough requests in a row have failed, the breaker opens and
+// further requests fail straight away for a cool-down instead of each waiting out its retries. The
+// app is told with an `ollama-unavailable` event (and `ollama-available` when a request gets
+// through again) so the UI can ask the user to start Ollama once. Only connection failures count:
+// an error reply from a running Ollama is passed on as is.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use crate::ollama_http::{EndpointClass, EndpointSettings};
+
+pub const UNAVAILABLE_MESSAGE: &str = "Ollama is not running. Start Ollama and try again.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrySettings {
+    pub failure_threshold: u32, // Failed requests in a row that open the breaker
+    pub cooldown_secs: u64,     // How long an open breaker fails requests without trying
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown_secs: 15,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    app_handle: Option<AppHandle>,
+}
+
+lazy_static::lazy_static! {
+    static ref BREAKER: Mutex<Breaker> = Mutex::new(Breaker::default());
+    static ref SETTINGS: Mutex<Option<RetrySettings>> = Mutex::new(None);
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("ollama_retry.json")
+}
+
+// Read once and kept: every Ollama request goes through here
+fn load_settings() -> RetrySettings {
+    let mut cached = match SETTINGS.lock() {
+        Ok(cached) => cached,
+        Err(_) => return RetrySettings::default(),
+    };
+    cached
+        .get_or_insert_with(|| {
+            get_settings_path()
+                .ok()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+pub fn init_ollama_retry(app_handle: AppHandle) {
+    if let Ok(mut breaker) = BREAKER.lock() {
+        breaker.app_handle = Some(app_handle);
+    }
+}
+
+/// Delay before retry number `attempt` (0-based), doubling up to the maximum.
+fn backoff(policy: &EndpointSettings, attempt: u32) -> Duration {
+    let delay = policy.initial_backoff_ms.saturating_mul(1u64 << attempt.min(16));
+    Duration::from_millis(delay.min(policy.max_backoff_ms))
+}
+
+impl Breaker {
+    fn is_open(&self, now: Instant) -> bool {
+        self.open_until.is_some_and(|until| now < until)
+    }
+
+    // True when this closed an open breaker
+    fn succeed(&mut self) -> bool {
+        self.consecutive_failures = 0;
+        self.open_until.take().is_some()
+    }
+
+    // True when this opened a closed breaker. A failure while half-open (cool-down over, not yet
+    // recovered) re-opens it quietly; the app already knows Ollama is down.
+    fn fail(&mut self, settings: &RetrySettings, now: Instant) -> bool {
+        self.consecutive_failures += 1;
+        let was_tripped = self.open_until.is_some();
+        if self.consecutive_failures >= settings.failure_threshold.max(1) {
+            self.open_until = Some(now + Duration::from_secs(settings.cooldown_secs));
+        }
+        !was_tripped && self.open_until.is_some()
+    }
+}
+
+fn is_open() -> bool {
+    BREAKER.lock().map(|breaker| breaker.is_open(Instant::now())).unwrap_or(false)
+}
+
+fn emit(app_handle: Option<AppHandle>, event: &str, payload: serde_json::Value) {
+    if let Some(app_handle) = app_handle {
+        if let Err(e) = app_handle.emit(event, payload) {
+            eprintln!("Failed to emit {}: {}", event, e);
+        }
+    }
+}
+
+/// A request reached Ollama; closes the breaker if it was open.
+pub fn record_success() {
+    let recovered = match BREAKER.lock() {
+        Ok(mut breaker) => breaker.succeed().then(|| breaker.app_handle.clone()),
+        Err(_) => None,
+    };
+    if let Some(app_handle) = recovered {
+        println!("✅ Ollama is reachable again");
+        emit(app_handle, "ollama-available", serde_json::json!({}));
+    }
+}
+
+/// A request couldn't reach Ollama even after its retries.
+pub fn record_failure(settings: &RetrySettings, error: &str) {
+    let opened = match BREAKER.lock() {
+        Ok(mut breaker) => breaker.fail(settings, Instant::now()).then(|| breaker.app_handle.clone()),
+        Err(_) => None,
+    };
+    if let Some(app_handle) = opened {
+        println!("🔌 Ollama unreachable after {} failed requests; pausing requests for {}s", settings.failure_threshold, settings.cooldown_secs);
+        emit(app_handle, "ollama-unavailable", serde_json::json!({
+            "error": error,
+            "retryAfterSecs": settings.cooldown_secs,
+            "message": UNAVAILABLE_MESSAGE,
+        }));
+    }
+}
+
+/// Send a request to Ollama, retrying connection failures with backoff. Fails straight away while
+/// the breaker is open. Any HTTP reply counts as Ollama being up, whatever its status.
+pub async fn send_with_retry(class: EndpointClass, request: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+    let settings = load_settings();
+    let policy = crate::ollama_http::endpoint_settings(class);
+    if is_open() {
+        return Err(UNAVAILABLE_MESSAGE.to_string());
+    }
+
+    let mut attempt = 0;
+    let mut request = request;
+    loop {
+        // Bodies here are JSON and clone fine; a request that can't be cloned gets one try
+        let retry = request.try_clone();
+        match request.send().await {
+            Ok(response) => {
+                record_success();
+                return Ok(response);
+            }
+            Err(e) if e.is_connect() && attempt < policy.max_retries && !is_open() => {
+                let Some(next) = retry else {
+                    record_failure(&settings, &e.to_string());
+                    return Err(format!("Failed to connect to Ollama: {}. Make sure Ollama is running.", e));
+                };
+                tokio::time::sleep(backoff(&policy, attempt)).await;
+                attempt += 1;
+                request = next;
+            }
+            Err(e) if e.is_connect() => {
+                record_failure(&settings, &e.to_string());
+                return Err(format!("Failed to connect to Ollama: {}. Make sure Ollama is running.", e));
+            }
+            Err(e) => return Err(format!("Request failed: {}", e)),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_ollama_retry_settings() -> Result<RetrySettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_ollama_retry_settings(settings: RetrySettings) -> Result<(), String> {
+    if settings.failure_threshold == 0 {
+        return Err("Failure threshold must be at least 1".to_string());
+    }
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    if let Ok(mut cached) = SETTINGS.lock() {
+        *cached = Some(settings.clone());
+    }
+    println!("💾 Saved Ollama retry settings: breaker after {} failures, {}s cool-down", settings.failure_threshold, settings.cooldown_secs);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_and_breaker() {
+        let policy = EndpointSettings { initial_backoff_ms: 100, max_backoff_ms: 1000, ..EndpointSettings::default() };
+        assert_eq!(backoff(&policy, 0), Duration::from_millis(100));
+        assert_eq!(backoff(&policy, 2), Duration::from_millis(400));
+        assert_eq!(backoff(&policy, 10), Duration::from_millis(1000));
+
+        let settings = RetrySettings { failure_threshold: 2, cooldown_secs: 60 };
+
+        let now = Instant::now();
+        let mut breaker = Breaker::default();
+        assert!(!breaker.fail(&settings, now));
+        assert!(breaker.fail(&settings, now));
+        assert!(breaker.is_open(now + Duration::from_secs(30)));
+        // Half-open after the cool-down: one more failure opens it again without a second event
+        let later = now + Duration::from_secs(61);
+        assert!(!breaker.is_open(later));
+        assert!(!breaker.fail(&settings, later));
+        assert!(breaker.is_open(later));
+        assert!(breaker.succeed());
+        assert!(!breaker.is_open(later));
+        assert!(!breaker.succeed());
+    }
+}
@@ -0,0 +1,939 @@
+// src-tauri/src/plugins.rs
+// User plugins. A plugin is a folder in the config directory's `plugins/` with a plugin.json
+// manifest and an entry point: a WASI module (run by the wasmtime CLI with no filesystem,
+// network or environment beyond what its capabilities grant) or a script (run directly or by
+// its interpreter, with a cleared environment). Scripts can't be sandboxed, so they are only
+// loaded or run while the "allowScriptPlugins" general setting is on; it is off by default.
+// A plugin can contribute MCP tools, RAG parsers for file types the app can't read, and
+// conversation insight generators.
+//
+// Each call starts the entry point once, writes a JSON request on stdin and reads one JSON reply
+// from stdout, under a time limit and an output cap. Manifests are validated on load, and nothing
+// runs until the user has approved the plugin's capabilities; the approval is a persisted MCP
+// approval rule tied to the capabilities and a hash of every file in the plugin folder (manifest
+// included), so a changed plugin needs approving again. The folder is hashed again before every
+// call, and a plugin that no longer matches doesn't run. Plugin MCP tools go through the
+// session's usual approval prompts and step log; every plugin call is also appended to
+// plugin_audit.jsonl.
+use async_trait::async_trait;
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use crate::mcp::tools::ComputerUseTool;
+use crate::mcp::types::{DangerLevel, ToolExecutionResult};
+
+const MANIFEST_FILE: &str = "plugin.json";
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const MAX_TIMEOUT_MS: u64 = 120_000;
+const MAX_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
+const MAX_STDERR_BYTES: usize = 16 * 1024;
+const AUDIT_READ_LIMIT: usize = 500;
+const ALLOW_SCRIPTS_SETTING: &str = "allowScriptPlugins";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    Network, // wasm: inherit the host network
+    Storage, // wasm: a private data directory mounted at /data
+    Native,  // Runs as an ordinary process; required for scripts, which can't be contained
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginRuntime {
+    Wasm,
+    Script,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginToolSpec {
+    pub name: String,
+    pub description: String,
+    #[serde(default = "default_parameters_schema")]
+    pub parameters_schema: serde_json::Value,
+    #[serde(default = "default_danger_level")]
+    pub danger_level: DangerLevel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginParserSpec {
+    pub name: String,
+    pub file_types: Vec<String>, // MIME types the parser reads, e.g. "application/vnd.ms-outlook"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInsightSpec {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub runtime: PluginRuntime,
+    pub entry: String,               // Relative to the plugin folder
+    #[serde(default)]
+    pub interpreter: Option<String>, // Scripts only, e.g. "python3"
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+    #[serde(default)]
+    pub mcp_tools: Vec<PluginToolSpec>,
+    #[serde(default)]
+    pub rag_parsers: Vec<PluginParserSpec>,
+    #[serde(default)]
+    pub insight_generators: Vec<PluginInsightSpec>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_parameters_schema() -> serde_json::Value {
+    serde_json::json!({ "type": "object" })
+}
+
+fn default_danger_level() -> DangerLevel {
+    DangerLevel::Medium
+}
+
+#[derive(Debug, Clone)]
+struct LoadedPlugin {
+    manifest: PluginManifest,
+    dir: PathBuf,
+    content_sha256: String,
+    approved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub runtime: Option<PluginRuntime>,
+    pub capabilities: Vec<Capability>,
+    pub tools: Vec<String>,
+    pub parsers: Vec<String>,
+    pub insight_generators: Vec<String>,
+    pub approved: bool,
+    pub error: Option<String>, // Why the plugin couldn't be loaded
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInsight {
+    pub plugin_id: String,
+    pub generator: String,
+    pub text: String,
+    pub insight_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginAuditEntry {
+    pub timestamp: String,
+    pub plugin_id: String,
+    pub kind: String, // "mcp_tool", "rag_parser" or "insight_generator"
+    pub name: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PluginReply {
+    ok: bool,
+    #[serde(default)]
+    result: serde_json::Value,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Default)]
+struct Registry {
+    loaded: bool,
+    plugins: Vec<LoadedPlugin>,
+    failed: Vec<PluginInfo>, // Folders whose manifest didn't validate
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+fn plugins_dir() -> anyhow::Result<PathBuf> {
+    let dir = crate::data::paths::config_dir()?.join("plugins");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// Kept apart from the plugin folder so replacing a plugin keeps its data
+fn plugin_data_dir(plugin_id: &str) -> Result<PathBuf, String> {
+    let dir = crate::data::paths::config_dir()
+        .map_err(|e| format!("Failed to get config directory: {}", e))?
+        .join("plugin_data")
+        .join(plugin_id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create plugin data directory: {}", e))?;
+    Ok(dir)
+}
+
+fn danger_rank(level: DangerLevel) -> u8 {
+    match level {
+        DangerLevel::Low => 0,
+        DangerLevel::Medium => 1,
+        DangerLevel::High => 2,
+        DangerLevel::Critical => 3,
+    }
+}
+
+/// A plugin tool's danger level: at least Medium so every call asks, and at least High when the
+/// plugin can reach the network or runs natively, whatever the manifest claims.
+fn effective_danger(declared: DangerLevel, capabilities: &[Capability]) -> DangerLevel {
+    let floor = if capabilities.iter().any(|c| matches!(c, Capability::Network | Capability::Native)) {
+        DangerLevel::High
+    } else {
+        DangerLevel::Medium
+    };
+    if danger_rank(declared) >= danger_rank(floor) { declared } else { floor }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// Check a manifest before anything from the plugin is registered.
+fn validate_manifest(manifest: &PluginManifest, builtin_tools: &HashSet<String>) -> Result<(), String> {
+    if !valid_name(&manifest.id) {
+        return Err(format!("Plugin id '{}' must be 1-64 lowercase letters, digits, '-' or '_'", manifest.id));
+    }
+    let entry = Path::new(&manifest.entry);
+    if manifest.entry.is_empty() || entry.is_absolute() || entry.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err(format!("Entry '{}' must be a path inside the plugin folder", manifest.entry));
+    }
+    match manifest.runtime {
+        PluginRuntime::Script if !manifest.capabilities.contains(&Capability::Native) => {
+            return Err("Script plugins run as ordinary processes and must declare the 'native' capability".to_string());
+        }
+        PluginRuntime::Wasm if manifest.interpreter.is_some() => {
+            return Err("WASM plugins don't take an interpreter".to_string());
+        }
+        _ => {}
+    }
+    if manifest.mcp_tools.is_empty() && manifest.rag_parsers.is_empty() && manifest.insight_generators.is_empty() {
+        return Err("The plugin contributes no tools, parsers or insight generators".to_string());
+    }
+    if manifest.timeout_ms.is_some_and(|ms| ms == 0 || ms > MAX_TIMEOUT_MS) {
+        return Err(format!("timeout_ms must be between 1 and {}", MAX_TIMEOUT_MS));
+    }
+
+    let mut names = HashSet::new();
+    let contributions = manifest.mcp_tools.iter().map(|t| &t.name)
+        .chain(manifest.rag_parsers.iter().map(|p| &p.name))
+        .chain(manifest.insight_generators.iter().map(|g| &g.name));
+    for name in contributions {
+        if !valid_name(name) {
+            return Err(format!("Name '{}' must be 1-64 lowercase letters, digits, '-' or '_'", name));
+        }
+        if !names.insert(name.as_str()) {
+            return Err(format!("Name '{}' is used twice", name));
+        }
+    }
+    for tool in &manifest.mcp_tools {
+        if builtin_tools.contains(&tool_name(&manifest.id, &tool.name)) {
+            return Err(format!("Tool '{}' clashes with a built-in tool", tool.name));
+        }
+        if tool.parameters_schema.get("type").and_then(|t| t.as_str()) != Some("object") {
+            return Err(format!("Tool '{}' parameters_schema must be a JSON schema of type object", tool.name));
+        }
+    }
+    for parser in &manifest.rag_parsers {
+        if parser.file_types.is_empty() {
+            return Err(format!("Parser '{}' declares no file types", parser.name));
+        }
+    }
+    Ok(())
+}
+
+/// MCP tool names are namespaced by plugin.
+fn tool_name(plugin_id: &str, tool: &str) -> String {
+    format!("{}.{}", plugin_id, tool)
+}
+
+fn approval_rule_name(plugin_id: &str) -> String {
+    format!("plugin:{}", plugin_id)
+}
+
+/// SHA-256 over every file in the plugin folder by relative path: the manifest (interpreter, tool
+/// danger levels and schemas, timeout), the entry point and anything it imports.
+fn plugin_digest(dir: &Path) -> Result<String, String> {
+    fn collect(dir: &Path, base: &Path, files: &mut Vec<(String, PathBuf)>) -> std::io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            // Symlinked folders aren't followed (they could loop); reading one fails the load
+            if entry.file_type()?.is_dir() {
+                collect(&path, base, files)?;
+            } else {
+                let relative = path.strip_prefix(base).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+                files.push((relative, path));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect(dir, dir, &mut files).map_err(|e| format!("Failed to read the plugin folder: {}", e))?;
+    files.sort();
+    let mut hasher = Sha256::new();
+    for (relative, path) in files {
+        let content = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", relative, e))?;
+        // Lengths first, so bytes can't move between a name and a file without changing the digest
+        hasher.update((relative.len() as u64).to_le_bytes());
+        hasher.update(relative.as_bytes());
+        hasher.update((content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// What an approval covers: changing either needs approving again
+fn approval_parameters(manifest: &PluginManifest, content_sha256: &str) -> serde_json::Value {
+    let mut capabilities: Vec<Capability> = manifest.capabilities.clone();
+    capabilities.sort_by_key(|c| *c as u8);
+    capabilities.dedup();
+    serde_json::json!({ "capabilities": capabilities, "content_sha256": content_sha256 })
+}
+
+fn is_approved(manifest: &PluginManifest, content_sha256: &str) -> bool {
+    let rule_name = approval_rule_name(&manifest.id);
+    let parameters = approval_parameters(manifest, content_sha256);
+    crate::mcp::policy::load_persisted_rules()
+        .iter()
+        .any(|rule| rule.tool_name == rule_name && rule.parameters.as_ref() == Some(&parameters))
+}
+
+fn scripts_allowed() -> bool {
+    crate::audio_loopback::settings::general_setting_bool(ALLOW_SCRIPTS_SETTING).unwrap_or(false)
+}
+
+fn require_runtime_allowed(manifest: &PluginManifest) -> Result<(), String> {
+    if manifest.runtime == PluginRuntime::Script && !scripts_allowed() {
+        return Err(format!(
+            "Script plugins run unsandboxed and are turned off; enable the '{}' setting to use them",
+            ALLOW_SCRIPTS_SETTING
+        ));
+    }
+    Ok(())
+}
+
+/// Hash the folder again and refuse a plugin whose files changed since it was loaded (and approved).
+fn verify_unchanged(plugin: &LoadedPlugin) -> Result<(), String> {
+    if plugin_digest(&plugin.dir)? != plugin.content_sha256 {
+        return Err(format!(
+            "Plugin '{}' changed since it was approved; reload and approve it again",
+            plugin.manifest.id
+        ));
+    }
+    Ok(())
+}
+
+fn load_plugin(dir: &Path, builtin_tools: &HashSet<String>) -> Result<LoadedPlugin, String> {
+    let json = fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest = serde_json::from_str(&json).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+    validate_manifest(&manifest, builtin_tools)?;
+    require_runtime_allowed(&manifest)?;
+    if !dir.join(&manifest.entry).is_file() {
+        return Err(format!("Entry '{}' doesn't exist", manifest.entry));
+    }
+    let content_sha256 = plugin_digest(dir)?;
+    let approved = is_approved(&manifest, &content_sha256);
+    Ok(LoadedPlugin { manifest, dir: dir.to_path_buf(), content_sha256, approved })
+}
+
+fn info(plugin: &LoadedPlugin) -> PluginInfo {
+    let manifest = &plugin.manifest;
+    PluginInfo {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        version: manifest.version.clone(),
+        description: manifest.description.clone(),
+        runtime: Some(manifest.runtime),
+        capabilities: manifest.capabilities.clone(),
+        tools: manifest.mcp_tools.iter().map(|t| tool_name(&manifest.id, &t.name)).collect(),
+        parsers: manifest.rag_parsers.iter().map(|p| p.name.clone()).collect(),
+        insight_generators: manifest.insight_generators.iter().map(|g| g.name.clone()).collect(),
+        approved: plugin.approved,
+        error: None,
+    }
+}
+
+fn failed_info(dir: &Path, error: String) -> PluginInfo {
+    let id = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    PluginInfo {
+        name: id.clone(),
+        id,
+        version: String::new(),
+        description: String::new(),
+        runtime: None,
+        capabilities: vec![],
+        tools: vec![],
+        parsers: vec![],
+        insight_generators: vec![],
+        approved: false,
+        error: Some(error),
+    }
+}
+
+// Rescan the plugins folder
+fn scan(registry: &mut Registry) {
+    let builtin_tools: HashSet<String> = crate::mcp::tools::builtin_tools().into_keys().collect();
+    registry.plugins.clear();
+    registry.failed.clear();
+    registry.loaded = true;
+
+    let dir = match plugins_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("Failed to open plugins folder: {}", e);
+            return;
+        }
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return;
+    };
+    let mut ids = HashSet::new();
+    for entry in entries.flatten().filter(|entry| entry.path().is_dir()) {
+        let path = entry.path();
+        match load_plugin(&path, &builtin_tools) {
+            Ok(plugin) if !ids.insert(plugin.manifest.id.clone()) => {
+                registry.failed.push(failed_info(&path, format!("Another plugin already uses the id '{}'", plugin.manifest.id)));
+            }
+            Ok(plugin) => registry.plugins.push(plugin),
+            Err(e) => {
+                eprintln!("⚠️ Plugin in {} not loaded: {}", path.display(), e);
+                registry.failed.push(failed_info(&path, e));
+            }
+        }
+    }
+    registry.plugins.sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+    println!(
+        "🧩 Loaded {} plugins ({} approved, {} failed)",
+        registry.plugins.len(),
+        registry.plugins.iter().filter(|p| p.approved).count(),
+        registry.failed.len()
+    );
+}
+
+// Approved plugins, loading the folder on first use
+fn approved_plugins() -> Vec<LoadedPlugin> {
+    let Ok(mut registry) = REGISTRY.lock() else {
+        return vec![];
+    };
+    if !registry.loaded {
+        scan(&mut registry);
+    }
+    registry.plugins.iter().filter(|p| p.approved).cloned().collect()
+}
+
+fn approved_plugin(plugin_id: &str) -> Result<LoadedPlugin, String> {
+    approved_plugins()
+        .into_iter()
+        .find(|plugin| plugin.manifest.id == plugin_id)
+        .ok_or_else(|| format!("Plugin '{}' is not installed or not approved", plugin_id))
+}
+
+#[cfg(windows)]
+fn hide_console(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    command.creation_flags(CREATE_NO_WINDOW);
+}
+
+#[cfg(not(windows))]
+fn hide_console(_command: &mut Command) {}
+
+fn wasmtime_binary() -> String {
+    std::env::var("ENTERACT_WASMTIME").unwrap_or_else(|_| "wasmtime".to_string())
+}
+
+// The sandboxed command line for one call
+fn plugin_command(plugin: &LoadedPlugin) -> Result<Command, String> {
+    let manifest = &plugin.manifest;
+    let entry = plugin.dir.join(&manifest.entry);
+    let data_dir = plugin_data_dir(&manifest.id)?;
+    let mut command = match manifest.runtime {
+        PluginRuntime::Wasm => {
+            // WASI grants nothing by default; capabilities add the network and the data mount
+            let mut command = Command::new(wasmtime_binary());
+            command.arg("run");
+            if manifest.capabilities.contains(&Capability::Network) {
+                command.args(["-S", "inherit-network=y"]);
+            }
+            if manifest.capabilities.contains(&Capability::Storage) {
+                command.arg("--dir").arg(format!("{}::/data", data_dir.display()));
+            }
+            command.arg(&entry);
+            command
+        }
+        PluginRuntime::Script => {
+            let mut command = match &manifest.interpreter {
+                Some(interpreter) => {
+                    let mut command = Command::new(interpreter);
+                    command.arg(&entry);
+                    command
+                }
+                None => Command::new(&entry),
+            };
+            command
+        }
+    };
+    // Only PATH survives, so the runtime can be found; no tokens or keys from the app's environment
+    command.env_clear();
+    if let Some(path) = std::env::var_os("PATH") {
+        command.env("PATH", path);
+    }
+    if manifest.runtime == PluginRuntime::Script {
+        command.env("ENTERACT_PLUGIN_DATA", &data_dir);
+    }
+    command
+        .current_dir(&data_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    hide_console(&mut command);
+    Ok(command)
+}
+
+fn read_capped(mut source: impl Read, cap: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    let _ = (&mut source).take(cap as u64 + 1).read_to_end(&mut out);
+    out
+}
+
+/// Run one request through the plugin and return its result, enforcing the time limit and the
+/// output cap.
+fn run_plugin(plugin: &LoadedPlugin, request: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let id = &plugin.manifest.id;
+    // Either may have changed since the registry was loaded
+    require_runtime_allowed(&plugin.manifest)?;
+    verify_unchanged(plugin)?;
+    let timeout = Duration::from_millis(plugin.manifest.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let mut child = plugin_command(plugin)?
+        .spawn()
+        .map_err(|e| format!("Failed to start plugin '{}': {}", id, e))?;
+
+    let input = serde_json::to_vec(request).map_err(|e| format!("Failed to encode plugin request: {}", e))?;
+    let mut stdin = child.stdin.take().ok_or("Plugin stdin unavailable")?;
+    let stdout = child.stdout.take().ok_or("Plugin stdout unavailable")?;
+    let stderr = child.stderr.take().ok_or("Plugin stderr unavailable")?;
+    // Dropping stdin after the write tells the plugin the request is complete
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+    let reader = std::thread::spawn(move || read_capped(stdout, MAX_OUTPUT_BYTES));
+    let error_reader = std::thread::spawn(move || read_capped(stderr, MAX_STDERR_BYTES));
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Plugin '{}' timed out after {:?}", id, timeout));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(format!("Failed to wait for plugin '{}': {}", id, e)),
+        }
+    };
+    let _ = writer.join();
+    let output = reader.join().unwrap_or_default();
+    let errors = error_reader.join().unwrap_or_default();
+    if !errors.is_empty() {
+        eprintln!("🧩 [{}] {}", id, String::from_utf8_lossy(&errors[..errors.len().min(MAX_STDERR_BYTES)]).trim());
+    }
+
+    if output.len() > MAX_OUTPUT_BYTES {
+        return Err(format!("Plugin '{}' replied with more than {} bytes", id, MAX_OUTPUT_BYTES));
+    }
+    if !status.success() {
+        return Err(format!("Plugin '{}' exited with {}", id, status));
+    }
+    let reply: PluginReply = serde_json::from_slice(&output)
+        .map_err(|e| format!("Plugin '{}' sent an invalid reply: {}", id, e))?;
+    if reply.ok {
+        Ok(reply.result)
+    } else {
+        Err(reply.error.unwrap_or_else(|| format!("Plugin '{}' reported a failure", id)))
+    }
+}
+
+fn audit_path() -> anyhow::Result<PathBuf> {
+    Ok(crate::data::paths::config_dir()?.join("plugin_audit.jsonl"))
+}
+
+fn audit(entry: &PluginAuditEntry) {
+    let result = audit_path().and_then(|path| {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to write plugin audit entry: {}", e);
+    }
+}
+
+/// Run and audit one call.
+fn call(plugin: &LoadedPlugin, kind: &str, name: &str, input: serde_json::Value) -> Result<serde_json::Value, String> {
+    let started = Instant::now();
+    let result = run_plugin(plugin, &serde_json::json!({ "kind": kind, "name": name, "input": input }));
+    audit(&PluginAuditEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        plugin_id: plugin.manifest.id.clone(),
+        kind: kind.to_string(),
+        name: name.to_string(),
+        success: result.is_ok(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.as_ref().err().cloned(),
+    });
+    result
+}
+
+/// An approved plugin's MCP tool. Calls go through the session's approval and step log like any
+/// built-in tool.
+#[derive(Clone)]
+pub struct PluginTool {
+    plugin_id: String,
+    full_name: String,
+    spec: PluginToolSpec,
+    danger_level: DangerLevel,
+}
+
+#[async_trait]
+impl ComputerUseTool for PluginTool {
+    fn name(&self) -> &str { &self.full_name }
+
+    fn description(&self) -> String {
+        format!("{} (plugin: {})", self.spec.description, self.plugin_id)
+    }
+
+    fn danger_level(&self) -> DangerLevel { self.danger_level }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        self.spec.parameters_schema.clone()
+    }
+
+    async fn execute(&self, params: serde_json::Value, session_id: &str) -> Result<ToolExecutionResult, String> {
+        let started = Instant::now();
+        log::info!("Session {}: Executing plugin tool {}", session_id, self.full_name);
+        // Approval may have been withdrawn since the session registered the tool
+        let plugin = approved_plugin(&self.plugin_id)?;
+        let name = self.spec.name.clone();
+        let result = tokio::task::spawn_blocking(move || call(&plugin, "mcp_tool", &name, params))
+            .await
+            .map_err(|e| format!("Plugin task failed: {}", e))?;
+        let execution_time_ms = started.elapsed().as_millis() as u64;
+        Ok(match result {
+            Ok(result) => ToolExecutionResult {
+                success: true,
+                result,
+                error: None,
+                execution_time_ms,
+                tool_name: self.full_name.clone(),
+            },
+            Err(e) => ToolExecutionResult {
+                success: false,
+                result: serde_json::json!({ "error": e }),
+                error: Some(e),
+                execution_time_ms,
+                tool_name: self.full_name.clone(),
+            },
+        })
+    }
+
+    fn clone_box(&self) -> Box<dyn ComputerUseTool + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+/// MCP tools from approved plugins, keyed by their namespaced name.
+pub fn mcp_tools() -> Vec<(String, Box<dyn ComputerUseTool + Send + Sync>)> {
+    approved_plugins()
+        .into_iter()
+        .flat_map(|plugin| {
+            let manifest = plugin.manifest;
+            manifest.mcp_tools.clone().into_iter().map(move |spec| {
+                let full_name = tool_name(&manifest.id, &spec.name);
+                let tool = PluginTool {
+                    plugin_id: manifest.id.clone(),
+                    full_name: full_name.clone(),
+                    danger_level: effective_danger(spec.danger_level, &manifest.capabilities),
+                    spec,
+                };
+                (full_name, Box::new(tool) as Box<dyn ComputerUseTool + Send + Sync>)
+            })
+        })
+        .collect()
+}
+
+/// Whether an approved plugin parser claims `file_type`.
+pub fn claims_file_type(file_type: &str) -> bool {
+    let file_type = file_type.to_lowercase();
+    approved_plugins().iter().any(|plugin| {
+        plugin.manifest.rag_parsers.iter().any(|parser| parser.file_types.iter().any(|t| t.to_lowercase() == file_type))
+    })
+}
+
+/// Text from a document whose exact file type an approved plugin parser claims; the built-in
+/// extractors are only used when none does. Errors from a claiming parser are returned, not
+/// skipped. Runs the plugin for up to its timeout, so call it on a blocking thread.
+pub fn parse_document(file_type: &str, content: &[u8]) -> Option<Result<String, String>> {
+    let file_type = file_type.to_lowercase();
+    let (plugin, parser) = approved_plugins().into_iter().find_map(|plugin| {
+        let parser = plugin.manifest.rag_parsers.iter()
+            .find(|parser| parser.file_types.iter().any(|t| t.to_lowercase() == file_type))?
+            .name
+            .clone();
+        Some((plugin, parser))
+    })?;
+    let input = serde_json::json!({ "file_type": file_type, "content_base64": BASE64_STANDARD.encode(content) });
+    Some(call(&plugin, "rag_parser", &parser, input).and_then(|result| {
+        result.get("text")
+            .and_then(|text| text.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| format!("Parser '{}' of plugin '{}' returned no text", parser, plugin.manifest.id))
+    }))
+}
+
+fn insights_from(plugin_id: &str, generator: &str, result: &serde_json::Value) -> Vec<PluginInsight> {
+    result.get("insights")
+        .and_then(|insights| insights.as_array())
+        .map(|insights| insights.iter().filter_map(|insight| {
+            let text = insight.get("text")?.as_str()?.trim();
+            (!text.is_empty()).then(|| PluginInsight {
+                plugin_id: plugin_id.to_string(),
+                generator: generator.to_string(),
+                text: text.to_string(),
+                insight_type: insight.get("type").and_then(|t| t.as_str()).unwrap_or("custom").to_string(),
+            })
+        }).collect())
+        .unwrap_or_default()
+}
+
+fn infos() -> Vec<PluginInfo> {
+    let Ok(registry) = REGISTRY.lock() else {
+        return vec![];
+    };
+    registry.plugins.iter().map(info).chain(registry.failed.iter().cloned()).collect()
+}
+
+// Scanning hashes every plugin file, so commands do their work off the async runtime's threads
+async fn blocking<T: Send + 'static>(work: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| format!("Plugin task failed: {}", e))?
+}
+
+fn rescan(app_handle: &AppHandle) -> Result<Vec<PluginInfo>, String> {
+    scan(&mut *REGISTRY.lock().map_err(|e| e.to_string())?);
+    let plugins = infos();
+    let _ = app_handle.emit("plugins-changed", &plugins);
+    Ok(plugins)
+}
+
+#[tauri::command]
+pub async fn list_plugins() -> Result<Vec<PluginInfo>, String> {
+    blocking(|| {
+        approved_plugins();
+        Ok(infos())
+    })
+    .await
+}
+
+/// Rescan the plugins folder. New MCP sessions pick up tool changes.
+#[tauri::command]
+pub async fn reload_plugins(app_handle: AppHandle) -> Result<Vec<PluginInfo>, String> {
+    blocking(move || rescan(&app_handle)).await
+}
+
+/// Let a plugin run with the capabilities it declares. Stored as a persisted approval rule.
+#[tauri::command]
+pub async fn approve_plugin(app_handle: AppHandle, plugin_id: String) -> Result<Vec<PluginInfo>, String> {
+    blocking(move || {
+        let plugin = {
+            let mut registry = REGISTRY.lock().map_err(|e| e.to_string())?;
+            scan(&mut registry);
+            registry.plugins.iter().find(|p| p.manifest.id == plugin_id).cloned().ok_or_else(|| {
+                // A plugin that failed to load (e.g. a script while scripts are off) says why
+                registry.failed.iter().find(|f| f.id == plugin_id).and_then(|f| f.error.clone())
+                    .unwrap_or_else(|| format!("Plugin '{}' is not installed", plugin_id))
+            })?
+        };
+        let parameters = approval_parameters(&plugin.manifest, &plugin.content_sha256);
+        crate::mcp::policy::remember_exact(&approval_rule_name(&plugin_id), &parameters)?;
+        println!("🧩 Approved plugin {} with capabilities {:?}", plugin_id, plugin.manifest.capabilities);
+        rescan(&app_handle)
+    })
+    .await
+}
+
+/// Withdraw every approval of a plugin; it stops running straight away.
+#[tauri::command]
+pub async fn revoke_plugin(app_handle: AppHandle, plugin_id: String) -> Result<Vec<PluginInfo>, String> {
+    blocking(move || {
+        let rule_name = approval_rule_name(&plugin_id);
+        for rule in crate::mcp::policy::load_persisted_rules().into_iter().filter(|rule| rule.tool_name == rule_name) {
+            crate::mcp::policy::revoke_persisted(&rule.id)?;
+        }
+        println!("🧩 Revoked plugin {}", plugin_id);
+        rescan(&app_handle)
+    })
+    .await
+}
+
+/// Run every approved insight generator over the conversation. Redacted messages are removed
+/// first, as for model insights.
+#[tauri::command]
+pub async fn generate_plugin_insights(
+    app_handle: AppHandle,
+    conversation_id: Option<String>,
+    conversation_context: String,
+) -> Result<Vec<PluginInsight>, String> {
+    let context = match &conversation_id {
+        Some(conversation_id) => {
+            let conversation = crate::data::conversation::ConversationStorage::new(&app_handle)
+                .and_then(|storage| storage.get_session(conversation_id))
+                .map_err(|e| format!("Failed to load conversation: {}", e))?;
+            match conversation {
+                Some(conversation) => crate::data::conversation::redaction::scrub_redacted(&conversation_context, &conversation.messages),
+                None => conversation_context,
+            }
+        }
+        None => conversation_context,
+    };
+
+    blocking(move || {
+        let mut insights = Vec::new();
+        for plugin in approved_plugins() {
+            for generator in &plugin.manifest.insight_generators {
+                let input = serde_json::json!({ "conversation_id": conversation_id, "context": context });
+                match call(&plugin, "insight_generator", &generator.name, input) {
+                    Ok(result) => insights.extend(insights_from(&plugin.manifest.id, &generator.name, &result)),
+                    // One failing generator doesn't hide the others' insights
+                    Err(e) => eprintln!("⚠️ Insight generator {}/{} failed: {}", plugin.manifest.id, generator.name, e),
+                }
+            }
+        }
+        Ok(insights)
+    })
+    .await
+}
+
+/// The most recent plugin calls, newest first.
+#[tauri::command]
+pub async fn get_plugin_audit(limit: Option<usize>) -> Result<Vec<PluginAuditEntry>, String> {
+    blocking(move || {
+        let path = audit_path().map_err(|e| format!("Failed to get audit path: {}", e))?;
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(format!("Failed to read plugin audit: {}", e)),
+        };
+        Ok(contents
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .take(limit.unwrap_or(AUDIT_READ_LIMIT).min(AUDIT_READ_LIMIT))
+            .collect())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(json: serde_json::Value) -> PluginManifest {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_manifests_are_validated_and_capabilities_raise_danger() {
+        let builtin: HashSet<String> = ["click".to_string()].into_iter().collect();
+        let tools = serde_json::json!([{ "name": "create_issue", "description": "File an issue", "danger_level": "Low" }]);
+        let wasm = manifest(serde_json::json!({
+            "id": "issues", "name": "Issues", "version": "1.0.0", "runtime": "wasm",
+            "entry": "issues.wasm", "capabilities": ["network"], "mcp_tools": tools,
+        }));
+        assert!(validate_manifest(&wasm, &builtin).is_ok());
+        // Claimed "Low", but network access makes it High
+        assert!(matches!(effective_danger(DangerLevel::Low, &wasm.capabilities), DangerLevel::High));
+        assert!(matches!(effective_danger(DangerLevel::Low, &[]), DangerLevel::Medium));
+
+        let script = PluginManifest { runtime: PluginRuntime::Script, capabilities: vec![], ..wasm.clone() };
+        assert!(validate_manifest(&script, &builtin).unwrap_err().contains("native"));
+        let escaping = PluginManifest { entry: "../../bin/sh".to_string(), ..wasm.clone() };
+        assert!(validate_manifest(&escaping, &builtin).is_err());
+        let empty = PluginManifest { mcp_tools: vec![], ..wasm.clone() };
+        assert!(validate_manifest(&empty, &builtin).is_err());
+
+        let insights = insights_from("issues", "risks", &serde_json::json!({
+            "insights": [{ "text": "Deadline moved to Friday", "type": "action_item" }, { "text": "  " }, { "text": "Budget unclear" }]
+        }));
+        assert_eq!(insights.len(), 2);
+        assert_eq!(insights[1].insight_type, "custom");
+    }
+
+    #[test]
+    fn test_approval_digest_covers_every_plugin_file() {
+        let dir = std::env::temp_dir().join(format!("enteract-plugin-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), r#"{"timeout_ms": 1000}"#).unwrap();
+        fs::write(dir.join("main.py"), "import lib.helpers").unwrap();
+        fs::write(dir.join("lib").join("helpers.py"), "def run(): pass").unwrap();
+
+        let original = plugin_digest(&dir).unwrap();
+        assert_eq!(plugin_digest(&dir).unwrap(), original);
+        fs::write(dir.join("lib").join("helpers.py"), "import os; os.system('curl evil')").unwrap();
+        let helper_changed = plugin_digest(&dir).unwrap();
+        assert_ne!(helper_changed, original);
+        fs::write(dir.join(MANIFEST_FILE), r#"{"timeout_ms": 120000}"#).unwrap();
+        assert_ne!(plugin_digest(&dir).unwrap(), helper_changed);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_changed_plugin_is_refused_before_running() {
+        let dir = std::env::temp_dir().join(format!("enteract-plugin-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("issues.wasm"), b"\0asm").unwrap();
+        let plugin = LoadedPlugin {
+            manifest: manifest(serde_json::json!({
+                "id": "issues", "name": "Issues", "version": "1.0.0", "runtime": "wasm", "entry": "issues.wasm",
+                "mcp_tools": [{ "name": "create_issue", "description": "File an issue" }],
+            })),
+            dir: dir.clone(),
+            content_sha256: plugin_digest(&dir).unwrap(),
+            approved: true,
+        };
+        assert!(verify_unchanged(&plugin).is_ok());
+
+        fs::write(dir.join("issues.wasm"), b"\0asm-swapped").unwrap();
+        assert!(verify_unchanged(&plugin).unwrap_err().contains("changed since it was approved"));
+        assert!(run_plugin(&plugin, &serde_json::json!({})).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
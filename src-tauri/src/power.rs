@@ -0,0 +1,275 @@
+// src-tauri/src/power.rs
+// Power-source awareness so laptops on battery transcribe less often, use smaller models
+// and defer background jobs.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerPolicy {
+    pub enabled: bool,
+    pub low_battery_threshold: u8,
+    // Least time between transcriptions of ongoing speech; 0 leaves the capture pipeline's own cadence
+    pub battery_transcription_interval_ms: u64,
+    pub plugged_transcription_interval_ms: u64,
+    pub prefer_small_models_on_battery: bool,
+    pub defer_background_jobs_on_battery: bool,
+}
+
+impl Default for PowerPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            low_battery_threshold: 20,
+            battery_transcription_interval_ms: 5000,
+            plugged_transcription_interval_ms: 0,
+            prefer_small_models_on_battery: true,
+            defer_background_jobs_on_battery: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PowerState {
+    pub has_battery: bool,
+    pub on_battery: bool,
+    pub battery_percent: Option<u8>,
+    pub low_battery: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PowerStatus {
+    pub state: PowerState,
+    pub policy: PowerPolicy,
+    pub throttled: bool,
+    pub transcription_interval_ms: u64,
+}
+
+#[derive(Default)]
+struct PowerMonitor {
+    status: PowerStatus,
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+lazy_static::lazy_static! {
+    static ref POWER_MONITOR: Arc<Mutex<PowerMonitor>> = Arc::new(Mutex::new(PowerMonitor::default()));
+}
+
+fn get_policy_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("power_policy.json")
+}
+
+fn load_policy() -> PowerPolicy {
+    get_policy_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+pub fn read_power_state(low_battery_threshold: u8) -> PowerState {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+        let mut status = SYSTEM_POWER_STATUS::default();
+        if unsafe { GetSystemPowerStatus(&mut status) }.is_err() {
+            return PowerState::default();
+        }
+
+        // BatteryFlag 128 means "no system battery"; 255 means unknown
+        let has_battery = status.BatteryFlag != 128 && status.BatteryFlag != 255;
+        let battery_percent = if status.BatteryLifePercent <= 100 { Some(status.BatteryLifePercent) } else { None };
+        let on_battery = has_battery && status.ACLineStatus == 0;
+
+        return PowerState {
+            has_battery,
+            on_battery,
+            battery_percent,
+            low_battery: on_battery && battery_percent.map(|p| p <= low_battery_threshold).unwrap_or(false),
+        };
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = match std::process::Command::new("pmset").args(&["-g", "batt"]).output() {
+            Ok(output) => output,
+            Err(_) => return PowerState::default(),
+        };
+        let text = String::from_utf8_lossy(&output.stdout);
+        let on_battery = text.contains("'Battery Power'");
+        let battery_percent = text
+            .split_whitespace()
+            .find(|token| token.ends_with("%;"))
+            .and_then(|token| token.trim_end_matches("%;").parse::<u8>().ok());
+
+        return PowerState {
+            has_battery: battery_percent.is_some(),
+            on_battery,
+            battery_percent,
+            low_battery: on_battery && battery_percent.map(|p| p <= low_battery_threshold).unwrap_or(false),
+        };
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut has_battery = false;
+        let mut on_ac = false;
+        let mut battery_percent = None;
+
+        if let Ok(entries) = fs::read_dir("/sys/class/power_supply") {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+                match kind.trim() {
+                    "Mains" => {
+                        on_ac |= fs::read_to_string(path.join("online")).map(|v| v.trim() == "1").unwrap_or(false);
+                    }
+                    "Battery" => {
+                        has_battery = true;
+                        battery_percent = fs::read_to_string(path.join("capacity"))
+                            .ok()
+                            .and_then(|v| v.trim().parse::<u8>().ok());
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let on_battery = has_battery && !on_ac;
+        return PowerState {
+            has_battery,
+            on_battery,
+            battery_percent,
+            low_battery: on_battery && battery_percent.map(|p| p <= low_battery_threshold).unwrap_or(false),
+        };
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = low_battery_threshold;
+        PowerState::default()
+    }
+}
+
+fn build_status(state: PowerState, policy: PowerPolicy) -> PowerStatus {
+    let throttled = policy.enabled && state.on_battery;
+    let mut transcription_interval_ms = if throttled {
+        policy.battery_transcription_interval_ms
+    } else {
+        policy.plugged_transcription_interval_ms
+    };
+    if throttled && state.low_battery {
+        transcription_interval_ms *= 2;
+    }
+
+    PowerStatus {
+        state,
+        policy,
+        throttled,
+        transcription_interval_ms,
+    }
+}
+
+pub fn current_power_status() -> PowerStatus {
+    POWER_MONITOR
+        .lock()
+        .map(|monitor| monitor.status.clone())
+        .unwrap_or_default()
+}
+
+/// The least time the capture pipeline should leave between transcriptions of ongoing speech.
+pub fn transcription_interval() -> Duration {
+    Duration::from_millis(current_power_status().transcription_interval_ms)
+}
+
+/// True when policy says background jobs should wait for mains power.
+pub fn should_defer_background_work() -> bool {
+    let status = current_power_status();
+    status.throttled && status.policy.defer_background_jobs_on_battery
+}
+
+/// Swap a Whisper model for a lighter one while running on battery.
+pub fn preferred_whisper_model(requested: &str) -> String {
+    let status = current_power_status();
+    if !(status.throttled && status.policy.prefer_small_models_on_battery) {
+        return requested.to_string();
+    }
+
+    match requested {
+        "large" | "medium" | "small" if status.state.low_battery => "tiny".to_string(),
+        "large" | "medium" | "small" => "base".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn refresh_power(app_handle: &AppHandle) {
+    let policy = match POWER_MONITOR.lock() {
+        Ok(monitor) => monitor.status.policy.clone(),
+        Err(_) => return,
+    };
+    let status = build_status(read_power_state(policy.low_battery_threshold), policy);
+
+    let changed = match POWER_MONITOR.lock() {
+        Ok(mut monitor) => {
+            let previous = &monitor.status;
+            let changed = previous.state.on_battery != status.state.on_battery
+                || previous.state.low_battery != status.state.low_battery
+                || previous.throttled != status.throttled;
+            monitor.status = status.clone();
+            changed
+        }
+        Err(_) => return,
+    };
+
+    if changed {
+        println!("🔋 Power state changed: on_battery={} low={} ({:?}%)",
+                 status.state.on_battery, status.state.low_battery, status.state.battery_percent);
+        if let Err(e) = app_handle.emit("power-state-changed", &status) {
+            eprintln!("Failed to emit power state event: {}", e);
+        }
+    }
+}
+
+pub fn start_power_monitor(app_handle: AppHandle) {
+    let mut monitor = match POWER_MONITOR.lock() {
+        Ok(monitor) => monitor,
+        Err(_) => return,
+    };
+    if monitor.task.is_some() {
+        return;
+    }
+
+    monitor.status.policy = load_policy();
+    monitor.task = Some(tauri::async_runtime::spawn(async move {
+        loop {
+            refresh_power(&app_handle);
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        }
+    }));
+}
+
+#[tauri::command]
+pub async fn get_power_status() -> Result<PowerStatus, String> {
+    Ok(current_power_status())
+}
+
+#[tauri::command]
+pub async fn update_power_policy(app_handle: AppHandle, policy: PowerPolicy) -> Result<PowerStatus, String> {
+    let policy_path = get_policy_path()
+        .map_err(|e| format!("Failed to get power policy path: {}", e))?;
+    let json = serde_json::to_string_pretty(&policy)
+        .map_err(|e| format!("Failed to serialize power policy: {}", e))?;
+    fs::write(policy_path, json)
+        .map_err(|e| format!("Failed to write power policy: {}", e))?;
+
+    {
+        let mut monitor = POWER_MONITOR.lock().map_err(|e| e.to_string())?;
+        monitor.status.policy = policy;
+    }
+    refresh_power(&app_handle);
+    Ok(current_power_status())
+}
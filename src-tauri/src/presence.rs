@@ -0,0 +1,317 @@
+// src-tauri/src/presence.rs
+// Idle/presence detection used to hold back resource-heavy background jobs
+// (re-indexing, re-embedding, benchmarks) while the user is busy in a meeting or game.
+// Settings, including a manual pause, are kept in presence_settings.json across restarts.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum BackgroundWorkOverride {
+    Auto,
+    ForcePaused,
+    ForceRunning,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceSettings {
+    pub resume_after_idle_minutes: u64,
+    pub poll_interval_secs: u64,
+    pub work_override: BackgroundWorkOverride,
+}
+
+impl Default for PresenceSettings {
+    fn default() -> Self {
+        Self {
+            resume_after_idle_minutes: 5,
+            poll_interval_secs: 15,
+            work_override: BackgroundWorkOverride::Auto,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceStatus {
+    pub idle_seconds: Option<u64>,
+    pub in_meeting: bool,
+    pub fullscreen_app: bool,
+    pub background_work_allowed: bool,
+    pub reason: String,
+    pub settings: PresenceSettings,
+}
+
+impl Default for PresenceStatus {
+    fn default() -> Self {
+        Self {
+            idle_seconds: None,
+            in_meeting: false,
+            fullscreen_app: false,
+            background_work_allowed: true,
+            reason: "Presence monitor not running".to_string(),
+            settings: PresenceSettings::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PresenceState {
+    status: PresenceStatus,
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+lazy_static::lazy_static! {
+    static ref PRESENCE_STATE: Arc<Mutex<PresenceState>> = Arc::new(Mutex::new(PresenceState::default()));
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("presence_settings.json")
+}
+
+fn load_settings() -> PresenceSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+// Store the settings and apply them to the running monitor
+fn save_settings(settings: PresenceSettings) -> Result<(), String> {
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize presence settings: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save presence settings: {}", e))?;
+
+    let mut state = PRESENCE_STATE.lock().map_err(|e| e.to_string())?;
+    state.status.settings = settings;
+    Ok(())
+}
+
+/// Background jobs call this before doing heavy work. Defaults to true when the monitor isn't running.
+pub fn background_work_allowed() -> bool {
+    PRESENCE_STATE
+        .lock()
+        .map(|state| state.status.background_work_allowed)
+        .unwrap_or(true)
+}
+
+/// Wait until background work is allowed again, checking every few seconds.
+pub async fn wait_for_background_slot() {
+    while !background_work_allowed() {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+// Milliseconds since the last keyboard/mouse input, if the platform lets us ask
+pub fn get_idle_millis() -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+        unsafe {
+            let mut info = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return None;
+            }
+            return Some(GetTickCount().wrapping_sub(info.dwTime) as u64);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("ioreg")
+            .args(&["-c", "IOHIDSystem"])
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let line = text.lines().find(|line| line.contains("HIDIdleTime"))?;
+        let nanos = line.rsplit('=').next()?.trim().parse::<u64>().ok()?;
+        return Some(nanos / 1_000_000);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use crate::mcp::linux::{display_session, DisplaySession};
+
+        // xprintidle only sees X11 input; under Wayland it would report XWayland's stale idle time
+        if display_session() != DisplaySession::Wayland {
+            let output = std::process::Command::new("xprintidle").output().ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            return String::from_utf8_lossy(&output.stdout).trim().parse::<u64>().ok();
+        }
+        // GNOME on Wayland reports it through Mutter's idle monitor; prints "(uint64 1234,)"
+        let output = std::process::Command::new("gdbus")
+            .args(&[
+                "call", "--session",
+                "--dest", "org.gnome.Mutter.IdleMonitor",
+                "--object-path", "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method", "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        return text.trim().trim_start_matches("(uint64").trim_end_matches(",)").trim().parse::<u64>().ok();
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+pub fn get_idle_seconds() -> Option<u64> {
+    get_idle_millis().map(|millis| millis / 1000)
+}
+
+/// Loopback capture or a screen share in progress means the user is in a meeting.
+pub fn is_in_meeting() -> bool {
+    let capturing = crate::audio_loopback::CAPTURE_STATE
+        .lock()
+        .map(|state| state.is_capturing)
+        .unwrap_or(false);
+    capturing || crate::screen_share::is_screen_share_active()
+}
+
+// A foreground window covering the whole primary screen is treated as a game or presentation
+async fn is_fullscreen_app_active(foreground: Option<crate::window_manager::ForegroundWindowInfo>) -> bool {
+    let Some(foreground) = foreground else {
+        return false;
+    };
+    if foreground.process_id == std::process::id() {
+        return false;
+    }
+    match crate::window_manager::get_screen_size().await {
+        Ok((width, height)) => foreground.width >= width && foreground.height >= height,
+        Err(_) => false,
+    }
+}
+
+async fn evaluate(settings: &PresenceSettings) -> PresenceStatus {
+    // Both lookups may run external commands, so they go to a blocking thread
+    let (idle_seconds, foreground) = tauri::async_runtime::spawn_blocking(|| {
+        (get_idle_seconds(), crate::window_manager::get_foreground_window_info())
+    })
+    .await
+    .unwrap_or((None, None));
+    let in_meeting = is_in_meeting();
+    let fullscreen_app = is_fullscreen_app_active(foreground).await;
+    let idle_enough = idle_seconds
+        .map(|secs| secs >= settings.resume_after_idle_minutes * 60)
+        .unwrap_or(false);
+
+    let (allowed, reason) = match settings.work_override {
+        BackgroundWorkOverride::ForcePaused => (false, "Paused manually".to_string()),
+        BackgroundWorkOverride::ForceRunning => (true, "Running manually".to_string()),
+        BackgroundWorkOverride::Auto if crate::power::should_defer_background_work() => {
+            (false, "Deferred while on battery power".to_string())
+        }
+        BackgroundWorkOverride::Auto if !(in_meeting || fullscreen_app) => (true, "User not busy".to_string()),
+        BackgroundWorkOverride::Auto if idle_enough => (
+            true,
+            format!("Idle for over {} minutes", settings.resume_after_idle_minutes),
+        ),
+        BackgroundWorkOverride::Auto if in_meeting => (false, "User is in a meeting".to_string()),
+        BackgroundWorkOverride::Auto => (false, "Fullscreen application active".to_string()),
+    };
+
+    PresenceStatus {
+        idle_seconds,
+        in_meeting,
+        fullscreen_app,
+        background_work_allowed: allowed,
+        reason,
+        settings: settings.clone(),
+    }
+}
+
+async fn refresh_presence(app_handle: &AppHandle) {
+    let settings = match PRESENCE_STATE.lock() {
+        Ok(state) => state.status.settings.clone(),
+        Err(_) => return,
+    };
+
+    let status = evaluate(&settings).await;
+
+    let changed = match PRESENCE_STATE.lock() {
+        Ok(mut state) => {
+            let changed = state.status.background_work_allowed != status.background_work_allowed;
+            state.status = status.clone();
+            changed
+        }
+        Err(_) => return,
+    };
+
+    if !status.in_meeting {
+        crate::notifications::release_held(app_handle);
+    }
+
+    if changed {
+        println!("🧍 Background work {}: {}",
+                 if status.background_work_allowed { "resumed" } else { "paused" }, status.reason);
+        if let Err(e) = app_handle.emit("presence-changed", &status) {
+            eprintln!("Failed to emit presence event: {}", e);
+        }
+    }
+}
+
+pub fn start_presence_monitor(app_handle: AppHandle) {
+    let mut state = match PRESENCE_STATE.lock() {
+        Ok(state) => state,
+        Err(_) => return,
+    };
+    if state.task.is_some() {
+        return;
+    }
+    state.status.settings = load_settings();
+
+    state.task = Some(tauri::async_runtime::spawn(async move {
+        loop {
+            refresh_presence(&app_handle).await;
+            let interval = PRESENCE_STATE
+                .lock()
+                .map(|state| state.status.settings.poll_interval_secs)
+                .unwrap_or(15)
+                .max(1);
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    }));
+}
+
+#[tauri::command]
+pub async fn get_presence_status() -> Result<PresenceStatus, String> {
+    let state = PRESENCE_STATE.lock().map_err(|e| e.to_string())?;
+    Ok(state.status.clone())
+}
+
+#[tauri::command]
+pub async fn update_presence_settings(app_handle: AppHandle, settings: PresenceSettings) -> Result<PresenceStatus, String> {
+    save_settings(settings)?;
+    refresh_presence(&app_handle).await;
+    get_presence_status().await
+}
+
+#[tauri::command]
+pub async fn set_background_work_override(
+    app_handle: AppHandle,
+    work_override: BackgroundWorkOverride,
+) -> Result<PresenceStatus, String> {
+    let settings = {
+        let state = PRESENCE_STATE.lock().map_err(|e| e.to_string())?;
+        PresenceSettings { work_override, ..state.status.settings.clone() }
+    };
+    save_settings(settings)?;
+    refresh_presence(&app_handle).await;
+    get_presence_status().await
+}
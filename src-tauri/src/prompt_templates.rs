@@ -0,0 +1,100 @@
+// src-tauri/src/prompt_templates.rs
+// User-defined prompt templates, stored alongside the other settings files and shareable as signed bundles.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub agent_type: Option<String>,
+    pub system_prompt: String,
+    pub user_template: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+fn get_templates_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("prompt_templates.json")
+}
+
+pub fn load_templates() -> Result<HashMap<String, PromptTemplate>, String> {
+    let path = get_templates_path().map_err(|e| format!("Failed to get templates path: {}", e))?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read templates file: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse templates: {}", e))
+}
+
+fn store_templates(templates: &HashMap<String, PromptTemplate>) -> Result<(), String> {
+    let path = get_templates_path().map_err(|e| format!("Failed to get templates path: {}", e))?;
+    let json = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("Failed to serialize templates: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write templates file: {}", e))
+}
+
+#[tauri::command]
+pub async fn save_prompt_template(mut template: PromptTemplate) -> Result<PromptTemplate, String> {
+    let mut templates = load_templates()?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    if template.id.is_empty() {
+        template.id = uuid::Uuid::new_v4().to_string();
+        template.created_at = now.clone();
+    }
+    template.updated_at = now;
+
+    templates.insert(template.id.clone(), template.clone());
+    store_templates(&templates)?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn list_prompt_templates() -> Result<Vec<PromptTemplate>, String> {
+    let mut templates: Vec<PromptTemplate> = load_templates()?.into_values().collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+#[tauri::command]
+pub async fn delete_prompt_template(template_id: String) -> Result<(), String> {
+    let mut templates = load_templates()?;
+    if templates.remove(&template_id).is_none() {
+        return Err(format!("Template not found: {}", template_id));
+    }
+    store_templates(&templates)
+}
+
+#[tauri::command]
+pub async fn export_prompt_template(template_id: String, path: String) -> Result<String, String> {
+    let templates = load_templates()?;
+    let template = templates
+        .get(&template_id)
+        .ok_or_else(|| format!("Template not found: {}", template_id))?;
+
+    let bundle = crate::share_format::seal("prompt_template", template)?;
+    crate::share_format::write_bundle(&bundle, Path::new(&path))?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn import_prompt_template(app_handle: tauri::AppHandle, path: String) -> Result<PromptTemplate, String> {
+    let bundle = crate::share_format::read_bundle(Path::new(&path))?;
+    let mut template: PromptTemplate = crate::share_format::open(&app_handle, bundle, "prompt_template").await?;
+
+    // Give imports a fresh id so they never overwrite a local template
+    let mut templates = load_templates()?;
+    let now = chrono::Utc::now().to_rfc3339();
+    template.id = uuid::Uuid::new_v4().to_string();
+    template.created_at = now.clone();
+    template.updated_at = now;
+
+    templates.insert(template.id.clone(), template.clone());
+    store_templates(&templates)?;
+    Ok(template)
+}
@@ -0,0 +1,127 @@
+// src-tauri/src/query_condensation.rs
+// Rewrites follow-up questions into standalone retrieval queries using recent chat history.
+use serde::{Deserialize, Serialize};
+
+use crate::ollama::{generate_completion, ChatContextMessage};
+use crate::system_prompts::QUERY_CONDENSATION_PROMPT;
+
+const CONDENSATION_MODEL: &str = "gemma3:1b-it-qat";
+const MAX_HISTORY_MESSAGES: usize = 6;
+const MAX_MESSAGE_CHARS: usize = 600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CondensedQuery {
+    pub original_query: String,
+    pub standalone_query: String,
+    pub was_rewritten: bool,
+    pub method: String, // "none", "llm", "fallback"
+}
+
+// Words that usually point back at something said earlier in the conversation
+const REFERENCE_MARKERS: &[&str] = &[
+    "it", "its", "that", "this", "those", "these", "they", "them", "he", "she",
+    "first", "second", "third", "last", "previous", "above", "other", "same", "former", "latter",
+];
+
+const FOLLOW_UP_PREFIXES: &[&str] = &["what about", "how about", "and ", "also", "why", "what else", "more on"];
+
+pub fn looks_like_follow_up(query: &str) -> bool {
+    let lower = query.trim().to_lowercase();
+    if FOLLOW_UP_PREFIXES.iter().any(|prefix| lower.starts_with(prefix)) {
+        return true;
+    }
+
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // Long, self-contained questions rarely need rewriting
+    words.len() <= 12 && words.iter().any(|w| REFERENCE_MARKERS.contains(w))
+}
+
+fn format_history(history: &[ChatContextMessage]) -> String {
+    history
+        .iter()
+        .rev()
+        .take(MAX_HISTORY_MESSAGES)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .map(|message| {
+            let content: String = message.content.chars().take(MAX_MESSAGE_CHARS).collect();
+            format!("{}: {}", message.role, content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn fallback_query(query: &str, history: &[ChatContextMessage]) -> String {
+    // Without a model, anchoring on the last user turn still beats the bare follow-up
+    match history.iter().rev().find(|m| m.role == "user") {
+        Some(previous) => {
+            let previous: String = previous.content.chars().take(200).collect();
+            format!("{} {}", previous.trim(), query.trim())
+        }
+        None => query.to_string(),
+    }
+}
+
+pub async fn condense_query(query: &str, history: &[ChatContextMessage]) -> CondensedQuery {
+    if history.is_empty() || !looks_like_follow_up(query) {
+        return CondensedQuery {
+            original_query: query.to_string(),
+            standalone_query: query.to_string(),
+            was_rewritten: false,
+            method: "none".to_string(),
+        };
+    }
+
+    let prompt = format!(
+        "Conversation history:\n{}\n\nFollow-up question: {}\n\nStandalone query:",
+        format_history(history),
+        query
+    );
+    let options = serde_json::json!({
+        "temperature": 0.0,
+        "num_predict": 64
+    });
+
+    let model = crate::agent_models::resolve_helper_model(CONDENSATION_MODEL).await;
+    match generate_completion(&model, prompt, Some(QUERY_CONDENSATION_PROMPT.to_string()), Some(options)).await {
+        Ok(response) => {
+            let rewritten = response
+                .lines()
+                .map(|line| line.trim().trim_matches('"'))
+                .find(|line| !line.is_empty())
+                .unwrap_or("")
+                .to_string();
+
+            if rewritten.is_empty() {
+                CondensedQuery {
+                    original_query: query.to_string(),
+                    standalone_query: fallback_query(query, history),
+                    was_rewritten: true,
+                    method: "fallback".to_string(),
+                }
+            } else {
+                println!("🔎 Condensed follow-up '{}' -> '{}'", query, rewritten);
+                CondensedQuery {
+                    original_query: query.to_string(),
+                    standalone_query: rewritten,
+                    was_rewritten: true,
+                    method: "llm".to_string(),
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Query condensation failed, using fallback: {}", e);
+            CondensedQuery {
+                original_query: query.to_string(),
+                standalone_query: fallback_query(query, history),
+                was_rewritten: true,
+                method: "fallback".to_string(),
+            }
+        }
+    }
+}
@@ -6,7 +6,6 @@ use std::collections::HashMap;
 use chrono::Utc;
 use uuid::Uuid;
 use std::sync::{Arc, Mutex};
-use tauri::Manager;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Document {
@@ -79,7 +78,7 @@ pub struct RagSystem {
 
 impl RagSystem {
     pub fn new(app_handle: &tauri::AppHandle) -> Result<Self, Box<dyn std::error::Error>> {
-        let app_dir = app_handle.path().app_data_dir()?;
+        let app_dir = crate::data::paths::user_data_dir(app_handle)?;
         let db_path = app_dir.join("rag_documents.db");
         let storage_path = app_dir.join("document_storage");
         
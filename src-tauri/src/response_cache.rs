@@ -0,0 +1,268 @@
+// src-tauri/src/response_cache.rs
+// Opt-in disk cache for non-streaming Ollama calls: plain generations, structured (JSON) replies
+// such as MCP plans, and image analyses such as the screen inventory. A reply is keyed by a hash
+// of everything that decides it (model, system prompt, prompt, images, output format and model
+// options), so asking the same model the same thing about the same screenshot answers from disk.
+// Streamed replies are never cached. Entries expire after `max_age_hours` and the least recently
+// used go first once there are more than `max_entries`.
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseCacheSettings {
+    pub enabled: bool,
+    pub max_age_hours: u64,
+    pub max_entries: usize,
+}
+
+impl Default for ResponseCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age_hours: 24,
+            max_entries: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub bytes: u64,
+}
+
+/// What an Ollama reply depends on.
+pub struct CacheRequest<'a> {
+    pub model: &'a str,
+    pub system: Option<&'a str>,
+    pub prompt: &'a str,
+    pub images: &'a [String],
+    pub format: Option<&'a Value>,
+    pub options: Option<&'a Value>,
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("response_cache.json")
+}
+
+fn load_settings() -> ResponseCacheSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+// Lookups run without an app handle, so the per-user directory is resolved the way Tauri would
+fn database_path() -> Result<PathBuf, String> {
+    if crate::data::paths::in_memory_database_requested() {
+        return Ok(crate::data::paths::in_memory_database_uri("enteract-response-cache"));
+    }
+    let dir = crate::data::paths::default_user_data_dir();
+    crate::data::paths::ensure_private_dir(&dir)
+        .map_err(|e| format!("Failed to create data directory {}: {}", dir.display(), e))?;
+    Ok(dir.join("response_cache.db"))
+}
+
+fn open(db_path: &Path) -> rusqlite::Result<Connection> {
+    let conn = crate::data::paths::open_database(db_path)?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS response_cache (
+            key TEXT PRIMARY KEY,
+            model TEXT NOT NULL,
+            response TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            last_used_at INTEGER NOT NULL,
+            hits INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_response_cache_last_used ON response_cache(last_used_at)",
+        [],
+    )?;
+    Ok(conn)
+}
+
+// Objects with their keys sorted, so option order never changes the key
+fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            Value::Object(keys.into_iter().map(|key| (key.clone(), canonical(&map[key]))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+        other => other.clone(),
+    }
+}
+
+impl CacheRequest<'_> {
+    fn key(&self) -> String {
+        let images: Vec<String> = self.images.iter().map(|image| format!("{:x}", Sha256::digest(image.as_bytes()))).collect();
+        let identity = serde_json::json!({
+            "model": self.model,
+            "system": self.system,
+            "prompt": self.prompt,
+            "images": images,
+            "format": self.format.map(canonical),
+            "options": self.options.map(canonical),
+        });
+        format!("{:x}", Sha256::digest(identity.to_string().as_bytes()))
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn get(conn: &Connection, key: &str, max_age_ms: i64, now: i64) -> rusqlite::Result<Option<String>> {
+    let response: Option<String> = conn
+        .query_row(
+            "SELECT response FROM response_cache WHERE key = ?1 AND created_at > ?2",
+            params![key, now - max_age_ms],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if response.is_some() {
+        conn.execute(
+            "UPDATE response_cache SET hits = hits + 1, last_used_at = ?2 WHERE key = ?1",
+            params![key, now],
+        )?;
+    }
+    Ok(response)
+}
+
+fn put(conn: &Connection, key: &str, model: &str, response: &str, settings: &ResponseCacheSettings, now: i64) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO response_cache (key, model, response, created_at, last_used_at, hits)
+         VALUES (?1, ?2, ?3, ?4, ?4, 0)",
+        params![key, model, response, now],
+    )?;
+    let max_age_ms = settings.max_age_hours as i64 * 3_600_000;
+    conn.execute("DELETE FROM response_cache WHERE created_at <= ?1", params![now - max_age_ms])?;
+    conn.execute(
+        "DELETE FROM response_cache WHERE key NOT IN (
+            SELECT key FROM response_cache ORDER BY last_used_at DESC LIMIT ?1
+        )",
+        params![settings.max_entries as i64],
+    )?;
+    Ok(())
+}
+
+/// The cached reply to `request`, when the cache is on and has a fresh one.
+pub fn lookup(request: &CacheRequest) -> Option<String> {
+    let settings = load_settings();
+    if !settings.enabled {
+        return None;
+    }
+    let conn = database_path().ok().and_then(|path| open(&path).ok())?;
+    match get(&conn, &request.key(), settings.max_age_hours as i64 * 3_600_000, now_ms()) {
+        Ok(Some(response)) => {
+            println!("⚡ Response cache hit for {}", request.model);
+            Some(response)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("Failed to read response cache: {}", e);
+            None
+        }
+    }
+}
+
+/// Keep a successful reply to `request`, when the cache is on.
+pub fn store(request: &CacheRequest, response: &str) {
+    let settings = load_settings();
+    if !settings.enabled || response.trim().is_empty() {
+        return;
+    }
+    let result = database_path()
+        .and_then(|path| open(&path).map_err(|e| e.to_string()))
+        .and_then(|conn| put(&conn, &request.key(), request.model, response, &settings, now_ms()).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("Failed to write response cache: {}", e);
+    }
+}
+
+fn open_cache() -> Result<Connection, String> {
+    open(&database_path()?).map_err(|e| format!("Failed to open response cache: {}", e))
+}
+
+#[tauri::command]
+pub fn get_response_cache_settings() -> Result<ResponseCacheSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_response_cache_settings(settings: ResponseCacheSettings) -> Result<(), String> {
+    if settings.max_age_hours == 0 || settings.max_entries == 0 {
+        return Err("Cache age and size must be at least 1".to_string());
+    }
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("💾 Saved response cache settings (enabled: {})", settings.enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_response_cache_stats() -> Result<ResponseCacheStats, String> {
+    let conn = open_cache()?;
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(hits), 0), COALESCE(SUM(LENGTH(response)), 0) FROM response_cache",
+        [],
+        |row| Ok(ResponseCacheStats {
+            entries: row.get::<_, i64>(0)? as usize,
+            hits: row.get::<_, i64>(1)? as u64,
+            bytes: row.get::<_, i64>(2)? as u64,
+        }),
+    )
+    .map_err(|e| format!("Failed to read response cache: {}", e))
+}
+
+/// Drop every cached reply. Returns how many there were.
+#[tauri::command]
+pub fn clear_response_cache() -> Result<usize, String> {
+    let conn = open_cache()?;
+    let cleared = conn.execute("DELETE FROM response_cache", [])
+        .map_err(|e| format!("Failed to clear response cache: {}", e))?;
+    println!("🧹 Cleared {} cached responses", cleared);
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::memory_db::MemoryDatabase;
+
+    #[test]
+    fn test_replies_are_keyed_by_everything_that_decides_them() {
+        let options = serde_json::json!({ "temperature": 0.0, "num_ctx": 4096 });
+        let reordered = serde_json::json!({ "num_ctx": 4096, "temperature": 0.0 });
+        let screenshot = vec!["iVBORw0KGgo=".to_string()];
+        let request = CacheRequest { model: "qwen2.5vl:3b", system: Some("List the controls"), prompt: "Inventory", images: &screenshot, format: None, options: Some(&options) };
+        assert_eq!(request.key(), CacheRequest { options: Some(&reordered), ..request }.key());
+        assert_ne!(request.key(), CacheRequest { images: &[], ..request }.key());
+        assert_ne!(request.key(), CacheRequest { model: "llava:7b", ..request }.key());
+
+        let db = MemoryDatabase::new();
+        let conn = open(db.path()).unwrap();
+        let settings = ResponseCacheSettings { enabled: true, max_age_hours: 1, max_entries: 2 };
+        put(&conn, "a", "m", "first", &settings, 1_000).unwrap();
+        put(&conn, "b", "m", "second", &settings, 2_000).unwrap();
+        assert_eq!(get(&conn, "a", 3_600_000, 3_000).unwrap().as_deref(), Some("first"));
+        // "b" is now the least recently used, so it goes when a third reply arrives
+        put(&conn, "c", "m", "third", &settings, 4_000).unwrap();
+        assert_eq!(get(&conn, "b", 3_600_000, 5_000).unwrap(), None);
+        // Expired after an hour
+        assert_eq!(get(&conn, "a", 3_600_000, 1_000 + 3_600_000).unwrap(), None);
+    }
+}
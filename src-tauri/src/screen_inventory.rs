@@ -0,0 +1,330 @@
+// src-tauri/src/screen_inventory.rs
+// Structured screen reading: the vision agent lists the application, UI elements and visible
+// errors on a screenshot as JSON instead of prose, for code that acts on the screen (the MCP
+// planner) rather than for the user to read. Small vision models often wrap, truncate or loosely
+// shape their JSON, so the reply is repaired and coerced here before anyone sees it.
+use crate::system_prompts::VISION_INVENTORY_PROMPT;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+const MAX_ELEMENTS: usize = 100;
+// One retry when the reply can't be repaired into JSON at all
+const ATTEMPTS: usize = 2;
+
+/// Approximate area of an element, as fractions (0 to 1) of the image from its top-left corner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Region {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiElement {
+    pub kind: String, // button, link, input, text, ...
+    pub text: String,
+    pub region: Option<Region>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenInventory {
+    pub app: Option<String>,
+    pub elements: Vec<UiElement>,
+    pub errors: Vec<String>,
+    pub width: Option<u32>, // Size of the analyzed image, when known
+    pub height: Option<u32>,
+    pub model: String,
+}
+
+impl ScreenInventory {
+    /// The element whose text the request mentions, preferring the longest (most specific) text.
+    pub fn element_named_in(&self, request: &str) -> Option<&UiElement> {
+        let request = request.to_lowercase();
+        self.elements
+            .iter()
+            .filter(|element| element.text.chars().count() >= 2 && request.contains(&element.text.to_lowercase()))
+            .max_by_key(|element| element.text.len())
+    }
+
+    /// Pixel center of an element, when both its region and the image size are known.
+    pub fn center_of(&self, element: &UiElement) -> Option<(i32, i32)> {
+        let region = element.region.as_ref()?;
+        let (width, height) = (self.width? as f32, self.height? as f32);
+        Some((
+            ((region.x + region.width / 2.0) * width).round() as i32,
+            ((region.y + region.height / 2.0) * height).round() as i32,
+        ))
+    }
+}
+
+fn strip_trailing_commas(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in json.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '}' || c == ']' {
+            let trimmed = out.trim_end().len();
+            if out[..trimmed].ends_with(',') {
+                out.truncate(trimmed - 1);
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
+// Close whatever a truncated reply left open: a string, then arrays and objects innermost first
+fn close_truncated(json: &str) -> String {
+    let mut open = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in json.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    let mut closed = json.trim_end().to_string();
+    if in_string {
+        closed.push('"');
+    } else if closed.ends_with(':') {
+        closed.push_str("null");
+    }
+    while let Some(c) = open.pop() {
+        closed.push(c);
+    }
+    strip_trailing_commas(&closed)
+}
+
+/// Parse a model reply into JSON, repairing code fences, surrounding prose, trailing commas and
+/// truncation.
+pub fn repair_json(raw: &str) -> Option<serde_json::Value> {
+    // Cut the object out of whatever the model wrapped around it
+    let tail = &raw[raw.find('{')?..];
+    let object = match tail.rfind('}') {
+        Some(end) => &tail[..=end],
+        None => tail,
+    };
+    let candidates = [object.to_string(), strip_trailing_commas(object), close_truncated(tail)];
+    candidates
+        .iter()
+        .find_map(|candidate| serde_json::from_str::<serde_json::Value>(candidate).ok())
+        .filter(|value| value.is_object())
+}
+
+fn string_field(value: &serde_json::Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(|v| v.as_str()))
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty() && text != "null")
+}
+
+fn number(value: Option<&serde_json::Value>) -> Option<f32> {
+    value.and_then(|v| v.as_f64()).map(|v| v as f32)
+}
+
+// Accepts {x, y, width|w, height|h} or [x, y, width, height]; pixel values are scaled by the image
+// size when it's known and dropped otherwise
+fn parse_region(value: &serde_json::Value, size: Option<(u32, u32)>) -> Option<Region> {
+    let [x, y, width, height] = match value {
+        serde_json::Value::Array(items) if items.len() == 4 => [
+            number(items.first())?,
+            number(items.get(1))?,
+            number(items.get(2))?,
+            number(items.get(3))?,
+        ],
+        serde_json::Value::Object(_) => [
+            number(value.get("x"))?,
+            number(value.get("y"))?,
+            number(value.get("width").or_else(|| value.get("w")))?,
+            number(value.get("height").or_else(|| value.get("h")))?,
+        ],
+        _ => return None,
+    };
+    let (x, y, width, height) = if [x, y, width, height].iter().all(|v| *v <= 1.0) {
+        (x, y, width, height)
+    } else {
+        let (image_width, image_height) = size?;
+        let (image_width, image_height) = (image_width as f32, image_height as f32);
+        (x / image_width, y / image_height, width / image_width, height / image_height)
+    };
+    let x = x.clamp(0.0, 1.0);
+    let y = y.clamp(0.0, 1.0);
+    Some(Region {
+        x,
+        y,
+        width: width.clamp(0.0, 1.0 - x),
+        height: height.clamp(0.0, 1.0 - y),
+    })
+}
+
+fn parse_element(value: &serde_json::Value, size: Option<(u32, u32)>) -> Option<UiElement> {
+    if let Some(text) = value.as_str() {
+        return Some(UiElement { kind: "text".to_string(), text: text.trim().to_string(), region: None })
+            .filter(|element| !element.text.is_empty());
+    }
+    let text = string_field(value, &["text", "label", "name", "content"]).unwrap_or_default();
+    let region = ["region", "bbox", "bounds", "box"]
+        .iter()
+        .find_map(|key| value.get(*key))
+        .and_then(|region| parse_region(region, size));
+    if text.is_empty() && region.is_none() {
+        return None;
+    }
+    Some(UiElement {
+        kind: string_field(value, &["type", "kind", "role"]).unwrap_or_else(|| "element".to_string()).to_lowercase(),
+        text,
+        region,
+    })
+}
+
+/// Shape repaired JSON into an inventory, tolerating the key names models commonly substitute.
+pub fn parse_inventory(value: &serde_json::Value, size: Option<(u32, u32)>, model: &str) -> ScreenInventory {
+    let list = |keys: &[&str]| -> Vec<serde_json::Value> {
+        keys.iter()
+            .find_map(|key| value.get(*key).and_then(|v| v.as_array()))
+            .cloned()
+            .unwrap_or_default()
+    };
+    let elements = list(&["elements", "ui_elements", "uiElements"])
+        .iter()
+        .filter_map(|element| parse_element(element, size))
+        .take(MAX_ELEMENTS)
+        .collect();
+    let errors = list(&["errors", "detected_errors", "detectedErrors"])
+        .iter()
+        .filter_map(|error| match error.as_str() {
+            Some(text) => Some(text.trim().to_string()),
+            None => string_field(error, &["message", "text"]),
+        })
+        .filter(|error| !error.is_empty())
+        .collect();
+
+    ScreenInventory {
+        app: string_field(value, &["app", "application", "detected_app", "detectedApp"]),
+        elements,
+        errors,
+        width: size.map(|(width, _)| width),
+        height: size.map(|(_, height)| height),
+        model: model.to_string(),
+    }
+}
+
+fn image_size(image_base64: &str) -> Option<(u32, u32)> {
+    use base64::Engine;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(image_base64).ok()?;
+    image::ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Ask the vision agent's model for a structured inventory of a screenshot.
+pub async fn analyze_image(app_handle: &AppHandle, image_base64: String, size: Option<(u32, u32)>) -> Result<ScreenInventory, String> {
+    let model = crate::agent_models::resolve_agent_model(app_handle, "vision").await?;
+    let size = size.or_else(|| image_size(&image_base64));
+    let active_app = crate::window_manager::active_app().await;
+    let prompt = match &active_app {
+        Some(app) => format!("Active application: {} ({})\n\nList the screen contents as JSON.", app.app_name, app.window_title),
+        None => "List the screen contents as JSON.".to_string(),
+    };
+    let options = serde_json::json!({ "temperature": 0.1, "num_predict": 2048 });
+
+    let mut last_reply = String::new();
+    for attempt in 1..=ATTEMPTS {
+        let reply = crate::ollama::generate_json_with_image(
+            &model,
+            prompt.clone(),
+            VISION_INVENTORY_PROMPT.to_string(),
+            image_base64.clone(),
+            Some(options.clone()),
+        )
+        .await?;
+        if let Some(value) = repair_json(&reply) {
+            let mut inventory = parse_inventory(&value, size, &model);
+            if inventory.app.is_none() {
+                inventory.app = active_app.map(|app| app.app_name);
+            }
+            println!("🧾 Screen inventory: {} element(s), {} error(s)", inventory.elements.len(), inventory.errors.len());
+            return Ok(inventory);
+        }
+        println!("⚠️ Screen inventory attempt {} was not valid JSON", attempt);
+        last_reply = reply;
+    }
+    let preview: String = last_reply.chars().take(200).collect();
+    Err(format!("The vision model did not return usable JSON: {}", preview))
+}
+
+/// Capture the primary screen and inventory it.
+pub async fn capture_inventory(app_handle: &AppHandle) -> Result<ScreenInventory, String> {
+    let screenshot = crate::screenshot::capture_screenshot().await?;
+    analyze_image(app_handle, screenshot.image_base64, Some((screenshot.width, screenshot.height))).await
+}
+
+/// Structured counterpart of generate_vision_analysis. Analyzes `image_base64`, else a capture of
+/// `window` (id or title), else a fresh capture of the primary screen.
+#[tauri::command]
+pub async fn analyze_screen_structured(
+    app_handle: AppHandle,
+    image_base64: Option<String>,
+    window: Option<String>,
+) -> Result<ScreenInventory, String> {
+    match (image_base64, window) {
+        (Some(image_base64), _) => analyze_image(&app_handle, image_base64, None).await,
+        (None, Some(window)) => {
+            let screenshot = crate::screenshot::capture_window(window).await?;
+            analyze_image(&app_handle, screenshot.image_base64, Some((screenshot.width, screenshot.height))).await
+        }
+        (None, None) => capture_inventory(&app_handle).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loose_model_output_is_repaired_into_an_inventory() {
+        let raw = "Here you go:\n```json\n{\"application\": \"VS Code\", \"elements\": [\n  {\"type\": \"Button\", \"label\": \"Run\", \"bbox\": [960, 540, 96, 54]},\n  \"Explorer\",\n  {\"type\": \"tab\", \"text\": \"main.rs\", \"region\": {\"x\": 0.2, \"y\": 0.05, \"w\": 0.1, \"h\": 0.03}},\n], \"errors\": [{\"message\": \"cannot find value `x`\"}], \"notes\": \"the build is fail";
+        let value = repair_json(raw).unwrap();
+        let inventory = parse_inventory(&value, Some((1920, 1080)), "qwen2.5vl:3b");
+
+        assert_eq!(inventory.app.as_deref(), Some("VS Code"));
+        assert_eq!(inventory.elements.len(), 3);
+        assert_eq!(inventory.elements[0].kind, "button");
+        assert_eq!(inventory.elements[0].region, Some(Region { x: 0.5, y: 0.5, width: 0.05, height: 0.05 }));
+        assert_eq!(inventory.elements[1].text, "Explorer");
+        assert_eq!(inventory.errors, vec!["cannot find value `x`".to_string()]);
+        assert_eq!(inventory.element_named_in("click the Run button").map(|e| e.text.as_str()), Some("Run"));
+        assert_eq!(inventory.center_of(&inventory.elements[0]), Some((1008, 567)));
+        assert!(repair_json("no json here").is_none());
+    }
+}
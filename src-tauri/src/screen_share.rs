@@ -0,0 +1,308 @@
+// src-tauri/src/screen_share.rs
+// Detects active screen sharing so sensitive overlays can be hidden before they end up on someone else's screen.
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Process names of dedicated sharing/capture hosts (lowercase, without extension). These only run
+// while a share is live; apps like OBS run whether or not they're capturing, so they aren't listed.
+const SHARE_PROCESS_INDICATORS: &[(&str, &str)] = &[
+    ("cpthost", "Zoom"),
+    ("zoomsharehost", "Zoom"),
+    ("caphost", "Zoom"),
+    ("screenrecordingcontrol", "macOS Screen Recording"),
+];
+
+// Window titles shown by meeting apps and browsers (getDisplayMedia) while a share is live
+const SHARE_TITLE_INDICATORS: &[(&str, &str)] = &[
+    ("is sharing your screen", "Browser"),
+    ("is sharing a window", "Browser"),
+    ("is sharing this tab", "Browser"),
+    ("you are screen sharing", "Zoom"),
+    ("zoom share", "Zoom"),
+    ("sharing control bar", "Microsoft Teams"),
+    ("you're sharing", "Microsoft Teams"),
+];
+
+// Our windows that show assistant content; the approval dialog stays up so pending actions can be answered
+const OVERLAY_WINDOW_LABELS: &[&str] = &["main", "caption-bar", "quick-ask"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ScreenShareStatus {
+    pub is_sharing: bool,
+    pub sources: Vec<String>,
+    pub detected_at: Option<i64>,
+    pub overlays_hidden: bool,
+    pub monitoring: bool,
+}
+
+#[derive(Default)]
+struct ScreenShareMonitor {
+    status: ScreenShareStatus,
+    auto_hide: bool,
+    hidden_windows: Vec<String>,
+    monitor_handle: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+lazy_static::lazy_static! {
+    static ref SCREEN_SHARE_MONITOR: Arc<Mutex<ScreenShareMonitor>> = Arc::new(Mutex::new(ScreenShareMonitor::default()));
+}
+
+/// Returns true while a screen share is believed to be active.
+/// Other subsystems use this to keep transcript text out of notifications.
+pub fn is_screen_share_active() -> bool {
+    SCREEN_SHARE_MONITOR
+        .lock()
+        .map(|monitor| monitor.status.is_sharing)
+        .unwrap_or(false)
+}
+
+fn list_process_snapshot() -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("tasklist")
+        .args(&["/V", "/FO", "CSV", "/NH"])
+        .output()
+        .map_err(|e| format!("Failed to execute tasklist: {}", e))?;
+
+    #[cfg(not(target_os = "windows"))]
+    let output = Command::new("ps")
+        .args(&["-A", "-o", "comm="])
+        .output()
+        .map_err(|e| format!("Failed to execute ps: {}", e))?;
+
+    if !output.status.success() {
+        return Err("Process listing command failed".to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_lowercase())
+}
+
+// tasklist /V already carries window titles; elsewhere they're read separately. Lowercased like the snapshot.
+#[cfg(target_os = "windows")]
+async fn list_window_titles() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+async fn list_window_titles() -> Vec<String> {
+    // Needs wmctrl on X11; Wayland doesn't expose other apps' windows, leaving process names only
+    crate::mcp::linux::list_windows()
+        .await
+        .map(|windows| windows.into_iter().map(|window| window.title.to_lowercase()).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+async fn list_window_titles() -> Vec<String> {
+    // System Events needs the accessibility permission the automation tools already ask for
+    let output = tokio::process::Command::new("osascript")
+        .args(&["-e", "tell application \"System Events\" to get name of every window of every process"])
+        .output()
+        .await;
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split(", ")
+            .map(|title| title.trim().to_lowercase())
+            .filter(|title| !title.is_empty() && title != "missing value")
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+async fn list_window_titles() -> Vec<String> {
+    Vec::new()
+}
+
+fn detect_share_sources(snapshot: &str, window_titles: &[String]) -> Vec<String> {
+    let mut sources = HashSet::new();
+
+    for line in snapshot.lines() {
+        // tasklist CSV puts the image name in the first column, ps prints a bare path
+        let image = line
+            .split(',')
+            .next()
+            .unwrap_or("")
+            .trim_matches('"')
+            .rsplit(|c| c == '/' || c == '\\')
+            .next()
+            .unwrap_or("")
+            .trim_end_matches(".exe");
+
+        for (indicator, source) in SHARE_PROCESS_INDICATORS {
+            if image == *indicator {
+                sources.insert(source.to_string());
+            }
+        }
+
+        for (indicator, source) in SHARE_TITLE_INDICATORS {
+            if line.contains(indicator) {
+                sources.insert(source.to_string());
+            }
+        }
+    }
+
+    for title in window_titles {
+        for (indicator, source) in SHARE_TITLE_INDICATORS {
+            if title.contains(indicator) {
+                sources.insert(source.to_string());
+            }
+        }
+    }
+
+    let mut sources: Vec<String> = sources.into_iter().collect();
+    sources.sort();
+    sources
+}
+
+fn hide_overlay_windows(app_handle: &AppHandle) -> Vec<String> {
+    let mut hidden = Vec::new();
+    for (label, window) in app_handle.webview_windows() {
+        if !OVERLAY_WINDOW_LABELS.contains(&label.as_str()) {
+            continue;
+        }
+        if window.is_visible().unwrap_or(false) {
+            if let Err(e) = window.hide() {
+                eprintln!("Failed to hide window '{}' during screen share: {}", label, e);
+                continue;
+            }
+            hidden.push(label);
+        }
+    }
+    hidden
+}
+
+fn restore_overlay_windows(app_handle: &AppHandle, labels: &[String]) {
+    for label in labels {
+        if let Some(window) = app_handle.get_webview_window(label) {
+            if let Err(e) = window.show() {
+                eprintln!("Failed to restore window '{}' after screen share: {}", label, e);
+            }
+        }
+    }
+}
+
+fn apply_detection(app_handle: &AppHandle, sources: Vec<String>) {
+    let is_sharing = !sources.is_empty();
+
+    let status = {
+        let mut monitor = match SCREEN_SHARE_MONITOR.lock() {
+            Ok(monitor) => monitor,
+            Err(_) => return,
+        };
+
+        if monitor.status.is_sharing == is_sharing && monitor.status.sources == sources {
+            return;
+        }
+
+        if is_sharing && !monitor.status.is_sharing {
+            println!("🖥️ Screen share detected: {:?}", sources);
+            monitor.status.detected_at = Some(chrono::Utc::now().timestamp_millis());
+            if monitor.auto_hide {
+                monitor.hidden_windows = hide_overlay_windows(app_handle);
+                monitor.status.overlays_hidden = !monitor.hidden_windows.is_empty();
+            }
+        } else if !is_sharing {
+            println!("🖥️ Screen share ended");
+            monitor.status.detected_at = None;
+            let hidden = std::mem::take(&mut monitor.hidden_windows);
+            restore_overlay_windows(app_handle, &hidden);
+            monitor.status.overlays_hidden = false;
+        }
+
+        monitor.status.is_sharing = is_sharing;
+        monitor.status.sources = sources;
+        monitor.status.clone()
+    };
+
+    if let Err(e) = app_handle.emit("screen-share-detected", &status) {
+        eprintln!("Failed to emit screen share event: {}", e);
+    }
+}
+
+#[tauri::command]
+pub async fn start_screen_share_monitor(
+    app_handle: AppHandle,
+    interval_ms: Option<u64>,
+    auto_hide: Option<bool>,
+) -> Result<ScreenShareStatus, String> {
+    let interval = Duration::from_millis(interval_ms.unwrap_or(2000).max(500));
+
+    let mut monitor = SCREEN_SHARE_MONITOR.lock().map_err(|e| e.to_string())?;
+    monitor.auto_hide = auto_hide.unwrap_or(true);
+
+    if monitor.monitor_handle.is_none() {
+        let handle = app_handle.clone();
+        monitor.monitor_handle = Some(tauri::async_runtime::spawn(async move {
+            loop {
+                let snapshot = tokio::task::spawn_blocking(list_process_snapshot).await;
+                match snapshot {
+                    Ok(Ok(snapshot)) => {
+                        let titles = list_window_titles().await;
+                        apply_detection(&handle, detect_share_sources(&snapshot, &titles));
+                    }
+                    Ok(Err(e)) => eprintln!("Screen share detection failed: {}", e),
+                    Err(e) => eprintln!("Screen share detection task failed: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }));
+        println!("🖥️ Screen share monitor started (interval: {:?})", interval);
+    }
+
+    monitor.status.monitoring = true;
+    Ok(monitor.status.clone())
+}
+
+#[tauri::command]
+pub async fn stop_screen_share_monitor(app_handle: AppHandle) -> Result<(), String> {
+    let mut monitor = SCREEN_SHARE_MONITOR.lock().map_err(|e| e.to_string())?;
+
+    if let Some(handle) = monitor.monitor_handle.take() {
+        handle.abort();
+    }
+
+    // Never leave the overlay hidden once we stop watching
+    let hidden = std::mem::take(&mut monitor.hidden_windows);
+    restore_overlay_windows(&app_handle, &hidden);
+
+    monitor.status = ScreenShareStatus::default();
+    println!("🖥️ Screen share monitor stopped");
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_screen_share_status() -> Result<ScreenShareStatus, String> {
+    let monitor = SCREEN_SHARE_MONITOR.lock().map_err(|e| e.to_string())?;
+    Ok(monitor.status.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_share_sources() {
+        // tasklist /V CSV, lowercased: image name first, window title last
+        let tasklist = concat!(
+            "\"explorer.exe\",\"4120\",\"console\",\"1\",\"98,304 k\",\"running\",\"pc\\user\",\"0:00:12\",\"n/a\"\n",
+            "\"cpthost.exe\",\"9012\",\"console\",\"1\",\"40,112 k\",\"running\",\"pc\\user\",\"0:00:03\",\"n/a\"\n",
+        );
+        assert_eq!(detect_share_sources(tasklist, &[]), vec!["Zoom".to_string()]);
+
+        let tasklist = "\"chrome.exe\",\"7000\",\"console\",\"1\",\"212,000 k\",\"running\",\"pc\\user\",\"0:01:40\",\"meet.google.com is sharing your screen.\"\n";
+        assert_eq!(detect_share_sources(tasklist, &[]), vec!["Browser".to_string()]);
+
+        // ps prints bare paths and no titles; those come from the window list
+        let ps = "/sbin/launchd\n/applications/obs.app/contents/macos/obs\n/usr/bin/zsh\n";
+        assert!(detect_share_sources(ps, &[]).is_empty(), "OBS merely running isn't a share");
+        let titles = vec!["sharing control bar | microsoft teams".to_string(), "inbox - mail".to_string()];
+        assert_eq!(detect_share_sources(ps, &titles), vec!["Microsoft Teams".to_string()]);
+
+        // Substrings of other process names don't count
+        assert!(detect_share_sources("/usr/lib/mycpthost-helper\n", &[]).is_empty());
+    }
+}
@@ -1,11 +1,12 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use tantivy::collector::TopDocs;
-use tantivy::query::QueryParser;
-use tantivy::schema::{Schema, STORED, TEXT, FAST, Field, Value};
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::query::{AllQuery, Query, QueryParser, TermQuery};
+use tantivy::schema::{Schema, STORED, STRING, TEXT, FAST, Field, IndexRecordOption, Value};
 use tantivy::{Index, IndexWriter, IndexReader};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +39,29 @@ pub struct SearchResult {
     pub metadata: Option<String>,
 }
 
+// Reciprocal rank fusion constant shared by hybrid search and explanations
+const RRF_K: f32 = 60.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermContribution {
+    pub term: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkScoreExplanation {
+    pub chunk_id: String,
+    pub bm25_score: f32,
+    pub bm25_rank: Option<usize>,
+    pub term_contributions: Vec<TermContribution>,
+    pub vector_similarity: Option<f32>,
+    pub vector_rank: Option<usize>, // Always None until search_vector is implemented
+    pub bm25_weight: f32,
+    pub vector_weight: f32,
+    pub fused_score: f32,
+    pub bm25_explanation: Option<serde_json::Value>,
+}
+
 #[derive(Clone)]
 pub struct SearchService {
     index: Arc<Index>,
@@ -46,6 +70,7 @@ pub struct SearchService {
     schema: Schema,
     fields: SearchFields,
     config: SearchConfig,
+    needs_reindex: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone)]
@@ -64,8 +89,9 @@ impl SearchService {
         // Build schema
         let mut schema_builder = Schema::builder();
         
-        let chunk_id = schema_builder.add_text_field("chunk_id", STORED | FAST);
-        let document_id = schema_builder.add_text_field("document_id", STORED | FAST);
+        // Ids are indexed as raw strings so deletes by term actually match
+        let chunk_id = schema_builder.add_text_field("chunk_id", STRING | STORED | FAST);
+        let document_id = schema_builder.add_text_field("document_id", STRING | STORED | FAST);
         let content = schema_builder.add_text_field("content", TEXT | STORED);
         let embedding = schema_builder.add_bytes_field("embedding", STORED | FAST);
         let metadata = schema_builder.add_text_field("metadata", STORED);
@@ -89,8 +115,20 @@ impl SearchService {
             let _ = std::fs::remove_file(&lock_file); // Ignore errors, might be in use
         }
         
+        let mut needs_reindex = false;
         let index = if index_dir.join("meta.json").exists() {
-            Index::open_in_dir(&index_dir)?
+            let existing = Index::open_in_dir(&index_dir)?;
+            if Self::ids_are_indexed(&existing.schema()) {
+                existing
+            } else {
+                // Older indexes stored ids without indexing them, so deletes never applied
+                println!("Search index uses an outdated schema, rebuilding: {:?}", index_dir);
+                drop(existing);
+                std::fs::remove_dir_all(&index_dir)?;
+                std::fs::create_dir_all(&index_dir)?;
+                needs_reindex = true;
+                Index::create_in_dir(&index_dir, schema.clone())?
+            }
         } else {
             Index::create_in_dir(&index_dir, schema.clone())?
         };
@@ -107,9 +145,23 @@ impl SearchService {
             schema,
             fields,
             config,
+            needs_reindex: Arc::new(AtomicBool::new(needs_reindex)),
         })
     }
     
+    fn ids_are_indexed(schema: &Schema) -> bool {
+        ["chunk_id", "document_id"].iter().all(|name| {
+            schema.get_field(name)
+                .map(|field| schema.get_field_entry(field).is_indexed())
+                .unwrap_or(false)
+        })
+    }
+    
+    /// True once after the index was recreated and must be repopulated from the database
+    pub fn take_needs_reindex(&self) -> bool {
+        self.needs_reindex.swap(false, Ordering::SeqCst)
+    }
+    
     pub fn initialize_writer(&self) -> Result<()> {
         let mut writer_guard = self.writer.lock().map_err(|e| anyhow!("Mutex lock failed: {}", e))?;
         
@@ -232,7 +284,7 @@ impl SearchService {
         vector_results: Vec<SearchResult>,
         limit: usize,
     ) -> Result<Vec<SearchResult>> {
-        let k = 60.0; // RRF parameter
+        let k = RRF_K;
         let mut score_map: HashMap<String, (SearchResult, f32)> = HashMap::new();
         
         // Process BM25 results
@@ -266,6 +318,94 @@ impl SearchService {
         Ok(final_results)
     }
     
+    /// Score breakdown for the `limit` best-scoring chunks of one document against a query.
+    /// BM25 ranks and fused scores are the ones hybrid_search computes. The index has no vector
+    /// search yet, so vector_rank stays None and vector_similarity is informational only.
+    pub fn explain_document(
+        &self,
+        query: &str,
+        document_id: &str,
+        query_embedding: Option<&[f32]>,
+        limit: usize,
+    ) -> Result<Vec<ChunkScoreExplanation>> {
+        let searcher = self.reader.searcher();
+        let query_parser = QueryParser::for_index(&self.index, vec![self.fields.content]);
+        let parsed_query = query_parser.parse_query(query)?;
+        
+        // Ranks as hybrid_search would see them
+        let bm25_ranking = self.search_bm25(query, limit * 2)?;
+        let vector_ranking = match query_embedding {
+            Some(embedding) => self.search_vector(embedding, limit * 2)?,
+            None => Vec::new(),
+        };
+        
+        let mut terms: Vec<&str> = query.split_whitespace().collect();
+        terms.dedup();
+        let term_queries: Vec<(String, Box<dyn Query>)> = terms.iter()
+            .filter_map(|term| query_parser.parse_query(term).ok().map(|q| (term.to_string(), q)))
+            .collect();
+        
+        let document_query = TermQuery::new(
+            tantivy::Term::from_field_text(self.fields.document_id, document_id),
+            IndexRecordOption::Basic,
+        );
+        let addresses = searcher.search(&document_query, &DocSetCollector)?;
+        
+        let mut explanations = Vec::new();
+        for address in addresses {
+            let doc: tantivy::TantivyDocument = searcher.doc(address)?;
+            let chunk_id = doc.get_first(self.fields.chunk_id)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            
+            // explain() errors when the document doesn't match, which simply means no BM25 contribution
+            let bm25_explanation = parsed_query.explain(&searcher, address).ok();
+            let bm25_score = bm25_explanation.as_ref().map(|e| e.value()).unwrap_or(0.0);
+            
+            let term_contributions = term_queries.iter()
+                .map(|(term, term_query)| TermContribution {
+                    term: term.clone(),
+                    score: term_query.explain(&searcher, address).map(|e| e.value()).unwrap_or(0.0),
+                })
+                .collect();
+            
+            let vector_similarity = match (query_embedding, doc.get_first(self.fields.embedding).and_then(|v| v.as_bytes())) {
+                (Some(query_embedding), Some(bytes)) => bytes_to_embedding(bytes)
+                    .ok()
+                    .map(|embedding| cosine_similarity(query_embedding, &embedding)),
+                _ => None,
+            };
+            
+            let bm25_rank = bm25_ranking.iter().position(|r| r.chunk_id == chunk_id);
+            let vector_rank = vector_ranking.iter().position(|r| r.chunk_id == chunk_id);
+            let fused_score = bm25_rank.map(|rank| self.config.bm25_weight / (RRF_K + rank as f32 + 1.0)).unwrap_or(0.0)
+                + vector_rank.map(|rank| self.config.vector_weight / (RRF_K + rank as f32 + 1.0)).unwrap_or(0.0);
+            
+            explanations.push(ChunkScoreExplanation {
+                chunk_id,
+                bm25_score,
+                bm25_rank,
+                term_contributions,
+                vector_similarity,
+                vector_rank,
+                bm25_weight: self.config.bm25_weight,
+                vector_weight: self.config.vector_weight,
+                fused_score,
+                bm25_explanation: bm25_explanation.and_then(|e| serde_json::to_value(&e).ok()),
+            });
+        }
+        
+        // Chunks outside the fused ranking tie at 0, so BM25 decides among them
+        explanations.sort_by(|a, b| {
+            b.fused_score.partial_cmp(&a.fused_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.bm25_score.partial_cmp(&a.bm25_score).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        explanations.truncate(limit);
+        Ok(explanations)
+    }
+    
     pub fn delete_document(&self, document_id: &str) -> Result<()> {
         let mut writer_guard = self.writer.lock().map_err(|e| anyhow!("Mutex lock failed: {}", e))?;
         let writer = writer_guard.as_mut().ok_or_else(|| anyhow!("Writer not initialized"))?;
@@ -276,6 +416,34 @@ impl SearchService {
         Ok(())
     }
     
+    pub fn delete_chunks(&self, chunk_ids: &[String]) -> Result<()> {
+        let mut writer_guard = self.writer.lock().map_err(|e| anyhow!("Mutex lock failed: {}", e))?;
+        let writer = writer_guard.as_mut().ok_or_else(|| anyhow!("Writer not initialized"))?;
+        
+        for chunk_id in chunk_ids {
+            writer.delete_term(tantivy::Term::from_field_text(self.fields.chunk_id, chunk_id));
+        }
+        
+        Ok(())
+    }
+    
+    /// Every document id currently present in the index, used to spot entries the database no longer knows about
+    pub fn indexed_document_ids(&self) -> Result<HashSet<String>> {
+        self.reader.reload()?;
+        let searcher = self.reader.searcher();
+        let addresses = searcher.search(&AllQuery, &DocSetCollector)?;
+        
+        let mut document_ids = HashSet::new();
+        for address in addresses {
+            let doc: tantivy::TantivyDocument = searcher.doc(address)?;
+            if let Some(document_id) = doc.get_first(self.fields.document_id).and_then(|v| v.as_str()) {
+                document_ids.insert(document_id.to_string());
+            }
+        }
+        
+        Ok(document_ids)
+    }
+    
     pub fn clear_index(&self) -> Result<()> {
         let mut writer_guard = self.writer.lock().map_err(|e| anyhow!("Mutex lock failed: {}", e))?;
         let writer = writer_guard.as_mut().ok_or_else(|| anyhow!("Writer not initialized"))?;
@@ -366,4 +534,27 @@ mod tests {
         let service = SearchService::new(temp_dir.path().to_path_buf(), None);
         assert!(service.is_ok());
     }
+    
+    #[test]
+    fn test_explain_document_keeps_the_best_chunks_up_to_limit() {
+        let temp_dir = tempdir().unwrap();
+        let service = SearchService::new(temp_dir.path().to_path_buf(), None).unwrap();
+        service.initialize_writer().unwrap();
+        let contents = ["apple apple pie", "banana bread", "apple crumble", "cherry tart", "plum jam"];
+        service.add_documents(contents.iter().enumerate().map(|(i, content)| DocumentChunk {
+            id: format!("chunk-{}", i),
+            document_id: "doc".to_string(),
+            content: content.to_string(),
+            embedding: None,
+            metadata: None,
+        }).collect()).unwrap();
+        service.commit().unwrap();
+        service.reader.reload().unwrap();
+        
+        let explanations = service.explain_document("apple", "doc", None, 2).unwrap();
+        assert_eq!(explanations.len(), 2);
+        assert_eq!(explanations[0].chunk_id, "chunk-0");
+        assert_eq!(explanations[1].chunk_id, "chunk-2");
+        assert!(explanations.iter().all(|e| e.bm25_rank.is_some() && e.vector_rank.is_none()));
+    }
 }
\ No newline at end of file
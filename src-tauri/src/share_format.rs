@@ -0,0 +1,331 @@
+// src-tauri/src/share_format.rs
+// Signed JSON envelope for sharing automation plans and prompt templates between machines.
+// Every install has its own ed25519 key in the system keychain; bundles carry the signer's public
+// key, and importing from a key this install hasn't seen before asks the user to trust it first.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+pub const SHARE_FORMAT: &str = "enteract-share";
+// Version 1 files only carried an unkeyed checksum and can't be verified
+pub const SHARE_SCHEMA_VERSION: u32 = 2;
+const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+const KEYCHAIN_SERVICE: &str = "enteract";
+const KEYCHAIN_SIGNING_KEY_ACCOUNT: &str = "share-signing-key";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedBundle {
+    pub format: String,
+    pub schema_version: u32,
+    pub kind: String,
+    pub app_version: String,
+    pub exported_at: String,
+    #[serde(default)]
+    pub signature_algorithm: String,
+    /// Base64 ed25519 public key of the exporting install
+    #[serde(default)]
+    pub public_key: String,
+    /// Base64 ed25519 signature over format, schema version, kind and payload
+    #[serde(default)]
+    pub signature: String,
+    pub payload: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedSigner {
+    pub public_key: String,
+    pub fingerprint: String,
+    pub trusted_at: String,
+}
+
+// serde_json::Value keeps object keys sorted, so serializing it gives a stable input. Each field is
+// length-prefixed so no two (kind, payload) pairs share the same bytes.
+fn signing_input(kind: &str, schema_version: u32, payload: &serde_json::Value) -> Result<Vec<u8>, String> {
+    let payload_json = serde_json::to_string(payload)
+        .map_err(|e| format!("Failed to serialize payload: {}", e))?;
+
+    let mut input = Vec::with_capacity(payload_json.len() + 64);
+    for field in [SHARE_FORMAT.as_bytes(), &schema_version.to_le_bytes(), kind.as_bytes(), payload_json.as_bytes()] {
+        input.extend_from_slice(&(field.len() as u64).to_le_bytes());
+        input.extend_from_slice(field);
+    }
+    Ok(input)
+}
+
+/// Short, human-comparable form of a public key, shown in the trust prompt.
+pub fn fingerprint(public_key: &str) -> String {
+    let digest = Sha256::digest(public_key.as_bytes());
+    digest[..8]
+        .chunks(2)
+        .map(|pair| format!("{:02x}{:02x}", pair[0], pair[1]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn signing_key_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_SIGNING_KEY_ACCOUNT)
+        .map_err(|e| format!("Failed to open the system keychain: {}", e))
+}
+
+// The keypair is created on first export and lives in the keychain, never in the config directory
+fn load_or_create_signing_key() -> Result<SigningKey, String> {
+    let entry = signing_key_entry()?;
+    match entry.get_password() {
+        Ok(encoded) => {
+            let bytes: [u8; 32] = STANDARD
+                .decode(encoded.trim())
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or("The share signing key in the system keychain is corrupted")?;
+            Ok(SigningKey::from_bytes(&bytes))
+        }
+        Err(keyring::Error::NoEntry) => {
+            let mut secret = [0u8; 32];
+            rand::rngs::OsRng.fill_bytes(&mut secret);
+            entry
+                .set_password(&STANDARD.encode(secret))
+                .map_err(|e| format!("Failed to store the share signing key in the system keychain: {}", e))?;
+            println!("🔑 Created a share signing key ({})", fingerprint(&STANDARD.encode(SigningKey::from_bytes(&secret).verifying_key().as_bytes())));
+            Ok(SigningKey::from_bytes(&secret))
+        }
+        Err(e) => Err(format!("Failed to read the share signing key from the system keychain: {}", e)),
+    }
+}
+
+fn own_public_key() -> Option<String> {
+    let entry = signing_key_entry().ok()?;
+    let encoded = entry.get_password().ok()?;
+    let bytes: [u8; 32] = STANDARD.decode(encoded.trim()).ok()?.try_into().ok()?;
+    Some(STANDARD.encode(SigningKey::from_bytes(&bytes).verifying_key().as_bytes()))
+}
+
+fn seal_with<T: Serialize>(signing_key: &SigningKey, kind: &str, item: &T) -> Result<SharedBundle, String> {
+    let payload = serde_json::to_value(item)
+        .map_err(|e| format!("Failed to serialize {}: {}", kind, e))?;
+    let signature = signing_key.sign(&signing_input(kind, SHARE_SCHEMA_VERSION, &payload)?);
+
+    Ok(SharedBundle {
+        format: SHARE_FORMAT.to_string(),
+        schema_version: SHARE_SCHEMA_VERSION,
+        kind: kind.to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        signature_algorithm: SIGNATURE_ALGORITHM.to_string(),
+        public_key: STANDARD.encode(signing_key.verifying_key().as_bytes()),
+        signature: STANDARD.encode(signature.to_bytes()),
+        payload,
+    })
+}
+
+pub fn seal<T: Serialize>(kind: &str, item: &T) -> Result<SharedBundle, String> {
+    seal_with(&load_or_create_signing_key()?, kind, item)
+}
+
+// Checks everything but trust; returns the signer's public key
+fn verify(bundle: &SharedBundle, expected_kind: &str) -> Result<String, String> {
+    if bundle.format != SHARE_FORMAT {
+        return Err(format!("Not an Enteract share file (format: {})", bundle.format));
+    }
+    if bundle.kind != expected_kind {
+        return Err(format!("Expected a {} file but found {}", expected_kind, bundle.kind));
+    }
+    if bundle.schema_version > SHARE_SCHEMA_VERSION {
+        return Err(format!(
+            "File uses schema version {} but this build only supports up to {} (exported by Enteract {})",
+            bundle.schema_version, SHARE_SCHEMA_VERSION, bundle.app_version
+        ));
+    }
+    if bundle.schema_version < SHARE_SCHEMA_VERSION {
+        return Err(format!(
+            "File was exported unsigned by Enteract {}; export it again from that install",
+            bundle.app_version
+        ));
+    }
+    if bundle.signature_algorithm != SIGNATURE_ALGORITHM {
+        return Err(format!("Unsupported signature algorithm: {}", bundle.signature_algorithm));
+    }
+
+    let key_bytes: [u8; 32] = STANDARD
+        .decode(&bundle.public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("File has a malformed signer key")?;
+    let verifying_key = VerifyingKey::from_bytes(&key_bytes).map_err(|_| "File has a malformed signer key".to_string())?;
+    let signature = STANDARD
+        .decode(&bundle.signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or("File has a malformed signature")?;
+
+    let input = signing_input(&bundle.kind, bundle.schema_version, &bundle.payload)?;
+    verifying_key
+        .verify_strict(&input, &signature)
+        .map_err(|_| "Signature mismatch: the file was modified after it was exported".to_string())?;
+
+    Ok(bundle.public_key.clone())
+}
+
+fn get_trusted_signers_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("trusted_share_signers.json")
+}
+
+fn load_trusted_signers() -> Result<Vec<TrustedSigner>, String> {
+    let path = get_trusted_signers_path().map_err(|e| format!("Failed to get trusted signers path: {}", e))?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read trusted signers: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse trusted signers: {}", e))
+}
+
+fn save_trusted_signers(signers: &[TrustedSigner]) -> Result<(), String> {
+    let path = get_trusted_signers_path().map_err(|e| format!("Failed to get trusted signers path: {}", e))?;
+    let json = serde_json::to_string_pretty(signers)
+        .map_err(|e| format!("Failed to serialize trusted signers: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save trusted signers: {}", e))
+}
+
+async fn confirm_new_signer(app_handle: &AppHandle, bundle: &SharedBundle) -> bool {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app_handle
+        .dialog()
+        .message(format!(
+            "This {} was signed by a key this install hasn't seen before.\n\nKey fingerprint: {}\nExported by Enteract {} on {}\n\nOnly trust it if the sender confirms the same fingerprint.",
+            bundle.kind.replace('_', " "),
+            fingerprint(&bundle.public_key),
+            bundle.app_version,
+            bundle.exported_at
+        ))
+        .title("Trust this signer?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom("Trust and import".to_string(), "Cancel".to_string()))
+        .show(move |accepted| {
+            let _ = tx.send(accepted);
+        });
+    rx.await.unwrap_or(false)
+}
+
+async fn ensure_trusted(app_handle: &AppHandle, bundle: &SharedBundle, public_key: &str) -> Result<(), String> {
+    if own_public_key().as_deref() == Some(public_key) {
+        return Ok(());
+    }
+    let mut signers = load_trusted_signers()?;
+    if signers.iter().any(|signer| signer.public_key == public_key) {
+        return Ok(());
+    }
+
+    if !confirm_new_signer(app_handle, bundle).await {
+        return Err(format!("Import cancelled: signer {} is not trusted", fingerprint(public_key)));
+    }
+    signers.push(TrustedSigner {
+        public_key: public_key.to_string(),
+        fingerprint: fingerprint(public_key),
+        trusted_at: chrono::Utc::now().to_rfc3339(),
+    });
+    save_trusted_signers(&signers)?;
+    println!("🔑 Trusted share signer {}", fingerprint(public_key));
+    Ok(())
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(bundle: SharedBundle, expected_kind: &str) -> Result<T, String> {
+    serde_json::from_value(bundle.payload)
+        .map_err(|e| format!("Failed to decode {}: {}", expected_kind, e))
+}
+
+/// Validate format, kind, schema compatibility and signature, ask the user about signers they
+/// haven't trusted yet, then decode the payload.
+pub async fn open<T: for<'de> Deserialize<'de>>(
+    app_handle: &AppHandle,
+    bundle: SharedBundle,
+    expected_kind: &str,
+) -> Result<T, String> {
+    let public_key = verify(&bundle, expected_kind)?;
+    ensure_trusted(app_handle, &bundle, &public_key).await?;
+    decode(bundle, expected_kind)
+}
+
+pub fn write_bundle(bundle: &SharedBundle, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create export directory: {}", e))?;
+        }
+    }
+    let json = serde_json::to_string_pretty(bundle)
+        .map_err(|e| format!("Failed to serialize share file: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write share file: {}", e))
+}
+
+pub fn read_bundle(path: &Path) -> Result<SharedBundle, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read share file: {}", e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse share file: {}", e))
+}
+
+#[tauri::command]
+pub async fn list_trusted_share_signers() -> Result<Vec<TrustedSigner>, String> {
+    load_trusted_signers()
+}
+
+#[tauri::command]
+pub async fn remove_trusted_share_signer(fingerprint: String) -> Result<(), String> {
+    let mut signers = load_trusted_signers()?;
+    let before = signers.len();
+    signers.retain(|signer| signer.fingerprint != fingerprint);
+    if signers.len() == before {
+        return Err(format!("No trusted signer with fingerprint {}", fingerprint));
+    }
+    save_trusted_signers(&signers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn test_seal_and_verify_round_trip() {
+        let key = test_key(7);
+        let item = serde_json::json!({ "name": "Standup", "steps": [1, 2, 3] });
+        let bundle = seal_with(&key, "automation_plan", &item).unwrap();
+
+        let signer = verify(&bundle, "automation_plan").unwrap();
+        assert_eq!(signer, STANDARD.encode(key.verifying_key().as_bytes()));
+        let decoded: serde_json::Value = decode(bundle, "automation_plan").unwrap();
+        assert_eq!(decoded, item);
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let bundle = seal_with(&test_key(7), "automation_plan", &serde_json::json!({ "tool": "read_file" })).unwrap();
+
+        let mut edited = bundle.clone();
+        edited.payload = serde_json::json!({ "tool": "delete_file" });
+        assert!(verify(&edited, "automation_plan").unwrap_err().contains("Signature mismatch"));
+
+        // Re-signing with another key and keeping the original public key must not pass either
+        let mut resigned = seal_with(&test_key(9), "automation_plan", &edited.payload).unwrap();
+        resigned.public_key = bundle.public_key.clone();
+        assert!(verify(&resigned, "automation_plan").is_err());
+
+        // Relabelling the kind invalidates the signature even when the caller expects the new kind
+        let mut relabelled = bundle;
+        relabelled.kind = "prompt_template".to_string();
+        assert!(verify(&relabelled, "prompt_template").is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_newer_schema() {
+        let mut bundle = seal_with(&test_key(7), "digest", &serde_json::json!({})).unwrap();
+        bundle.schema_version = SHARE_SCHEMA_VERSION + 1;
+        assert!(verify(&bundle, "digest").unwrap_err().contains("only supports up to"));
+    }
+}
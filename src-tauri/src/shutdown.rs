@@ -0,0 +1,227 @@
+// src-tauri/src/shutdown.rs
+// Orderly shutdown when the app exits. The first exit request is held back while the steps below
+// run in order, each with its own time limit so a stuck subsystem can't keep the app open:
+// stop captures, cancel model streams, let the frontend flush its write-behind message queue,
+// save what MCP sessions were in the middle of, checkpoint the SQLite WAL, free the Whisper
+// model and release the instance lock. The outcome of each step is kept in last_shutdown.json
+// so problems show up on the next launch.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(3);
+const STREAM_TIMEOUT: Duration = Duration::from_secs(3);
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+const MCP_TIMEOUT: Duration = Duration::from_secs(2);
+const CHECKPOINT_TIMEOUT: Duration = Duration::from_secs(3);
+const WHISPER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The longest shutdown can take with every step running into its limit.
+pub const MAX_DURATION: Duration = Duration::from_secs(
+    CAPTURE_TIMEOUT.as_secs() + STREAM_TIMEOUT.as_secs() + FLUSH_TIMEOUT.as_secs()
+        + MCP_TIMEOUT.as_secs() + CHECKPOINT_TIMEOUT.as_secs() + WHISPER_TIMEOUT.as_secs(),
+);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StepOutcome {
+    pub step: String,
+    pub status: String, // "ok" | "failed" | "timed_out"
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShutdownReport {
+    pub started_at: String,
+    pub elapsed_ms: u64,
+    pub steps: Vec<StepOutcome>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Idle,
+    Running,
+    Done,
+}
+
+lazy_static::lazy_static! {
+    static ref PHASE: Mutex<Phase> = Mutex::new(Phase::Idle);
+    static ref FLUSH_DONE: tokio::sync::Notify = tokio::sync::Notify::new();
+}
+
+fn get_report_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("last_shutdown.json")
+}
+
+async fn run_step<F>(steps: &mut Vec<StepOutcome>, step: &str, limit: Duration, work: F)
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let started = Instant::now();
+    let (status, error) = match tokio::time::timeout(limit, work).await {
+        Ok(Ok(())) => ("ok", None),
+        Ok(Err(e)) => ("failed", Some(e)),
+        Err(_) => ("timed_out", Some(format!("Gave up after {:?}", limit))),
+    };
+    if let Some(error) = &error {
+        eprintln!("⚠️ Shutdown step {} {}: {}", step, status, error);
+    }
+    steps.push(StepOutcome {
+        step: step.to_string(),
+        status: status.to_string(),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        error,
+    });
+}
+
+async fn stop_captures(app_handle: &AppHandle) -> Result<(), String> {
+    crate::audio_loopback::stop_audio_loopback_capture().await?;
+    crate::conversation_audio::stop_conversation_audio().await?;
+    crate::dictation::stop_dictation(app_handle.clone()).await?;
+    // Errors here only mean the tracker was never started
+    let _ = crate::eye_tracking::stop_ml_eye_tracking().await;
+    Ok(())
+}
+
+async fn cancel_streams() -> Result<(), String> {
+    let cancelled = crate::ollama::cancel_all_sessions();
+    if cancelled > 0 {
+        println!("🛑 Cancelling {} model streams for shutdown", cancelled);
+    }
+    // Each stream notices the cancellation on its next chunk and removes itself
+    while crate::ollama::active_session_count() > 0 {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok(())
+}
+
+async fn flush_write_behind(app_handle: &AppHandle) -> Result<(), String> {
+    if app_handle.webview_windows().is_empty() {
+        return Ok(());
+    }
+    // Register before emitting so an immediate acknowledgement isn't missed
+    let flushed = FLUSH_DONE.notified();
+    app_handle
+        .emit("app-shutdown-flush", serde_json::json!({ "timeoutMs": FLUSH_TIMEOUT.as_millis() as u64 }))
+        .map_err(|e| e.to_string())?;
+    flushed.await;
+    Ok(())
+}
+
+async fn persist_mcp_state(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(sessions) = app_handle.try_state::<crate::mcp::MCPSessionManager>() else {
+        return Ok(());
+    };
+    let sessions: Vec<_> = sessions.lock().await.values().cloned().collect();
+    if sessions.is_empty() {
+        return Ok(());
+    }
+
+    let mut snapshots = Vec::new();
+    for session in &sessions {
+        snapshots.push(session.snapshot().await);
+        session.cleanup().await?;
+    }
+    let path = crate::data::paths::user_data_dir(app_handle)?.join("mcp_interrupted_sessions.json");
+    let json = serde_json::to_string_pretty(&snapshots).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save MCP sessions: {}", e))?;
+    println!("💾 Saved {} MCP sessions to {}", snapshots.len(), path.display());
+    Ok(())
+}
+
+async fn checkpoint_wal(app_handle: &AppHandle) -> Result<(), String> {
+    let mut databases = vec![crate::data::paths::database_path(app_handle)?];
+    let rag_database = crate::data::paths::user_data_dir(app_handle)?.join("enhanced_rag_documents.db");
+    if rag_database.exists() {
+        databases.push(rag_database);
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        for path in databases {
+            let connection = rusqlite::Connection::open(&path).map_err(|e| e.to_string())?;
+            // Folds the WAL into the main file and truncates it, so the next launch starts clean
+            connection
+                .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+                .map_err(|e| format!("Checkpoint of {} failed: {}", path.display(), e))?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+async fn release_whisper() -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(crate::speech::release_whisper_context)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn run(app_handle: &AppHandle) -> ShutdownReport {
+    println!("👋 Shutting down");
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let started = Instant::now();
+    let mut steps = Vec::new();
+
+    run_step(&mut steps, "stop_captures", CAPTURE_TIMEOUT, stop_captures(app_handle)).await;
+    run_step(&mut steps, "cancel_streams", STREAM_TIMEOUT, cancel_streams()).await;
+    run_step(&mut steps, "flush_write_behind", FLUSH_TIMEOUT, flush_write_behind(app_handle)).await;
+    run_step(&mut steps, "persist_mcp_state", MCP_TIMEOUT, persist_mcp_state(app_handle)).await;
+    run_step(&mut steps, "checkpoint_wal", CHECKPOINT_TIMEOUT, checkpoint_wal(app_handle)).await;
+    run_step(&mut steps, "release_whisper", WHISPER_TIMEOUT, release_whisper()).await;
+    crate::instance::release();
+
+    let report = ShutdownReport {
+        started_at,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        steps,
+    };
+    match get_report_path().and_then(|path| Ok(fs::write(path, serde_json::to_string_pretty(&report)?)?)) {
+        Ok(()) => println!("✅ Shutdown finished in {}ms", report.elapsed_ms),
+        Err(e) => eprintln!("Failed to save shutdown report: {}", e),
+    }
+    report
+}
+
+/// Called for every exit request. Returns true when the app may exit now; otherwise the caller
+/// must prevent this exit, and the app exits by itself once shutdown has run.
+pub fn on_exit_requested(app_handle: &AppHandle) -> bool {
+    let Ok(mut phase) = PHASE.lock() else {
+        return true;
+    };
+    match *phase {
+        Phase::Done => true,
+        Phase::Running => false,
+        Phase::Idle => {
+            *phase = Phase::Running;
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                run(&app_handle).await;
+                if let Ok(mut phase) = PHASE.lock() {
+                    *phase = Phase::Done;
+                }
+                app_handle.exit(0);
+            });
+            false
+        }
+    }
+}
+
+/// The frontend calls this once its pending message saves have gone out (or given up).
+#[tauri::command]
+pub async fn complete_shutdown_flush() -> Result<(), String> {
+    FLUSH_DONE.notify_waiters();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_last_shutdown_report() -> Result<Option<ShutdownReport>, String> {
+    let path = get_report_path().map_err(|e| format!("Failed to get shutdown report path: {}", e))?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read shutdown report: {}", e))?;
+    Ok(serde_json::from_str(&json).ok())
+}
@@ -4,12 +4,25 @@ use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
 use std::collections::HashMap;
 
+/// Where embeddings come from. Switching provider changes the vector space, so documents need
+/// their embeddings regenerated afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    #[default]
+    Simple, // Local feature hashing, always available
+    Ollama, // An Ollama embedding model, see ollama_embedding_service
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EmbeddingConfig {
     pub model_name: String,
     pub max_length: usize,
     pub normalize_embeddings: bool,
     pub embedding_dimension: usize,
+    pub provider: EmbeddingProvider,
+    pub ollama_model: String,
 }
 
 impl Default for EmbeddingConfig {
@@ -19,6 +32,8 @@ impl Default for EmbeddingConfig {
             max_length: 512,
             normalize_embeddings: true,
             embedding_dimension: 384, // Match BGE-small dimensions
+            provider: EmbeddingProvider::Simple,
+            ollama_model: "nomic-embed-text".to_string(),
         }
     }
 }
@@ -33,6 +48,16 @@ pub struct SimpleEmbeddingService {
     initialized: Arc<Mutex<bool>>,
 }
 
+impl EmbeddingConfig {
+    /// Name of the model the configured provider embeds with
+    pub fn active_model(&self) -> &str {
+        match self.provider {
+            EmbeddingProvider::Simple => &self.model_name,
+            EmbeddingProvider::Ollama => &self.ollama_model,
+        }
+    }
+}
+
 impl SimpleEmbeddingService {
     pub fn new(cache_dir: PathBuf, config: Option<EmbeddingConfig>) -> Self {
         let config = config.unwrap_or_default();
@@ -76,6 +101,14 @@ impl SimpleEmbeddingService {
         self.generate_embedding(query)
     }
     
+    /// Drop cached embeddings for the given texts, returning how many were evicted
+    pub fn evict_cached(&self, texts: &[String]) -> usize {
+        match self.cache.lock() {
+            Ok(mut cache) => texts.iter().filter(|text| cache.remove(text.as_str()).is_some()).count(),
+            Err(_) => 0,
+        }
+    }
+    
     /// Generate a deterministic embedding based on text features
     /// This is a simplified approach that creates embeddings based on:
     /// - Character n-grams
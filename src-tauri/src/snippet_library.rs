@@ -0,0 +1,269 @@
+// src-tauri/src/snippet_library.rs
+// Pulls fenced code blocks out of assistant messages into a searchable snippet library.
+// Descriptions start from the surrounding text and are upgraded by a small model in the background.
+use crate::data::chat::ChatStorage;
+use crate::data::types::{ChatMessage, ChatSession, CodeSnippet};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter};
+
+const DESCRIPTION_MODEL: &str = "gemma3:1b-it-qat";
+// One-liners and stray fences aren't worth keeping
+const MIN_SNIPPET_CHARS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedBlock {
+    pub language: String,
+    pub code: String,
+    pub description: String,
+}
+
+/// Find ``` / ~~~ fenced blocks in markdown, with a description taken from the text just before them.
+pub fn extract_code_blocks(text: &str) -> Vec<ExtractedBlock> {
+    let mut blocks = Vec::new();
+    let mut lines = text.lines();
+    let mut last_prose = String::new();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        let fence = if trimmed.starts_with("```") {
+            "```"
+        } else if trimmed.starts_with("~~~") {
+            "~~~"
+        } else {
+            if !trimmed.trim().is_empty() {
+                last_prose = trimmed.trim().to_string();
+            }
+            continue;
+        };
+
+        let language = trimmed[3..]
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        let mut code_lines = Vec::new();
+        for code_line in lines.by_ref() {
+            if code_line.trim_start().starts_with(fence) {
+                break;
+            }
+            code_lines.push(code_line);
+        }
+        let code = code_lines.join("\n").trim_matches('\n').to_string();
+
+        if code.trim().len() >= MIN_SNIPPET_CHARS {
+            let language = if language.is_empty() { "text".to_string() } else { language };
+            let description = initial_description(&last_prose, &code, &language);
+            blocks.push(ExtractedBlock { language, code, description });
+        }
+        last_prose.clear();
+    }
+
+    blocks
+}
+
+fn initial_description(prose: &str, code: &str, language: &str) -> String {
+    let prose = prose.trim_end_matches(':').trim();
+    if !prose.is_empty() {
+        return prose.chars().take(160).collect();
+    }
+
+    // Fall back to a leading comment in the code itself
+    let comment = code.lines().map(str::trim).find_map(|line| {
+        ["//", "#", "--", "/*", "*"]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))
+            .map(|rest| rest.trim().trim_end_matches("*/").trim().to_string())
+            .filter(|rest| !rest.is_empty() && !rest.starts_with('!'))
+    });
+    comment.unwrap_or_else(|| format!("{} snippet", language))
+}
+
+fn content_hash(language: &str, code: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(language.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(code.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Code blocks in the chats' assistant messages, ready to be offered to the library.
+pub fn collect_snippet_candidates(chats: &[ChatSession]) -> Vec<CodeSnippet> {
+    let mut candidates = Vec::new();
+    for chat in chats {
+        for message in chat.history.iter().filter(|m| m.sender == "assistant" && m.is_interim != Some(true)) {
+            for block in extract_code_blocks(&message.text) {
+                candidates.push(CodeSnippet {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    language: block.language,
+                    code: block.code,
+                    description: block.description,
+                    chat_id: Some(chat.id.clone()),
+                    message_id: Some(message.id),
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    use_count: 0,
+                });
+            }
+        }
+    }
+    candidates
+}
+
+/// Add candidates to the library. Already-known code is skipped, so this is cheap on every chat save.
+pub fn index_snippets(app_handle: &AppHandle, candidates: Vec<CodeSnippet>) {
+    if candidates.is_empty() {
+        return;
+    }
+    let mut storage = match ChatStorage::new(app_handle) {
+        Ok(storage) => storage,
+        Err(e) => {
+            eprintln!("Failed to open chat storage for snippets: {}", e);
+            return;
+        }
+    };
+
+    let mut added = Vec::new();
+    for snippet in candidates {
+        match storage.insert_snippet(&snippet, &content_hash(&snippet.language, &snippet.code)) {
+            Ok(true) => added.push(snippet),
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to store snippet: {}", e),
+        }
+    }
+
+    if added.is_empty() {
+        return;
+    }
+    println!("📎 Added {} code snippets to the library", added.len());
+    if let Err(e) = app_handle.emit("snippets-updated", added.len()) {
+        eprintln!("Failed to emit snippets update: {}", e);
+    }
+
+    // Better descriptions from a small model, whenever background work is allowed
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        for snippet in added {
+            crate::presence::wait_for_background_slot().await;
+            if crate::power::should_defer_background_work() {
+                return;
+            }
+
+            let code: String = snippet.code.chars().take(4000).collect();
+            let prompt = format!(
+                "Describe in one short sentence what this {} code does. Reply with the sentence only.\n\n{}",
+                snippet.language, code
+            );
+            let model = crate::agent_models::resolve_helper_model(DESCRIPTION_MODEL).await;
+            let description = match crate::ollama::generate_completion(
+                &model,
+                prompt,
+                None,
+                Some(serde_json::json!({ "temperature": 0.2, "num_predict": 60 })),
+            ).await {
+                Ok(description) => description.trim().trim_matches('"').to_string(),
+                Err(e) => {
+                    eprintln!("Snippet description failed: {}", e);
+                    continue;
+                }
+            };
+            if description.is_empty() {
+                continue;
+            }
+
+            let result = ChatStorage::new(&handle)
+                .and_then(|mut storage| storage.set_snippet_description(&snippet.id, &description));
+            if let Err(e) = result {
+                eprintln!("Failed to save snippet description: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn search_snippets(
+    app_handle: AppHandle,
+    query: Option<String>,
+    language: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<CodeSnippet>, String> {
+    ChatStorage::new(&app_handle)
+        .and_then(|storage| storage.search_snippets(query.as_deref(), language.as_deref(), limit.unwrap_or(50).min(500)))
+        .map_err(|e| format!("Failed to search snippets: {}", e))
+}
+
+#[tauri::command]
+pub fn get_snippet_languages(app_handle: AppHandle) -> Result<Vec<(String, i64)>, String> {
+    ChatStorage::new(&app_handle)
+        .and_then(|storage| storage.snippet_languages())
+        .map_err(|e| format!("Failed to load snippet languages: {}", e))
+}
+
+#[tauri::command]
+pub fn delete_snippet(app_handle: AppHandle, snippet_id: String) -> Result<bool, String> {
+    ChatStorage::new(&app_handle)
+        .and_then(|mut storage| storage.delete_snippet(&snippet_id))
+        .map_err(|e| format!("Failed to delete snippet: {}", e))
+}
+
+/// Build a user message containing the snippet for the frontend to append to a chat.
+#[tauri::command]
+pub fn insert_snippet_into_chat(
+    app_handle: AppHandle,
+    snippet_id: String,
+    chat_id: Option<String>,
+) -> Result<ChatMessage, String> {
+    let mut storage = ChatStorage::new(&app_handle)
+        .map_err(|e| format!("Failed to initialize chat storage: {}", e))?;
+    let snippet = storage.get_snippet(&snippet_id)
+        .map_err(|e| format!("Snippet not found: {}", e))?;
+    let message_id = storage.next_message_id()
+        .map_err(|e| format!("Failed to allocate message id: {}", e))?;
+    storage.record_snippet_use(&snippet_id)
+        .map_err(|e| format!("Failed to update snippet: {}", e))?;
+
+    let message = ChatMessage {
+        id: message_id,
+        text: format!("{}\n\n```{}\n{}\n```", snippet.description, snippet.language, snippet.code),
+        sender: "user".to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        is_interim: None,
+        confidence: None,
+        source: Some("snippet_library".to_string()),
+        attachments: None,
+        thinking: None,
+        message_type: Some("snippet".to_string()),
+        metadata: None,
+        bookmarked: None,
+        reactions: None,
+    };
+
+    if let Err(e) = app_handle.emit("snippet-inserted", serde_json::json!({
+        "chatId": chat_id,
+        "message": &message,
+    })) {
+        eprintln!("Failed to emit snippet insertion: {}", e);
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extracts_fenced_blocks_with_descriptions() {
+        let text = "Here is a helper that adds numbers:\n\n```Rust\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n```\n\nAnd a tiny one:\n```\nx\n```";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "rust");
+        assert_eq!(blocks[0].description, "Here is a helper that adds numbers");
+        assert!(blocks[0].code.starts_with("fn add"));
+    }
+
+    #[test]
+    fn test_description_falls_back_to_leading_comment() {
+        let text = "```python\n# Parse the config file\nimport json\nconfig = json.load(open('c.json'))\n```";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].description, "Parse the config file");
+    }
+}
@@ -0,0 +1,197 @@
+// src-tauri/src/speakers.rs
+// Speaker naming. The user labels who was speaking on each audio source of a conversation; the label
+// is stored on the messages and shows up in transcripts. With the "speakerFingerprinting" setting on
+// (and conversation audio kept), each labeled speaker also gets a voiceprint so they can be
+// recognized in later conversations and their label pre-filled. The active conversation profile
+// can turn this off for its conversations (dictation does by default).
+use crate::data::conversation::ConversationStorage;
+use crate::data::types::{ConversationSession, Speaker, SpeakerSuggestion};
+use crate::voice_fingerprint;
+use tauri::AppHandle;
+
+const SOURCES: [&str; 2] = ["microphone", "loopback"];
+// Enough audio for a stable fingerprint without reading a whole meeting back in
+const MAX_FINGERPRINT_SECONDS: usize = 60;
+
+fn open_storage(app_handle: &AppHandle) -> Result<ConversationStorage, String> {
+    ConversationStorage::new(app_handle).map_err(|e| format!("Failed to initialize conversation storage: {}", e))
+}
+
+fn load_session(storage: &ConversationStorage, session_id: &str) -> Result<ConversationSession, String> {
+    storage
+        .get_session(session_id)
+        .map_err(|e| format!("Failed to load conversation: {}", e))?
+        .ok_or_else(|| format!("Conversation not found: {}", session_id))
+}
+
+async fn fingerprinting_enabled() -> bool {
+    if !crate::conversation_profiles::active_profile().diarization_enabled {
+        return false;
+    }
+    crate::audio_loopback::settings::load_general_settings()
+        .await
+        .ok()
+        .flatten()
+        .and_then(|settings| settings.get("speakerFingerprinting").and_then(|v| v.as_bool()))
+        .unwrap_or(false)
+}
+
+// Fingerprint the kept audio of a source's messages (or only `message_ids` of them)
+fn fingerprint_source(
+    app_handle: &AppHandle,
+    session: &ConversationSession,
+    source: &str,
+    message_ids: Option<&[String]>,
+) -> Option<Vec<f32>> {
+    let mut samples = Vec::new();
+    for message in &session.messages {
+        if message.source != source || message_ids.is_some_and(|ids| !ids.contains(&message.id)) {
+            continue;
+        }
+        if let Ok(pcm) = crate::conversation_audio::message_pcm(app_handle, &session.id, message) {
+            samples.extend(crate::speech::pcm16_to_f32(&pcm));
+        }
+        if samples.len() >= MAX_FINGERPRINT_SECONDS * 16000 {
+            break;
+        }
+    }
+    voice_fingerprint::fingerprint(&samples)
+}
+
+fn find_speaker(storage: &ConversationStorage, speaker_id: &str) -> Result<Speaker, String> {
+    storage
+        .list_speakers()
+        .map_err(|e| format!("Failed to load speakers: {}", e))?
+        .into_iter()
+        .find(|speaker| speaker.id == speaker_id)
+        .ok_or_else(|| format!("Speaker not found: {}", speaker_id))
+}
+
+fn clean_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Speaker name is empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+#[tauri::command]
+pub async fn list_speakers(app_handle: AppHandle) -> Result<Vec<Speaker>, String> {
+    open_storage(&app_handle)?
+        .list_speakers()
+        .map_err(|e| format!("Failed to load speakers: {}", e))
+}
+
+/// Name the speaker on one audio source of a conversation, or on just some of its messages.
+/// Reusing an existing name (case-insensitive) links the messages to that speaker.
+#[tauri::command]
+pub async fn name_conversation_speaker(
+    app_handle: AppHandle,
+    session_id: String,
+    source: String,
+    name: String,
+    message_ids: Option<Vec<String>>,
+) -> Result<Speaker, String> {
+    if !SOURCES.contains(&source.as_str()) {
+        return Err(format!("Unknown audio source: {}", source));
+    }
+    let name = clean_name(&name)?;
+    let fingerprinting = fingerprinting_enabled().await;
+
+    let mut storage = open_storage(&app_handle)?;
+    let speaker_id = storage
+        .find_or_create_speaker(&name)
+        .map_err(|e| format!("Failed to save speaker: {}", e))?;
+    let changed = storage
+        .assign_speaker(&session_id, &source, message_ids.as_deref(), &speaker_id)
+        .map_err(|e| format!("Failed to label messages: {}", e))?;
+    println!("🗣️ Labeled {} {} message(s) in {} as {}", changed, source, session_id, name);
+
+    if fingerprinting {
+        let session = load_session(&storage, &session_id)?;
+        if let Some(fingerprint) = fingerprint_source(&app_handle, &session, &source, message_ids.as_deref()) {
+            let existing = storage
+                .speaker_voiceprints()
+                .map_err(|e| format!("Failed to load voiceprints: {}", e))?
+                .into_iter()
+                .find(|(id, ..)| *id == speaker_id);
+            let (voiceprint, samples) = match existing {
+                Some((_, _, blob, samples)) => {
+                    (voice_fingerprint::blend(&voice_fingerprint::from_blob(&blob), samples, &fingerprint), samples + 1)
+                }
+                None => (fingerprint, 1),
+            };
+            storage
+                .set_speaker_voiceprint(&speaker_id, &voice_fingerprint::to_blob(&voiceprint), samples)
+                .map_err(|e| format!("Failed to save voiceprint: {}", e))?;
+        }
+    }
+
+    find_speaker(&storage, &speaker_id)
+}
+
+/// Known speakers whose voice matches the unlabeled audio sources of a conversation, best match
+/// per source. Empty when fingerprinting is off or no audio was kept.
+#[tauri::command]
+pub async fn suggest_conversation_speakers(app_handle: AppHandle, session_id: String) -> Result<Vec<SpeakerSuggestion>, String> {
+    if !fingerprinting_enabled().await {
+        return Ok(Vec::new());
+    }
+    let storage = open_storage(&app_handle)?;
+    let voiceprints: Vec<(String, String, Vec<f32>)> = storage
+        .speaker_voiceprints()
+        .map_err(|e| format!("Failed to load voiceprints: {}", e))?
+        .into_iter()
+        .map(|(id, name, blob, _)| (id, name, voice_fingerprint::from_blob(&blob)))
+        .collect();
+    if voiceprints.is_empty() {
+        return Ok(Vec::new());
+    }
+    let session = load_session(&storage, &session_id)?;
+
+    let mut suggestions = Vec::new();
+    for source in SOURCES {
+        let unlabeled = session.messages.iter().any(|m| m.source == source && m.speaker_id.is_none());
+        if !unlabeled {
+            continue;
+        }
+        let Some(fingerprint) = fingerprint_source(&app_handle, &session, source, None) else {
+            continue;
+        };
+        let best = voiceprints
+            .iter()
+            .map(|(id, name, voiceprint)| (id, name, voice_fingerprint::similarity(&fingerprint, voiceprint)))
+            .filter(|(_, _, similarity)| *similarity >= voice_fingerprint::MATCH_THRESHOLD)
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        if let Some((id, name, similarity)) = best {
+            suggestions.push(SpeakerSuggestion {
+                source: source.to_string(),
+                speaker_id: id.clone(),
+                name: name.clone(),
+                similarity,
+            });
+        }
+    }
+    Ok(suggestions)
+}
+
+#[tauri::command]
+pub async fn rename_speaker(app_handle: AppHandle, speaker_id: String, name: String) -> Result<Speaker, String> {
+    let name = clean_name(&name)?;
+    let mut storage = open_storage(&app_handle)?;
+    let renamed = storage
+        .rename_speaker(&speaker_id, &name)
+        .map_err(|e| format!("Failed to rename speaker (is the name already taken?): {}", e))?;
+    if !renamed {
+        return Err(format!("Speaker not found: {}", speaker_id));
+    }
+    find_speaker(&storage, &speaker_id)
+}
+
+/// Forget a speaker, including their voiceprint. Their messages become unlabeled again.
+#[tauri::command]
+pub async fn delete_speaker(app_handle: AppHandle, speaker_id: String) -> Result<bool, String> {
+    open_storage(&app_handle)?
+        .delete_speaker(&speaker_id)
+        .map_err(|e| format!("Failed to delete speaker: {}", e))
+}
@@ -4,7 +4,6 @@ use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
 use std::fs;
 use base64::{Engine as _, engine::general_purpose};
-use tempfile::NamedTempFile;
 use anyhow::Result;
 use whisper_rs::{WhisperContext, WhisperContextParameters, FullParams, SamplingStrategy};
 
@@ -64,21 +63,32 @@ pub struct WhisperModelConfig {
     pub maxSegmentLength: u32,
 }
 
+/// Stand-in confidence for results whose provider reported none (see `confidence_unknown`).
+pub const NEUTRAL_CONFIDENCE: f32 = 0.5;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct TranscriptionResult {
     pub text: String,
     pub confidence: f32,
+    // The provider gave no confidence: `confidence` is then NEUTRAL_CONFIDENCE, which says nothing
+    // about the transcript, and quality filters and stats leave it out
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confidence_unknown: bool,
     pub start_time: f32,
     pub end_time: f32,
     pub language: Option<String>,
+    // Where this chunk was kept in the conversation audio, when audio persistence is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<crate::conversation_audio::AudioSpan>,
 }
 
 // Global whisper context
 lazy_static::lazy_static! {
     pub static ref WHISPER_CONTEXT: Arc<Mutex<Option<WhisperContext>>> = Arc::new(Mutex::new(None));
     static ref MODEL_CACHE_DIR: PathBuf = {
+        // The temp directory is shared between users on Linux and macOS
         let mut cache_dir = std::env::temp_dir();
-        cache_dir.push("enteract");
+        cache_dir.push(format!("enteract-{}", crate::data::paths::os_user_name()));
         cache_dir.push("whisper_models");
         cache_dir
     };
@@ -100,25 +110,65 @@ pub async fn initialize_whisper_model(config: WhisperModelConfig) -> Result<Stri
     Ok(format!("Whisper model '{}' initialized successfully", config.modelSize))
 }
 
+/// Drop the loaded Whisper model and its memory; the next transcription loads it again.
+pub(crate) fn release_whisper_context() {
+    if let Ok(mut whisper_ctx) = WHISPER_CONTEXT.lock() {
+        *whisper_ctx = None;
+    }
+}
+
+/// Transcribe microphone audio. Audio recorded for a conversation names its session and is refused
+/// unless that conversation's recording consent allows it; without a session it's the user's own
+/// voice input (a chat prompt or dictation) and isn't kept with any conversation.
 #[tauri::command]
-pub async fn transcribe_audio_base64(audioData: String, config: WhisperModelConfig) -> Result<TranscriptionResult, String> {
-    // Decode base64 audio data
+pub async fn transcribe_audio_base64(app_handle: tauri::AppHandle, audioData: String, config: WhisperModelConfig, sessionId: Option<String>) -> Result<TranscriptionResult, String> {
+    if let Some(session_id) = &sessionId {
+        crate::consent::ensure_recording(&app_handle, "microphone", session_id)?;
+    }
+
+    // Decode base64 audio data (raw PCM16 mono at 16kHz)
     let audio_bytes = general_purpose::STANDARD
         .decode(&audioData)
         .map_err(|e| format!("Failed to decode base64 audio: {}", e))?;
     
-    // Create temporary file for audio - using .pcm extension for raw PCM data
-    let temp_file = NamedTempFile::with_suffix(".pcm")
-        .map_err(|e| format!("Failed to create temp file: {}", e))?;
-    
-    fs::write(temp_file.path(), audio_bytes)
-        .map_err(|e| format!("Failed to write audio to temp file: {}", e))?;
-    
-    transcribe_audio_file(temp_file.path().to_string_lossy().to_string(), config).await
+    // Microphone audio goes through whichever STT provider is configured for that stream
+    let result = crate::stt_provider::transcribe_pcm16(crate::stt_provider::SttStream::Microphone, audio_bytes, config, sessionId.is_some()).await?;
+
+    // The user's own speech can carry voice commands; matching runs apart so the transcript isn't held up
+    if !result.text.trim().is_empty() {
+        let text = result.text.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::voice_commands::handle_microphone_transcript(app_handle, text).await {
+                eprintln!("Voice command handling failed: {}", e);
+            }
+        });
+    }
+    Ok(result)
 }
 
 #[tauri::command]
-pub async fn transcribe_audio_file(file_path: String, config: WhisperModelConfig) -> Result<TranscriptionResult, String> {
+pub async fn transcribe_audio_file(app_handle: tauri::AppHandle, file_path: String, config: WhisperModelConfig) -> Result<TranscriptionResult, String> {
+    let audio_data = load_audio_file(&file_path)?;
+    let result = transcribe_samples_local(audio_data, config).await?;
+
+    let file_name = std::path::Path::new(&file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or(file_path);
+    crate::notifications::notify(
+        &app_handle,
+        crate::notifications::BackgroundNotification::new(
+            "transcription",
+            "Transcription finished",
+            format!("{} has been transcribed.", file_name),
+            None,
+        ),
+    );
+    Ok(result)
+}
+
+/// In-process whisper-rs transcription of 16kHz mono samples.
+pub(crate) async fn transcribe_samples_local(audio_data: Vec<f32>, config: WhisperModelConfig) -> Result<TranscriptionResult, String> {
     // Ensure model is initialized
     let needs_init = {
         let whisper_ctx = WHISPER_CONTEXT.lock().unwrap();
@@ -129,13 +179,13 @@ pub async fn transcribe_audio_file(file_path: String, config: WhisperModelConfig
         initialize_whisper_model(config.clone()).await?;
     }
     
-    // Load and preprocess audio
-    let audio_data = load_audio_file(&file_path)?;
-    
     // Get Whisper context
     let whisper_ctx = WHISPER_CONTEXT.lock().unwrap();
     let ctx = whisper_ctx.as_ref().ok_or("Whisper context not initialized")?;
     
+    // Glossary spellings bias the decoder towards names and jargon; must outlive params
+    let glossary_prompt = crate::glossary::whisper_prompt();
+    
     // Set up transcription parameters - MATCHING PYTHON SCRIPT
     // Python uses: beam_size=1, best_of=1, temperature=0.0
     let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
@@ -162,6 +212,9 @@ pub async fn transcribe_audio_file(file_path: String, config: WhisperModelConfig
     params.set_no_context(true);          // Python: condition_on_previous_text=False
     params.set_temperature(0.0);          // Python: temperature=0.0
     params.set_no_timestamps(true);       // Python: without_timestamps=True
+    if let Some(prompt) = glossary_prompt.as_deref() {
+        params.set_initial_prompt(prompt);
+    }
     
     // Run transcription
     let mut state = ctx.create_state().map_err(|e| format!("Failed to create state: {}", e))?;
@@ -196,11 +249,13 @@ pub async fn transcribe_audio_file(file_path: String, config: WhisperModelConfig
     let avg_confidence = if num_segments > 0 { total_confidence / num_segments as f32 } else { 0.0 };
     
     Ok(TranscriptionResult {
-        text: full_text.trim().to_string(),
+        text: crate::glossary::apply_casing(full_text.trim(), &crate::glossary::load_glossary().active_terms()),
         confidence: avg_confidence,
+        confidence_unknown: false,
         start_time,
         end_time,
         language: config.language,
+        audio: None,
     })
 }
 
@@ -299,13 +354,7 @@ fn load_audio_file(file_path: &str) -> Result<Vec<f32>, String> {
     
     println!("[WHISPER] Loading audio file: {} bytes from {}", audio_bytes.len(), file_path);
     
-    let mut audio_f32 = Vec::new();
-    for chunk in audio_bytes.chunks(2) {
-        if chunk.len() == 2 {
-            let sample = i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / 32768.0;
-            audio_f32.push(sample);
-        }
-    }
+    let audio_f32 = pcm16_to_f32(&audio_bytes);
     
     println!("[WHISPER] Converted to {} f32 samples", audio_f32.len());
     
@@ -316,3 +365,9 @@ fn load_audio_file(file_path: &str) -> Result<Vec<f32>, String> {
     Ok(audio_f32)
 }
 
+pub(crate) fn pcm16_to_f32(audio_bytes: &[u8]) -> Vec<f32> {
+    audio_bytes
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]) as f32 / 32768.0)
+        .collect()
+}
@@ -0,0 +1,359 @@
+// src-tauri/src/startup.rs
+// Launch at login and background-agent mode. Registering auto-start writes the per-user entry the
+// OS reads at login (the HKCU Run key on Windows, a LaunchAgent on macOS, an XDG autostart desktop
+// entry on Linux); the entry starts the app with `--background`. With background-agent mode on,
+// such a launch stays hidden in the tray, and the capture hotkeys are registered globally so they
+// work while no window has focus. Settings changes apply straight away.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+const BACKGROUND_FLAG: &str = "--background";
+const TRAY_ID: &str = "enteract-tray";
+const AUTOSTART_NAME: &str = "Enteract";
+#[cfg(target_os = "macos")]
+const LAUNCH_AGENT_LABEL: &str = "com.enteract.app";
+#[cfg(windows)]
+const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartupSettings {
+    pub background_agent: bool,       // Tray icon, global hotkeys, hidden launch at login
+    pub show_hotkey: String,          // Brings up the control panel
+    pub conversation_hotkey: String,  // Opens the conversation window to start capturing
+}
+
+impl Default for StartupSettings {
+    fn default() -> Self {
+        Self {
+            background_agent: false,
+            show_hotkey: "CommandOrControl+Shift+Space".to_string(),
+            conversation_hotkey: "CommandOrControl+Shift+L".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutostartStatus {
+    pub registered: bool,
+    pub location: String, // Registry value, plist or desktop entry the OS reads
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("startup.json")
+}
+
+fn load_settings() -> StartupSettings {
+    get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn launch_command() -> Result<String, String> {
+    std::env::current_exe()
+        .map(|exe| exe.to_string_lossy().to_string())
+        .map_err(|e| format!("Failed to locate the Enteract executable: {}", e))
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent_plist(exe: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+        LAUNCH_AGENT_LABEL,
+        exe.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+        BACKGROUND_FLAG
+    )
+}
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn desktop_entry(exe: &str) -> String {
+    // Exec arguments are quoted, with backslash, quote, backtick and dollar escaped; the file's
+    // own string escaping then doubles every backslash
+    let quoted: String = exe.chars().fold(String::new(), |mut quoted, c| {
+        if matches!(c, '\\' | '"' | '`' | '$') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+        quoted
+    }).replace('\\', "\\\\");
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec=\"{}\" {}\nX-GNOME-Autostart-enabled=true\nTerminal=false\n",
+        AUTOSTART_NAME, quoted, BACKGROUND_FLAG
+    )
+}
+
+#[cfg(windows)]
+fn reg(args: &[&str]) -> Result<std::process::Output, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run reg.exe: {}", e))
+}
+
+#[cfg(windows)]
+fn autostart_location() -> Result<PathBuf, String> {
+    Ok(PathBuf::from(format!(r"{}\{}", RUN_KEY, AUTOSTART_NAME)))
+}
+
+#[cfg(target_os = "macos")]
+fn autostart_location() -> Result<PathBuf, String> {
+    dirs::home_dir()
+        .map(|home| home.join("Library/LaunchAgents").join(format!("{}.plist", LAUNCH_AGENT_LABEL)))
+        .ok_or_else(|| "Could not find the home directory".to_string())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn autostart_location() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|config| config.join("autostart").join("enteract.desktop"))
+        .ok_or_else(|| "Could not find the config directory".to_string())
+}
+
+#[cfg(windows)]
+fn is_registered() -> Result<bool, String> {
+    Ok(reg(&["query", RUN_KEY, "/v", AUTOSTART_NAME])?.status.success())
+}
+
+#[cfg(not(windows))]
+fn is_registered() -> Result<bool, String> {
+    Ok(autostart_location()?.exists())
+}
+
+fn status() -> Result<AutostartStatus, String> {
+    Ok(AutostartStatus {
+        registered: is_registered()?,
+        location: autostart_location()?.to_string_lossy().to_string(),
+    })
+}
+
+/// True when the OS started this launch from the auto-start entry.
+pub fn launched_in_background() -> bool {
+    std::env::args().skip(1).any(|arg| arg == BACKGROUND_FLAG)
+}
+
+fn show_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn ensure_tray(app_handle: &AppHandle) -> tauri::Result<()> {
+    if app_handle.tray_by_id(TRAY_ID).is_some() {
+        return Ok(());
+    }
+    let show = MenuItem::with_id(app_handle, "show", "Show Enteract", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(app_handle, &[&show, &quit])?;
+    let mut tray = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Enteract")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app_handle, event| match event.id.as_ref() {
+            "show" => show_main_window(app_handle),
+            "quit" => app_handle.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app_handle.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app_handle)?;
+    Ok(())
+}
+
+fn parse_hotkey(name: &str, hotkey: &str) -> Result<Shortcut, String> {
+    hotkey.parse::<Shortcut>().map_err(|e| format!("Invalid {} hotkey '{}': {}", name, hotkey, e))
+}
+
+// Register the capture hotkeys (or drop them when background-agent mode is off)
+fn arm_hotkeys(app_handle: &AppHandle, settings: &StartupSettings) -> Result<(), String> {
+    let shortcuts = app_handle.global_shortcut();
+    shortcuts.unregister_all().map_err(|e| format!("Failed to release hotkeys: {}", e))?;
+    if !settings.background_agent {
+        return Ok(());
+    }
+    for (name, hotkey) in [("show", &settings.show_hotkey), ("conversation", &settings.conversation_hotkey)] {
+        let shortcut = parse_hotkey(name, hotkey)?;
+        shortcuts
+            .register(shortcut)
+            .map_err(|e| format!("Failed to register {} hotkey '{}': {}", name, hotkey, e))?;
+    }
+    println!("⌨️ Capture hotkeys armed: {} (show), {} (conversation)", settings.show_hotkey, settings.conversation_hotkey);
+    Ok(())
+}
+
+fn on_hotkey(app_handle: &AppHandle, shortcut: &Shortcut) {
+    let settings = load_settings();
+    let action = if parse_hotkey("show", &settings.show_hotkey).ok().as_ref() == Some(shortcut) {
+        "show"
+    } else if parse_hotkey("conversation", &settings.conversation_hotkey).ok().as_ref() == Some(shortcut) {
+        "conversation"
+    } else {
+        return;
+    };
+    show_main_window(app_handle);
+    if let Err(e) = app_handle.emit("capture-hotkey", serde_json::json!({ "action": action })) {
+        eprintln!("Failed to emit capture hotkey: {}", e);
+    }
+}
+
+fn apply(app_handle: &AppHandle, settings: &StartupSettings) -> Result<(), String> {
+    if settings.background_agent {
+        ensure_tray(app_handle).map_err(|e| format!("Failed to create tray icon: {}", e))?;
+    } else {
+        app_handle.remove_tray_by_id(TRAY_ID);
+    }
+    arm_hotkeys(app_handle, settings)
+}
+
+/// Set up background-agent mode at startup. A login launch stays hidden in the tray.
+pub fn init_startup(app: &tauri::App) -> tauri::Result<()> {
+    app.handle().plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app_handle, shortcut, event| {
+                if event.state() == ShortcutState::Pressed {
+                    on_hotkey(app_handle, shortcut);
+                }
+            })
+            .build(),
+    )?;
+
+    let settings = load_settings();
+    if let Err(e) = apply(app.handle(), &settings) {
+        eprintln!("⚠️ Background agent setup incomplete: {}", e);
+    }
+    if settings.background_agent && launched_in_background() {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+        println!("🌙 Started at login in background-agent mode; Enteract is in the tray");
+    }
+    Ok(())
+}
+
+/// Start Enteract at login (with `--background`).
+#[tauri::command]
+pub fn register_autostart() -> Result<AutostartStatus, String> {
+    let exe = launch_command()?;
+
+    #[cfg(windows)]
+    {
+        let value = format!("\"{}\" {}", exe, BACKGROUND_FLAG);
+        let output = reg(&["add", RUN_KEY, "/v", AUTOSTART_NAME, "/t", "REG_SZ", "/d", &value, "/f"])?;
+        if !output.status.success() {
+            return Err(format!("Failed to write the Run key: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        let location = autostart_location()?;
+        if let Some(parent) = location.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        #[cfg(target_os = "macos")]
+        let contents = launch_agent_plist(&exe);
+        #[cfg(not(target_os = "macos"))]
+        let contents = desktop_entry(&exe);
+        fs::write(&location, contents).map_err(|e| format!("Failed to write {}: {}", location.display(), e))?;
+    }
+
+    println!("🚀 Registered Enteract to start at login");
+    status()
+}
+
+/// Stop starting Enteract at login.
+#[tauri::command]
+pub fn unregister_autostart() -> Result<AutostartStatus, String> {
+    if is_registered()? {
+        #[cfg(windows)]
+        {
+            let output = reg(&["delete", RUN_KEY, "/v", AUTOSTART_NAME, "/f"])?;
+            if !output.status.success() {
+                return Err(format!("Failed to remove the Run key: {}", String::from_utf8_lossy(&output.stderr).trim()));
+            }
+        }
+
+        #[cfg(not(windows))]
+        {
+            let location = autostart_location()?;
+            fs::remove_file(&location).map_err(|e| format!("Failed to remove {}: {}", location.display(), e))?;
+        }
+        println!("🗑️ Enteract no longer starts at login");
+    }
+    status()
+}
+
+#[tauri::command]
+pub fn get_autostart_status() -> Result<AutostartStatus, String> {
+    status()
+}
+
+#[tauri::command]
+pub fn get_startup_settings() -> Result<StartupSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub fn save_startup_settings(app_handle: AppHandle, settings: StartupSettings) -> Result<(), String> {
+    parse_hotkey("show", &settings.show_hotkey)?;
+    parse_hotkey("conversation", &settings.conversation_hotkey)?;
+    if settings.show_hotkey == settings.conversation_hotkey {
+        return Err("The show and conversation hotkeys must differ".to_string());
+    }
+    let path = get_settings_path().map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write settings file: {}", e))?;
+    println!("💾 Saved startup settings (background agent: {})", settings.background_agent);
+    apply(&app_handle, &settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_quotes_the_executable_and_default_hotkeys_parse() {
+        let entry = desktop_entry("/opt/My Apps/en$teract");
+        assert!(entry.contains("Exec=\"/opt/My Apps/en\\\\$teract\" --background\n"));
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+
+        let settings = StartupSettings::default();
+        assert!(parse_hotkey("show", &settings.show_hotkey).is_ok());
+        assert!(parse_hotkey("conversation", &settings.conversation_hotkey).is_ok());
+        assert!(parse_hotkey("show", "Ctrl+Nonsense+").is_err());
+    }
+}
@@ -0,0 +1,393 @@
+// src-tauri/src/stt_provider.rs
+// Speech-to-text provider abstraction. Each audio stream (microphone, loopback) picks a provider
+// in settings: in-process whisper-rs, a whisper.cpp / faster-whisper server, or an optional cloud API.
+// Every provider returns the same TranscriptionResult so event payloads don't change.
+use crate::speech::{TranscriptionResult, WhisperModelConfig};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum SttStream {
+    Microphone,
+    Loopback,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SttProviderConfig {
+    /// whisper-rs running in this process (the default)
+    LocalWhisper,
+    /// whisper.cpp `server` example: POST {base_url}/inference
+    WhisperCppServer { base_url: String },
+    /// OpenAI-compatible /v1/audio/transcriptions (faster-whisper-server, OpenAI, Groq, ...)
+    OpenAiCompatible {
+        base_url: String,
+        api_key: Option<String>,
+        model: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SttSettings {
+    pub microphone: SttProviderConfig,
+    pub loopback: SttProviderConfig,
+    pub request_timeout_secs: u64,
+    pub fallback_to_local: bool, // Retry with local Whisper when a remote provider fails
+}
+
+impl Default for SttSettings {
+    fn default() -> Self {
+        Self {
+            microphone: SttProviderConfig::LocalWhisper,
+            loopback: SttProviderConfig::LocalWhisper,
+            request_timeout_secs: 30,
+            fallback_to_local: true,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    // Read once, then kept in step by save_stt_settings, so chunks don't each hit the disk
+    static ref SETTINGS: Mutex<Option<SttSettings>> = Mutex::new(None);
+}
+
+#[async_trait]
+pub trait SttProvider: Send + Sync {
+    fn name(&self) -> &str;
+    /// Transcribe raw PCM16 mono audio at 16kHz.
+    async fn transcribe(&self, pcm16: &[u8], config: &WhisperModelConfig) -> Result<TranscriptionResult, String>;
+}
+
+pub struct LocalWhisperProvider;
+
+#[async_trait]
+impl SttProvider for LocalWhisperProvider {
+    fn name(&self) -> &str { "local_whisper" }
+
+    async fn transcribe(&self, pcm16: &[u8], config: &WhisperModelConfig) -> Result<TranscriptionResult, String> {
+        let samples = crate::speech::pcm16_to_f32(pcm16);
+        crate::speech::transcribe_samples_local(samples, config.clone()).await
+    }
+}
+
+pub struct WhisperCppServerProvider {
+    base_url: String,
+    timeout: Duration,
+}
+
+#[derive(Deserialize)]
+struct ResponseSegment {
+    #[serde(default)]
+    text: String,
+    avg_logprob: Option<f32>,
+    no_speech_prob: Option<f32>,
+}
+
+// `json` responses carry the text only; `verbose_json` adds per-segment scores
+#[derive(Deserialize)]
+struct TextResponse {
+    text: String,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    segments: Option<Vec<ResponseSegment>>,
+}
+
+impl TextResponse {
+    /// The server's own confidence, else the segments' mean token probability (discounted by the
+    /// chance the segment isn't speech) weighted by text length. None when the server gave neither.
+    fn confidence(&self) -> Option<f32> {
+        if let Some(confidence) = self.confidence {
+            return Some(confidence.clamp(0.0, 1.0));
+        }
+        let (sum, weight) = self
+            .segments
+            .iter()
+            .flatten()
+            .filter_map(|segment| {
+                let probability = segment.avg_logprob?.exp() * (1.0 - segment.no_speech_prob.unwrap_or(0.0));
+                Some((probability, segment.text.trim().chars().count().max(1) as f32))
+            })
+            .fold((0.0, 0.0), |(sum, weight), (probability, chars)| (sum + probability * chars, weight + chars));
+        (weight > 0.0).then(|| (sum / weight).clamp(0.0, 1.0))
+    }
+}
+
+#[async_trait]
+impl SttProvider for WhisperCppServerProvider {
+    fn name(&self) -> &str { "whisper_cpp_server" }
+
+    async fn transcribe(&self, pcm16: &[u8], config: &WhisperModelConfig) -> Result<TranscriptionResult, String> {
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", wav_part(pcm16)?)
+            .text("response_format", "verbose_json")
+            .text("temperature", "0.0");
+        if let Some(language) = requested_language(config) {
+            form = form.text("language", language);
+        }
+        if let Some(prompt) = crate::glossary::whisper_prompt() {
+            form = form.text("prompt", prompt);
+        }
+
+        let response = http_client(self.timeout)?
+            .post(format!("{}/inference", self.base_url.trim_end_matches('/')))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| format!("whisper.cpp server request failed: {}", e))?;
+        let body = read_text_response(response).await?;
+        Ok(to_result(&body, pcm16, config))
+    }
+}
+
+pub struct OpenAiCompatibleProvider {
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    timeout: Duration,
+}
+
+impl OpenAiCompatibleProvider {
+    async fn send(&self, pcm16: &[u8], config: &WhisperModelConfig, response_format: &'static str) -> Result<reqwest::Response, String> {
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", wav_part(pcm16)?)
+            .text("model", self.model.clone())
+            .text("response_format", response_format)
+            .text("temperature", "0");
+        if let Some(language) = requested_language(config) {
+            form = form.text("language", language);
+        }
+        if let Some(prompt) = crate::glossary::whisper_prompt() {
+            form = form.text("prompt", prompt);
+        }
+
+        let mut request = http_client(self.timeout)?
+            .post(format!("{}/v1/audio/transcriptions", self.base_url.trim_end_matches('/')))
+            .multipart(form);
+        if let Some(api_key) = self.api_key.as_ref().filter(|key| !key.is_empty()) {
+            request = request.bearer_auth(api_key);
+        }
+
+        request
+            .send()
+            .await
+            .map_err(|e| format!("Transcription API request failed: {}", e))
+    }
+}
+
+#[async_trait]
+impl SttProvider for OpenAiCompatibleProvider {
+    fn name(&self) -> &str { "openai_compatible" }
+
+    async fn transcribe(&self, pcm16: &[u8], config: &WhisperModelConfig) -> Result<TranscriptionResult, String> {
+        let mut response = self.send(pcm16, config, "verbose_json").await?;
+        // Some models (e.g. gpt-4o-transcribe) only return plain JSON, without segment scores
+        if response.status() == reqwest::StatusCode::BAD_REQUEST {
+            response = self.send(pcm16, config, "json").await?;
+        }
+        let body = read_text_response(response).await?;
+        Ok(to_result(&body, pcm16, config))
+    }
+}
+
+fn http_client(timeout: Duration) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+async fn read_text_response(response: reqwest::Response) -> Result<TextResponse, String> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Transcription server returned HTTP {}: {}", status, body));
+    }
+    response
+        .json::<TextResponse>()
+        .await
+        .map_err(|e| format!("Failed to parse transcription response: {}", e))
+}
+
+fn requested_language(config: &WhisperModelConfig) -> Option<String> {
+    config
+        .language
+        .clone()
+        .filter(|lang| !lang.is_empty() && lang != "auto")
+}
+
+// Remote servers expect a real audio file, so wrap the PCM in a WAV header
+fn wav_part(pcm16: &[u8]) -> Result<reqwest::multipart::Part, String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)
+            .map_err(|e| format!("Failed to create WAV writer: {}", e))?;
+        for chunk in pcm16.chunks_exact(2) {
+            writer
+                .write_sample(i16::from_le_bytes([chunk[0], chunk[1]]))
+                .map_err(|e| format!("Failed to write WAV sample: {}", e))?;
+        }
+        writer.finalize().map_err(|e| format!("Failed to finalize WAV: {}", e))?;
+    }
+
+    reqwest::multipart::Part::bytes(cursor.into_inner())
+        .file_name("audio.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| format!("Failed to build upload: {}", e))
+}
+
+// Fill in what remote providers leave out the way the local path does
+fn to_result(body: &TextResponse, pcm16: &[u8], config: &WhisperModelConfig) -> TranscriptionResult {
+    let text = crate::glossary::apply_casing(body.text.trim(), &crate::glossary::load_glossary().active_terms());
+    let duration = (pcm16.len() / 2) as f32 / 16000.0;
+    let confidence = body.confidence();
+    TranscriptionResult {
+        confidence: if text.is_empty() { 0.0 } else { confidence.unwrap_or(crate::speech::NEUTRAL_CONFIDENCE) },
+        confidence_unknown: !text.is_empty() && confidence.is_none(),
+        text,
+        start_time: 0.0,
+        end_time: duration,
+        language: config.language.clone(),
+        audio: None,
+    }
+}
+
+fn get_stt_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("stt_settings.json")
+}
+
+fn load_settings() -> SttSettings {
+    let mut cached = SETTINGS.lock().unwrap_or_else(|e| e.into_inner());
+    cached
+        .get_or_insert_with(|| {
+            get_stt_settings_path()
+                .ok()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default()
+        })
+        .clone()
+}
+
+pub fn build_provider(config: &SttProviderConfig, timeout: Duration) -> Box<dyn SttProvider> {
+    match config {
+        SttProviderConfig::LocalWhisper => Box::new(LocalWhisperProvider),
+        SttProviderConfig::WhisperCppServer { base_url } => Box::new(WhisperCppServerProvider {
+            base_url: base_url.clone(),
+            timeout,
+        }),
+        SttProviderConfig::OpenAiCompatible { base_url, api_key, model } => Box::new(OpenAiCompatibleProvider {
+            base_url: base_url.clone(),
+            api_key: api_key.clone(),
+            model: model.clone(),
+            timeout,
+        }),
+    }
+}
+
+/// Transcribe PCM16 audio for a stream with that stream's configured provider. `keep_audio` is off
+/// for audio that isn't part of the conversation being archived.
+pub async fn transcribe_pcm16(
+    stream: SttStream,
+    pcm16: Vec<u8>,
+    config: WhisperModelConfig,
+    keep_audio: bool,
+) -> Result<TranscriptionResult, String> {
+    let settings = load_settings();
+    let provider_config = match stream {
+        SttStream::Microphone => &settings.microphone,
+        SttStream::Loopback => &settings.loopback,
+    };
+    let provider = build_provider(provider_config, Duration::from_secs(settings.request_timeout_secs.max(1)));
+
+    let mut result = match provider.transcribe(&pcm16, &config).await {
+        Ok(result) => result,
+        Err(e) if settings.fallback_to_local && *provider_config != SttProviderConfig::LocalWhisper => {
+            println!("⚠️ {} transcription failed, falling back to local Whisper: {}", provider.name(), e);
+            LocalWhisperProvider.transcribe(&pcm16, &config).await?
+        }
+        Err(e) => return Err(e),
+    };
+
+    // Only audio that produced a transcript line is worth keeping for playback
+    if keep_audio && !result.text.trim().is_empty() {
+        result.audio = crate::conversation_audio::append(stream, &pcm16);
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_stt_settings() -> Result<SttSettings, String> {
+    Ok(load_settings())
+}
+
+#[tauri::command]
+pub async fn save_stt_settings(settings: SttSettings) -> Result<(), String> {
+    for provider in [&settings.microphone, &settings.loopback] {
+        match provider {
+            SttProviderConfig::LocalWhisper => {}
+            SttProviderConfig::WhisperCppServer { base_url } | SttProviderConfig::OpenAiCompatible { base_url, .. } => {
+                if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+                    return Err(format!("Invalid STT server URL: {}", base_url));
+                }
+            }
+        }
+    }
+
+    let settings_path = get_stt_settings_path()
+        .map_err(|e| format!("Failed to get settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(settings_path, json)
+        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+
+    println!("🎙️ STT providers: microphone={}, loopback={}", provider_label(&settings.microphone), provider_label(&settings.loopback));
+    *SETTINGS.lock().unwrap_or_else(|e| e.into_inner()) = Some(settings);
+    Ok(())
+}
+
+fn provider_label(config: &SttProviderConfig) -> &'static str {
+    match config {
+        SttProviderConfig::LocalWhisper => "local_whisper",
+        SttProviderConfig::WhisperCppServer { .. } => "whisper_cpp_server",
+        SttProviderConfig::OpenAiCompatible { .. } => "openai_compatible",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(json: serde_json::Value) -> TextResponse {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_response_confidence_comes_from_the_server_or_nowhere() {
+        assert_eq!(response(serde_json::json!({ "text": "hello there" })).confidence(), None);
+        assert_eq!(response(serde_json::json!({ "text": "hi", "confidence": 1.4 })).confidence(), Some(1.0));
+
+        // exp(0) = 1.0 over 3 chars and exp(ln 0.5) = 0.5 over 1 char, the latter half likely not speech
+        let verbose = response(serde_json::json!({
+            "text": "abc d",
+            "segments": [
+                { "text": "abc", "avg_logprob": 0.0, "no_speech_prob": 0.0 },
+                { "text": "d", "avg_logprob": 0.5f32.ln(), "no_speech_prob": 0.5 },
+                { "text": "unscored" }
+            ]
+        }));
+        assert!((verbose.confidence().unwrap() - (3.0 + 0.25) / 4.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,56 @@
+// src-tauri/src/test_support/memory_db.rs
+// An in-memory SQLite database per test. Storage types open it through `open(path)` like a file,
+// and any number of connections see the same data until the fixture is dropped.
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+
+pub struct MemoryDatabase {
+    path: PathBuf,
+    _keeper: Connection, // The database is freed when its last connection closes
+}
+
+impl MemoryDatabase {
+    pub fn new() -> Self {
+        let path = crate::data::paths::in_memory_database_uri(&format!("enteract-test-{}", uuid::Uuid::new_v4()));
+        let keeper = Connection::open(&path).expect("Failed to open in-memory database");
+        Self { path, _keeper: keeper }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::conversation::storage::ConversationStorage;
+    use crate::data::types::{ConversationMessage, ConversationSession};
+
+    #[test]
+    fn test_conversations_round_trip_across_connections() {
+        let db = MemoryDatabase::new();
+        let session: ConversationSession = serde_json::from_value(serde_json::json!({
+            "id": "session-1", "name": "Standup", "startTime": 1000, "endTime": null,
+            "messages": [], "isActive": true
+        })).unwrap();
+        let message: ConversationMessage = serde_json::from_value(serde_json::json!({
+            "id": "message-1", "type": "user", "source": "microphone", "content": "Ship it",
+            "timestamp": 1500, "confidence": 0.9, "audioOffset": 3200, "audioDurationMs": 1200
+        })).unwrap();
+
+        let mut writer = ConversationStorage::open(db.path()).unwrap();
+        writer.save_or_update_session(session).unwrap();
+        writer.save_conversation_message("session-1", message).unwrap();
+
+        let reader = ConversationStorage::open(db.path()).unwrap();
+        let (session_id, stored) = reader.get_message("message-1").unwrap().unwrap();
+        assert_eq!(session_id, "session-1");
+        assert_eq!(stored.content, "Ship it");
+        assert_eq!(stored.audio_offset, Some(3200));
+
+        // Another fixture is a different database
+        let other = ConversationStorage::open(MemoryDatabase::new().path()).unwrap();
+        assert!(other.get_message("message-1").unwrap().is_none());
+    }
+}
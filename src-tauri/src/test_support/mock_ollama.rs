@@ -0,0 +1,176 @@
+// src-tauri/src/test_support/mock_ollama.rs
+// A local HTTP server that answers like Ollama with scripted text. Starting one points the ollama
+// module at it until it's dropped; tests that use it run one at a time since that setting is
+// process-wide. Streaming replies split every JSON line across two writes so callers have to
+// reassemble lines the way they must with a real server.
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::OwnedMutexGuard;
+
+pub const MOCK_MODEL: &str = "gemma3:1b-it-qat";
+
+lazy_static::lazy_static! {
+    static ref SERVER_SLOT: Arc<tokio::sync::Mutex<()>> = Arc::new(tokio::sync::Mutex::new(()));
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Value,
+}
+
+pub struct MockOllama {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    server: tokio::task::JoinHandle<()>,
+    _slot: OwnedMutexGuard<()>,
+}
+
+impl MockOllama {
+    /// Serve `chunks` as the reply to every generate or chat request: one NDJSON line per chunk when
+    /// streaming, concatenated otherwise.
+    pub async fn start(chunks: &[&str]) -> Self {
+        let slot = SERVER_SLOT.clone().lock_owned().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind mock Ollama");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let chunks: Arc<Vec<String>> = Arc::new(chunks.iter().map(|c| c.to_string()).collect());
+
+        let recorded = requests.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, chunks.clone(), recorded.clone()));
+            }
+        });
+
+        crate::ollama::set_base_url_override(Some(url.clone()));
+        Self { url, requests, server, _slot: slot }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockOllama {
+    fn drop(&mut self) {
+        crate::ollama::set_base_url_override(None);
+        self.server.abort();
+    }
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<RecordedRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut buf).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..read]);
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut request_line = head.lines().next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while data.len() < header_end + content_length {
+        let read = stream.read(&mut buf).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..read]);
+    }
+    let body = serde_json::from_slice(&data[header_end..]).unwrap_or(Value::Null);
+    Some(RecordedRequest { method, path, body })
+}
+
+async fn write_json(stream: &mut TcpStream, body: Value) {
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+fn generate_line(model: &str, text: &str, done: bool) -> Value {
+    json!({
+        "model": model,
+        "created_at": "2024-01-01T00:00:00Z",
+        "response": text,
+        "done": done,
+    })
+}
+
+fn chat_line(model: &str, text: &str, done: bool) -> Value {
+    json!({
+        "model": model,
+        "created_at": "2024-01-01T00:00:00Z",
+        "message": { "role": "assistant", "content": text },
+        "done": done,
+    })
+}
+
+async fn serve(mut stream: TcpStream, chunks: Arc<Vec<String>>, requests: Arc<Mutex<Vec<RecordedRequest>>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    requests.lock().unwrap().push(request.clone());
+
+    match request.path.as_str() {
+        "/api/version" => write_json(&mut stream, json!({ "version": "0.0.0-mock" })).await,
+        "/api/tags" => {
+            write_json(&mut stream, json!({
+                "models": [{ "name": MOCK_MODEL, "modified_at": "2024-01-01T00:00:00Z", "size": 0, "digest": "mock" }]
+            }))
+            .await
+        }
+        "/api/generate" | "/api/chat" => {
+            let model = request.body["model"].as_str().unwrap_or(MOCK_MODEL).to_string();
+            let line_for = if request.path == "/api/chat" { chat_line } else { generate_line };
+            // Ollama streams unless told not to
+            if request.body["stream"].as_bool() == Some(false) {
+                write_json(&mut stream, line_for(&model, &chunks.concat(), true)).await;
+                return;
+            }
+
+            let header = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n";
+            if stream.write_all(header.as_bytes()).await.is_err() {
+                return;
+            }
+            let lines = chunks
+                .iter()
+                .map(|chunk| line_for(&model, chunk, false))
+                .chain(std::iter::once(line_for(&model, "", true)));
+            for line in lines {
+                let line = format!("{}\n", line);
+                let (first, second) = line.as_bytes().split_at(line.len() / 2);
+                for part in [first, second] {
+                    if stream.write_all(part).await.is_err() || stream.flush().await.is_err() {
+                        return;
+                    }
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                }
+            }
+        }
+        _ => {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+        }
+    }
+}
@@ -0,0 +1,7 @@
+// src-tauri/src/test_support/mod.rs
+// Fixtures for end-to-end tests that must not depend on the machine they run on: a mock Ollama
+// server, a synthetic audio source for the capture pipeline, and throwaway in-memory databases.
+// Nothing here is compiled outside `cargo test`.
+pub mod memory_db;
+pub mod mock_ollama;
+pub mod synthetic_audio;
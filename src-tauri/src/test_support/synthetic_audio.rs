@@ -0,0 +1,93 @@
+// src-tauri/src/test_support/synthetic_audio.rs
+// A stand-in for a capture device: builds known PCM16 (tones and silence) and feeds it to the
+// capture pipeline in device-sized chunks on a simulated clock, so a test sees exactly the
+// transcription requests and preview chunks a real device would produce for the same audio.
+use crate::audio_loopback::capture_pipeline::{CapturePipeline, CaptureSink};
+use crate::audio_loopback::latency::SegmentTiming;
+use std::time::{Duration, Instant};
+
+pub struct SyntheticSource {
+    sample_rate: u32,
+    channels: u16,
+    chunk_ms: u32,
+    frames: Vec<i16>, // Mono; duplicated into every channel on output
+}
+
+impl SyntheticSource {
+    pub fn new(sample_rate: u32, channels: u16, chunk_ms: u32) -> Self {
+        Self {
+            sample_rate,
+            channels,
+            chunk_ms,
+            frames: Vec::new(),
+        }
+    }
+
+    fn frame_count(&self, seconds: f32) -> usize {
+        (self.sample_rate as f32 * seconds) as usize
+    }
+
+    /// Append a sine tone; `amplitude` is a fraction of full scale.
+    pub fn tone(&mut self, frequency: f32, amplitude: f32, seconds: f32) -> &mut Self {
+        let start = self.frames.len();
+        for i in 0..self.frame_count(seconds) {
+            let t = (start + i) as f32 / self.sample_rate as f32;
+            let sample = (2.0 * std::f32::consts::PI * frequency * t).sin() * amplitude;
+            self.frames.push((sample * i16::MAX as f32) as i16);
+        }
+        self
+    }
+
+    pub fn silence(&mut self, seconds: f32) -> &mut Self {
+        let count = self.frame_count(seconds);
+        self.frames.extend(std::iter::repeat(0).take(count));
+        self
+    }
+
+    /// Interleaved little-endian PCM16, as a device would deliver it.
+    pub fn pcm16_bytes(&self) -> Vec<u8> {
+        self.frames
+            .iter()
+            .flat_map(|&sample| std::iter::repeat(sample.to_le_bytes()).take(self.channels as usize))
+            .flatten()
+            .collect()
+    }
+
+    /// Push everything through a fresh pipeline. The clock advances by each chunk's duration.
+    pub fn run(&mut self, vad_rms_threshold: f32, sink: &mut dyn CaptureSink) -> CapturePipeline {
+        self.run_with_min_interval(vad_rms_threshold, Duration::ZERO, sink)
+    }
+
+    /// `run` with the pipeline's transcription interval floored, as the power policy does.
+    pub fn run_with_min_interval(&mut self, vad_rms_threshold: f32, min_interval: Duration, sink: &mut dyn CaptureSink) -> CapturePipeline {
+        let start = Instant::now();
+        let mut pipeline = CapturePipeline::new(vad_rms_threshold, start);
+        pipeline.set_min_interval(min_interval);
+        let bytes = self.pcm16_bytes();
+        let chunk_frames = (self.sample_rate * self.chunk_ms / 1000) as usize;
+        let chunk_bytes = chunk_frames * self.channels as usize * 2;
+
+        for (index, chunk) in bytes.chunks(chunk_bytes).enumerate() {
+            let now = start + Duration::from_millis(self.chunk_ms as u64 * (index as u64 + 1));
+            pipeline.push(chunk, 16, self.channels, self.sample_rate, now, sink);
+        }
+        pipeline
+    }
+}
+
+/// Keeps whatever the pipeline sends so a test can assert on it.
+#[derive(Default)]
+pub struct RecordingSink {
+    pub transcriptions: Vec<(Vec<u8>, u32)>,
+    pub chunks: Vec<(Vec<u8>, f32, u64)>,
+}
+
+impl CaptureSink for RecordingSink {
+    fn transcribe(&mut self, pcm16_stereo: Vec<u8>, sample_rate: u32, _timing: SegmentTiming) {
+        self.transcriptions.push((pcm16_stereo, sample_rate));
+    }
+
+    fn audio_chunk(&mut self, pcm16_mono: Vec<u8>, level: f32, total_samples: u64) {
+        self.chunks.push((pcm16_mono, level, total_samples));
+    }
+}
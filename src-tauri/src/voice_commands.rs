@@ -0,0 +1,377 @@
+// src-tauri/src/voice_commands.rs
+// Voice commands matched against the user's own transcript stream and mapped to backend actions,
+// with confirmation policies so destructive actions can't fire from a misheard phrase. Only the
+// backend microphone transcription feeds the matcher, so loopback audio (the other side of a
+// call) can never issue a command.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum VoiceAction {
+    StartCapture,
+    StopCapture,
+    ScreenshotAndAsk,
+    NewChat,
+    Summarize,
+}
+
+impl VoiceAction {
+    fn is_destructive(&self) -> bool {
+        matches!(self, VoiceAction::StopCapture | VoiceAction::NewChat)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ConfirmationPolicy {
+    Immediate,
+    AskInApp,   // The frontend shows a prompt and calls confirm_voice_command
+    SayConfirm, // The next user utterance must be "confirm" (or "cancel")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCommand {
+    pub id: String,
+    pub phrases: Vec<String>,
+    pub action: VoiceAction,
+    pub enabled: bool,
+    pub confirmation: ConfirmationPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCommandSettings {
+    pub enabled: bool,
+    pub wake_phrase: Option<String>, // e.g. "hey enteract"; None matches commands anywhere in an utterance
+    pub confirmation_timeout_secs: u64,
+    pub commands: Vec<VoiceCommand>,
+}
+
+fn command(id: &str, phrases: &[&str], action: VoiceAction) -> VoiceCommand {
+    VoiceCommand {
+        id: id.to_string(),
+        phrases: phrases.iter().map(|p| p.to_string()).collect(),
+        action,
+        enabled: true,
+        confirmation: if action.is_destructive() { ConfirmationPolicy::SayConfirm } else { ConfirmationPolicy::Immediate },
+    }
+}
+
+impl Default for VoiceCommandSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wake_phrase: Some("hey enteract".to_string()),
+            confirmation_timeout_secs: 10,
+            commands: vec![
+                command("start_capture", &["start recording", "start listening", "start capture"], VoiceAction::StartCapture),
+                command("stop_capture", &["stop recording", "stop listening", "stop capture"], VoiceAction::StopCapture),
+                command("screenshot_ask", &["take a screenshot and ask", "look at my screen", "what's on my screen"], VoiceAction::ScreenshotAndAsk),
+                command("new_chat", &["new chat", "start a new chat"], VoiceAction::NewChat),
+                command("summarize", &["summarize this", "summarize the conversation"], VoiceAction::Summarize),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoiceCommandMatch {
+    pub command_id: String,
+    pub action: VoiceAction,
+    pub argument: Option<String>, // Whatever followed the phrase, e.g. the question for ScreenshotAndAsk
+    pub status: String,           // "executed", "awaiting_confirmation", "cancelled", "failed"
+    pub pending_id: Option<String>,
+    pub detail: Option<String>,
+}
+
+struct PendingCommand {
+    command: VoiceCommand,
+    argument: Option<String>,
+    created: Instant,
+}
+
+#[derive(Default)]
+struct VoiceCommandState {
+    settings: Option<VoiceCommandSettings>,
+    pending: HashMap<String, PendingCommand>,
+}
+
+lazy_static::lazy_static! {
+    static ref VOICE_COMMAND_STATE: Arc<Mutex<VoiceCommandState>> = Arc::new(Mutex::new(VoiceCommandState::default()));
+}
+
+fn get_settings_path() -> anyhow::Result<PathBuf> {
+    crate::data::paths::config_file("voice_commands.json")
+}
+
+fn current_settings() -> VoiceCommandSettings {
+    if let Ok(state) = VOICE_COMMAND_STATE.lock() {
+        if let Some(settings) = state.settings.as_ref() {
+            return settings.clone();
+        }
+    }
+
+    let settings: VoiceCommandSettings = get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    if let Ok(mut state) = VOICE_COMMAND_STATE.lock() {
+        state.settings = Some(settings.clone());
+    }
+    settings
+}
+
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '\'' { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Find the first enabled command in an utterance, returning it with any trailing argument.
+pub fn match_command(settings: &VoiceCommandSettings, utterance: &str) -> Option<(VoiceCommand, Option<String>)> {
+    let mut text = normalize(utterance);
+
+    if let Some(wake) = settings.wake_phrase.as_ref().map(|w| normalize(w)).filter(|w| !w.is_empty()) {
+        let position = format!(" {} ", text).find(&format!(" {} ", wake))?;
+        text = text[position + wake.len()..].trim().to_string();
+    }
+
+    let padded = format!(" {} ", text);
+    settings
+        .commands
+        .iter()
+        .filter(|command| command.enabled)
+        .flat_map(|command| command.phrases.iter().map(move |phrase| (command, normalize(phrase))))
+        .filter(|(_, phrase)| !phrase.is_empty())
+        .filter_map(|(command, phrase)| {
+            padded.find(&format!(" {} ", phrase)).map(|position| (position, phrase.len(), command))
+        })
+        // Earliest match wins; on ties prefer the longer, more specific phrase
+        .min_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+        .map(|(position, length, command)| {
+            let rest = padded[position + length + 1..].trim().to_string();
+            (command.clone(), if rest.is_empty() { None } else { Some(rest) })
+        })
+}
+
+async fn execute_action(app_handle: &AppHandle, action: VoiceAction, argument: Option<String>) -> Result<Option<String>, String> {
+    match action {
+        VoiceAction::StartCapture => {
+            let device_id = crate::audio_loopback::load_audio_settings()
+                .await?
+                .and_then(|settings| settings.selectedLoopbackDevice)
+                .ok_or_else(|| "No capture device selected in audio settings".to_string())?;
+            // The command was spoken into the conversation the microphone is recording
+            let session_id = crate::consent::recording_session("microphone")
+                .ok_or_else(|| "Start a conversation before starting capture".to_string())?;
+            crate::audio_loopback::start_audio_loopback_capture(device_id, None, session_id, app_handle.clone()).await.map(Some)
+        }
+        VoiceAction::StopCapture => {
+            crate::audio_loopback::stop_audio_loopback_capture().await?;
+            Ok(Some("Audio capture stopped".to_string()))
+        }
+        VoiceAction::ScreenshotAndAsk => {
+            let screenshot = crate::screenshot::capture_screenshot().await?;
+            let session_id = uuid::Uuid::new_v4().to_string();
+            let prompt = argument.unwrap_or_else(|| "What is on my screen and what should I do next?".to_string());
+
+            // The answer streams on ollama-stream-{session_id}, same as a typed vision request
+            let handle = app_handle.clone();
+            let stream_session = session_id.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::ollama::generate_vision_analysis(handle, prompt, screenshot.image_base64, stream_session, None, None).await {
+                    eprintln!("Voice screenshot analysis failed: {}", e);
+                }
+            });
+            Ok(Some(session_id))
+        }
+        // Chats and conversation context live in the frontend, which handles these via the event below
+        VoiceAction::NewChat | VoiceAction::Summarize => Ok(None),
+    }
+}
+
+async fn run_command(app_handle: &AppHandle, command: VoiceCommand, argument: Option<String>) -> VoiceCommandMatch {
+    let (status, detail) = match execute_action(app_handle, command.action, argument.clone()).await {
+        Ok(detail) => ("executed", detail),
+        Err(e) => ("failed", Some(e)),
+    };
+
+    let result = VoiceCommandMatch {
+        command_id: command.id,
+        action: command.action,
+        argument,
+        status: status.to_string(),
+        pending_id: None,
+        detail,
+    };
+    println!("🗣️ Voice command {:?}: {}", result.action, result.status);
+    if let Err(e) = app_handle.emit("voice-command", &result) {
+        eprintln!("Failed to emit voice command event: {}", e);
+    }
+    result
+}
+
+fn take_pending(pending_id: Option<&str>, timeout: Duration) -> Option<(String, PendingCommand)> {
+    let mut state = VOICE_COMMAND_STATE.lock().ok()?;
+    state.pending.retain(|_, pending| pending.created.elapsed() < timeout);
+
+    let id = match pending_id {
+        Some(id) => id.to_string(),
+        // Spoken confirmations apply to the most recent SayConfirm request
+        None => state
+            .pending
+            .iter()
+            .filter(|(_, p)| p.command.confirmation == ConfirmationPolicy::SayConfirm)
+            .max_by_key(|(_, p)| p.created)
+            .map(|(id, _)| id.clone())?,
+    };
+    state.pending.remove(&id).map(|pending| (id, pending))
+}
+
+/// Match a microphone transcript against the voice commands and run, or queue for confirmation,
+/// whichever it names.
+pub async fn handle_microphone_transcript(app_handle: AppHandle, text: String) -> Result<Option<VoiceCommandMatch>, String> {
+    let settings = current_settings();
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let timeout = Duration::from_secs(settings.confirmation_timeout_secs.max(1));
+
+    let normalized = normalize(&text);
+    if matches!(normalized.as_str(), "confirm" | "yes confirm" | "do it" | "cancel" | "never mind") {
+        if let Some((pending_id, pending)) = take_pending(None, timeout) {
+            if normalized == "cancel" || normalized == "never mind" {
+                let result = VoiceCommandMatch {
+                    command_id: pending.command.id,
+                    action: pending.command.action,
+                    argument: pending.argument,
+                    status: "cancelled".to_string(),
+                    pending_id: Some(pending_id),
+                    detail: None,
+                };
+                let _ = app_handle.emit("voice-command", &result);
+                return Ok(Some(result));
+            }
+            return Ok(Some(run_command(&app_handle, pending.command, pending.argument).await));
+        }
+    }
+
+    let (command, argument) = match match_command(&settings, &text) {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+
+    if command.confirmation == ConfirmationPolicy::Immediate {
+        return Ok(Some(run_command(&app_handle, command, argument).await));
+    }
+
+    let pending_id = uuid::Uuid::new_v4().to_string();
+    let result = VoiceCommandMatch {
+        command_id: command.id.clone(),
+        action: command.action,
+        argument: argument.clone(),
+        status: "awaiting_confirmation".to_string(),
+        pending_id: Some(pending_id.clone()),
+        detail: Some(match command.confirmation {
+            ConfirmationPolicy::SayConfirm => "Say \"confirm\" or \"cancel\"".to_string(),
+            _ => "Confirm in the app".to_string(),
+        }),
+    };
+    {
+        let mut state = VOICE_COMMAND_STATE.lock().map_err(|e| e.to_string())?;
+        state.pending.insert(pending_id, PendingCommand { command, argument, created: Instant::now() });
+    }
+    let _ = app_handle.emit("voice-command", &result);
+    Ok(Some(result))
+}
+
+#[tauri::command]
+pub async fn confirm_voice_command(app_handle: AppHandle, pending_id: String) -> Result<VoiceCommandMatch, String> {
+    let timeout = Duration::from_secs(current_settings().confirmation_timeout_secs.max(1));
+    let (_, pending) = take_pending(Some(&pending_id), timeout)
+        .ok_or_else(|| "Voice command expired or not found".to_string())?;
+    Ok(run_command(&app_handle, pending.command, pending.argument).await)
+}
+
+#[tauri::command]
+pub async fn cancel_voice_command(pending_id: String) -> Result<(), String> {
+    let mut state = VOICE_COMMAND_STATE.lock().map_err(|e| e.to_string())?;
+    state.pending.remove(&pending_id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_voice_command_settings() -> Result<VoiceCommandSettings, String> {
+    Ok(current_settings())
+}
+
+#[tauri::command]
+pub async fn update_voice_command_settings(settings: VoiceCommandSettings) -> Result<VoiceCommandSettings, String> {
+    let settings_path = get_settings_path()
+        .map_err(|e| format!("Failed to get voice command settings path: {}", e))?;
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize voice command settings: {}", e))?;
+    fs::write(settings_path, json)
+        .map_err(|e| format!("Failed to write voice command settings: {}", e))?;
+
+    let mut state = VOICE_COMMAND_STATE.lock().map_err(|e| e.to_string())?;
+    state.settings = Some(settings.clone());
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(wake_phrase: Option<&str>) -> VoiceCommandSettings {
+        VoiceCommandSettings {
+            enabled: true,
+            wake_phrase: wake_phrase.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn matched(settings: &VoiceCommandSettings, utterance: &str) -> Option<(String, Option<String>)> {
+        match_command(settings, utterance).map(|(command, argument)| (command.id, argument))
+    }
+
+    #[test]
+    fn test_wake_phrase_is_required_before_the_command() {
+        let settings = settings(Some("Hey Enteract"));
+        assert_eq!(matched(&settings, "Hey, Enteract! Stop recording."), Some(("stop_capture".to_string(), None)));
+        assert_eq!(matched(&settings, "so anyway hey enteract new chat"), Some(("new_chat".to_string(), None)));
+        // The command alone, or before the wake phrase, doesn't count
+        assert_eq!(matched(&settings, "stop recording"), None);
+        assert_eq!(matched(&settings, "stop recording hey enteract"), None);
+    }
+
+    #[test]
+    fn test_trailing_words_become_the_argument() {
+        let settings = settings(None);
+        assert_eq!(
+            matched(&settings, "Look at my screen, why does this build fail?"),
+            Some(("screenshot_ask".to_string(), Some("why does this build fail".to_string())))
+        );
+        // The longer phrase wins over "new chat" at the same position
+        assert_eq!(matched(&settings, "start a new chat"), Some(("new_chat".to_string(), None)));
+    }
+
+    #[test]
+    fn test_non_matches() {
+        let mut settings = settings(None);
+        assert_eq!(matched(&settings, "we should summarize things later"), None);
+        // Phrases match whole words only
+        assert_eq!(matched(&settings, "restart recordings"), None);
+        settings.commands.iter_mut().for_each(|command| command.enabled = command.id != "new_chat");
+        assert_eq!(matched(&settings, "new chat"), None);
+    }
+}
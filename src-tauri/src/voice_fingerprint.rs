@@ -0,0 +1,190 @@
+// src-tauri/src/voice_fingerprint.rs
+// Lightweight voice fingerprints for recognizing recurring speakers. A fingerprint is the average
+// spectral envelope of the voiced frames (log energy in log-spaced bands, loudness removed) plus how
+// much each band varies, so it captures timbre rather than what was said. It is far cruder than a
+// neural speaker embedding, but needs no model and is good enough to pre-fill a label for review.
+
+const SAMPLE_RATE: f32 = 16000.0;
+const FRAME_SAMPLES: usize = 320; // 20ms
+const BANDS: usize = 16;
+const LOWEST_BAND_HZ: f32 = 150.0;
+const HIGHEST_BAND_HZ: f32 = 6000.0;
+// Frames quieter than this are pauses or background and say nothing about the voice
+const VOICED_RMS: f32 = 0.01;
+// About a second of speech before a fingerprint means anything
+const MIN_VOICED_FRAMES: usize = 50;
+
+pub const DIMENSIONS: usize = BANDS * 2;
+
+/// Cosine similarity above which two fingerprints are taken to be the same speaker.
+pub const MATCH_THRESHOLD: f32 = 0.85;
+
+// RBJ band-pass biquad
+struct BandFilter {
+    b0: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl BandFilter {
+    fn new(center_hz: f32, q: f32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * center_hz / SAMPLE_RATE;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: alpha / a0,
+            b2: -alpha / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+fn filter_bank() -> Vec<BandFilter> {
+    let ratio = (HIGHEST_BAND_HZ / LOWEST_BAND_HZ).powf(1.0 / (BANDS - 1) as f32);
+    let q = 1.0 / (ratio - 1.0);
+    (0..BANDS)
+        .map(|band| BandFilter::new(LOWEST_BAND_HZ * ratio.powi(band as i32), q))
+        .collect()
+}
+
+/// Fingerprint 16kHz mono audio, or None when it holds too little speech.
+pub fn fingerprint(samples: &[f32]) -> Option<Vec<f32>> {
+    let mut filters = filter_bank();
+    let mut sums = [0.0f64; BANDS];
+    let mut squares = [0.0f64; BANDS];
+    let mut voiced = 0;
+
+    for frame in samples.chunks_exact(FRAME_SAMPLES) {
+        let mut energies = [0.0f32; BANDS];
+        for &sample in frame {
+            for (energy, filter) in energies.iter_mut().zip(filters.iter_mut()) {
+                let y = filter.process(sample);
+                *energy += y * y;
+            }
+        }
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / FRAME_SAMPLES as f32).sqrt();
+        if rms < VOICED_RMS {
+            continue;
+        }
+
+        let logs: Vec<f32> = energies.iter().map(|e| (e / FRAME_SAMPLES as f32 + 1e-10).ln()).collect();
+        let loudness = logs.iter().sum::<f32>() / BANDS as f32;
+        for band in 0..BANDS {
+            let shape = (logs[band] - loudness) as f64;
+            sums[band] += shape;
+            squares[band] += shape * shape;
+        }
+        voiced += 1;
+    }
+    if voiced < MIN_VOICED_FRAMES {
+        return None;
+    }
+
+    let count = voiced as f64;
+    let means: Vec<f32> = sums.iter().map(|sum| (sum / count) as f32).collect();
+    let spreads: Vec<f32> = sums
+        .iter()
+        .zip(squares.iter())
+        .map(|(sum, square)| (square / count - (sum / count).powi(2)).max(0.0).sqrt() as f32)
+        .collect();
+    // Center the spreads too, otherwise they are all positive and make every pair look alike
+    let mean_spread = spreads.iter().sum::<f32>() / BANDS as f32;
+
+    let mut vector: Vec<f32> = means;
+    vector.extend(spreads.iter().map(|spread| spread - mean_spread));
+    Some(normalize(vector))
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Fold a new fingerprint into a speaker's voiceprint, weighting by how many went into it so far.
+pub fn blend(existing: &[f32], existing_samples: i64, new: &[f32]) -> Vec<f32> {
+    if existing.len() != new.len() || existing_samples <= 0 {
+        return new.to_vec();
+    }
+    let weight = existing_samples as f32;
+    normalize(
+        existing
+            .iter()
+            .zip(new)
+            .map(|(old, new)| (old * weight + new) / (weight + 1.0))
+            .collect(),
+    )
+}
+
+pub fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A buzzy harmonic "voice" whose harmonics are louder near `formant_hz`
+    fn voice(f0: f32, formant_hz: f32, seconds: f32, phase: f32) -> Vec<f32> {
+        (0..(seconds * SAMPLE_RATE) as usize)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE;
+                (1..30)
+                    .map(|harmonic| {
+                        let frequency = f0 * harmonic as f32;
+                        let gain = 1.0 / (1.0 + ((frequency - formant_hz) / 400.0).powi(2));
+                        gain * (2.0 * std::f32::consts::PI * frequency * t + phase * harmonic as f32).sin()
+                    })
+                    .sum::<f32>()
+                    * 0.05
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_voice_is_closer_than_a_different_one() {
+        let first = fingerprint(&voice(110.0, 700.0, 2.0, 0.0)).unwrap();
+        let again = fingerprint(&voice(110.0, 700.0, 2.0, 1.3)).unwrap();
+        let other = fingerprint(&voice(220.0, 2200.0, 2.0, 0.0)).unwrap();
+
+        assert_eq!(first.len(), DIMENSIONS);
+        assert!(similarity(&first, &again) > MATCH_THRESHOLD);
+        assert!(similarity(&first, &other) < similarity(&first, &again));
+        assert!(fingerprint(&vec![0.0; 32000]).is_none());
+        assert_eq!(from_blob(&to_blob(&first)), first);
+    }
+}
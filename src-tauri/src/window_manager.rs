@@ -176,4 +176,367 @@ pub async fn set_window_bounds(window: Window, x: i32, y: i32, width: u32, heigh
     window.set_size(size).map_err(|e| e.to_string())?;
     
     Ok(())
-} 
\ No newline at end of file
+} 
+// Purpose-built auxiliary windows spawned from the backend with presets defined here,
+// so the frontend only has to ask for a kind instead of configuring each window ad hoc.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub enum WindowAnchor {
+    Center,
+    TopCenter,
+    BottomCenter,
+    TopRight,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChildWindowProfile {
+    pub kind: String,
+    pub title: String,
+    pub width: f64,
+    pub height: f64,
+    pub anchor: WindowAnchor,
+    pub margin: f64,
+    pub decorations: bool,
+    pub transparent: bool,
+    pub alpha: f64,
+    pub always_on_top: bool,
+    pub skip_taskbar: bool,
+    pub resizable: bool,
+    pub focused: bool,
+}
+
+// Every kind is also a window label in capabilities/default.json, which grants permissions by label
+pub fn child_window_profiles() -> Vec<ChildWindowProfile> {
+    vec![
+        ChildWindowProfile {
+            kind: "caption-bar".to_string(),
+            title: "Enteract Captions".to_string(),
+            width: 720.0,
+            height: 96.0,
+            anchor: WindowAnchor::BottomCenter,
+            margin: 48.0,
+            decorations: false,
+            transparent: true,
+            alpha: 0.9,
+            always_on_top: true,
+            skip_taskbar: true,
+            resizable: true,
+            focused: false,
+        },
+        ChildWindowProfile {
+            kind: "approval-dialog".to_string(),
+            title: "Enteract Approval".to_string(),
+            width: 420.0,
+            height: 260.0,
+            anchor: WindowAnchor::Center,
+            margin: 0.0,
+            decorations: false,
+            transparent: false,
+            alpha: 1.0,
+            always_on_top: true,
+            skip_taskbar: false,
+            resizable: false,
+            focused: true,
+        },
+        ChildWindowProfile {
+            kind: "quick-ask".to_string(),
+            title: "Enteract Quick Ask".to_string(),
+            width: 560.0,
+            height: 72.0,
+            anchor: WindowAnchor::TopCenter,
+            margin: 120.0,
+            decorations: false,
+            transparent: true,
+            alpha: 0.95,
+            always_on_top: true,
+            skip_taskbar: true,
+            resizable: false,
+            focused: true,
+        },
+    ]
+}
+
+fn find_child_window_profile(kind: &str) -> Option<ChildWindowProfile> {
+    child_window_profiles().into_iter().find(|profile| profile.kind == kind)
+}
+
+// Resolve the logical top-left position of a profile on the primary monitor
+fn anchored_position(app_handle: &tauri::AppHandle, profile: &ChildWindowProfile) -> Option<(f64, f64)> {
+    let monitor = app_handle.primary_monitor().ok().flatten()?;
+    let scale = monitor.scale_factor();
+    let origin = monitor.position().to_logical::<f64>(scale);
+    let size = monitor.size().to_logical::<f64>(scale);
+
+    let (x, y) = match profile.anchor {
+        WindowAnchor::Center => (
+            (size.width - profile.width) / 2.0,
+            (size.height - profile.height) / 2.0,
+        ),
+        WindowAnchor::TopCenter => ((size.width - profile.width) / 2.0, profile.margin),
+        WindowAnchor::BottomCenter => (
+            (size.width - profile.width) / 2.0,
+            size.height - profile.height - profile.margin,
+        ),
+        WindowAnchor::TopRight => (size.width - profile.width - profile.margin, profile.margin),
+    };
+
+    Some((origin.x + x.max(0.0), origin.y + y.max(0.0)))
+}
+
+#[tauri::command]
+pub async fn list_child_window_profiles() -> Result<Vec<ChildWindowProfile>, String> {
+    Ok(child_window_profiles())
+}
+
+/// Open (or show) the window for a profile. The label is always the profile's kind, which is what
+/// the capability file grants permissions to, so a caller can't create an ungranted window.
+#[tauri::command]
+pub async fn spawn_child_window(
+    app_handle: tauri::AppHandle,
+    kind: String,
+) -> Result<String, String> {
+    use tauri::Manager;
+
+    let profile = find_child_window_profile(&kind)
+        .ok_or_else(|| format!("Unknown window kind: {}", kind))?;
+    let label = profile.kind.clone();
+
+    // Reuse the existing window of this kind rather than stacking duplicates
+    if let Some(existing) = app_handle.get_webview_window(&label) {
+        existing.show().map_err(|e| e.to_string())?;
+        if profile.focused {
+            existing.set_focus().map_err(|e| e.to_string())?;
+        }
+        return Ok(label);
+    }
+
+    let url = tauri::WebviewUrl::App(format!("index.html?window={}", profile.kind).into());
+    let mut builder = tauri::WebviewWindowBuilder::new(&app_handle, &label, url)
+        .title(&profile.title)
+        .inner_size(profile.width, profile.height)
+        .decorations(profile.decorations)
+        .always_on_top(profile.always_on_top)
+        .skip_taskbar(profile.skip_taskbar)
+        .resizable(profile.resizable)
+        .focused(profile.focused)
+        .shadow(false);
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        builder = builder.transparent(profile.transparent);
+    }
+
+    if let Some((x, y)) = anchored_position(&app_handle, &profile) {
+        builder = builder.position(x, y);
+    } else {
+        builder = builder.center();
+    }
+
+    let window = builder
+        .build()
+        .map_err(|e| format!("Failed to create {} window: {}", kind, e))?;
+
+    if profile.alpha < 1.0 {
+        crate::transparency::set_window_transparency(window.as_ref().window(), profile.alpha).await?;
+    }
+
+    println!("🪟 Spawned {} window", label);
+    Ok(label)
+}
+
+#[tauri::command]
+pub async fn close_child_window(app_handle: tauri::AppHandle, label: String) -> Result<(), String> {
+    use tauri::Manager;
+
+    if label == "main" {
+        return Err("The main window cannot be closed from here".to_string());
+    }
+
+    match app_handle.get_webview_window(&label) {
+        Some(window) => window.close().map_err(|e| e.to_string()),
+        None => Err(format!("No window with label: {}", label)),
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ForegroundWindowInfo {
+    pub title: String,
+    pub process_id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl From<crate::mcp::types::DesktopWindow> for ForegroundWindowInfo {
+    fn from(window: crate::mcp::types::DesktopWindow) -> Self {
+        ForegroundWindowInfo {
+            title: window.title,
+            process_id: window.process_id,
+            x: window.x,
+            y: window.y,
+            width: window.width,
+            height: window.height,
+        }
+    }
+}
+
+/// Ok when this session can report the focused window, otherwise why not. Windows and macOS
+/// always can; Linux needs the X11 tools or a compositor that reports it (see mcp::linux).
+pub fn foreground_window_backend() -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        return crate::mcp::linux::require_active_window_backend();
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    {
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Err("Reading the focused window isn't supported on this platform".to_string())
+    }
+}
+
+// Query the OS for the currently focused top-level window, using the same window enumeration as
+// the MCP window tools off Windows. None when nothing has focus or the session can't tell
+// (see foreground_window_backend).
+pub fn get_foreground_window_info() -> Option<ForegroundWindowInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::RECT;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetForegroundWindow, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId
+        };
+
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0 == 0 {
+                return None;
+            }
+
+            let mut rect = RECT::default();
+            GetWindowRect(hwnd, &mut rect).ok()?;
+
+            let mut title_buf = [0u16; 512];
+            let len = GetWindowTextW(hwnd, &mut title_buf);
+            let title = String::from_utf16_lossy(&title_buf[..len.max(0) as usize]);
+
+            let mut process_id = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+
+            return Some(ForegroundWindowInfo {
+                title,
+                process_id,
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left).max(0) as u32,
+                height: (rect.bottom - rect.top).max(0) as u32,
+            });
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        return crate::mcp::macos::frontmost_window().map(ForegroundWindowInfo::from);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        return crate::mcp::linux::active_window().ok().map(ForegroundWindowInfo::from);
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        None
+    }
+}
+
+#[tauri::command]
+pub async fn get_foreground_window() -> Result<Option<ForegroundWindowInfo>, String> {
+    Ok(get_foreground_window_info())
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
+pub struct ActiveApp {
+    pub app_name: String, // Executable name without extension, e.g. "Figma"
+    pub window_title: String,
+    pub process_id: u32,
+}
+
+lazy_static::lazy_static! {
+    static ref LAST_ACTIVE_APP: std::sync::Mutex<Option<ActiveApp>> = std::sync::Mutex::new(None);
+}
+
+pub(crate) fn process_name(process_id: u32) -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::PWSTR;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION
+        };
+
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+            let mut path_buf = [0u16; 1024];
+            let mut len = path_buf.len() as u32;
+            let queried = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(path_buf.as_mut_ptr()), &mut len);
+            let _ = CloseHandle(handle);
+            queried.ok()?;
+
+            let path = String::from_utf16_lossy(&path_buf[..len as usize]);
+            return std::path::Path::new(&path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        return crate::mcp::linux::process_name(process_id);
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut name_buf = [0u8; 256];
+        let len = unsafe { libc::proc_name(process_id as i32, name_buf.as_mut_ptr() as *mut libc::c_void, name_buf.len() as u32) };
+        return (len > 0).then(|| String::from_utf8_lossy(&name_buf[..len as usize]).into_owned());
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        let _ = process_id;
+        None
+    }
+}
+
+/// The application the user is working in. While one of our own windows has focus this is the last
+/// external application, so glancing at the overlay doesn't change what a transcript is attributed to.
+/// Blocking: the lookup can run external commands, so async code goes through `active_app`.
+pub fn current_active_app() -> Option<ActiveApp> {
+    // Look up first; the cache is only locked to swap the value
+    let current = get_foreground_window_info()
+        .filter(|info| info.process_id != std::process::id())
+        .map(|info| ActiveApp {
+            app_name: process_name(info.process_id).unwrap_or_else(|| info.title.clone()),
+            window_title: info.title,
+            process_id: info.process_id,
+        });
+    let mut last = LAST_ACTIVE_APP.lock().ok()?;
+    if current.is_some() {
+        *last = current;
+    }
+    last.clone()
+}
+
+/// `current_active_app` on a blocking thread, for async callers.
+pub async fn active_app() -> Option<ActiveApp> {
+    tokio::task::spawn_blocking(current_active_app).await.ok().flatten()
+}
+
+#[tauri::command]
+pub async fn get_active_app() -> Result<Option<ActiveApp>, String> {
+    Ok(active_app().await)
+}
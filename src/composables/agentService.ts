@@ -5,6 +5,7 @@ import { SessionManager } from './sessionManager'
 import { ContextManager } from './contextManager'
 import { enhancedRagService } from '../services/enhancedRagService'
 import { MCPService } from './mcpService'
+import type { GenerationOptions } from '../types'
 
 let messageIdCounter = 1
 
@@ -116,7 +117,7 @@ export class AgentService {
 
 
   // Send message function
-  static async sendMessage(userMessage: string, selectedModel: string | null, agentType: string = 'enteract', selectedDocumentIds: string[] = []) {
+  static async sendMessage(userMessage: string, selectedModel: string | null, agentType: string = 'enteract', selectedDocumentIds: string[] = [], options?: GenerationOptions) {
     // Check if this is an explicit @enteract MCP command
     const isExplicitMCP = userMessage.trim().toLowerCase().startsWith('@enteract')
     
@@ -209,6 +210,8 @@ export class AgentService {
       let isTyping = true
       let hasStarted = false
       let isInThinking = false
+      // Chunks that went into the prompt, recorded in the reply's provenance
+      let ragChunkIds: string[] = []
       
       // Set up streaming listener
       const unlisten = await listen(`ollama-stream-${sessionId}`, (event: any) => {
@@ -221,6 +224,16 @@ export class AgentService {
             console.log(`🤖 Started ${agentType} response with ${data.model}`)
             if (currentHistory[streamingMessageIndex]) {
               currentHistory[streamingMessageIndex].text = `🤖 ${agentName} (${data.model})▋`
+              if (data.provenance) {
+                currentHistory[streamingMessageIndex].metadata = {
+                  ...currentHistory[streamingMessageIndex].metadata,
+                  model: data.model,
+                  modelTag: data.provenance.modelTag,
+                  provider: data.provenance.provider,
+                  generationOptions: data.provenance.generationOptions ?? undefined,
+                  ragChunkIds: ragChunkIds.length > 0 ? ragChunkIds : undefined
+                }
+              }
             }
             setTimeout(() => {
               AgentService.scrollChatToBottom()
@@ -331,7 +344,7 @@ export class AgentService {
                 errorMessage = `❌ Qwen2.5-Coder model not found. Please install it first:\n\n\`\`\`bash\nollama pull qwen2.5-coder:1.5b\n\`\`\``
               } else if (data.error.includes('qwen2.5vl:3b') && agentType === 'vision') {
                 errorMessage = `❌ Qwen2.5-VL model not found. Please install it first:\n\n\`\`\`bash\nollama pull qwen2.5vl:3b\n\`\`\``
-              } else if (data.error.includes('connection refused') || data.error.includes('ECONNREFUSED')) {
+              } else if (data.error.includes('connection refused') || data.error.includes('ECONNREFUSED') || data.error.includes('Ollama is not running')) {
                 errorMessage = `❌ Cannot connect to Ollama. Please make sure Ollama is running:\n\n\`\`\`bash\nollama serve\n\`\`\``
               }
               currentHistory[streamingMessageIndex].text = errorMessage
@@ -355,6 +368,14 @@ export class AgentService {
             console.log(`🎉 ${agentType} streaming session completed`)
             if (currentHistory[streamingMessageIndex]) {
               currentHistory[streamingMessageIndex].isStreaming = false
+              if (data.usage) {
+                currentHistory[streamingMessageIndex].metadata = {
+                  ...currentHistory[streamingMessageIndex].metadata,
+                  tokens: data.usage.totalTokens,
+                  promptTokens: data.usage.promptTokens,
+                  completionTokens: data.usage.completionTokens
+                }
+              }
             }
             // Clean up
             AgentService.activeSessionIds.delete(streamingMessageId)
@@ -391,11 +412,18 @@ export class AgentService {
           
           if (readyDocs.length === 0 && pendingDocs.length > 0) {
             console.log('⏳ All selected documents are still processing embeddings, proceeding without RAG context')
+          } else if (selectedDocumentIds.length > 1) {
+            // Several pinned documents: pack them into one budget instead of concatenating search hits
+            const packing = await enhancedRagService.packDocumentContext(userMessage, selectedDocumentIds)
+            ragContext = packing.context
+            ragChunkIds = packing.chunks.map(chunk => chunk.chunk_id)
+            console.log(`📚 Packed ${packing.chunks.length} chunks from ${packing.documents.filter(d => d.chunks_included > 0).length} documents, ~${packing.tokens_used}/${packing.budget_tokens} tokens`)
           } else {
             const ragResults = await enhancedRagService.searchDocuments(userMessage, selectedDocumentIds)
             
             if (ragResults.length > 0) {
               ragContext = enhancedRagService.formatContextForAI(ragResults)
+              ragChunkIds = ragResults.map(chunk => chunk.id)
               console.log(`📚 RAG context retrieved: ${ragResults.length} chunks, ${ragContext.length} characters`)
             } else {
               console.log('📚 No relevant content found in selected documents')
@@ -429,7 +457,8 @@ export class AgentService {
           await invoke('generate_coding_agent_response', {
             prompt: enhancedPrompt,
             context: truncatedContext,
-            sessionId: sessionId
+            sessionId: sessionId,
+            options
           })
           break
           
@@ -438,7 +467,8 @@ export class AgentService {
           await invoke('generate_deep_research', {
             prompt: enhancedPrompt,
             context: truncatedContext,
-            sessionId: sessionId
+            sessionId: sessionId,
+            options
           })
           break
           
@@ -448,7 +478,8 @@ export class AgentService {
           await invoke('generate_vision_analysis', {
             prompt: enhancedPrompt,
             imageBase64: '', // Empty for text-only requests
-            sessionId: sessionId
+            sessionId: sessionId,
+            options
           })
           break
           
@@ -458,7 +489,8 @@ export class AgentService {
           await invoke('generate_enteract_agent_response', {
             prompt: enhancedPrompt,
             context: truncatedContext,
-            sessionId: sessionId
+            sessionId: sessionId,
+            options
           })
           break
       }
@@ -474,7 +506,7 @@ export class AgentService {
       
       // Enhanced error messages
       let errorMessage = `❌ Failed to get AI response: ${errorString}. Make sure Ollama is running and the model "${selectedModel || 'gemma3:1b-it-qat'}" is available.`
-      if (errorString.includes('connection refused') || errorString.includes('ECONNREFUSED')) {
+      if (errorString.includes('connection refused') || errorString.includes('ECONNREFUSED') || errorString.includes('Ollama is not running')) {
         errorMessage = `❌ Cannot connect to Ollama. Please make sure Ollama is running:\n\n\`\`\`bash\nollama serve\n\`\`\``
       } else if (errorString.includes('model') && errorString.includes('not found')) {
         errorMessage = `❌ Model not available. Install with:\n\n\`\`\`bash\nollama pull ${selectedModel || 'gemma3:1b-it-qat'}\n\`\`\``
@@ -501,7 +533,7 @@ export class AgentService {
     const sessionId = AgentService.activeSessionIds.get(messageId)
     if (sessionId) {
       try {
-        await invoke('cancel_ai_response', { sessionId })
+        await invoke('cancel_ollama_stream', { sessionId })
         console.log(`🛑 Cancellation requested for message ${messageId}, session ${sessionId}`)
       } catch (error) {
         console.error('Failed to cancel AI response:', error)
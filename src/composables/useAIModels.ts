@@ -21,6 +21,13 @@ interface OllamaStatus {
   version?: string
 }
 
+export interface OllamaConnectionTest {
+  url: string
+  version: string | null
+  modelCount: number
+  latencyMs: number
+}
+
 export const useAIModels = () => {
   const cache = useOllamaCache()
   const cachedData = cache.getCache()
@@ -139,6 +146,19 @@ export const useAIModels = () => {
     }
   }
 
+  // Point the app at Ollama on another machine or in Docker; the server is checked before saving
+  const setOllamaEndpoint = async (baseUrl: string | null, bearerToken: string | null) => {
+    const test = baseUrl
+      ? await invoke<OllamaConnectionTest>('test_ollama_connection', { baseUrl, bearerToken })
+      : null
+    const settings = await invoke<Record<string, unknown>>('get_ollama_http_settings')
+    await invoke('save_ollama_http_settings', { settings: { ...settings, base_url: baseUrl, bearer_token: bearerToken } })
+    cache.clearCache()
+    await fetchOllamaStatus(true)
+    await fetchOllamaModels(true)
+    return test
+  }
+
   const formatModelSize = (size: number): string => {
     const gb = size / (1024 * 1024 * 1024)
     return `${gb.toFixed(1)} GB`
@@ -160,6 +180,7 @@ export const useAIModels = () => {
     fetchOllamaModels,
     pullModel,
     deleteModel,
+    setOllamaEndpoint,
     formatModelSize,
     getModelDisplayName
   }
@@ -0,0 +1,51 @@
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+interface Announcement {
+  source: string
+  kind: 'response' | 'caption' | 'status'
+  text: string
+  politeness: 'polite' | 'assertive'
+}
+
+let unlisten: UnlistenFn | null = null
+
+// One visually hidden live region per politeness level, created on first use
+const liveRegion = (politeness: Announcement['politeness']): HTMLElement => {
+  const id = `enteract-announcer-${politeness}`
+  let region = document.getElementById(id)
+  if (!region) {
+    region = document.createElement('div')
+    region.id = id
+    region.setAttribute('aria-live', politeness)
+    region.setAttribute('aria-atomic', 'true')
+    region.setAttribute('role', politeness === 'assertive' ? 'alert' : 'status')
+    region.style.cssText = 'position:absolute;width:1px;height:1px;margin:-1px;padding:0;overflow:hidden;clip:rect(0,0,0,0);border:0;'
+    document.body.appendChild(region)
+  }
+  return region
+}
+
+/**
+ * Speaks the backend's accessibility announcements (sentence-batched responses and finished
+ * captions) through aria-live regions. The backend only sends them when accessibility output is on.
+ */
+export function useAccessibilityAnnouncer() {
+  const start = async () => {
+    if (unlisten) return
+    unlisten = await listen<Announcement>('accessibility-announcement', (event) => {
+      const region = liveRegion(event.payload.politeness)
+      // Clearing first makes screen readers repeat identical consecutive announcements
+      region.textContent = ''
+      setTimeout(() => {
+        region.textContent = event.payload.text
+      }, 50)
+    })
+  }
+
+  const stop = () => {
+    unlisten?.()
+    unlisten = null
+  }
+
+  return { start, stop }
+}
@@ -3,6 +3,7 @@ import { ref, computed, onUnmounted } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 import { listen, UnlistenFn } from '@tauri-apps/api/event'
 import { transcribeAudioBase64 } from '../services/whisperService'
+import { useConversationStore } from '../stores/conversation'
 
 // Types matching the Rust backend
 export interface AudioLoopbackDevice {
@@ -188,6 +189,12 @@ export function useAudioLoopback() {
   const startCapture = async () => {
     if (!selectedDevice.value || isCapturing.value) return
     
+    // Capture records for the open conversation, under that conversation's consent
+    const sessionId = useConversationStore().currentSession?.id
+    if (!sessionId) {
+      captureError.value = 'Start a conversation before capturing audio'
+      return
+    }
     captureError.value = null
     
     try {
@@ -214,7 +221,8 @@ export function useAudioLoopback() {
       
       // Start capture on backend
       await invoke('start_audio_loopback_capture', {
-        deviceId: selectedDevice.value.id
+        deviceId: selectedDevice.value.id,
+        sessionId
       })
       
       isCapturing.value = true
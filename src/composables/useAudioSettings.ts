@@ -2,6 +2,7 @@
 import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
+import { useConversationStore } from '../stores/conversation'
 
 // Types matching the Rust implementation
 export interface AudioLoopbackDevice {
@@ -168,6 +169,12 @@ export const useAudioSettings = () => {
       throw new Error('Audio loopback is disabled')
     }
 
+    // Capture records for the open conversation, under that conversation's consent
+    const sessionId = useConversationStore().currentSession?.id
+    if (!sessionId) {
+      throw new Error('Start a conversation before capturing audio')
+    }
+
     try {
       console.log('🎤 Starting audio loopback capture...')
       
@@ -176,7 +183,8 @@ export const useAudioSettings = () => {
       
       // Start capture
       await invoke('start_audio_loopback_capture', {
-        deviceId: audioSettings.value.selectedLoopbackDevice
+        deviceId: audioSettings.value.selectedLoopbackDevice,
+        sessionId
       })
       
       isCapturing.value = true
@@ -1,5 +1,6 @@
-import { ref, onMounted } from 'vue'
+import { ref, onMounted, onUnmounted } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 
 export interface GPUInfo {
   name: string
@@ -8,16 +9,70 @@ export interface GPUInfo {
   driver_version?: string
 }
 
+export interface PromptBenchmark {
+  promptId: string
+  timeToFirstTokenMs: number
+  tokensPerSecond: number
+  promptTokensPerSecond: number | null
+  outputTokens: number
+  totalMs: number
+}
+
+export interface ModelBenchmark {
+  id: number | null
+  model: string
+  gpu: string | null
+  loadMs: number
+  tokensPerSecond: number
+  timeToFirstTokenMs: number
+  memoryBytes: number | null
+  vramBytes: number | null
+  prompts: PromptBenchmark[]
+  createdAt: number
+}
+
+// Present once at least one model has been benchmarked on this machine
+export interface MeasuredRecommendation {
+  model: string
+  tokensPerSecond: number
+  timeToFirstTokenMs: number
+  fullyOnGpu: boolean
+  reason: string
+}
+
+// A model Ollama has in memory, from /api/ps
+export interface LoadedModel {
+  name: string
+  sizeBytes: number
+  vramBytes: number
+  ramBytes: number
+  gpuPercent: number
+  expiresAt: string | null
+  parameterSize: string | null
+  quantization: string | null
+  contextLength: number | null
+}
+
+export interface LoadedModelsStatus {
+  reachable: boolean
+  models: LoadedModel[]
+  checkedAt: string
+}
+
 export interface GPUAccelerationStatus {
   enabled: boolean
   layers: number
   gpus: GPUInfo[]
+  benchmarks: ModelBenchmark[]
+  recommendation: MeasuredRecommendation | null
 }
 
 export function useGPUStatus() {
   const gpuStatus = ref<GPUAccelerationStatus | null>(null)
   const isLoading = ref(false)
   const error = ref<string | null>(null)
+  const loadedModels = ref<LoadedModelsStatus | null>(null)
+  let unlistenLoadedModels: UnlistenFn | null = null
 
   const checkGPUStatus = async () => {
     try {
@@ -59,20 +114,37 @@ export function useGPUStatus() {
     return 'CPU Mode (No GPU acceleration)'
   }
 
+  // Measure a model on this machine; the status then recommends from the measured runs
+  const benchmarkModel = async (model: string): Promise<ModelBenchmark> => {
+    const benchmark = await invoke<ModelBenchmark>('benchmark_ollama_model', { model })
+    await checkGPUStatus()
+    return benchmark
+  }
+
   const getStatusColor = () => {
     if (!gpuStatus.value) return 'text-yellow-500'
     return gpuStatus.value.enabled ? 'text-green-500' : 'text-orange-500'
   }
 
-  onMounted(() => {
+  onMounted(async () => {
     checkGPUStatus()
+    loadedModels.value = await invoke<LoadedModelsStatus>('get_ollama_loaded_models')
+    unlistenLoadedModels = await listen<LoadedModelsStatus>('ollama-loaded-models', (event) => {
+      loadedModels.value = event.payload
+    })
+  })
+
+  onUnmounted(() => {
+    unlistenLoadedModels?.()
   })
 
   return {
     gpuStatus,
     isLoading,
     error,
+    loadedModels,
     checkGPUStatus,
+    benchmarkModel,
     getStatusMessage,
     getStatusColor
   }
@@ -17,6 +17,16 @@ export interface LiveAIResponse {
   sessionId: string
 }
 
+export interface InsightBudgetUsage {
+  session: string
+  generationsLastHour: number
+  maxGenerationsPerHour: number
+  tokensToday: number
+  maxTokensPerDay: number
+  boostedUntil: number | null
+  blocked: string | null
+}
+
 export interface InsightItem {
   id: string
   text: string
@@ -34,11 +44,13 @@ export function useLiveAI() {
   const isProcessing = ref(false)
   const error = ref<string | null>(null)
   const isAnalyzing = ref(false)
+  const budget = ref<InsightBudgetUsage | null>(null)
   
   // Get insights from the current conversation session
   const insights = computed(() => conversationStore.currentSession?.insights || [])
   
   let streamListener: any = null
+  let budgetListener: any = null
   let analysisTimeout: number | null = null
   let lastAnalysisTime = 0
 
@@ -54,6 +66,11 @@ export function useLiveAI() {
       const newSessionId = `live-ai-${Date.now()}`
       sessionId.value = newSessionId
       
+      // Insight budget usage arrives after every check and generation
+      budgetListener = await listen<InsightBudgetUsage>('insight-budget', (event) => {
+        budget.value = event.payload
+      })
+      
       // Set up streaming listener for live AI responses
       streamListener = await listen(`ollama-stream-${newSessionId}`, async (event: any) => {
         const data = event.payload
@@ -126,6 +143,10 @@ export function useLiveAI() {
         streamListener()
         streamListener = null
       }
+      if (budgetListener) {
+        budgetListener()
+        budgetListener = null
+      }
       
       isActive.value = false
       response.value = ''
@@ -143,6 +164,18 @@ export function useLiveAI() {
     }
   }
 
+  // Lift the insight limits for a few minutes
+  const boostInsights = async (): Promise<void> => {
+    try {
+      budget.value = await invoke<InsightBudgetUsage>('boost_insights', {
+        conversationId: conversationStore.currentSession?.id ?? null
+      })
+      error.value = null
+    } catch (err) {
+      error.value = err instanceof Error ? err.message : 'Failed to boost insights'
+    }
+  }
+
   const analyzeConversationContext = async (messages: any[]): Promise<void> => {
     if (!isActive.value || !sessionId.value) return
     
@@ -161,7 +194,7 @@ export function useLiveAI() {
       
       // Simple conversation context
       const conversationContext = messages
-        .filter(msg => !msg.isPreview)
+        .filter(msg => !msg.isPreview && !msg.redacted)
         .slice(-contextSize)
         .map(msg => {
           const speaker = msg.source === 'loopback' ? 'System' : 'User'
@@ -178,7 +211,8 @@ export function useLiveAI() {
         // Generate AI insights
         await invoke('generate_conversational_ai', {
           conversationContext,
-          sessionId: sessionId.value
+          sessionId: sessionId.value,
+          conversationId: conversationStore.currentSession?.id ?? null
         })
       }
     } catch (err) {
@@ -248,6 +282,10 @@ export function useLiveAI() {
       streamListener()
       streamListener = null
     }
+    if (budgetListener) {
+      budgetListener()
+      budgetListener = null
+    }
     if (analysisTimeout) {
       clearTimeout(analysisTimeout)
       analysisTimeout = null
@@ -268,6 +306,8 @@ export function useLiveAI() {
     insights,
     isProcessing,
     error,
+    budget,
+    boostInsights,
     startLiveAI,
     stopLiveAI,
     analyzeConversationContext,
@@ -1,6 +1,8 @@
 import { ref, Ref, onUnmounted } from 'vue'
 import { listen } from '@tauri-apps/api/event'
-import { useConversationStore } from '../stores/conversation'
+import { useConversationStore, type AudioSpan } from '../stores/conversation'
+
+const AUDIO_BYTES_PER_MS = 32 // 16 kHz mono PCM16
 
 export function useLoopbackTranscription() {
   const conversationStore = useConversationStore()
@@ -10,6 +12,7 @@ export function useLoopbackTranscription() {
   const loopbackLastTimestamp = ref<number>(0)
   const loopbackThoughtTimer = ref<number | null>(null)
   const loopbackBufferStartTime = ref<number>(0)
+  const loopbackAudio = ref<AudioSpan | null>(null) // Kept audio behind the buffered text
   const THOUGHT_PAUSE_DURATION = 2500  // Shorter pause for natural breaks (2.5s)
   const MAX_BUFFER_DURATION = 10000    // Max 10s speaking length as requested
   const MAX_CONCATENATION_TIME = 3000  // Only concatenate within 3s of last message
@@ -77,6 +80,15 @@ export function useLoopbackTranscription() {
     return existingTrimmed + ' ' + newTextTrimmed
   }
   
+  // One span covering both, so a message built from several chunks plays back as one clip
+  const mergeAudioSpans = (a: AudioSpan | null | undefined, b: AudioSpan | null | undefined): AudioSpan | null => {
+    if (!a) return b || null
+    if (!b) return a
+    const start = Math.min(a.offset, b.offset)
+    const end = Math.max(a.offset + a.durationMs * AUDIO_BYTES_PER_MS, b.offset + b.durationMs * AUDIO_BYTES_PER_MS)
+    return { offset: start, durationMs: Math.round((end - start) / AUDIO_BYTES_PER_MS) }
+  }
+  
   const findLongestCommonSubstring = (str1: string, str2: string): string | null => {
     if (!str1 || !str2) return null
     
@@ -135,12 +147,18 @@ export function useLoopbackTranscription() {
         if (timeDiff < MAX_CONCATENATION_TIME) {
           // Concatenate with existing message
           const concatenatedContent = intelligentConcatenation(lastMessage.content, finalContent)
+          const lastAudio = lastMessage.audioOffset !== undefined && lastMessage.audioDurationMs !== undefined
+            ? { offset: lastMessage.audioOffset, durationMs: lastMessage.audioDurationMs }
+            : null
+          const audio = mergeAudioSpans(lastAudio, loopbackAudio.value)
           
           // Update the existing message instead of creating a new one
           conversationStore.updateMessage(lastMessage.id, {
             content: concatenatedContent,
             timestamp: Date.now(), // Update timestamp
-            confidence: Math.min(0.95, (lastMessage.confidence || 0.8) + 0.05) // Slightly increase confidence
+            confidence: Math.min(0.95, (lastMessage.confidence || 0.8) + 0.05), // Slightly increase confidence
+            audioOffset: audio?.offset,
+            audioDurationMs: audio?.durationMs
           })
           
           console.log('🔗 Concatenated with existing message:', concatenatedContent.substring(0, 50))
@@ -165,6 +183,7 @@ export function useLoopbackTranscription() {
         }
       }
       
+      const audio = loopbackAudio.value
       clearBufferState()
       
       recentMessages.value.add(messageFingerprint)
@@ -185,7 +204,9 @@ export function useLoopbackTranscription() {
           source: 'loopback',
           content: finalContent,
           confidence: finalConfidence,
-          timestamp: Date.now()
+          timestamp: Date.now(),
+          audioOffset: audio?.offset,
+          audioDurationMs: audio?.durationMs
         })
         console.log('📝 Created new loopback message:', finalContent.substring(0, 50))
       } else {
@@ -195,6 +216,7 @@ export function useLoopbackTranscription() {
     
     function clearBufferState() {
       loopbackBuffer.value = ''
+      loopbackAudio.value = null
       loopbackPreviewMessage.value = ''
       isLoopbackTyping.value = false
       currentPreviewMessageId.value = null
@@ -220,7 +242,7 @@ export function useLoopbackTranscription() {
   
   const handleConversationalUserSpeech = (event: Event) => {
     const customEvent = event as CustomEvent
-    const { text, confidence, timestamp } = customEvent.detail
+    const { text, confidence, timestamp, audio } = customEvent.detail
     
     if (text && text.trim()) {
       isMicrophoneTyping.value = false
@@ -234,7 +256,9 @@ export function useLoopbackTranscription() {
           source: 'microphone',
           content: text.trim(),
           confidence,
-          timestamp: timestamp || Date.now()
+          timestamp: timestamp || Date.now(),
+          audioOffset: audio?.offset,
+          audioDurationMs: audio?.durationMs
         })
       } else {
         console.log('🚫 Skipping microphone message - no active session:', text.substring(0, 50))
@@ -243,7 +267,7 @@ export function useLoopbackTranscription() {
   }
   
   const handleLoopbackTranscription = (payload: any) => {
-    const { text, timestamp } = payload
+    const { text, timestamp, audio } = payload
     
     if (text && text.trim()) {
       const currentTime = timestamp || Date.now()
@@ -278,6 +302,7 @@ export function useLoopbackTranscription() {
         currentPreviewMessageId.value = `loopback-preview-${currentTime}`
         sentenceBuffer.value = []
       }
+      loopbackAudio.value = mergeAudioSpans(loopbackAudio.value, audio)
       
       const newBufferContent = intelligentConcatenation(loopbackBuffer.value, cleanedText)
       
@@ -1,5 +1,6 @@
 import { ref, watch } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
 import { ConversationMessage, ConversationSession } from '../stores/conversation'
 
 interface MessageSaveRequest {
@@ -110,7 +111,9 @@ export function useMessagePersistence() {
           persistenceState: message.persistenceState,
           retryCount: message.retryCount || 0,
           lastSaveAttempt: message.lastSaveAttempt,
-          saveError: message.saveError
+          saveError: message.saveError,
+          audioOffset: message.audioOffset,
+          audioDurationMs: message.audioDurationMs
         }
       })
       
@@ -463,7 +466,9 @@ export function useMessagePersistence() {
         updates: {
           content: updates.content,
           confidence: updates.confidence,
-          timestamp: updates.timestamp
+          timestamp: updates.timestamp,
+          audioOffset: updates.audioOffset,
+          audioDurationMs: updates.audioDurationMs
         }
       })
       return true
@@ -490,6 +495,38 @@ export function useMessagePersistence() {
     }
   }
 
+  // Save everything still queued, including failed messages, without waiting out retry delays.
+  // The backend holds app exit until this is done or its timeout passes.
+  const flushAll = async (timeoutMs: number) => {
+    const deadline = Date.now() + timeoutMs
+    if (saveTimer) {
+      clearTimeout(saveTimer)
+      saveTimer = null
+    }
+    pendingQueue.value.push(...failedQueue.value.splice(0))
+    
+    while ((pendingQueue.value.length > 0 || concurrentSaves.value > 0) && Date.now() < deadline) {
+      await processPendingQueue()
+      await new Promise(resolve => setTimeout(resolve, 20))
+    }
+    
+    if (pendingQueue.value.length > 0) {
+      console.warn(`⚠️ ${pendingQueue.value.length} messages were not saved before shutdown`)
+    }
+  }
+
+  let unlistenShutdown: UnlistenFn | null = null
+  listen<{ timeoutMs: number }>('app-shutdown-flush', async (event) => {
+    try {
+      // Leave some of the backend's time budget for the acknowledgement itself
+      await flushAll(Math.max(event.payload.timeoutMs - 500, 0))
+    } finally {
+      await invoke('complete_shutdown_flush').catch(() => {})
+    }
+  }).then(unlisten => {
+    unlistenShutdown = unlisten
+  })
+
   // Get queue status
   const getQueueStatus = () => ({
     pendingCount: pendingQueue.value.length,
@@ -522,6 +559,8 @@ export function useMessagePersistence() {
     }
     window.removeEventListener('online', () => {})
     window.removeEventListener('offline', () => {})
+    unlistenShutdown?.()
+    unlistenShutdown = null
   }
 
   // Start monitoring
@@ -534,6 +573,7 @@ export function useMessagePersistence() {
     deleteMessage,
     processPendingQueue,
     processFailedQueue,
+    flushAll,
     getQueueStatus,
     clearQueues,
     cleanup,
@@ -0,0 +1,71 @@
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export type PluginCapability = 'network' | 'storage' | 'native'
+
+export interface PluginInfo {
+  id: string
+  name: string
+  version: string
+  description: string
+  runtime: 'wasm' | 'script' | null
+  capabilities: PluginCapability[]
+  tools: string[]
+  parsers: string[]
+  insightGenerators: string[]
+  approved: boolean
+  error: string | null
+}
+
+export interface PluginInsight {
+  pluginId: string
+  generator: string
+  text: string
+  insightType: string
+}
+
+export interface PluginAuditEntry {
+  timestamp: string
+  pluginId: string
+  kind: 'mcp_tool' | 'rag_parser' | 'insight_generator'
+  name: string
+  success: boolean
+  durationMs: number
+  error: string | null
+}
+
+const plugins = ref<PluginInfo[]>([])
+let unlisten: UnlistenFn | null = null
+
+/**
+ * Plugins installed in the config directory's `plugins/` folder. A plugin does nothing until its
+ * declared capabilities are approved; changing its manifest or entry point withdraws the approval.
+ */
+export function usePlugins() {
+  const start = async () => {
+    if (unlisten) return
+    plugins.value = await invoke<PluginInfo[]>('list_plugins')
+    unlisten = await listen<PluginInfo[]>('plugins-changed', (event) => {
+      plugins.value = event.payload
+    })
+  }
+
+  const stop = () => {
+    unlisten?.()
+    unlisten = null
+  }
+
+  const reload = () => invoke<PluginInfo[]>('reload_plugins')
+
+  const approve = (pluginId: string) => invoke<PluginInfo[]>('approve_plugin', { pluginId })
+
+  const revoke = (pluginId: string) => invoke<PluginInfo[]>('revoke_plugin', { pluginId })
+
+  const generateInsights = (conversationContext: string, conversationId?: string) =>
+    invoke<PluginInsight[]>('generate_plugin_insights', { conversationId, conversationContext })
+
+  const getAudit = (limit?: number) => invoke<PluginAuditEntry[]>('get_plugin_audit', { limit })
+
+  return { plugins, start, stop, reload, approve, revoke, generateInsights, getAudit }
+}
@@ -0,0 +1,58 @@
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+
+export type ConsentBasis = 'explicit' | 'jurisdiction' | 'not_required'
+
+export interface ConsentStatus {
+  sessionId: string
+  required: boolean
+  granted: boolean
+  basis: ConsentBasis | null
+}
+
+export interface RecordingIndicator {
+  recording: boolean
+  sources: string[]
+  sessions: Record<string, string>
+  sessionId: string | null
+  basis: ConsentBasis | null
+  since: number | null
+}
+
+const indicator = ref<RecordingIndicator>({ recording: false, sources: [], sessions: {}, sessionId: null, basis: null, since: null })
+let unlisten: UnlistenFn | null = null
+
+/**
+ * Recording consent for conversations. The backend refuses to record until consent is confirmed
+ * for the session (or the consent policy allows it), and reports what is recording on the
+ * `recording-indicator` event, shared here by every window that shows the indicator.
+ */
+export function useRecordingConsent() {
+  const start = async () => {
+    if (unlisten) return
+    indicator.value = await invoke<RecordingIndicator>('get_recording_indicator')
+    unlisten = await listen<RecordingIndicator>('recording-indicator', (event) => {
+      indicator.value = event.payload
+    })
+  }
+
+  const stop = () => {
+    unlisten?.()
+    unlisten = null
+  }
+
+  const getConsent = (sessionId: string) =>
+    invoke<ConsentStatus>('get_recording_consent', { sessionId })
+
+  const grantConsent = (sessionId: string, method = 'banner') =>
+    invoke<ConsentStatus>('grant_recording_consent', { sessionId, method })
+
+  const revokeConsent = (sessionId: string) =>
+    invoke<ConsentStatus>('revoke_recording_consent', { sessionId })
+
+  const setMicrophoneRecording = (sessionId: string, active: boolean) =>
+    invoke<RecordingIndicator>('set_microphone_recording', { sessionId, active })
+
+  return { indicator, start, stop, getConsent, grantConsent, revokeConsent, setMicrophoneRecording }
+}
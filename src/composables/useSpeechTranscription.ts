@@ -35,6 +35,7 @@ export function useSpeechTranscription() {
   const error = ref<string | null>(null)
   const autoSendToChat = ref(true) // Control whether to auto-send to main chat
   const continuousMode = ref(false) // Keep mic open during conversations
+  const recordingSessionId = ref<string | null>(null) // Conversation the mic records for, if any
 
   // Audio recording
   let mediaRecorder: MediaRecorder | null = null
@@ -221,6 +222,9 @@ export function useSpeechTranscription() {
         }
         
         transcriptionHistory.value.push(transcriptionResult)
+        if (!hasWhisperModel.value) {
+          submitDictationSegment(final)
+        }
         
         // Emit final transcription event
         emitTranscriptionEvent('transcription-final', {
@@ -296,6 +300,14 @@ export function useSpeechTranscription() {
     }
   }
 
+  // Dictation types final mic segments into the focused app; the backend ignores them while
+  // dictation is off. With a Whisper model its result is submitted instead of the Web Speech
+  // finals, so nothing is typed twice.
+  function submitDictationSegment(text: string) {
+    invoke<boolean>('dictation_submit_segment', { text })
+      .catch(err => console.warn('Dictation failed:', err))
+  }
+
   // Event emitter for transcription updates
   function emitTranscriptionEvent(eventType: string, data?: any) {
     const event = new CustomEvent(eventType, { 
@@ -621,8 +633,11 @@ export function useSpeechTranscription() {
           start_time: number
           end_time: number
           language?: string
+          confidence_unknown?: boolean // The STT provider gave no score; confidence is a placeholder
+          audio?: { offset: number; durationMs: number } // Set when conversation audio is being kept
         }>('transcribe_audio_base64', {
           audioData: audioBase64,
+          sessionId: recordingSessionId.value ?? undefined,
           config: {
             modelSize: defaultWhisperConfig.modelSize,
             language: defaultWhisperConfig.language,
@@ -664,12 +679,14 @@ export function useSpeechTranscription() {
         }
 
         transcriptionHistory.value.push(transcriptionResult)
+        submitDictationSegment(newText)
 
         // Emit final transcription event
         emitTranscriptionEvent('transcription-final', {
           text: newText,
-          confidence: result.confidence,
-          timestamp: Date.now()
+          confidence: result.confidence_unknown ? undefined : result.confidence,
+          timestamp: Date.now(),
+          audio: result.audio
         })
 
         console.log('✅ Whisper transcription (small model):', newText)
@@ -801,6 +818,11 @@ export function useSpeechTranscription() {
     console.log(`🎤 Continuous mode ${enabled ? 'enabled' : 'disabled'}`)
   }
 
+  // The conversation recorded audio belongs to; the backend refuses it without that session's consent
+  function setRecordingSession(sessionId: string | null) {
+    recordingSessionId.value = sessionId
+  }
+
   // Reinitialize with new model settings
   async function reinitializeWithSettings() {
     try {
@@ -889,6 +911,7 @@ export function useSpeechTranscription() {
     startTranscription,
     setAutoSendToChat,
     setContinuousMode,
+    setRecordingSession,
     reinitializeWithSettings
   }
 } 
\ No newline at end of file
@@ -151,7 +151,7 @@ export class VisionService {
       // More detailed error messages
       const errorString = error instanceof Error ? error.message : String(error)
       let errorMessage = `❌ Failed to analyze screen: ${errorString}`
-      if (errorString.includes('connection refused') || errorString.includes('ECONNREFUSED')) {
+      if (errorString.includes('connection refused') || errorString.includes('ECONNREFUSED') || errorString.includes('Ollama is not running')) {
         errorMessage = `❌ Cannot connect to Ollama. Please make sure Ollama is running:\n\n\`\`\`bash\nollama serve\n\`\`\``
       } else if (errorString.includes('model') && errorString.includes('not found')) {
         errorMessage = `❌ Vision model not available. Install with:\n\n\`\`\`bash\nollama pull qwen2.5vl:3b\n\`\`\``
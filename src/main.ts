@@ -1,9 +1,20 @@
-import { createApp } from "vue";
+import { createApp, type Component } from "vue";
 import { createPinia } from "pinia";
 import App from "./App.vue";
+import CaptionBarWindow from "./components/windows/CaptionBarWindow.vue";
+import ApprovalDialogWindow from "./components/windows/ApprovalDialogWindow.vue";
+import QuickAskWindow from "./components/windows/QuickAskWindow.vue";
 import "./style.css";
 
-const app = createApp(App);
+// Child windows spawned by spawn_child_window load index.html?window=<kind>
+const childWindows: Record<string, Component> = {
+  "caption-bar": CaptionBarWindow,
+  "approval-dialog": ApprovalDialogWindow,
+  "quick-ask": QuickAskWindow,
+};
+
+const kind = new URLSearchParams(window.location.search).get("window");
+const app = createApp((kind && childWindows[kind]) || App);
 const pinia = createPinia();
 
 app.use(pinia);
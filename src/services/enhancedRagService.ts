@@ -18,6 +18,87 @@ export interface EnhancedDocument {
   metadata: string | null
 }
 
+export interface StaleDocument {
+  document_id: string
+  file_name: string
+  source_path: string
+  reason: 'modified' | 'missing'
+  detected_at: string
+}
+
+export interface UnavailableDocument {
+  document_id: string
+  file_name: string
+  source_path: string
+  volume_root: string
+  reason: string
+  since: string
+}
+
+export interface PackingConstraints {
+  budget_tokens: number
+  min_chunks_per_document: number
+  max_chunks_per_document: number
+  diversity: number
+}
+
+export interface ContextPacking {
+  context: string
+  chunks: {
+    chunk_id: string
+    document_id: string
+    chunk_index: number
+    content: string
+    tokens: number
+    relevance: number
+  }[]
+  documents: {
+    document_id: string
+    file_name: string
+    chunks_included: number
+    tokens_used: number
+    best_relevance: number
+  }[]
+  decisions: {
+    chunk_id: string
+    document_id: string
+    chunk_index: number
+    tokens: number
+    relevance: number
+    included: boolean
+    reason: 'document_minimum' | 'marginal_relevance' | 'over_budget' | 'document_maximum' | 'redundant'
+  }[]
+  tokens_used: number
+  budget_tokens: number
+}
+
+export interface FileCitation {
+  index: number
+  chunk_index: number
+  start_char: number
+  end_char: number
+  excerpt: string
+  relevance: number
+}
+
+export interface FileAnswer {
+  file_name: string
+  answer: string
+  citations: FileCitation[]
+  cited: number[]
+}
+
+export interface DocumentTags {
+  tags: string[]
+  source: 'auto' | 'manual'
+}
+
+export interface StaleReindexReport {
+  reindexed: string[]
+  skipped_missing: string[]
+  failed: [string, string][]
+}
+
 export interface EnhancedDocumentChunk {
   id: string
   document_id: string
@@ -47,6 +128,9 @@ export interface EmbeddingConfig {
   max_length: number
   normalize_embeddings: boolean
   show_download_progress: boolean
+  // 'ollama' embeds with ollama_model (e.g. nomic-embed-text); re-embed documents after switching
+  provider?: 'simple' | 'ollama'
+  ollama_model?: string
 }
 
 export interface SearchConfig {
@@ -127,7 +211,9 @@ class EnhancedRagService {
       const document = await invoke<EnhancedDocument>('upload_enhanced_document', {
         fileName: file.name,
         fileContent: Array.from(uint8Array),
-        fileType: file.type
+        fileType: file.type,
+        // Tauri file drops carry the original path, which lets edits to the file be detected
+        sourcePath: (file as File & { path?: string }).path ?? null
       })
       
       console.log('Enhanced document uploaded:', document)
@@ -358,6 +444,116 @@ class EnhancedRagService {
     }
   }
 
+  async packDocumentContext(
+    query: string,
+    documentIds: string[],
+    constraints?: Partial<PackingConstraints>
+  ): Promise<ContextPacking> {
+    try {
+      if (!this.initialized) {
+        await this.initialize()
+      }
+
+      return await invoke<ContextPacking>('pack_document_context', {
+        query,
+        documentIds,
+        constraints: constraints ?? null
+      })
+    } catch (error) {
+      console.error('Context packing failed:', error)
+      throw error
+    }
+  }
+
+  // Answers stream on `ollama-stream-${sessionId}`; citations arrive first on `file-qa-citations-${sessionId}`
+  async askAboutFile(path: string, question: string, sessionId: string): Promise<FileAnswer> {
+    try {
+      if (!this.initialized) {
+        await this.initialize()
+      }
+
+      return await invoke<FileAnswer>('ask_about_file', { path, question, sessionId })
+    } catch (error) {
+      console.error('File question failed:', error)
+      throw error
+    }
+  }
+
+  async promoteFileToIndex(path: string): Promise<EnhancedDocument> {
+    try {
+      if (!this.initialized) {
+        await this.initialize()
+      }
+
+      return await invoke<EnhancedDocument>('promote_file_to_index', { path })
+    } catch (error) {
+      console.error('Failed to add file to the index:', error)
+      throw error
+    }
+  }
+
+  async getTagTaxonomy(): Promise<string[]> {
+    return await invoke<string[]>('get_document_tag_taxonomy')
+  }
+
+  async setTagTaxonomy(tags: string[]): Promise<string[]> {
+    return await invoke<string[]>('set_document_tag_taxonomy', { tags })
+  }
+
+  async getDocumentTags(documentId: string): Promise<DocumentTags | null> {
+    return await invoke<DocumentTags | null>('get_document_tags', { documentId })
+  }
+
+  async setDocumentTags(documentId: string, tags: string[]): Promise<DocumentTags> {
+    return await invoke<DocumentTags>('set_document_tags', { documentId, tags })
+  }
+
+  // Queues classification for untagged documents (or all auto-tagged ones with retag); returns how many
+  async classifyDocuments(retag = false): Promise<number> {
+    return await invoke<number>('classify_documents', { retag })
+  }
+
+  async getStaleDocuments(recheck = false): Promise<StaleDocument[]> {
+    try {
+      if (!this.initialized) {
+        await this.initialize()
+      }
+
+      return await invoke<StaleDocument[]>('get_stale_documents', { recheck })
+    } catch (error) {
+      console.error('Failed to get stale documents:', error)
+      throw error
+    }
+  }
+
+  async getUnavailableDocuments(recheck = false): Promise<UnavailableDocument[]> {
+    try {
+      if (!this.initialized) {
+        await this.initialize()
+      }
+
+      return await invoke<UnavailableDocument[]>('get_unavailable_documents', { recheck })
+    } catch (error) {
+      console.error('Failed to get unavailable documents:', error)
+      throw error
+    }
+  }
+
+  async reindexStale(): Promise<StaleReindexReport> {
+    try {
+      if (!this.initialized) {
+        await this.initialize()
+      }
+
+      const report = await invoke<StaleReindexReport>('reindex_stale')
+      console.log(`Reindexed ${report.reindexed.length} stale documents`)
+      return report
+    } catch (error) {
+      console.error('Failed to reindex stale documents:', error)
+      throw error
+    }
+  }
+
   // Helper methods
   private async fileToArrayBuffer(file: File): Promise<ArrayBuffer> {
     return new Promise((resolve, reject) => {
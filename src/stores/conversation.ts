@@ -16,6 +16,23 @@ export interface ConversationMessage {
   retryCount?: number
   lastSaveAttempt?: number
   saveError?: string
+  redacted?: boolean // Off the record: excluded from insights, summaries and exports
+  audioOffset?: number // Byte offset of the message's clip in the kept conversation audio
+  audioDurationMs?: number
+  speakerId?: string // Named speaker, set through name_conversation_speaker
+  speakerName?: string
+}
+
+export interface AudioSpan {
+  offset: number
+  durationMs: number
+}
+
+export interface MessageAudio {
+  messageId: string
+  source: 'microphone' | 'loopback'
+  durationMs: number
+  wavBase64: string
 }
 
 export interface ConversationInsight {
@@ -23,7 +40,25 @@ export interface ConversationInsight {
   text: string
   timestamp: number
   contextLength: number
-  type: 'insight' | 'welcome' | 'question' | 'answer'
+  type: InsightType
+  template?: FilledTemplate
+}
+
+// The first six are the taxonomy shown as insight tabs; the rest are live AI and template kinds
+export type InsightType =
+  | 'summary' | 'action_item' | 'question' | 'fact_check' | 'sentiment' | 'custom'
+  | 'insight' | 'welcome' | 'answer' | 'template'
+
+export interface InsightCounts {
+  sessionId: string
+  total: number
+  byType: Partial<Record<InsightType, number>>
+}
+
+export interface FilledTemplate {
+  templateId: string
+  templateName: string
+  sections: { key: string; title: string; items: string[] }[]
 }
 
 export interface ConversationSession {
@@ -34,6 +69,7 @@ export interface ConversationSession {
   messages: ConversationMessage[]
   isActive: boolean
   insights: ConversationInsight[]
+  templateId?: string
 }
 
 export const useConversationStore = defineStore('conversation', () => {
@@ -150,6 +186,23 @@ export const useConversationStore = defineStore('conversation', () => {
       .slice(0, 10)
   })
 
+  // Keep transcribed audio for the recording session when the user has turned that on
+  const syncConversationAudio = async (sessionId: string | null) => {
+    try {
+      if (sessionId) {
+        await invoke<boolean>('start_conversation_audio', { sessionId })
+      } else {
+        await invoke('stop_conversation_audio')
+      }
+    } catch (error) {
+      console.error('🎧 Store: Failed to update conversation audio:', error)
+    }
+  }
+
+  const getMessageAudio = async (messageId: string): Promise<MessageAudio> => {
+    return await invoke<MessageAudio>('get_message_audio', { messageId })
+  }
+
   // Actions
   const createSession = async (name?: string): Promise<ConversationSession> => {
     console.log('🆕 Store: Creating new session')
@@ -180,6 +233,7 @@ export const useConversationStore = defineStore('conversation', () => {
       // For new sessions, use the existing save method which handles creation
       await saveSessions(true) // Force immediate save for new session creation
       console.log('✅ Store: New session created and saved')
+      await syncConversationAudio(session.id)
       
       return session
     } finally {
@@ -206,6 +260,7 @@ export const useConversationStore = defineStore('conversation', () => {
       try {
         targetSession.isActive = false
         targetSession.endTime = Date.now()
+        await syncConversationAudio(null)
         console.log(`🏁 Store: Session ended with ${targetSession.messages.length} messages:`, targetSession.id)
         
         if (currentSession.value?.id === targetSession.id) {
@@ -242,6 +297,7 @@ export const useConversationStore = defineStore('conversation', () => {
       try {
         targetSession.isActive = false
         targetSession.endTime = Date.now()
+        await syncConversationAudio(null)
         
         // DON'T clear currentSession - this keeps the window open
         // and allows for continued interaction with the completed session
@@ -338,6 +394,7 @@ export const useConversationStore = defineStore('conversation', () => {
           isActive: session.isActive
         })
         console.log('✅ Store: Session metadata updated incrementally')
+        await syncConversationAudio(session.id)
         
       } catch (error) {
         console.error('▶️ Store: Failed to resume session properly:', error)
@@ -745,7 +802,8 @@ export const useConversationStore = defineStore('conversation', () => {
           text: insight.text,
           timestamp: insight.timestamp,
           contextLength: insight.contextLength,
-          insightType: insight.type
+          type: insight.type,
+          template: insight.template
         }
       })
       
@@ -767,6 +825,24 @@ export const useConversationStore = defineStore('conversation', () => {
     }
   }
 
+  const getInsightsByType = async (sessionId: string, types: InsightType[], limit?: number): Promise<ConversationInsight[]> => {
+    try {
+      return await invoke<ConversationInsight[]>('get_insights_by_type', { sessionId, types, limit })
+    } catch (error) {
+      console.error('Failed to load insights by type:', sessionId, error)
+      return []
+    }
+  }
+
+  const getInsightCounts = async (sessionId: string): Promise<InsightCounts | null> => {
+    try {
+      return await invoke<InsightCounts>('get_insight_counts', { sessionId })
+    } catch (error) {
+      console.error('Failed to count insights:', sessionId, error)
+      return null
+    }
+  }
+
   const loadCurrentSessionInsights = async () => {
     if (!currentSession.value) return
 
@@ -801,6 +877,7 @@ export const useConversationStore = defineStore('conversation', () => {
     completeSession,
     switchToSession,
     resumeSession,
+    getMessageAudio,
     addMessage,
     updateMessage,
     deleteMessage,
@@ -829,6 +906,8 @@ export const useConversationStore = defineStore('conversation', () => {
     // Insight management
     addInsight,
     getInsightsForSession,
+    getInsightsByType,
+    getInsightCounts,
     loadCurrentSessionInsights,
     
     // Message persistence
@@ -1,4 +1,5 @@
 import { ref } from 'vue'
+import { listen } from '@tauri-apps/api/event'
 
 interface OllamaModel {
   name: string
@@ -36,6 +37,14 @@ const cache = ref<OllamaCache>({
   selectedModel: null
 })
 
+// The backend's circuit breaker reports when Ollama stops and starts answering
+listen('ollama-unavailable', () => {
+  cache.value.status = { status: 'not_running' }
+})
+listen('ollama-available', () => {
+  cache.value.status = { status: 'running' }
+})
+
 export const useOllamaCache = () => {
   const isCacheValid = () => {
     return Date.now() - cache.value.lastFetch < CACHE_DURATION
@@ -35,6 +35,7 @@ export const createMockSpeechTranscription = () => ({
   error: ref(null),
   setAutoSendToChat: vi.fn(),
   setContinuousMode: vi.fn(),
+  setRecordingSession: vi.fn(),
 })
 
 export const createMockChatManagement = () => ({
@@ -10,6 +10,8 @@ export interface ChatMessage {
     isStreaming?: boolean
     sessionId?: string
     metadata?: any
+    bookmarked?: boolean
+    reactions?: string[]
   }
   
   export interface ChatSession {
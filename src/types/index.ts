@@ -89,10 +89,30 @@ export interface MessageMetadata {
   agentType?: 'enteract' | 'vision' | 'deep_research'
   model?: string
   tokens?: number
+  promptTokens?: number
+  completionTokens?: number
   processingTime?: number
   analysisType?: string[]
   searchQueries?: string[]
   sources?: string[]
+  // Provenance: what exactly produced the message
+  modelTag?: string
+  provider?: string
+  generationOptions?: Record<string, unknown>
+  ragChunkIds?: string[]
+  screenContextId?: string
+}
+
+// Per-request sampling overrides; unset fields fall back to the agent's saved defaults
+export interface GenerationOptions {
+  temperature?: number
+  top_p?: number
+  top_k?: number
+  repeat_penalty?: number
+  num_predict?: number
+  num_ctx?: number
+  seed?: number
+  stop?: string[]
 }
 
 // File upload types